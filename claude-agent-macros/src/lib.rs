@@ -0,0 +1,211 @@
+//! Procedural macros for `claude-agent`.
+//!
+//! Use them through the re-exports in `claude_agent` (e.g. `claude_agent::tool`);
+//! the generated code refers to items by their `::claude_agent` paths.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{
+    Attribute, Expr, ExprLit, FnArg, Ident, ItemFn, Lit, LitStr, Meta, Type, parse_macro_input,
+};
+
+/// Turn an async function into a tool.
+///
+/// ```ignore
+/// #[derive(Deserialize, JsonSchema)]
+/// struct GreetInput {
+///     /// Who to greet
+///     name: String,
+/// }
+///
+/// /// Greet someone by name.
+/// #[claude_agent::tool(name = "Greet")]
+/// async fn greet(input: GreetInput) -> ToolResult {
+///     ToolResult::success(format!("Hello, {}!", input.name))
+/// }
+///
+/// Agent::builder().tool(GreetTool)
+/// ```
+///
+/// Generates a unit struct named after the function (`greet` becomes
+/// `GreetTool`) implementing `SchemaTool`, so the input schema comes from the
+/// input type's `JsonSchema` derive and arguments arrive already deserialized.
+///
+/// The function takes the input and, optionally, `&ExecutionContext` as a
+/// second argument, and returns anything convertible into `ToolResult`.
+///
+/// Options:
+/// - `name = "..."`: tool name (default: the function name in `UpperCamelCase`)
+/// - `description = "..."`: tool description (default: the function's doc comment)
+/// - `strict`: enable strict schema validation
+#[proc_macro_attribute]
+pub fn tool(args: TokenStream, item: TokenStream) -> TokenStream {
+    let mut options = ToolOptions::default();
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("name") {
+            options.name = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("description") {
+            options.description = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("strict") {
+            options.strict = true;
+        } else {
+            return Err(meta.error("expected `name`, `description` or `strict`"));
+        }
+        Ok(())
+    });
+    parse_macro_input!(args with parser);
+
+    let function = parse_macro_input!(item as ItemFn);
+    expand(options, function)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct ToolOptions {
+    name: Option<LitStr>,
+    description: Option<LitStr>,
+    strict: bool,
+}
+
+fn expand(options: ToolOptions, function: ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let sig = &function.sig;
+    if sig.asyncness.is_none() {
+        return Err(syn::Error::new(
+            sig.fn_token.span,
+            "tool functions must be async",
+        ));
+    }
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            sig.generics.span(),
+            "tool functions cannot be generic",
+        ));
+    }
+
+    let mut inputs = sig.inputs.iter();
+    let input_type = match inputs.next() {
+        Some(FnArg::Typed(arg)) => arg.ty.as_ref().clone(),
+        Some(FnArg::Receiver(receiver)) => {
+            return Err(syn::Error::new(
+                receiver.span(),
+                "tool functions cannot take `self`",
+            ));
+        }
+        None => {
+            return Err(syn::Error::new(
+                sig.inputs.span(),
+                "tool functions take the typed input as their first argument",
+            ));
+        }
+    };
+    let takes_context = match inputs.next() {
+        None => false,
+        Some(FnArg::Typed(arg)) if matches!(*arg.ty, Type::Reference(_)) => true,
+        Some(other) => {
+            return Err(syn::Error::new(
+                other.span(),
+                "the second argument must be `&ExecutionContext`",
+            ));
+        }
+    };
+    if let Some(extra) = inputs.next() {
+        return Err(syn::Error::new(
+            extra.span(),
+            "tool functions take at most two arguments",
+        ));
+    }
+
+    let fn_ident = &sig.ident;
+    let camel = to_upper_camel(&fn_ident.to_string());
+    let struct_ident = if camel.ends_with("Tool") {
+        Ident::new(&camel, fn_ident.span())
+    } else {
+        format_ident!("{}Tool", camel, span = fn_ident.span())
+    };
+    let name = options
+        .name
+        .unwrap_or_else(|| LitStr::new(camel.trim_end_matches("Tool"), fn_ident.span()));
+    let description = match options.description {
+        Some(description) => description,
+        None => doc_comment(&function.attrs).ok_or_else(|| {
+            syn::Error::new(
+                fn_ident.span(),
+                "add a doc comment or `description = \"...\"` to describe the tool",
+            )
+        })?,
+    };
+    let strict = options.strict;
+    let vis = &function.vis;
+    let (context, call) = if takes_context {
+        (quote!(context), quote!(#fn_ident(input, context)))
+    } else {
+        (quote!(_context), quote!(#fn_ident(input)))
+    };
+    let struct_doc = format!("Tool generated from `{}`.", fn_ident);
+
+    Ok(quote! {
+        #function
+
+        #[doc = #struct_doc]
+        #[derive(Debug, Clone, Copy, Default)]
+        #vis struct #struct_ident;
+
+        #[::claude_agent::__private::async_trait]
+        impl ::claude_agent::tools::SchemaTool for #struct_ident {
+            type Input = #input_type;
+            const NAME: &'static str = #name;
+            const DESCRIPTION: &'static str = #description;
+            const STRICT: bool = #strict;
+
+            async fn handle(
+                &self,
+                input: Self::Input,
+                #context: &::claude_agent::tools::ExecutionContext,
+            ) -> ::claude_agent::ToolResult {
+                ::core::convert::Into::into(#call.await)
+            }
+        }
+    })
+}
+
+/// The item's doc comment, without the space rustdoc keeps after `///`.
+fn doc_comment(attrs: &[Attribute]) -> Option<LitStr> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(nv) => match &nv.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(s), ..
+                }) => Some(s.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    let text = lines
+        .iter()
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let text = text.trim();
+    (!text.is_empty()).then(|| LitStr::new(text, Span::call_site()))
+}
+
+fn to_upper_camel(snake: &str) -> String {
+    snake
+        .trim_start_matches("r#")
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
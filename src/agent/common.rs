@@ -2,20 +2,33 @@
 
 use std::path::Path;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
 
 use rust_decimal::Decimal;
 use serde_json::Value;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, broadcast};
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, info, warn};
 
 use crate::ToolRegistry;
-use crate::budget::{BudgetTracker, TenantBudget};
+use crate::budget::{BudgetStatus, BudgetTracker, TenantBudget};
+use crate::common::clock;
 use crate::context::PromptOrchestrator;
-use crate::hooks::{HookContext, HookEvent, HookInput, HookManager};
-use crate::session::ToolState;
-use crate::types::{CompactResult, ToolResult, Usage};
+use crate::hooks::{HookContext, HookEvent, HookInput, HookManager, HookOutput, NotificationKind};
+use crate::mcp::McpNotification;
+use crate::observability::MetricsRegistry;
+use crate::permissions::{PermissionAuditEntry, is_write_tool};
+use crate::session::types::TodoStatus;
+use crate::session::{CompactExecutor, SessionManager, SessionState, ToolState};
+use crate::tools::ExecutionContext;
+use crate::types::{CompactResult, ToolError, ToolOutput, ToolResult, ToolUseBlock, Usage};
 
 use super::config::{BudgetConfig, ExecutionConfig};
+use super::event_sink::EventRecorder;
+use super::events::AgentEvent;
+use super::plan::PlannedToolCall;
 use super::state::AgentMetrics;
 use super::state_formatter::collect_compaction_state;
 
@@ -72,6 +85,25 @@ impl BudgetContext<'_> {
     }
 }
 
+/// Whether the next API call is projected to push a run past its cost ceiling.
+///
+/// The next call is assumed to cost at least as much as the previous one, since
+/// the context only grows between iterations.
+pub(crate) fn exceeds_run_ceiling(
+    ceiling: Option<Decimal>,
+    spent: Decimal,
+    last_call_cost: Decimal,
+) -> bool {
+    let Some(max) = ceiling else {
+        return false;
+    };
+    let exceeded = spent + last_call_cost > max;
+    if exceeded {
+        warn!(%spent, projected = %last_call_cost, limit = %max, "Run cost ceiling reached, stopping");
+    }
+    exceeded
+}
+
 /// Accumulate usage from an API response into total_usage, metrics, and budget.
 pub(crate) fn accumulate_response_usage(
     total_usage: &mut Usage,
@@ -86,6 +118,7 @@ pub(crate) fn accumulate_response_usage(
         .output_tokens
         .saturating_add(usage.output_tokens);
     metrics.add_usage_with_cache(usage);
+    metrics.record_cache_usage(usage);
     metrics.record_model_usage(model, usage);
 
     if let Some(ref server_usage) = usage.server_tool_use {
@@ -102,6 +135,43 @@ pub(crate) fn accumulate_response_usage(
     cost
 }
 
+/// Run `PreToolUse` hooks for a call. A block is added to the session's
+/// permission audit trail with the name of the hook that blocked.
+pub(crate) async fn run_pre_tool_use(
+    hooks: &HookManager,
+    tool_state: &ToolState,
+    session_id: &str,
+    tool_use: &ToolUseBlock,
+    hook_context: &HookContext,
+) -> crate::Result<HookOutput> {
+    let input = HookInput::pre_tool_use(session_id, &tool_use.name, tool_use.input.clone());
+    let mut blocked_by = None;
+    let output = hooks
+        .execute_with_handler(
+            HookEvent::PreToolUse,
+            input,
+            hook_context,
+            |hook, output| {
+                if !output.continue_execution {
+                    blocked_by.get_or_insert_with(|| hook.to_string());
+                }
+            },
+        )
+        .await?;
+    if !output.continue_execution {
+        let entry = PermissionAuditEntry::hook_block(
+            &tool_use.name,
+            &tool_use.input,
+            blocked_by,
+            output.stop_reason.as_deref().unwrap_or("Blocked by hook"),
+        );
+        tool_state
+            .with_session_mut(|session| session.permissions.audit.push(entry))
+            .await;
+    }
+    Ok(output)
+}
+
 /// Accumulate inner usage from a tool result (e.g., subagent calls).
 pub(crate) async fn accumulate_inner_usage(
     tool_state: &ToolState,
@@ -141,6 +211,16 @@ pub(crate) async fn accumulate_inner_usage(
     }
 }
 
+/// Save the session to the agent's session manager, if one is configured.
+pub(crate) async fn persist_session(state: &ToolState, manager: Option<&SessionManager>) {
+    let Some(manager) = manager else {
+        return;
+    };
+    if let Err(e) = manager.update(&state.session().await).await {
+        warn!(session_id = %state.session_id(), error = %e, "Failed to persist session");
+    }
+}
+
 /// Run post-tool hooks (PostToolUse on success, PostToolUseFailure on error).
 pub(crate) async fn run_post_tool_hooks(
     hooks: &HookManager,
@@ -170,6 +250,288 @@ pub(crate) async fn run_post_tool_hooks(
     }
 }
 
+/// A tool call that passed PreToolUse hooks and is ready to execute.
+pub(crate) struct PendingToolCall {
+    pub id: String,
+    pub name: String,
+    pub input: Value,
+}
+
+/// Record calls as a dry-run plan, returning the placeholder results for the model.
+pub(crate) async fn plan_tool_calls(
+    calls: Vec<PendingToolCall>,
+    context: &ExecutionContext,
+    metrics: &mut AgentMetrics,
+) -> Vec<PlannedToolCall> {
+    let mut planned = Vec::with_capacity(calls.len());
+    for call in &calls {
+        let entry = PlannedToolCall::from_call(call, context).await;
+        debug!(tool = %entry.name, "Tool call recorded (dry run)");
+        metrics.record_planned_tool_call(entry.clone());
+        planned.push(entry);
+    }
+    planned
+}
+
+/// Outcome of a single executed tool call.
+pub(crate) struct CompletedToolCall {
+    pub id: String,
+    pub name: String,
+    pub input: Value,
+    pub result: ToolResult,
+    pub duration_ms: u64,
+}
+
+impl CompletedToolCall {
+    /// A `SecurityViolation` for each operation the sandbox denied the call.
+    pub(crate) fn violation_events(&self) -> Vec<AgentEvent> {
+        self.result
+            .sandbox_violations()
+            .iter()
+            .map(|violation| AgentEvent::SecurityViolation {
+                id: self.id.clone(),
+                name: self.name.clone(),
+                violation: violation.clone(),
+            })
+            .collect()
+    }
+
+    /// Counts the call and its sandbox violations in `registry`, if any.
+    pub(crate) fn record_metrics(&self, registry: Option<&MetricsRegistry>) {
+        let Some(registry) = registry else {
+            return;
+        };
+        registry.record_tool_call(!self.result.is_error());
+        for violation in self.result.sandbox_violations() {
+            registry.record_sandbox_violation(violation);
+        }
+    }
+
+    /// `ToolBlocked` when a usage quota rejected the call, `ToolComplete` otherwise.
+    pub(crate) fn into_event(self) -> AgentEvent {
+        match &self.result.output {
+            ToolOutput::Error(error @ ToolError::QuotaExceeded { .. }) => AgentEvent::ToolBlocked {
+                reason: error.to_string(),
+                id: self.id,
+                name: self.name,
+            },
+            _ => AgentEvent::ToolComplete {
+                output: self.result.text(),
+                is_error: self.result.is_error(),
+                id: self.id,
+                name: self.name,
+                duration_ms: self.duration_ms,
+            },
+        }
+    }
+}
+
+/// Returns the exclusive end index of the execution batch starting at `start`.
+///
+/// A write tool always forms a batch of its own; otherwise the batch extends
+/// up to (but not including) the next write tool. `names` must already be
+/// resolved through [`ToolRegistry::canonical_name`], so aliases count too.
+pub(crate) fn tool_batch_end<'a>(names: impl IntoIterator<Item = &'a str>, start: usize) -> usize {
+    let mut end = start;
+    for (offset, name) in names.into_iter().skip(start).enumerate() {
+        if is_write_tool(name) {
+            return if offset == 0 { start + 1 } else { end };
+        }
+        end = start + offset + 1;
+    }
+    end
+}
+
+/// Execute a batch of tool calls with bounded concurrency, preserving call order.
+///
+/// Failed calls are retried according to the tool's [`ToolRetryPolicy`], if any,
+/// and results are redacted when [`ExecutionConfig::secret_redaction`] is set.
+pub(crate) async fn execute_tool_batch(
+    tools: &ToolRegistry,
+    batch: Vec<PendingToolCall>,
+    execution: &ExecutionConfig,
+) -> Vec<CompletedToolCall> {
+    futures::stream::iter(batch.into_iter().map(|call| async move {
+        let start = clock::now();
+        let mut result = tools.execute(&call.name, call.input.clone()).await;
+        if let Some(policy) = execution.tool_retry_policy(&call.name) {
+            let mut attempt = 0;
+            while attempt < policy.max_retries && policy.should_retry(&result) {
+                attempt += 1;
+                let delay = policy.backoff_for(attempt);
+                warn!(
+                    tool = %call.name,
+                    attempt,
+                    delay_ms = delay.as_millis() as u64,
+                    error = %result.error_message(),
+                    "Retrying failed tool call"
+                );
+                tokio::time::sleep(delay).await;
+                result = tools.execute(&call.name, call.input.clone()).await;
+            }
+        }
+        if let Some(redactor) = &execution.secret_redaction
+            && redactor.redact_result(&mut result)
+        {
+            info!(tool = %call.name, "Redacted secrets from tool output");
+        }
+        CompletedToolCall {
+            id: call.id,
+            name: call.name,
+            input: call.input,
+            result,
+            duration_ms: clock::tool_elapsed(start).as_millis() as u64,
+        }
+    }))
+    .buffered(execution.max_parallel_tools.max(1))
+    .collect()
+    .await
+}
+
+/// Build a progress event from the run's metrics and the session's todo list.
+pub(crate) async fn progress_event(
+    state: &ToolState,
+    metrics: &AgentMetrics,
+    max_iterations: usize,
+    started: Instant,
+) -> AgentEvent {
+    let (todos_done, todos_total) = state
+        .with_session(|session| {
+            let done = session
+                .todos
+                .iter()
+                .filter(|t| t.status == TodoStatus::Completed)
+                .count();
+            (done, session.todos.len())
+        })
+        .await;
+    AgentEvent::Progress {
+        iteration: metrics.iterations,
+        max_iterations,
+        todos_done,
+        todos_total,
+        elapsed: clock::elapsed(started),
+    }
+}
+
+/// `ToolDeprecated` events for calls through a former tool name or to a
+/// deprecated tool.
+pub(crate) fn deprecation_events(
+    tools: &ToolRegistry,
+    calls: &[PendingToolCall],
+) -> Vec<AgentEvent> {
+    calls
+        .iter()
+        .filter_map(|call| {
+            let message = tools.deprecation(&call.name)?;
+            warn!(tool = %call.name, %message, "Deprecated tool called");
+            Some(AgentEvent::ToolDeprecated {
+                id: call.id.clone(),
+                name: call.name.clone(),
+                message,
+            })
+        })
+        .collect()
+}
+
+/// Event for an MCP notification; log messages only when enabled in `config`.
+fn mcp_event(notification: McpNotification, config: &ExecutionConfig) -> Option<AgentEvent> {
+    if matches!(notification, McpNotification::Log { .. }) && !config.emit_mcp_logs {
+        return None;
+    }
+    Some(notification.into())
+}
+
+/// Events for MCP notifications received since the last call.
+pub(crate) fn mcp_events(
+    notifications: &mut Option<broadcast::Receiver<McpNotification>>,
+    config: &ExecutionConfig,
+) -> Vec<AgentEvent> {
+    let Some(rx) = notifications else {
+        return Vec::new();
+    };
+    let mut events = Vec::new();
+    loop {
+        match rx.try_recv() {
+            Ok(notification) => events.extend(mcp_event(notification, config)),
+            Err(broadcast::error::TryRecvError::Lagged(skipped)) => {
+                warn!(skipped, "Dropped MCP notifications");
+            }
+            Err(_) => break,
+        }
+    }
+    events
+}
+
+/// Await `fut`, emitting MCP notifications as events while it runs so that
+/// tool progress is reported live rather than after the tool round.
+pub(crate) async fn with_mcp_events<F: std::future::Future>(
+    notifications: &mut Option<broadcast::Receiver<McpNotification>>,
+    recorder: &EventRecorder,
+    config: &ExecutionConfig,
+    turn: usize,
+    fut: F,
+) -> F::Output {
+    let Some(rx) = notifications.as_mut().filter(|_| recorder.is_enabled()) else {
+        return fut.await;
+    };
+    tokio::pin!(fut);
+    loop {
+        tokio::select! {
+            output = &mut fut => return output,
+            received = rx.recv() => match received {
+                Ok(notification) => {
+                    if let Some(event) = mcp_event(notification, config) {
+                        recorder.emit(turn, &event).await;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!(skipped, "Dropped MCP notifications");
+                }
+                Err(broadcast::error::RecvError::Closed) => return fut.await,
+            },
+        }
+    }
+}
+
+/// Await `fut` unless `token` is cancelled first, in which case `fut` is dropped.
+pub(crate) async fn cancellable<F: std::future::Future>(
+    token: &CancellationToken,
+    fut: F,
+) -> crate::Result<F::Output> {
+    tokio::select! {
+        biased;
+        _ = token.cancelled() => Err(crate::Error::Cancelled),
+        output = fut => Ok(output),
+    }
+}
+
+/// Tear down a cancelled run: kill background shells and mark the session cancelled.
+pub(crate) async fn finalize_cancelled(state: &ToolState, tools: &ToolRegistry) {
+    if let Some(pm) = tools.process_manager() {
+        let killed = pm.kill_all().await;
+        if killed > 0 {
+            debug!(killed, "Killed background processes on cancellation");
+        }
+    }
+    state
+        .with_session_mut(|session| session.set_state(SessionState::Cancelled))
+        .await;
+    info!("Agent execution cancelled");
+}
+
+/// Merge any interjected user input into the session. Returns whether anything was added.
+pub(crate) async fn apply_interjections(state: &ToolState) -> bool {
+    let Some(merged) = state.take_interjections().await else {
+        return false;
+    };
+    debug!(count = merged.ids.len(), "Applying interjected input");
+    state
+        .with_session_mut(|session| session.add_interjection(merged.content))
+        .await;
+    true
+}
+
 /// Activate dynamic rules for file-related tool operations.
 pub(crate) async fn try_activate_dynamic_rules(
     tool_name: &str,
@@ -188,6 +550,80 @@ pub(crate) async fn try_activate_dynamic_rules(
     }
 }
 
+/// Append the `additional_context` hooks returned for the prompt to it.
+pub(crate) fn with_hook_context(
+    prompt: String,
+    contexts: impl IntoIterator<Item = Option<String>>,
+) -> String {
+    contexts
+        .into_iter()
+        .flatten()
+        .filter(|context| !context.trim().is_empty())
+        .fold(prompt, |prompt, context| {
+            format!("{}\n\n{}", prompt, context)
+        })
+}
+
+/// Run Notification hooks. Failures are logged and never stop the agent.
+pub(crate) async fn run_notification_hooks(
+    hooks: &HookManager,
+    hook_ctx: &HookContext,
+    session_id: &str,
+    kind: NotificationKind,
+    message: impl Into<String>,
+) {
+    let input = HookInput::notification(session_id, kind, message);
+    if let Err(e) = hooks
+        .execute(HookEvent::Notification, input, hook_ctx)
+        .await
+    {
+        warn!(error = %e, "Notification hook failed");
+    }
+}
+
+/// Message for the budget warning, sent once per tracker at 80% of the limit.
+pub(crate) fn budget_warning(budget_tracker: &BudgetTracker) -> Option<String> {
+    match budget_tracker.take_warning()? {
+        BudgetStatus::WithinBudget { used, limit, .. }
+        | BudgetStatus::Exceeded { used, limit, .. } => Some(format!(
+            "Budget 80% consumed: ${:.2} of ${:.2} used",
+            used, limit
+        )),
+        BudgetStatus::Unlimited { .. } => None,
+    }
+}
+
+/// Notification text for a tool batch that is still running.
+pub(crate) fn idle_message(calls: &[PendingToolCall], config: &ExecutionConfig) -> String {
+    let after = config.idle_notification.unwrap_or_default();
+    let names: Vec<&str> = calls.iter().map(|call| call.name.as_str()).collect();
+    format!(
+        "Tool calls still running after {}s: {}",
+        after.as_secs(),
+        names.join(", ")
+    )
+}
+
+/// Await `fut`, running `on_idle` once if it has not finished after `after`.
+pub(crate) async fn notify_when_idle<F, N>(after: Option<Duration>, fut: F, on_idle: N) -> F::Output
+where
+    F: std::future::Future,
+    N: std::future::Future<Output = ()>,
+{
+    let Some(after) = after else {
+        return fut.await;
+    };
+    tokio::pin!(fut);
+    tokio::select! {
+        output = &mut fut => return output,
+        _ = tokio::time::sleep(after) => {}
+    }
+    tokio::select! {
+        output = &mut fut => output,
+        _ = on_idle => fut.await,
+    }
+}
+
 /// Run Stop and SessionEnd hooks in sequence.
 pub(crate) async fn run_stop_hooks(hooks: &HookManager, hook_ctx: &HookContext, session_id: &str) {
     let stop_input = HookInput::stop(session_id);
@@ -231,6 +667,32 @@ pub(crate) async fn handle_compaction(
         return;
     }
 
+    if let Some(strategy) = &config.compact_strategy {
+        let executor = CompactExecutor::new(strategy.clone());
+        let (pruned, still_needed) = tool_state
+            .with_session_mut(|session| {
+                let pruned = executor.prune(session);
+                let still_needed = session.should_compact(
+                    max_tokens,
+                    config.compact_threshold,
+                    config.compact_keep_messages,
+                );
+                (pruned, still_needed)
+            })
+            .await;
+        if pruned.count > 0 {
+            info!(
+                pruned = pruned.count,
+                saved_tokens = pruned.saved_tokens,
+                "Pruned old tool results"
+            );
+            if !still_needed {
+                metrics.record_compaction();
+                return;
+            }
+        }
+    }
+
     let pre_compact_input = HookInput::pre_compact(session_id);
     if let Err(e) = hooks
         .execute(HookEvent::PreCompact, pre_compact_input, hook_ctx)
@@ -241,7 +703,11 @@ pub(crate) async fn handle_compaction(
 
     debug!("Compacting session context");
     let compact_result = tool_state
-        .compact(client, config.compact_keep_messages)
+        .compact_with(
+            client,
+            config.compact_keep_messages,
+            config.compact_strategy.as_ref(),
+        )
         .await;
 
     match compact_result {
@@ -273,11 +739,13 @@ pub(crate) async fn handle_compaction(
 /// Extract file path from tool input for rule activation.
 pub(crate) fn extract_file_path(tool_name: &str, input: &Value) -> Option<String> {
     match tool_name {
-        "Read" | "Write" | "Edit" => input
+        "Read" | "Write" | "Edit" | "MultiEdit" | "AstEdit" => input
             .get("file_path")
             .and_then(|v| v.as_str())
             .map(String::from),
-        "Glob" | "Grep" => input.get("path").and_then(|v| v.as_str()).map(String::from),
+        "Glob" | "Grep" | "LS" | "str_replace_based_edit_tool" | "str_replace_editor" => {
+            input.get("path").and_then(|v| v.as_str()).map(String::from)
+        }
         _ => None,
     }
 }
@@ -306,6 +774,7 @@ mod tests {
     use rust_decimal_macros::dec;
 
     use super::*;
+    use crate::mcp::McpLogLevel;
 
     #[test]
     fn test_extract_structured_output_with_schema() {
@@ -331,6 +800,71 @@ mod tests {
         assert!(result.is_none());
     }
 
+    fn pending(name: &str) -> PendingToolCall {
+        PendingToolCall {
+            id: format!("id_{}", name),
+            name: name.to_string(),
+            input: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn test_tool_batch_end() {
+        let names = ["Read", "Grep", "Edit", "NotebookEdit", "Read", "Glob"];
+        assert_eq!(tool_batch_end(names, 0), 2);
+        assert_eq!(tool_batch_end(names, 2), 3);
+        assert_eq!(tool_batch_end(names, 3), 4);
+        assert_eq!(tool_batch_end(names, 4), 6);
+        assert_eq!(tool_batch_end(names, 6), 6);
+    }
+
+    #[tokio::test]
+    async fn test_execute_tool_batch_preserves_order() {
+        let registry = ToolRegistry::new();
+        let calls = vec![pending("A"), pending("B"), pending("C")];
+        let execution = ExecutionConfig::default().max_parallel_tools(2);
+        let completed = execute_tool_batch(&registry, calls, &execution).await;
+
+        let ids: Vec<_> = completed.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["id_A", "id_B", "id_C"]);
+        assert!(completed.iter().all(|c| c.result.is_error()));
+    }
+
+    #[test]
+    fn test_with_hook_context() {
+        let prompt = with_hook_context(
+            "Fix the build".to_string(),
+            [
+                Some("Branch: main".to_string()),
+                None,
+                Some(" ".to_string()),
+            ],
+        );
+        assert_eq!(prompt, "Fix the build\n\nBranch: main");
+    }
+
+    #[tokio::test]
+    async fn test_notify_when_idle() {
+        let notified = std::sync::atomic::AtomicUsize::new(0);
+        let on_idle = async {
+            notified.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        };
+        let slow = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            "done"
+        };
+        let output = notify_when_idle(Some(Duration::from_millis(10)), slow, on_idle).await;
+        assert_eq!(output, "done");
+        assert_eq!(notified.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        let on_idle = async {
+            notified.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        };
+        notify_when_idle(Some(Duration::from_secs(60)), async {}, on_idle).await;
+        notify_when_idle(None, async {}, async { unreachable!() }).await;
+        assert_eq!(notified.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
     #[test]
     fn test_budget_context_check_ok() {
         let tracker = BudgetTracker::new(dec!(10));
@@ -408,4 +942,65 @@ mod tests {
         let input = serde_json::json!({"path": null});
         assert_eq!(extract_file_path("Glob", &input), None);
     }
+
+    #[test]
+    fn test_mcp_events_drains_notifications() {
+        let config = ExecutionConfig::default();
+        assert!(mcp_events(&mut None, &config).is_empty());
+
+        let (tx, rx) = broadcast::channel(8);
+        let mut notifications = Some(rx);
+        tx.send(McpNotification::ResourceUpdated {
+            server: "files".into(),
+            uri: "file:///notes.md".into(),
+        })
+        .unwrap();
+
+        let events = mcp_events(&mut notifications, &config);
+        assert!(matches!(
+            events.as_slice(),
+            [AgentEvent::ResourceChanged { server, uri }]
+                if server == "files" && uri == "file:///notes.md"
+        ));
+        assert!(mcp_events(&mut notifications, &config).is_empty());
+
+        tx.send(McpNotification::Progress {
+            server: "build".into(),
+            tool: "compile".into(),
+            progress: 3.0,
+            total: Some(4.0),
+            message: Some("linking".into()),
+        })
+        .unwrap();
+        let events = mcp_events(&mut notifications, &config);
+        assert!(matches!(
+            events.as_slice(),
+            [AgentEvent::ToolProgress { name, percentage: Some(p), message: Some(m), .. }]
+                if name == "mcp__build__compile" && *p == 75.0 && m == "linking"
+        ));
+    }
+
+    #[test]
+    fn test_mcp_log_events_are_opt_in() {
+        let (tx, rx) = broadcast::channel(8);
+        let mut notifications = Some(rx);
+        let log = McpNotification::Log {
+            server: "db".into(),
+            level: McpLogLevel::Error,
+            logger: Some("pool".into()),
+            data: serde_json::json!({"error": "connection refused"}),
+        };
+
+        tx.send(log.clone()).unwrap();
+        assert!(mcp_events(&mut notifications, &ExecutionConfig::default()).is_empty());
+
+        tx.send(log).unwrap();
+        let config = ExecutionConfig::default().emit_mcp_logs(true);
+        let events = mcp_events(&mut notifications, &config);
+        assert!(matches!(
+            events.as_slice(),
+            [AgentEvent::McpLog { server, level: McpLogLevel::Error, logger: Some(l), message }]
+                if server == "db" && l == "pool" && message == r#"{"error":"connection refused"}"#
+        ));
+    }
 }
@@ -8,10 +8,14 @@ use std::time::Duration;
 
 use rust_decimal::Decimal;
 
+use super::preview::PreviewPolicy;
 use crate::client::messages::DEFAULT_MAX_TOKENS;
 use crate::output_style::OutputStyle;
-use crate::permissions::PermissionPolicy;
-use crate::tools::ToolAccess;
+use crate::permissions::{PermissionPolicy, ToolLimits};
+use crate::security::bash::{BashPolicy, EnvPolicy};
+use crate::security::{AuditLog, FileLimits, PiiScrubber, SecretRedactor};
+use crate::session::CompactStrategy;
+use crate::tools::{ToolAccess, ToolCacheConfig, ToolQuotas};
 
 /// Model-related configuration.
 #[derive(Debug, Clone)]
@@ -61,6 +65,9 @@ impl AgentModelConfig {
     }
 }
 
+/// Default number of tool calls executed concurrently within a turn.
+pub const DEFAULT_MAX_PARALLEL_TOOLS: usize = 10;
+
 /// Execution behavior configuration.
 #[derive(Debug, Clone)]
 pub struct ExecutionConfig {
@@ -76,6 +83,39 @@ pub struct ExecutionConfig {
     pub compact_threshold: f32,
     /// Messages to preserve during compaction
     pub compact_keep_messages: usize,
+    /// How to compact once the threshold is crossed (pruning, prompt, model).
+    /// `None` summarizes with the short built-in prompt and the small model.
+    pub compact_strategy: Option<CompactStrategy>,
+    /// Maximum tool calls executed concurrently within a single turn
+    pub max_parallel_tools: usize,
+    /// Record proposed tool calls instead of executing them
+    pub dry_run: bool,
+    /// Preview write tool diffs and apply them per policy (off when `None`)
+    pub tool_preview: Option<PreviewPolicy>,
+    /// Retry policies for failing tool calls, keyed by tool name
+    pub tool_retry: HashMap<String, ToolRetryPolicy>,
+    /// Timeouts and concurrency caps for tool calls, keyed by tool name.
+    /// Fields set here take precedence over the permission policy's limits.
+    pub tool_limits: HashMap<String, ToolLimits>,
+    /// Per-session caching of read-only tool results (disabled when `None`)
+    pub tool_cache: Option<ToolCacheConfig>,
+    /// Per-session caps on tool calls and output (unlimited when `None`)
+    pub tool_quotas: Option<ToolQuotas>,
+    /// Directory names Glob and Grep skip (`None` keeps `node_modules` and `target`)
+    pub search_excludes: Option<Vec<String>>,
+    /// Back up files before write tools change them, so a run can be rolled back
+    pub backup_writes: bool,
+    /// Upload files too large to send inline (images, PDFs read by `Read`)
+    /// through the Files API instead of rejecting them
+    pub upload_large_files: bool,
+    /// Emit `AgentEvent::Thinking` for extended thinking content
+    pub emit_thinking: bool,
+    /// Emit `AgentEvent::McpLog` for MCP server log messages
+    pub emit_mcp_logs: bool,
+    /// Send an idle `Notification` hook when tool calls run longer than this
+    pub idle_notification: Option<Duration>,
+    /// Redact secrets from tool results and streamed text (off when `None`)
+    pub secret_redaction: Option<SecretRedactor>,
 }
 
 impl Default for ExecutionConfig {
@@ -87,6 +127,21 @@ impl Default for ExecutionConfig {
             auto_compact: true,
             compact_threshold: crate::session::compact::DEFAULT_COMPACT_THRESHOLD,
             compact_keep_messages: 4,
+            compact_strategy: None,
+            max_parallel_tools: DEFAULT_MAX_PARALLEL_TOOLS,
+            dry_run: false,
+            tool_preview: None,
+            tool_retry: HashMap::new(),
+            tool_limits: HashMap::new(),
+            tool_cache: None,
+            tool_quotas: None,
+            search_excludes: None,
+            backup_writes: false,
+            upload_large_files: false,
+            emit_thinking: true,
+            emit_mcp_logs: false,
+            idle_notification: Some(Duration::from_secs(60)),
+            secret_redaction: None,
         }
     }
 }
@@ -126,6 +181,189 @@ impl ExecutionConfig {
         self.compact_keep_messages = count;
         self
     }
+
+    pub fn compact_strategy(mut self, strategy: CompactStrategy) -> Self {
+        self.compact_strategy = Some(strategy);
+        self
+    }
+
+    pub fn max_parallel_tools(mut self, max: usize) -> Self {
+        self.max_parallel_tools = max.max(1);
+        self
+    }
+
+    pub fn dry_run(mut self, enabled: bool) -> Self {
+        self.dry_run = enabled;
+        self
+    }
+
+    pub fn tool_preview(mut self, policy: PreviewPolicy) -> Self {
+        self.tool_preview = Some(policy);
+        self
+    }
+
+    pub fn tool_retry(mut self, tool: impl Into<String>, policy: ToolRetryPolicy) -> Self {
+        self.tool_retry.insert(tool.into(), policy);
+        self
+    }
+
+    pub fn tool_retry_policy(&self, tool: &str) -> Option<&ToolRetryPolicy> {
+        self.tool_retry.get(tool)
+    }
+
+    pub fn tool_limits(mut self, tool: impl Into<String>, limits: ToolLimits) -> Self {
+        self.tool_limits.insert(tool.into(), limits);
+        self
+    }
+
+    pub fn tool_cache(mut self, config: ToolCacheConfig) -> Self {
+        self.tool_cache = Some(config);
+        self
+    }
+
+    pub fn tool_quotas(mut self, quotas: ToolQuotas) -> Self {
+        self.tool_quotas = Some(quotas);
+        self
+    }
+
+    pub fn search_excludes(
+        mut self,
+        excludes: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.search_excludes = Some(excludes.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn backup_writes(mut self, enabled: bool) -> Self {
+        self.backup_writes = enabled;
+        self
+    }
+
+    pub fn upload_large_files(mut self, enabled: bool) -> Self {
+        self.upload_large_files = enabled;
+        self
+    }
+
+    pub fn emit_thinking(mut self, enabled: bool) -> Self {
+        self.emit_thinking = enabled;
+        self
+    }
+
+    pub fn emit_mcp_logs(mut self, enabled: bool) -> Self {
+        self.emit_mcp_logs = enabled;
+        self
+    }
+
+    pub fn idle_notification(mut self, after: Option<Duration>) -> Self {
+        self.idle_notification = after;
+        self
+    }
+
+    pub fn redact_secrets(mut self, redactor: SecretRedactor) -> Self {
+        self.secret_redaction = Some(redactor);
+        self
+    }
+}
+
+/// Retry policy for failed tool calls.
+///
+/// Retries happen inside the SDK before the result is returned to the model,
+/// so a transient failure does not cost an extra turn. Permission, security,
+/// unknown-tool and invalid-input errors are never retried.
+#[derive(Debug, Clone)]
+pub struct ToolRetryPolicy {
+    /// Retries after the first failed attempt
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled for each following retry
+    pub initial_backoff: Duration,
+    /// Upper bound for the delay between retries
+    pub max_backoff: Duration,
+    /// Case-insensitive substrings of the error message that make a failure
+    /// retryable. Empty retries any retryable error.
+    pub retry_on: Vec<String>,
+}
+
+impl Default for ToolRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            retry_on: Vec::new(),
+        }
+    }
+}
+
+impl ToolRetryPolicy {
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..Default::default()
+        }
+    }
+
+    /// Retry only errors that look like transient network failures
+    /// (timeouts, resets, 429/5xx), e.g. for `WebFetch` or MCP tools.
+    pub fn transient_network(max_retries: u32) -> Self {
+        Self::new(max_retries).retry_on([
+            "timeout",
+            "timed out",
+            "connection",
+            "temporarily unavailable",
+            "too many requests",
+            "429",
+            "502",
+            "503",
+            "504",
+        ])
+    }
+
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    pub fn retry_on<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.retry_on.extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Whether a failed result should be retried under this policy.
+    pub fn should_retry(&self, result: &crate::types::ToolResult) -> bool {
+        use crate::types::{ToolError, ToolOutput};
+
+        let ToolOutput::Error(error) = &result.output else {
+            return false;
+        };
+        if result.is_non_retryable()
+            || matches!(
+                error,
+                ToolError::InvalidInput { .. } | ToolError::BlockedByHook { .. }
+            )
+        {
+            return false;
+        }
+        if self.retry_on.is_empty() {
+            return true;
+        }
+        let message = error.to_string().to_lowercase();
+        self.retry_on
+            .iter()
+            .any(|p| message.contains(&p.to_lowercase()))
+    }
+
+    /// Delay before retry number `attempt` (1-based).
+    pub fn backoff_for(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
 }
 
 /// Security and permission configuration.
@@ -137,6 +375,20 @@ pub struct SecurityConfig {
     pub tool_access: ToolAccess,
     /// Environment variables for tool execution
     pub env: HashMap<String, String>,
+    /// Directories outside the working directory that tools may access
+    pub additional_directories: Vec<PathBuf>,
+    /// Tokenize PII in outbound requests and restore it in responses
+    pub pii_scrubber: Option<PiiScrubber>,
+    /// Record tool calls blocked by the security guard
+    pub audit_log: Option<AuditLog>,
+    /// Refuse file writes and non-read-only Bash commands, and drop the write tools
+    pub read_only: bool,
+    /// Size, line-length and binary limits for reading and writing files
+    pub file_limits: Option<FileLimits>,
+    /// Environment inherited by Bash commands, hooks and MCP stdio servers
+    pub env_policy: Option<EnvPolicy>,
+    /// Commands, arguments and shell features Bash may use
+    pub bash_policy: Option<BashPolicy>,
 }
 
 impl SecurityConfig {
@@ -152,6 +404,7 @@ impl SecurityConfig {
         Self {
             permission_policy: PermissionPolicy::read_only(),
             tool_access: ToolAccess::only(["Read", "Glob", "Grep", "Task", "TaskOutput"]),
+            read_only: true,
             ..Default::default()
         }
     }
@@ -180,6 +433,16 @@ impl SecurityConfig {
         }
         self
     }
+
+    pub fn scrub_pii(mut self, scrubber: PiiScrubber) -> Self {
+        self.pii_scrubber = Some(scrubber);
+        self
+    }
+
+    pub fn audit_log(mut self, log: AuditLog) -> Self {
+        self.audit_log = Some(log);
+        self
+    }
 }
 
 /// Budget and cost control configuration.
@@ -187,6 +450,8 @@ impl SecurityConfig {
 pub struct BudgetConfig {
     /// Maximum cost in USD
     pub max_cost_usd: Option<Decimal>,
+    /// Maximum cost in USD of a single `execute()` call
+    pub max_cost_per_run_usd: Option<Decimal>,
     /// Tenant identifier for multi-tenant tracking
     pub tenant_id: Option<String>,
     /// Model to fall back to when budget exceeded
@@ -203,6 +468,11 @@ impl BudgetConfig {
         self
     }
 
+    pub fn max_cost_per_run(mut self, usd: Decimal) -> Self {
+        self.max_cost_per_run_usd = Some(usd);
+        self
+    }
+
     pub fn tenant(mut self, tenant_id: impl Into<String>) -> Self {
         self.tenant_id = Some(tenant_id.into());
         self
@@ -382,12 +652,14 @@ impl CacheConfig {
 
 /// Server-side tools configuration.
 ///
-/// Anthropic's built-in server-side tools (Brave Search, web fetch).
-/// These are automatically enabled when "WebSearch" or "WebFetch" are in ToolAccess.
+/// Anthropic's built-in server-side tools (Brave Search, web fetch, code execution).
+/// Web search and fetch are automatically enabled when "WebSearch" or "WebFetch"
+/// are in ToolAccess; code execution is opt-in.
 #[derive(Debug, Clone, Default)]
 pub struct ServerToolsConfig {
     pub web_search: Option<crate::types::WebSearchTool>,
     pub web_fetch: Option<crate::types::WebFetchTool>,
+    pub code_execution: Option<crate::types::CodeExecutionTool>,
 }
 
 impl ServerToolsConfig {
@@ -395,6 +667,7 @@ impl ServerToolsConfig {
         Self {
             web_search: Some(crate::types::WebSearchTool::default()),
             web_fetch: Some(crate::types::WebFetchTool::default()),
+            code_execution: None,
         }
     }
 
@@ -407,6 +680,11 @@ impl ServerToolsConfig {
         self.web_fetch = Some(config);
         self
     }
+
+    pub fn code_execution(mut self, config: crate::types::CodeExecutionTool) -> Self {
+        self.code_execution = Some(config);
+        self
+    }
 }
 
 /// Complete agent configuration combining all domain configs.
@@ -501,6 +779,13 @@ mod tests {
         assert_eq!(config.max_iterations, 50);
         assert_eq!(config.timeout, Some(Duration::from_secs(600)));
         assert!(!config.auto_compact);
+        assert_eq!(config.max_parallel_tools, DEFAULT_MAX_PARALLEL_TOOLS);
+        assert_eq!(
+            ExecutionConfig::default()
+                .max_parallel_tools(0)
+                .max_parallel_tools,
+            1
+        );
     }
 
     #[test]
@@ -586,4 +871,27 @@ mod tests {
         assert_eq!(config.static_ttl, crate::types::CacheTtl::FiveMinutes);
         assert_eq!(config.message_ttl, crate::types::CacheTtl::OneHour);
     }
+
+    #[test]
+    fn test_tool_retry_policy() {
+        use crate::types::{ToolError, ToolOutput, ToolResult};
+
+        let policy = ToolRetryPolicy::new(5)
+            .backoff(Duration::from_millis(100), Duration::from_millis(350))
+            .retry_on(["Timeout"]);
+        assert_eq!(policy.backoff_for(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff_for(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff_for(3), Duration::from_millis(350));
+
+        assert!(
+            policy.should_retry(&ToolResult::from(ToolOutput::tool_error(
+                ToolError::timeout(5000)
+            )))
+        );
+        assert!(!policy.should_retry(&ToolResult::error("disk full")));
+        assert!(!policy.should_retry(&ToolResult::success("ok")));
+        assert!(!ToolRetryPolicy::default().should_retry(&ToolResult::from(
+            ToolOutput::tool_error(ToolError::permission_denied("Bash", "exec"))
+        )));
+    }
 }
@@ -0,0 +1,363 @@
+//! Persistent sinks for agent events.
+//!
+//! An [`EventSink`] receives every [`AgentEvent`] a run produces, wrapped in an
+//! [`EventRecord`] with a timestamp and session/run/turn correlation. Sinks are
+//! meant for audit trails and post-mortem debugging: a failing sink is logged
+//! and never aborts the run.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Serializer};
+use serde_json::json;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{Mutex, mpsc};
+use tracing::warn;
+
+use super::events::AgentEvent;
+use crate::common::clock;
+
+/// An [`AgentEvent`] with audit metadata.
+#[derive(Debug, Clone, Serialize)]
+pub struct EventRecord {
+    pub timestamp: DateTime<Utc>,
+    pub session_id: String,
+    /// Identifies one `execute`/`execute_stream` call within the session.
+    pub run_id: String,
+    /// Agent loop iteration the event belongs to (1-based).
+    pub turn: usize,
+    /// Position of the event within the run, starting at 0.
+    pub sequence: u64,
+    #[serde(serialize_with = "serialize_event")]
+    pub event: AgentEvent,
+}
+
+/// Destination for [`EventRecord`]s (file, database, channel, ...).
+#[async_trait]
+pub trait EventSink: Send + Sync {
+    async fn record(&self, record: &EventRecord) -> crate::Result<()>;
+}
+
+/// Appends records as JSON lines to a file.
+pub struct JsonlEventSink {
+    path: PathBuf,
+    file: Mutex<tokio::fs::File>,
+}
+
+impl JsonlEventSink {
+    /// Open `path` for appending, creating it and its parent directories if needed.
+    pub async fn open(path: impl Into<PathBuf>) -> crate::Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[async_trait]
+impl EventSink for JsonlEventSink {
+    async fn record(&self, record: &EventRecord) -> crate::Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        let mut file = self.file.lock().await;
+        file.write_all(&line).await?;
+        file.flush().await?;
+        Ok(())
+    }
+}
+
+/// Forwards records to an unbounded channel.
+pub struct ChannelEventSink {
+    sender: mpsc::UnboundedSender<EventRecord>,
+}
+
+impl ChannelEventSink {
+    pub fn new(sender: mpsc::UnboundedSender<EventRecord>) -> Self {
+        Self { sender }
+    }
+
+    /// Create a sink together with the receiving end of its channel.
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<EventRecord>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (Self { sender }, receiver)
+    }
+}
+
+#[async_trait]
+impl EventSink for ChannelEventSink {
+    async fn record(&self, record: &EventRecord) -> crate::Result<()> {
+        self.sender
+            .send(record.clone())
+            .map_err(|_| crate::Error::Stream("Event channel closed".into()))
+    }
+}
+
+/// Stamps and fans out the events of a single run.
+pub(crate) struct EventRecorder {
+    sinks: Arc<[Arc<dyn EventSink>]>,
+    session_id: String,
+    run_id: String,
+    sequence: AtomicU64,
+}
+
+impl EventRecorder {
+    pub(crate) fn new(sinks: Arc<[Arc<dyn EventSink>]>, session_id: &str) -> Self {
+        Self {
+            sinks,
+            session_id: session_id.to_string(),
+            run_id: clock::new_uuid().to_string(),
+            sequence: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        !self.sinks.is_empty()
+    }
+
+    pub(crate) async fn emit(&self, turn: usize, event: &AgentEvent) {
+        if !self.is_enabled() {
+            return;
+        }
+        let record = EventRecord {
+            timestamp: clock::now_utc(),
+            session_id: self.session_id.clone(),
+            run_id: self.run_id.clone(),
+            turn,
+            sequence: self.sequence.fetch_add(1, Ordering::Relaxed),
+            event: event.clone(),
+        };
+        for sink in self.sinks.iter() {
+            if let Err(e) = sink.record(&record).await {
+                warn!(error = %e, run_id = %record.run_id, "Failed to record agent event");
+            }
+        }
+    }
+}
+
+fn serialize_event<S: Serializer>(event: &AgentEvent, serializer: S) -> Result<S::Ok, S::Error> {
+    let value = match event {
+        AgentEvent::Text(text) => json!({"type": "text", "text": text}),
+        AgentEvent::Thinking(thinking) => json!({"type": "thinking", "thinking": thinking}),
+        AgentEvent::ToolComplete {
+            id,
+            name,
+            output,
+            is_error,
+            duration_ms,
+        } => json!({
+            "type": "tool_complete",
+            "id": id,
+            "name": name,
+            "output": output,
+            "is_error": is_error,
+            "duration_ms": duration_ms,
+        }),
+        AgentEvent::ToolBlocked { id, name, reason } => json!({
+            "type": "tool_blocked",
+            "id": id,
+            "name": name,
+            "reason": reason,
+        }),
+        AgentEvent::SecurityViolation {
+            id,
+            name,
+            violation,
+        } => json!({
+            "type": "security_violation",
+            "id": id,
+            "name": name,
+            "kind": violation.kind,
+            "command": violation.command,
+            "detail": violation.detail,
+        }),
+        AgentEvent::ToolDeprecated { id, name, message } => json!({
+            "type": "tool_deprecated",
+            "id": id,
+            "name": name,
+            "message": message,
+        }),
+        AgentEvent::ToolPlanned(planned) => json!({
+            "type": "tool_planned",
+            "id": planned.tool_use_id,
+            "name": planned.name,
+            "input": planned.input,
+            "diff": planned.diff,
+            "error": planned.error,
+        }),
+        AgentEvent::ToolPreview(preview) => json!({
+            "type": "tool_preview",
+            "id": preview.tool_use_id,
+            "name": preview.name,
+            "input": preview.input,
+            "diff": preview.diff,
+        }),
+        AgentEvent::CodeExecution(block) => json!({
+            "type": "code_execution",
+            "tool_use_id": block.tool_use_id,
+            "content": block.content,
+        }),
+        AgentEvent::ResourceChanged { server, uri } => json!({
+            "type": "resource_changed",
+            "server": server,
+            "uri": uri,
+        }),
+        AgentEvent::ToolProgress {
+            name,
+            progress,
+            total,
+            percentage,
+            message,
+        } => json!({
+            "type": "tool_progress",
+            "name": name,
+            "progress": progress,
+            "total": total,
+            "percentage": percentage,
+            "message": message,
+        }),
+        AgentEvent::McpLog {
+            server,
+            level,
+            logger,
+            message,
+        } => json!({
+            "type": "mcp_log",
+            "server": server,
+            "level": level,
+            "logger": logger,
+            "message": message,
+        }),
+        AgentEvent::ContextUpdate {
+            used_tokens,
+            max_tokens,
+        } => json!({
+            "type": "context_update",
+            "used_tokens": used_tokens,
+            "max_tokens": max_tokens,
+        }),
+        AgentEvent::Progress {
+            iteration,
+            max_iterations,
+            todos_done,
+            todos_total,
+            elapsed,
+        } => json!({
+            "type": "progress",
+            "iteration": iteration,
+            "max_iterations": max_iterations,
+            "todos_done": todos_done,
+            "todos_total": todos_total,
+            "elapsed_ms": elapsed.as_millis() as u64,
+        }),
+        AgentEvent::Complete(result) => json!({
+            "type": "complete",
+            "uuid": result.uuid,
+            "text": result.text,
+            "stop_reason": result.stop_reason,
+            "state": result.state,
+            "iterations": result.iterations,
+            "tool_calls": result.tool_calls,
+            "usage": result.usage,
+            "total_cost_usd": result.metrics.total_cost_usd,
+        }),
+    };
+    value.serialize(serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::PlannedToolCall;
+    use crate::security::sandbox::{SandboxViolation, ViolationKind};
+
+    #[tokio::test]
+    async fn test_jsonl_sink_appends_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit/events.jsonl");
+        let sink: Arc<dyn EventSink> = Arc::new(JsonlEventSink::open(&path).await.unwrap());
+        let recorder = EventRecorder::new(Arc::from(vec![sink]), "session-1");
+
+        recorder.emit(1, &AgentEvent::Text("hello".into())).await;
+        recorder
+            .emit(
+                1,
+                &AgentEvent::ToolBlocked {
+                    id: "toolu_1".into(),
+                    name: "Bash".into(),
+                    reason: "denied".into(),
+                },
+            )
+            .await;
+        recorder
+            .emit(
+                1,
+                &AgentEvent::SecurityViolation {
+                    id: "toolu_2".into(),
+                    name: "Bash".into(),
+                    violation: SandboxViolation::new(
+                        ViolationKind::Filesystem,
+                        "touch /etc/x",
+                        "Permission denied",
+                    ),
+                },
+            )
+            .await;
+        recorder
+            .emit(
+                1,
+                &AgentEvent::ToolPlanned(PlannedToolCall {
+                    tool_use_id: "toolu_3".into(),
+                    name: "Edit".into(),
+                    input: json!({"file_path": "a.txt"}),
+                    diff: None,
+                    error: Some("old_string not found".into()),
+                }),
+            )
+            .await;
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<serde_json::Value> = content
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0]["session_id"], "session-1");
+        assert_eq!(lines[0]["event"]["type"], "text");
+        assert_eq!(lines[1]["sequence"], 1);
+        assert_eq!(lines[1]["event"]["reason"], "denied");
+        assert_eq!(lines[0]["run_id"], lines[1]["run_id"]);
+        assert_eq!(lines[2]["event"]["type"], "security_violation");
+        assert_eq!(lines[2]["event"]["kind"], "filesystem");
+        assert_eq!(lines[2]["event"]["command"], "touch /etc/x");
+        assert_eq!(lines[3]["event"]["error"], "old_string not found");
+    }
+
+    #[tokio::test]
+    async fn test_channel_sink() {
+        let (sink, mut rx) = ChannelEventSink::channel();
+        let recorder =
+            EventRecorder::new(Arc::from(vec![Arc::new(sink) as Arc<dyn EventSink>]), "s");
+
+        recorder.emit(3, &AgentEvent::Thinking("hmm".into())).await;
+
+        let record = rx.recv().await.unwrap();
+        assert_eq!(record.turn, 3);
+        assert!(matches!(record.event, AgentEvent::Thinking(ref t) if t == "hmm"));
+    }
+}
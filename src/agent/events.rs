@@ -1,12 +1,22 @@
 //! Agent events and result types.
 
+use std::time::Duration;
+
+use super::plan::PlannedToolCall;
+use super::preview::ToolPreview;
 use super::state::{AgentMetrics, AgentState};
-use crate::types::{Message, StopReason, Usage};
+use crate::common::clock;
+use crate::mcp::{McpLogLevel, McpNotification, make_mcp_name};
+use crate::security::sandbox::SandboxViolation;
+use crate::types::{CodeExecutionToolResultBlock, Message, StopReason, Usage};
 
 /// Events emitted during agent execution.
 #[derive(Debug, Clone)]
 pub enum AgentEvent {
     Text(String),
+    /// Extended thinking, kept apart from `Text`; streamed as deltas.
+    ///
+    /// Suppressed when `ExecutionConfig::emit_thinking` is off.
     Thinking(String),
     ToolComplete {
         id: String,
@@ -20,13 +30,132 @@ pub enum AgentEvent {
         name: String,
         reason: String,
     },
+    /// A call through a tool's former name, or to a tool marked deprecated.
+    /// The call still runs.
+    /// An operation the sandbox denied a tool call, such as a write outside
+    /// the allowed paths or a connection to a blocked host. Emitted before
+    /// the call's `ToolComplete`.
+    SecurityViolation {
+        id: String,
+        name: String,
+        violation: SandboxViolation,
+    },
+    ToolDeprecated {
+        id: String,
+        name: String,
+        message: String,
+    },
+    /// A tool call recorded instead of executed (dry-run mode).
+    ToolPlanned(PlannedToolCall),
+    /// A file change awaiting its preview decision (preview mode).
+    ToolPreview(ToolPreview),
+    /// Output of a server-side code execution run, including generated file IDs.
+    CodeExecution(CodeExecutionToolResultBlock),
+    /// A subscribed MCP resource changed; read it again for the new content.
+    ResourceChanged {
+        server: String,
+        uri: String,
+    },
+    /// Progress reported by an MCP server for a running tool call.
+    ToolProgress {
+        /// Qualified tool name (`mcp__server__tool`)
+        name: String,
+        progress: f64,
+        total: Option<f64>,
+        /// Completion (0-100) when the server reports a total
+        percentage: Option<f64>,
+        message: Option<String>,
+    },
+    /// Log message from an MCP server.
+    ///
+    /// Only emitted when `ExecutionConfig::emit_mcp_logs` is on; the messages
+    /// always go to `tracing`.
+    McpLog {
+        server: String,
+        level: McpLogLevel,
+        logger: Option<String>,
+        message: String,
+    },
     ContextUpdate {
         used_tokens: u64,
         max_tokens: u64,
     },
+    /// Progress estimate, emitted after each tool round before the next request.
+    Progress {
+        /// Iterations completed so far
+        iteration: usize,
+        max_iterations: usize,
+        /// Completed entries of the todo list
+        todos_done: usize,
+        todos_total: usize,
+        elapsed: Duration,
+    },
     Complete(Box<AgentResult>),
 }
 
+impl AgentEvent {
+    /// Estimated completion (0.0-1.0) for a [`AgentEvent::Progress`] event.
+    ///
+    /// Based on the todo list when there is one, otherwise on the share of
+    /// the iteration limit used.
+    pub fn progress_ratio(&self) -> Option<f64> {
+        let Self::Progress {
+            iteration,
+            max_iterations,
+            todos_done,
+            todos_total,
+            ..
+        } = self
+        else {
+            return None;
+        };
+        let ratio = if *todos_total > 0 {
+            *todos_done as f64 / *todos_total as f64
+        } else if *max_iterations > 0 {
+            *iteration as f64 / *max_iterations as f64
+        } else {
+            0.0
+        };
+        Some(ratio.clamp(0.0, 1.0))
+    }
+}
+
+impl From<McpNotification> for AgentEvent {
+    fn from(notification: McpNotification) -> Self {
+        let percentage = notification.percentage();
+        let log_message = notification.log_message();
+        match notification {
+            McpNotification::ResourceUpdated { server, uri } => {
+                Self::ResourceChanged { server, uri }
+            }
+            McpNotification::Progress {
+                server,
+                tool,
+                progress,
+                total,
+                message,
+            } => Self::ToolProgress {
+                name: make_mcp_name(&server, &tool),
+                progress,
+                total,
+                percentage,
+                message,
+            },
+            McpNotification::Log {
+                server,
+                level,
+                logger,
+                ..
+            } => Self::McpLog {
+                server,
+                level,
+                logger,
+                message: log_message.unwrap_or_default(),
+            },
+        }
+    }
+}
+
 /// Result of agent execution.
 #[derive(Debug, Clone)]
 pub struct AgentResult {
@@ -58,7 +187,7 @@ impl AgentResult {
         Self {
             tool_calls: metrics.tool_calls,
             state: AgentState::Completed,
-            uuid: uuid::Uuid::new_v4().to_string(),
+            uuid: clock::new_uuid().to_string(),
             text,
             usage,
             iterations,
@@ -90,6 +219,12 @@ impl AgentResult {
         &self.session_id
     }
 
+    /// Tool calls proposed during a dry run, in the order the model made them.
+    #[must_use]
+    pub fn planned_tool_calls(&self) -> &[PlannedToolCall] {
+        &self.metrics.planned_tool_calls
+    }
+
     pub fn extract<T: serde::de::DeserializeOwned>(&self) -> crate::Result<T> {
         let value = self
             .structured_output
@@ -1,23 +1,40 @@
 //! Agent execution logic with session-based context management.
 
 use std::sync::Arc;
-use std::time::Instant;
 
+use rust_decimal::Decimal;
 use tracing::{debug, info, instrument, warn};
 
-use super::AgentMetrics;
 use super::common::{
-    self, BudgetContext, accumulate_inner_usage, accumulate_response_usage, handle_compaction,
-    run_post_tool_hooks, run_stop_hooks, try_activate_dynamic_rules,
+    self, BudgetContext, PendingToolCall, accumulate_inner_usage, accumulate_response_usage,
+    apply_interjections, budget_warning, deprecation_events, exceeds_run_ceiling,
+    execute_tool_batch, handle_compaction, idle_message, mcp_events, notify_when_idle,
+    persist_session, plan_tool_calls, progress_event, run_notification_hooks, run_post_tool_hooks,
+    run_stop_hooks, tool_batch_end, try_activate_dynamic_rules, with_hook_context, with_mcp_events,
 };
-use super::events::AgentResult;
+use super::event_sink::EventRecorder;
+use super::events::{AgentEvent, AgentResult};
 use super::executor::Agent;
+use super::plan::PlannedToolCall;
+use super::preview::{approval_message, review_tool_calls};
 use super::request::RequestBuilder;
-use crate::hooks::{HookContext, HookEvent, HookInput};
+use super::{AgentMetrics, AgentState};
+use crate::common::clock;
+use crate::hooks::{HookContext, HookEvent, HookInput, NotificationKind};
 use crate::types::{
     ContentBlock, Message, PermissionDenial, StopReason, ToolResultBlock, Usage, context_window,
 };
 
+/// Per-call overrides for [`Agent::execute_inner`].
+#[derive(Default)]
+struct RunOptions<'a> {
+    output_schema: Option<&'a serde_json::Value>,
+    max_cost_usd: Option<Decimal>,
+}
+
+/// Maximum follow-up requests [`Agent::execute_as`] makes when the response does not parse.
+pub const MAX_STRUCTURED_OUTPUT_REPAIRS: usize = 2;
+
 impl Agent {
     fn check_budget(&self) -> crate::Result<()> {
         BudgetContext {
@@ -28,12 +45,15 @@ impl Agent {
         .check()
     }
 
-    pub async fn execute(&self, prompt: &str) -> crate::Result<AgentResult> {
-        let timeout = self
-            .config
+    fn execution_timeout(&self) -> std::time::Duration {
+        self.config
             .execution
             .timeout
-            .unwrap_or(std::time::Duration::from_secs(600));
+            .unwrap_or(std::time::Duration::from_secs(600))
+    }
+
+    pub async fn execute(&self, prompt: &str) -> crate::Result<AgentResult> {
+        let timeout = self.execution_timeout();
 
         if self.state.is_executing() {
             self.state
@@ -43,24 +63,127 @@ impl Agent {
             return self.wait_for_execution(timeout).await;
         }
 
-        tokio::time::timeout(timeout, self.execute_inner(prompt))
+        self.run_cancellable(timeout, self.execute_inner(prompt, RunOptions::default()))
             .await
-            .map_err(|_| crate::Error::Timeout(timeout))?
+    }
+
+    /// Execute with a cost ceiling in USD for this call only.
+    ///
+    /// Overrides [`BudgetConfig::max_cost_per_run_usd`](crate::BudgetConfig::max_cost_per_run_usd).
+    /// When the next API call would cross the ceiling, the run stops with its
+    /// partial output and [`AgentState::BudgetExceeded`] instead of erroring.
+    pub async fn execute_with_budget(
+        &self,
+        prompt: &str,
+        max_cost_usd: Decimal,
+    ) -> crate::Result<AgentResult> {
+        let options = RunOptions {
+            max_cost_usd: Some(max_cost_usd),
+            ..Default::default()
+        };
+        self.run_cancellable(
+            self.execution_timeout(),
+            self.execute_inner(prompt, options),
+        )
+        .await
+    }
+
+    /// Execute with structured output derived from `T` and return the parsed value.
+    ///
+    /// The strict JSON schema for `T` overrides any configured output schema for
+    /// this call. If the final response does not deserialize into `T`, the parse
+    /// error is sent back to the model, up to [`MAX_STRUCTURED_OUTPUT_REPAIRS`] times.
+    pub async fn execute_as<T>(&self, prompt: &str) -> crate::Result<T>
+    where
+        T: schemars::JsonSchema + serde::de::DeserializeOwned,
+    {
+        let schema = crate::client::strict_schema::<T>();
+        let timeout = self.execution_timeout();
+
+        let mut prompt = prompt.to_string();
+        let mut repairs = 0;
+        loop {
+            let result = self
+                .run_cancellable(
+                    timeout,
+                    self.execute_inner(
+                        &prompt,
+                        RunOptions {
+                            output_schema: Some(&schema),
+                            ..Default::default()
+                        },
+                    ),
+                )
+                .await?;
+
+            match serde_json::from_str::<T>(result.text()) {
+                Ok(value) => return Ok(value),
+                Err(e) if repairs < MAX_STRUCTURED_OUTPUT_REPAIRS => {
+                    repairs += 1;
+                    warn!(attempt = repairs, error = %e, "Structured output did not match schema, repairing");
+                    prompt = format!(
+                        "Your previous response could not be parsed: {e}. \
+                         Respond again with only a JSON value that matches the required schema."
+                    );
+                }
+                Err(e) => {
+                    return Err(crate::Error::Parse(format!(
+                        "Structured output did not match schema after {repairs} repair attempts: {e}"
+                    )));
+                }
+            }
+        }
     }
 
     async fn wait_for_execution(&self, timeout: std::time::Duration) -> crate::Result<AgentResult> {
-        tokio::time::timeout(timeout, async {
+        self.run_cancellable(timeout, async {
             loop {
                 self.state.wait_for_queue_signal().await;
                 if !self.state.is_executing()
                     && let Some(merged) = self.state.dequeue_or_merge().await
                 {
-                    return self.execute_inner(&merged.content).await;
+                    return self
+                        .execute_inner(&merged.content, RunOptions::default())
+                        .await;
                 }
             }
         })
         .await
-        .map_err(|_| crate::Error::Timeout(timeout))?
+    }
+
+    /// Drive an execution under the configured timeout and the agent's cancellation token.
+    ///
+    /// On cancellation the execution future is dropped, which aborts the in-flight API
+    /// request and any running tool calls, before the session is finalized as cancelled.
+    async fn run_cancellable(
+        &self,
+        timeout: std::time::Duration,
+        execution: impl std::future::Future<Output = crate::Result<AgentResult>>,
+    ) -> crate::Result<AgentResult> {
+        let outcome =
+            tokio::time::timeout(timeout, common::cancellable(&self.cancel_token, execution))
+                .await
+                .map_err(|_| crate::Error::Timeout(timeout))?;
+
+        match outcome {
+            Ok(result) => result,
+            Err(e) => {
+                common::finalize_cancelled(&self.state, &self.tools).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Steer a running execution without cancelling it.
+    ///
+    /// The input is merged into the conversation at the next iteration boundary:
+    /// alongside the pending tool results, or as a follow-up turn if the model
+    /// has just finished. Input sent while idle is picked up by the next run.
+    pub async fn interject(&self, content: impl Into<String>) -> crate::Result<uuid::Uuid> {
+        self.state
+            .interject(content)
+            .await
+            .map_err(|e| crate::Error::Session(format!("Queue full: {}", e)))
     }
 
     pub async fn execute_with_messages(
@@ -94,20 +217,35 @@ impl Agent {
         self.execute(&enriched_prompt).await
     }
 
-    #[instrument(skip(self, prompt), fields(session_id = %self.session_id))]
-    async fn execute_inner(&self, prompt: &str) -> crate::Result<AgentResult> {
+    #[instrument(skip(self, prompt, options), fields(session_id = %self.session_id))]
+    async fn execute_inner(
+        &self,
+        prompt: &str,
+        options: RunOptions<'_>,
+    ) -> crate::Result<AgentResult> {
+        let output_schema = options
+            .output_schema
+            .or(self.config.prompt.output_schema.as_ref());
+        let cost_ceiling = options
+            .max_cost_usd
+            .or(self.config.budget.max_cost_per_run_usd);
         let _guard = self.state.acquire_execution().await;
-        let execution_start = Instant::now();
+        let execution_start = clock::now();
+        let recorder = EventRecorder::new(Arc::clone(&self.event_sinks), &self.session_id);
         let hook_ctx = self.hook_context();
 
         let session_start_input = HookInput::session_start(&*self.session_id);
-        if let Err(e) = self
+        let session_context = match self
             .hooks
             .execute(HookEvent::SessionStart, session_start_input, &hook_ctx)
             .await
         {
-            warn!(error = %e, "SessionStart hook failed");
-        }
+            Ok(output) => output.additional_context,
+            Err(e) => {
+                warn!(error = %e, "SessionStart hook failed");
+                None
+            }
+        };
 
         let final_prompt = if let Some(merged) = self.state.dequeue_or_merge().await {
             format!("{}\n{}", prompt, merged.content)
@@ -136,6 +274,10 @@ impl Agent {
                     .unwrap_or_else(|| "Blocked by hook".into()),
             ));
         }
+        let final_prompt = with_hook_context(
+            final_prompt,
+            [session_context, prompt_output.additional_context],
+        );
 
         self.state
             .with_session_mut(|session| {
@@ -148,12 +290,15 @@ impl Agent {
         let mut final_stop_reason = StopReason::EndTurn;
         let mut dynamic_rules_context = String::new();
         let mut total_usage = Usage::default();
+        let mut last_call_cost = Decimal::ZERO;
+        let mut budget_exceeded = false;
 
         let mut request_builder = {
-            let builder = RequestBuilder::new(&self.config, Arc::clone(&self.tools));
+            let builder = RequestBuilder::new(&self.config, Arc::clone(&self.tools))
+                .output_schema(output_schema.cloned());
 
             if let Some(ref tsm) = self.tool_search_manager {
-                let prepared = tsm.prepare_tools().await;
+                let prepared = tsm.prepare_tools_for(&final_prompt).await;
                 if prepared.use_search {
                     info!(
                         immediate = prepared.immediate.len(),
@@ -168,6 +313,7 @@ impl Agent {
             }
         };
         let max_tokens = context_window::for_model(&self.config.model.primary);
+        let mut mcp_notifications = self.mcp_manager.as_ref().map(|m| m.notifications());
 
         info!(prompt_len = final_prompt.len(), "Starting agent execution");
 
@@ -182,6 +328,11 @@ impl Agent {
             }
 
             self.check_budget()?;
+            if exceeds_run_ceiling(cost_ceiling, metrics.total_cost_usd, last_call_cost) {
+                metrics.iterations -= 1;
+                budget_exceeded = true;
+                break;
+            }
 
             let budget_ctx = BudgetContext {
                 tracker: &self.budget_tracker,
@@ -201,12 +352,19 @@ impl Agent {
                 })
                 .await;
 
-            let api_start = Instant::now();
-            let request = request_builder.build(messages, &dynamic_rules_context);
-            let response = self.client.send_with_auth_retry(request).await?;
-            let api_duration_ms = api_start.elapsed().as_millis() as u64;
+            let api_start = clock::now();
+            let request = self
+                .client
+                .betas()
+                .apply(request_builder.build(messages, &dynamic_rules_context))?;
+            metrics.record_applied_betas(&request);
+            let mut response = self.client.send_with_auth_retry(request).await?;
+            let api_duration_ms = clock::elapsed(api_start).as_millis() as u64;
             metrics.record_api_call_with_timing(api_duration_ms);
             debug!(api_time_ms = api_duration_ms, "API call completed");
+            if let Some(scrubber) = &self.config.security.pii_scrubber {
+                scrubber.restore_blocks(&mut response.content);
+            }
 
             self.state
                 .with_session_mut(|session| {
@@ -214,7 +372,7 @@ impl Agent {
                 })
                 .await;
 
-            accumulate_response_usage(
+            last_call_cost = accumulate_response_usage(
                 &mut total_usage,
                 &mut metrics,
                 &self.budget_tracker,
@@ -222,17 +380,66 @@ impl Agent {
                 &self.config.model.primary,
                 &response.usage,
             );
+            if let Some(message) = budget_warning(&self.budget_tracker) {
+                run_notification_hooks(
+                    &self.hooks,
+                    &self.hook_context(),
+                    &self.session_id,
+                    NotificationKind::BudgetWarning,
+                    message,
+                )
+                .await;
+            }
 
             final_text = response.text();
             final_stop_reason = response.stop_reason.unwrap_or(StopReason::EndTurn);
 
+            if recorder.is_enabled() {
+                for thinking in response
+                    .content
+                    .iter()
+                    .filter_map(ContentBlock::as_thinking)
+                    .filter(|_| self.config.execution.emit_thinking)
+                {
+                    let event = AgentEvent::Thinking(thinking.thinking.clone());
+                    recorder.emit(metrics.iterations, &event).await;
+                }
+                if !final_text.is_empty() {
+                    let text = match &self.config.execution.secret_redaction {
+                        Some(redactor) => redactor.redact(&final_text).into_owned(),
+                        None => final_text.clone(),
+                    };
+                    let event = AgentEvent::Text(text);
+                    recorder.emit(metrics.iterations, &event).await;
+                }
+                for block in response
+                    .content
+                    .iter()
+                    .filter_map(ContentBlock::as_code_execution_result)
+                {
+                    let event = AgentEvent::CodeExecution(block.clone());
+                    recorder.emit(metrics.iterations, &event).await;
+                }
+            }
+
+            request_builder.load_discovered_tools(&response.content);
+
             self.state
                 .with_session_mut(|session| {
                     session.add_assistant_message(response.content.clone(), Some(response.usage));
                 })
                 .await;
 
+            if final_stop_reason == StopReason::PauseTurn {
+                debug!("Server tool turn paused, resending to continue");
+                continue;
+            }
+
             if !response.wants_tool_use() {
+                if apply_interjections(&self.state).await {
+                    debug!("Continuing with interjected input");
+                    continue;
+                }
                 debug!("No tool use requested, ending loop");
                 break;
             }
@@ -244,15 +451,14 @@ impl Agent {
             let mut blocked = Vec::with_capacity(tool_uses.len());
 
             for tool_use in &tool_uses {
-                let pre_input = HookInput::pre_tool_use(
-                    &*self.session_id,
-                    &tool_use.name,
-                    tool_use.input.clone(),
-                );
-                let pre_output = self
-                    .hooks
-                    .execute(HookEvent::PreToolUse, pre_input, &hook_ctx)
-                    .await?;
+                let pre_output = common::run_pre_tool_use(
+                    &self.hooks,
+                    &self.state,
+                    &self.session_id,
+                    tool_use,
+                    &hook_ctx,
+                )
+                .await?;
 
                 if !pre_output.continue_execution {
                     debug!(tool = %tool_use.name, "Tool blocked by hook");
@@ -263,50 +469,138 @@ impl Agent {
                     blocked.push(ToolResultBlock::error(&tool_use.id, reason.clone()));
                     metrics.record_permission_denial(
                         PermissionDenial::new(&tool_use.name, &tool_use.id, tool_use.input.clone())
-                            .reason(reason),
+                            .reason(reason.clone()),
                     );
+                    let event = AgentEvent::ToolBlocked {
+                        id: tool_use.id.clone(),
+                        name: tool_use.name.clone(),
+                        reason,
+                    };
+                    recorder.emit(metrics.iterations, &event).await;
                 } else {
                     let input = pre_output.updated_input.unwrap_or(tool_use.input.clone());
-                    prepared.push((tool_use.id.clone(), tool_use.name.clone(), input));
+                    prepared.push(PendingToolCall {
+                        id: tool_use.id.clone(),
+                        name: tool_use.name.clone(),
+                        input,
+                    });
                 }
             }
 
-            let tool_futures = prepared.into_iter().map(|(id, name, input)| {
-                let tools = &self.tools;
-                async move {
-                    let start = Instant::now();
-                    let result = tools.execute(&name, input.clone()).await;
-                    let duration_ms = start.elapsed().as_millis() as u64;
-                    (id, name, input, result, duration_ms)
+            for event in deprecation_events(&self.tools, &prepared) {
+                recorder.emit(metrics.iterations, &event).await;
+            }
+
+            if let Some(policy) = &self.config.execution.tool_preview
+                && !self.config.execution.dry_run
+            {
+                if let Some(message) = approval_message(&prepared, policy, &self.tools) {
+                    run_notification_hooks(
+                        &self.hooks,
+                        &hook_ctx,
+                        &self.session_id,
+                        NotificationKind::ApprovalNeeded,
+                        message,
+                    )
+                    .await;
                 }
-            });
+                let reviewed = review_tool_calls(&mut prepared, policy, &self.tools).await;
+                for call in reviewed {
+                    if let Some(reason) = &call.rejection {
+                        debug!(tool = %call.preview.name, %reason, "Previewed change rejected");
+                        blocked.push(call.result_block(reason));
+                        metrics.record_permission_denial(call.denial(reason));
+                    }
+                    let rejected = call.rejection.map(|reason| AgentEvent::ToolBlocked {
+                        id: call.preview.tool_use_id.clone(),
+                        name: call.preview.name.clone(),
+                        reason,
+                    });
+                    recorder
+                        .emit(metrics.iterations, &AgentEvent::ToolPreview(call.preview))
+                        .await;
+                    if let Some(event) = rejected {
+                        recorder.emit(metrics.iterations, &event).await;
+                    }
+                }
+            }
 
-            let parallel_results: Vec<_> = futures::future::join_all(tool_futures).await;
+            if self.config.execution.dry_run {
+                let planned = plan_tool_calls(
+                    std::mem::take(&mut prepared),
+                    self.tools.get_context(),
+                    &mut metrics,
+                )
+                .await;
+                blocked.extend(planned.iter().map(PlannedToolCall::result_block));
+                for call in planned {
+                    recorder
+                        .emit(metrics.iterations, &AgentEvent::ToolPlanned(call))
+                        .await;
+                }
+            }
 
-            let all_non_retryable = !parallel_results.is_empty()
-                && parallel_results
-                    .iter()
-                    .all(|(_, _, _, result, _)| result.is_non_retryable());
+            let mut completed = Vec::with_capacity(prepared.len());
+            while !prepared.is_empty() {
+                let batch_end = tool_batch_end(
+                    prepared.iter().map(|c| self.tools.canonical_name(&c.name)),
+                    0,
+                );
+                let rest = prepared.split_off(batch_end);
+                let batch = std::mem::replace(&mut prepared, rest);
+                let idle_message = idle_message(&batch, &self.config.execution);
+                let batch = execute_tool_batch(&self.tools, batch, &self.config.execution);
+                let batch = notify_when_idle(
+                    self.config.execution.idle_notification,
+                    batch,
+                    run_notification_hooks(
+                        &self.hooks,
+                        &hook_ctx,
+                        &self.session_id,
+                        NotificationKind::Idle,
+                        idle_message,
+                    ),
+                );
+                completed.extend(
+                    with_mcp_events(
+                        &mut mcp_notifications,
+                        &recorder,
+                        &self.config.execution,
+                        metrics.iterations,
+                        batch,
+                    )
+                    .await,
+                );
+            }
+
+            let all_non_retryable =
+                !completed.is_empty() && completed.iter().all(|c| c.result.is_non_retryable());
 
             let mut results = blocked;
-            for (id, name, input, result, duration_ms) in parallel_results {
-                let is_error = result.is_error();
-                debug!(tool = %name, duration_ms, is_error, "Tool execution completed");
-                metrics.record_tool(&id, &name, duration_ms, is_error);
+            for call in completed {
+                let is_error = call.result.is_error();
+                debug!(
+                    tool = %call.name,
+                    duration_ms = call.duration_ms,
+                    is_error,
+                    "Tool execution completed"
+                );
+                metrics.record_tool(&call.id, &call.name, call.duration_ms, is_error);
+                call.record_metrics(self.metrics_registry.as_deref());
 
                 accumulate_inner_usage(
                     &self.state,
                     &mut total_usage,
                     &mut metrics,
                     &self.budget_tracker,
-                    &result,
-                    &name,
+                    &call.result,
+                    &call.name,
                 )
                 .await;
 
                 try_activate_dynamic_rules(
-                    &name,
-                    &input,
+                    &call.name,
+                    &call.input,
                     &self.orchestrator,
                     &mut dynamic_rules_context,
                 )
@@ -316,13 +610,19 @@ impl Agent {
                     &self.hooks,
                     &hook_ctx,
                     &self.session_id,
-                    &name,
+                    &call.name,
                     is_error,
-                    &result,
+                    &call.result,
                 )
                 .await;
 
-                results.push(ToolResultBlock::from_tool_result(&id, &result));
+                results.push(ToolResultBlock::from_tool_result(&call.id, &call.result));
+                if recorder.is_enabled() {
+                    for event in call.violation_events() {
+                        recorder.emit(metrics.iterations, &event).await;
+                    }
+                    recorder.emit(metrics.iterations, &call.into_event()).await;
+                }
             }
 
             self.state
@@ -330,8 +630,9 @@ impl Agent {
                     session.add_tool_results(results);
                 })
                 .await;
+            let interjected = apply_interjections(&self.state).await;
 
-            if all_non_retryable {
+            if all_non_retryable && !interjected {
                 warn!("All tool calls failed with non-retryable errors, ending execution");
                 break;
             }
@@ -348,9 +649,23 @@ impl Agent {
                 &mut metrics,
             )
             .await;
+
+            if recorder.is_enabled() {
+                let event = progress_event(
+                    &self.state,
+                    &metrics,
+                    self.config.execution.max_iterations,
+                    execution_start,
+                )
+                .await;
+                recorder.emit(metrics.iterations, &event).await;
+            }
+            for event in mcp_events(&mut mcp_notifications, &self.config.execution) {
+                recorder.emit(metrics.iterations, &event).await;
+            }
         }
 
-        metrics.execution_time_ms = execution_start.elapsed().as_millis() as u64;
+        metrics.execution_time_ms = clock::elapsed(execution_start).as_millis() as u64;
 
         run_stop_hooks(&self.hooks, &hook_ctx, &self.session_id).await;
 
@@ -358,6 +673,7 @@ impl Agent {
             iterations = metrics.iterations,
             tool_calls = metrics.tool_calls,
             api_calls = metrics.api_calls,
+            cache_hits = metrics.cache_hits,
             total_tokens = metrics.total_tokens(),
             execution_time_ms = metrics.execution_time_ms,
             "Agent execution completed"
@@ -368,8 +684,8 @@ impl Agent {
             .with_session(|session| session.to_api_messages())
             .await;
 
-        let structured_output = self.extract_structured_output(&final_text);
-        Ok(AgentResult::new(
+        let structured_output = common::extract_structured_output(output_schema, &final_text);
+        let mut result = AgentResult::new(
             final_text,
             total_usage,
             metrics.iterations,
@@ -378,17 +694,24 @@ impl Agent {
             self.session_id.to_string(),
             structured_output,
             messages,
-        ))
+        );
+        if budget_exceeded {
+            result.state = AgentState::BudgetExceeded;
+        }
+        persist_session(&self.state, self.session_manager.as_ref()).await;
+        if recorder.is_enabled() {
+            let event = AgentEvent::Complete(Box::new(result.clone()));
+            recorder.emit(result.iterations, &event).await;
+        }
+        Ok(result)
     }
 
     pub(crate) fn hook_context(&self) -> HookContext {
         HookContext::new(&*self.session_id)
+            .cancellation_token(self.cancel_token.clone())
             .cwd(self.config.working_dir.clone().unwrap_or_default())
             .env(self.config.security.env.clone())
-    }
-
-    fn extract_structured_output(&self, text: &str) -> Option<serde_json::Value> {
-        common::extract_structured_output(self.config.prompt.output_schema.as_ref(), text)
+            .env_policy(self.config.security.env_policy.clone().unwrap_or_default())
     }
 }
 
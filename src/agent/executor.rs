@@ -3,14 +3,17 @@
 use std::sync::Arc;
 
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 
 use super::config::AgentConfig;
+use super::event_sink::EventSink;
 use crate::Client;
 use crate::budget::{BudgetTracker, TenantBudget};
 use crate::context::PromptOrchestrator;
 use crate::hooks::HookManager;
-use crate::session::ToolState;
-use crate::tools::{ToolRegistry, ToolSearchManager};
+use crate::observability::MetricsRegistry;
+use crate::session::{MessageId, SessionManager, ToolState};
+use crate::tools::{ToolRegistry, ToolRegistryBuilder, ToolSearchManager};
 use crate::types::Message;
 
 pub struct Agent {
@@ -26,6 +29,10 @@ pub struct Agent {
     pub(crate) tenant_budget: Option<Arc<TenantBudget>>,
     pub(crate) mcp_manager: Option<Arc<crate::mcp::McpManager>>,
     pub(crate) tool_search_manager: Option<Arc<ToolSearchManager>>,
+    pub(crate) cancel_token: CancellationToken,
+    pub(crate) event_sinks: Arc<[Arc<dyn EventSink>]>,
+    pub(crate) metrics_registry: Option<Arc<MetricsRegistry>>,
+    pub(crate) session_manager: Option<SessionManager>,
 }
 
 impl Agent {
@@ -40,11 +47,25 @@ impl Agent {
             config.model.small = resolved_small.to_string();
         }
 
-        let tools = ToolRegistry::default_tools(
-            config.security.tool_access.clone(),
-            config.working_dir.clone(),
-            Some(config.security.permission_policy.clone()),
-        );
+        let mut builder = ToolRegistryBuilder::new()
+            .access(config.security.tool_access.clone())
+            .policy(config.security.permission_policy.clone());
+        if let Some(dir) = config.working_dir.clone() {
+            builder = builder.working_dir(dir);
+        }
+        if config.security.read_only {
+            builder = builder.read_only();
+        }
+        if let Some(limits) = config.security.file_limits.clone() {
+            builder = builder.file_limits(limits);
+        }
+        if let Some(policy) = config.security.env_policy.clone() {
+            builder = builder.env_policy(policy);
+        }
+        if let Some(policy) = config.security.bash_policy.clone() {
+            builder = builder.bash_policy(policy);
+        }
+        let tools = builder.build();
         Self::from_parts(
             Arc::new(client),
             Arc::new(config),
@@ -101,6 +122,10 @@ impl Agent {
             tenant_budget: None,
             mcp_manager: None,
             tool_search_manager: None,
+            cancel_token: CancellationToken::new(),
+            event_sinks: Arc::from(Vec::new()),
+            metrics_registry: None,
+            session_manager: None,
         }
     }
 
@@ -119,6 +144,26 @@ impl Agent {
         self
     }
 
+    pub(crate) fn cancel_token(mut self, token: CancellationToken) -> Self {
+        self.cancel_token = token;
+        self
+    }
+
+    pub(crate) fn event_sinks(mut self, sinks: Vec<Arc<dyn EventSink>>) -> Self {
+        self.event_sinks = sinks.into();
+        self
+    }
+
+    pub(crate) fn metrics_registry(mut self, registry: Arc<MetricsRegistry>) -> Self {
+        self.metrics_registry = Some(registry);
+        self
+    }
+
+    pub(crate) fn session_manager(mut self, manager: SessionManager) -> Self {
+        self.session_manager = Some(manager);
+        self
+    }
+
     pub(crate) fn initial_messages(mut self, messages: Vec<Message>) -> Self {
         self.initial_messages = Some(messages);
         self
@@ -139,6 +184,17 @@ impl Agent {
         super::AgentBuilder::new().model(model)
     }
 
+    /// Builder configured by a named preset.
+    ///
+    /// Looks the name up among the built-in presets and the preset files in
+    /// `~/.claude/presets` and `./.claude/presets` (see [`PresetRegistry`](super::PresetRegistry)).
+    pub async fn from_preset(name: &str) -> crate::Result<super::AgentBuilder> {
+        let cwd = std::env::current_dir().ok();
+        super::PresetRegistry::load_default(cwd.as_deref())
+            .await?
+            .builder(name)
+    }
+
     pub async fn default_agent() -> crate::Result<Self> {
         Self::builder().build().await
     }
@@ -172,8 +228,82 @@ impl Agent {
         &self.tools
     }
 
+    /// Undo this session's file changes; requires
+    /// [`AgentBuilder::backup_writes`](super::AgentBuilder::backup_writes).
+    ///
+    /// Returns the restored paths. Files the session created are deleted.
+    pub async fn rollback_writes(&self) -> crate::Result<Vec<std::path::PathBuf>> {
+        self.tools
+            .rollback_writes(&self.state.session_id().to_string())
+            .await
+    }
+
     #[must_use]
     pub fn state(&self) -> &ToolState {
         &self.state
     }
+
+    /// Branch the conversation at `message_id` and return an agent continuing from there.
+    ///
+    /// The new agent runs in a child session (see [`Session::fork_at`]) and shares
+    /// this agent's client, configuration, hooks, tools and budget. This agent's
+    /// session is left untouched. With a session manager configured, the child
+    /// session is saved before it is returned.
+    ///
+    /// [`Session::fork_at`]: crate::session::Session::fork_at
+    pub async fn fork_at(&self, message_id: &MessageId) -> crate::Result<Agent> {
+        let session = self.state.session().await;
+        let forked = session.fork_at(message_id).ok_or_else(|| {
+            crate::Error::Session(format!(
+                "Message {} not found in session {}",
+                message_id, session.id
+            ))
+        })?;
+
+        if let Some(ref manager) = self.session_manager {
+            manager.update(&forked).await?;
+        }
+
+        let session_id: Arc<str> = forked.id.to_string().into();
+        let state = ToolState::from_session(forked);
+        let tools = Arc::new(self.tools.with_tool_state(state.clone()));
+
+        Ok(Self {
+            client: Arc::clone(&self.client),
+            config: Arc::clone(&self.config),
+            tools,
+            hooks: Arc::clone(&self.hooks),
+            session_id,
+            state,
+            orchestrator: self.orchestrator.clone(),
+            initial_messages: None,
+            budget_tracker: Arc::clone(&self.budget_tracker),
+            tenant_budget: self.tenant_budget.clone(),
+            mcp_manager: self.mcp_manager.clone(),
+            tool_search_manager: self.tool_search_manager.clone(),
+            cancel_token: CancellationToken::new(),
+            event_sinks: Arc::clone(&self.event_sinks),
+            metrics_registry: self.metrics_registry.clone(),
+            session_manager: self.session_manager.clone(),
+        })
+    }
+
+    /// Token that cancels this agent's executions when triggered.
+    ///
+    /// Cancellation is permanent: once cancelled, further executions fail
+    /// immediately with [`crate::Error::Cancelled`].
+    #[must_use]
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancel_token.clone()
+    }
+
+    /// Cancel any in-flight execution.
+    pub fn cancel(&self) {
+        self.cancel_token.cancel();
+    }
+
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_token.is_cancelled()
+    }
 }
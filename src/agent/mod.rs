@@ -2,10 +2,15 @@
 
 mod common;
 mod config;
+mod event_sink;
 mod events;
 mod execution;
 mod executor;
 mod options;
+mod pipe;
+mod plan;
+mod preset;
+mod preview;
 mod request;
 mod state;
 mod state_formatter;
@@ -13,18 +18,30 @@ mod streaming;
 mod task;
 mod task_output;
 mod task_registry;
+mod warm_up;
 
 #[cfg(test)]
-mod tests;
+pub(crate) mod tests;
 
 pub use config::{
-    AgentConfig, AgentModelConfig, BudgetConfig, CacheConfig, CacheStrategy, ExecutionConfig,
-    PromptConfig, SecurityConfig, SystemPromptMode,
+    AgentConfig, AgentModelConfig, BudgetConfig, CacheConfig, CacheStrategy,
+    DEFAULT_MAX_PARALLEL_TOOLS, ExecutionConfig, PromptConfig, SecurityConfig, ServerToolsConfig,
+    SystemPromptMode, ToolRetryPolicy,
 };
+pub use event_sink::{ChannelEventSink, EventRecord, EventSink, JsonlEventSink};
 pub use events::{AgentEvent, AgentResult};
+pub use execution::MAX_STRUCTURED_OUTPUT_REPAIRS;
 pub use executor::Agent;
 pub use options::{AgentBuilder, DEFAULT_COMPACT_KEEP_MESSAGES};
+pub use pipe::{DEFAULT_PIPE_CONCURRENCY, PipeInput, PipeJob, PipeRecord, PipeRunner, PipeSummary};
+pub use plan::PlannedToolCall;
+pub use preset::{
+    AgentPreset, PRESETS_DIR, PresetRegistry, builtin_presets, code_reviewer_preset,
+    docs_writer_preset, sre_triage_preset,
+};
+pub use preview::{PREVIEW_TOOLS, PreviewApprover, PreviewDecision, PreviewPolicy, ToolPreview};
 pub use state::{AgentMetrics, AgentState, ToolCallRecord, ToolStats};
 pub use task::{TaskInput, TaskOutput, TaskTool};
 pub use task_output::{TaskOutputInput, TaskOutputResult, TaskOutputTool, TaskStatus};
 pub use task_registry::TaskRegistry;
+pub use warm_up::WarmUp;
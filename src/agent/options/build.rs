@@ -22,11 +22,12 @@ impl AgentBuilder {
 
         self.resolve_output_style().await?;
         self.resolve_model_aliases();
+        self.load_resumed_session().await?;
         self.connect_mcp_servers().await?;
         self.initialize_tool_search().await;
 
         let client = self.build_client().await?;
-        let tools = self.build_tools().await;
+        let tools = self.build_tools(&client).await?;
         let orchestrator = self.build_orchestrator().await;
 
         let tenant_budget = self.tenant_budget_manager.as_ref().and_then(|m| {
@@ -60,10 +61,39 @@ impl AgentBuilder {
         if let Some(tsm) = self.tool_search_manager {
             agent = agent.tool_search_manager(tsm);
         }
+        if let Some(token) = self.cancellation_token {
+            agent = agent.cancel_token(token);
+        }
+        if let Some(manager) = self.session_manager {
+            agent = agent.session_manager(manager);
+        }
+        if !self.event_sinks.is_empty() {
+            agent = agent.event_sinks(self.event_sinks);
+        }
+        if let Some(registry) = self.metrics_registry {
+            agent = agent.metrics_registry(registry);
+        }
 
         Ok(agent)
     }
 
+    async fn load_resumed_session(&mut self) -> crate::Result<()> {
+        if self.resumed_session.is_some() {
+            return Ok(());
+        }
+        let Some(ref id) = self.resume_session_id else {
+            return Ok(());
+        };
+
+        let manager = self.session_manager.get_or_insert_with(Default::default);
+        let session = manager
+            .get(&crate::session::SessionId::from(id.as_str()))
+            .await?;
+        manager.authorize(&session, crate::session::SessionAccess::Execute)?;
+        self.resumed_session = Some(session);
+        Ok(())
+    }
+
     #[cfg(feature = "cli-integration")]
     async fn resolve_output_style(&mut self) -> crate::Result<()> {
         use crate::common::{Provider, SourceType};
@@ -87,6 +117,13 @@ impl AgentBuilder {
 
         let mut chain = ChainOutputStyleProvider::new().provider(builtins);
 
+        if !self.output_styles.is_empty() {
+            let loaded = InMemoryOutputStyleProvider::new()
+                .items(self.output_styles.clone())
+                .priority(30);
+            chain = chain.provider(loaded);
+        }
+
         if let Some(ref working_dir) = self.config.working_dir {
             let project = file_output_style_provider()
                 .project_path(working_dir)
@@ -150,10 +187,25 @@ impl AgentBuilder {
             return Ok(());
         }
 
-        let manager = self
-            .mcp_manager
-            .take()
-            .unwrap_or_else(|| std::sync::Arc::new(crate::mcp::McpManager::new()));
+        let manager = self.mcp_manager.take().unwrap_or_else(|| {
+            let manager = crate::mcp::McpManager::new();
+            #[cfg(feature = "mcp")]
+            let manager =
+                manager.env_policy(self.config.security.env_policy.clone().unwrap_or_default());
+            std::sync::Arc::new(manager)
+        });
+
+        // Scope filesystem servers to the project unless roots were set explicitly
+        if manager.roots().await.is_empty() {
+            let working_dir = self
+                .config
+                .working_dir
+                .clone()
+                .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+            manager
+                .set_roots(vec![crate::mcp::McpRoot::from_path(working_dir)])
+                .await;
+        }
 
         for (name, config) in std::mem::take(&mut self.mcp_configs) {
             manager.add_server(&name, config).await.map_err(|e| {
@@ -185,7 +237,7 @@ impl AgentBuilder {
         };
 
         // Set toolset registry if available
-        if let Some(registry) = self.mcp_toolset_registry.take() {
+        if let Some(registry) = self.mcp_toolset_registry.clone() {
             manager.set_toolset_registry(registry);
         }
 
@@ -231,6 +283,9 @@ impl AgentBuilder {
         if self.load_local {
             self.load_local_resources().await;
         }
+
+        // 5. Explicit Claude directories, in the order they were added
+        self.load_claude_dirs().await;
     }
 
     #[cfg(not(feature = "cli-integration"))]
@@ -349,7 +404,7 @@ impl AgentBuilder {
             .skill_registry(skill_registry)
     }
 
-    async fn build_tools(&mut self) -> Arc<ToolRegistry> {
+    async fn build_tools(&mut self, client: &crate::Client) -> crate::Result<Arc<ToolRegistry>> {
         let skill_registry = self.skill_registry.take().unwrap_or_default();
         let skill_count = skill_registry.iter().count();
         tracing::debug!(skill_count, "build_tools: skill_registry taken");
@@ -383,6 +438,7 @@ impl AgentBuilder {
             .working_dir(working_dir)
             .skill_executor(skill_executor)
             .policy(self.config.security.permission_policy.clone())
+            .allowed_paths(self.config.security.additional_directories.clone())
             .tool_state(tool_state)
             .session_id(session_id);
 
@@ -392,21 +448,68 @@ impl AgentBuilder {
         if let Some(sc) = sandbox_config {
             builder = builder.sandbox_config(sc);
         }
+        if self.config.execution.upload_large_files {
+            builder = builder.files_client(Arc::new(client.clone()));
+        }
+        if let Some(excludes) = self.config.execution.search_excludes.clone() {
+            builder = builder.search_excludes(excludes);
+        }
+        if let Some(log) = self.config.security.audit_log.clone() {
+            builder = builder.audit_log(log);
+        }
+        if self.config.security.read_only {
+            builder = builder.read_only();
+        }
+        if let Some(limits) = self.config.security.file_limits.clone() {
+            builder = builder.file_limits(limits);
+        }
+        if let Some(policy) = self.config.security.env_policy.clone() {
+            builder = builder.env_policy(policy);
+        }
+        if let Some(policy) = self.config.security.bash_policy.clone() {
+            builder = builder.bash_policy(policy);
+        }
 
         let mut tools = builder.build();
 
         for tool in std::mem::take(&mut self.custom_tools) {
             tools.register(tool);
         }
+        for tool in std::mem::take(&mut self.tool_versions) {
+            tools.register_version(tool)?;
+        }
+        for (alias, target) in std::mem::take(&mut self.tool_aliases) {
+            tools.alias(alias, target);
+        }
+        for middleware in std::mem::take(&mut self.tool_middleware) {
+            tools.add_middleware(middleware);
+        }
+        if let Some(handler) = self.permission_handler.take() {
+            tools.set_permission_handler(handler);
+        }
+        for (tool, limits) in &self.config.execution.tool_limits {
+            tools.set_limits(tool.clone(), limits.clone());
+        }
+        if let Some(config) = &self.config.execution.tool_cache {
+            tools.enable_cache(config.clone());
+        }
+        if let Some(quotas) = &self.config.execution.tool_quotas {
+            tools.set_quotas(quotas.clone());
+        }
+        if self.config.execution.backup_writes {
+            tools.enable_backups();
+        }
 
         if let Some(ref mcp_manager) = self.mcp_manager {
-            let mcp_tools = crate::tools::create_mcp_tools(Arc::clone(mcp_manager)).await;
+            let toolsets = self.mcp_toolset_registry.take().unwrap_or_default();
+            let mcp_tools =
+                crate::tools::create_filtered_mcp_tools(Arc::clone(mcp_manager), &toolsets).await;
             for tool in mcp_tools {
                 tools.register(tool);
             }
         }
 
-        Arc::new(tools)
+        Ok(Arc::new(tools))
     }
 
     async fn build_client(&mut self) -> crate::Result<crate::Client> {
@@ -440,6 +543,12 @@ impl AgentBuilder {
             tracing::debug!("Enabled extended context window (1M tokens)");
         }
 
+        // Tool results may reference files uploaded by the Read tool
+        if self.config.execution.upload_large_files {
+            config.beta.add(crate::client::BetaFeature::FilesApi);
+            tracing::debug!("Enabled Files API for large tool attachments");
+        }
+
         // Enable structured outputs beta if output_schema is configured
         if self.config.prompt.output_schema.is_some() {
             config
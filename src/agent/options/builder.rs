@@ -34,14 +34,14 @@ use crate::budget::TenantBudgetManager;
 use crate::client::{CloudProvider, FallbackConfig, ModelConfig, ProviderConfig};
 use crate::common::IndexRegistry;
 use crate::context::{LeveledMemoryProvider, RuleIndex};
-use crate::hooks::{Hook, HookManager};
+use crate::hooks::{Hook, HookManager, HookRateLimit};
 use crate::output_style::OutputStyle;
-use crate::permissions::{PermissionMode, PermissionPolicy, PermissionRule};
+use crate::permissions::{PermissionHandler, PermissionMode, PermissionPolicy, PermissionRule};
 use crate::skills::SkillIndex;
 use crate::subagents::{SubagentIndex, builtin_subagents};
-use crate::tools::{Tool, ToolAccess};
+use crate::tools::{Tool, ToolAccess, ToolMiddleware};
 
-use crate::agent::config::{AgentConfig, CacheConfig, SystemPromptMode};
+use crate::agent::config::{AgentConfig, CacheConfig, SystemPromptMode, ToolRetryPolicy};
 
 /// Default number of messages to preserve during context compaction.
 pub const DEFAULT_COMPACT_KEEP_MESSAGES: usize = 4;
@@ -63,6 +63,10 @@ pub struct AgentBuilder {
     pub(super) rule_indices: Vec<RuleIndex>,
     pub(super) hooks: HookManager,
     pub(super) custom_tools: Vec<Arc<dyn Tool>>,
+    pub(super) tool_versions: Vec<Arc<dyn Tool>>,
+    pub(super) tool_aliases: Vec<(String, String)>,
+    pub(super) tool_middleware: Vec<Arc<dyn ToolMiddleware>>,
+    pub(super) permission_handler: Option<Arc<dyn PermissionHandler>>,
     pub(super) memory_provider: Option<LeveledMemoryProvider>,
     pub(super) sandbox_settings: Option<crate::config::SandboxSettings>,
     pub(super) initial_messages: Option<Vec<crate::types::Message>>,
@@ -77,6 +81,9 @@ pub struct AgentBuilder {
     pub(super) tool_search_config: Option<crate::tools::ToolSearchConfig>,
     pub(super) tool_search_manager: Option<std::sync::Arc<crate::tools::ToolSearchManager>>,
     pub(super) session_manager: Option<crate::session::SessionManager>,
+    pub(super) cancellation_token: Option<tokio_util::sync::CancellationToken>,
+    pub(super) event_sinks: Vec<Arc<dyn crate::agent::EventSink>>,
+    pub(super) metrics_registry: Option<Arc<crate::observability::MetricsRegistry>>,
 
     // Resource level flags - loaded in fixed order during build()
     // Order: Enterprise → User → Project → Local (later overrides earlier)
@@ -84,6 +91,10 @@ pub struct AgentBuilder {
     pub(super) load_user: bool,
     pub(super) load_project: bool,
     pub(super) load_local: bool,
+    #[cfg(feature = "cli-integration")]
+    pub(super) claude_dirs: Vec<crate::config::ClaudeDir>,
+    #[cfg(feature = "cli-integration")]
+    pub(super) output_styles: Vec<OutputStyle>,
 
     #[cfg(feature = "aws")]
     pub(super) aws_region: Option<String>,
@@ -181,7 +192,11 @@ impl AgentBuilder {
         self.auth_type = Some(auth);
 
         if self.supports_server_tools() {
-            self.config.server_tools = crate::agent::config::ServerToolsConfig::all();
+            let code_execution = self.config.server_tools.code_execution.take();
+            self.config.server_tools = crate::agent::config::ServerToolsConfig {
+                code_execution,
+                ..crate::agent::config::ServerToolsConfig::all()
+            };
         }
 
         Ok(self)
@@ -292,6 +307,41 @@ impl AgentBuilder {
         self
     }
 
+    /// Registers one version of a custom tool, keeping other versions callable.
+    ///
+    /// The newest version is offered to the model; older ones stay reachable
+    /// as `name@version` through [`tool_alias`](Self::tool_alias). See
+    /// [`ToolRegistry::register_version`](crate::tools::ToolRegistry::register_version).
+    pub fn tool_version<T: Tool + 'static>(mut self, tool: T) -> Self {
+        self.tool_versions.push(Arc::new(tool));
+        self
+    }
+
+    /// Resolves calls to `alias`, such as a tool's former name still present
+    /// in saved sessions, to `target`. Such calls emit
+    /// `AgentEvent::ToolDeprecated`.
+    pub fn tool_alias(mut self, alias: impl Into<String>, target: impl Into<String>) -> Self {
+        self.tool_aliases.push((alias.into(), target.into()));
+        self
+    }
+
+    /// Wraps every tool execution, built-in and custom, in `middleware`.
+    ///
+    /// Middleware runs in the order added; see [`ToolMiddleware`].
+    pub fn tool_middleware<M: ToolMiddleware + 'static>(mut self, middleware: M) -> Self {
+        self.tool_middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Enables Anthropic's server-side code execution tool.
+    ///
+    /// Code runs in Anthropic's sandboxed container, not locally; results are
+    /// reported as [`AgentEvent::CodeExecution`](crate::agent::AgentEvent::CodeExecution).
+    pub fn code_execution(mut self, tool: crate::types::CodeExecutionTool) -> Self {
+        self.config.server_tools.code_execution = Some(tool);
+        self
+    }
+
     // =========================================================================
     // Execution
     // =========================================================================
@@ -348,6 +398,268 @@ impl AgentBuilder {
         self
     }
 
+    /// Sets how auto-compaction reduces the context.
+    ///
+    /// The strategy supplies the summarization prompt, model and "must
+    /// preserve" hints. With
+    /// [`CompactStrategy::prune_tool_results`](crate::session::CompactStrategy::prune_tool_results),
+    /// old tool results are replaced with short digests first and the
+    /// conversation is only summarized if that does not bring it back under
+    /// the threshold.
+    ///
+    /// Default: none; the conversation is summarized with a short built-in
+    /// prompt on the small model
+    pub fn compact_strategy(mut self, strategy: crate::session::CompactStrategy) -> Self {
+        self.config.execution.compact_strategy = Some(strategy);
+        self
+    }
+
+    /// Sets how many tool calls from a single turn may execute concurrently.
+    ///
+    /// File-mutating tools ([`WRITE_TOOLS`](crate::permissions::WRITE_TOOLS))
+    /// always run on their own, in order.
+    ///
+    /// Default: `10`
+    pub fn max_parallel_tools(mut self, max: usize) -> Self {
+        self.config.execution.max_parallel_tools = max.max(1);
+        self
+    }
+
+    /// Records proposed tool calls instead of executing them.
+    ///
+    /// Each call is returned in [`AgentResult::planned_tool_calls`](crate::AgentResult::planned_tool_calls),
+    /// with a unified diff for `Write` and `Edit`. PreToolUse hooks still run.
+    ///
+    /// Default: `false`
+    pub fn dry_run(mut self, enabled: bool) -> Self {
+        self.config.execution.dry_run = enabled;
+        self
+    }
+
+    /// Previews `Write`, `Edit` and `MultiEdit` calls as unified diffs before
+    /// they touch disk.
+    ///
+    /// Each diff is emitted as [`AgentEvent::ToolPreview`](crate::AgentEvent::ToolPreview);
+    /// `policy` decides whether the change is applied. Rejected changes are
+    /// reported as `ToolBlocked` and the model is told they were not applied.
+    /// Ignored in dry-run mode.
+    pub fn tool_preview(mut self, policy: crate::agent::PreviewPolicy) -> Self {
+        self.config.execution.tool_preview = Some(policy);
+        self
+    }
+
+    /// Emits extended thinking as [`AgentEvent::Thinking`](crate::AgentEvent::Thinking)
+    /// events, separate from `Text`.
+    ///
+    /// Disable to keep reasoning out of streamed output and event sinks.
+    ///
+    /// Default: `true`
+    pub fn emit_thinking(mut self, enabled: bool) -> Self {
+        self.config.execution.emit_thinking = enabled;
+        self
+    }
+
+    /// Redacts API keys, tokens and private keys from tool results before
+    /// they reach the conversation, hooks and events, and from streamed text
+    /// before it is emitted.
+    ///
+    /// With redaction on, streamed [`AgentEvent::Text`](crate::AgentEvent::Text)
+    /// arrives a line at a time so secrets split across deltas are caught.
+    ///
+    /// ```rust,no_run
+    /// # use claude_agent::{Agent, security::SecretRedactor};
+    /// let builder = Agent::builder().redact_secrets(
+    ///     SecretRedactor::new().pattern("employee_id", regex::Regex::new(r"EMP-\d{6}").unwrap()),
+    /// );
+    /// ```
+    pub fn redact_secrets(mut self, redactor: crate::security::SecretRedactor) -> Self {
+        self.config.execution.secret_redaction = Some(redactor);
+        self
+    }
+
+    /// Replaces emails, phone numbers, card numbers and the scrubber's custom
+    /// patterns with tokens such as `[EMAIL_1]` in every request sent to the
+    /// API, and maps the tokens back in responses, so text, events and tool
+    /// inputs carry the real values.
+    ///
+    /// The session keeps the real values; wrap its persistence in
+    /// [`ScrubbedPersistence`](crate::session::ScrubbedPersistence) with a
+    /// clone of `scrubber` to keep them out of storage too.
+    pub fn scrub_pii(mut self, scrubber: crate::security::PiiScrubber) -> Self {
+        self.config.security.pii_scrubber = Some(scrubber);
+        self
+    }
+
+    /// Records tool calls blocked by the security guard (sandbox escapes,
+    /// denied paths, blocked bash commands, symlink escapes) to `log`, tagged
+    /// with the session. Keep a clone to [`query`](crate::security::AuditLog::query) it.
+    pub fn audit_log(mut self, log: crate::security::AuditLog) -> Self {
+        self.config.security.audit_log = Some(log);
+        self
+    }
+
+    /// Limits how large a file Read loads and Write produces, how many bytes
+    /// and how long lines one Read returns, and whether binary files are refused.
+    pub fn file_limits(mut self, limits: crate::security::FileLimits) -> Self {
+        self.config.security.file_limits = Some(limits);
+        self
+    }
+
+    /// Controls which environment variables Bash commands, hooks and MCP
+    /// stdio servers inherit: extra allowlisted names, forced removals such
+    /// as `AWS_*`, and injected values.
+    pub fn env_policy(mut self, policy: crate::security::bash::EnvPolicy) -> Self {
+        self.config.security.env_policy = Some(policy);
+        self
+    }
+
+    /// Restricts which commands Bash runs, with which arguments, and which
+    /// shell features they may use. See [`BashPolicy::from_file`](crate::security::bash::BashPolicy::from_file)
+    /// to load one from JSON or YAML.
+    pub fn bash_policy(mut self, policy: crate::security::bash::BashPolicy) -> Self {
+        self.config.security.bash_policy = Some(policy);
+        self
+    }
+
+    /// Enforces read-only mode below the permission rules: every file write
+    /// fails, Bash runs only [`READ_ONLY_COMMANDS`](crate::security::bash::READ_ONLY_COMMANDS)
+    /// and Write, Edit and the other write tools are not registered.
+    pub fn read_only(mut self) -> Self {
+        self.config.security.read_only = true;
+        self
+    }
+
+    /// Emits MCP server log messages as [`AgentEvent::McpLog`](crate::AgentEvent::McpLog)
+    /// events. They are written to `tracing` either way.
+    ///
+    /// Default: `false`
+    pub fn emit_mcp_logs(mut self, enabled: bool) -> Self {
+        self.config.execution.emit_mcp_logs = enabled;
+        self
+    }
+
+    /// Sends a [`HookEvent::Notification`](crate::hooks::HookEvent::Notification)
+    /// when a batch of tool calls is still running after `after`. `None`
+    /// disables it.
+    ///
+    /// Default: 60 seconds
+    pub fn idle_notification(mut self, after: Option<Duration>) -> Self {
+        self.config.execution.idle_notification = after;
+        self
+    }
+
+    /// Retries failed calls of `tool` inside the SDK instead of handing the
+    /// error back to the model.
+    ///
+    /// ```rust,no_run
+    /// # use claude_agent::{Agent, ToolRetryPolicy};
+    /// # async fn example() -> claude_agent::Result<()> {
+    /// let agent = Agent::builder()
+    ///     .tool_retry("WebFetch", ToolRetryPolicy::transient_network(3))
+    ///     .build()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn tool_retry(mut self, tool: impl Into<String>, policy: ToolRetryPolicy) -> Self {
+        self.config.execution.tool_retry.insert(tool.into(), policy);
+        self
+    }
+
+    /// Sets the timeout and concurrency cap for calls of `tool`.
+    ///
+    /// ```rust,no_run
+    /// # use claude_agent::Agent;
+    /// # use claude_agent::permissions::ToolLimits;
+    /// # async fn example() -> claude_agent::Result<()> {
+    /// let agent = Agent::builder()
+    ///     .tool_limits("Bash", ToolLimits::serial().with_timeout(120_000))
+    ///     .tool_limits("HttpRequest", ToolLimits::concurrency(4).with_timeout(30_000))
+    ///     .build()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn tool_limits(
+        mut self,
+        tool: impl Into<String>,
+        limits: crate::permissions::ToolLimits,
+    ) -> Self {
+        self.config
+            .execution
+            .tool_limits
+            .insert(tool.into(), limits);
+        self
+    }
+
+    /// Caches successful Read/Glob/Grep/LS results per session, so repeated
+    /// identical calls skip execution. Writes and Bash invalidate affected entries.
+    pub fn tool_cache(mut self, config: crate::tools::ToolCacheConfig) -> Self {
+        self.config.execution.tool_cache = Some(config);
+        self
+    }
+
+    /// Caps tool usage per session. Calls past a quota are rejected without
+    /// running and reported as `AgentEvent::ToolBlocked`.
+    ///
+    /// ```rust,no_run
+    /// # use claude_agent::{Agent, tools::ToolQuotas};
+    /// let builder = Agent::builder().tool_quotas(
+    ///     ToolQuotas::new()
+    ///         .calls("Bash", 50)
+    ///         .total_calls(200)
+    ///         .output_bytes("WebFetch", 10 * 1024 * 1024),
+    /// );
+    /// ```
+    pub fn tool_quotas(mut self, quotas: crate::tools::ToolQuotas) -> Self {
+        self.config.execution.tool_quotas = Some(quotas);
+        self
+    }
+
+    /// Sets the directory names `Glob` and `Grep` skip, replacing the
+    /// defaults ([`DEFAULT_SEARCH_EXCLUDES`](crate::tools::DEFAULT_SEARCH_EXCLUDES)).
+    ///
+    /// Files ignored by `.gitignore`/`.ignore` are skipped as well; a call
+    /// can set `no_ignore` to search everything.
+    pub fn search_excludes(
+        mut self,
+        excludes: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.config.execution.search_excludes =
+            Some(excludes.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Backs up each file before `Write`, `Edit` or `MultiEdit` first changes
+    /// it, so [`Agent::rollback_writes`](crate::Agent::rollback_writes) can
+    /// restore the workspace after a failed or rejected run.
+    ///
+    /// Default: `false`
+    pub fn backup_writes(mut self, enabled: bool) -> Self {
+        self.config.execution.backup_writes = enabled;
+        self
+    }
+
+    /// Uploads images and PDFs too large to send inline through the Files
+    /// API, so `Read` can return them by file ID instead of failing.
+    ///
+    /// Uploaded files stay in the account's Files storage until deleted.
+    ///
+    /// Default: `false`
+    pub fn upload_large_files(mut self, enabled: bool) -> Self {
+        self.config.execution.upload_large_files = enabled;
+        self
+    }
+
+    /// Sets the token used to cancel in-flight executions.
+    ///
+    /// Cancelling aborts API streaming and tool calls, kills background shells,
+    /// and marks the session `Cancelled`. A fresh token is created if unset.
+    pub fn cancellation_token(mut self, token: tokio_util::sync::CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
     // =========================================================================
     // Caching
     // =========================================================================
@@ -387,6 +699,11 @@ impl AgentBuilder {
         self
     }
 
+    /// Applies a named preset's model, tools, permissions and prompt.
+    pub fn preset(self, preset: &crate::agent::AgentPreset) -> Self {
+        preset.apply(self)
+    }
+
     /// Sets the output style for response formatting.
     pub fn output_style(mut self, style: OutputStyle) -> Self {
         self.config.prompt.output_style = Some(style);
@@ -448,6 +765,35 @@ impl AgentBuilder {
         self
     }
 
+    /// Adds a rule requiring approval for a tool or pattern (e.g., `"Bash(git push:*)"`).
+    ///
+    /// Matching calls go to the [`permission_handler`](Self::permission_handler)
+    /// and are denied if none is set.
+    pub fn ask_tool(mut self, pattern: impl Into<String>) -> Self {
+        self.config
+            .security
+            .permission_policy
+            .rules
+            .push(PermissionRule::ask_pattern(pattern));
+        self
+    }
+
+    /// Lets tools access `path` in addition to the working directory.
+    pub fn additional_directory(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config
+            .security
+            .additional_directories
+            .push(path.into());
+        self
+    }
+
+    /// Sets the handler that approves or denies calls the permission policy
+    /// marks as `Ask`, e.g. by prompting a user.
+    pub fn permission_handler<H: PermissionHandler + 'static>(mut self, handler: H) -> Self {
+        self.permission_handler = Some(Arc::new(handler));
+        self
+    }
+
     // =========================================================================
     // Environment
     // =========================================================================
@@ -520,6 +866,16 @@ impl AgentBuilder {
         self
     }
 
+    /// Sets a cost ceiling in USD for each `execute()` call.
+    ///
+    /// Checked before every API call against the run's spend plus the cost of the
+    /// previous call. When the ceiling would be crossed the run stops early and
+    /// returns its partial output with [`AgentState::BudgetExceeded`](crate::AgentState::BudgetExceeded).
+    pub fn max_run_cost_usd(mut self, amount: Decimal) -> Self {
+        self.config.budget.max_cost_per_run_usd = Some(amount);
+        self
+    }
+
     /// Sets the tenant ID for multi-tenant budget tracking.
     pub fn tenant_id(mut self, id: impl Into<String>) -> Self {
         self.config.budget.tenant_id = Some(id.into());
@@ -549,6 +905,8 @@ impl AgentBuilder {
     // =========================================================================
 
     /// Sets a custom session manager for persistence.
+    ///
+    /// The agent saves its session to the manager after every run.
     pub fn session_manager(mut self, manager: crate::session::SessionManager) -> Self {
         self.session_manager = Some(manager);
         self
@@ -570,12 +928,26 @@ impl AgentBuilder {
         Ok(self)
     }
 
+    /// Resumes an existing session when the agent is built.
+    ///
+    /// Messages, todos, the current plan and compaction history are loaded from the
+    /// [`session_manager`](Self::session_manager) (in-memory if none is set), and the
+    /// session is saved back to it after every run. A manager scoped with
+    /// [`for_principal`](crate::session::SessionManager::for_principal) requires
+    /// execute access to the session.
+    pub fn resume(mut self, session_id: impl Into<String>) -> Self {
+        self.resume_session_id = Some(session_id.into());
+        self.resumed_session = None;
+        self
+    }
+
     /// Resumes an existing session by ID.
     pub async fn resume_session(mut self, session_id: impl Into<String>) -> crate::Result<Self> {
         let session_id_str: String = session_id.into();
         let id = crate::session::SessionId::from(session_id_str);
         let manager = self.session_manager.take().unwrap_or_default();
         let session = manager.get(&id).await?;
+        manager.authorize(&session, crate::session::SessionAccess::Execute)?;
 
         let messages: Vec<crate::types::Message> = session
             .messages
@@ -686,6 +1058,23 @@ impl AgentBuilder {
         self
     }
 
+    /// Selects MCP tools for each prompt by embedding similarity.
+    ///
+    /// Once tool search is active, the deferred tools closest to the prompt
+    /// are loaded up front; the model finds the rest with keyword search.
+    pub fn tool_search_embedder(
+        mut self,
+        embedder: std::sync::Arc<dyn crate::tools::Embedder>,
+    ) -> Self {
+        let config = self
+            .tool_search_config
+            .take()
+            .unwrap_or_default()
+            .embedder(embedder);
+        self.tool_search_config = Some(config);
+        self
+    }
+
     /// Sets tools that should always be loaded immediately (never deferred).
     pub fn always_load_tools(mut self, tools: impl IntoIterator<Item = impl Into<String>>) -> Self {
         let config = self
@@ -797,6 +1186,44 @@ impl AgentBuilder {
         self.hooks.register(hook);
         self
     }
+
+    /// Runs up to `max` hooks of a non-blocking event (such as `PostToolUse`)
+    /// concurrently. Hooks that can block always run one at a time.
+    ///
+    /// Default: `1`
+    pub fn hook_parallelism(mut self, max: usize) -> Self {
+        self.hooks.set_max_parallel(max);
+        self
+    }
+
+    /// Registers an event hook that runs at most as often as `limit` allows,
+    /// e.g. a formatter once every few seconds per file.
+    pub fn rate_limited_hook<H: Hook + 'static>(mut self, hook: H, limit: HookRateLimit) -> Self {
+        self.hooks.register_rate_limited(hook, limit);
+        self
+    }
+
+    // =========================================================================
+    // Event Sinks
+    // =========================================================================
+
+    /// Records every agent event to a sink, e.g. [`JsonlEventSink`](crate::agent::JsonlEventSink)
+    /// for an audit log. May be called multiple times.
+    pub fn event_sink<S: crate::agent::EventSink + 'static>(mut self, sink: S) -> Self {
+        self.event_sinks.push(Arc::new(sink));
+        self
+    }
+
+    /// Counts tool calls and sandbox violations in `registry`, e.g. one from
+    /// [`ObservabilityConfig::build_registry`](crate::observability::ObservabilityConfig::build_registry)
+    /// to export them over OpenTelemetry.
+    pub fn metrics_registry(
+        mut self,
+        registry: Arc<crate::observability::MetricsRegistry>,
+    ) -> Self {
+        self.metrics_registry = Some(registry);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -825,4 +1252,34 @@ mod tests {
         let builder = AgentBuilder::new().max_tokens(16384);
         assert_eq!(builder.config.model.max_tokens, 16384);
     }
+
+    #[test]
+    fn test_preset() {
+        let builder = AgentBuilder::new()
+            .model("haiku")
+            .preset(&crate::agent::code_reviewer_preset());
+        let security = &builder.config.security;
+        assert!(security.tool_access.is_allowed("Read"));
+        assert!(!security.tool_access.is_allowed("Write"));
+        assert_eq!(security.permission_policy.rules.len(), 7);
+        assert!(
+            builder
+                .config
+                .prompt
+                .system_prompt
+                .as_deref()
+                .unwrap()
+                .contains("code reviewer")
+        );
+
+        let builder = AgentBuilder::new().preset(&crate::agent::docs_writer_preset());
+        assert_eq!(
+            builder.config.security.permission_policy.mode,
+            PermissionMode::AcceptEdits
+        );
+        assert_eq!(
+            builder.config.prompt.output_style.as_ref().unwrap().name,
+            "explanatory"
+        );
+    }
 }
@@ -7,11 +7,13 @@ use std::path::Path;
 
 use crate::auth::Auth;
 use crate::common::{IndexRegistry, Named, Provider};
-use crate::config::{Settings, SettingsLoader};
-use crate::context::{LeveledMemoryProvider, MemoryLoader, enterprise_base_path, user_base_path};
+use crate::config::{ClaudeDir, ClaudeDirResources, Settings, SettingsLoader};
+use crate::context::{LeveledMemoryProvider, MemoryLoader, enterprise_base_path};
 use crate::hooks::CommandHook;
+use crate::mcp::parse_mcp_servers;
 use crate::output_style::file_output_style_provider;
 use crate::permissions::{PermissionMode, PermissionPolicy};
+use crate::security::bash::BashPolicy;
 use crate::skills::SkillIndexLoader;
 use crate::subagents::{SubagentIndexLoader, builtin_subagents};
 
@@ -94,6 +96,31 @@ impl AgentBuilder {
         self
     }
 
+    /// Loads a Claude Code configuration directory during build.
+    ///
+    /// Consumes settings (model, env, permissions, hooks, MCP servers, output style),
+    /// `CLAUDE.md`, `commands/`, `skills/`, `agents/`, `output-styles/` and the
+    /// directory's MCP config file. Directories are applied after the level
+    /// resources, in call order, so later directories override earlier ones.
+    pub fn claude_dir(mut self, dir: ClaudeDir) -> Self {
+        self.claude_dirs.push(dir);
+        self
+    }
+
+    /// Mirrors the effective configuration of the Claude Code CLI run in `project`.
+    ///
+    /// Equivalent to [`from_claude_code`](Self::from_claude_code) with every
+    /// resource level enabled, for drop-in parity with subprocess-based integrations.
+    pub async fn claude_code_compat(self, project: impl AsRef<Path>) -> crate::Result<Self> {
+        Ok(self
+            .from_claude_code(project)
+            .await?
+            .enterprise_resources()
+            .user_resources()
+            .project_resources()
+            .local_resources())
+    }
+
     // =========================================================================
     // Internal resource loading methods (called by build.rs in fixed order)
     // =========================================================================
@@ -111,15 +138,11 @@ impl AgentBuilder {
     }
 
     pub(super) async fn load_user_resources(&mut self) {
-        let Some(base) = user_base_path() else {
+        let Some(dir) = ClaudeDir::user() else {
             return;
         };
 
-        self.load_settings_from(&base).await;
-        self.load_skills_from(&base).await;
-        self.load_subagents_from(&base).await;
-        self.load_output_styles_from(&base).await;
-        self.load_memory_from(&base).await;
+        self.load_claude_dir(&dir).await;
     }
 
     pub(super) async fn load_project_resources(&mut self) {
@@ -128,11 +151,26 @@ impl AgentBuilder {
             return;
         };
 
-        self.load_settings_from(&working_dir).await;
-        self.load_skills_from(&working_dir).await;
+        match ClaudeDir::project(&working_dir).load().await {
+            Ok(mut resources) => {
+                // Project memory lives at the project root and its ancestors,
+                // not inside `.claude/`.
+                resources.memory = MemoryLoader::new()
+                    .load_shared_hierarchy(&working_dir)
+                    .await
+                    .unwrap_or_default();
+                self.apply_claude_dir_resources(resources);
+            }
+            Err(e) => tracing::warn!(error = %e, "Failed to load project resources"),
+        }
         self.load_subagents_from(&working_dir).await;
         self.load_output_styles_from(&working_dir).await;
-        self.load_memory_from(&working_dir).await;
+    }
+
+    pub(super) async fn load_claude_dirs(&mut self) {
+        for dir in std::mem::take(&mut self.claude_dirs) {
+            self.load_claude_dir(&dir).await;
+        }
     }
 
     pub(super) async fn load_local_resources(&mut self) {
@@ -148,7 +186,7 @@ impl AgentBuilder {
         }
 
         let loader = MemoryLoader::new();
-        if let Ok(content) = loader.load_local(&working_dir).await
+        if let Ok(content) = loader.load_local_hierarchy(&working_dir).await
             && !content.local_md.is_empty()
         {
             let provider = self
@@ -162,6 +200,50 @@ impl AgentBuilder {
     // Helper methods for resource loading
     // =========================================================================
 
+    async fn load_claude_dir(&mut self, dir: &ClaudeDir) {
+        match dir.load().await {
+            Ok(resources) => self.apply_claude_dir_resources(resources),
+            Err(e) => {
+                tracing::warn!(dir = %dir.root().display(), error = %e, "Failed to load Claude directory")
+            }
+        }
+    }
+
+    fn apply_claude_dir_resources(&mut self, resources: ClaudeDirResources) {
+        self.apply_settings_mut(&resources.settings);
+
+        if !resources.skills.is_empty() {
+            let registry = self.skill_registry.get_or_insert_with(IndexRegistry::new);
+            for skill in resources.skills {
+                registry.register(skill);
+            }
+        }
+
+        if !resources.subagents.is_empty() {
+            let registry = self.subagent_registry.get_or_insert_with(|| {
+                let mut registry = IndexRegistry::new();
+                registry.register_all(builtin_subagents());
+                registry
+            });
+            for subagent in resources.subagents {
+                registry.register(subagent);
+            }
+        }
+
+        for style in resources.output_styles {
+            self.output_styles.retain(|s| s.name != style.name);
+            self.output_styles.push(style);
+        }
+
+        if !resources.memory.is_empty() {
+            self.memory_provider
+                .get_or_insert_with(LeveledMemoryProvider::new)
+                .add_memory_content(resources.memory);
+        }
+
+        self.mcp_configs.extend(resources.mcp_servers);
+    }
+
     async fn load_settings_from(&mut self, base: &Path) {
         let mut loader = SettingsLoader::new();
         if loader.load_from(base).await.is_ok() {
@@ -213,7 +295,7 @@ impl AgentBuilder {
 
     async fn load_memory_from(&mut self, base: &Path) {
         let loader = MemoryLoader::new();
-        if let Ok(content) = loader.load_shared(base).await
+        if let Ok(content) = loader.load_base(base).await
             && !content.is_empty()
         {
             let provider = self
@@ -243,6 +325,31 @@ impl AgentBuilder {
 
         self.config.security.env.extend(settings.env.clone());
 
+        if let Some(ref setting) = settings.bash_policy {
+            let base = self
+                .config
+                .working_dir
+                .clone()
+                .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+            let policy = setting.load(&base).unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "Invalid bashPolicy, using the strict policy");
+                BashPolicy::strict()
+            });
+            self.config.security.bash_policy = Some(policy);
+        }
+
+        if !settings.permissions.additional_directories.is_empty() {
+            let base = self
+                .config
+                .working_dir
+                .clone()
+                .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+            self.config
+                .security
+                .additional_directories
+                .extend(settings.permissions.resolved_directories(&base));
+        }
+
         if !settings.permissions.is_empty() {
             let loaded_policy = settings.permissions.to_policy();
             let existing_policy = std::mem::take(&mut self.config.security.permission_policy);
@@ -258,11 +365,8 @@ impl AgentBuilder {
             self.output_style_name = Some(style_name.clone());
         }
 
-        for (name, config_value) in &settings.mcp_servers {
-            if let Ok(config) = serde_json::from_value(config_value.clone()) {
-                self.mcp_configs.insert(name.clone(), config);
-            }
-        }
+        self.mcp_configs
+            .extend(parse_mcp_servers(&settings.mcp_servers));
 
         // Apply tool search settings
         if !settings.tool_search.is_empty() && settings.tool_search.is_enabled() {
@@ -300,6 +404,10 @@ impl AgentBuilder {
             mode,
             rules,
             tool_limits,
+            deny_above_risk: programmatic
+                .deny_above_risk
+                .or(from_settings.deny_above_risk),
+            ask_above_risk: programmatic.ask_above_risk.or(from_settings.ask_above_risk),
         }
     }
 }
@@ -373,6 +481,35 @@ mod tests {
         assert_eq!(builder.config.model.small, "project-small");
     }
 
+    #[test]
+    fn test_settings_permissions_applied() {
+        let settings: Settings = serde_json::from_str(
+            r#"{"permissions": {
+                "allow": ["Bash(git:*)"],
+                "ask": ["Bash(git push:*)"],
+                "deny": ["WebFetch"],
+                "defaultMode": "acceptEdits",
+                "additionalDirectories": ["../shared"]
+            }}"#,
+        )
+        .unwrap();
+
+        let builder = AgentBuilder::new()
+            .working_dir("/work/app")
+            .apply_settings(settings);
+
+        let security = &builder.config.security;
+        let policy = &security.permission_policy;
+        assert_eq!(policy.mode, PermissionMode::AcceptEdits);
+        let push = serde_json::json!({"command": "git push"});
+        assert!(policy.check("Bash", &push).needs_approval());
+        assert!(policy.check("WebFetch", &serde_json::json!({})).is_denied());
+        assert_eq!(
+            security.additional_directories,
+            [std::path::PathBuf::from("/work/app/../shared")]
+        );
+    }
+
     #[test]
     fn test_resource_flags_are_independent() {
         // Verify that flag methods don't actually load anything, just set flags
@@ -408,4 +545,27 @@ mod tests {
         assert_eq!(builder1.load_user, builder2.load_user);
         assert_eq!(builder1.load_project, builder2.load_project);
     }
+
+    #[tokio::test]
+    async fn test_claude_dir_applied_after_levels() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("commands")).unwrap();
+        std::fs::write(
+            dir.path().join("settings.json"),
+            r#"{"model": "dir-model", "mcpServers": {"fs": {"command": "mcp-fs"}}}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("commands").join("ship.md"), "Ship it").unwrap();
+
+        let mut builder = AgentBuilder::new()
+            .model("explicit-model")
+            .claude_dir(ClaudeDir::new(dir.path()));
+        builder.load_claude_dirs().await;
+
+        assert!(builder.claude_dirs.is_empty());
+        assert_eq!(builder.config.model.primary, "dir-model");
+        assert!(builder.mcp_configs.contains_key("fs"));
+        let skills = builder.skill_registry.unwrap();
+        assert_eq!(skills.get("ship").unwrap().description, "Ship it");
+    }
 }
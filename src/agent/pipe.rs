@@ -0,0 +1,394 @@
+//! Stdin/stdout pipe mode for shell pipelines and batch jobs.
+//!
+//! [`PipeRunner`] reads either a single prompt or a JSONL job stream, runs each
+//! job on its own [`Agent`] with bounded parallelism, and writes one NDJSON
+//! [`PipeRecord`] per job as soon as it finishes.
+//!
+//! ```rust,no_run
+//! use claude_agent::Agent;
+//! use claude_agent::agent::PipeRunner;
+//!
+//! # async fn example() -> claude_agent::Result<()> {
+//! // echo '{"id":"a","prompt":"2+2?"}' | my-tool
+//! let summary = PipeRunner::new(|_job| Agent::builder().build())
+//!     .max_concurrency(8)
+//!     .run_stdio()
+//!     .await?;
+//! eprintln!("{} ok, {} failed", summary.succeeded, summary.failed);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+use std::time::Instant;
+
+use futures::{StreamExt, stream};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use super::events::AgentResult;
+use super::executor::Agent;
+use crate::types::{StopReason, Usage};
+
+/// Default number of jobs executed concurrently.
+pub const DEFAULT_PIPE_CONCURRENCY: usize = 4;
+
+/// How stdin is interpreted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PipeInput {
+    /// The whole input is a single prompt.
+    Prompt,
+    /// Each non-empty line is a JSON [`PipeJob`].
+    #[default]
+    Jsonl,
+}
+
+/// A single unit of work read from the input stream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipeJob {
+    /// Caller-supplied identifier, echoed in the result. Defaults to the line number.
+    #[serde(default)]
+    pub id: Option<String>,
+    pub prompt: String,
+    /// Arbitrary data echoed back unchanged in the result.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Value>,
+}
+
+/// NDJSON result line written for every job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipeRecord {
+    pub id: String,
+    pub is_error: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<StopReason>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
+    #[serde(default)]
+    pub num_turns: usize,
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub total_cost_usd: Decimal,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured_output: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Value>,
+}
+
+impl PipeRecord {
+    fn success(id: String, result: AgentResult, duration_ms: u64, metadata: Option<Value>) -> Self {
+        Self {
+            id,
+            is_error: false,
+            total_cost_usd: result.metrics.total_cost_usd,
+            result: Some(result.text),
+            error: None,
+            session_id: Some(result.session_id),
+            stop_reason: Some(result.stop_reason),
+            usage: Some(result.usage),
+            num_turns: result.iterations,
+            duration_ms,
+            structured_output: result.structured_output,
+            metadata,
+        }
+    }
+
+    fn failure(
+        id: String,
+        error: impl std::fmt::Display,
+        duration_ms: u64,
+        metadata: Option<Value>,
+    ) -> Self {
+        Self {
+            id,
+            is_error: true,
+            result: None,
+            error: Some(error.to_string()),
+            session_id: None,
+            stop_reason: None,
+            usage: None,
+            num_turns: 0,
+            duration_ms,
+            total_cost_usd: Decimal::ZERO,
+            structured_output: None,
+            metadata,
+        }
+    }
+}
+
+/// Counts reported after the input stream is exhausted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PipeSummary {
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+/// Drives agents from a prompt or JSONL job stream.
+///
+/// The factory is called once per job so every job gets an isolated session.
+pub struct PipeRunner<F> {
+    factory: F,
+    input: PipeInput,
+    max_concurrency: usize,
+}
+
+impl<F, Fut> PipeRunner<F>
+where
+    F: Fn(&PipeJob) -> Fut,
+    Fut: Future<Output = crate::Result<Agent>>,
+{
+    pub fn new(factory: F) -> Self {
+        Self {
+            factory,
+            input: PipeInput::default(),
+            max_concurrency: DEFAULT_PIPE_CONCURRENCY,
+        }
+    }
+
+    pub fn input(mut self, input: PipeInput) -> Self {
+        self.input = input;
+        self
+    }
+
+    pub fn max_concurrency(mut self, max: usize) -> Self {
+        self.max_concurrency = max.max(1);
+        self
+    }
+
+    /// Read jobs from stdin and write results to stdout.
+    pub async fn run_stdio(&self) -> crate::Result<PipeSummary> {
+        let stdin = tokio::io::BufReader::new(tokio::io::stdin());
+        self.run(stdin, tokio::io::stdout()).await
+    }
+
+    /// Read jobs from `reader` and write one NDJSON record per job to `writer`.
+    ///
+    /// Records are written in completion order. Malformed job lines produce
+    /// error records rather than aborting the run; I/O failures abort it.
+    pub async fn run<R, W>(&self, mut reader: R, mut writer: W) -> crate::Result<PipeSummary>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let mut summary = PipeSummary::default();
+
+        match self.input {
+            PipeInput::Prompt => {
+                let mut prompt = String::new();
+                reader.read_to_string(&mut prompt).await?;
+                let prompt = prompt.trim();
+                if prompt.is_empty() {
+                    return Err(crate::Error::InvalidRequest(
+                        "No prompt provided on input".into(),
+                    ));
+                }
+                let job = PipeJob {
+                    id: None,
+                    prompt: prompt.to_string(),
+                    metadata: None,
+                };
+                let record = self.execute_job(1, job).await;
+                Self::emit(&mut writer, &record, &mut summary).await?;
+            }
+            PipeInput::Jsonl => {
+                let lines = stream::unfold(
+                    (Some(reader.lines()), 0usize),
+                    |(lines, line_no)| async move {
+                        let mut lines = lines?;
+                        match lines.next_line().await {
+                            Ok(Some(line)) => {
+                                Some((Ok((line_no + 1, line)), (Some(lines), line_no + 1)))
+                            }
+                            Ok(None) => None,
+                            Err(e) => Some((Err(e), (None, line_no))),
+                        }
+                    },
+                );
+
+                let records = lines
+                    .filter(|line| {
+                        let keep = line.as_ref().map_or(true, |(_, l)| !l.trim().is_empty());
+                        async move { keep }
+                    })
+                    .map(|line| async move {
+                        let (line_no, line) = line?;
+                        Ok::<_, crate::Error>(match serde_json::from_str::<PipeJob>(&line) {
+                            Ok(job) => self.execute_job(line_no, job).await,
+                            Err(e) => PipeRecord::failure(
+                                line_no.to_string(),
+                                format!("Invalid job on line {line_no}: {e}"),
+                                0,
+                                None,
+                            ),
+                        })
+                    })
+                    .buffer_unordered(self.max_concurrency);
+                let mut records = std::pin::pin!(records);
+
+                while let Some(record) = records.next().await {
+                    Self::emit(&mut writer, &record?, &mut summary).await?;
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+
+    async fn execute_job(&self, line_no: usize, job: PipeJob) -> PipeRecord {
+        let id = job.id.clone().unwrap_or_else(|| line_no.to_string());
+        let start = Instant::now();
+
+        let outcome = match (self.factory)(&job).await {
+            Ok(agent) => agent.execute(&job.prompt).await,
+            Err(e) => Err(e),
+        };
+        let duration_ms = start.elapsed().as_millis() as u64;
+
+        match outcome {
+            Ok(result) => PipeRecord::success(id, result, duration_ms, job.metadata),
+            Err(e) => PipeRecord::failure(id, e, duration_ms, job.metadata),
+        }
+    }
+
+    async fn emit<W: AsyncWrite + Unpin>(
+        writer: &mut W,
+        record: &PipeRecord,
+        summary: &mut PipeSummary,
+    ) -> crate::Result<()> {
+        summary.total += 1;
+        if record.is_error {
+            summary.failed += 1;
+        } else {
+            summary.succeeded += 1;
+        }
+
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        writer.write_all(&line).await?;
+        writer.flush().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AgentConfig;
+    use crate::agent::tests::helpers::text_response;
+    use crate::client::{AnthropicAdapter, ProviderConfig};
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer};
+
+    async fn mock_server() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .respond_with(text_response("done"))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    fn agent_for(uri: String) -> crate::Result<Agent> {
+        let adapter = AnthropicAdapter::new(ProviderConfig::default())
+            .api_key("test-key")
+            .base_url(uri);
+        Ok(Agent::new(
+            crate::Client::new(adapter)?,
+            AgentConfig::default(),
+        ))
+    }
+
+    fn parse(output: &[u8]) -> Vec<PipeRecord> {
+        String::from_utf8_lossy(output)
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_jsonl_jobs() {
+        let server = mock_server().await;
+        let uri = server.uri();
+        let input = concat!(
+            "{\"id\":\"a\",\"prompt\":\"first\",\"metadata\":{\"row\":1}}\n",
+            "\n",
+            "not json\n",
+            "{\"prompt\":\"second\"}\n",
+        );
+
+        let mut output = Vec::new();
+        let summary = PipeRunner::new(|_| std::future::ready(agent_for(uri.clone())))
+            .max_concurrency(2)
+            .run(input.as_bytes(), &mut output)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            summary,
+            PipeSummary {
+                total: 3,
+                succeeded: 2,
+                failed: 1
+            }
+        );
+
+        let mut records = parse(&output);
+        records.sort_by(|a, b| a.id.cmp(&b.id));
+        assert_eq!(records[0].id, "3");
+        assert!(records[0].is_error);
+        assert_eq!(records[1].id, "4");
+        assert_eq!(records[1].result.as_deref(), Some("done"));
+        assert_eq!(records[2].id, "a");
+        assert_eq!(records[2].metadata, Some(serde_json::json!({"row": 1})));
+        assert_eq!(records[2].usage.unwrap().output_tokens, 5);
+    }
+
+    #[tokio::test]
+    async fn test_prompt_mode() {
+        let server = mock_server().await;
+        let uri = server.uri();
+
+        let mut output = Vec::new();
+        let summary = PipeRunner::new(|_| std::future::ready(agent_for(uri.clone())))
+            .input(PipeInput::Prompt)
+            .run("summarize this\nplease\n".as_bytes(), &mut output)
+            .await
+            .unwrap();
+
+        assert_eq!(summary.succeeded, 1);
+        let records = parse(&output);
+        assert_eq!(records[0].id, "1");
+        assert_eq!(records[0].stop_reason, Some(StopReason::EndTurn));
+
+        let empty = PipeRunner::new(|_| std::future::ready(agent_for(uri.clone())))
+            .input(PipeInput::Prompt)
+            .run("   \n".as_bytes(), Vec::new())
+            .await;
+        assert!(matches!(empty, Err(crate::Error::InvalidRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_factory_error_becomes_record() {
+        let mut output = Vec::new();
+        let summary = PipeRunner::new(|_| {
+            std::future::ready(Err::<Agent, _>(crate::Error::Config("no auth".into())))
+        })
+        .run("{\"prompt\":\"x\"}\n".as_bytes(), &mut output)
+        .await
+        .unwrap();
+
+        assert_eq!(summary.failed, 1);
+        let records = parse(&output);
+        assert!(records[0].error.as_ref().unwrap().contains("no auth"));
+    }
+}
@@ -0,0 +1,413 @@
+//! Dry-run planning: record proposed tool calls instead of executing them.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::common::PendingToolCall;
+use crate::security::SecurityError;
+use crate::security::fs::SecureFileHandle;
+use crate::security::path::SafePath;
+use crate::tools::ExecutionContext;
+use crate::tools::edit::replace;
+use crate::tools::multi_edit::{EditOperation, apply_edits};
+use crate::types::ToolResultBlock;
+
+/// A tool call the agent proposed while running in dry-run mode.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedToolCall {
+    pub tool_use_id: String,
+    pub name: String,
+    pub input: Value,
+    /// Unified diff of the file change, for `Write`, `Edit`, `MultiEdit` and
+    /// text editor calls. `None` for a `Write` over a file that cannot be
+    /// read as text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+    /// Why the file change would fail, in place of its diff: the file is
+    /// outside the sandbox or unreadable, or the edit does not apply.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl PlannedToolCall {
+    /// Build the plan entry for a call, reading the target file through the
+    /// sandbox to diff file edits.
+    pub(crate) async fn from_call(call: &PendingToolCall, context: &ExecutionContext) -> Self {
+        let (diff, error) = if has_file_diff(&call.name, &call.input) {
+            match file_diff(&call.name, &call.input, context, &StagedFiles::default()).await {
+                Ok(change) => (Some(change.diff), None),
+                Err(DiffError::Unreadable(_)) => (None, None),
+                Err(DiffError::Fails(e)) => (None, Some(e)),
+            }
+        } else {
+            (None, None)
+        };
+        Self {
+            tool_use_id: call.id.clone(),
+            name: call.name.clone(),
+            input: call.input.clone(),
+            diff,
+            error,
+        }
+    }
+
+    /// Tool result returned to the model in place of real output.
+    pub(crate) fn result_block(&self) -> ToolResultBlock {
+        if let Some(error) = &self.error {
+            return ToolResultBlock::error(
+                &self.tool_use_id,
+                format!("Dry run: {} would fail: {}", self.name, error),
+            );
+        }
+        ToolResultBlock::success(
+            &self.tool_use_id,
+            format!(
+                "Dry run: {} was not executed. It has been recorded in the plan for review; \
+                 continue as if it succeeded.",
+                self.name
+            ),
+        )
+    }
+}
+
+/// Whether [`file_diff`] can compute the change `tool` makes. Other write
+/// tools (`NotebookEdit`, `AstEdit`, the text editor's `undo_edit`) have no
+/// diff ahead of time.
+pub(super) fn has_file_diff(tool: &str, input: &Value) -> bool {
+    match tool {
+        "Write" | "Edit" | "MultiEdit" => true,
+        "str_replace_based_edit_tool" | "str_replace_editor" => matches!(
+            input.get("command").and_then(Value::as_str),
+            Some("create" | "str_replace" | "insert")
+        ),
+        _ => false,
+    }
+}
+
+/// Why [`file_diff`] produced no diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum DiffError {
+    /// The call would fail for this reason.
+    Fails(String),
+    /// The file a `Write` (or text editor `create`) replaces cannot be read
+    /// as text, being binary or over the size limit; the write itself would
+    /// still succeed.
+    Unreadable(String),
+}
+
+impl From<String> for DiffError {
+    fn from(reason: String) -> Self {
+        Self::Fails(reason)
+    }
+}
+
+impl std::fmt::Display for DiffError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fails(reason) | Self::Unreadable(reason) => f.write_str(reason),
+        }
+    }
+}
+
+/// A file tool call's change to one file.
+pub(super) struct FileChange {
+    path: PathBuf,
+    content: String,
+    /// Unified diff from the file's current content to `content`.
+    pub(super) diff: String,
+}
+
+/// File contents as the changes approved so far leave them, so a later call
+/// on the same file is diffed against those rather than against the disk.
+#[derive(Debug, Default)]
+pub(super) struct StagedFiles(HashMap<PathBuf, String>);
+
+impl StagedFiles {
+    pub(super) fn stage(&mut self, change: FileChange) {
+        self.0.insert(change.path, change.content);
+    }
+}
+
+/// The change a file tool call would make. The file is taken from `staged`,
+/// or else read through the sandbox of `context`, as the tool itself would; a
+/// missing file diffs as empty.
+pub(super) async fn file_diff(
+    tool: &str,
+    input: &Value,
+    context: &ExecutionContext,
+    staged: &StagedFiles,
+) -> Result<FileChange, DiffError> {
+    let field = |name: &str| input.get(name).and_then(Value::as_str);
+    let invalid = || DiffError::Fails(format!("invalid input for {}", tool));
+    let file_path = field("file_path")
+        .or_else(|| field("path"))
+        .ok_or_else(invalid)?;
+    let path = context
+        .resolve_for(tool, file_path)
+        .map_err(|e| DiffError::Fails(e.to_string()))?;
+    let replaces = tool == "Write" || field("command") == Some("create");
+    let key = path.as_path().to_path_buf();
+    let original = match staged.0.get(&key) {
+        Some(content) => content.clone(),
+        None => read_original(path, replaces).await?,
+    };
+
+    let updated = match tool {
+        "Write" => field("content").ok_or_else(invalid)?.to_string(),
+        "MultiEdit" => {
+            let edits: Vec<EditOperation> = input
+                .get("edits")
+                .and_then(|edits| serde_json::from_value(edits.clone()).ok())
+                .ok_or_else(invalid)?;
+            apply_edits(&original, &edits)?
+        }
+        "Edit" => {
+            let (old, new) = field("old_string")
+                .zip(field("new_string"))
+                .ok_or_else(invalid)?;
+            if old == new {
+                return Err("old_string and new_string must be different"
+                    .to_string()
+                    .into());
+            }
+            let replace_all = input.get("replace_all").and_then(Value::as_bool) == Some(true);
+            replace(&original, old, new, replace_all)?.0
+        }
+        "str_replace_based_edit_tool" | "str_replace_editor" => {
+            text_editor_update(&original, input).ok_or_else(invalid)??
+        }
+        _ => return Err(format!("{} has no diff", tool).into()),
+    };
+
+    let diff = similar::TextDiff::from_lines(&original, &updated)
+        .unified_diff()
+        .header(file_path, file_path)
+        .to_string();
+    Ok(FileChange {
+        path: key,
+        content: updated,
+        diff,
+    })
+}
+
+/// Current text of `path`, read through a sandboxed handle. `replaces` marks
+/// a call that overwrites the file, which still succeeds if it is unreadable.
+async fn read_original(path: SafePath, replaces: bool) -> Result<String, DiffError> {
+    tokio::task::spawn_blocking(move || {
+        let handle = match SecureFileHandle::open_read(path) {
+            Ok(handle) => handle,
+            Err(SecurityError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(String::new());
+            }
+            Err(e) => return Err(DiffError::Fails(e.to_string())),
+        };
+        handle.read_to_string().map_err(|e| {
+            if replaces {
+                DiffError::Unreadable(e.to_string())
+            } else {
+                DiffError::Fails(e.to_string())
+            }
+        })
+    })
+    .await
+    .map_err(|e| DiffError::Fails(e.to_string()))?
+}
+
+/// File content after a text editor `create`, `str_replace` or `insert`, or
+/// `None` if the input is malformed.
+fn text_editor_update(original: &str, input: &Value) -> Option<Result<String, String>> {
+    let new_str = || {
+        input
+            .get("new_str")
+            .or_else(|| input.get("insert_text"))
+            .and_then(Value::as_str)
+    };
+    match input.get("command")?.as_str()? {
+        "create" => Some(Ok(input.get("file_text")?.as_str()?.to_string())),
+        "str_replace" => {
+            let old = input.get("old_str")?.as_str()?;
+            Some(replace(original, old, new_str().unwrap_or_default(), false).map(|(s, _)| s))
+        }
+        "insert" => {
+            let line = input.get("insert_line")?.as_u64()? as usize;
+            let mut lines: Vec<&str> = original.lines().collect();
+            if line > lines.len() {
+                return Some(Err(format!(
+                    "insert_line {} is beyond the end of the file ({} lines)",
+                    line,
+                    lines.len()
+                )));
+            }
+            lines.splice(line..line, new_str()?.lines());
+            let mut updated = lines.join("\n");
+            if original.ends_with('\n') || original.is_empty() {
+                updated.push('\n');
+            }
+            Some(Ok(updated))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(name: &str, input: Value) -> PendingToolCall {
+        PendingToolCall {
+            id: "toolu_1".into(),
+            name: name.into(),
+            input,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_edit_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("main.rs"), "fn main() {\n    old();\n}\n").unwrap();
+
+        let planned = PlannedToolCall::from_call(
+            &call(
+                "Edit",
+                serde_json::json!({
+                    "file_path": "main.rs",
+                    "old_string": "old()",
+                    "new_string": "new()"
+                }),
+            ),
+            &ExecutionContext::from_path(dir.path()).unwrap(),
+        )
+        .await;
+
+        let diff = planned.diff.unwrap();
+        assert!(diff.contains("-    old();"));
+        assert!(diff.contains("+    new();"));
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("main.rs")).unwrap(),
+            "fn main() {\n    old();\n}\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_multi_edit_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("lib.rs"),
+            "const A: u8 = 1;\nconst B: u8 = 2;\n",
+        )
+        .unwrap();
+
+        let input = |second: &str| {
+            serde_json::json!({
+                "file_path": "lib.rs",
+                "edits": [
+                    {"old_string": "A: u8 = 1", "new_string": "A: u8 = 10"},
+                    {"old_string": second, "new_string": "B: u8 = 20"}
+                ]
+            })
+        };
+        let context = ExecutionContext::from_path(dir.path()).unwrap();
+        let planned =
+            PlannedToolCall::from_call(&call("MultiEdit", input("B: u8 = 2")), &context).await;
+        let diff = planned.diff.unwrap();
+        assert!(diff.contains("+const A: u8 = 10;"));
+        assert!(diff.contains("+const B: u8 = 20;"));
+
+        let invalid =
+            PlannedToolCall::from_call(&call("MultiEdit", input("missing")), &context).await;
+        assert!(invalid.diff.is_none());
+        assert!(invalid.error.unwrap().starts_with("Edit 2:"));
+    }
+
+    #[tokio::test]
+    async fn test_diff_reads_through_sandbox() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("project");
+        std::fs::create_dir(&root).unwrap();
+        std::fs::write(dir.path().join("secret.txt"), "token\n").unwrap();
+        std::os::unix::fs::symlink(dir.path().join("secret.txt"), root.join("link.txt")).unwrap();
+        let context = ExecutionContext::from_path(&root).unwrap();
+
+        for path in ["../secret.txt", "link.txt"] {
+            let planned = PlannedToolCall::from_call(
+                &call(
+                    "Edit",
+                    serde_json::json!({"file_path": path, "old_string": "token", "new_string": "x"}),
+                ),
+                &context,
+            )
+            .await;
+            assert!(planned.diff.is_none(), "{}", path);
+            assert!(planned.error.is_some(), "{}", path);
+            assert!(planned.result_block().is_error.unwrap_or(false));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_new_file_and_other_tools() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("new.txt");
+
+        let planned = PlannedToolCall::from_call(
+            &call(
+                "Write",
+                serde_json::json!({"file_path": path.to_str().unwrap(), "content": "hello\n"}),
+            ),
+            &ExecutionContext::permissive(),
+        )
+        .await;
+        assert!(planned.diff.unwrap().contains("+hello"));
+        assert!(!path.exists());
+
+        let bash = PlannedToolCall::from_call(
+            &call("Bash", serde_json::json!({"command": "ls"})),
+            &ExecutionContext::permissive(),
+        )
+        .await;
+        assert!(bash.diff.is_none());
+        assert!(!bash.result_block().is_error.unwrap_or(false));
+    }
+
+    #[tokio::test]
+    async fn test_text_editor_and_binary_write() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo\n").unwrap();
+        std::fs::write(dir.path().join("a.bin"), [0xff, 0xfe, 0x00]).unwrap();
+        let context = ExecutionContext::from_path(dir.path()).unwrap();
+
+        let planned = PlannedToolCall::from_call(
+            &call(
+                "str_replace_based_edit_tool",
+                serde_json::json!({"command": "str_replace", "path": "a.txt", "old_str": "two", "new_str": "2"}),
+            ),
+            &context,
+        )
+        .await;
+        assert!(planned.diff.unwrap().contains("-two\n+2"));
+
+        let planned = PlannedToolCall::from_call(
+            &call(
+                "Write",
+                serde_json::json!({"file_path": "a.bin", "content": "text\n"}),
+            ),
+            &context,
+        )
+        .await;
+        assert!(planned.diff.is_none());
+        assert!(planned.error.is_none());
+        assert!(!planned.result_block().is_error.unwrap_or(false));
+
+        let planned = PlannedToolCall::from_call(
+            &call(
+                "Edit",
+                serde_json::json!({"file_path": "a.bin", "old_string": "a", "new_string": "b"}),
+            ),
+            &context,
+        )
+        .await;
+        assert!(planned.error.is_some());
+    }
+}
@@ -0,0 +1,424 @@
+//! Named agent presets.
+//!
+//! An [`AgentPreset`] bundles model, tool access, permission rules, output
+//! style and an extra system prompt under a name, so services can share one
+//! definition instead of repeating builder chains. Presets are defined in code,
+//! taken from the built-ins, or loaded from Markdown files with YAML
+//! frontmatter whose body is appended to the system prompt:
+//!
+//! ```text
+//! .claude/presets/api-reviewer.md
+//! ---
+//! name: api-reviewer
+//! description: Reviews public API changes
+//! model: opus
+//! tools: [Read, Grep, Glob]
+//! allow: [Read, Grep, Glob]
+//! ---
+//! Focus on breaking changes to public items.
+//! ```
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::options::AgentBuilder;
+use crate::common::parse_frontmatter;
+use crate::output_style::find_builtin;
+use crate::permissions::PermissionMode;
+use crate::tools::ToolAccess;
+
+/// Directory, relative to a project or the home directory, holding preset files.
+pub const PRESETS_DIR: &str = ".claude/presets";
+
+/// A reusable agent profile.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentPreset {
+    pub name: String,
+    pub description: String,
+    /// Primary model (alias or full id)
+    pub model: Option<String>,
+    #[serde(alias = "smallModel")]
+    pub small_model: Option<String>,
+    /// Allowed tools; `None` keeps every tool available
+    pub tools: Option<Vec<String>>,
+    /// Tools removed from the available set
+    #[serde(alias = "disallowedTools")]
+    pub disallowed_tools: Vec<String>,
+    #[serde(alias = "permissionMode")]
+    pub permission_mode: Option<PermissionMode>,
+    /// Allow rules, e.g. `Read` or `Bash(git diff:*)`
+    pub allow: Vec<String>,
+    /// Deny rules
+    pub deny: Vec<String>,
+    /// Output style name
+    #[serde(alias = "outputStyle")]
+    pub output_style: Option<String>,
+    /// Appended to the system prompt
+    #[serde(alias = "systemPrompt")]
+    pub system_prompt: Option<String>,
+    #[serde(alias = "maxIterations")]
+    pub max_iterations: Option<usize>,
+}
+
+impl AgentPreset {
+    pub fn new(name: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    pub fn small_model(mut self, model: impl Into<String>) -> Self {
+        self.small_model = Some(model.into());
+        self
+    }
+
+    pub fn tools(mut self, tools: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.tools = Some(tools.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn disallowed_tools(mut self, tools: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.disallowed_tools = tools.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn permission_mode(mut self, mode: PermissionMode) -> Self {
+        self.permission_mode = Some(mode);
+        self
+    }
+
+    pub fn allow(mut self, patterns: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.allow.extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn deny(mut self, patterns: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.deny.extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn output_style(mut self, name: impl Into<String>) -> Self {
+        self.output_style = Some(name.into());
+        self
+    }
+
+    pub fn system_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.system_prompt = Some(prompt.into());
+        self
+    }
+
+    pub fn max_iterations(mut self, max: usize) -> Self {
+        self.max_iterations = Some(max);
+        self
+    }
+
+    /// Tool access described by `tools` and `disallowed_tools`.
+    pub fn tool_access(&self) -> ToolAccess {
+        match &self.tools {
+            Some(tools) => ToolAccess::only(
+                tools
+                    .iter()
+                    .filter(|t| !self.disallowed_tools.contains(t))
+                    .cloned(),
+            ),
+            None if self.disallowed_tools.is_empty() => ToolAccess::All,
+            None => ToolAccess::except(self.disallowed_tools.iter().cloned()),
+        }
+    }
+
+    /// Parse a preset file: YAML frontmatter, with the body as system prompt.
+    ///
+    /// The name defaults to `default_name` (usually the file stem).
+    pub fn parse(content: &str, default_name: &str) -> crate::Result<Self> {
+        let doc = parse_frontmatter::<Self>(content)?;
+        let mut preset = doc.frontmatter;
+        if preset.name.is_empty() {
+            preset.name = default_name.to_string();
+        }
+        if !doc.body.is_empty() {
+            preset.system_prompt = Some(match preset.system_prompt.take() {
+                Some(prompt) => format!("{prompt}\n\n{}", doc.body),
+                None => doc.body,
+            });
+        }
+        Ok(preset)
+    }
+
+    /// Apply the preset on top of `builder`.
+    pub fn apply(&self, mut builder: AgentBuilder) -> AgentBuilder {
+        if let Some(ref model) = self.model {
+            builder = builder.model(model);
+        }
+        if let Some(ref model) = self.small_model {
+            builder = builder.small_model(model);
+        }
+        if self.tools.is_some() || !self.disallowed_tools.is_empty() {
+            builder = builder.tools(self.tool_access());
+        }
+        if let Some(mode) = self.permission_mode {
+            builder = builder.permission_mode(mode);
+        }
+        for pattern in &self.allow {
+            builder = builder.allow_tool(pattern);
+        }
+        for pattern in &self.deny {
+            builder = builder.deny_tool(pattern);
+        }
+        if let Some(ref name) = self.output_style {
+            builder = match find_builtin(name) {
+                Some(style) => builder.output_style(style),
+                None => builder.output_style_name(name),
+            };
+        }
+        if let Some(ref prompt) = self.system_prompt {
+            builder = builder.append_system_prompt(prompt);
+        }
+        if let Some(max) = self.max_iterations {
+            builder = builder.max_iterations(max);
+        }
+        builder
+    }
+}
+
+/// Presets by name.
+#[derive(Debug, Clone, Default)]
+pub struct PresetRegistry {
+    presets: HashMap<String, AgentPreset>,
+}
+
+impl PresetRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry holding the [`builtin_presets`].
+    pub fn builtins() -> Self {
+        let mut registry = Self::new();
+        for preset in builtin_presets() {
+            registry.register(preset);
+        }
+        registry
+    }
+
+    /// Built-ins, then `~/.claude/presets`, then `<project>/.claude/presets`;
+    /// later sources replace presets of the same name.
+    pub async fn load_default(project_dir: Option<&Path>) -> crate::Result<Self> {
+        let mut registry = Self::builtins();
+        if let Some(home) = crate::common::home_dir() {
+            registry.load_dir(home.join(PRESETS_DIR)).await?;
+        }
+        if let Some(dir) = project_dir {
+            registry.load_dir(dir.join(PRESETS_DIR)).await?;
+        }
+        Ok(registry)
+    }
+
+    /// Register a preset, replacing any preset with the same name.
+    pub fn register(&mut self, preset: AgentPreset) {
+        self.presets.insert(preset.name.clone(), preset);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AgentPreset> {
+        self.presets.get(name)
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<_> = self.presets.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Load one preset file and register it.
+    pub async fn load_file(&mut self, path: impl AsRef<Path>) -> crate::Result<&AgentPreset> {
+        let path = path.as_ref();
+        let content = tokio::fs::read_to_string(path).await?;
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        let preset = AgentPreset::parse(&content, stem)
+            .map_err(|e| crate::Error::Config(format!("{}: {}", path.display(), e)))?;
+        let name = preset.name.clone();
+        self.register(preset);
+        Ok(&self.presets[&name])
+    }
+
+    /// Load every `*.md` file in `dir`. A missing directory loads nothing.
+    pub async fn load_dir(&mut self, dir: impl AsRef<Path>) -> crate::Result<usize> {
+        let dir = dir.as_ref();
+        if !dir.is_dir() {
+            return Ok(0);
+        }
+        let mut paths = Vec::new();
+        let mut entries = tokio::fs::read_dir(dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "md") {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        for path in &paths {
+            self.load_file(path).await?;
+        }
+        Ok(paths.len())
+    }
+
+    /// Builder configured by the named preset.
+    pub fn builder(&self, name: &str) -> crate::Result<AgentBuilder> {
+        let preset = self.get(name).ok_or_else(|| {
+            crate::Error::Config(format!(
+                "Unknown agent preset '{}' (available: {})",
+                name,
+                self.names().join(", ")
+            ))
+        })?;
+        Ok(preset.apply(AgentBuilder::new()))
+    }
+}
+
+/// Reviews changes without modifying anything.
+pub fn code_reviewer_preset() -> AgentPreset {
+    AgentPreset::new(
+        "code-reviewer",
+        "Read-only code review of the working tree and git history",
+    )
+    .tools(["Read", "Grep", "Glob", "Bash"])
+    .allow([
+        "Read",
+        "Grep",
+        "Glob",
+        "Bash(git diff:*)",
+        "Bash(git log:*)",
+        "Bash(git show:*)",
+        "Bash(git status:*)",
+    ])
+    .system_prompt(
+        "You are a code reviewer. Inspect the changes and report bugs, risky patterns, \
+         missing tests and unclear code, ordered by severity, with file and line references. \
+         Do not modify files.",
+    )
+}
+
+/// Investigates incidents from logs and system state.
+pub fn sre_triage_preset() -> AgentPreset {
+    AgentPreset::new(
+        "sre-triage",
+        "Incident triage from logs, metrics and read-only system commands",
+    )
+    .tools(["Read", "Grep", "Glob", "Bash", "TodoWrite"])
+    .allow([
+        "Read",
+        "Grep",
+        "Glob",
+        "TodoWrite",
+        "Bash(kubectl get:*)",
+        "Bash(kubectl describe:*)",
+        "Bash(kubectl logs:*)",
+        "Bash(journalctl:*)",
+        "Bash(systemctl status:*)",
+        "Bash(df:*)",
+        "Bash(free:*)",
+        "Bash(uptime:*)",
+    ])
+    .system_prompt(
+        "You are an SRE triaging an incident. Establish impact and timeline first, then \
+         narrow down the cause from evidence. Only run read-only commands. Finish with a \
+         summary of findings, the likely root cause and recommended next steps.",
+    )
+}
+
+/// Writes and updates documentation.
+pub fn docs_writer_preset() -> AgentPreset {
+    AgentPreset::new(
+        "docs-writer",
+        "Writes and updates documentation next to the code it describes",
+    )
+    .tools([
+        "Read",
+        "Grep",
+        "Glob",
+        "LS",
+        "Write",
+        "Edit",
+        "MultiEdit",
+        "TodoWrite",
+    ])
+    .permission_mode(PermissionMode::AcceptEdits)
+    .output_style("explanatory")
+    .system_prompt(
+        "You are a technical writer. Read the code before documenting it, match the tone and \
+         structure of existing docs, keep examples runnable and only change documentation files.",
+    )
+}
+
+/// All built-in presets.
+pub fn builtin_presets() -> Vec<AgentPreset> {
+    vec![
+        code_reviewer_preset(),
+        sre_triage_preset(),
+        docs_writer_preset(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_access() {
+        let preset = AgentPreset::new("p", "")
+            .tools(["Read", "Bash"])
+            .disallowed_tools(["Bash"]);
+        let access = preset.tool_access();
+        assert!(access.is_allowed("Read"));
+        assert!(!access.is_allowed("Bash"));
+
+        let access = AgentPreset::new("p", "")
+            .disallowed_tools(["Write"])
+            .tool_access();
+        assert!(access.is_allowed("Read"));
+        assert!(!access.is_allowed("Write"));
+    }
+
+    #[tokio::test]
+    async fn test_load_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("api-reviewer.md"),
+            "---\ndescription: Reviews API changes\nmodel: opus\ntools: [Read, Grep]\n\
+             permission_mode: plan\n---\nFocus on breaking changes.\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let mut registry = PresetRegistry::builtins();
+        assert_eq!(registry.load_dir(dir.path()).await.unwrap(), 1);
+
+        let preset = registry.get("api-reviewer").unwrap();
+        assert_eq!(preset.model.as_deref(), Some("opus"));
+        assert_eq!(preset.permission_mode, Some(PermissionMode::Plan));
+        assert_eq!(
+            preset.system_prompt.as_deref(),
+            Some("Focus on breaking changes.")
+        );
+        assert_eq!(
+            registry.names(),
+            vec!["api-reviewer", "code-reviewer", "docs-writer", "sre-triage"]
+        );
+        assert!(matches!(
+            registry.builder("missing"),
+            Err(crate::Error::Config(_))
+        ));
+    }
+}
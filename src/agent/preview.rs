@@ -0,0 +1,410 @@
+//! Preview mode: show file changes as diffs and apply them only once approved.
+
+use std::fmt;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::common::PendingToolCall;
+use super::plan::{StagedFiles, file_diff, has_file_diff};
+use crate::tools::ToolRegistry;
+use crate::types::{PermissionDenial, ToolResultBlock};
+
+/// Tools whose calls are previewed before they touch disk.
+pub const PREVIEW_TOOLS: &[&str] = crate::permissions::WRITE_TOOLS;
+
+/// A proposed file change awaiting a decision.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolPreview {
+    pub tool_use_id: String,
+    pub name: String,
+    pub input: Value,
+    /// Unified diff of the change; empty for tools whose change cannot be
+    /// diffed ahead of time (`NotebookEdit`, `AstEdit`), so review `input`.
+    /// When the diff fails, e.g. the file is binary or too large to read,
+    /// this holds the reason instead.
+    pub diff: String,
+}
+
+/// Whether a previewed change may be applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreviewDecision {
+    Apply,
+    Reject(String),
+}
+
+/// Decides whether previewed changes are applied, e.g. by asking a user.
+#[async_trait]
+pub trait PreviewApprover: Send + Sync {
+    async fn review(&self, preview: &ToolPreview) -> PreviewDecision;
+}
+
+/// How previewed changes are handled.
+#[derive(Clone)]
+pub enum PreviewPolicy {
+    /// Never apply; the diff is only suggested.
+    SuggestOnly,
+    /// Apply every change after emitting its preview.
+    AutoApply,
+    /// Apply the changes the approver accepts.
+    Approve(Arc<dyn PreviewApprover>),
+}
+
+impl PreviewPolicy {
+    pub fn approver(approver: impl PreviewApprover + 'static) -> Self {
+        Self::Approve(Arc::new(approver))
+    }
+
+    async fn decide(&self, preview: &ToolPreview) -> PreviewDecision {
+        match self {
+            Self::SuggestOnly => {
+                PreviewDecision::Reject("changes are suggested for review only".into())
+            }
+            Self::AutoApply => PreviewDecision::Apply,
+            Self::Approve(approver) => approver.review(preview).await,
+        }
+    }
+}
+
+impl fmt::Debug for PreviewPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SuggestOnly => f.write_str("SuggestOnly"),
+            Self::AutoApply => f.write_str("AutoApply"),
+            Self::Approve(_) => f.write_str("Approve(..)"),
+        }
+    }
+}
+
+/// A previewed call and, if it was rejected, the reason.
+pub(crate) struct ReviewedCall {
+    pub preview: ToolPreview,
+    pub rejection: Option<String>,
+}
+
+impl ReviewedCall {
+    /// Tool result returned to the model for a rejected change.
+    pub(crate) fn result_block(&self, reason: &str) -> ToolResultBlock {
+        ToolResultBlock::error(
+            &self.preview.tool_use_id,
+            format!(
+                "{} was not applied: {}. The proposed diff was shown to the user.",
+                self.preview.name, reason
+            ),
+        )
+    }
+
+    pub(crate) fn denial(&self, reason: &str) -> PermissionDenial {
+        PermissionDenial::new(
+            &self.preview.name,
+            &self.preview.tool_use_id,
+            self.preview.input.clone(),
+        )
+        .reason(reason)
+    }
+}
+
+/// Whether `call` changes files and is therefore previewed. The text
+/// editor's `view` command only reads.
+fn is_previewed(call: &PendingToolCall, tools: &ToolRegistry) -> bool {
+    PREVIEW_TOOLS.contains(&tools.canonical_name(&call.name))
+        && call.input.get("command").and_then(Value::as_str) != Some("view")
+}
+
+/// Notification text when `policy` will ask the approver about any of `calls`.
+pub(crate) fn approval_message(
+    calls: &[PendingToolCall],
+    policy: &PreviewPolicy,
+    tools: &ToolRegistry,
+) -> Option<String> {
+    if !matches!(policy, PreviewPolicy::Approve(_)) {
+        return None;
+    }
+    let names: Vec<&str> = calls
+        .iter()
+        .filter(|call| is_previewed(call, tools))
+        .map(|call| call.name.as_str())
+        .collect();
+    if names.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "Approval needed for {} file change(s): {}",
+        names.len(),
+        names.join(", ")
+    ))
+}
+
+/// Preview the file changes in `calls` and keep only those the policy applies.
+///
+/// Calls to other tools pass through untouched. Write tools that have no
+/// diff are still reviewed, with an empty diff, and so are changes whose diff
+/// fails, with the reason in its place. A change to a file an earlier applied
+/// call also changes is diffed against that call's result.
+pub(crate) async fn review_tool_calls(
+    calls: &mut Vec<PendingToolCall>,
+    policy: &PreviewPolicy,
+    tools: &ToolRegistry,
+) -> Vec<ReviewedCall> {
+    let mut reviewed = Vec::new();
+    let mut kept = Vec::with_capacity(calls.len());
+    let mut staged = StagedFiles::default();
+    for call in std::mem::take(calls) {
+        if !is_previewed(&call, tools) {
+            kept.push(call);
+            continue;
+        }
+        let name = tools.canonical_name(&call.name);
+        let (diff, change) = if has_file_diff(name, &call.input) {
+            match file_diff(name, &call.input, tools.get_context(), &staged).await {
+                Ok(change) => (change.diff.clone(), Some(change)),
+                Err(e) => (format!("Diff unavailable: {}", e), None),
+            }
+        } else {
+            (String::new(), None)
+        };
+        let preview = ToolPreview {
+            tool_use_id: call.id.clone(),
+            name: call.name.clone(),
+            input: call.input.clone(),
+            diff,
+        };
+        let rejection = match policy.decide(&preview).await {
+            PreviewDecision::Apply => {
+                if let Some(change) = change {
+                    staged.stage(change);
+                }
+                kept.push(call);
+                None
+            }
+            PreviewDecision::Reject(reason) => Some(reason),
+        };
+        reviewed.push(ReviewedCall { preview, rejection });
+    }
+    *calls = kept;
+    reviewed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{ExecutionContext, WriteTool};
+
+    struct OnlyFile(&'static str);
+
+    #[async_trait]
+    impl PreviewApprover for OnlyFile {
+        async fn review(&self, preview: &ToolPreview) -> PreviewDecision {
+            if preview.input["file_path"] == self.0 {
+                PreviewDecision::Apply
+            } else {
+                PreviewDecision::Reject("not approved".into())
+            }
+        }
+    }
+
+    fn write(id: &str, path: &str) -> PendingToolCall {
+        PendingToolCall {
+            id: id.into(),
+            name: "Write".into(),
+            input: serde_json::json!({"file_path": path, "content": "new\n"}),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_review_tool_calls() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut calls = vec![
+            write("toolu_1", "a.txt"),
+            PendingToolCall {
+                id: "toolu_2".into(),
+                name: "Bash".into(),
+                input: serde_json::json!({"command": "ls"}),
+            },
+            write("toolu_3", "b.txt"),
+        ];
+
+        let reviewed = review_tool_calls(
+            &mut calls,
+            &PreviewPolicy::approver(OnlyFile("a.txt")),
+            &ToolRegistry::from_context(ExecutionContext::from_path(dir.path()).unwrap()),
+        )
+        .await;
+
+        let ids: Vec<_> = calls.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, ["toolu_1", "toolu_2"]);
+        assert_eq!(reviewed.len(), 2);
+        assert!(reviewed[0].preview.diff.contains("+new"));
+        assert!(reviewed[0].rejection.is_none());
+        assert_eq!(reviewed[1].rejection.as_deref(), Some("not approved"));
+    }
+
+    #[tokio::test]
+    async fn test_text_editor_changes_are_previewed() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo\n").unwrap();
+        let editor = |id: &str, input: Value| PendingToolCall {
+            id: id.into(),
+            name: "str_replace_based_edit_tool".into(),
+            input,
+        };
+        let mut calls = vec![
+            editor(
+                "toolu_1",
+                serde_json::json!({"command": "view", "path": "a.txt"}),
+            ),
+            editor(
+                "toolu_2",
+                serde_json::json!({"command": "str_replace", "path": "a.txt", "old_str": "two", "new_str": "2"}),
+            ),
+            editor(
+                "toolu_3",
+                serde_json::json!({"command": "insert", "path": "a.txt", "insert_line": 0, "new_str": "zero"}),
+            ),
+        ];
+
+        let reviewed = review_tool_calls(
+            &mut calls,
+            &PreviewPolicy::SuggestOnly,
+            &ToolRegistry::from_context(ExecutionContext::from_path(dir.path()).unwrap()),
+        )
+        .await;
+
+        let ids: Vec<_> = calls.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, ["toolu_1"]);
+        assert!(reviewed[0].preview.diff.contains("-two\n+2"));
+        assert!(reviewed[1].preview.diff.contains("+zero\n one"));
+    }
+
+    #[tokio::test]
+    async fn test_changes_without_diff_are_reviewed() {
+        let mut calls = vec![
+            PendingToolCall {
+                id: "toolu_1".into(),
+                name: "NotebookEdit".into(),
+                input: serde_json::json!({"notebook_path": "a.ipynb", "new_source": "x = 1"}),
+            },
+            PendingToolCall {
+                id: "toolu_2".into(),
+                name: "Edit".into(),
+                input: serde_json::json!({"file_path": "/nonexistent/a.txt", "old_string": "x", "new_string": "y"}),
+            },
+        ];
+
+        let reviewed = review_tool_calls(
+            &mut calls,
+            &PreviewPolicy::SuggestOnly,
+            &ToolRegistry::from_context(ExecutionContext::permissive()),
+        )
+        .await;
+
+        assert_eq!(reviewed.len(), 2);
+        assert_eq!(reviewed[0].preview.name, "NotebookEdit");
+        assert!(reviewed[0].preview.diff.is_empty());
+        assert!(reviewed[0].rejection.is_some());
+        assert!(reviewed[1].preview.diff.starts_with("Diff unavailable:"));
+        assert!(reviewed[1].rejection.is_some());
+        assert!(calls.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_file_is_reviewed() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.bin"), [0xff, 0xfe, 0x00]).unwrap();
+        let mut calls = vec![write("toolu_1", "a.bin")];
+
+        let reviewed = review_tool_calls(
+            &mut calls,
+            &PreviewPolicy::approver(OnlyFile("b.txt")),
+            &ToolRegistry::from_context(ExecutionContext::from_path(dir.path()).unwrap()),
+        )
+        .await;
+
+        assert!(calls.is_empty());
+        assert!(reviewed[0].preview.diff.starts_with("Diff unavailable:"));
+        assert_eq!(reviewed[0].rejection.as_deref(), Some("not approved"));
+    }
+
+    #[test]
+    fn test_approval_message() {
+        let calls = vec![write("toolu_1", "a.txt"), write("toolu_2", "b.txt")];
+        let policy = PreviewPolicy::approver(OnlyFile("a.txt"));
+        let tools = ToolRegistry::new();
+        assert_eq!(
+            approval_message(&calls, &policy, &tools).as_deref(),
+            Some("Approval needed for 2 file change(s): Write, Write")
+        );
+        assert!(approval_message(&calls, &PreviewPolicy::AutoApply, &tools).is_none());
+        assert!(approval_message(&[], &policy, &tools).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_same_file_edits_diff_in_sequence() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo\n").unwrap();
+        let edit = |id: &str, old: &str, new: &str| PendingToolCall {
+            id: id.into(),
+            name: "Edit".into(),
+            input: serde_json::json!({"file_path": "a.txt", "old_string": old, "new_string": new}),
+        };
+        let tools = ToolRegistry::from_context(ExecutionContext::from_path(dir.path()).unwrap());
+
+        let mut calls = vec![edit("toolu_1", "one", "1"), edit("toolu_2", "1", "uno")];
+        let reviewed = review_tool_calls(&mut calls, &PreviewPolicy::AutoApply, &tools).await;
+        assert_eq!(calls.len(), 2);
+        assert!(reviewed[0].preview.diff.contains("-one\n+1"));
+        assert!(reviewed[1].preview.diff.contains("-1\n+uno"));
+
+        let mut calls = vec![edit("toolu_1", "one", "1"), edit("toolu_2", "1", "uno")];
+        let reviewed = review_tool_calls(&mut calls, &PreviewPolicy::SuggestOnly, &tools).await;
+        assert!(reviewed[0].preview.diff.contains("-one\n+1"));
+        assert!(reviewed[1].preview.diff.starts_with("Diff unavailable:"));
+    }
+
+    #[tokio::test]
+    async fn test_aliased_changes_are_previewed() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut tools =
+            ToolRegistry::from_context(ExecutionContext::from_path(dir.path()).unwrap());
+        tools.register(Arc::new(WriteTool));
+        tools.alias("write_file", "Write");
+        let mut calls = vec![PendingToolCall {
+            name: "write_file".into(),
+            ..write("toolu_1", "a.txt")
+        }];
+
+        assert!(
+            approval_message(&calls, &PreviewPolicy::approver(OnlyFile("a.txt")), &tools).is_some()
+        );
+        let reviewed = review_tool_calls(&mut calls, &PreviewPolicy::SuggestOnly, &tools).await;
+
+        assert!(calls.is_empty());
+        assert_eq!(reviewed[0].preview.name, "write_file");
+        assert!(reviewed[0].preview.diff.contains("+new"));
+        assert!(reviewed[0].rejection.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_suggest_only_rejects_all_changes() {
+        let mut calls = vec![write("toolu_1", "/tmp/x.txt")];
+        let reviewed = review_tool_calls(
+            &mut calls,
+            &PreviewPolicy::SuggestOnly,
+            &ToolRegistry::from_context(ExecutionContext::permissive()),
+        )
+        .await;
+        assert!(calls.is_empty());
+        assert!(reviewed[0].rejection.is_some());
+
+        let mut calls = vec![write("toolu_1", "/tmp/x.txt")];
+        review_tool_calls(
+            &mut calls,
+            &PreviewPolicy::AutoApply,
+            &ToolRegistry::from_context(ExecutionContext::permissive()),
+        )
+        .await;
+        assert_eq!(calls.len(), 1);
+    }
+}
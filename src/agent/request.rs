@@ -4,11 +4,12 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use crate::agent::config::{AgentConfig, CacheConfig, ServerToolsConfig, SystemPromptMode};
-use crate::client::messages::CreateMessageRequest;
+use crate::client::messages::{ApiTool, CreateMessageRequest};
 use crate::output_style::{OutputStyle, SystemPromptGenerator};
+use crate::security::PiiScrubber;
 use crate::tools::ToolRegistry;
 use crate::tools::search::{PreparedTools, SearchMode};
-use crate::types::{Message, SystemBlock, SystemPrompt, ToolSearchTool};
+use crate::types::{ContentBlock, Message, SystemBlock, SystemPrompt, ToolSearchTool};
 
 pub struct RequestBuilder {
     model: String,
@@ -23,6 +24,7 @@ pub struct RequestBuilder {
     prepared_mcp_tools: Option<PreparedTools>,
     /// JSON schema for structured output
     output_schema: Option<serde_json::Value>,
+    pii_scrubber: Option<PiiScrubber>,
 }
 
 impl RequestBuilder {
@@ -45,6 +47,7 @@ impl RequestBuilder {
             cache_config: config.cache.clone(),
             prepared_mcp_tools: None,
             output_schema: config.prompt.output_schema.clone(),
+            pii_scrubber: config.security.pii_scrubber.clone(),
         }
     }
 
@@ -53,11 +56,36 @@ impl RequestBuilder {
         self
     }
 
+    /// Send the full definitions of deferred MCP tools found by tool search
+    /// in later requests.
+    pub fn load_discovered_tools(&mut self, content: &[ContentBlock]) {
+        let Some(prepared) = self.prepared_mcp_tools.as_mut() else {
+            return;
+        };
+        let names: Vec<&str> = content
+            .iter()
+            .filter_map(ContentBlock::as_tool_search_result)
+            .flat_map(|block| block.tool_names())
+            .collect();
+        if !names.is_empty() {
+            let loaded = prepared.load(&names);
+            tracing::debug!(loaded, "Loaded tools found by tool search");
+        }
+    }
+
+    pub fn output_schema(mut self, schema: Option<serde_json::Value>) -> Self {
+        self.output_schema = schema;
+        self
+    }
+
     pub fn set_model(&mut self, model: &str) {
         self.model = model.to_string();
     }
 
-    pub fn build(&self, messages: Vec<Message>, dynamic_rules: &str) -> CreateMessageRequest {
+    pub fn build(&self, mut messages: Vec<Message>, dynamic_rules: &str) -> CreateMessageRequest {
+        if let Some(scrubber) = &self.pii_scrubber {
+            scrubber.scrub_messages(&mut messages);
+        }
         let system_prompt = self.build_system_prompt_blocks(dynamic_rules);
 
         let mut request = CreateMessageRequest::new(&self.model, messages)
@@ -68,10 +96,10 @@ impl RequestBuilder {
         request = match &self.prepared_mcp_tools {
             Some(prepared) => {
                 // Progressive Disclosure mode: separate built-in and MCP tools
-                let registry_tools = self.tools.definitions();
+                let registry_tools = self.tools.api_tools();
                 let builtin_tools: Vec<_> = registry_tools
                     .into_iter()
-                    .filter(|t| !crate::mcp::is_mcp_name(&t.name))
+                    .filter(|t| !crate::mcp::is_mcp_name(t.name()))
                     .collect();
 
                 // Pre-allocate capacity to avoid reallocations
@@ -83,20 +111,22 @@ impl RequestBuilder {
                 tools.extend(builtin_tools);
 
                 // 2. Immediate MCP tools (full schema, no defer_loading)
-                tools.extend(prepared.immediate.iter().cloned());
+                tools.extend(prepared.immediate.iter().cloned().map(ApiTool::Custom));
 
                 // 3. Deferred MCP tools (full schema, defer_loading: true)
-                tools.extend(prepared.deferred.iter().cloned());
+                tools.extend(prepared.deferred.iter().cloned().map(ApiTool::Custom));
 
                 if !tools.is_empty() {
-                    request = request.tools(tools);
+                    request = request.api_tools(tools);
                 }
 
                 // Add ToolSearchTool when threshold exceeded
                 if prepared.use_search {
                     let tool_search = match prepared.search_mode {
                         SearchMode::Regex => ToolSearchTool::regex(),
-                        SearchMode::Bm25 => ToolSearchTool::bm25(),
+                        // Tools relevant to the prompt are already loaded; the
+                        // model searches the rest by keyword.
+                        SearchMode::Bm25 | SearchMode::Semantic => ToolSearchTool::bm25(),
                     };
                     request = request.tool_search(tool_search);
                 }
@@ -105,9 +135,9 @@ impl RequestBuilder {
             }
             None => {
                 // Standard mode: all tools from registry
-                let tool_defs = self.tools.definitions();
-                if !tool_defs.is_empty() {
-                    request.tools(tool_defs)
+                let api_tools = self.tools.api_tools();
+                if !api_tools.is_empty() {
+                    request.api_tools(api_tools)
                 } else {
                     request
                 }
@@ -119,11 +149,17 @@ impl RequestBuilder {
             request = request.web_search(web_search);
         }
 
-        if self.tool_access.is_allowed("WebFetch") {
+        // A registered client-side WebFetch (HttpFetchTool or BrowserFetchTool)
+        // replaces the server tool.
+        if self.tool_access.is_allowed("WebFetch") && !self.tools.contains("WebFetch") {
             let web_fetch = self.server_tools.web_fetch.clone().unwrap_or_default();
             request = request.web_fetch(web_fetch);
         }
 
+        if let Some(ref code_execution) = self.server_tools.code_execution {
+            request = request.code_execution(code_execution.clone());
+        }
+
         // Add structured output schema if configured
         if let Some(ref schema) = self.output_schema {
             request = request.json_schema(schema.clone());
@@ -3,6 +3,9 @@
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
+use super::plan::PlannedToolCall;
+use crate::client::CreateMessageRequest;
+
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum AgentState {
@@ -15,11 +18,16 @@ pub enum AgentState {
     Completed,
     Failed,
     Cancelled,
+    /// Stopped early because the run's cost ceiling would have been exceeded.
+    BudgetExceeded,
 }
 
 impl AgentState {
     pub fn is_terminal(&self) -> bool {
-        matches!(self, Self::Completed | Self::Failed | Self::Cancelled)
+        matches!(
+            self,
+            Self::Completed | Self::Failed | Self::Cancelled | Self::BudgetExceeded
+        )
     }
 
     pub fn is_waiting(&self) -> bool {
@@ -44,6 +52,8 @@ pub struct AgentMetrics {
     pub output_tokens: u32,
     pub cache_read_tokens: u32,
     pub cache_creation_tokens: u32,
+    /// API calls that read part of their prompt from the cache.
+    pub cache_hits: usize,
     pub execution_time_ms: u64,
     pub errors: usize,
     pub compactions: usize,
@@ -55,6 +65,10 @@ pub struct AgentMetrics {
     pub server_tool_use: ServerToolUse,
     pub permission_denials: Vec<PermissionDenial>,
     pub api_time_ms: u64,
+    /// Beta flags sent with each API call, in call order.
+    pub applied_betas: Vec<Vec<String>>,
+    /// Tool calls recorded instead of executed in dry-run mode.
+    pub planned_tool_calls: Vec<PlannedToolCall>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -99,6 +113,33 @@ impl AgentMetrics {
         self.cache_read_tokens as f64 / self.input_tokens as f64
     }
 
+    /// Record the prompt-cache outcome of one API call.
+    pub fn record_cache_usage(&mut self, usage: &Usage) {
+        if usage.cache_read_input_tokens.unwrap_or(0) > 0 {
+            self.cache_hits += 1;
+        }
+    }
+
+    /// Share of API calls that hit the prompt cache.
+    pub fn cache_call_hit_rate(&self) -> f64 {
+        if self.api_calls == 0 {
+            return 0.0;
+        }
+        self.cache_hits as f64 / self.api_calls as f64
+    }
+
+    /// Share of all prompt tokens (uncached, cache writes and cache reads)
+    /// that were served from the cache.
+    pub fn prompt_cache_hit_rate(&self) -> f64 {
+        let total = self.input_tokens as u64
+            + self.cache_read_tokens as u64
+            + self.cache_creation_tokens as u64;
+        if total == 0 {
+            return 0.0;
+        }
+        self.cache_read_tokens as f64 / total as f64
+    }
+
     /// Calculate cache efficiency (reads vs total cache operations).
     ///
     /// Returns 1.0 for perfect cache reuse (all reads, no writes),
@@ -170,6 +211,15 @@ impl AgentMetrics {
         self.api_calls += 1;
     }
 
+    pub fn record_applied_betas(&mut self, request: &CreateMessageRequest) {
+        let flags = request
+            .betas
+            .as_ref()
+            .map(|betas| betas.flags())
+            .unwrap_or_default();
+        self.applied_betas.push(flags);
+    }
+
     pub fn record_compaction(&mut self) {
         self.compactions += 1;
     }
@@ -217,6 +267,10 @@ impl AgentMetrics {
         self.permission_denials.push(denial);
     }
 
+    pub fn record_planned_tool_call(&mut self, planned: PlannedToolCall) {
+        self.planned_tool_calls.push(planned);
+    }
+
     /// Get the total cost across all models.
     pub fn total_model_cost(&self) -> Decimal {
         self.model_usage.values().map(|m| m.cost_usd).sum()
@@ -289,6 +343,23 @@ mod tests {
         assert!((metrics.avg_tool_time_ms() - 150.0).abs() < 0.1);
     }
 
+    #[test]
+    fn test_agent_metrics_applied_betas() {
+        use crate::client::{BetaConfig, BetaFeature};
+        use crate::types::Message;
+
+        let mut metrics = AgentMetrics::default();
+        let request = CreateMessageRequest::new("model", vec![Message::user("Hi")]);
+        metrics.record_applied_betas(&request);
+        metrics.record_applied_betas(
+            &request.betas(BetaConfig::new().feature(BetaFeature::WebSearch)),
+        );
+
+        assert_eq!(metrics.applied_betas.len(), 2);
+        assert!(metrics.applied_betas[0].is_empty());
+        assert_eq!(metrics.applied_betas[1], vec!["web-search-2025-03-05"]);
+    }
+
     #[test]
     fn test_cache_efficiency_no_activity() {
         let metrics = AgentMetrics::default();
@@ -349,6 +420,33 @@ mod tests {
         assert!((metrics.cache_hit_rate() - 0.8).abs() < 0.001);
     }
 
+    #[test]
+    fn test_cache_hit_rates() {
+        let mut metrics = AgentMetrics::default();
+        for usage in [
+            Usage {
+                input_tokens: 100,
+                cache_creation_input_tokens: Some(2000),
+                ..Default::default()
+            },
+            Usage {
+                input_tokens: 100,
+                cache_read_input_tokens: Some(2000),
+                ..Default::default()
+            },
+        ] {
+            metrics.record_api_call();
+            metrics.add_usage_with_cache(&usage);
+            metrics.record_cache_usage(&usage);
+        }
+
+        assert_eq!(metrics.cache_hits, 1);
+        assert!((metrics.cache_call_hit_rate() - 0.5).abs() < 0.001);
+        // 2000 / (200 + 2000 + 2000)
+        assert!((metrics.prompt_cache_hit_rate() - 0.476).abs() < 0.001);
+        assert_eq!(AgentMetrics::default().prompt_cache_hit_rate(), 0.0);
+    }
+
     #[test]
     fn test_cache_tokens_saved() {
         let metrics = AgentMetrics {
@@ -1,26 +1,39 @@
 //! Agent streaming execution with session-based context management.
 
+use std::collections::VecDeque;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Instant;
 
 use futures::{Stream, StreamExt, stream};
-use tokio::sync::RwLock;
+use rust_decimal::Decimal;
+use tokio::sync::{RwLock, broadcast};
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, warn};
 
 use super::common::{
-    BudgetContext, accumulate_inner_usage, accumulate_response_usage, handle_compaction,
-    run_post_tool_hooks, run_stop_hooks, try_activate_dynamic_rules,
+    BudgetContext, PendingToolCall, accumulate_inner_usage, accumulate_response_usage,
+    apply_interjections, budget_warning, cancellable, deprecation_events, exceeds_run_ceiling,
+    execute_tool_batch, finalize_cancelled, handle_compaction, idle_message, mcp_events,
+    notify_when_idle, persist_session, plan_tool_calls, progress_event, run_notification_hooks,
+    run_post_tool_hooks, run_stop_hooks, tool_batch_end, try_activate_dynamic_rules,
+    with_hook_context,
 };
+use super::event_sink::{EventRecorder, EventSink};
 use super::events::{AgentEvent, AgentResult};
 use super::executor::Agent;
+use super::preview::{approval_message, review_tool_calls};
 use super::request::RequestBuilder;
-use super::{AgentConfig, AgentMetrics};
+use super::{AgentConfig, AgentMetrics, AgentState};
 use crate::budget::{BudgetTracker, TenantBudget};
 use crate::client::{RecoverableStream, StreamItem};
+use crate::common::clock;
 use crate::context::PromptOrchestrator;
-use crate::hooks::{HookContext, HookEvent, HookInput, HookManager};
-use crate::session::ToolState;
+use crate::hooks::{HookContext, HookEvent, HookInput, HookManager, NotificationKind};
+use crate::mcp::McpNotification;
+use crate::observability::MetricsRegistry;
+use crate::security::{PiiStreamRestorer, StreamRedactor};
+use crate::session::{SessionManager, ToolState};
 use crate::types::{
     ContentBlock, PermissionDenial, StopReason, StreamEvent, ToolResultBlock, ToolUseBlock, Usage,
     context_window,
@@ -47,6 +60,10 @@ impl Agent {
                 .await
                 .map_err(|e| crate::Error::Session(format!("Queue full: {}", e)))?;
         }
+        let mut request_builder = RequestBuilder::new(&self.config, Arc::clone(&self.tools));
+        if let Some(ref tsm) = self.tool_search_manager {
+            request_builder = request_builder.prepared_tools(tsm.prepare_tools_for(prompt).await);
+        }
         let state = StreamState::new(
             StreamStateConfig {
                 tool_state: self.state.clone(),
@@ -55,11 +72,16 @@ impl Agent {
                 tools: Arc::clone(&self.tools),
                 hooks: Arc::clone(&self.hooks),
                 hook_context: self.hook_context(),
-                request_builder: RequestBuilder::new(&self.config, Arc::clone(&self.tools)),
+                request_builder,
                 orchestrator: self.orchestrator.clone(),
                 session_id: Arc::clone(&self.session_id),
                 budget_tracker: Arc::clone(&self.budget_tracker),
                 tenant_budget: self.tenant_budget.clone(),
+                cancel_token: self.cancel_token.clone(),
+                event_sinks: Arc::clone(&self.event_sinks),
+                metrics_registry: self.metrics_registry.clone(),
+                session_manager: self.session_manager.clone(),
+                mcp_notifications: self.mcp_manager.as_ref().map(|m| m.notifications()),
             },
             timeout,
             prompt.to_string(),
@@ -83,6 +105,11 @@ struct StreamStateConfig {
     session_id: Arc<str>,
     budget_tracker: Arc<BudgetTracker>,
     tenant_budget: Option<Arc<TenantBudget>>,
+    cancel_token: CancellationToken,
+    event_sinks: Arc<[Arc<dyn EventSink>]>,
+    metrics_registry: Option<Arc<MetricsRegistry>>,
+    session_manager: Option<SessionManager>,
+    mcp_notifications: Option<broadcast::Receiver<McpNotification>>,
 }
 
 enum StreamPollResult {
@@ -114,18 +141,40 @@ struct StreamState {
     last_chunk_time: Instant,
     pending_tool_results: Vec<ToolResultBlock>,
     pending_tool_uses: Vec<ToolUseBlock>,
+    pending_events: VecDeque<AgentEvent>,
     final_text: String,
+    /// Maps PII tokens in streamed text back to the real values.
+    pii_restorer: Option<PiiStreamRestorer>,
+    /// Holds back streamed text until it can be redacted whole.
+    text_redactor: Option<StreamRedactor>,
     total_usage: Usage,
+    last_call_cost: Decimal,
     phase: Phase,
     session_started: bool,
     prompt_submitted: bool,
     initial_prompt: Option<String>,
+    /// Set once the run reaches a terminal event; an unfinished run is cleaned up on drop.
+    finished: bool,
+    recorder: EventRecorder,
 }
 
 impl StreamState {
     fn new(cfg: StreamStateConfig, timeout: std::time::Duration, prompt: String) -> Self {
         let chunk_timeout = cfg.config.execution.chunk_timeout;
-        let now = Instant::now();
+        let recorder = EventRecorder::new(Arc::clone(&cfg.event_sinks), &cfg.session_id);
+        let pii_restorer = cfg
+            .config
+            .security
+            .pii_scrubber
+            .clone()
+            .map(PiiStreamRestorer::new);
+        let text_redactor = cfg
+            .config
+            .execution
+            .secret_redaction
+            .clone()
+            .map(StreamRedactor::new);
+        let now = clock::now();
         Self {
             cfg,
             timeout,
@@ -136,12 +185,18 @@ impl StreamState {
             last_chunk_time: now,
             pending_tool_results: Vec::new(),
             pending_tool_uses: Vec::new(),
+            pending_events: VecDeque::new(),
             final_text: String::new(),
+            pii_restorer,
+            text_redactor,
             total_usage: Usage::default(),
+            last_call_cost: Decimal::ZERO,
             phase: Phase::StartRequest,
             session_started: false,
             prompt_submitted: false,
             initial_prompt: Some(prompt),
+            finished: false,
+            recorder,
         }
     }
 
@@ -172,12 +227,42 @@ impl StreamState {
     }
 
     async fn next_event(&mut self) -> Option<crate::Result<AgentEvent>> {
+        let token = self.cfg.cancel_token.clone();
+        let event = match cancellable(&token, self.advance()).await {
+            Ok(event) => event,
+            Err(e) if self.finished => {
+                debug!(error = %e, "Cancellation after stream completed");
+                None
+            }
+            Err(e) => {
+                self.phase = Phase::Done;
+                finalize_cancelled(&self.cfg.tool_state, &self.cfg.tools).await;
+                Some(Err(e))
+            }
+        };
+        if matches!(self.phase, Phase::Done) {
+            self.finished = true;
+        }
+        if let Some(Ok(ref event)) = event {
+            if matches!(event, AgentEvent::Complete(_)) {
+                persist_session(&self.cfg.tool_state, self.cfg.session_manager.as_ref()).await;
+            }
+            self.recorder.emit(self.metrics.iterations, event).await;
+        }
+        event
+    }
+
+    async fn advance(&mut self) -> Option<crate::Result<AgentEvent>> {
         loop {
+            if let Some(event) = self.pending_events.pop_front() {
+                return Some(Ok(event));
+            }
+
             if matches!(self.phase, Phase::Done) {
                 return None;
             }
 
-            if self.start_time.elapsed() > self.timeout {
+            if clock::elapsed(self.start_time) > self.timeout {
                 self.phase = Phase::Done;
                 return Some(Err(crate::Error::Timeout(self.timeout)));
             }
@@ -208,6 +293,10 @@ impl StreamState {
                             self.phase = Phase::StreamEnded {
                                 accumulated_usage: streaming.accumulated_usage,
                             };
+                            let held = self.flush_text();
+                            if !held.is_empty() {
+                                return Some(Ok(AgentEvent::Text(held)));
+                            }
                         }
                     }
                 }
@@ -242,11 +331,32 @@ impl StreamState {
         None
     }
 
+    /// Stop before the next request, reporting the iterations completed so far.
+    async fn finish_early(&mut self, stop_reason: StopReason) -> AgentResult {
+        self.phase = Phase::Done;
+        self.metrics.execution_time_ms = clock::elapsed(self.start_time).as_millis() as u64;
+
+        run_stop_hooks(
+            &self.cfg.hooks,
+            &self.cfg.hook_context,
+            &self.cfg.session_id,
+        )
+        .await;
+
+        let messages = self
+            .cfg
+            .tool_state
+            .with_session(|session| session.to_api_messages())
+            .await;
+        self.build_result(self.metrics.iterations - 1, stop_reason, messages)
+    }
+
     async fn do_start_request(&mut self) -> Option<crate::Result<AgentEvent>> {
+        let mut session_context = None;
         if !self.session_started {
             self.session_started = true;
             let session_start_input = HookInput::session_start(&*self.cfg.session_id);
-            if let Err(e) = self
+            match self
                 .cfg
                 .hooks
                 .execute(
@@ -256,7 +366,8 @@ impl StreamState {
                 )
                 .await
             {
-                warn!(error = %e, "SessionStart hook failed");
+                Ok(output) => session_context = output.additional_context,
+                Err(e) => warn!(error = %e, "SessionStart hook failed"),
             }
         }
 
@@ -288,6 +399,8 @@ impl StreamState {
                             .unwrap_or_else(|| "Blocked by hook".into()),
                     )));
                 }
+                let prompt =
+                    with_hook_context(prompt, [session_context, prompt_output.additional_context]);
 
                 self.cfg
                     .tool_state
@@ -301,23 +414,16 @@ impl StreamState {
 
         self.metrics.iterations += 1;
         if self.metrics.iterations > self.cfg.config.execution.max_iterations {
-            self.phase = Phase::Done;
-            self.metrics.execution_time_ms = self.start_time.elapsed().as_millis() as u64;
-
-            run_stop_hooks(
-                &self.cfg.hooks,
-                &self.cfg.hook_context,
-                &self.cfg.session_id,
-            )
-            .await;
-
-            let messages = self
-                .cfg
-                .tool_state
-                .with_session(|session| session.to_api_messages())
-                .await;
-            let result =
-                self.build_result(self.metrics.iterations - 1, StopReason::MaxTokens, messages);
+            let result = self.finish_early(StopReason::MaxTokens).await;
+            return Some(Ok(AgentEvent::Complete(Box::new(result))));
+        }
+        if exceeds_run_ceiling(
+            self.cfg.config.budget.max_cost_per_run_usd,
+            self.metrics.total_cost_usd,
+            self.last_call_cost,
+        ) {
+            let mut result = self.finish_early(StopReason::EndTurn).await;
+            result.state = AgentState::BudgetExceeded;
             return Some(Ok(AgentEvent::Complete(Box::new(result))));
         }
 
@@ -338,11 +444,19 @@ impl StreamState {
             })
             .await;
 
-        let stream_request = self
-            .cfg
-            .request_builder
-            .build(messages, &self.dynamic_rules)
-            .stream();
+        let stream_request = match self.cfg.client.betas().apply(
+            self.cfg
+                .request_builder
+                .build(messages, &self.dynamic_rules)
+                .stream(),
+        ) {
+            Ok(r) => r,
+            Err(e) => {
+                self.phase = Phase::Done;
+                return Some(Err(e));
+            }
+        };
+        self.metrics.record_applied_betas(&stream_request);
 
         let response = match self
             .cfg
@@ -377,7 +491,7 @@ impl StreamState {
 
         match chunk_result {
             Ok(Some(Ok(item))) => {
-                self.last_chunk_time = Instant::now();
+                self.last_chunk_time = clock::now();
                 self.handle_stream_item(item, accumulated_usage)
             }
             Ok(Some(Err(e))) => {
@@ -403,13 +517,25 @@ impl StreamState {
         match item {
             StreamItem::Text(text) => {
                 self.final_text.push_str(&text);
-                StreamPollResult::Event(Ok(AgentEvent::Text(text)))
+                let text = self.filter_text(text);
+                if text.is_empty() {
+                    StreamPollResult::Continue
+                } else {
+                    StreamPollResult::Event(Ok(AgentEvent::Text(text)))
+                }
             }
             StreamItem::Thinking(thinking) => {
-                StreamPollResult::Event(Ok(AgentEvent::Thinking(thinking)))
+                if self.cfg.config.execution.emit_thinking {
+                    StreamPollResult::Event(Ok(AgentEvent::Thinking(thinking)))
+                } else {
+                    StreamPollResult::Continue
+                }
             }
             StreamItem::Citation(_) => StreamPollResult::Continue,
-            StreamItem::ToolUseComplete(tool_use) => {
+            StreamItem::ToolUseComplete(mut tool_use) => {
+                if let Some(scrubber) = &self.cfg.config.security.pii_scrubber {
+                    scrubber.restore_value(&mut tool_use.input);
+                }
                 self.pending_tool_uses.push(tool_use);
                 StreamPollResult::Continue
             }
@@ -417,6 +543,32 @@ impl StreamState {
         }
     }
 
+    /// Restores PII tokens and redacts secrets in a text delta; either may
+    /// hold part of it back until the next delta.
+    fn filter_text(&mut self, text: String) -> String {
+        let text = match &mut self.pii_restorer {
+            Some(restorer) => restorer.push(&text),
+            None => text,
+        };
+        match &mut self.text_redactor {
+            Some(redactor) => redactor.push(&text),
+            None => text,
+        }
+    }
+
+    /// Text still held back by [`filter_text`](Self::filter_text) when the stream ends.
+    fn flush_text(&mut self) -> String {
+        let held = self
+            .pii_restorer
+            .as_mut()
+            .map(PiiStreamRestorer::finish)
+            .unwrap_or_default();
+        match &mut self.text_redactor {
+            Some(redactor) => redactor.push(&held) + &redactor.finish(),
+            None => held,
+        }
+    }
+
     fn handle_stream_event(
         &mut self,
         event: StreamEvent,
@@ -431,7 +583,17 @@ impl StreamState {
                 accumulated_usage.cache_read_input_tokens = message.usage.cache_read_input_tokens;
                 StreamPollResult::Continue
             }
-            StreamEvent::ContentBlockStart { .. } => StreamPollResult::Continue,
+            StreamEvent::ContentBlockStart { content_block, .. } => {
+                self.cfg
+                    .request_builder
+                    .load_discovered_tools(std::slice::from_ref(&content_block));
+                match content_block.as_code_execution_result() {
+                    Some(block) => {
+                        StreamPollResult::Event(Ok(AgentEvent::CodeExecution(block.clone())))
+                    }
+                    None => StreamPollResult::Continue,
+                }
+            }
             StreamEvent::ContentBlockDelta { .. } => StreamPollResult::Continue,
             StreamEvent::ContentBlockStop { .. } => StreamPollResult::Continue,
             StreamEvent::MessageDelta { usage, .. } => {
@@ -451,6 +613,9 @@ impl StreamState {
         &mut self,
         accumulated_usage: Usage,
     ) -> Option<crate::Result<AgentEvent>> {
+        if let Some(scrubber) = &self.cfg.config.security.pii_scrubber {
+            self.final_text = scrubber.restore(&self.final_text);
+        }
         self.cfg
             .tool_state
             .with_session_mut(|session| {
@@ -458,7 +623,7 @@ impl StreamState {
             })
             .await;
 
-        accumulate_response_usage(
+        self.last_call_cost = accumulate_response_usage(
             &mut self.total_usage,
             &mut self.metrics,
             &self.cfg.budget_tracker,
@@ -466,6 +631,16 @@ impl StreamState {
             &self.cfg.config.model.primary,
             &accumulated_usage,
         );
+        if let Some(message) = budget_warning(&self.cfg.budget_tracker) {
+            run_notification_hooks(
+                &self.cfg.hooks,
+                &self.cfg.hook_context,
+                &self.cfg.session_id,
+                NotificationKind::BudgetWarning,
+                message,
+            )
+            .await;
+        }
 
         self.cfg
             .tool_state
@@ -489,8 +664,13 @@ impl StreamState {
             .await;
 
         if self.pending_tool_uses.is_empty() {
+            if apply_interjections(&self.cfg.tool_state).await {
+                self.final_text.clear();
+                self.phase = Phase::StartRequest;
+                return None;
+            }
             self.phase = Phase::Done;
-            self.metrics.execution_time_ms = self.start_time.elapsed().as_millis() as u64;
+            self.metrics.execution_time_ms = clock::elapsed(self.start_time).as_millis() as u64;
 
             run_stop_hooks(
                 &self.cfg.hooks,
@@ -520,131 +700,184 @@ impl StreamState {
             self.final_text.clear();
             self.pending_tool_uses.clear();
             self.phase = Phase::StartRequest;
-            return None;
+            let progress = progress_event(
+                &self.cfg.tool_state,
+                &self.metrics,
+                self.cfg.config.execution.max_iterations,
+                self.start_time,
+            )
+            .await;
+            self.pending_events.extend(mcp_events(
+                &mut self.cfg.mcp_notifications,
+                &self.cfg.config.execution,
+            ));
+            return Some(Ok(progress));
         }
 
-        let tool_use = self.pending_tool_uses[tool_index].clone();
-        self.execute_tool(tool_use, tool_index).await
-    }
-
-    async fn execute_tool(
-        &mut self,
-        tool_use: ToolUseBlock,
-        tool_index: usize,
-    ) -> Option<crate::Result<AgentEvent>> {
-        let pre_input = HookInput::pre_tool_use(
-            &*self.cfg.session_id,
-            &tool_use.name,
-            tool_use.input.clone(),
+        let batch_end = tool_batch_end(
+            self.pending_tool_uses
+                .iter()
+                .map(|t| self.cfg.tools.canonical_name(&t.name)),
+            tool_index,
         );
-        let pre_output = match self
-            .cfg
-            .hooks
-            .execute(HookEvent::PreToolUse, pre_input, &self.cfg.hook_context)
-            .await
-        {
-            Ok(output) => output,
-            Err(e) => {
-                self.phase = Phase::Done;
-                return Some(Err(e));
-            }
+        let batch = self.pending_tool_uses[tool_index..batch_end].to_vec();
+        if let Err(e) = self.execute_tool_batch(batch).await {
+            self.phase = Phase::Done;
+            return Some(Err(e));
+        }
+
+        self.phase = Phase::ProcessingTools {
+            tool_index: batch_end,
         };
+        self.pending_events.pop_front().map(Ok)
+    }
 
-        if !pre_output.continue_execution {
-            let reason = pre_output
-                .stop_reason
-                .clone()
-                .unwrap_or_else(|| "Blocked by hook".into());
-            debug!(tool = %tool_use.name, "Tool blocked by hook");
+    async fn execute_tool_batch(&mut self, tool_uses: Vec<ToolUseBlock>) -> crate::Result<()> {
+        let mut prepared = Vec::with_capacity(tool_uses.len());
 
-            self.pending_tool_results
-                .push(ToolResultBlock::error(&tool_use.id, reason.clone()));
-            self.metrics.record_permission_denial(
-                PermissionDenial::new(&tool_use.name, &tool_use.id, tool_use.input.clone())
-                    .reason(reason.clone()),
-            );
-            self.phase = Phase::ProcessingTools {
-                tool_index: tool_index + 1,
-            };
-
-            return Some(Ok(AgentEvent::ToolBlocked {
+        for tool_use in tool_uses {
+            let pre_output = super::common::run_pre_tool_use(
+                &self.cfg.hooks,
+                &self.cfg.tool_state,
+                &self.cfg.session_id,
+                &tool_use,
+                &self.cfg.hook_context,
+            )
+            .await?;
+
+            if !pre_output.continue_execution {
+                let reason = pre_output
+                    .stop_reason
+                    .clone()
+                    .unwrap_or_else(|| "Blocked by hook".into());
+                debug!(tool = %tool_use.name, "Tool blocked by hook");
+
+                self.pending_tool_results
+                    .push(ToolResultBlock::error(&tool_use.id, reason.clone()));
+                self.metrics.record_permission_denial(
+                    PermissionDenial::new(&tool_use.name, &tool_use.id, tool_use.input.clone())
+                        .reason(reason.clone()),
+                );
+                self.pending_events.push_back(AgentEvent::ToolBlocked {
+                    id: tool_use.id,
+                    name: tool_use.name,
+                    reason,
+                });
+                continue;
+            }
+
+            let input = pre_output.updated_input.unwrap_or(tool_use.input);
+            prepared.push(PendingToolCall {
                 id: tool_use.id,
                 name: tool_use.name,
-                reason,
-            }));
+                input,
+            });
         }
 
-        let actual_input = pre_output.updated_input.unwrap_or(tool_use.input.clone());
+        self.pending_events
+            .extend(deprecation_events(&self.cfg.tools, &prepared));
 
-        let start = Instant::now();
-        let result = self
-            .cfg
-            .tools
-            .execute(&tool_use.name, actual_input.clone())
-            .await;
-        let duration_ms = start.elapsed().as_millis() as u64;
-
-        let (output, is_error) = match &result.output {
-            crate::types::ToolOutput::Success(s) => (s.clone(), false),
-            crate::types::ToolOutput::SuccessBlocks(blocks) => {
-                let text = blocks
-                    .iter()
-                    .filter_map(|b| match b {
-                        crate::types::ToolOutputBlock::Text { text } => Some(text.as_str()),
-                        _ => None,
-                    })
-                    .collect::<Vec<_>>()
-                    .join("\n");
-                (text, false)
+        if let Some(policy) = &self.cfg.config.execution.tool_preview
+            && !self.cfg.config.execution.dry_run
+        {
+            if let Some(message) = approval_message(&prepared, policy, &self.cfg.tools) {
+                run_notification_hooks(
+                    &self.cfg.hooks,
+                    &self.cfg.hook_context,
+                    &self.cfg.session_id,
+                    NotificationKind::ApprovalNeeded,
+                    message,
+                )
+                .await;
             }
-            crate::types::ToolOutput::Error(e) => (e.to_string(), true),
-            crate::types::ToolOutput::Empty => (String::new(), false),
-        };
+            let reviewed = review_tool_calls(&mut prepared, policy, &self.cfg.tools).await;
+            for call in reviewed {
+                if let Some(reason) = &call.rejection {
+                    debug!(tool = %call.preview.name, %reason, "Previewed change rejected");
+                    self.pending_tool_results.push(call.result_block(reason));
+                    self.metrics.record_permission_denial(call.denial(reason));
+                }
+                let blocked = call.rejection.map(|reason| AgentEvent::ToolBlocked {
+                    id: call.preview.tool_use_id.clone(),
+                    name: call.preview.name.clone(),
+                    reason,
+                });
+                self.pending_events
+                    .push_back(AgentEvent::ToolPreview(call.preview));
+                self.pending_events.extend(blocked);
+            }
+        }
 
-        self.metrics
-            .record_tool(&tool_use.id, &tool_use.name, duration_ms, is_error);
+        if self.cfg.config.execution.dry_run {
+            let planned = plan_tool_calls(
+                std::mem::take(&mut prepared),
+                self.cfg.tools.get_context(),
+                &mut self.metrics,
+            )
+            .await;
+            for entry in planned {
+                self.pending_tool_results.push(entry.result_block());
+                self.pending_events
+                    .push_back(AgentEvent::ToolPlanned(entry));
+            }
+        }
 
-        accumulate_inner_usage(
-            &self.cfg.tool_state,
-            &mut self.total_usage,
-            &mut self.metrics,
-            &self.cfg.budget_tracker,
-            &result,
-            &tool_use.name,
+        let idle_message = idle_message(&prepared, &self.cfg.config.execution);
+        let completed = notify_when_idle(
+            self.cfg.config.execution.idle_notification,
+            execute_tool_batch(&self.cfg.tools, prepared, &self.cfg.config.execution),
+            run_notification_hooks(
+                &self.cfg.hooks,
+                &self.cfg.hook_context,
+                &self.cfg.session_id,
+                NotificationKind::Idle,
+                idle_message,
+            ),
         )
         .await;
 
-        run_post_tool_hooks(
-            &self.cfg.hooks,
-            &self.cfg.hook_context,
-            &self.cfg.session_id,
-            &tool_use.name,
-            is_error,
-            &result,
-        )
-        .await;
+        for call in completed {
+            let is_error = call.result.is_error();
 
-        try_activate_dynamic_rules(
-            &tool_use.name,
-            &actual_input,
-            &self.cfg.orchestrator,
-            &mut self.dynamic_rules,
-        )
-        .await;
+            self.metrics
+                .record_tool(&call.id, &call.name, call.duration_ms, is_error);
+            call.record_metrics(self.cfg.metrics_registry.as_deref());
 
-        self.pending_tool_results
-            .push(ToolResultBlock::from_tool_result(&tool_use.id, &result));
-        self.phase = Phase::ProcessingTools {
-            tool_index: tool_index + 1,
-        };
+            accumulate_inner_usage(
+                &self.cfg.tool_state,
+                &mut self.total_usage,
+                &mut self.metrics,
+                &self.cfg.budget_tracker,
+                &call.result,
+                &call.name,
+            )
+            .await;
 
-        Some(Ok(AgentEvent::ToolComplete {
-            id: tool_use.id,
-            name: tool_use.name,
-            output,
-            is_error,
-            duration_ms,
-        }))
+            run_post_tool_hooks(
+                &self.cfg.hooks,
+                &self.cfg.hook_context,
+                &self.cfg.session_id,
+                &call.name,
+                is_error,
+                &call.result,
+            )
+            .await;
+
+            try_activate_dynamic_rules(
+                &call.name,
+                &call.input,
+                &self.cfg.orchestrator,
+                &mut self.dynamic_rules,
+            )
+            .await;
+
+            self.pending_tool_results
+                .push(ToolResultBlock::from_tool_result(&call.id, &call.result));
+            self.pending_events.extend(call.violation_events());
+            self.pending_events.push_back(call.into_event());
+        }
+
+        Ok(())
     }
 
     async fn finalize_tool_results(&mut self) {
@@ -657,6 +890,7 @@ impl StreamState {
                 session.add_tool_results(results);
             })
             .await;
+        apply_interjections(&self.cfg.tool_state).await;
 
         handle_compaction(
             &self.cfg.tool_state,
@@ -673,6 +907,24 @@ impl StreamState {
     }
 }
 
+impl Drop for StreamState {
+    fn drop(&mut self) {
+        if self.finished || !self.session_started {
+            return;
+        }
+        // The stream was dropped mid-run: in-flight tool futures are already gone,
+        // but background shells outlive them and must be reaped explicitly.
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let tool_state = self.cfg.tool_state.clone();
+        let tools = Arc::clone(&self.cfg.tools);
+        handle.spawn(async move {
+            finalize_cancelled(&tool_state, &tools).await;
+        });
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
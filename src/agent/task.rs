@@ -10,7 +10,7 @@ use super::AgentBuilder;
 use super::task_registry::TaskRegistry;
 use crate::auth::Auth;
 use crate::client::CloudProvider;
-use crate::common::{Index, IndexRegistry};
+use crate::common::{Index, IndexRegistry, clock};
 use crate::hooks::{HookEvent, HookInput};
 use crate::subagents::{SubagentIndex, builtin_subagents};
 use crate::tools::{ExecutionContext, SchemaTool};
@@ -222,7 +222,7 @@ impl SchemaTool for TaskTool {
         let agent_id = input
             .resume
             .clone()
-            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()[..7].to_string());
+            .unwrap_or_else(|| clock::new_uuid().to_string()[..7].to_string());
 
         let session_id = context.session_id().unwrap_or("").to_string();
         let run_in_background = input.run_in_background.unwrap_or(false);
@@ -8,6 +8,7 @@ use tokio::sync::{RwLock, oneshot};
 use tokio::task::JoinHandle;
 use tracing::warn;
 
+use crate::common::clock;
 use crate::session::{
     Persistence, Session, SessionConfig, SessionId, SessionMessage, SessionState, SessionType,
 };
@@ -264,7 +265,7 @@ impl TaskRegistry {
                     SessionType::Subagent { description, .. } => description.clone(),
                     _ => String::new(),
                 };
-                let elapsed = (chrono::Utc::now() - session.created_at)
+                let elapsed = (clock::now_utc() - session.created_at)
                     .to_std()
                     .unwrap_or_default();
                 result.push((id.clone(), description, elapsed));
@@ -4,6 +4,11 @@ use async_trait::async_trait;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
+use serde_json::{Value, json};
+use wiremock::{MockServer, ResponseTemplate};
+
+use crate::agent::{Agent, AgentConfig};
+use crate::client::{AnthropicAdapter, ProviderConfig};
 use crate::hooks::{Hook, HookContext, HookEvent, HookInput, HookOutput};
 use crate::tools::{ExecutionContext, Tool, ToolOutput, ToolResult};
 
@@ -115,3 +120,88 @@ impl Tool for DummyTool {
         ToolResult::from(self.output.clone())
     }
 }
+
+/// Fails with `error` for the first `failures` calls, then succeeds.
+pub struct FlakyTool {
+    pub name: String,
+    pub error: String,
+    pub failures: usize,
+    pub calls: AtomicUsize,
+}
+
+#[async_trait]
+impl Tool for FlakyTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        "Flaky tool for testing"
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {}
+        })
+    }
+
+    async fn execute(&self, _input: serde_json::Value, _context: &ExecutionContext) -> ToolResult {
+        if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+            ToolResult::error(&self.error)
+        } else {
+            ToolResult::success("fetched")
+        }
+    }
+}
+
+/// A Messages API response as the mock server returns it.
+pub fn message_response(content: Value, stop_reason: &str) -> ResponseTemplate {
+    message_response_with_usage(
+        content,
+        stop_reason,
+        json!({"input_tokens": 10, "output_tokens": 5}),
+    )
+}
+
+pub fn message_response_with_usage(
+    content: Value,
+    stop_reason: &str,
+    usage: Value,
+) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(json!({
+        "id": "msg_1",
+        "type": "message",
+        "role": "assistant",
+        "content": content,
+        "model": "claude-sonnet-4-5",
+        "stop_reason": stop_reason,
+        "stop_sequence": null,
+        "usage": usage
+    }))
+}
+
+/// A response that ends the turn with `text`.
+pub fn text_response(text: &str) -> ResponseTemplate {
+    message_response(json!([{"type": "text", "text": text}]), "end_turn")
+}
+
+/// A response that calls `name` once, as `toolu_1`.
+pub fn tool_use_response(name: &str, input: Value) -> ResponseTemplate {
+    message_response(
+        json!([{"type": "tool_use", "id": "toolu_1", "name": name, "input": input}]),
+        "tool_use",
+    )
+}
+
+/// A client that sends its requests to `server`.
+pub fn mock_client(server: &MockServer) -> crate::Client {
+    let adapter = AnthropicAdapter::new(ProviderConfig::default())
+        .api_key("test-key")
+        .base_url(server.uri());
+    crate::Client::new(adapter).unwrap()
+}
+
+pub fn mock_agent(server: &MockServer) -> Agent {
+    Agent::new(mock_client(server), AgentConfig::default())
+}
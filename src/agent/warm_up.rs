@@ -0,0 +1,95 @@
+//! Prompt-cache warm start.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use rust_decimal::Decimal;
+use tracing::info;
+
+use super::executor::Agent;
+use super::request::RequestBuilder;
+use crate::types::{Message, Usage};
+
+const PRIMING_PROMPT: &str = "Reply with OK.";
+
+/// Outcome of [`Agent::warm_up`].
+#[derive(Debug, Clone)]
+pub struct WarmUp {
+    pub usage: Usage,
+    pub cost_usd: Decimal,
+    pub duration_ms: u64,
+}
+
+impl WarmUp {
+    /// Tokens written to the cache by the priming request.
+    pub fn cache_creation_tokens(&self) -> u32 {
+        self.usage.cache_creation_input_tokens.unwrap_or(0)
+    }
+
+    /// Tokens that were already cached (another agent warmed the same prefix).
+    pub fn cache_read_tokens(&self) -> u32 {
+        self.usage.cache_read_input_tokens.unwrap_or(0)
+    }
+
+    /// Whether the static prefix is now in the cache.
+    pub fn is_cached(&self) -> bool {
+        self.cache_creation_tokens() > 0 || self.cache_read_tokens() > 0
+    }
+}
+
+impl Agent {
+    /// Prime the prompt cache with this agent's tools and static system prompt.
+    ///
+    /// Sends a minimal request whose prefix matches the one `execute` builds, so
+    /// the cache breakpoint on the static system prompt (1h TTL by default, see
+    /// [`CacheConfig`](super::CacheConfig)) is written once. Agents sharing the
+    /// same configuration then read it instead of each paying for a cache write,
+    /// which matters when many of them start in parallel. The session is not
+    /// modified; the cost is recorded against the budget.
+    pub async fn warm_up(&self) -> crate::Result<WarmUp> {
+        if !self.config.cache.strategy.cache_system() {
+            return Err(crate::Error::Config(
+                "Prompt caching of the system prompt is disabled".into(),
+            ));
+        }
+
+        let mut builder = RequestBuilder::new(&self.config, Arc::clone(&self.tools));
+        if let Some(ref tsm) = self.tool_search_manager {
+            builder = builder.prepared_tools(tsm.prepare_tools().await);
+        }
+
+        // Thinking needs room for its budget; otherwise a single token is enough.
+        let max_tokens = self
+            .client
+            .config()
+            .thinking_budget
+            .map_or(1, |budget| budget + 1);
+        let request = builder
+            .build(vec![Message::user(PRIMING_PROMPT)], "")
+            .max_tokens(max_tokens);
+        let request = self.client.betas().apply(request)?;
+
+        let start = Instant::now();
+        let response = self.client.send_with_auth_retry(request).await?;
+        let duration_ms = start.elapsed().as_millis() as u64;
+
+        let model = &self.config.model.primary;
+        let cost_usd = self.budget_tracker.record(model, &response.usage);
+        if let Some(ref tenant_budget) = self.tenant_budget {
+            tenant_budget.record(model, &response.usage);
+        }
+
+        let warm_up = WarmUp {
+            usage: response.usage,
+            cost_usd,
+            duration_ms,
+        };
+        info!(
+            cache_creation_tokens = warm_up.cache_creation_tokens(),
+            cache_read_tokens = warm_up.cache_read_tokens(),
+            duration_ms,
+            "Prompt cache warmed up"
+        );
+        Ok(warm_up)
+    }
+}
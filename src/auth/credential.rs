@@ -112,6 +112,46 @@ impl OAuthCredential {
     }
 }
 
+/// OAuth tokens issued by a remote MCP server's authorization server.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpOAuthCredential {
+    pub server_name: String,
+    pub server_url: String,
+    /// Client ID from dynamic client registration.
+    pub client_id: String,
+    #[serde(with = "secret_serde")]
+    pub access_token: SecretString,
+    #[serde(
+        with = "option_secret_serde",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub refresh_token: Option<SecretString>,
+    /// Unix timestamp in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+impl fmt::Debug for McpOAuthCredential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("McpOAuthCredential")
+            .field("server_name", &self.server_name)
+            .field("server_url", &self.server_url)
+            .field("client_id", &self.client_id)
+            .field("access_token", &"[redacted]")
+            .field(
+                "refresh_token",
+                &self.refresh_token.as_ref().map(|_| "[redacted]"),
+            )
+            .field("expires_at", &self.expires_at)
+            .field("scope", &self.scope)
+            .finish()
+    }
+}
+
 #[derive(Clone)]
 pub enum Credential {
     ApiKey(SecretString),
@@ -11,7 +11,7 @@ mod helper;
 mod provider;
 mod providers;
 #[cfg(feature = "cli-integration")]
-mod storage;
+pub(crate) mod storage;
 
 use std::sync::Arc;
 
@@ -19,7 +19,7 @@ use secrecy::{ExposeSecret, SecretString};
 
 pub use cache::CachedProvider;
 pub use config::{CLAUDE_CODE_BETA, OAuthConfig, OAuthConfigBuilder};
-pub use credential::{Credential, OAuthCredential};
+pub use credential::{Credential, McpOAuthCredential, OAuthCredential};
 pub use helper::{ApiKeyHelper, AwsCredentialRefresh, AwsCredentials, CredentialManager};
 pub use provider::CredentialProvider;
 #[cfg(feature = "cli-integration")]
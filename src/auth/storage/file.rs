@@ -1,6 +1,6 @@
 //! File-based credential storage.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use directories::BaseDirs;
 
@@ -52,6 +52,36 @@ impl FileStorage {
 
         Ok(Some(creds))
     }
+
+    /// Write credentials to file with owner-only permissions.
+    pub async fn save(creds: &CliCredentials) -> Result<()> {
+        let path = Self::credentials_path()
+            .ok_or_else(|| crate::Error::auth("Cannot determine home directory"))?;
+        Self::save_to(&path, creds).await
+    }
+
+    async fn save_to(path: &Path, creds: &CliCredentials) -> Result<()> {
+        let write_err =
+            |e: std::io::Error| crate::Error::auth(format!("Failed to write credentials: {}", e));
+
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await.map_err(write_err)?;
+        }
+
+        let content = serde_json::to_vec_pretty(creds)
+            .map_err(|e| crate::Error::auth(format!("Failed to serialize credentials: {}", e)))?;
+
+        let tmp = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp, content).await.map_err(write_err)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            tokio::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600))
+                .await
+                .map_err(write_err)?;
+        }
+        tokio::fs::rename(&tmp, path).await.map_err(write_err)
+    }
 }
 
 #[cfg(test)]
@@ -65,4 +95,22 @@ mod tests {
         let path = path.unwrap();
         assert!(path.to_string_lossy().contains(".claude"));
     }
+
+    #[tokio::test]
+    async fn test_save_is_owner_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".claude").join(CREDENTIALS_FILE);
+
+        FileStorage::save_to(&path, &CliCredentials::default())
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{}");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
 }
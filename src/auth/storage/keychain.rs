@@ -49,4 +49,33 @@ impl KeychainStorage {
         .await
         .map_err(|e| crate::Error::auth(format!("Keychain task panicked: {}", e)))?
     }
+
+    /// Store credentials in Keychain, replacing any existing entry.
+    pub async fn save(creds: &CliCredentials) -> Result<()> {
+        let secret = serde_json::to_string(creds)
+            .map_err(|e| crate::Error::auth(format!("Failed to serialize credentials: {}", e)))?;
+        let account = std::env::var("USER").unwrap_or_default();
+
+        tokio::task::spawn_blocking(move || {
+            let output = Command::new("security")
+                .args(["add-generic-password", "-U", "-s", SERVICE_NAME, "-a"])
+                .arg(&account)
+                .arg("-w")
+                .arg(&secret)
+                .output()
+                .map_err(|e| {
+                    crate::Error::auth(format!("Failed to execute security command: {}", e))
+                })?;
+
+            if !output.status.success() {
+                return Err(crate::Error::auth(format!(
+                    "Failed to write keychain entry: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )));
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| crate::Error::auth(format!("Keychain task panicked: {}", e)))?
+    }
 }
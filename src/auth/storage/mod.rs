@@ -4,9 +4,11 @@ mod file;
 #[cfg(target_os = "macos")]
 mod keychain;
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
-use super::OAuthCredential;
+use super::{McpOAuthCredential, OAuthCredential};
 use crate::Result;
 
 pub use file::FileStorage;
@@ -20,6 +22,16 @@ pub struct CliCredentials {
     /// OAuth credentials.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub claude_ai_oauth: Option<OAuthCredential>,
+    /// OAuth tokens for remote MCP servers, keyed by server name and URL.
+    #[serde(
+        rename = "mcpOAuth",
+        default,
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    pub mcp_oauth: HashMap<String, McpOAuthCredential>,
+    /// Entries this crate does not model, kept so saving does not drop them.
+    #[serde(flatten)]
+    pub other: HashMap<String, serde_json::Value>,
 }
 
 impl CliCredentials {
@@ -41,6 +53,19 @@ pub async fn load_cli_credentials() -> Result<Option<CliCredentials>> {
     FileStorage::load().await
 }
 
+/// Save CLI credentials to the storage they are loaded from.
+#[cfg_attr(not(feature = "mcp"), allow(dead_code))]
+pub async fn save_cli_credentials(creds: &CliCredentials) -> Result<()> {
+    #[cfg(target_os = "macos")]
+    {
+        if KeychainStorage::load().await?.is_some() {
+            return KeychainStorage::save(creds).await;
+        }
+    }
+
+    FileStorage::save(creds).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -63,4 +88,34 @@ mod tests {
         assert_eq!(oauth.access_token.expose_secret(), "sk-ant-oat01-test");
         assert_eq!(oauth.subscription_type, Some("pro".to_string()));
     }
+
+    #[test]
+    fn test_cli_credentials_roundtrip_keeps_unknown_entries() {
+        let json = r#"{
+            "claudeAiOauth": {"accessToken": "sk-ant-oat01-test", "scopes": []},
+            "mcpOAuth": {
+                "linear|https://mcp.linear.app/mcp": {
+                    "serverName": "linear",
+                    "serverUrl": "https://mcp.linear.app/mcp",
+                    "clientId": "client-1",
+                    "accessToken": "at",
+                    "refreshToken": "rt",
+                    "expiresAt": 1234567890
+                }
+            },
+            "organizationUuid": "org-1"
+        }"#;
+
+        let creds: CliCredentials = serde_json::from_str(json).unwrap();
+        let mcp = &creds.mcp_oauth["linear|https://mcp.linear.app/mcp"];
+        assert_eq!(mcp.client_id, "client-1");
+        assert_eq!(mcp.refresh_token.as_ref().unwrap().expose_secret(), "rt");
+
+        let value = serde_json::to_value(&creds).unwrap();
+        assert_eq!(value["organizationUuid"], "org-1");
+        assert_eq!(
+            value["mcpOAuth"]["linear|https://mcp.linear.app/mcp"]["expiresAt"],
+            1234567890
+        );
+    }
 }
@@ -1,6 +1,6 @@
 //! Budget tracking for individual agent sessions.
 
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
 use rust_decimal::Decimal;
 
@@ -36,6 +36,7 @@ impl OnExceed {
 pub struct BudgetTracker {
     max_cost_usd: Option<Decimal>,
     used_cost_bits: AtomicU64,
+    warned: AtomicBool,
     on_exceed: OnExceed,
     pricing: &'static PricingTable,
 }
@@ -45,6 +46,7 @@ impl Default for BudgetTracker {
         Self {
             max_cost_usd: None,
             used_cost_bits: AtomicU64::new(0),
+            warned: AtomicBool::new(false),
             on_exceed: OnExceed::default(),
             pricing: global_pricing_table(),
         }
@@ -56,6 +58,7 @@ impl Clone for BudgetTracker {
         Self {
             max_cost_usd: self.max_cost_usd,
             used_cost_bits: AtomicU64::new(self.used_cost_bits.load(Ordering::Relaxed)),
+            warned: AtomicBool::new(self.warned.load(Ordering::Relaxed)),
             on_exceed: self.on_exceed.clone(),
             pricing: self.pricing,
         }
@@ -107,6 +110,15 @@ impl BudgetTracker {
         }
     }
 
+    /// Status the first time at least 80% of the limit has been used, then `None`.
+    pub fn take_warning(&self) -> Option<BudgetStatus> {
+        let max = self.max_cost_usd?;
+        if self.used_cost_usd_internal() < max * Decimal::new(8, 1) {
+            return None;
+        }
+        (!self.warned.swap(true, Ordering::Relaxed)).then(|| self.check())
+    }
+
     pub fn should_stop(&self) -> bool {
         matches!(self.on_exceed, OnExceed::StopBeforeNext)
             && matches!(self.check(), BudgetStatus::Exceeded { .. })
@@ -228,4 +240,26 @@ mod tests {
         assert!(matches!(tracker.check(), BudgetStatus::Exceeded { .. }));
         assert!(!tracker.should_stop()); // WarnAndContinue doesn't stop
     }
+
+    #[test]
+    fn test_take_warning_once_at_80_percent() {
+        let tracker = BudgetTracker::new(dec!(1));
+        let usage = Usage {
+            input_tokens: 100_000,
+            ..Default::default()
+        };
+
+        // $0.30 per call on Sonnet
+        tracker.record("claude-sonnet-4-5", &usage);
+        tracker.record("claude-sonnet-4-5", &usage);
+        assert!(tracker.take_warning().is_none());
+
+        tracker.record("claude-sonnet-4-5", &usage);
+        assert!(matches!(
+            tracker.take_warning(),
+            Some(BudgetStatus::WithinBudget { .. })
+        ));
+        assert!(tracker.take_warning().is_none());
+        assert!(BudgetTracker::unlimited().take_warning().is_none());
+    }
 }
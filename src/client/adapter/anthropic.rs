@@ -6,7 +6,7 @@ use async_trait::async_trait;
 use secrecy::{ExposeSecret, SecretString};
 use tokio::sync::RwLock;
 
-use super::config::{BetaFeature, ProviderConfig};
+use super::config::{BetaConfig, BetaFeature, ProviderConfig};
 use super::traits::ProviderAdapter;
 use crate::auth::{Credential, CredentialProvider, OAuthConfig};
 use crate::client::messages::{
@@ -151,22 +151,20 @@ impl AnthropicAdapter {
         &self,
         req: reqwest::RequestBuilder,
         auth: &AuthMethod,
+        beta: &BetaConfig,
     ) -> reqwest::RequestBuilder {
         let mut r = match auth {
             AuthMethod::ApiKey(key) => req
                 .header("x-api-key", key.expose_secret())
                 .header("anthropic-version", &self.config.api_version)
                 .header("content-type", "application/json"),
-            AuthMethod::OAuth { token, config } => config.apply_headers(
-                req,
-                token.expose_secret(),
-                &self.config.api_version,
-                &self.config.beta,
-            ),
+            AuthMethod::OAuth { token, config } => {
+                config.apply_headers(req, token.expose_secret(), &self.config.api_version, beta)
+            }
         };
 
         if let AuthMethod::ApiKey(_) = auth
-            && let Some(beta) = self.config.beta.header_value()
+            && let Some(beta) = beta.header_value()
         {
             r = r.header("anthropic-beta", beta);
         }
@@ -189,10 +187,10 @@ impl AnthropicAdapter {
             let mut blocks = vec![crate::types::SystemBlock::uncached(CLI_IDENTITY)];
 
             match &request.system {
-                Some(crate::types::SystemPrompt::Text(existing)) if !existing.is_empty() => {
-                    if !existing.starts_with(CLI_IDENTITY) {
-                        blocks.push(crate::types::SystemBlock::uncached(existing));
-                    }
+                Some(crate::types::SystemPrompt::Text(existing))
+                    if !existing.is_empty() && !existing.starts_with(CLI_IDENTITY) =>
+                {
+                    blocks.push(crate::types::SystemBlock::uncached(existing));
                 }
                 Some(crate::types::SystemPrompt::Blocks(existing_blocks))
                     if !existing_blocks.is_empty() =>
@@ -223,27 +221,39 @@ impl AnthropicAdapter {
         self.credential_provider.as_ref()
     }
 
-    fn needs_structured_outputs(request: &CreateMessageRequest) -> bool {
-        let has_strict_tools = request
-            .tools
-            .as_ref()
-            .is_some_and(|tools| tools.iter().any(|t| t.is_strict()));
-        request.output_format.is_some() || has_strict_tools
+    /// Beta flags for a request: those resolved by the client's registry, or the
+    /// provider defaults plus flags the request needs when sent without a registry.
+    fn request_betas(&self, request: &CreateMessageRequest) -> BetaConfig {
+        if let Some(ref betas) = request.betas {
+            return betas.clone();
+        }
+        let mut betas = self.config.beta.clone();
+        if request.requires_structured_outputs() {
+            betas.add(BetaFeature::StructuredOutputs);
+        }
+        for feature in request.tool_betas() {
+            betas.add(feature);
+        }
+        betas
     }
 
-    fn apply_structured_outputs_header(
+    async fn send_request(
         &self,
-        req: reqwest::RequestBuilder,
-        needs: bool,
-    ) -> reqwest::RequestBuilder {
-        if needs && !self.config.beta.has(BetaFeature::StructuredOutputs) {
-            req.header(
-                "anthropic-beta",
-                BetaFeature::StructuredOutputs.header_value(),
-            )
-        } else {
-            req
-        }
+        http: &reqwest::Client,
+        request: CreateMessageRequest,
+    ) -> Result<reqwest::Response> {
+        let betas = self.request_betas(&request);
+
+        let (req, body) = {
+            let auth = self.auth.read().await;
+            let url = self.build_endpoint_url(&auth, "/v1/messages");
+            let req = self.build_headers(http.post(&url), &auth, &betas);
+            let prepared = self.prepare_request_with_auth(request, &auth);
+            (req, serde_json::to_value(&prepared)?)
+        };
+
+        let response = req.json(&body).send().await?;
+        Self::check_error_response(response).await
     }
 
     async fn check_error_response(response: reqwest::Response) -> Result<reqwest::Response> {
@@ -283,7 +293,7 @@ impl ProviderAdapter for AnthropicAdapter {
 
     async fn apply_auth_headers(&self, req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
         let auth = self.auth.read().await;
-        self.build_headers(req, &auth)
+        self.build_headers(req, &auth, &self.config.beta)
     }
 
     async fn send(
@@ -291,20 +301,7 @@ impl ProviderAdapter for AnthropicAdapter {
         http: &reqwest::Client,
         request: CreateMessageRequest,
     ) -> Result<ApiResponse> {
-        let needs = Self::needs_structured_outputs(&request);
-
-        let (url, body) = {
-            let auth = self.auth.read().await;
-            let url = self.build_endpoint_url(&auth, "/v1/messages");
-            let prepared = self.prepare_request_with_auth(request, &auth);
-            (url, serde_json::to_value(&prepared)?)
-        };
-
-        let req = self.apply_auth_headers(http.post(&url)).await;
-        let req = self.apply_structured_outputs_header(req, needs);
-        let response = req.json(&body).send().await?;
-        let response = Self::check_error_response(response).await?;
-
+        let response = self.send_request(http, request).await?;
         let json: serde_json::Value = response.json().await?;
         self.transform_response(json)
     }
@@ -314,20 +311,8 @@ impl ProviderAdapter for AnthropicAdapter {
         http: &reqwest::Client,
         mut request: CreateMessageRequest,
     ) -> Result<reqwest::Response> {
-        let needs = Self::needs_structured_outputs(&request);
         request.stream = Some(true);
-
-        let (url, body) = {
-            let auth = self.auth.read().await;
-            let url = self.build_endpoint_url(&auth, "/v1/messages");
-            let prepared = self.prepare_request_with_auth(request, &auth);
-            (url, serde_json::to_value(&prepared)?)
-        };
-
-        let req = self.apply_auth_headers(http.post(&url)).await;
-        let req = self.apply_structured_outputs_header(req, needs);
-        let response = req.json(&body).send().await?;
-        Self::check_error_response(response).await
+        self.send_request(http, request).await
     }
 
     fn supports_credential_refresh(&self) -> bool {
@@ -450,6 +435,20 @@ mod tests {
         assert!(header.contains("new-feature-2026-01-01"));
     }
 
+    #[test]
+    fn test_request_betas_prefer_resolved_set() {
+        let config = ProviderConfig::new(ModelConfig::anthropic()).beta(BetaFeature::WebSearch);
+        let adapter = AnthropicAdapter::new(config);
+
+        let request = CreateMessageRequest::new("model", vec![Message::user("Hi")]);
+        assert!(adapter.request_betas(&request).has(BetaFeature::WebSearch));
+
+        let request = request.betas(BetaConfig::new().feature(BetaFeature::Effort));
+        let betas = adapter.request_betas(&request);
+        assert!(betas.has(BetaFeature::Effort));
+        assert!(!betas.has(BetaFeature::WebSearch));
+    }
+
     #[tokio::test]
     async fn test_oauth_prepends_cli_identity_to_system_prompt() {
         let credential = Credential::oauth("test-token");
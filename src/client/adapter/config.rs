@@ -139,6 +139,7 @@ pub enum BetaFeature {
     StructuredOutputs,
     PromptCaching,
     MaxTokens128k,
+    /// Server-side code execution tool (`code_execution_20250825`).
     CodeExecution,
     Mcp,
     WebSearch,
@@ -150,6 +151,8 @@ pub enum BetaFeature {
     Context1M,
     /// Tool search for progressive disclosure of MCP tools.
     AdvancedToolUse,
+    /// Computer-use tool (`computer_20250124`).
+    ComputerUse,
 }
 
 impl BetaFeature {
@@ -159,7 +162,7 @@ impl BetaFeature {
         (Self::StructuredOutputs, "structured-outputs-2025-11-13"),
         (Self::PromptCaching, "prompt-caching-2024-07-31"),
         (Self::MaxTokens128k, "max-tokens-3-5-sonnet-2024-07-15"),
-        (Self::CodeExecution, "code-execution-2025-01-24"),
+        (Self::CodeExecution, "code-execution-2025-08-25"),
         (Self::Mcp, "mcp-2025-04-08"),
         (Self::WebSearch, "web-search-2025-03-05"),
         (Self::WebFetch, "web-fetch-2025-09-10"),
@@ -168,6 +171,7 @@ impl BetaFeature {
         (Self::Effort, "effort-2025-11-24"),
         (Self::Context1M, "context-1m-2025-08-07"),
         (Self::AdvancedToolUse, "advanced-tool-use-2025-11-20"),
+        (Self::ComputerUse, "computer-use-2025-01-24"),
     ];
 
     pub fn header_value(&self) -> &'static str {
@@ -178,7 +182,7 @@ impl BetaFeature {
             .expect("all variants covered in FEATURES")
     }
 
-    fn from_header(value: &str) -> Option<Self> {
+    pub(crate) fn from_header(value: &str) -> Option<Self> {
         Self::FEATURES
             .iter()
             .find(|(_, v)| *v == value)
@@ -242,7 +246,30 @@ impl BetaConfig {
         config
     }
 
-    pub fn header_value(&self) -> Option<String> {
+    pub fn remove(&mut self, feature: BetaFeature) {
+        self.features.remove(&feature);
+    }
+
+    pub fn remove_custom(&mut self, flag: &str) {
+        self.custom.retain(|f| f != flag);
+    }
+
+    pub fn clear(&mut self) {
+        self.features.clear();
+        self.custom.clear();
+    }
+
+    pub fn merge(&mut self, other: &BetaConfig) {
+        self.features.extend(other.features.iter().copied());
+        for flag in &other.custom {
+            if !self.custom.contains(flag) {
+                self.custom.push(flag.clone());
+            }
+        }
+    }
+
+    /// Header flags in the order they are sent: known features sorted, then custom flags.
+    pub fn flags(&self) -> Vec<String> {
         let mut flags: Vec<&str> = self.features.iter().map(|f| f.header_value()).collect();
         flags.sort();
 
@@ -252,6 +279,11 @@ impl BetaConfig {
             }
         }
 
+        flags.into_iter().map(String::from).collect()
+    }
+
+    pub fn header_value(&self) -> Option<String> {
+        let flags = self.flags();
         if flags.is_empty() {
             None
         } else {
@@ -0,0 +1,289 @@
+//! Scripted adapter for tests.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde_json::{Value, json};
+
+use super::config::ProviderConfig;
+use super::traits::ProviderAdapter;
+use crate::client::messages::CreateMessageRequest;
+use crate::types::{ApiResponse, ContentBlock, StopReason, ToolUseBlock, Usage};
+use crate::{Error, Result};
+
+#[derive(Debug, Default)]
+struct Script {
+    responses: VecDeque<Result<ApiResponse>>,
+    requests: Vec<CreateMessageRequest>,
+    served: usize,
+}
+
+/// Adapter that answers requests from a scripted queue instead of the network.
+///
+/// Responses are served in order to both `send` and `send_stream`; streamed
+/// responses are replayed as server-sent events. Every request is recorded.
+/// Clones share the same script, so keep one to inspect requests after handing
+/// another to [`Client::new`](crate::Client::new).
+#[derive(Debug, Clone, Default)]
+pub struct MockAdapter {
+    config: ProviderConfig,
+    script: Arc<Mutex<Script>>,
+}
+
+impl MockAdapter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_config(config: ProviderConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Queue a complete response.
+    pub fn respond(self, response: ApiResponse) -> Self {
+        self.lock().responses.push_back(Ok(response));
+        self
+    }
+
+    /// Queue a text answer ending the turn.
+    pub fn text(self, text: impl Into<String>) -> Self {
+        let block = ContentBlock::Text {
+            text: text.into(),
+            citations: None,
+            cache_control: None,
+        };
+        self.blocks(vec![block], StopReason::EndTurn)
+    }
+
+    /// Queue a single tool call. Its id is `toolu_<n>`, `n` counting responses.
+    pub fn tool_use(self, name: impl Into<String>, input: Value) -> Self {
+        let id = format!("toolu_{:03}", self.lock().responses.len() + 1);
+        self.tool_uses(vec![ToolUseBlock {
+            id,
+            name: name.into(),
+            input,
+        }])
+    }
+
+    /// Queue several tool calls in one response.
+    pub fn tool_uses(self, calls: Vec<ToolUseBlock>) -> Self {
+        let blocks = calls.into_iter().map(ContentBlock::ToolUse).collect();
+        self.blocks(blocks, StopReason::ToolUse)
+    }
+
+    /// Queue a response with the given content.
+    pub fn blocks(self, content: Vec<ContentBlock>, stop_reason: StopReason) -> Self {
+        let n = self.lock().responses.len() + 1;
+        self.respond(ApiResponse {
+            id: format!("msg_{n:03}"),
+            response_type: "message".into(),
+            role: "assistant".into(),
+            content,
+            model: "mock".into(),
+            stop_reason: Some(stop_reason),
+            stop_sequence: None,
+            usage: Usage {
+                input_tokens: 10,
+                output_tokens: 5,
+                ..Default::default()
+            },
+            context_management: None,
+        })
+    }
+
+    /// Queue an error returned instead of a response.
+    pub fn error(self, error: Error) -> Self {
+        self.lock().responses.push_back(Err(error));
+        self
+    }
+
+    /// Requests received so far.
+    pub fn requests(&self) -> Vec<CreateMessageRequest> {
+        self.lock().requests.clone()
+    }
+
+    /// Responses not yet served.
+    pub fn remaining(&self) -> usize {
+        self.lock().responses.len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Script> {
+        self.script.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn next(&self, request: CreateMessageRequest) -> Result<ApiResponse> {
+        let mut script = self.lock();
+        script.requests.push(request);
+        script.served += 1;
+        let served = script.served;
+        script.responses.pop_front().unwrap_or_else(|| {
+            Err(Error::Config(format!(
+                "MockAdapter has no response scripted for request {served}"
+            )))
+        })
+    }
+}
+
+/// Server-sent events replaying `response`.
+fn sse_body(response: &ApiResponse) -> Result<String> {
+    let mut events = vec![json!({
+        "type": "message_start",
+        "message": {
+            "id": response.id,
+            "type": response.response_type,
+            "role": response.role,
+            "model": response.model,
+            "usage": {"input_tokens": response.usage.input_tokens, "output_tokens": 0},
+        },
+    })];
+
+    for (index, block) in response.content.iter().enumerate() {
+        let block = serde_json::to_value(block)?;
+        let (start, deltas) = match block["type"].as_str() {
+            Some("text") => (
+                json!({"type": "text", "text": ""}),
+                vec![json!({"type": "text_delta", "text": block["text"]})],
+            ),
+            Some("thinking") => (
+                json!({"type": "thinking", "thinking": "", "signature": ""}),
+                vec![
+                    json!({"type": "thinking_delta", "thinking": block["thinking"]}),
+                    json!({"type": "signature_delta", "signature": block["signature"]}),
+                ],
+            ),
+            Some("tool_use") => {
+                let mut start = block.clone();
+                start["input"] = json!({});
+                let partial_json = block["input"].to_string();
+                (
+                    start,
+                    vec![json!({"type": "input_json_delta", "partial_json": partial_json})],
+                )
+            }
+            _ => (block, Vec::new()),
+        };
+        events.push(json!({"type": "content_block_start", "index": index, "content_block": start}));
+        for delta in deltas {
+            events.push(json!({"type": "content_block_delta", "index": index, "delta": delta}));
+        }
+        events.push(json!({"type": "content_block_stop", "index": index}));
+    }
+
+    events.push(json!({
+        "type": "message_delta",
+        "delta": {"stop_reason": response.stop_reason, "stop_sequence": response.stop_sequence},
+        "usage": response.usage,
+    }));
+    events.push(json!({"type": "message_stop"}));
+
+    Ok(events
+        .iter()
+        .map(|event| {
+            format!(
+                "event: {}\ndata: {}\n\n",
+                event["type"].as_str().unwrap_or(""),
+                event
+            )
+        })
+        .collect())
+}
+
+#[async_trait]
+impl ProviderAdapter for MockAdapter {
+    fn config(&self) -> &ProviderConfig {
+        &self.config
+    }
+
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    async fn build_url(&self, _model: &str, _stream: bool) -> String {
+        format!("{}/v1/messages", self.base_url())
+    }
+
+    async fn transform_request(&self, request: CreateMessageRequest) -> Result<Value> {
+        serde_json::to_value(&request).map_err(|e| Error::InvalidRequest(e.to_string()))
+    }
+
+    async fn send(
+        &self,
+        _http: &reqwest::Client,
+        request: CreateMessageRequest,
+    ) -> Result<ApiResponse> {
+        self.next(request)
+    }
+
+    async fn send_stream(
+        &self,
+        _http: &reqwest::Client,
+        mut request: CreateMessageRequest,
+    ) -> Result<reqwest::Response> {
+        request.stream = Some(true);
+        let body = sse_body(&self.next(request)?)?;
+        let response = http::Response::builder()
+            .status(200)
+            .header("content-type", "text/event-stream")
+            .body(body)
+            .map_err(|e| Error::Stream(e.to_string()))?;
+        Ok(reqwest::Response::from(response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::StreamItem;
+    use crate::types::Message;
+    use futures::StreamExt;
+
+    fn request(prompt: &str) -> CreateMessageRequest {
+        CreateMessageRequest::new("mock", vec![Message::user(prompt)])
+    }
+
+    #[tokio::test]
+    async fn test_scripted_responses() {
+        let adapter = MockAdapter::new()
+            .tool_use("Read", json!({"file_path": "a.rs"}))
+            .text("done");
+        let http = reqwest::Client::new();
+
+        let first = adapter.send(&http, request("one")).await.unwrap();
+        assert_eq!(first.tool_uses()[0].id, "toolu_001");
+        let second = adapter.send(&http, request("two")).await.unwrap();
+        assert_eq!(second.text(), "done");
+        assert!(matches!(
+            adapter.send(&http, request("three")).await,
+            Err(Error::Config(_))
+        ));
+        assert_eq!(adapter.requests().len(), 3);
+        assert_eq!(adapter.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_stream_replays_response() {
+        let adapter = MockAdapter::new().tool_use("Grep", json!({"pattern": "fn main"}));
+        let client = crate::Client::new(adapter.clone()).unwrap();
+
+        let items: Vec<_> = client
+            .stream_recoverable(request("find main"))
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        let tool_use = items
+            .into_iter()
+            .find_map(|item| match item.unwrap() {
+                StreamItem::ToolUseComplete(tool_use) => Some(tool_use),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(tool_use.name, "Grep");
+        assert_eq!(tool_use.input, json!({"pattern": "fn main"}));
+        assert_eq!(adapter.requests()[0].stream, Some(true));
+    }
+}
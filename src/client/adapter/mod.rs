@@ -4,6 +4,8 @@ mod anthropic;
 #[cfg(any(feature = "aws", feature = "gcp", feature = "azure"))]
 mod base;
 mod config;
+#[cfg(any(test, feature = "testing"))]
+mod mock;
 mod request;
 mod traits;
 
@@ -22,6 +24,8 @@ pub use config::{
     BetaConfig, BetaFeature, DEFAULT_MODEL, DEFAULT_REASONING_MODEL, DEFAULT_SMALL_MODEL,
     FRONTIER_MODEL, ModelConfig, ModelType, ProviderConfig,
 };
+#[cfg(any(test, feature = "testing"))]
+pub use mock::MockAdapter;
 pub use traits::ProviderAdapter;
 
 #[cfg(feature = "aws")]
@@ -133,7 +133,7 @@ impl<'a> BatchClient<'a> {
 
         request = self.client.adapter().apply_auth_headers(request).await;
 
-        if let Some(beta_header) = self.client.config().beta.header_value() {
+        if let Some(beta_header) = self.client.betas().active().header_value() {
             request = request.header("anthropic-beta", beta_header);
         }
 
@@ -0,0 +1,269 @@
+//! Runtime registry of beta features applied to API requests.
+//!
+//! A [`BetaRegistry`] is the single place that decides which `anthropic-beta`
+//! flags a [`Client`](super::Client) sends. It is seeded from the provider's
+//! [`BetaConfig`], can be toggled at runtime through any clone of the handle,
+//! and stamps every outgoing request with the validated flag set so callers can
+//! see exactly what was applied.
+
+use std::sync::{Arc, RwLock};
+
+use super::adapter::{BetaConfig, BetaFeature};
+use super::messages::CreateMessageRequest;
+use crate::{Error, Result};
+
+#[derive(Debug, Default)]
+struct RegistryState {
+    config: BetaConfig,
+    disabled: bool,
+}
+
+/// Shared, runtime-toggleable set of enabled beta features.
+///
+/// Cloning the registry yields another handle to the same state.
+#[derive(Clone, Debug, Default)]
+pub struct BetaRegistry {
+    state: Arc<RwLock<RegistryState>>,
+}
+
+impl BetaRegistry {
+    pub fn new(config: BetaConfig) -> Self {
+        Self {
+            state: Arc::new(RwLock::new(RegistryState {
+                config,
+                disabled: false,
+            })),
+        }
+    }
+
+    pub fn enable(&self, feature: BetaFeature) {
+        self.write(|s| s.config.add(feature));
+    }
+
+    pub fn disable(&self, feature: BetaFeature) {
+        self.write(|s| s.config.remove(feature));
+    }
+
+    pub fn enable_custom(&self, flag: impl Into<String>) {
+        let flag = flag.into();
+        match BetaFeature::from_header(&flag) {
+            Some(feature) => self.enable(feature),
+            None => self.write(|s| {
+                if !s.config.flags().contains(&flag) {
+                    s.config.add_custom(flag);
+                }
+            }),
+        }
+    }
+
+    pub fn disable_custom(&self, flag: &str) {
+        match BetaFeature::from_header(flag) {
+            Some(feature) => self.disable(feature),
+            None => self.write(|s| s.config.remove_custom(flag)),
+        }
+    }
+
+    /// Suppress all beta flags without forgetting which ones are enabled.
+    ///
+    /// Used for gateways that reject experimental headers.
+    pub fn set_disabled(&self, disabled: bool) {
+        self.write(|s| s.disabled = disabled);
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.read(|s| s.disabled)
+    }
+
+    pub fn is_enabled(&self, feature: BetaFeature) -> bool {
+        self.read(|s| !s.disabled && s.config.has(feature))
+    }
+
+    /// Currently enabled flags, ignoring the global disable switch.
+    pub fn snapshot(&self) -> BetaConfig {
+        self.read(|s| s.config.clone())
+    }
+
+    /// Flags that would be sent right now, honoring the global disable switch.
+    pub fn active(&self) -> BetaConfig {
+        self.read(|s| {
+            if s.disabled {
+                BetaConfig::new()
+            } else {
+                s.config.clone()
+            }
+        })
+    }
+
+    /// Check the currently enabled flags for malformed or conflicting entries.
+    pub fn validate(&self) -> Result<()> {
+        Self::validate_config(&self.snapshot())
+    }
+
+    /// Resolve the flags a request should be sent with.
+    ///
+    /// Combines the registry state with flags the request itself needs
+    /// (e.g. structured outputs or computer use) and any flags already set on the request.
+    pub fn resolve(&self, request: &CreateMessageRequest) -> Result<BetaConfig> {
+        let (mut config, disabled) = self.read(|s| (s.config.clone(), s.disabled));
+        if disabled {
+            return Ok(BetaConfig::new());
+        }
+
+        if let Some(ref extra) = request.betas {
+            config.merge(extra);
+        }
+        if request.requires_structured_outputs() {
+            config.add(BetaFeature::StructuredOutputs);
+        }
+        for feature in request.tool_betas() {
+            config.add(feature);
+        }
+
+        Self::validate_config(&config)?;
+        Ok(config)
+    }
+
+    /// Stamp the request with its resolved flags.
+    pub fn apply(&self, mut request: CreateMessageRequest) -> Result<CreateMessageRequest> {
+        request.betas = Some(self.resolve(&request)?);
+        Ok(request)
+    }
+
+    fn validate_config(config: &BetaConfig) -> Result<()> {
+        let flags = config.flags();
+
+        for flag in &flags {
+            let well_formed = !flag.is_empty()
+                && flag
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+            if !well_formed {
+                return Err(Error::Config(format!("Invalid beta flag: {flag:?}")));
+            }
+        }
+
+        for (i, a) in flags.iter().enumerate() {
+            for b in &flags[i + 1..] {
+                if a != b && beta_family(a).is_some() && beta_family(a) == beta_family(b) {
+                    return Err(Error::Config(format!(
+                        "Conflicting beta flags: {a} and {b} are different versions of the same feature"
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read<T>(&self, f: impl FnOnce(&RegistryState) -> T) -> T {
+        f(&self.state.read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    fn write(&self, f: impl FnOnce(&mut RegistryState)) {
+        f(&mut self.state.write().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+/// Strip the trailing `-YYYY-MM-DD` version from a flag.
+fn beta_family(flag: &str) -> Option<&str> {
+    let (family, date) = flag.split_at_checked(flag.len().checked_sub(11)?)?;
+    let date = date.strip_prefix('-')?;
+    let valid = date.len() == 10
+        && date.char_indices().all(|(i, c)| match i {
+            4 | 7 => c == '-',
+            _ => c.is_ascii_digit(),
+        });
+    valid.then_some(family)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::messages::OutputFormat;
+    use crate::types::Message;
+
+    fn request() -> CreateMessageRequest {
+        CreateMessageRequest::new("model", vec![Message::user("hi")])
+    }
+
+    #[test]
+    fn test_runtime_toggle_shared_across_clones() {
+        let registry = BetaRegistry::new(BetaConfig::new().feature(BetaFeature::WebSearch));
+        let handle = registry.clone();
+
+        handle.enable(BetaFeature::Effort);
+        handle.disable(BetaFeature::WebSearch);
+
+        assert!(registry.is_enabled(BetaFeature::Effort));
+        assert!(!registry.is_enabled(BetaFeature::WebSearch));
+    }
+
+    #[test]
+    fn test_custom_flag_maps_to_known_feature() {
+        let registry = BetaRegistry::default();
+        registry.enable_custom("effort-2025-11-24");
+        registry.enable_custom("my-beta-2025-01-01");
+        registry.enable_custom("my-beta-2025-01-01");
+
+        assert!(registry.is_enabled(BetaFeature::Effort));
+        assert_eq!(
+            registry.snapshot().flags(),
+            vec!["effort-2025-11-24", "my-beta-2025-01-01"]
+        );
+
+        registry.disable_custom("my-beta-2025-01-01");
+        assert_eq!(registry.snapshot().flags(), vec!["effort-2025-11-24"]);
+    }
+
+    #[test]
+    fn test_resolve_adds_request_betas() {
+        let registry = BetaRegistry::new(BetaConfig::new().feature(BetaFeature::WebFetch));
+        let req = request().output_format(OutputFormat::json_schema(serde_json::json!({})));
+
+        let resolved = registry.resolve(&req).unwrap();
+        assert!(resolved.has(BetaFeature::WebFetch));
+        assert!(resolved.has(BetaFeature::StructuredOutputs));
+
+        let applied = registry.apply(req).unwrap();
+        assert_eq!(applied.betas.unwrap().flags(), resolved.flags());
+    }
+
+    #[test]
+    fn test_resolve_adds_tool_betas() {
+        let registry = BetaRegistry::default();
+        let req = request().api_tools(vec![crate::types::ComputerUseTool::new(1024, 768).into()]);
+
+        let resolved = registry.resolve(&req).unwrap();
+        assert_eq!(resolved.flags(), vec!["computer-use-2025-01-24"]);
+    }
+
+    #[test]
+    fn test_disabled_registry_sends_nothing() {
+        let registry = BetaRegistry::new(BetaConfig::all());
+        registry.set_disabled(true);
+
+        assert!(!registry.is_enabled(BetaFeature::Mcp));
+        assert!(registry.resolve(&request()).unwrap().is_empty());
+        assert!(!registry.snapshot().is_empty());
+    }
+
+    #[test]
+    fn test_validate_rejects_conflicts() {
+        let registry = BetaRegistry::new(BetaConfig::new().feature(BetaFeature::WebFetch));
+        assert!(registry.validate().is_ok());
+
+        registry.enable_custom("web-fetch-2024-01-01");
+        assert!(matches!(registry.validate(), Err(Error::Config(_))));
+
+        registry.disable_custom("web-fetch-2024-01-01");
+        registry.enable_custom("bad flag");
+        assert!(registry.resolve(&request()).is_err());
+    }
+
+    #[test]
+    fn test_beta_family() {
+        assert_eq!(beta_family("web-fetch-2025-09-10"), Some("web-fetch"));
+        assert_eq!(beta_family("claude-code-20250219"), None);
+        assert_eq!(beta_family("short"), None);
+    }
+}
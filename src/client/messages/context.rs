@@ -87,6 +87,18 @@ impl ContextManagement {
         self.edits.push(edit);
         self
     }
+
+    /// Keep `tool`'s uses and results when clearing tool uses.
+    pub fn exclude_tool(&mut self, tool: &str) {
+        for edit in &mut self.edits {
+            if let ContextEdit::ClearToolUses { exclude_tools, .. } = edit {
+                let excluded = exclude_tools.get_or_insert_with(Vec::new);
+                if !excluded.iter().any(|t| t == tool) {
+                    excluded.push(tool.to_string());
+                }
+            }
+        }
+    }
 }
 
 impl Default for ContextManagement {
@@ -8,8 +8,10 @@ use super::config::{
 };
 use super::context::ContextManagement;
 use super::types::{ApiTool, RequestMetadata};
+use crate::client::adapter::{BetaConfig, BetaFeature};
 use crate::types::{
-    Message, SystemPrompt, ToolDefinition, ToolSearchTool, WebFetchTool, WebSearchTool,
+    CodeExecutionTool, MemoryToolDefinition, Message, SystemPrompt, ToolDefinition, ToolSearchTool,
+    WebFetchTool, WebSearchTool,
 };
 
 #[derive(Debug, Clone, Serialize)]
@@ -43,6 +45,9 @@ pub struct CreateMessageRequest {
     pub context_management: Option<ContextManagement>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub output_config: Option<OutputConfig>,
+    /// Beta flags sent as the `anthropic-beta` header, resolved by the client's `BetaRegistry`.
+    #[serde(skip)]
+    pub betas: Option<BetaConfig>,
 }
 
 impl CreateMessageRequest {
@@ -64,6 +69,7 @@ impl CreateMessageRequest {
             output_format: None,
             context_management: None,
             output_config: None,
+            betas: None,
         }
     }
 
@@ -122,8 +128,25 @@ impl CreateMessageRequest {
         self
     }
 
+    pub fn code_execution(mut self, config: CodeExecutionTool) -> Self {
+        let mut tools = self.tools.unwrap_or_default();
+        tools.push(ApiTool::CodeExecution(config));
+        self.tools = Some(tools);
+        self
+    }
+
+    /// Enable the memory tool; its commands are executed client-side.
+    pub fn memory(mut self) -> Self {
+        let mut tools = self.tools.unwrap_or_default();
+        tools.push(ApiTool::Memory(MemoryToolDefinition::default()));
+        self.tools = Some(tools);
+        self.protect_memory();
+        self
+    }
+
     pub fn api_tools(mut self, tools: Vec<ApiTool>) -> Self {
         self.tools = Some(tools);
+        self.protect_memory();
         self
     }
 
@@ -197,6 +220,52 @@ impl CreateMessageRequest {
         self
     }
 
+    /// Request-specific beta flags, merged with the client's enabled betas.
+    pub fn betas(mut self, betas: BetaConfig) -> Self {
+        self.betas = Some(betas);
+        self
+    }
+
+    /// Whether the request uses structured outputs (output format or strict tools).
+    pub fn requires_structured_outputs(&self) -> bool {
+        self.output_format.is_some()
+            || self
+                .tools
+                .as_ref()
+                .is_some_and(|tools| tools.iter().any(|t| t.is_strict()))
+    }
+
+    /// Beta flags required by the request's tools (e.g. computer use) and
+    /// context management.
+    pub fn tool_betas(&self) -> impl Iterator<Item = BetaFeature> + '_ {
+        self.tools
+            .iter()
+            .flatten()
+            .filter_map(|tool| tool.beta_feature())
+            .chain(
+                self.context_management
+                    .as_ref()
+                    .map(|_| BetaFeature::ContextManagement),
+            )
+    }
+
+    fn has_memory_tool(&self) -> bool {
+        self.tools
+            .iter()
+            .flatten()
+            .any(|tool| matches!(tool, ApiTool::Memory(_)))
+    }
+
+    /// Clearing old tool uses must never drop the memory tool's results: they
+    /// are how the model learns what it stored.
+    fn protect_memory(&mut self) {
+        if self.has_memory_tool()
+            && let Some(management) = &mut self.context_management
+        {
+            management.exclude_tool(MemoryToolDefinition::NAME);
+        }
+    }
+
     pub fn output_format(mut self, format: OutputFormat) -> Self {
         self.output_format = Some(format);
         self
@@ -216,6 +285,7 @@ impl CreateMessageRequest {
 
     pub fn context_management(mut self, management: ContextManagement) -> Self {
         self.context_management = Some(management);
+        self.protect_memory();
         self
     }
 
@@ -429,6 +499,48 @@ mod tests {
         let request = CreateMessageRequest::new("claude-sonnet-4-5", vec![Message::user("Hi")])
             .context_management(mgmt);
         assert!(request.context_management.is_some());
+        assert_eq!(
+            request.tool_betas().collect::<Vec<_>>(),
+            vec![BetaFeature::ContextManagement]
+        );
+    }
+
+    fn mgmt_with_exclusion() -> ContextManagement {
+        let mut mgmt = ContextManagement::new().edit(ContextManagement::clear_tool_uses());
+        mgmt.exclude_tool("memory");
+        mgmt
+    }
+
+    #[test]
+    fn test_memory_tool_excluded_from_tool_clearing() {
+        let mgmt = ContextManagement::new()
+            .edit(ContextManagement::clear_tool_uses())
+            .edit(ContextManagement::clear_thinking(2));
+        let request = CreateMessageRequest::new("claude-sonnet-4-5", vec![Message::user("Hi")])
+            .context_management(mgmt)
+            .memory();
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["tools"][0]["type"], "memory_20250818");
+        assert_eq!(
+            json["context_management"]["edits"][0]["exclude_tools"],
+            serde_json::json!(["memory"])
+        );
+        assert!(
+            request
+                .tool_betas()
+                .all(|beta| beta == BetaFeature::ContextManagement)
+        );
+
+        // Order of the builder calls does not matter, and the exclusion is not duplicated.
+        let request = CreateMessageRequest::new("claude-sonnet-4-5", vec![Message::user("Hi")])
+            .memory()
+            .context_management(mgmt_with_exclusion());
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            json["context_management"]["edits"][0]["exclude_tools"],
+            serde_json::json!(["memory"])
+        );
     }
 
     #[test]
@@ -4,7 +4,12 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
-use crate::types::{ToolDefinition, ToolSearchTool, WebFetchTool, WebSearchTool};
+use crate::client::BetaFeature;
+use crate::common::clock;
+use crate::types::{
+    CodeExecutionTool, ComputerUseTool, MemoryToolDefinition, TextEditorDefinition, ToolDefinition,
+    ToolSearchTool, WebFetchTool, WebSearchTool,
+};
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RequestMetadata {
@@ -16,9 +21,9 @@ pub struct RequestMetadata {
 
 impl RequestMetadata {
     pub fn generate() -> Self {
-        let session_id = uuid::Uuid::new_v4();
+        let session_id = clock::new_uuid();
         let user_hash = format!("{:x}", simple_hash(session_id.as_bytes()));
-        let account_uuid = uuid::Uuid::new_v4();
+        let account_uuid = clock::new_uuid();
         Self {
             user_id: Some(format!(
                 "user_{}_account_{}_session_{}",
@@ -45,6 +50,10 @@ pub enum ApiTool {
     WebSearch(WebSearchTool),
     WebFetch(WebFetchTool),
     ToolSearch(ToolSearchTool),
+    CodeExecution(CodeExecutionTool),
+    Computer(ComputerUseTool),
+    TextEditor(TextEditorDefinition),
+    Memory(MemoryToolDefinition),
 }
 
 impl From<ToolDefinition> for ApiTool {
@@ -71,13 +80,60 @@ impl From<WebFetchTool> for ApiTool {
     }
 }
 
+impl From<CodeExecutionTool> for ApiTool {
+    fn from(tool: CodeExecutionTool) -> Self {
+        Self::CodeExecution(tool)
+    }
+}
+
+impl From<ComputerUseTool> for ApiTool {
+    fn from(tool: ComputerUseTool) -> Self {
+        Self::Computer(tool)
+    }
+}
+
+impl From<TextEditorDefinition> for ApiTool {
+    fn from(tool: TextEditorDefinition) -> Self {
+        Self::TextEditor(tool)
+    }
+}
+
+impl From<MemoryToolDefinition> for ApiTool {
+    fn from(tool: MemoryToolDefinition) -> Self {
+        Self::Memory(tool)
+    }
+}
+
 impl ApiTool {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Custom(def) => &def.name,
+            Self::WebSearch(tool) => &tool.name,
+            Self::WebFetch(tool) => &tool.name,
+            Self::ToolSearch(tool) => tool.name(),
+            Self::CodeExecution(tool) => &tool.name,
+            Self::Computer(tool) => &tool.name,
+            Self::TextEditor(tool) => &tool.name,
+            Self::Memory(tool) => &tool.name,
+        }
+    }
+
     pub fn is_strict(&self) -> bool {
         match self {
             Self::Custom(def) => def.strict == Some(true),
             _ => false,
         }
     }
+
+    /// Beta flag the API requires before it accepts this tool.
+    pub fn beta_feature(&self) -> Option<BetaFeature> {
+        match self {
+            Self::CodeExecution(_) => Some(BetaFeature::CodeExecution),
+            Self::Computer(_) => Some(BetaFeature::ComputerUse),
+            Self::Memory(_) => Some(BetaFeature::ContextManagement),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
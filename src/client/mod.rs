@@ -2,6 +2,7 @@
 
 pub mod adapter;
 pub mod batch;
+pub mod beta;
 pub mod fallback;
 pub mod files;
 pub mod gateway;
@@ -12,6 +13,8 @@ pub mod resilience;
 pub mod schema;
 mod streaming;
 
+#[cfg(any(test, feature = "testing"))]
+pub use adapter::MockAdapter;
 pub use adapter::{
     AnthropicAdapter, BetaConfig, BetaFeature, CloudProvider, DEFAULT_MODEL,
     DEFAULT_REASONING_MODEL, DEFAULT_SMALL_MODEL, FRONTIER_MODEL, ModelConfig, ModelType,
@@ -20,6 +23,7 @@ pub use adapter::{
 pub use batch::{
     BatchClient, BatchRequest, BatchResult, BatchStatus, CreateBatchRequest, MessageBatch,
 };
+pub use beta::BetaRegistry;
 pub use fallback::{FallbackConfig, FallbackTrigger};
 pub use files::{File, FileData, FileDownload, FileListResponse, FilesClient, UploadFileRequest};
 pub use gateway::GatewayConfig;
@@ -59,6 +63,7 @@ pub struct Client {
     http: reqwest::Client,
     fallback_config: Option<FallbackConfig>,
     resilience: Option<Arc<Resilience>>,
+    betas: BetaRegistry,
 }
 
 impl Client {
@@ -69,20 +74,17 @@ impl Client {
             .build()
             .map_err(Error::Network)?;
 
-        Ok(Self {
-            adapter: Arc::new(adapter),
-            http,
-            fallback_config: None,
-            resilience: None,
-        })
+        Ok(Self::from_http(adapter, http))
     }
 
     pub fn from_http(adapter: impl ProviderAdapter + 'static, http: reqwest::Client) -> Self {
+        let betas = BetaRegistry::new(adapter.config().beta.clone());
         Self {
             adapter: Arc::new(adapter),
             http,
             fallback_config: None,
             resilience: None,
+            betas,
         }
     }
 
@@ -104,6 +106,14 @@ impl Client {
         ClientBuilder::default()
     }
 
+    /// Beta features applied to every request sent through this client.
+    ///
+    /// The registry is shared across clones, so toggling a feature affects
+    /// subsequent requests from all of them.
+    pub fn betas(&self) -> &BetaRegistry {
+        &self.betas
+    }
+
     pub async fn query(&self, prompt: &str) -> Result<String> {
         self.query_with_model(prompt, ModelType::Primary).await
     }
@@ -113,6 +123,7 @@ impl Client {
         let request = CreateMessageRequest::new(&model, vec![crate::types::Message::user(prompt)])
             .max_tokens(self.adapter.config().max_tokens);
         request.validate()?;
+        let request = self.betas.apply(request)?;
 
         let response = self.adapter.send(&self.http, request).await?;
         Ok(response.text())
@@ -146,6 +157,7 @@ impl Client {
 
     async fn send_inner(&self, request: CreateMessageRequest) -> Result<crate::types::ApiResponse> {
         request.validate()?;
+        let request = self.betas.apply(request)?;
 
         let fallback = match &self.fallback_config {
             Some(f) => f,
@@ -197,6 +209,7 @@ impl Client {
         request: CreateMessageRequest,
     ) -> Result<crate::types::ApiResponse> {
         request.validate()?;
+        let request = self.betas.apply(request)?;
         self.adapter.send(&self.http, request).await
     }
 
@@ -212,6 +225,7 @@ impl Client {
         let request = CreateMessageRequest::new(&model, vec![crate::types::Message::user(prompt)])
             .max_tokens(self.adapter.config().max_tokens);
         request.validate()?;
+        let request = self.betas.apply(request)?;
 
         let response = self.adapter.send_stream(&self.http, request).await?;
         let stream = StreamParser::new(response.bytes_stream());
@@ -243,6 +257,7 @@ impl Client {
         request: CreateMessageRequest,
     ) -> Result<impl futures::Stream<Item = Result<StreamItem>> + Send + 'static + use<>> {
         request.validate()?;
+        let request = self.betas.apply(request)?;
         let response = self.adapter.send_stream(&self.http, request).await?;
         Ok(StreamParser::new(response.bytes_stream()))
     }
@@ -259,6 +274,7 @@ impl Client {
         >,
     > {
         request.validate()?;
+        let request = self.betas.apply(request)?;
         let response = self.adapter.send_stream(&self.http, request).await?;
         Ok(RecoverableStream::new(response.bytes_stream()))
     }
@@ -340,6 +356,7 @@ impl Client {
         request: CreateMessageRequest,
     ) -> Result<reqwest::Response> {
         request.validate()?;
+        let request = self.betas.apply(request)?;
         self.with_auth_retry(|| self.adapter.send_stream(&self.http, request.clone()))
             .await
     }
@@ -593,11 +610,17 @@ impl ClientBuilder {
 
         let resilience = self.resilience_config.map(|c| Arc::new(Resilience::new(c)));
 
+        let betas = BetaRegistry::new(adapter.config().beta.clone());
+        if self.gateway.as_ref().is_some_and(|gw| gw.disable_betas) {
+            betas.set_disabled(true);
+        }
+
         Ok(Client {
             adapter: Arc::from(adapter),
             http,
             fallback_config: self.fallback_config,
             resilience,
+            betas,
         })
     }
 }
@@ -0,0 +1,91 @@
+//! Time and ID sources of the agent loop.
+//!
+//! Normally these read the system clock and generate random UUIDs. While a
+//! [`Deterministic`](crate::testing::Deterministic) guard is installed on the
+//! current thread they return virtual time and seeded UUIDs instead.
+
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use rand::RngCore;
+use rand::rngs::StdRng;
+use uuid::Uuid;
+
+pub(crate) struct VirtualState {
+    rng: StdRng,
+    wall: DateTime<Utc>,
+    origin: Instant,
+    offset: Duration,
+    tool_duration: Duration,
+}
+
+impl VirtualState {
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn new(seed: u64, wall: DateTime<Utc>, tool_duration: Duration) -> Self {
+        Self {
+            rng: rand::SeedableRng::seed_from_u64(seed),
+            wall,
+            origin: Instant::now(),
+            offset: Duration::ZERO,
+            tool_duration,
+        }
+    }
+}
+
+thread_local! {
+    static VIRTUAL: RefCell<Option<VirtualState>> = const { RefCell::new(None) };
+}
+
+fn with_virtual<R>(f: impl FnOnce(&mut VirtualState) -> R) -> Option<R> {
+    VIRTUAL.with(|v| v.borrow_mut().as_mut().map(f))
+}
+
+/// Install (or remove) virtual state on this thread, returning the previous one.
+#[cfg(any(test, feature = "testing"))]
+pub(crate) fn swap(state: Option<VirtualState>) -> Option<VirtualState> {
+    VIRTUAL.with(|v| std::mem::replace(&mut *v.borrow_mut(), state))
+}
+
+/// Move virtual time forward. No-op without virtual state.
+#[cfg(any(test, feature = "testing"))]
+pub(crate) fn advance(by: Duration) {
+    with_virtual(|s| s.offset += by);
+}
+
+/// Current wall-clock time.
+pub(crate) fn now_utc() -> DateTime<Utc> {
+    with_virtual(|s| s.wall + s.offset).unwrap_or_else(Utc::now)
+}
+
+/// Today's date in local time (UTC under virtual time, to not depend on the host).
+pub(crate) fn today() -> NaiveDate {
+    with_virtual(|s| (s.wall + s.offset).date_naive()).unwrap_or_else(|| Local::now().date_naive())
+}
+
+/// Current monotonic time.
+pub(crate) fn now() -> Instant {
+    with_virtual(|s| s.origin + s.offset).unwrap_or_else(Instant::now)
+}
+
+pub(crate) fn elapsed(since: Instant) -> Duration {
+    now().saturating_duration_since(since)
+}
+
+/// Duration of a tool call started at `since`.
+///
+/// Virtual time reports the configured fixed tool duration, so results do not
+/// depend on the order in which parallel calls finish.
+pub(crate) fn tool_elapsed(since: Instant) -> Duration {
+    with_virtual(|s| s.tool_duration).unwrap_or_else(|| since.elapsed())
+}
+
+/// A new v4 UUID, drawn from the seeded generator under virtual state.
+pub(crate) fn new_uuid() -> Uuid {
+    with_virtual(|s| {
+        let mut bytes = [0u8; 16];
+        s.rng.fill_bytes(&mut bytes);
+        uuid::Builder::from_random_bytes(bytes).into_uuid()
+    })
+    .unwrap_or_else(Uuid::new_v4)
+}
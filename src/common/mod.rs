@@ -1,3 +1,4 @@
+pub(crate) mod clock;
 mod content_source;
 mod directory;
 mod file_provider;
@@ -27,7 +28,9 @@ pub use path_matched::PathMatched;
 pub use provider::Provider;
 pub(crate) use provider::{ChainProvider, InMemoryProvider};
 pub use source_type::SourceType;
-pub use tool_matcher::{is_tool_allowed, matches_tool_pattern};
+pub use tool_matcher::{
+    is_tool_allowed, is_tool_use_allowed, matches_tool_pattern, matches_tool_use,
+};
 
 pub trait Named {
     fn name(&self) -> &str;
@@ -43,4 +46,10 @@ pub trait ToolRestricted {
     fn is_tool_allowed(&self, tool_name: &str) -> bool {
         tool_matcher::is_tool_allowed(self.allowed_tools(), tool_name)
     }
+
+    /// Like [`is_tool_allowed`](Self::is_tool_allowed), but also checks
+    /// scopes such as `Bash(git:*)` against the call's input.
+    fn is_tool_use_allowed(&self, tool_name: &str, input: &serde_json::Value) -> bool {
+        tool_matcher::is_tool_use_allowed(self.allowed_tools(), tool_name, input)
+    }
 }
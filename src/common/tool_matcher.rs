@@ -1,5 +1,10 @@
 //! Tool name matching utilities.
 
+use serde_json::Value;
+
+use crate::permissions::ArgumentMatcher;
+use crate::security::bash::{CommandPattern, matches_all};
+
 /// Checks if a tool name matches an allowed tool pattern.
 ///
 /// Supports patterns like:
@@ -22,6 +27,44 @@ pub fn is_tool_allowed(allowed: &[String], tool_name: &str) -> bool {
     allowed.iter().any(|p| matches_tool_pattern(p, tool_name))
 }
 
+/// Checks if a tool call matches a pattern, scope included.
+///
+/// `Bash(git:*,cargo:*)` matches a Bash command whose simple commands each
+/// start with `git` or `cargo`; other scopes are matched as in permission
+/// rules. A pattern without a scope matches every call of the tool.
+pub fn matches_tool_use(pattern: &str, tool_name: &str, input: &Value) -> bool {
+    if !matches_tool_pattern(pattern, tool_name) {
+        return false;
+    }
+    let Some(scope) = pattern
+        .strip_prefix(tool_name)
+        .and_then(|rest| rest.strip_prefix('('))
+        .and_then(|rest| rest.strip_suffix(')'))
+    else {
+        return true;
+    };
+    let scopes = scope.split(',').map(str::trim);
+    if tool_name == "Bash" {
+        let patterns: Vec<CommandPattern> = scopes.map(CommandPattern::new).collect();
+        return input
+            .get("command")
+            .and_then(Value::as_str)
+            .is_some_and(|command| matches_all(&patterns, command));
+    }
+    scopes
+        .filter_map(|scope| ArgumentMatcher::for_tool(tool_name, scope))
+        .any(|matcher| matcher.matches(input, None, true))
+}
+
+/// Checks if a tool call is allowed by a list of allowed patterns, scopes
+/// included. An empty list allows everything.
+pub fn is_tool_use_allowed(allowed: &[String], tool_name: &str, input: &Value) -> bool {
+    allowed.is_empty()
+        || allowed
+            .iter()
+            .any(|p| matches_tool_use(p, tool_name, input))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -56,4 +99,37 @@ mod tests {
         assert!(is_tool_allowed(&allowed, "Bash"));
         assert!(!is_tool_allowed(&allowed, "Write"));
     }
+
+    #[test]
+    fn test_is_tool_use_allowed_scoped() {
+        let allowed = vec!["Read".to_string(), "Bash(git:*, cargo test:*)".to_string()];
+        let bash = |command: &str| serde_json::json!({"command": command});
+
+        assert!(is_tool_use_allowed(
+            &allowed,
+            "Read",
+            &serde_json::json!({})
+        ));
+        assert!(is_tool_use_allowed(
+            &allowed,
+            "Bash",
+            &bash("git status && cargo test -q")
+        ));
+        assert!(!is_tool_use_allowed(
+            &allowed,
+            "Bash",
+            &bash("env CI=1 git log | git show")
+        ));
+        assert!(!is_tool_use_allowed(
+            &allowed,
+            "Bash",
+            &bash("git status; (rm -rf .)")
+        ));
+        assert!(!is_tool_use_allowed(&allowed, "Bash", &bash("cargo build")));
+        assert!(!is_tool_use_allowed(
+            &allowed,
+            "Write",
+            &serde_json::json!({})
+        ));
+    }
 }
@@ -0,0 +1,289 @@
+//! Typed access to a Claude Code configuration directory.
+//!
+//! A [`ClaudeDir`] describes the layout the CLI uses for `~/.claude` (and, with
+//! [`ClaudeDir::project`], `<project>/.claude`):
+//!
+//! ```text
+//! ~/.claude/
+//! ├── settings.json      # model, env, permissions, hooks, mcpServers, outputStyle
+//! ├── CLAUDE.md          # memory
+//! ├── rules/             # rule files indexed with the memory
+//! ├── commands/          # slash commands (nested dirs become `a:b`)
+//! ├── skills/            # SKILL.md / *.skill.md
+//! ├── agents/            # subagent definitions
+//! └── output-styles/     # custom output styles
+//! ~/.claude.json         # user-scoped MCP servers
+//! ```
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use super::{Settings, SettingsLoader};
+use crate::common::Provider;
+use crate::context::{MemoryContent, MemoryLoader};
+use crate::mcp::{McpServerConfig, parse_mcp_servers};
+use crate::output_style::{OutputStyle, file_output_style_provider};
+use crate::skills::{SkillIndex, SkillIndexLoader};
+use crate::subagents::{SubagentIndex, SubagentIndexLoader};
+
+/// Layout of a Claude Code configuration directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaudeDir {
+    root: PathBuf,
+    mcp_config: Option<PathBuf>,
+}
+
+impl ClaudeDir {
+    /// A configuration directory at an arbitrary path, without an MCP config file.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            mcp_config: None,
+        }
+    }
+
+    /// `~/.claude`, with user-scoped MCP servers from `~/.claude.json`.
+    pub fn user() -> Option<Self> {
+        crate::common::home_dir().map(|home| Self {
+            root: home.join(".claude"),
+            mcp_config: Some(home.join(".claude.json")),
+        })
+    }
+
+    /// `<project>/.claude`, with project-scoped MCP servers from `<project>/.mcp.json`.
+    pub fn project(project_dir: impl AsRef<Path>) -> Self {
+        let project_dir = project_dir.as_ref();
+        Self {
+            root: project_dir.join(".claude"),
+            mcp_config: Some(project_dir.join(".mcp.json")),
+        }
+    }
+
+    /// Override the file MCP servers are read from (`{"mcpServers": {...}}`).
+    pub fn mcp_config(mut self, path: impl Into<PathBuf>) -> Self {
+        self.mcp_config = Some(path.into());
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn settings_file(&self) -> PathBuf {
+        self.root.join("settings.json")
+    }
+
+    pub fn memory_file(&self) -> PathBuf {
+        self.root.join("CLAUDE.md")
+    }
+
+    pub fn commands_dir(&self) -> PathBuf {
+        self.root.join("commands")
+    }
+
+    pub fn skills_dir(&self) -> PathBuf {
+        self.root.join("skills")
+    }
+
+    pub fn agents_dir(&self) -> PathBuf {
+        self.root.join("agents")
+    }
+
+    pub fn output_styles_dir(&self) -> PathBuf {
+        self.root.join("output-styles")
+    }
+
+    pub fn mcp_config_file(&self) -> Option<&Path> {
+        self.mcp_config.as_deref()
+    }
+
+    pub fn exists(&self) -> bool {
+        self.root.is_dir()
+    }
+
+    /// Load everything the directory defines.
+    ///
+    /// Missing files and directories are skipped; malformed `settings.json` or
+    /// MCP config files are errors. Skills take precedence over commands of the same name.
+    pub async fn load(&self) -> crate::Result<ClaudeDirResources> {
+        let mut settings_loader = SettingsLoader::new();
+        if self.settings_file().exists() {
+            settings_loader.load_from(&self.root).await?;
+        }
+        let settings = settings_loader.into_settings();
+
+        let loader = SkillIndexLoader::new();
+        let mut skills = loader.scan_directory(&self.skills_dir()).await?;
+        for command in loader.scan_commands(&self.commands_dir()).await? {
+            if !skills.iter().any(|s| s.name == command.name) {
+                skills.push(command);
+            }
+        }
+
+        let subagents = SubagentIndexLoader::new()
+            .scan_directory(&self.agents_dir())
+            .await?;
+
+        let output_styles = if self.output_styles_dir().is_dir() {
+            file_output_style_provider()
+                .path(self.output_styles_dir())
+                .load_all()
+                .await?
+        } else {
+            Vec::new()
+        };
+
+        let memory = MemoryLoader::new()
+            .load_base(&self.root)
+            .await
+            .unwrap_or_default();
+
+        let mut mcp_servers = HashMap::new();
+        if let Some(path) = self.mcp_config_file().filter(|p| p.exists()) {
+            let content = tokio::fs::read_to_string(path).await?;
+            let file: Value = serde_json::from_str(&content)?;
+            if let Some(servers) = file.get("mcpServers").and_then(Value::as_object) {
+                mcp_servers.extend(parse_mcp_servers(servers));
+            }
+        }
+
+        Ok(ClaudeDirResources {
+            settings,
+            skills,
+            subagents,
+            output_styles,
+            memory,
+            mcp_servers,
+        })
+    }
+}
+
+/// Resources loaded from a [`ClaudeDir`].
+#[derive(Debug, Default)]
+pub struct ClaudeDirResources {
+    pub settings: Settings,
+    /// Skills and slash commands.
+    pub skills: Vec<SkillIndex>,
+    pub subagents: Vec<SubagentIndex>,
+    pub output_styles: Vec<OutputStyle>,
+    pub memory: MemoryContent,
+    /// Servers from the MCP config file. Servers in `settings.json` stay in `settings`.
+    pub mcp_servers: HashMap<String, McpServerConfig>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout() {
+        let dir = ClaudeDir::project("/work/app");
+        assert_eq!(dir.root(), Path::new("/work/app/.claude"));
+        assert_eq!(dir.agents_dir(), PathBuf::from("/work/app/.claude/agents"));
+        assert_eq!(
+            dir.mcp_config_file(),
+            Some(Path::new("/work/app/.mcp.json"))
+        );
+        assert_eq!(ClaudeDir::new("/tmp/claude").mcp_config_file(), None);
+    }
+
+    #[tokio::test]
+    async fn test_load_full_directory() {
+        let home = tempfile::tempdir().unwrap();
+        let root = home.path().join(".claude");
+        for sub in [
+            "commands/git",
+            "skills/deploy",
+            "agents",
+            "output-styles",
+            "rules",
+        ] {
+            std::fs::create_dir_all(root.join(sub)).unwrap();
+        }
+
+        std::fs::write(
+            root.join("settings.json"),
+            r#"{"model": "claude-opus-4-5", "outputStyle": "terse", "env": {"FOO": "1"}}"#,
+        )
+        .unwrap();
+        std::fs::write(root.join("CLAUDE.md"), "Prefer small commits.").unwrap();
+        std::fs::write(root.join("rules/testing.md"), "# Testing").unwrap();
+        std::fs::write(
+            root.join("commands/git/commit.md"),
+            "Write a commit message",
+        )
+        .unwrap();
+        std::fs::write(root.join("commands/deploy.md"), "Shadowed by the skill").unwrap();
+        std::fs::write(
+            root.join("skills/deploy/SKILL.md"),
+            "---\nname: deploy\ndescription: Deploy the app\n---\nSteps",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("agents/reviewer.md"),
+            "---\nname: reviewer\ndescription: Reviews code\n---\nYou review code.",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("output-styles/terse.md"),
+            "---\nname: terse\ndescription: Short answers\n---\nBe brief.",
+        )
+        .unwrap();
+        std::fs::write(
+            home.path().join(".claude.json"),
+            r#"{"mcpServers": {
+                "fs": {"command": "mcp-fs", "args": ["/tmp"]},
+                "remote": {"type": "sse", "url": "http://localhost:1/sse"},
+                "unsupported": {"type": "carrier-pigeon"}
+            }}"#,
+        )
+        .unwrap();
+
+        let resources = ClaudeDir::new(&root)
+            .mcp_config(home.path().join(".claude.json"))
+            .load()
+            .await
+            .unwrap();
+
+        assert_eq!(resources.settings.model.as_deref(), Some("claude-opus-4-5"));
+        assert_eq!(resources.settings.output_style.as_deref(), Some("terse"));
+
+        let mut skills: Vec<_> = resources.skills.iter().map(|s| s.name.as_str()).collect();
+        skills.sort();
+        assert_eq!(skills, vec!["deploy", "git:commit"]);
+        let deploy = resources
+            .skills
+            .iter()
+            .find(|s| s.name == "deploy")
+            .unwrap();
+        assert_eq!(deploy.description, "Deploy the app");
+
+        assert_eq!(resources.subagents.len(), 1);
+        assert_eq!(resources.subagents[0].name, "reviewer");
+        assert_eq!(resources.output_styles.len(), 1);
+        assert!(
+            resources
+                .memory
+                .combined_claude_md()
+                .contains("small commits")
+        );
+        assert_eq!(resources.memory.rule_indices.len(), 1);
+        assert_eq!(resources.memory.rule_indices[0].name, "testing");
+
+        assert_eq!(resources.mcp_servers.len(), 2);
+        assert!(matches!(
+            resources.mcp_servers.get("fs"),
+            Some(McpServerConfig::Stdio { command, .. }) if command == "mcp-fs"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_load_missing_directory() {
+        let resources = ClaudeDir::new("/nonexistent/.claude").load().await.unwrap();
+        assert!(resources.skills.is_empty());
+        assert!(resources.mcp_servers.is_empty());
+        assert!(resources.settings.model.is_none());
+    }
+}
@@ -13,6 +13,8 @@
 //! # }
 //! ```
 
+#[cfg(feature = "cli-integration")]
+pub mod claude_dir;
 pub mod cloud;
 pub mod composite;
 pub mod env;
@@ -22,6 +24,8 @@ pub mod provider;
 pub mod settings;
 pub mod validator;
 
+#[cfg(feature = "cli-integration")]
+pub use claude_dir::{ClaudeDir, ClaudeDirResources};
 pub use cloud::{BedrockConfig, CloudConfig, FoundryConfig, TokenLimits, VertexConfig};
 pub use composite::CompositeConfigProvider;
 pub use env::EnvConfigProvider;
@@ -29,8 +33,8 @@ pub use file::FileConfigProvider;
 pub use memory::MemoryConfigProvider;
 pub use provider::{ConfigProvider, ConfigProviderExt};
 pub use settings::{
-    HookConfig, HooksSettings, NetworkSandboxSettings, PermissionSettings, SandboxSettings,
-    Settings, SettingsLoader, SettingsSource, ToolSearchSettings,
+    BashPolicySetting, HookConfig, HooksSettings, NetworkSandboxSettings, PermissionSettings,
+    SandboxSettings, Settings, SettingsLoader, SettingsSource, ToolSearchSettings,
 };
 pub use validator::{ConfigValidator, ValueType};
 
@@ -12,6 +12,8 @@ use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 
 use super::ConfigResult;
+use crate::hooks::HookRule;
+use crate::security::bash::BashPolicy;
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -68,6 +70,13 @@ pub struct Settings {
     #[serde(default, rename = "toolSearch")]
     pub tool_search: ToolSearchSettings,
 
+    #[serde(
+        default,
+        rename = "bashPolicy",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub bash_policy: Option<BashPolicySetting>,
+
     #[serde(flatten)]
     pub extra: HashMap<String, serde_json::Value>,
 }
@@ -81,21 +90,130 @@ impl Settings {
     pub fn is_managed(&self) -> bool {
         self.source == SettingsSource::Managed
     }
+
+    /// Make a relative `bashPolicy` file path relative to `base`, the
+    /// directory of the settings file.
+    fn resolve_paths(&mut self, base: &Path) {
+        if let Some(BashPolicySetting::File(path)) = &mut self.bash_policy {
+            *path = base.join(&*path);
+        }
+    }
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// `bashPolicy` in settings.json: the policy itself, or the path of a JSON
+/// or YAML file holding it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BashPolicySetting {
+    File(PathBuf),
+    Inline(BashPolicy),
+}
+
+impl BashPolicySetting {
+    /// The policy, reading it from its file if it has one. Relative paths
+    /// are resolved against `base`.
+    pub fn load(&self, base: &Path) -> crate::Result<BashPolicy> {
+        match self {
+            Self::File(path) => BashPolicy::from_file(base.join(path)),
+            Self::Inline(policy) => Ok(policy.clone()),
+        }
+    }
+}
+
+/// `hooks` in settings.json.
+///
+/// Accepts the Claude Code format, where each event lists matcher groups:
+///
+/// ```json
+/// {"PreToolUse": [{"matcher": "Bash(git:*)", "hooks": [{"type": "command", "command": "audit.sh", "timeout": 30}]}]}
+/// ```
+///
+/// as well as the older SDK format of named `PreToolUse`/`PostToolUse` hooks
+/// and `SessionStart`/`SessionEnd` command lists.
+#[derive(Debug, Clone, Default)]
 pub struct HooksSettings {
-    #[serde(default, rename = "PreToolUse")]
     pub pre_tool_use: HashMap<String, HookConfig>,
-
-    #[serde(default, rename = "PostToolUse")]
     pub post_tool_use: HashMap<String, HookConfig>,
-
-    #[serde(default, rename = "SessionStart")]
     pub session_start: Vec<HookConfig>,
-
-    #[serde(default, rename = "SessionEnd")]
     pub session_end: Vec<HookConfig>,
+    /// Matcher groups by PascalCase event name.
+    pub rules: HashMap<String, Vec<HookRule>>,
+}
+
+/// One event's entry in `hooks`, in either format.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum EventHooks {
+    Rules(Vec<HookRule>),
+    Commands(Vec<HookConfig>),
+    Named(HashMap<String, HookConfig>),
+}
+
+impl<'de> Deserialize<'de> for HooksSettings {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut settings = Self::default();
+        for (event, hooks) in HashMap::<String, EventHooks>::deserialize(deserializer)? {
+            match (event.as_str(), hooks) {
+                ("PreToolUse", EventHooks::Named(named)) => settings.pre_tool_use.extend(named),
+                ("PostToolUse", EventHooks::Named(named)) => settings.post_tool_use.extend(named),
+                ("SessionStart", EventHooks::Commands(list)) => settings.session_start.extend(list),
+                ("SessionEnd", EventHooks::Commands(list)) => settings.session_end.extend(list),
+                (_, EventHooks::Rules(rules)) => {
+                    settings.rules.entry(event).or_default().extend(rules)
+                }
+                (_, EventHooks::Commands(list)) => settings
+                    .rules
+                    .entry(event)
+                    .or_default()
+                    .extend(list.into_iter().map(HookRule::from)),
+                (_, EventHooks::Named(named)) => settings
+                    .rules
+                    .entry(event)
+                    .or_default()
+                    .extend(named.into_values().map(HookRule::from)),
+            }
+        }
+        Ok(settings)
+    }
+}
+
+impl Serialize for HooksSettings {
+    /// Events with matcher groups are written in the Claude Code format, with
+    /// any older-format hooks for the same event folded in (losing their names).
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let legacy = [
+            ("PreToolUse", EventHooks::Named(self.pre_tool_use.clone())),
+            ("PostToolUse", EventHooks::Named(self.post_tool_use.clone())),
+            (
+                "SessionStart",
+                EventHooks::Commands(self.session_start.clone()),
+            ),
+            ("SessionEnd", EventHooks::Commands(self.session_end.clone())),
+        ];
+        let mut events: std::collections::BTreeMap<&str, EventHooks> = self
+            .rules
+            .iter()
+            .map(|(event, rules)| (event.as_str(), EventHooks::Rules(rules.clone())))
+            .collect();
+        for (event, hooks) in legacy {
+            let configs: Vec<HookConfig> = match hooks {
+                EventHooks::Named(ref named) if !named.is_empty() => {
+                    named.values().cloned().collect()
+                }
+                EventHooks::Commands(ref list) if !list.is_empty() => list.clone(),
+                _ => continue,
+            };
+            match events.get_mut(event) {
+                Some(EventHooks::Rules(rules)) => {
+                    rules.extend(configs.into_iter().map(HookRule::from))
+                }
+                _ => {
+                    events.insert(event, hooks);
+                }
+            }
+        }
+        events.serialize(serializer)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -117,8 +235,13 @@ pub struct PermissionSettings {
     pub deny: Vec<String>,
     #[serde(default)]
     pub allow: Vec<String>,
+    #[serde(default)]
+    pub ask: Vec<String>,
     #[serde(default, rename = "defaultMode")]
     pub default_mode: Option<String>,
+    /// Directories outside the working directory that tools may access.
+    #[serde(default, rename = "additionalDirectories")]
+    pub additional_directories: Vec<String>,
 }
 
 impl PermissionSettings {
@@ -141,11 +264,33 @@ impl PermissionSettings {
             builder = builder.allow(pattern);
         }
 
+        for pattern in &self.ask {
+            builder = builder.ask(pattern);
+        }
+
         builder.build()
     }
 
+    /// `additional_directories` as absolute paths, resolving `~/` against the
+    /// home directory and relative paths against `base`.
+    pub fn resolved_directories(&self, base: &Path) -> Vec<PathBuf> {
+        self.additional_directories
+            .iter()
+            .map(|dir| match dir.strip_prefix("~/") {
+                Some(rest) => crate::common::home_dir()
+                    .map(|home| home.join(rest))
+                    .unwrap_or_else(|| PathBuf::from(dir)),
+                None => base.join(dir),
+            })
+            .collect()
+    }
+
     pub fn is_empty(&self) -> bool {
-        self.deny.is_empty() && self.allow.is_empty() && self.default_mode.is_none()
+        self.deny.is_empty()
+            && self.allow.is_empty()
+            && self.ask.is_empty()
+            && self.default_mode.is_none()
+            && self.additional_directories.is_empty()
     }
 }
 
@@ -165,6 +310,15 @@ pub struct SandboxSettings {
 
     #[serde(default, rename = "autoAllowBashIfSandboxed")]
     pub auto_allow_bash_if_sandboxed: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<crate::security::sandbox::ContainerConfig>,
+
+    #[serde(default)]
+    pub backend: Option<crate::security::sandbox::SandboxBackend>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seccomp: Option<crate::security::sandbox::SeccompProfile>,
 }
 
 impl SandboxSettings {
@@ -190,14 +344,21 @@ impl SandboxSettings {
                 socks_proxy_port: self.network.socks_proxy_port,
                 allow_unix_sockets: Vec::new(),
                 allow_local_binding: false,
+                isolate: self.network.isolate,
+                embedded_proxy: self.network.embedded_proxy,
+                command_rules: self.network.command_rules.clone(),
+                offline_outside_rules: self.network.offline_outside_rules,
             },
             working_dir,
             allowed_domains: self.network.allowed_domains.clone(),
             blocked_domains: self.network.blocked_domains.clone(),
             // Explicit defaults for clarity
             enable_weaker_nested_sandbox: false,
+            backend: self.backend.unwrap_or_default(),
             allowed_paths: Vec::new(),
             denied_paths: Vec::new(),
+            container: self.container.clone(),
+            seccomp: self.seccomp.clone(),
         }
     }
 
@@ -223,6 +384,22 @@ pub struct NetworkSandboxSettings {
 
     #[serde(default, rename = "socksProxyPort")]
     pub socks_proxy_port: Option<u16>,
+
+    #[serde(default)]
+    pub isolate: bool,
+
+    #[serde(default, rename = "embeddedProxy")]
+    pub embedded_proxy: bool,
+
+    #[serde(
+        default,
+        rename = "commandRules",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub command_rules: Vec<crate::security::sandbox::CommandNetworkRule>,
+
+    #[serde(default, rename = "offlineOutsideRules")]
+    pub offline_outside_rules: bool,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -260,6 +437,7 @@ impl ToolSearchSettings {
         if let Some(ref mode) = self.mode {
             let search_mode = match mode.to_lowercase().as_str() {
                 "bm25" => SearchMode::Bm25,
+                "semantic" => SearchMode::Semantic,
                 _ => SearchMode::Regex,
             };
             config = config.search_mode(search_mode);
@@ -356,7 +534,8 @@ impl SettingsLoader {
         let settings_path = enterprise_dir.join("settings.json");
         if settings_path.exists() {
             let content = tokio::fs::read_to_string(&settings_path).await?;
-            let managed: Settings = serde_json::from_str(&content)?;
+            let mut managed: Settings = serde_json::from_str(&content)?;
+            managed.resolve_paths(enterprise_dir);
 
             // Lock non-empty fields from enterprise settings
             if !managed.permissions.deny.is_empty() {
@@ -365,9 +544,15 @@ impl SettingsLoader {
             if !managed.permissions.allow.is_empty() {
                 self.locked_keys.insert("permissions.allow".to_string());
             }
+            if !managed.permissions.ask.is_empty() {
+                self.locked_keys.insert("permissions.ask".to_string());
+            }
             if managed.model.is_some() {
                 self.locked_keys.insert("model".to_string());
             }
+            if managed.bash_policy.is_some() {
+                self.locked_keys.insert("bashPolicy".to_string());
+            }
 
             self.merge_settings(managed, true);
         }
@@ -391,6 +576,9 @@ impl SettingsLoader {
         let content = tokio::fs::read_to_string(path).await?;
         let mut file_settings: Settings = serde_json::from_str(&content)?;
         file_settings.source = source;
+        if let Some(dir) = path.parent() {
+            file_settings.resolve_paths(dir);
+        }
         self.merge_settings(file_settings, false);
         Ok(())
     }
@@ -410,6 +598,13 @@ impl SettingsLoader {
                 .allow
                 .extend(other.permissions.allow);
         }
+        if !self.locked_keys.contains("permissions.ask") || is_managed {
+            self.settings.permissions.ask.extend(other.permissions.ask);
+        }
+        self.settings
+            .permissions
+            .additional_directories
+            .extend(other.permissions.additional_directories);
         if other.permissions.default_mode.is_some() {
             self.settings.permissions.default_mode = other.permissions.default_mode;
         }
@@ -445,6 +640,21 @@ impl SettingsLoader {
         if let Some(port) = other.sandbox.network.socks_proxy_port {
             self.settings.sandbox.network.socks_proxy_port = Some(port);
         }
+        if other.sandbox.container.is_some() {
+            self.settings.sandbox.container = other.sandbox.container;
+        }
+        if other.sandbox.backend.is_some() {
+            self.settings.sandbox.backend = other.sandbox.backend;
+        }
+        if other.sandbox.seccomp.is_some() {
+            self.settings.sandbox.seccomp = other.sandbox.seccomp;
+        }
+        if other.sandbox.network.isolate {
+            self.settings.sandbox.network.isolate = true;
+        }
+        if other.sandbox.network.embedded_proxy {
+            self.settings.sandbox.network.embedded_proxy = true;
+        }
 
         self.settings.mcp_servers.extend(other.mcp_servers);
 
@@ -463,6 +673,9 @@ impl SettingsLoader {
         if (!self.locked_keys.contains("model") || is_managed) && other.model.is_some() {
             self.settings.model = other.model;
         }
+        if (!self.locked_keys.contains("bashPolicy") || is_managed) && other.bash_policy.is_some() {
+            self.settings.bash_policy = other.bash_policy;
+        }
         if other.small_model.is_some() {
             self.settings.small_model = other.small_model;
         }
@@ -476,6 +689,9 @@ impl SettingsLoader {
                     existing.post_tool_use.extend(other_hooks.post_tool_use);
                     existing.session_start.extend(other_hooks.session_start);
                     existing.session_end.extend(other_hooks.session_end);
+                    for (event, rules) in other_hooks.rules {
+                        existing.rules.entry(event).or_default().extend(rules);
+                    }
                 }
                 None => self.settings.hooks = Some(other_hooks),
             }
@@ -512,6 +728,54 @@ impl SettingsLoader {
     pub fn into_settings(self) -> Settings {
         self.settings
     }
+
+    /// Add `pattern` to the `permissions` list for `decision` in the project's
+    /// `.claude/settings.local.json`, keeping the rest of the file.
+    pub async fn add_local_permission(
+        project_dir: &Path,
+        decision: crate::permissions::PermissionDecision,
+        pattern: &str,
+    ) -> ConfigResult<()> {
+        use crate::permissions::PermissionDecision;
+
+        let path = project_dir.join(".claude").join("settings.local.json");
+        let mut root = match tokio::fs::read_to_string(&path).await {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => serde_json::json!({}),
+            Err(e) => return Err(e.into()),
+        };
+        let key = match decision {
+            PermissionDecision::Allow => "allow",
+            PermissionDecision::Deny => "deny",
+            PermissionDecision::Ask => "ask",
+        };
+        let invalid = |message: &str| super::ConfigError::InvalidValue {
+            key: format!("permissions.{key}"),
+            message: format!("{message} in {}", path.display()),
+        };
+
+        let list = root
+            .as_object_mut()
+            .ok_or_else(|| invalid("settings are not an object"))?
+            .entry("permissions")
+            .or_insert_with(|| serde_json::json!({}))
+            .as_object_mut()
+            .ok_or_else(|| invalid("permissions are not an object"))?
+            .entry(key)
+            .or_insert_with(|| serde_json::json!([]))
+            .as_array_mut()
+            .ok_or_else(|| invalid("not a list"))?;
+        if list.iter().any(|p| p.as_str() == Some(pattern)) {
+            return Ok(());
+        }
+        list.push(pattern.into());
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, serde_json::to_string_pretty(&root)?).await?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -524,6 +788,73 @@ mod tests {
         assert!(loader.settings.env.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_bash_policy_setting() {
+        let dir = tempfile::tempdir().unwrap();
+        let claude = dir.path().join(".claude");
+        tokio::fs::create_dir_all(&claude).await.unwrap();
+        tokio::fs::write(
+            claude.join("settings.json"),
+            r#"{"bashPolicy": "bash-policy.json"}"#,
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(
+            claude.join("bash-policy.json"),
+            r#"{"allowedCommands": ["git"], "maxRisk": "low"}"#,
+        )
+        .await
+        .unwrap();
+
+        let mut loader = SettingsLoader::new();
+        let settings = loader.load_from(dir.path()).await.unwrap();
+        let policy = settings
+            .bash_policy
+            .as_ref()
+            .unwrap()
+            .load(Path::new("/elsewhere"))
+            .unwrap();
+        assert!(policy.allowed_commands.contains("git"));
+        assert_eq!(policy.max_risk, Some(crate::security::bash::RiskLevel::Low));
+
+        let inline: Settings =
+            serde_json::from_str(r#"{"bashPolicy": {"blockedCommands": ["rm"]}}"#).unwrap();
+        let policy = inline.bash_policy.unwrap().load(dir.path()).unwrap();
+        assert!(policy.is_command_blocked("rm"));
+    }
+
+    #[tokio::test]
+    async fn test_add_local_permission() {
+        use crate::permissions::PermissionDecision;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".claude").join("settings.local.json");
+        tokio::fs::create_dir_all(path.parent().unwrap())
+            .await
+            .unwrap();
+        tokio::fs::write(
+            &path,
+            r#"{"model": "opus", "permissions": {"allow": ["Read"]}}"#,
+        )
+        .await
+        .unwrap();
+
+        for pattern in ["Bash(cargo test)", "Bash(cargo test)"] {
+            SettingsLoader::add_local_permission(dir.path(), PermissionDecision::Allow, pattern)
+                .await
+                .unwrap();
+        }
+        SettingsLoader::add_local_permission(dir.path(), PermissionDecision::Deny, "WebFetch")
+            .await
+            .unwrap();
+
+        let mut loader = SettingsLoader::new();
+        let settings = loader.load_local(dir.path()).await.unwrap();
+        assert_eq!(settings.model.as_deref(), Some("opus"));
+        assert_eq!(settings.permissions.allow, ["Read", "Bash(cargo test)"]);
+        assert_eq!(settings.permissions.deny, ["WebFetch"]);
+    }
+
     #[test]
     fn test_permission_settings_to_policy() {
         use crate::permissions::PermissionMode;
@@ -531,12 +862,70 @@ mod tests {
         let settings = PermissionSettings {
             deny: vec!["Bash(rm:*)".to_string()],
             allow: vec!["Bash(git:*)".to_string()],
+            ask: vec!["Bash(git push:*)".to_string()],
             default_mode: Some("acceptEdits".to_string()),
+            ..Default::default()
         };
 
         let policy = settings.to_policy();
         assert_eq!(policy.mode, PermissionMode::AcceptEdits);
-        assert_eq!(policy.rules.len(), 2);
+        assert_eq!(policy.rules.len(), 3);
+        let push = serde_json::json!({"command": "git push origin main"});
+        assert!(policy.check("Bash", &push).needs_approval());
+        let status = serde_json::json!({"command": "git status"});
+        assert!(policy.check("Bash", &status).is_allowed());
+    }
+
+    #[test]
+    fn test_permission_settings_from_json() {
+        let settings: Settings = serde_json::from_str(
+            r#"{"permissions": {
+                "allow": ["Read"],
+                "ask": ["WebFetch"],
+                "defaultMode": "plan",
+                "additionalDirectories": ["../docs", "/opt/shared"]
+            }}"#,
+        )
+        .unwrap();
+        let permissions = &settings.permissions;
+        assert_eq!(permissions.ask, ["WebFetch"]);
+        assert_eq!(
+            permissions.resolved_directories(Path::new("/work/app")),
+            [
+                PathBuf::from("/work/app/../docs"),
+                PathBuf::from("/opt/shared")
+            ]
+        );
+        assert!(permissions.to_policy().mode.is_read_only());
+    }
+
+    #[test]
+    fn test_hooks_settings_formats() {
+        let hooks: HooksSettings = serde_json::from_value(serde_json::json!({
+            "PreToolUse": [{
+                "matcher": "Bash(git:*)",
+                "hooks": [{"type": "command", "command": "audit.sh", "timeout": 30}]
+            }],
+            "Stop": [{"hooks": [{"type": "command", "command": "notify.sh"}]}],
+            "PostToolUse": {"fmt": {"command": "fmt.sh", "matcher": "Edit"}},
+            "SessionStart": ["setup.sh"]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            hooks.rules["PreToolUse"][0].matcher.as_deref(),
+            Some("Bash(git:*)")
+        );
+        assert_eq!(hooks.rules["PreToolUse"][0].hooks[0].timeout, Some(30));
+        assert_eq!(hooks.rules["Stop"].len(), 1);
+        assert!(hooks.post_tool_use.contains_key("fmt"));
+        assert_eq!(hooks.session_start.len(), 1);
+
+        let value = serde_json::to_value(&hooks).unwrap();
+        assert!(value["PreToolUse"][0]["hooks"].is_array());
+        let reparsed: HooksSettings = serde_json::from_value(value).unwrap();
+        assert_eq!(reparsed.rules.len(), 2);
+        assert_eq!(reparsed.post_tool_use.len(), 1);
     }
 
     #[test]
@@ -574,11 +963,13 @@ mod tests {
                 blocked_domains: ["malware.com".to_string()].into_iter().collect(),
                 ..Default::default()
             },
+            container: Some(crate::security::sandbox::ContainerConfig::new("node:22")),
             ..Default::default()
         };
 
         let config = settings.to_sandbox_config(PathBuf::from("/tmp"));
         assert!(config.enabled);
+        assert_eq!(config.container.as_ref().unwrap().image, "node:22");
         assert!(config.allowed_domains.contains("example.com"));
         assert!(config.blocked_domains.contains("malware.com"));
 
@@ -8,6 +8,7 @@ use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
 use super::import_extractor::ImportExtractor;
+use super::level::{enterprise_base_path, user_base_path};
 use super::rule_index::RuleIndex;
 use super::{ContextError, ContextResult};
 
@@ -53,6 +54,8 @@ impl MemoryLoaderConfig {
 ///
 /// # Features
 /// - Loads CLAUDE.md and CLAUDE.local.md from project directories
+/// - Walks from the working directory upward, outermost files first
+/// - Merges enterprise, user, project and local levels in precedence order
 /// - Supports recursive @import with depth limiting
 /// - Circular import detection using canonical path tracking
 /// - Scans .claude/rules/ directory for rule files
@@ -104,41 +107,168 @@ impl MemoryLoader {
     pub async fn load_shared(&self, start_dir: &Path) -> ContextResult<MemoryContent> {
         let mut content = MemoryContent::default();
         let mut visited = HashSet::new();
+        self.collect_shared(start_dir, true, &mut content, &mut visited)
+            .await?;
+        Ok(content)
+    }
 
-        for path in Self::find_claude_files(start_dir) {
-            match self
-                .load_with_imports(&path, start_dir, 0, &mut visited)
-                .await
-            {
-                Ok(text) => content.claude_md.push(text),
-                Err(e) => tracing::debug!("Failed to load {}: {}", path.display(), e),
-            }
+    /// Loads local CLAUDE.local.md (private to the user, not in version control).
+    pub async fn load_local(&self, start_dir: &Path) -> ContextResult<MemoryContent> {
+        let mut content = MemoryContent::default();
+        let mut visited = HashSet::new();
+        self.collect_local(start_dir, true, &mut content, &mut visited)
+            .await;
+        Ok(content)
+    }
+
+    /// Loads shared CLAUDE.md files and rules from `start_dir` and every
+    /// ancestor directory below the filesystem root.
+    ///
+    /// Directories are visited outermost first, so files closer to
+    /// `start_dir` come later and take precedence. In the home directory only
+    /// `~/CLAUDE.md` is read; `~/.claude/` is the user level.
+    pub async fn load_shared_hierarchy(&self, start_dir: &Path) -> ContextResult<MemoryContent> {
+        let mut content = MemoryContent::default();
+        let mut visited = HashSet::new();
+        for (dir, dot_claude) in Self::hierarchy_dirs(start_dir) {
+            self.collect_shared(&dir, dot_claude, &mut content, &mut visited)
+                .await?;
         }
+        Ok(content)
+    }
 
-        let rules_dir = start_dir.join(".claude").join("rules");
-        if rules_dir.exists() {
-            content.rule_indices = self.scan_rules(&rules_dir).await?;
+    /// Loads CLAUDE.local.md files from `start_dir` and every ancestor
+    /// directory, in the same order as [`load_shared_hierarchy`](Self::load_shared_hierarchy).
+    pub async fn load_local_hierarchy(&self, start_dir: &Path) -> ContextResult<MemoryContent> {
+        let mut content = MemoryContent::default();
+        let mut visited = HashSet::new();
+        for (dir, dot_claude) in Self::hierarchy_dirs(start_dir) {
+            self.collect_local(&dir, dot_claude, &mut content, &mut visited)
+                .await;
         }
+        Ok(content)
+    }
 
+    /// Loads shared and local memory from `start_dir` and its ancestors.
+    pub async fn load_hierarchy(&self, start_dir: &Path) -> ContextResult<MemoryContent> {
+        let mut content = self.load_shared_hierarchy(start_dir).await?;
+        content.merge(self.load_local_hierarchy(start_dir).await?);
         Ok(content)
     }
 
-    /// Loads local CLAUDE.local.md (private to the user, not in version control).
-    pub async fn load_local(&self, start_dir: &Path) -> ContextResult<MemoryContent> {
+    /// Loads memory from every level in precedence order:
+    /// Enterprise → User → project hierarchy (outermost first) → local files.
+    pub async fn load_all_levels(&self, start_dir: &Path) -> ContextResult<MemoryContent> {
+        self.load_levels(
+            enterprise_base_path().as_deref(),
+            user_base_path().as_deref(),
+            start_dir,
+        )
+        .await
+    }
+
+    async fn load_levels(
+        &self,
+        enterprise: Option<&Path>,
+        user: Option<&Path>,
+        start_dir: &Path,
+    ) -> ContextResult<MemoryContent> {
+        let mut content = MemoryContent::default();
+        for base in enterprise.into_iter().chain(user) {
+            content.merge(self.load_base(base).await?);
+        }
+        content.merge(self.load_hierarchy(start_dir).await?);
+        Ok(content)
+    }
+
+    /// Loads `CLAUDE.md` and `rules/` from a level's base directory, such as
+    /// `~/.claude`, which has no nested `.claude/`.
+    pub async fn load_base(&self, base: &Path) -> ContextResult<MemoryContent> {
         let mut content = MemoryContent::default();
         let mut visited = HashSet::new();
+        self.collect_claude_md(base, false, &mut content, &mut visited)
+            .await;
+        self.collect_rules(&base.join("rules"), &mut content)
+            .await?;
+        Ok(content)
+    }
 
-        for path in Self::find_local_files(start_dir) {
-            match self
-                .load_with_imports(&path, start_dir, 0, &mut visited)
-                .await
-            {
-                Ok(text) => content.local_md.push(text),
+    async fn collect_shared(
+        &self,
+        dir: &Path,
+        dot_claude: bool,
+        content: &mut MemoryContent,
+        visited: &mut HashSet<PathBuf>,
+    ) -> ContextResult<()> {
+        self.collect_claude_md(dir, dot_claude, content, visited)
+            .await;
+        if dot_claude {
+            self.collect_rules(&dir.join(".claude").join("rules"), content)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn collect_claude_md(
+        &self,
+        dir: &Path,
+        dot_claude: bool,
+        content: &mut MemoryContent,
+        visited: &mut HashSet<PathBuf>,
+    ) {
+        for path in Self::find_claude_files(dir, dot_claude) {
+            match self.load_with_imports(&path, dir, 0, visited).await {
+                Ok(text) if !text.is_empty() => content.claude_md.push(text),
+                Ok(_) => {}
                 Err(e) => tracing::debug!("Failed to load {}: {}", path.display(), e),
             }
         }
+    }
 
-        Ok(content)
+    async fn collect_rules(
+        &self,
+        rules_dir: &Path,
+        content: &mut MemoryContent,
+    ) -> ContextResult<()> {
+        if rules_dir.exists() {
+            content
+                .rule_indices
+                .extend(self.scan_rules(rules_dir).await?);
+        }
+        Ok(())
+    }
+
+    async fn collect_local(
+        &self,
+        dir: &Path,
+        dot_claude: bool,
+        content: &mut MemoryContent,
+        visited: &mut HashSet<PathBuf>,
+    ) {
+        for path in Self::find_local_files(dir, dot_claude) {
+            match self.load_with_imports(&path, dir, 0, visited).await {
+                Ok(text) if !text.is_empty() => content.local_md.push(text),
+                Ok(_) => {}
+                Err(e) => tracing::debug!("Failed to load {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    /// Directories from the outermost ancestor of `start_dir` down to
+    /// `start_dir`, each paired with whether its `.claude/` directory is read.
+    fn hierarchy_dirs(start_dir: &Path) -> Vec<(PathBuf, bool)> {
+        let start = start_dir
+            .canonicalize()
+            .unwrap_or_else(|_| start_dir.to_path_buf());
+        let home = crate::common::home_dir().and_then(|h| h.canonicalize().ok());
+
+        let mut dirs: Vec<(PathBuf, bool)> = start
+            .ancestors()
+            .filter(|dir| dir.parent().is_some())
+            .map(|dir| (dir.to_path_buf(), home.as_deref() != Some(dir)))
+            .collect();
+        dirs.reverse();
+        dirs
     }
 
     /// Loads a file with recursive @import expansion.
@@ -217,41 +347,23 @@ impl MemoryLoader {
     }
 
     /// Finds CLAUDE.md files in standard locations.
-    fn find_claude_files(start_dir: &Path) -> Vec<PathBuf> {
-        let mut files = Vec::new();
-
-        // Project root CLAUDE.md
-        let claude_md = start_dir.join("CLAUDE.md");
-        if claude_md.exists() {
-            files.push(claude_md);
-        }
-
-        // .claude/CLAUDE.md (alternative location)
-        let claude_dir_md = start_dir.join(".claude").join("CLAUDE.md");
-        if claude_dir_md.exists() {
-            files.push(claude_dir_md);
-        }
-
-        files
+    fn find_claude_files(dir: &Path, dot_claude: bool) -> Vec<PathBuf> {
+        Self::find_files(dir, "CLAUDE.md", dot_claude)
     }
 
     /// Finds CLAUDE.local.md files in standard locations.
-    fn find_local_files(start_dir: &Path) -> Vec<PathBuf> {
-        let mut files = Vec::new();
-
-        // Project root CLAUDE.local.md
-        let local_md = start_dir.join("CLAUDE.local.md");
-        if local_md.exists() {
-            files.push(local_md);
-        }
+    fn find_local_files(dir: &Path, dot_claude: bool) -> Vec<PathBuf> {
+        Self::find_files(dir, "CLAUDE.local.md", dot_claude)
+    }
 
-        // .claude/CLAUDE.local.md (alternative location)
-        let local_dir_md = start_dir.join(".claude").join("CLAUDE.local.md");
-        if local_dir_md.exists() {
-            files.push(local_dir_md);
+    /// Finds `dir/name` and, if `dot_claude` is set, `dir/.claude/name`.
+    fn find_files(dir: &Path, name: &str, dot_claude: bool) -> Vec<PathBuf> {
+        let mut candidates = vec![dir.join(name)];
+        if dot_claude {
+            candidates.push(dir.join(".claude").join(name));
         }
-
-        files
+        candidates.retain(|path| path.exists());
+        candidates
     }
 
     /// Normalizes paths with duplicated .agents/ or .claude/ segments.
@@ -292,7 +404,7 @@ impl MemoryLoader {
     async fn scan_rules(&self, dir: &Path) -> ContextResult<Vec<RuleIndex>> {
         let mut indices = Vec::new();
         self.scan_rules_recursive(dir, &mut indices).await?;
-        indices.sort_by(|a, b| b.priority.cmp(&a.priority));
+        indices.sort_by_key(|i| std::cmp::Reverse(i.priority));
         Ok(indices)
     }
 
@@ -704,4 +816,136 @@ mod tests {
         assert_eq!(content1.claude_md.len(), 2);
         assert_eq!(content1.local_md.len(), 2);
     }
+
+    fn position(items: &[String], needle: &str) -> usize {
+        items
+            .iter()
+            .position(|c| c.contains(needle))
+            .unwrap_or_else(|| panic!("{needle} not loaded"))
+    }
+
+    #[tokio::test]
+    async fn test_load_hierarchy_walks_ancestors() {
+        let dir = tempdir().unwrap();
+        let repo = dir.path().join("repo");
+        let crate_dir = repo.join("crates").join("core");
+        fs::create_dir_all(crate_dir.join(".claude")).await.unwrap();
+        fs::create_dir_all(repo.join(".claude").join("rules"))
+            .await
+            .unwrap();
+
+        fs::write(repo.join("CLAUDE.md"), "REPO_SHARED")
+            .await
+            .unwrap();
+        fs::write(
+            repo.join(".claude").join("rules").join("rust.md"),
+            "---\npaths: **/*.rs\n---\n\n# Rust",
+        )
+        .await
+        .unwrap();
+        fs::write(repo.join("CLAUDE.local.md"), "REPO_LOCAL")
+            .await
+            .unwrap();
+        fs::write(crate_dir.join(".claude").join("CLAUDE.md"), "CRATE_SHARED")
+            .await
+            .unwrap();
+        fs::write(crate_dir.join("CLAUDE.local.md"), "CRATE_LOCAL")
+            .await
+            .unwrap();
+
+        let loader = MemoryLoader::new();
+        let content = loader.load_hierarchy(&crate_dir).await.unwrap();
+
+        assert!(
+            position(&content.claude_md, "REPO_SHARED")
+                < position(&content.claude_md, "CRATE_SHARED")
+        );
+        assert!(
+            position(&content.local_md, "REPO_LOCAL") < position(&content.local_md, "CRATE_LOCAL")
+        );
+        assert!(content.rule_indices.iter().any(|r| r.name == "rust"));
+
+        let combined = content.combined_claude_md();
+        assert!(combined.find("CRATE_SHARED").unwrap() < combined.find("REPO_LOCAL").unwrap());
+
+        // The single-directory loaders do not walk upward.
+        let shared = loader.load_shared(&crate_dir).await.unwrap();
+        assert_eq!(shared.claude_md, vec!["CRATE_SHARED".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_load_hierarchy_shares_imports() {
+        let dir = tempdir().unwrap();
+        let sub = dir.path().join("sub");
+        fs::create_dir_all(&sub).await.unwrap();
+
+        fs::write(dir.path().join("common.md"), "COMMON")
+            .await
+            .unwrap();
+        fs::write(dir.path().join("CLAUDE.md"), "PARENT @./common.md")
+            .await
+            .unwrap();
+        fs::write(sub.join("CLAUDE.md"), "CHILD @../common.md")
+            .await
+            .unwrap();
+
+        let content = MemoryLoader::new().load_hierarchy(&sub).await.unwrap();
+        let combined = content.combined_claude_md();
+        assert_eq!(combined.matches("COMMON").count(), 1);
+        assert!(combined.find("PARENT").unwrap() < combined.find("CHILD").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_load_levels_precedence() {
+        let dir = tempdir().unwrap();
+        let enterprise = dir.path().join("enterprise");
+        let user = dir.path().join("user");
+        let project = dir.path().join("project");
+        for d in [&enterprise, &user, &project] {
+            fs::create_dir_all(d).await.unwrap();
+        }
+
+        fs::write(enterprise.join("CLAUDE.md"), "ENTERPRISE")
+            .await
+            .unwrap();
+        fs::write(user.join("CLAUDE.md"), "USER").await.unwrap();
+        fs::write(project.join("CLAUDE.md"), "PROJECT")
+            .await
+            .unwrap();
+        fs::write(project.join("CLAUDE.local.md"), "LOCAL")
+            .await
+            .unwrap();
+
+        fs::create_dir_all(user.join("rules")).await.unwrap();
+        fs::write(user.join("rules").join("style.md"), "# Style")
+            .await
+            .unwrap();
+        fs::create_dir_all(user.join(".claude").join("rules"))
+            .await
+            .unwrap();
+        fs::write(user.join(".claude").join("CLAUDE.md"), "NESTED")
+            .await
+            .unwrap();
+        fs::write(user.join(".claude").join("rules").join("nested.md"), "x")
+            .await
+            .unwrap();
+
+        let content = MemoryLoader::new()
+            .load_levels(Some(&enterprise), Some(&user), &project)
+            .await
+            .unwrap();
+        let combined = content.combined_claude_md();
+        let order: Vec<usize> = ["ENTERPRISE", "USER", "PROJECT", "LOCAL"]
+            .iter()
+            .map(|s| combined.find(s).unwrap())
+            .collect();
+        assert!(order.is_sorted());
+        assert!(!combined.contains("NESTED"));
+        let rules: Vec<&str> = content
+            .rule_indices
+            .iter()
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(rules, ["style"]);
+    }
 }
@@ -16,7 +16,7 @@ pub use import_extractor::ImportExtractor;
 pub use level::{LeveledMemoryProvider, enterprise_base_path, user_base_path};
 pub use memory_loader::{MemoryContent, MemoryLoader, MemoryLoaderConfig};
 pub use orchestrator::PromptOrchestrator;
-pub use provider::{FileMemoryProvider, MemoryContextProvider, MemoryProvider};
+pub use provider::{FileMemoryProvider, MemoryContextProvider, MemoryEntry, MemoryProvider};
 pub use routing::RoutingStrategy;
 pub use rule_index::RuleIndex;
 pub use static_context::{McpToolMeta, StaticContext};
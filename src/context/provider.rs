@@ -1,13 +1,29 @@
 //! Memory provider trait and implementations.
 //!
 //! Providers abstract the source of memory content, allowing for both
-//! in-memory testing and file-based production use.
+//! in-memory testing and file-based production use. Writable providers also
+//! hold the files the model keeps under `/memories` through
+//! [`MemoryTool`](crate::tools::MemoryTool).
 
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
 
 use async_trait::async_trait;
+use tokio::sync::RwLock;
 
-use super::{ContextResult, MemoryContent, MemoryLoader};
+use super::{ContextError, ContextResult, MemoryContent, MemoryLoader};
+use crate::security::SecurityError;
+use crate::security::fs::{SecureFileHandle, SecureFs};
+
+/// A file or directory held by a [`MemoryProvider`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryEntry {
+    /// Path relative to the memory root, `/`-separated.
+    pub path: String,
+    pub is_dir: bool,
+    pub size: u64,
+}
 
 /// Trait for providing memory content from various sources.
 ///
@@ -22,10 +38,75 @@ pub trait MemoryProvider: Send + Sync {
 
     /// Loads memory content from this provider.
     async fn load(&self) -> ContextResult<MemoryContent>;
+
+    // Files for the memory tool. Paths are relative to the memory root,
+    // `/`-separated and already validated (no `..`, no absolute paths); the
+    // empty string is the root itself. Providers are read-only by default.
+
+    /// Entries under `dir`, recursively, in path order.
+    async fn list(&self, dir: &str) -> ContextResult<Vec<MemoryEntry>> {
+        if dir.is_empty() {
+            Ok(Vec::new())
+        } else {
+            Err(not_found(dir))
+        }
+    }
+
+    /// Whether `path` is a directory.
+    async fn is_dir(&self, path: &str) -> ContextResult<bool> {
+        Ok(path.is_empty())
+    }
+
+    /// Content of the file at `path`, or `None` if it does not exist.
+    async fn read(&self, _path: &str) -> ContextResult<Option<String>> {
+        Ok(None)
+    }
+
+    /// Create or overwrite the file at `path`, creating parent directories.
+    async fn write(&self, _path: &str, _content: &str) -> ContextResult<()> {
+        Err(read_only(self.name()))
+    }
+
+    /// Delete a file or directory. Returns `false` if nothing existed.
+    async fn delete(&self, _path: &str) -> ContextResult<bool> {
+        Err(read_only(self.name()))
+    }
+
+    /// Move a file or directory. Fails if `to` already exists.
+    async fn rename(&self, _from: &str, _to: &str) -> ContextResult<()> {
+        Err(read_only(self.name()))
+    }
+}
+
+fn read_only(name: &str) -> ContextError {
+    ContextError::Source {
+        message: format!("Memory provider '{}' is read-only", name),
+    }
+}
+
+fn not_found(path: &str) -> ContextError {
+    ContextError::Source {
+        message: format!("Memory path does not exist: {}", path),
+    }
+}
+
+fn already_exists(path: &str) -> ContextError {
+    ContextError::Source {
+        message: format!("Memory path already exists: {}", path),
+    }
+}
+
+fn invalid(path: &str) -> ContextError {
+    ContextError::Source {
+        message: format!("Invalid memory path: {}", path),
+    }
 }
 
 /// In-memory provider for testing and programmatic use.
 ///
+/// Memory tool files are kept in process and shared between clones;
+/// directories exist implicitly as prefixes of file paths.
+///
 /// # Example
 /// ```
 /// use claude_agent::context::MemoryContextProvider;
@@ -39,6 +120,7 @@ pub struct MemoryContextProvider {
     pub claude_md: Vec<String>,
     /// Content to include as CLAUDE.local.md.
     pub local_md: Vec<String>,
+    files: Arc<RwLock<BTreeMap<String, String>>>,
 }
 
 impl MemoryContextProvider {
@@ -58,6 +140,14 @@ impl MemoryContextProvider {
         self.local_md.push(content.into());
         self
     }
+
+    fn prefix(dir: &str) -> String {
+        if dir.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", dir)
+        }
+    }
 }
 
 #[async_trait]
@@ -73,12 +163,106 @@ impl MemoryProvider for MemoryContextProvider {
             rule_indices: Vec::new(),
         })
     }
+
+    async fn list(&self, dir: &str) -> ContextResult<Vec<MemoryEntry>> {
+        let prefix = Self::prefix(dir);
+        let files = self.files.read().await;
+        let mut entries: BTreeMap<String, MemoryEntry> = BTreeMap::new();
+        for (path, content) in files.range(prefix.clone()..) {
+            let Some(rest) = path.strip_prefix(&prefix) else {
+                break;
+            };
+            let mut current = prefix.trim_end_matches('/').to_string();
+            let parts: Vec<&str> = rest.split('/').collect();
+            for (i, part) in parts.iter().enumerate() {
+                if !current.is_empty() {
+                    current.push('/');
+                }
+                current.push_str(part);
+                let is_dir = i + 1 < parts.len();
+                entries
+                    .entry(current.clone())
+                    .or_insert_with(|| MemoryEntry {
+                        path: current.clone(),
+                        is_dir,
+                        size: if is_dir { 0 } else { content.len() as u64 },
+                    });
+            }
+        }
+        if entries.is_empty() && !dir.is_empty() {
+            return Err(not_found(dir));
+        }
+        Ok(entries.into_values().collect())
+    }
+
+    async fn is_dir(&self, path: &str) -> ContextResult<bool> {
+        let prefix = Self::prefix(path);
+        Ok(path.is_empty()
+            || self
+                .files
+                .read()
+                .await
+                .range(prefix.clone()..)
+                .next()
+                .is_some_and(|(p, _)| p.starts_with(&prefix)))
+    }
+
+    async fn read(&self, path: &str) -> ContextResult<Option<String>> {
+        Ok(self.files.read().await.get(path).cloned())
+    }
+
+    async fn write(&self, path: &str, content: &str) -> ContextResult<()> {
+        self.files
+            .write()
+            .await
+            .insert(path.to_string(), content.to_string());
+        Ok(())
+    }
+
+    async fn delete(&self, path: &str) -> ContextResult<bool> {
+        let prefix = Self::prefix(path);
+        let mut files = self.files.write().await;
+        let before = files.len();
+        files.retain(|p, _| p != path && !p.starts_with(&prefix));
+        Ok(files.len() != before)
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> ContextResult<()> {
+        let from_prefix = Self::prefix(from);
+        let to_prefix = Self::prefix(to);
+        let mut files = self.files.write().await;
+        if files.keys().any(|p| p == to || p.starts_with(&to_prefix)) {
+            return Err(already_exists(to));
+        }
+        let moved: Vec<String> = files
+            .keys()
+            .filter(|p| *p == from || p.starts_with(&from_prefix))
+            .cloned()
+            .collect();
+        if moved.is_empty() {
+            return Err(not_found(from));
+        }
+        for path in moved {
+            if let Some(content) = files.remove(&path) {
+                let target = if path == from {
+                    to.to_string()
+                } else {
+                    format!("{}{}", to_prefix, &path[from_prefix.len()..])
+                };
+                files.insert(target, content);
+            }
+        }
+        Ok(())
+    }
 }
 
 /// File-based memory provider for CLI-compatible behavior.
 ///
 /// Loads CLAUDE.md and CLAUDE.local.md files from the file system
-/// with full @import support.
+/// with full @import support. Memory tool files live under the same
+/// directory, which is created on the first write; they are opened through
+/// [`SecureFs`] without following any symlink, so nothing outside the
+/// directory can be read or changed.
 ///
 /// # Example
 /// ```no_run
@@ -97,6 +281,74 @@ impl FileMemoryProvider {
     pub fn new(path: impl Into<PathBuf>) -> Self {
         Self { path: path.into() }
     }
+
+    /// Runs `f` on a blocking thread with a filesystem rooted at the
+    /// directory, or `None` if it does not exist and `create` is false.
+    async fn with_fs<T, F>(&self, create: bool, f: F) -> ContextResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(Option<SecureFs>) -> ContextResult<T> + Send + 'static,
+    {
+        let root = self.path.clone();
+        tokio::task::spawn_blocking(move || {
+            if create {
+                std::fs::create_dir_all(&root)?;
+            } else if !root.is_dir() {
+                return f(None);
+            }
+            let fs = SecureFs::new(&root, Vec::new(), &[], 0).map_err(source)?;
+            f(Some(fs))
+        })
+        .await
+        .map_err(|e| ContextError::Source {
+            message: e.to_string(),
+        })?
+    }
+}
+
+fn source(e: SecurityError) -> ContextError {
+    match e {
+        SecurityError::Io(e) => ContextError::Io(e),
+        e => ContextError::Source {
+            message: e.to_string(),
+        },
+    }
+}
+
+fn is_missing(e: &SecurityError) -> bool {
+    matches!(e, SecurityError::Io(e) if e.kind() == std::io::ErrorKind::NotFound)
+}
+
+/// Rejects anything but plain relative components.
+fn checked(path: &str) -> ContextResult<&str> {
+    if Path::new(path)
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+    {
+        Ok(path)
+    } else {
+        Err(invalid(path))
+    }
+}
+
+/// Whether `path` is a directory, or `None` if nothing is there. Symlinks
+/// are rejected.
+fn kind(fs: &SecureFs, path: &str) -> ContextResult<Option<bool>> {
+    let target = if path.is_empty() { "." } else { path };
+    let opened = fs
+        .resolve(target)
+        .and_then(|p| p.open(rustix::fs::OFlags::RDONLY));
+    match opened {
+        Ok(fd) => {
+            let stat = rustix::fs::fstat(&fd).map_err(std::io::Error::from)?;
+            Ok(Some(
+                rustix::fs::FileType::from_raw_mode(stat.st_mode)
+                    == rustix::fs::FileType::Directory,
+            ))
+        }
+        Err(e) if is_missing(&e) => Ok(None),
+        Err(e) => Err(source(e)),
+    }
 }
 
 #[async_trait]
@@ -109,6 +361,106 @@ impl MemoryProvider for FileMemoryProvider {
         let loader = MemoryLoader::new();
         loader.load(&self.path).await
     }
+
+    async fn list(&self, dir: &str) -> ContextResult<Vec<MemoryEntry>> {
+        let dir = checked(dir)?.to_string();
+        self.with_fs(false, move |fs| {
+            let Some(fs) = fs else {
+                return if dir.is_empty() {
+                    Ok(Vec::new())
+                } else {
+                    Err(not_found(&dir))
+                };
+            };
+            if kind(&fs, &dir)? != Some(true) {
+                return Err(not_found(&dir));
+            }
+            let target = if dir.is_empty() { "." } else { dir.as_str() };
+            let walked = fs.resolve(target).and_then(|p| p.walk()).map_err(source)?;
+            Ok(walked
+                .into_iter()
+                .map(|(path, is_dir, size)| {
+                    let relative = path.to_string_lossy();
+                    MemoryEntry {
+                        path: if dir.is_empty() {
+                            relative.into_owned()
+                        } else {
+                            format!("{}/{}", dir, relative)
+                        },
+                        is_dir,
+                        size,
+                    }
+                })
+                .collect())
+        })
+        .await
+    }
+
+    async fn is_dir(&self, path: &str) -> ContextResult<bool> {
+        let path = checked(path)?.to_string();
+        self.with_fs(false, move |fs| match fs {
+            Some(fs) => Ok(kind(&fs, &path)? == Some(true)),
+            None => Ok(path.is_empty()),
+        })
+        .await
+    }
+
+    async fn read(&self, path: &str) -> ContextResult<Option<String>> {
+        let path = checked(path)?.to_string();
+        self.with_fs(false, move |fs| {
+            let Some(fs) = fs else { return Ok(None) };
+            match fs.open_read(&path).and_then(|h| h.read_to_string()) {
+                Ok(content) => Ok(Some(content)),
+                Err(e) if is_missing(&e) => Ok(None),
+                Err(e) => Err(source(e)),
+            }
+        })
+        .await
+    }
+
+    async fn write(&self, path: &str, content: &str) -> ContextResult<()> {
+        let path = checked(path)?.to_string();
+        let content = content.to_string();
+        self.with_fs(true, move |fs| {
+            let Some(fs) = fs else { return Ok(()) };
+            fs.resolve(&path)
+                .and_then(SecureFileHandle::for_atomic_write)
+                .and_then(|h| h.atomic_write(content.as_bytes()))
+                .map_err(source)
+        })
+        .await
+    }
+
+    async fn delete(&self, path: &str) -> ContextResult<bool> {
+        let path = checked(path)?.to_string();
+        self.with_fs(false, move |fs| {
+            let Some(fs) = fs else { return Ok(false) };
+            match fs.remove(&path) {
+                Ok(()) => Ok(true),
+                Err(e) if is_missing(&e) => Ok(false),
+                Err(e) => Err(source(e)),
+            }
+        })
+        .await
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> ContextResult<()> {
+        let (from, to) = (checked(from)?.to_string(), checked(to)?.to_string());
+        self.with_fs(false, move |fs| {
+            let Some(fs) = fs else {
+                return Err(not_found(&from));
+            };
+            match fs.rename(&from, &to) {
+                Ok(()) => Ok(()),
+                Err(e) if is_missing(&e) => Err(not_found(&from)),
+                Err(SecurityError::Io(e)) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    Err(already_exists(&to))
+                }
+                Err(e) => Err(source(e)),
+            }
+        })
+        .await
+    }
 }
 
 #[cfg(test)]
@@ -143,4 +495,84 @@ mod tests {
         let content = provider.load().await.unwrap();
         assert!(content.is_empty());
     }
+
+    async fn exercise(provider: &dyn MemoryProvider) {
+        provider.write("notes/todo.md", "- ship").await.unwrap();
+        provider.write("profile.md", "name: Ada").await.unwrap();
+
+        let paths: Vec<_> = provider
+            .list("")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.path, e.is_dir))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("notes".to_string(), true),
+                ("notes/todo.md".to_string(), false),
+                ("profile.md".to_string(), false),
+            ]
+        );
+        assert!(provider.is_dir("notes").await.unwrap());
+        assert!(!provider.is_dir("profile.md").await.unwrap());
+
+        provider.rename("notes", "archive").await.unwrap();
+        assert_eq!(
+            provider.read("archive/todo.md").await.unwrap().as_deref(),
+            Some("- ship")
+        );
+        assert!(provider.rename("archive", "profile.md").await.is_err());
+        assert!(provider.rename("missing", "elsewhere").await.is_err());
+
+        assert!(provider.delete("archive").await.unwrap());
+        assert!(!provider.delete("archive").await.unwrap());
+        assert_eq!(provider.read("archive/todo.md").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_files() {
+        exercise(&MemoryContextProvider::new()).await;
+    }
+
+    #[tokio::test]
+    async fn test_file_provider_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = FileMemoryProvider::new(dir.path().join("memories"));
+        assert!(provider.list("").await.unwrap().is_empty());
+        exercise(&provider).await;
+        assert!(provider.write("../escape.md", "x").await.is_err());
+        assert!(provider.read("/etc/passwd").await.is_err());
+        assert!(!dir.path().join("escape.md").exists());
+        assert!(!dir.path().join("memories/elsewhere").exists());
+    }
+
+    #[tokio::test]
+    async fn test_file_provider_ignores_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside = dir.path().join("outside");
+        std::fs::create_dir(&outside).unwrap();
+        std::fs::write(outside.join("secret.md"), "secret").unwrap();
+        let root = dir.path().join("memories");
+        std::fs::create_dir(&root).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+        std::os::unix::fs::symlink(outside.join("secret.md"), root.join("file.md")).unwrap();
+
+        let provider = FileMemoryProvider::new(&root);
+        assert!(provider.list("").await.unwrap().is_empty());
+        assert!(!provider.is_dir("link").await.unwrap_or(false));
+        assert!(provider.read("link/secret.md").await.is_err());
+        assert!(provider.read("file.md").await.is_err());
+        assert!(provider.write("link/new.md", "x").await.is_err());
+        assert!(provider.write("file.md", "x").await.is_err());
+        assert!(provider.rename("link/secret.md", "moved.md").await.is_err());
+
+        assert!(provider.delete("link").await.is_err());
+        assert!(provider.delete("link/secret.md").await.is_err());
+        assert_eq!(
+            std::fs::read_to_string(outside.join("secret.md")).unwrap(),
+            "secret"
+        );
+    }
 }
@@ -9,14 +9,18 @@ use regex::Regex;
 use tokio::io::AsyncWriteExt;
 use tokio::process::Command;
 
-use super::{Hook, HookContext, HookEvent, HookInput, HookOutput};
+use super::{Hook, HookContext, HookEvent, HookEventData, HookInput, HookOutput, NotificationKind};
 use crate::config::{HookConfig, HooksSettings};
+use crate::permissions::PermissionRule;
+use crate::security::bash::BashRisk;
 
 pub struct CommandHook {
     name: String,
     command: String,
     events: Vec<HookEvent>,
     tool_pattern: Option<Regex>,
+    /// Input scope of a matcher like `Bash(git:*)`.
+    input_scope: Option<PermissionRule>,
     timeout_secs: u64,
     extra_env: HashMap<String, String>,
 }
@@ -32,13 +36,31 @@ impl CommandHook {
             command: command.into(),
             events,
             tool_pattern: None,
+            input_scope: None,
             timeout_secs: 60,
             extra_env: HashMap::new(),
         }
     }
 
+    /// Restrict the hook to matching tool calls.
+    ///
+    /// Takes a tool name regex (`Edit|Write`), a scoped pattern as used by
+    /// permission rules (`Bash(git:*)`, `Edit(src/*)`) that is also checked
+    /// against the tool input where the event carries it, or `*`/empty for
+    /// every tool.
     pub fn matcher(mut self, pattern: &str) -> Self {
-        self.tool_pattern = Regex::new(pattern).ok();
+        let pattern = pattern.trim();
+        if pattern.is_empty() || pattern == "*" {
+            self.tool_pattern = None;
+            self.input_scope = None;
+        } else if let Some(tool) = pattern.strip_suffix(')').and_then(|p| p.split_once('(')) {
+            self.tool_pattern = Regex::new(&format!("^(?:{})$", tool.0)).ok();
+            // Deny semantics: a compound Bash command matches if any part does.
+            self.input_scope = Some(PermissionRule::deny_scoped(pattern));
+        } else {
+            self.tool_pattern = Regex::new(pattern).ok();
+            self.input_scope = None;
+        }
         self
     }
 
@@ -83,6 +105,26 @@ impl CommandHook {
             ));
         }
 
+        let mut events: Vec<_> = settings.rules.iter().collect();
+        events.sort_by_key(|(name, _)| name.as_str());
+        for (name, rules) in events {
+            let Some(event) = HookEvent::from_pascal_case(name) else {
+                tracing::debug!(event = %name, "Skipping hooks for unsupported event");
+                continue;
+            };
+            for (i, rule) in rules.iter().enumerate() {
+                for (j, action) in rule.hooks.iter().enumerate() {
+                    if let Some(config) = action.to_hook_config(rule.matcher.as_deref()) {
+                        hooks.push(Self::from_event_config(
+                            format!("{}-{}-{}", event, i, j),
+                            event,
+                            &config,
+                        ));
+                    }
+                }
+            }
+        }
+
         hooks
     }
 
@@ -132,6 +174,19 @@ impl Hook for CommandHook {
         self.timeout_secs
     }
 
+    fn input_matches(&self, input: &HookInput) -> bool {
+        match (
+            &self.input_scope,
+            input.tool_name(),
+            input.data.tool_input(),
+        ) {
+            (Some(scope), Some(tool), Some(tool_input)) => {
+                scope.matches_with_input(tool, tool_input)
+            }
+            _ => true,
+        }
+    }
+
     async fn execute(
         &self,
         input: HookInput,
@@ -140,28 +195,38 @@ impl Hook for CommandHook {
         let input_json = serde_json::to_string(&InputPayload::from_input(&input))
             .map_err(|e| crate::Error::Config(format!("Failed to serialize hook input: {}", e)))?;
 
-        let mut child = Command::new("sh")
+        let cwd = hook_context
+            .cwd
+            .as_deref()
+            .unwrap_or(std::path::Path::new("."));
+        let mut command = Command::new("sh");
+        if let Some(policy) = &hook_context.env_policy {
+            command
+                .env_clear()
+                .envs(policy.sanitize_current().working_dir(cwd));
+        }
+        let mut child = command
             .arg("-c")
             .arg(&self.command)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::inherit())
-            .current_dir(
-                hook_context
-                    .cwd
-                    .as_deref()
-                    .unwrap_or(std::path::Path::new(".")),
-            )
+            .stderr(Stdio::piped())
+            .current_dir(cwd)
             .envs(&hook_context.env)
             .envs(&self.extra_env)
             .spawn()
             .map_err(|e| crate::Error::Config(format!("Failed to spawn hook command: {}", e)))?;
 
         if let Some(mut stdin) = child.stdin.take() {
-            stdin
-                .write_all(input_json.as_bytes())
-                .await
-                .map_err(|e| crate::Error::Config(format!("Failed to write to stdin: {}", e)))?;
+            // Commands that never read stdin may exit before the payload is written.
+            if let Err(e) = stdin.write_all(input_json.as_bytes()).await
+                && e.kind() != std::io::ErrorKind::BrokenPipe
+            {
+                return Err(crate::Error::Config(format!(
+                    "Failed to write to stdin: {}",
+                    e
+                )));
+            }
         }
 
         let timeout = Duration::from_secs(self.timeout_secs);
@@ -171,6 +236,14 @@ impl Hook for CommandHook {
             .map_err(|e| crate::Error::Config(format!("Hook command failed: {}", e)))?;
 
         if !output.status.success() {
+            // Exit code 2 is a deliberate block whose reason is on stderr.
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if output.status.code() == Some(2) && !stderr.trim().is_empty() {
+                return Ok(HookOutput::block(stderr.trim()));
+            }
+            if !stderr.trim().is_empty() {
+                tracing::warn!(hook = %self.name, stderr = %stderr.trim(), "Hook command failed");
+            }
             return Ok(HookOutput::block(format!(
                 "Hook '{}' failed with exit code: {:?}",
                 self.name,
@@ -196,6 +269,12 @@ struct InputPayload {
     session_id: String,
     tool_name: Option<String>,
     tool_input: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bash_risk: Option<BashRisk>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    notification_type: Option<NotificationKind>,
 }
 
 impl InputPayload {
@@ -205,27 +284,88 @@ impl InputPayload {
             session_id: input.session_id.clone(),
             tool_name: input.tool_name().map(String::from),
             tool_input: input.data.tool_input().cloned(),
+            bash_risk: input.bash_risk(),
+            message: input.data.message().map(String::from),
+            notification_type: match &input.data {
+                HookEventData::Notification { kind, .. } => Some(*kind),
+                _ => None,
+            },
         }
     }
 }
 
+/// Hook stdout. Accepts the Claude Code contract (`continue`, `decision`,
+/// `hookSpecificOutput`, ...) as well as the older snake_case fields.
 #[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct OutputPayload {
-    #[serde(default = "default_true")]
+    #[serde(
+        rename = "continue",
+        alias = "continue_execution",
+        default = "default_true"
+    )]
     continue_execution: bool,
+    #[serde(alias = "stop_reason")]
     stop_reason: Option<String>,
+    #[serde(alias = "updated_input")]
+    updated_input: Option<serde_json::Value>,
+    decision: Option<Decision>,
+    reason: Option<String>,
+    #[serde(default)]
+    suppress_output: bool,
+    system_message: Option<String>,
+    #[serde(default)]
+    hook_specific_output: HookSpecificOutput,
+}
+
+#[derive(Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HookSpecificOutput {
+    permission_decision: Option<Decision>,
+    permission_decision_reason: Option<String>,
     updated_input: Option<serde_json::Value>,
+    additional_context: Option<String>,
+}
+
+/// `decision` (`approve`/`block`) or `permissionDecision` (`allow`/`deny`/`ask`).
+#[derive(Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Decision {
+    Approve,
+    Allow,
+    Block,
+    Deny,
+    /// No one can be asked from a hook, so this blocks like `deny`.
+    Ask,
 }
 
 use crate::common::serde_defaults::default_true;
 
 impl OutputPayload {
     fn into_output(self) -> HookOutput {
+        let specific = self.hook_specific_output;
+        let decision = specific.permission_decision.or(self.decision);
+        let reason = specific.permission_decision_reason.or(self.reason);
+        let blocked = match decision {
+            Some(Decision::Block | Decision::Deny) => Some("Blocked by hook"),
+            Some(Decision::Ask) => Some("Hook requires user approval"),
+            _ => None,
+        };
+        let stop_reason = match blocked {
+            Some(fallback) => Some(
+                reason
+                    .or(self.stop_reason)
+                    .unwrap_or_else(|| fallback.into()),
+            ),
+            None => self.stop_reason,
+        };
         HookOutput {
-            continue_execution: self.continue_execution,
-            stop_reason: self.stop_reason,
-            updated_input: self.updated_input,
-            ..Default::default()
+            continue_execution: self.continue_execution && blocked.is_none(),
+            stop_reason,
+            suppress_logging: self.suppress_output,
+            system_message: self.system_message,
+            updated_input: specific.updated_input.or(self.updated_input),
+            additional_context: specific.additional_context,
         }
     }
 }
@@ -263,6 +403,52 @@ mod tests {
         assert_eq!(hooks[0].timeout_secs(), 10);
     }
 
+    #[test]
+    fn test_from_settings_matcher_groups() {
+        let settings: HooksSettings = serde_json::from_value(serde_json::json!({
+            "PreToolUse": [{
+                "matcher": "Bash(git:*)",
+                "hooks": [
+                    {"type": "command", "command": "audit.sh", "timeout": 5},
+                    {"type": "command", "command": "log.sh"}
+                ]
+            }],
+            "UserPromptSubmit": [{"hooks": [{"type": "command", "command": "check.sh"}]}]
+        }))
+        .unwrap();
+
+        let hooks = CommandHook::from_settings(&settings);
+        let names: Vec<_> = hooks.iter().map(|h| h.name()).collect();
+        assert_eq!(
+            names,
+            [
+                "pre_tool_use-0-0",
+                "pre_tool_use-0-1",
+                "user_prompt_submit-0-0"
+            ]
+        );
+        assert_eq!(hooks[0].timeout_secs(), 5);
+        assert_eq!(hooks[2].events(), &[HookEvent::UserPromptSubmit]);
+    }
+
+    #[test]
+    fn test_scoped_matcher() {
+        let hook =
+            CommandHook::new("git", "true", vec![HookEvent::PreToolUse]).matcher("Bash(git:*)");
+        let matcher = hook.tool_matcher().unwrap();
+        assert!(matcher.is_match("Bash"));
+        assert!(!matcher.is_match("BashOutput"));
+
+        let git = HookInput::pre_tool_use("s", "Bash", serde_json::json!({"command": "git push"}));
+        let rm = HookInput::pre_tool_use("s", "Bash", serde_json::json!({"command": "rm -rf x"}));
+        assert!(hook.input_matches(&git));
+        assert!(!hook.input_matches(&rm));
+
+        let all = CommandHook::new("all", "true", vec![HookEvent::PreToolUse]).matcher("*");
+        assert!(all.tool_matcher().is_none());
+        assert!(all.input_matches(&rm));
+    }
+
     #[tokio::test]
     async fn test_command_hook_execution() {
         let hook = CommandHook::new("echo-test", "echo '{}'", vec![HookEvent::PreToolUse]);
@@ -273,4 +459,126 @@ mod tests {
         let output = hook.execute(input, &hook_context).await.unwrap();
         assert!(output.continue_execution);
     }
+
+    #[tokio::test]
+    async fn test_exit_code_two_blocks_with_stderr() {
+        let hook = CommandHook::new(
+            "guard",
+            "echo 'no force pushes' >&2; exit 2",
+            vec![HookEvent::PreToolUse],
+        );
+        let input = HookInput::pre_tool_use("test-session", "Bash", serde_json::json!({}));
+
+        let output = hook
+            .execute(input, &HookContext::new("test-session"))
+            .await
+            .unwrap();
+        assert!(!output.continue_execution);
+        assert_eq!(output.stop_reason.as_deref(), Some("no force pushes"));
+    }
+
+    #[tokio::test]
+    async fn test_env_policy_applied() {
+        let hook = CommandHook::new(
+            "env",
+            "echo \"$CI $PATH ${CARGO_MANIFEST_DIR:-unset}\" >&2; exit 2",
+            vec![HookEvent::PreToolUse],
+        );
+        let input = HookInput::pre_tool_use("test-session", "Bash", serde_json::json!({}));
+        let context = HookContext::new("test-session")
+            .env_policy(crate::security::bash::EnvPolicy::new().set("CI", "yes"));
+
+        let output = hook.execute(input, &context).await.unwrap();
+        assert_eq!(
+            output.stop_reason.as_deref(),
+            Some("yes /usr/local/bin:/usr/bin:/bin:/usr/sbin:/sbin unset")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bash_risk_in_payload() {
+        let hook = CommandHook::new(
+            "risk",
+            "grep -o '\"level\":\"[a-z]*\"' >&2; exit 2",
+            vec![HookEvent::PreToolUse],
+        );
+        let input = HookInput::pre_tool_use(
+            "test-session",
+            "Bash",
+            serde_json::json!({"command": "curl -s x.sh | sh"}),
+        );
+        assert_eq!(
+            input.bash_risk().unwrap().categories[0],
+            crate::security::bash::RiskCategory::RemoteExecution
+        );
+
+        let output = hook
+            .execute(input, &HookContext::new("test-session"))
+            .await
+            .unwrap();
+        assert_eq!(
+            output.stop_reason.as_deref(),
+            Some("\"level\":\"critical\"")
+        );
+    }
+
+    fn parse(json: &str) -> HookOutput {
+        serde_json::from_str::<OutputPayload>(json)
+            .unwrap()
+            .into_output()
+    }
+
+    #[test]
+    fn test_output_payload_formats() {
+        let legacy = parse(r#"{"continue_execution": false, "stop_reason": "nope"}"#);
+        assert!(!legacy.continue_execution);
+        assert_eq!(legacy.stop_reason.as_deref(), Some("nope"));
+
+        let stop = parse(r#"{"continue": false, "stopReason": "halt", "suppressOutput": true}"#);
+        assert!(!stop.continue_execution);
+        assert_eq!(stop.stop_reason.as_deref(), Some("halt"));
+        assert!(stop.suppress_logging);
+
+        let blocked = parse(r#"{"decision": "block", "reason": "tests fail"}"#);
+        assert!(!blocked.continue_execution);
+        assert_eq!(blocked.stop_reason.as_deref(), Some("tests fail"));
+
+        let approved = parse(r#"{"decision": "approve", "systemMessage": "auto-approved"}"#);
+        assert!(approved.continue_execution);
+        assert_eq!(approved.system_message.as_deref(), Some("auto-approved"));
+    }
+
+    #[test]
+    fn test_output_payload_hook_specific() {
+        let denied = parse(
+            r#"{"hookSpecificOutput": {
+                "hookEventName": "PreToolUse",
+                "permissionDecision": "deny",
+                "permissionDecisionReason": "outside project"
+            }}"#,
+        );
+        assert!(!denied.continue_execution);
+        assert_eq!(denied.stop_reason.as_deref(), Some("outside project"));
+
+        let ask = parse(r#"{"hookSpecificOutput": {"permissionDecision": "ask"}}"#);
+        assert!(!ask.continue_execution);
+        assert!(ask.stop_reason.is_some());
+
+        let allowed = parse(
+            r#"{"hookSpecificOutput": {
+                "permissionDecision": "allow",
+                "updatedInput": {"command": "ls -la"},
+                "additionalContext": "Repo uses pnpm"
+            }}"#,
+        );
+        assert!(allowed.continue_execution);
+        assert_eq!(
+            allowed.updated_input,
+            Some(serde_json::json!({"command": "ls -la"}))
+        );
+        assert_eq!(
+            allowed.additional_context.as_deref(),
+            Some("Repo uses pnpm")
+        );
+    }
 }
@@ -1,6 +1,7 @@
 //! Hook manager for registering and executing hooks.
 
-use super::{Hook, HookContext, HookEvent, HookInput, HookOutput};
+use super::{Hook, HookContext, HookEvent, HookInput, HookOutput, HookRateLimit, RateLimitedHook};
+use futures::{StreamExt, stream};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::time::{Duration, timeout};
@@ -10,6 +11,7 @@ pub struct HookManager {
     hooks: Vec<Arc<dyn Hook>>,
     cache: HashMap<HookEvent, Vec<usize>>,
     default_timeout_secs: u64,
+    max_parallel: usize,
 }
 
 impl Default for HookManager {
@@ -24,6 +26,7 @@ impl HookManager {
             hooks: Vec::new(),
             cache: HashMap::new(),
             default_timeout_secs: 60,
+            max_parallel: 1,
         }
     }
 
@@ -32,9 +35,21 @@ impl HookManager {
             hooks: Vec::new(),
             cache: HashMap::new(),
             default_timeout_secs: timeout_secs,
+            max_parallel: 1,
         }
     }
 
+    /// Run up to `max` hooks of a non-blockable event at once (default: 1).
+    ///
+    /// Blockable events always run their hooks one at a time.
+    pub fn set_max_parallel(&mut self, max: usize) {
+        self.max_parallel = max.max(1);
+    }
+
+    pub fn max_parallel(&self) -> usize {
+        self.max_parallel
+    }
+
     fn rebuild_cache(&mut self) {
         self.cache.clear();
         for event in HookEvent::all() {
@@ -55,6 +70,11 @@ impl HookManager {
         self.rebuild_cache();
     }
 
+    /// Register a hook that is skipped while over `limit`.
+    pub fn register_rate_limited<H: Hook + 'static>(&mut self, hook: H, limit: HookRateLimit) {
+        self.register(RateLimitedHook::new(hook, limit));
+    }
+
     pub fn register_arc(&mut self, hook: Arc<dyn Hook>) {
         self.hooks.push(hook);
         self.rebuild_cache();
@@ -115,7 +135,11 @@ impl HookManager {
     where
         F: FnMut(&str, &HookOutput),
     {
-        let hooks = self.hooks_for_event(event);
+        let hooks: Vec<_> = self
+            .hooks_for_event(event)
+            .into_iter()
+            .filter(|hook| Self::hook_matches(hook.as_ref(), &input))
+            .collect();
 
         if hooks.is_empty() {
             return Ok(HookOutput::allow());
@@ -123,52 +147,37 @@ impl HookManager {
 
         let mut merged_output = HookOutput::allow();
 
-        for hook in hooks {
-            if let (Some(matcher), Some(tool_name)) = (hook.tool_matcher(), input.tool_name())
-                && !matcher.is_match(tool_name)
-            {
-                continue;
-            }
-
-            let hook_timeout = hook.timeout_secs().min(self.default_timeout_secs);
-            let result = timeout(
-                Duration::from_secs(hook_timeout),
-                hook.execute(input.clone(), hook_context),
-            )
-            .await;
-
-            let output = match result {
-                Ok(Ok(output)) => output,
-                Ok(Err(e)) => {
-                    if event.can_block() {
-                        // Blockable hooks use fail-closed: errors propagate
-                        return Err(crate::Error::HookFailed {
-                            hook: hook.name().to_string(),
-                            reason: e.to_string(),
-                        });
-                    }
-                    // Non-blockable hooks use fail-open: log and continue
-                    tracing::warn!(hook = hook.name(), error = %e, "Hook execution failed");
+        // Blockable hooks run one at a time so a block stops the rest.
+        if event.can_block() || self.max_parallel <= 1 {
+            for hook in hooks {
+                let Some(output) = self.run_hook(hook, event, &input, hook_context).await? else {
                     continue;
+                };
+                if let Some(ref mut h) = handler {
+                    h(hook.name(), &output);
                 }
-                Err(_) => {
-                    if event.can_block() {
-                        // Blockable hooks use fail-closed: timeouts propagate
-                        return Err(crate::Error::HookTimeout {
-                            hook: hook.name().to_string(),
-                            duration_secs: hook_timeout,
-                        });
-                    }
-                    // Non-blockable hooks use fail-open: log and continue
-                    tracing::warn!(
-                        hook = hook.name(),
-                        timeout_secs = hook_timeout,
-                        "Hook timed out"
-                    );
-                    continue;
+                merged_output = Self::merge_outputs(merged_output, output);
+
+                if !merged_output.continue_execution {
+                    break;
                 }
-            };
+            }
+            return Ok(merged_output);
+        }
 
+        // Outputs are merged in priority order, whatever order hooks finish in.
+        let mut runs = Vec::with_capacity(hooks.len());
+        for hook in &hooks {
+            runs.push(self.run_hook(hook, event, &input, hook_context));
+        }
+        let outputs: Vec<_> = stream::iter(runs)
+            .buffered(self.max_parallel)
+            .collect()
+            .await;
+        for (hook, output) in hooks.into_iter().zip(outputs) {
+            let Some(output) = output? else {
+                continue;
+            };
             if let Some(ref mut h) = handler {
                 h(hook.name(), &output);
             }
@@ -182,6 +191,63 @@ impl HookManager {
         Ok(merged_output)
     }
 
+    fn hook_matches(hook: &dyn Hook, input: &HookInput) -> bool {
+        if let (Some(matcher), Some(tool_name)) = (hook.tool_matcher(), input.tool_name())
+            && !matcher.is_match(tool_name)
+        {
+            return false;
+        }
+        hook.input_matches(input)
+    }
+
+    /// Run one hook. `None` means a non-blockable hook failed and was skipped.
+    async fn run_hook(
+        &self,
+        hook: &Arc<dyn Hook>,
+        event: HookEvent,
+        input: &HookInput,
+        hook_context: &HookContext,
+    ) -> Result<Option<HookOutput>, crate::Error> {
+        let hook_timeout = hook.timeout_secs().min(self.default_timeout_secs);
+        let result = timeout(
+            Duration::from_secs(hook_timeout),
+            hook.execute(input.clone(), hook_context),
+        )
+        .await;
+
+        match result {
+            Ok(Ok(output)) => Ok(Some(output)),
+            Ok(Err(e)) => {
+                if event.can_block() {
+                    // Blockable hooks use fail-closed: errors propagate
+                    return Err(crate::Error::HookFailed {
+                        hook: hook.name().to_string(),
+                        reason: e.to_string(),
+                    });
+                }
+                // Non-blockable hooks use fail-open: log and continue
+                tracing::warn!(hook = hook.name(), error = %e, "Hook execution failed");
+                Ok(None)
+            }
+            Err(_) => {
+                if event.can_block() {
+                    // Blockable hooks use fail-closed: timeouts propagate
+                    return Err(crate::Error::HookTimeout {
+                        hook: hook.name().to_string(),
+                        duration_secs: hook_timeout,
+                    });
+                }
+                // Non-blockable hooks use fail-open: log and continue
+                tracing::warn!(
+                    hook = hook.name(),
+                    timeout_secs = hook_timeout,
+                    "Hook timed out"
+                );
+                Ok(None)
+            }
+        }
+    }
+
     fn merge_outputs(base: HookOutput, new: HookOutput) -> HookOutput {
         HookOutput {
             continue_execution: base.continue_execution && new.continue_execution,
@@ -203,6 +269,7 @@ impl std::fmt::Debug for HookManager {
             .field("hook_count", &self.hooks.len())
             .field("hook_names", &self.hook_names())
             .field("default_timeout_secs", &self.default_timeout_secs)
+            .field("max_parallel", &self.max_parallel)
             .finish()
     }
 }
@@ -278,6 +345,27 @@ mod tests {
         assert_eq!(manager.hook_names().len(), 2);
     }
 
+    #[tokio::test]
+    async fn test_rate_limited_registration() {
+        let mut manager = HookManager::new();
+        manager.register_rate_limited(
+            TestHook::blocking("slow", vec![HookEvent::PostToolUse], 0),
+            HookRateLimit::debounce(Duration::from_secs(60)),
+        );
+        assert!(manager.has_hook("slow"));
+
+        let hook_context = HookContext::new("test-session");
+        let run = || {
+            manager.execute(
+                HookEvent::PostToolUse,
+                HookInput::post_tool_use("test-session", "Edit", crate::types::ToolOutput::empty()),
+                &hook_context,
+            )
+        };
+        assert!(!run().await.unwrap().continue_execution);
+        assert!(run().await.unwrap().continue_execution);
+    }
+
     #[tokio::test]
     async fn test_hook_unregistration() {
         let mut manager = HookManager::new();
@@ -507,4 +595,90 @@ mod tests {
         assert!(result.is_ok());
         assert!(result.unwrap().continue_execution);
     }
+
+    struct DelayedHook {
+        name: String,
+        priority: i32,
+        delay_ms: u64,
+    }
+
+    #[async_trait]
+    impl Hook for DelayedHook {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn events(&self) -> &[HookEvent] {
+            &[HookEvent::PostToolUse, HookEvent::PreToolUse]
+        }
+
+        fn priority(&self) -> i32 {
+            self.priority
+        }
+
+        async fn execute(
+            &self,
+            _input: HookInput,
+            _hook_context: &HookContext,
+        ) -> Result<HookOutput, crate::Error> {
+            tokio::time::sleep(Duration::from_millis(self.delay_ms)).await;
+            Ok(HookOutput::allow().context(self.name.clone()))
+        }
+    }
+
+    fn delayed_manager(max_parallel: usize) -> HookManager {
+        let mut manager = HookManager::new();
+        manager.set_max_parallel(max_parallel);
+        for (name, priority, delay_ms) in
+            [("first", 30, 200), ("second", 20, 10), ("third", 10, 100)]
+        {
+            manager.register(DelayedHook {
+                name: name.to_string(),
+                priority,
+                delay_ms,
+            });
+        }
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_parallel_non_blockable_hooks() {
+        let manager = delayed_manager(3);
+        let input = HookInput::post_tool_use(
+            "session-1",
+            "Read",
+            crate::types::ToolOutput::success("result"),
+        );
+        let hook_context = HookContext::new("session-1");
+
+        let mut seen = Vec::new();
+        let start = std::time::Instant::now();
+        let output = manager
+            .execute_with_handler(HookEvent::PostToolUse, input, &hook_context, |name, _| {
+                seen.push(name.to_string())
+            })
+            .await
+            .unwrap();
+
+        assert!(start.elapsed() < Duration::from_millis(300));
+        assert_eq!(seen, ["first", "second", "third"]);
+        assert_eq!(
+            output.additional_context.as_deref(),
+            Some("first\nsecond\nthird")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_blockable_hooks_stay_sequential() {
+        let manager = delayed_manager(3);
+        let input = HookInput::pre_tool_use("session-1", "Read", serde_json::json!({}));
+        let hook_context = HookContext::new("session-1");
+
+        let start = std::time::Instant::now();
+        manager
+            .execute(HookEvent::PreToolUse, input, &hook_context)
+            .await
+            .unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(310));
+    }
 }
@@ -3,12 +3,14 @@
 mod command;
 mod manager;
 pub mod rule;
+mod throttle;
 mod traits;
 
 pub use command::CommandHook;
 pub use manager::HookManager;
 pub use rule::{HookAction, HookRule};
+pub use throttle::{HookRateLimit, HookRateScope, RateLimitedHook};
 pub use traits::{
     FnHook, FnHookBuilder, Hook, HookContext, HookEvent, HookEventData, HookInput, HookMetadata,
-    HookOutput, HookSource,
+    HookOutput, HookSource, NotificationKind,
 };
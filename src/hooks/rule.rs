@@ -28,6 +28,27 @@ pub struct HookAction {
     pub timeout: Option<u64>,
 }
 
+impl From<HookConfig> for HookRule {
+    fn from(config: HookConfig) -> Self {
+        let (command, timeout, matcher) = match config {
+            HookConfig::Command(command) => (command, None, None),
+            HookConfig::Full {
+                command,
+                timeout_secs,
+                matcher,
+            } => (command, timeout_secs, matcher),
+        };
+        Self {
+            matcher,
+            hooks: vec![HookAction {
+                hook_type: "command".into(),
+                command: Some(command),
+                timeout,
+            }],
+        }
+    }
+}
+
 impl HookAction {
     /// Create a `HookConfig` from this action, using the rule-level matcher.
     ///
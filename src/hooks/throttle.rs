@@ -0,0 +1,250 @@
+//! Rate limits for hooks that are expensive to run.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use regex::Regex;
+
+use super::{Hook, HookContext, HookEvent, HookInput, HookOutput, HookSource};
+use crate::common::clock;
+
+/// What calls share a rate limit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HookRateScope {
+    /// All calls of the hook.
+    #[default]
+    Hook,
+    /// Calls for the same tool.
+    Tool,
+    /// Calls for the same file (`file_path`, `notebook_path` or `path` in the
+    /// tool input). Calls without a path share one limit.
+    File,
+}
+
+/// How often a hook may run. Calls over the limit are skipped and allowed.
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use claude_agent::hooks::HookRateLimit;
+/// // A formatter that runs at most once every 5 seconds per file.
+/// let limit = HookRateLimit::debounce(Duration::from_secs(5)).per_file();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HookRateLimit {
+    max_calls: usize,
+    window: Duration,
+    scope: HookRateScope,
+}
+
+impl HookRateLimit {
+    /// At most one call per `interval`.
+    pub fn debounce(interval: Duration) -> Self {
+        Self::per_window(1, interval)
+    }
+
+    /// At most `max_calls` calls in any `window`.
+    pub fn per_window(max_calls: usize, window: Duration) -> Self {
+        Self {
+            max_calls: max_calls.max(1),
+            window,
+            scope: HookRateScope::Hook,
+        }
+    }
+
+    /// Limit each tool separately.
+    pub fn per_tool(mut self) -> Self {
+        self.scope = HookRateScope::Tool;
+        self
+    }
+
+    /// Limit each file separately.
+    pub fn per_file(mut self) -> Self {
+        self.scope = HookRateScope::File;
+        self
+    }
+
+    pub fn scope(&self) -> HookRateScope {
+        self.scope
+    }
+
+    fn key(&self, input: &HookInput) -> String {
+        match self.scope {
+            HookRateScope::Hook => String::new(),
+            HookRateScope::Tool => input.tool_name().unwrap_or_default().to_string(),
+            HookRateScope::File => input
+                .data
+                .tool_input()
+                .and_then(|tool_input| {
+                    ["file_path", "notebook_path", "path"]
+                        .iter()
+                        .find_map(|field| tool_input.get(field).and_then(|v| v.as_str()))
+                })
+                .unwrap_or_default()
+                .to_string(),
+        }
+    }
+}
+
+/// A hook that skips calls exceeding its [`HookRateLimit`].
+///
+/// Usually created through [`HookManager::register_rate_limited`](super::HookManager::register_rate_limited).
+/// A skipped call returns [`HookOutput::allow`], so limiting a blocking
+/// `PreToolUse` hook lets the calls it skips through.
+pub struct RateLimitedHook {
+    inner: Arc<dyn Hook>,
+    limit: HookRateLimit,
+    calls: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl RateLimitedHook {
+    pub fn new(hook: impl Hook + 'static, limit: HookRateLimit) -> Self {
+        Self::from_arc(Arc::new(hook), limit)
+    }
+
+    pub fn from_arc(hook: Arc<dyn Hook>, limit: HookRateLimit) -> Self {
+        Self {
+            inner: hook,
+            limit,
+            calls: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn limit(&self) -> &HookRateLimit {
+        &self.limit
+    }
+
+    /// Record a call at `now` unless it exceeds the limit.
+    fn admit(&self, key: String, now: Instant) -> bool {
+        let mut calls = self.calls.lock().unwrap_or_else(|e| e.into_inner());
+        let recent = calls.entry(key).or_default();
+        while recent
+            .front()
+            .is_some_and(|&at| now.saturating_duration_since(at) >= self.limit.window)
+        {
+            recent.pop_front();
+        }
+        if recent.len() >= self.limit.max_calls {
+            return false;
+        }
+        recent.push_back(now);
+        true
+    }
+}
+
+#[async_trait]
+impl Hook for RateLimitedHook {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn events(&self) -> &[HookEvent] {
+        self.inner.events()
+    }
+
+    fn tool_matcher(&self) -> Option<&Regex> {
+        self.inner.tool_matcher()
+    }
+
+    fn input_matches(&self, input: &HookInput) -> bool {
+        self.inner.input_matches(input)
+    }
+
+    fn timeout_secs(&self) -> u64 {
+        self.inner.timeout_secs()
+    }
+
+    fn priority(&self) -> i32 {
+        self.inner.priority()
+    }
+
+    fn source(&self) -> HookSource {
+        self.inner.source()
+    }
+
+    async fn execute(
+        &self,
+        input: HookInput,
+        hook_context: &HookContext,
+    ) -> Result<HookOutput, crate::Error> {
+        if !self.admit(self.limit.key(&input), clock::now()) {
+            tracing::debug!(hook = self.name(), "Hook skipped by rate limit");
+            return Ok(HookOutput::allow());
+        }
+        self.inner.execute(input, hook_context).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct CountingHook(AtomicUsize);
+
+    #[async_trait]
+    impl Hook for CountingHook {
+        fn name(&self) -> &str {
+            "format"
+        }
+
+        fn events(&self) -> &[HookEvent] {
+            &[HookEvent::PreToolUse]
+        }
+
+        async fn execute(
+            &self,
+            _input: HookInput,
+            _hook_context: &HookContext,
+        ) -> Result<HookOutput, crate::Error> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(HookOutput::allow())
+        }
+    }
+
+    fn edit(path: &str) -> HookInput {
+        HookInput::pre_tool_use("s", "Edit", serde_json::json!({"file_path": path}))
+    }
+
+    #[tokio::test]
+    async fn test_debounce_per_file() {
+        let counter = Arc::new(CountingHook::default());
+        let hook = RateLimitedHook::from_arc(
+            Arc::clone(&counter) as Arc<dyn Hook>,
+            HookRateLimit::debounce(Duration::from_secs(60)).per_file(),
+        );
+        let ctx = HookContext::new("s");
+
+        for path in ["a.rs", "a.rs", "b.rs", "a.rs"] {
+            let output = hook.execute(edit(path), &ctx).await.unwrap();
+            assert!(output.continue_execution);
+        }
+        assert_eq!(counter.0.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_window_expires() {
+        let hook = RateLimitedHook::new(
+            CountingHook::default(),
+            HookRateLimit::per_window(2, Duration::from_secs(5)),
+        );
+        let start = Instant::now();
+
+        assert!(hook.admit(String::new(), start));
+        assert!(hook.admit(String::new(), start + Duration::from_secs(1)));
+        assert!(!hook.admit(String::new(), start + Duration::from_secs(2)));
+        assert!(hook.admit(String::new(), start + Duration::from_secs(5)));
+        assert!(!hook.admit(String::new(), start + Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_scope_keys() {
+        let input = edit("src/lib.rs");
+        let limit = HookRateLimit::debounce(Duration::from_secs(1));
+        assert_eq!(limit.key(&input), "");
+        assert_eq!(limit.clone().per_tool().key(&input), "Edit");
+        assert_eq!(limit.per_file().key(&input), "src/lib.rs");
+    }
+}
@@ -1,5 +1,7 @@
 //! Hook traits and types.
 
+use crate::common::clock;
+use crate::security::bash::{BashAnalyzer, BashRisk};
 use crate::types::ToolOutput;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
@@ -22,6 +24,8 @@ pub enum HookEvent {
     PreCompact,
     SessionStart,
     SessionEnd,
+    /// Something a user should know about; see [`NotificationKind`].
+    Notification,
 }
 
 impl HookEvent {
@@ -58,6 +62,7 @@ impl HookEvent {
             "PreCompact" => Some(Self::PreCompact),
             "SessionStart" => Some(Self::SessionStart),
             "SessionEnd" => Some(Self::SessionEnd),
+            "Notification" => Some(Self::Notification),
             _ => None,
         }
     }
@@ -74,6 +79,7 @@ impl HookEvent {
             Self::PreCompact,
             Self::SessionStart,
             Self::SessionEnd,
+            Self::Notification,
         ]
     }
 }
@@ -91,6 +97,7 @@ impl std::fmt::Display for HookEvent {
             Self::PreCompact => "pre_compact",
             Self::SessionStart => "session_start",
             Self::SessionEnd => "session_end",
+            Self::Notification => "notification",
         };
         write!(f, "{}", s)
     }
@@ -128,6 +135,23 @@ pub enum HookEventData {
     PreCompact,
     SessionStart,
     SessionEnd,
+    Notification {
+        kind: NotificationKind,
+        message: String,
+    },
+}
+
+/// Why a [`HookEvent::Notification`] was sent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum NotificationKind {
+    /// A previewed change is waiting for the approver.
+    ApprovalNeeded,
+    /// Tool calls have been running without a result for a while.
+    Idle,
+    /// The cost budget is 80% consumed.
+    BudgetWarning,
 }
 
 impl HookEventData {
@@ -143,6 +167,7 @@ impl HookEventData {
             Self::PreCompact => HookEvent::PreCompact,
             Self::SessionStart => HookEvent::SessionStart,
             Self::SessionEnd => HookEvent::SessionEnd,
+            Self::Notification { .. } => HookEvent::Notification,
         }
     }
 
@@ -170,6 +195,13 @@ impl HookEventData {
             _ => None,
         }
     }
+
+    pub fn message(&self) -> Option<&str> {
+        match self {
+            Self::Notification { message, .. } => Some(message),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -184,7 +216,7 @@ impl HookInput {
     pub fn new(session_id: impl Into<String>, data: HookEventData) -> Self {
         Self {
             session_id: session_id.into(),
-            timestamp: Utc::now(),
+            timestamp: clock::now_utc(),
             data,
             metadata: None,
         }
@@ -202,6 +234,16 @@ impl HookInput {
         self.data.subagent_id()
     }
 
+    /// Risk of the command of a `Bash` tool call, so hooks can branch on
+    /// more than the command text.
+    pub fn bash_risk(&self) -> Option<BashRisk> {
+        if self.tool_name() != Some("Bash") {
+            return None;
+        }
+        let command = self.data.tool_input()?.get("command")?.as_str()?;
+        Some(BashAnalyzer::default().analyze(command).risk())
+    }
+
     pub fn pre_tool_use(
         session_id: impl Into<String>,
         tool_name: impl Into<String>,
@@ -269,6 +311,20 @@ impl HookInput {
         Self::new(session_id, HookEventData::PreCompact)
     }
 
+    pub fn notification(
+        session_id: impl Into<String>,
+        kind: NotificationKind,
+        message: impl Into<String>,
+    ) -> Self {
+        Self::new(
+            session_id,
+            HookEventData::Notification {
+                kind,
+                message: message.into(),
+            },
+        )
+    }
+
     pub fn subagent_start(
         session_id: impl Into<String>,
         subagent_id: impl Into<String>,
@@ -355,6 +411,9 @@ pub struct HookContext {
     pub cancellation_token: CancellationToken,
     pub cwd: Option<std::path::PathBuf>,
     pub env: std::collections::HashMap<String, String>,
+    /// Sanitizes the environment command hooks inherit; without one they
+    /// inherit the whole environment. `env` is added on top either way.
+    pub env_policy: Option<crate::security::bash::EnvPolicy>,
 }
 
 impl Default for HookContext {
@@ -364,6 +423,7 @@ impl Default for HookContext {
             cancellation_token: CancellationToken::new(),
             cwd: None,
             env: std::collections::HashMap::new(),
+            env_policy: None,
         }
     }
 }
@@ -390,6 +450,11 @@ impl HookContext {
         self.env = env;
         self
     }
+
+    pub fn env_policy(mut self, policy: crate::security::bash::EnvPolicy) -> Self {
+        self.env_policy = Some(policy);
+        self
+    }
 }
 
 /// Origin of a hook registration.
@@ -457,6 +522,13 @@ pub trait Hook: Send + Sync {
         None
     }
 
+    /// Whether a call that passed [`tool_matcher`](Self::tool_matcher)
+    /// should run this hook, judged by the rest of the input.
+    #[inline]
+    fn input_matches(&self, _input: &HookInput) -> bool {
+        true
+    }
+
     #[inline]
     fn timeout_secs(&self) -> u64 {
         60
@@ -614,6 +686,7 @@ mod tests {
         assert!(!HookEvent::SessionEnd.can_block());
         assert!(!HookEvent::SubagentStop.can_block());
         assert!(!HookEvent::Stop.can_block());
+        assert!(!HookEvent::Notification.can_block());
     }
 
     #[test]
@@ -663,5 +736,14 @@ mod tests {
         assert_eq!(data.event_type(), HookEvent::SessionStart);
         assert_eq!(data.tool_name(), None);
         assert!(data.tool_input().is_none());
+
+        let input =
+            HookInput::notification("session-1", NotificationKind::Idle, "Still running: Bash");
+        assert_eq!(input.event_type(), HookEvent::Notification);
+        assert_eq!(input.data.message(), Some("Still running: Bash"));
+        assert_eq!(
+            HookEvent::from_pascal_case("Notification"),
+            Some(HookEvent::Notification)
+        );
     }
 }
@@ -64,6 +64,7 @@ pub mod hooks;
 pub mod mcp;
 pub mod models;
 pub mod observability;
+pub mod orchestration;
 pub mod output_style;
 pub mod permissions;
 #[cfg(feature = "plugins")]
@@ -74,6 +75,8 @@ pub mod security;
 pub mod session;
 pub mod skills;
 pub mod subagents;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod tokens;
 pub mod tools;
 pub mod types;
@@ -84,6 +87,7 @@ pub mod types;
 
 pub use agent::{Agent, AgentBuilder, AgentConfig, AgentEvent, AgentResult};
 pub use auth::{Auth, Credential};
+pub use claude_agent_macros::tool;
 pub use client::{Client, ClientBuilder};
 pub use permissions::{PermissionMode, PermissionPolicy};
 pub use tools::{ExecutionContext, SchemaTool, Tool, ToolAccess, ToolRegistry};
@@ -95,12 +99,12 @@ pub use types::{ContentBlock, Message, Role, ToolDefinition, ToolError, ToolOutp
 
 pub use agent::{
     AgentMetrics, AgentModelConfig, AgentState, BudgetConfig, CacheConfig, CacheStrategy,
-    ExecutionConfig, PromptConfig, SecurityConfig, SystemPromptMode, ToolStats,
+    ExecutionConfig, PromptConfig, SecurityConfig, SystemPromptMode, ToolRetryPolicy, ToolStats,
 };
 pub use auth::{CredentialProvider, OAuthConfig};
 pub use client::{
-    BetaConfig, BetaFeature, CloudProvider, EffortLevel, FallbackConfig, ModelConfig, ModelType,
-    OutputConfig, ProviderConfig,
+    BetaConfig, BetaFeature, BetaRegistry, CloudProvider, EffortLevel, FallbackConfig, ModelConfig,
+    ModelType, OutputConfig, ProviderConfig,
 };
 pub use common::{ContentSource, Index, IndexRegistry, Named, SourceType, ToolRestricted};
 pub use context::{
@@ -129,6 +133,11 @@ pub use plugins::{PluginDescriptor, PluginDiscovery, PluginError, PluginManager,
 #[cfg(feature = "cli-integration")]
 pub use subagents::{SubagentFrontmatter, SubagentIndexLoader};
 
+#[doc(hidden)]
+pub mod __private {
+    pub use async_trait::async_trait;
+}
+
 /// Error type for claude-agent operations.
 ///
 /// All errors include actionable context to help diagnose and resolve issues.
@@ -258,6 +267,10 @@ pub enum Error {
     #[error("Circuit breaker is open")]
     CircuitOpen,
 
+    /// Execution was cancelled through the agent's cancellation token.
+    #[error("Execution cancelled")]
+    Cancelled,
+
     /// Plugin system error.
     #[cfg(feature = "plugins")]
     #[error("Plugin error: {0}")]
@@ -314,7 +327,9 @@ impl Error {
                 ..
             } => ErrorCategory::Transient,
 
-            Error::Session(_) | Error::Mcp(_) | Error::Stream(_) => ErrorCategory::Stateful,
+            Error::Session(_) | Error::Mcp(_) | Error::Stream(_) | Error::Cancelled => {
+                ErrorCategory::Stateful
+            }
 
             Error::BudgetExceeded { .. }
             | Error::ContextOverflow { .. }
@@ -424,6 +439,10 @@ impl From<session::SessionError> for Error {
                 Error::Config(format!("Session expired: {}", id))
             }
             session::SessionError::Storage { message } => Error::Config(message),
+            session::SessionError::Conflict { id } => {
+                Error::Config(format!("Session was modified concurrently: {}", id))
+            }
+            err @ session::SessionError::AccessDenied { .. } => Error::Permission(err.to_string()),
             session::SessionError::Serialization(e) => Error::Json(e),
             session::SessionError::Compact { message } => Error::Config(message),
             session::SessionError::Context(e) => e.into(),
@@ -454,6 +473,9 @@ impl From<security::SecurityError> for Error {
             security::SecurityError::SymlinkDepthExceeded { path, max } => Error::Permission(
                 format!("Symlink depth exceeded (max {}): {}", max, path.display()),
             ),
+            security::SecurityError::ReadOnly(msg) => {
+                Error::Permission(format!("Read-only mode: {}", msg))
+            }
         }
     }
 }
@@ -548,6 +570,8 @@ mod tests {
 
         let auth_error = Error::auth("Invalid token");
         assert_eq!(auth_error.category(), ErrorCategory::Authorization);
+
+        assert_eq!(Error::Cancelled.category(), ErrorCategory::Stateful);
     }
 
     #[test]
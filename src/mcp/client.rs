@@ -5,31 +5,50 @@
 #[cfg(feature = "mcp")]
 use serde_json::Value;
 #[cfg(feature = "mcp")]
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 #[cfg(feature = "mcp")]
 use std::sync::Arc;
 #[cfg(feature = "mcp")]
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, broadcast};
 
 use super::{
     McpConnectionStatus, McpError, McpResourceDefinition, McpResult, McpServerConfig,
     McpServerState, McpToolDefinition, McpToolResult,
 };
 #[cfg(feature = "mcp")]
-use super::{McpContent, McpServerInfo};
+use super::{McpContent, McpLogLevel, McpNotification, McpServerInfo};
 
 #[cfg(feature = "mcp")]
 use rmcp::{
     RoleClient,
-    model::{CallToolRequestParam, ReadResourceRequestParam},
-    service::{RunningService, ServiceError, ServiceExt},
-    transport::{ConfigureCommandExt, TokioChildProcess},
+    handler::client::progress::ProgressDispatcher,
+    model::{
+        CallToolRequest, CallToolRequestParam, CallToolResult, CancelledNotification,
+        CancelledNotificationParam, ClientRequest, ReadResourceRequestParam, ServerResult,
+        SetLevelRequestParam, SubscribeRequestParam, UnsubscribeRequestParam,
+    },
+    service::{
+        ClientInitializeError, PeerRequestOptions, RunningService, ServiceError, ServiceExt,
+    },
+    transport::{
+        ConfigureCommandExt, IntoTransport, StreamableHttpClientTransport, TokioChildProcess,
+        auth::{AuthClient, AuthError, AuthorizationManager, OAuthState},
+        streamable_http_client::StreamableHttpClientTransportConfig,
+    },
 };
 #[cfg(feature = "mcp")]
 use tokio::process::Command;
 
 #[cfg(feature = "mcp")]
-type McpRunningService = RunningService<RoleClient, ()>;
+use crate::security::bash::EnvPolicy;
+
+#[cfg(feature = "mcp")]
+use super::handler::{McpClientHandler, SharedRoots};
+#[cfg(feature = "mcp")]
+use super::oauth::{self, McpTokenStore};
+
+#[cfg(feature = "mcp")]
+type McpRunningService = RunningService<RoleClient, McpClientHandler>;
 
 /// Convert rmcp ServiceError into our McpError, preserving JSON-RPC error codes.
 #[cfg(feature = "mcp")]
@@ -45,11 +64,93 @@ fn map_service_error(e: ServiceError, context: &str) -> McpError {
     }
 }
 
+#[cfg(feature = "mcp")]
+fn auth_failed(e: AuthError) -> McpError {
+    McpError::AuthFailed {
+        message: e.to_string(),
+    }
+}
+
+/// Run the MCP handshake over `transport`.
+#[cfg(feature = "mcp")]
+async fn handshake<T, E, A>(
+    name: &str,
+    handler: McpClientHandler,
+    transport: T,
+) -> McpResult<McpRunningService>
+where
+    T: IntoTransport<RoleClient, E, A>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    tokio::time::timeout(super::MCP_CONNECT_TIMEOUT, handler.serve(transport))
+        .await
+        .map_err(|_| McpError::ConnectionFailed {
+            message: format!(
+                "Connection timed out after {:?}",
+                super::MCP_CONNECT_TIMEOUT
+            ),
+        })?
+        .map_err(|e: ClientInitializeError| {
+            if oauth::is_auth_error(&e) {
+                McpError::AuthRequired {
+                    name: name.to_string(),
+                }
+            } else {
+                McpError::ConnectionFailed {
+                    message: format!("Failed to connect: {}", e),
+                }
+            }
+        })
+}
+
+#[cfg(feature = "mcp")]
+fn http_client(headers: &HashMap<String, String>) -> McpResult<rmcp_reqwest::Client> {
+    let mut header_map = rmcp_reqwest::header::HeaderMap::new();
+    for (key, value) in headers {
+        let name = rmcp_reqwest::header::HeaderName::from_bytes(key.as_bytes()).map_err(|e| {
+            McpError::ConnectionFailed {
+                message: format!("Invalid header name '{}': {}", key, e),
+            }
+        })?;
+        let value = rmcp_reqwest::header::HeaderValue::from_str(value).map_err(|e| {
+            McpError::ConnectionFailed {
+                message: format!("Invalid value for header '{}': {}", key, e),
+            }
+        })?;
+        header_map.insert(name, value);
+    }
+    rmcp_reqwest::Client::builder()
+        .default_headers(header_map)
+        .build()
+        .map_err(|e| McpError::ConnectionFailed {
+            message: format!("Failed to create HTTP client: {}", e),
+        })
+}
+
 pub struct McpClient {
     name: String,
     state: McpServerState,
     #[cfg(feature = "mcp")]
     service: Option<Arc<RwLock<McpRunningService>>>,
+    #[cfg(feature = "mcp")]
+    roots: SharedRoots,
+    #[cfg(feature = "mcp")]
+    notifications: broadcast::Sender<McpNotification>,
+    /// Resource URIs subscribed to, renewed on reconnect.
+    #[cfg(feature = "mcp")]
+    subscriptions: BTreeSet<String>,
+    #[cfg(feature = "mcp")]
+    progress: ProgressDispatcher,
+    /// Minimum level requested from servers with the logging capability.
+    #[cfg(feature = "mcp")]
+    log_level: Option<McpLogLevel>,
+    /// Sanitizes the environment of a stdio server; without one it
+    /// inherits the whole environment.
+    #[cfg(feature = "mcp")]
+    env_policy: Option<EnvPolicy>,
+    /// OAuth flow started by `start_authorization`, awaiting its callback.
+    #[cfg(feature = "mcp")]
+    authorization: Option<OAuthState>,
     #[cfg(not(feature = "mcp"))]
     _phantom: std::marker::PhantomData<()>,
 }
@@ -62,14 +163,83 @@ impl McpClient {
             state: McpServerState::new(name, config),
             #[cfg(feature = "mcp")]
             service: None,
+            #[cfg(feature = "mcp")]
+            roots: SharedRoots::default(),
+            #[cfg(feature = "mcp")]
+            notifications: broadcast::channel(super::MCP_NOTIFICATION_CAPACITY).0,
+            #[cfg(feature = "mcp")]
+            subscriptions: BTreeSet::new(),
+            #[cfg(feature = "mcp")]
+            progress: ProgressDispatcher::new(),
+            #[cfg(feature = "mcp")]
+            log_level: None,
+            #[cfg(feature = "mcp")]
+            env_policy: None,
+            #[cfg(feature = "mcp")]
+            authorization: None,
             #[cfg(not(feature = "mcp"))]
             _phantom: std::marker::PhantomData,
         }
     }
 
+    /// Share a root list with this client; see [`McpManager::set_roots`](super::McpManager::set_roots).
+    #[cfg(feature = "mcp")]
+    pub(crate) fn with_roots(mut self, roots: SharedRoots) -> Self {
+        self.roots = roots;
+        self
+    }
+
+    /// Forward server notifications to `sender` instead of a private channel.
+    #[cfg(feature = "mcp")]
+    pub(crate) fn with_notifications(mut self, sender: broadcast::Sender<McpNotification>) -> Self {
+        self.notifications = sender;
+        self
+    }
+
+    /// Ask the server to send log messages at `level` and above.
+    ///
+    /// Sent as `logging/setLevel` after each connect when the server
+    /// advertises the logging capability; otherwise the server decides.
+    #[cfg(feature = "mcp")]
+    pub fn with_log_level(mut self, level: McpLogLevel) -> Self {
+        self.log_level = Some(level);
+        self
+    }
+
+    /// Start a stdio server with the variables `policy` lets through, plus
+    /// the `env` of its config.
+    #[cfg(feature = "mcp")]
+    pub fn with_env_policy(mut self, policy: EnvPolicy) -> Self {
+        self.env_policy = Some(policy);
+        self
+    }
+
+    /// Receive notifications from this server.
+    #[cfg(feature = "mcp")]
+    pub fn notifications(&self) -> broadcast::Receiver<McpNotification> {
+        self.notifications.subscribe()
+    }
+
+    #[cfg(feature = "mcp")]
+    fn handler(&self) -> McpClientHandler {
+        McpClientHandler::new(
+            &self.name,
+            self.roots.clone(),
+            self.notifications.clone(),
+            self.progress.clone(),
+        )
+    }
+
+    /// Connect to the server.
+    ///
+    /// For an HTTP server that requires authorization this fails with
+    /// [`McpError::AuthRequired`] and leaves the client in
+    /// [`McpConnectionStatus::NeedsAuth`]; complete the flow with
+    /// [`start_authorization`](Self::start_authorization) and
+    /// [`complete_authorization`](Self::complete_authorization).
     #[cfg(feature = "mcp")]
     pub async fn connect(&mut self) -> McpResult<()> {
-        match &self.state.config {
+        let result = match &self.state.config {
             McpServerConfig::Stdio {
                 command, args, env, ..
             } => {
@@ -79,7 +249,14 @@ impl McpClient {
             McpServerConfig::Sse { url, headers } => {
                 self.connect_sse(url.clone(), headers.clone()).await
             }
+            McpServerConfig::Http { url, headers } => {
+                self.connect_http(url.clone(), headers.clone()).await
+            }
+        };
+        if let Err(McpError::AuthRequired { .. }) = result {
+            self.state.status = McpConnectionStatus::NeedsAuth;
         }
+        result
     }
 
     #[cfg(not(feature = "mcp"))]
@@ -96,10 +273,12 @@ impl McpClient {
         args: Vec<String>,
         env: HashMap<String, String>,
     ) -> McpResult<()> {
-        use tokio::time::timeout;
-
         let transport = TokioChildProcess::new(Command::new(&command).configure(|cmd| {
             cmd.args(&args);
+            if let Some(policy) = &self.env_policy {
+                cmd.env_clear();
+                cmd.envs(policy.sanitize_current());
+            }
             for (key, value) in &env {
                 cmd.env(key, value);
             }
@@ -108,18 +287,103 @@ impl McpClient {
             message: format!("Failed to create transport: {}", e),
         })?;
 
-        let service: McpRunningService = timeout(super::MCP_CONNECT_TIMEOUT, ().serve(transport))
+        let service = handshake(&self.name, self.handler(), transport).await?;
+        self.finish_connect(service).await
+    }
+
+    #[cfg(feature = "mcp")]
+    async fn connect_http(
+        &mut self,
+        url: String,
+        headers: HashMap<String, String>,
+    ) -> McpResult<()> {
+        let http = http_client(&headers)?;
+        let config = StreamableHttpClientTransportConfig::with_uri(url.as_str());
+        let store = McpTokenStore::new(&self.name, &url);
+
+        let has_token = store.credential().await.map_err(auth_failed)?.is_some();
+        let service = if has_token {
+            let mut manager = AuthorizationManager::new(url.as_str())
+                .await
+                .map_err(auth_failed)?;
+            manager.with_client(http.clone()).map_err(auth_failed)?;
+            manager.set_credential_store(store);
+            manager.initialize_from_store().await.map_err(auth_failed)?;
+            let client = AuthClient::new(http, manager);
+            handshake(
+                &self.name,
+                self.handler(),
+                StreamableHttpClientTransport::with_client(client, config),
+            )
+            .await?
+        } else {
+            handshake(
+                &self.name,
+                self.handler(),
+                StreamableHttpClientTransport::with_client(http, config),
+            )
+            .await?
+        };
+        self.finish_connect(service).await
+    }
+
+    /// Begin OAuth authorization for an HTTP server, returning the URL the
+    /// user must open.
+    ///
+    /// Discovers the server's authorization server, registers a client
+    /// dynamically and builds a PKCE authorization request. After the user
+    /// approves, the authorization server redirects to `redirect_uri` with
+    /// `code` and `state` query parameters; pass them to
+    /// [`complete_authorization`](Self::complete_authorization).
+    #[cfg(feature = "mcp")]
+    pub async fn start_authorization(&mut self, redirect_uri: &str) -> McpResult<String> {
+        let McpServerConfig::Http { url, headers } = &self.state.config else {
+            return Err(McpError::AuthFailed {
+                message: format!("Server '{}' does not use the HTTP transport", self.name),
+            });
+        };
+
+        let mut manager = AuthorizationManager::new(url.as_str())
             .await
-            .map_err(|_| McpError::ConnectionFailed {
-                message: format!(
-                    "Connection timed out after {:?}",
-                    super::MCP_CONNECT_TIMEOUT
-                ),
-            })?
-            .map_err(|e| McpError::ConnectionFailed {
-                message: format!("Failed to connect: {}", e),
+            .map_err(auth_failed)?;
+        manager
+            .with_client(http_client(headers)?)
+            .map_err(auth_failed)?;
+        manager.set_credential_store(McpTokenStore::new(&self.name, url));
+
+        let mut authorization = OAuthState::Unauthorized(manager);
+        authorization
+            .start_authorization(&[], redirect_uri, Some(oauth::CLIENT_NAME))
+            .await
+            .map_err(auth_failed)?;
+        let authorization_url = authorization
+            .get_authorization_url()
+            .await
+            .map_err(auth_failed)?;
+
+        self.authorization = Some(authorization);
+        Ok(authorization_url)
+    }
+
+    /// Exchange the authorization code from the redirect for tokens, store
+    /// them, and connect.
+    #[cfg(feature = "mcp")]
+    pub async fn complete_authorization(&mut self, code: &str, state: &str) -> McpResult<()> {
+        let mut authorization = self
+            .authorization
+            .take()
+            .ok_or_else(|| McpError::AuthFailed {
+                message: "No authorization in progress; call start_authorization first".to_string(),
             })?;
+        authorization
+            .handle_callback(code, state)
+            .await
+            .map_err(auth_failed)?;
+        self.connect().await
+    }
 
+    #[cfg(feature = "mcp")]
+    async fn finish_connect(&mut self, service: McpRunningService) -> McpResult<()> {
         if let Some(info) = service.peer_info() {
             let protocol_version = info.protocol_version.to_string();
 
@@ -168,6 +432,28 @@ impl McpClient {
                 .collect();
         }
 
+        for uri in &self.subscriptions {
+            if let Err(e) = service
+                .subscribe(SubscribeRequestParam { uri: uri.clone() })
+                .await
+            {
+                tracing::warn!(server = %self.name, %uri, error = %e, "Failed to renew resource subscription");
+            }
+        }
+
+        if let Some(level) = self.log_level
+            && service
+                .peer_info()
+                .is_some_and(|info| info.capabilities.logging.is_some())
+            && let Err(e) = service
+                .set_level(SetLevelRequestParam {
+                    level: level.into(),
+                })
+                .await
+        {
+            tracing::debug!(server = %self.name, error = %e, "Failed to set MCP log level");
+        }
+
         self.service = Some(Arc::new(RwLock::new(service)));
 
         Ok(())
@@ -177,7 +463,7 @@ impl McpClient {
     ///
     /// SSE transport is not yet supported by the rmcp crate.
     /// The MCP specification has moved to Streamable HTTP as the preferred
-    /// remote transport. Use stdio transport for local servers.
+    /// remote transport; use [`McpServerConfig::Http`] instead.
     #[cfg(feature = "mcp")]
     async fn connect_sse(
         &mut self,
@@ -187,8 +473,7 @@ impl McpClient {
         Err(McpError::Protocol {
             message: format!(
                 "SSE transport is not supported (url: {}). \
-                 Use stdio transport for local MCP servers, or wait for \
-                 Streamable HTTP transport support.",
+                 Use the \"http\" (Streamable HTTP) transport for remote MCP servers.",
                 url
             ),
         })
@@ -214,10 +499,13 @@ impl McpClient {
         &self.state.resources
     }
 
+    /// Call a tool on the server.
+    ///
+    /// The call times out after a period without a response, but every
+    /// progress notification from the server restarts that period, up to an
+    /// overall limit. Progress is forwarded as [`McpNotification::Progress`].
     #[cfg(feature = "mcp")]
     pub async fn call_tool(&self, name: &str, arguments: Value) -> McpResult<McpToolResult> {
-        use tokio::time::timeout;
-
         let service = self
             .service
             .as_ref()
@@ -226,18 +514,7 @@ impl McpClient {
             })?;
 
         let service = service.read().await;
-        let result = timeout(
-            super::MCP_CALL_TIMEOUT,
-            service.call_tool(CallToolRequestParam {
-                name: name.to_string().into(),
-                arguments: arguments.as_object().cloned(),
-            }),
-        )
-        .await
-        .map_err(|_| McpError::ToolError {
-            message: format!("Tool call timed out after {:?}", super::MCP_CALL_TIMEOUT),
-        })?
-        .map_err(|e| map_service_error(e, "Tool call failed"))?;
+        let result = self.request_tool_call(&service, name, arguments).await?;
 
         let content = result
             .content
@@ -302,6 +579,90 @@ impl McpClient {
         })
     }
 
+    #[cfg(not(feature = "mcp"))]
+    pub async fn start_authorization(&mut self, _redirect_uri: &str) -> McpResult<String> {
+        Err(McpError::Protocol {
+            message: "MCP feature not enabled".to_string(),
+        })
+    }
+
+    #[cfg(not(feature = "mcp"))]
+    pub async fn complete_authorization(&mut self, _code: &str, _state: &str) -> McpResult<()> {
+        Err(McpError::Protocol {
+            message: "MCP feature not enabled".to_string(),
+        })
+    }
+
+    #[cfg(feature = "mcp")]
+    async fn request_tool_call(
+        &self,
+        service: &McpRunningService,
+        name: &str,
+        arguments: Value,
+    ) -> McpResult<CallToolResult> {
+        use futures::StreamExt;
+        use tokio::time::{Instant, sleep_until};
+
+        let request = ClientRequest::CallToolRequest(CallToolRequest {
+            method: Default::default(),
+            params: CallToolRequestParam {
+                name: name.to_string().into(),
+                arguments: arguments.as_object().cloned(),
+            },
+            extensions: Default::default(),
+        });
+        let handle = service
+            .send_request_with_option(request, PeerRequestOptions::no_options())
+            .await
+            .map_err(|e| map_service_error(e, "Tool call failed"))?;
+        let mut progress = self.progress.subscribe(handle.progress_token.clone()).await;
+
+        let limit = Instant::now() + super::MCP_MAX_CALL_DURATION;
+        let mut deadline = Instant::now() + super::MCP_CALL_TIMEOUT;
+        let mut response = handle.rx;
+        let response = loop {
+            tokio::select! {
+                response = &mut response => {
+                    break response.unwrap_or(Err(ServiceError::TransportClosed));
+                }
+                Some(update) = progress.next() => {
+                    deadline = (Instant::now() + super::MCP_CALL_TIMEOUT).min(limit);
+                    let _ = self.notifications.send(McpNotification::Progress {
+                        server: self.name.clone(),
+                        tool: name.to_string(),
+                        progress: update.progress,
+                        total: update.total,
+                        message: update.message,
+                    });
+                }
+                _ = sleep_until(deadline) => {
+                    let cancel = CancelledNotification {
+                        method: Default::default(),
+                        params: CancelledNotificationParam {
+                            request_id: handle.id,
+                            reason: Some("request timeout".to_string()),
+                        },
+                        extensions: Default::default(),
+                    };
+                    let _ = handle.peer.send_notification(cancel.into()).await;
+                    return Err(McpError::ToolError {
+                        message: format!(
+                            "Tool call timed out after {:?} without progress",
+                            super::MCP_CALL_TIMEOUT
+                        ),
+                    });
+                }
+            }
+        };
+
+        match response.map_err(|e| map_service_error(e, "Tool call failed"))? {
+            ServerResult::CallToolResult(result) => Ok(result),
+            _ => Err(McpError::Protocol {
+                message: "Unexpected response to tools/call".to_string(),
+            }),
+        }
+    }
+
     #[cfg(not(feature = "mcp"))]
     pub async fn call_tool(
         &self,
@@ -372,6 +733,85 @@ impl McpClient {
         })
     }
 
+    /// Subscribe to change notifications for a resource.
+    ///
+    /// Updates arrive as [`McpNotification::ResourceUpdated`] on
+    /// [`notifications`](Self::notifications). The subscription is renewed
+    /// when the client reconnects.
+    #[cfg(feature = "mcp")]
+    pub async fn subscribe_resource(&mut self, uri: &str) -> McpResult<()> {
+        let service = self
+            .service
+            .as_ref()
+            .ok_or_else(|| McpError::ConnectionFailed {
+                message: "Not connected".to_string(),
+            })?;
+        let service = service.read().await;
+
+        let supported = service
+            .peer_info()
+            .and_then(|info| info.capabilities.resources.as_ref())
+            .and_then(|resources| resources.subscribe)
+            .unwrap_or(false);
+        if !supported {
+            return Err(McpError::Protocol {
+                message: format!(
+                    "Server '{}' does not support resource subscriptions",
+                    self.name
+                ),
+            });
+        }
+
+        service
+            .subscribe(SubscribeRequestParam {
+                uri: uri.to_string(),
+            })
+            .await
+            .map_err(|e| map_service_error(e, &format!("Subscribe failed for {}", uri)))?;
+        drop(service);
+
+        self.subscriptions.insert(uri.to_string());
+        Ok(())
+    }
+
+    #[cfg(feature = "mcp")]
+    pub async fn unsubscribe_resource(&mut self, uri: &str) -> McpResult<()> {
+        if !self.subscriptions.remove(uri) {
+            return Ok(());
+        }
+        let Some(service) = self.service.as_ref() else {
+            return Ok(());
+        };
+        service
+            .read()
+            .await
+            .unsubscribe(UnsubscribeRequestParam {
+                uri: uri.to_string(),
+            })
+            .await
+            .map_err(|e| map_service_error(e, &format!("Unsubscribe failed for {}", uri)))
+    }
+
+    /// Resource URIs this client is subscribed to.
+    #[cfg(feature = "mcp")]
+    pub fn subscriptions(&self) -> impl Iterator<Item = &str> {
+        self.subscriptions.iter().map(String::as_str)
+    }
+
+    /// Tell the server its roots changed so it re-requests `roots/list`.
+    #[cfg(feature = "mcp")]
+    pub async fn notify_roots_changed(&self) -> McpResult<()> {
+        let Some(service) = self.service.as_ref() else {
+            return Ok(());
+        };
+        service
+            .read()
+            .await
+            .notify_roots_list_changed()
+            .await
+            .map_err(|e| map_service_error(e, "Failed to notify roots change"))
+    }
+
     #[cfg(feature = "mcp")]
     pub async fn close(&mut self) -> McpResult<()> {
         if let Some(service_arc) = self.service.take() {
@@ -401,6 +841,20 @@ impl McpClient {
         Ok(())
     }
 
+    #[cfg(not(feature = "mcp"))]
+    pub async fn subscribe_resource(&mut self, _uri: &str) -> McpResult<()> {
+        Err(McpError::Protocol {
+            message: "MCP feature not enabled".to_string(),
+        })
+    }
+
+    #[cfg(not(feature = "mcp"))]
+    pub async fn unsubscribe_resource(&mut self, _uri: &str) -> McpResult<()> {
+        Err(McpError::Protocol {
+            message: "MCP feature not enabled".to_string(),
+        })
+    }
+
     #[cfg(not(feature = "mcp"))]
     pub async fn close(&mut self) -> McpResult<()> {
         self.state.status = McpConnectionStatus::Disconnected;
@@ -427,4 +881,59 @@ mod tests {
         assert_eq!(client.name(), "test");
         assert!(!client.is_connected());
     }
+
+    #[cfg(feature = "mcp")]
+    #[tokio::test]
+    async fn test_unauthorized_http_server_needs_auth() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = [0u8; 4096];
+                let _ = socket.read(&mut buf).await;
+                let _ = socket
+                    .write_all(
+                        b"HTTP/1.1 401 Unauthorized\r\n\
+                          WWW-Authenticate: Bearer resource_metadata=\"x\"\r\n\
+                          Content-Length: 0\r\nConnection: close\r\n\r\n",
+                    )
+                    .await;
+            }
+        });
+
+        let mut client = McpClient::new(
+            "remote",
+            McpServerConfig::Http {
+                url: format!("http://{}/mcp", addr),
+                headers: std::collections::HashMap::new(),
+            },
+        );
+        let result = client.connect().await;
+        assert!(matches!(result, Err(McpError::AuthRequired { .. })));
+        assert!(client.state().needs_auth());
+        assert!(!client.is_connected());
+
+        let result = client.complete_authorization("code", "state").await;
+        assert!(matches!(result, Err(McpError::AuthFailed { .. })));
+    }
+
+    #[cfg(feature = "mcp")]
+    #[tokio::test]
+    async fn test_authorization_requires_http_transport() {
+        let mut client = McpClient::new(
+            "local",
+            McpServerConfig::Stdio {
+                command: "echo".to_string(),
+                args: vec![],
+                env: std::collections::HashMap::new(),
+                cwd: None,
+            },
+        );
+        let result = client
+            .start_authorization("http://localhost/callback")
+            .await;
+        assert!(matches!(result, Err(McpError::AuthFailed { .. })));
+    }
 }
@@ -0,0 +1,227 @@
+//! Client-side handler for requests and notifications sent by MCP servers.
+
+use std::sync::Arc;
+
+use rmcp::{
+    ClientHandler, ErrorData, RoleClient,
+    handler::client::progress::ProgressDispatcher,
+    model::{
+        ClientCapabilities, ClientInfo, ListRootsResult, LoggingLevel,
+        LoggingMessageNotificationParam, ProgressNotificationParam,
+        ResourceUpdatedNotificationParam, Root,
+    },
+    service::{NotificationContext, RequestContext},
+};
+use tokio::sync::{RwLock, broadcast};
+
+use super::{McpLogLevel, McpNotification, McpRoot};
+
+/// Roots shared between a manager and the handlers of its clients.
+pub(crate) type SharedRoots = Arc<RwLock<Vec<McpRoot>>>;
+
+/// Answers server-initiated requests for one connection.
+///
+/// Advertises the roots capability and serves `roots/list` from the shared
+/// root list, so updates are visible to every connected server. Server
+/// notifications are forwarded as [`McpNotification`]s, except progress,
+/// which goes to the request that owns the progress token.
+#[derive(Clone)]
+pub(crate) struct McpClientHandler {
+    server: String,
+    roots: SharedRoots,
+    notifications: broadcast::Sender<McpNotification>,
+    progress: ProgressDispatcher,
+}
+
+impl McpClientHandler {
+    pub(crate) fn new(
+        server: impl Into<String>,
+        roots: SharedRoots,
+        notifications: broadcast::Sender<McpNotification>,
+        progress: ProgressDispatcher,
+    ) -> Self {
+        Self {
+            server: server.into(),
+            roots,
+            notifications,
+            progress,
+        }
+    }
+
+    fn notify(&self, notification: McpNotification) {
+        // No receivers just means nobody is listening.
+        let _ = self.notifications.send(notification);
+    }
+}
+
+impl ClientHandler for McpClientHandler {
+    async fn list_roots(
+        &self,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<ListRootsResult, ErrorData> {
+        let roots = self.roots.read().await;
+        Ok(ListRootsResult {
+            roots: roots
+                .iter()
+                .map(|root| Root {
+                    uri: root.uri.clone(),
+                    name: root.name.clone(),
+                })
+                .collect(),
+        })
+    }
+
+    async fn on_progress(
+        &self,
+        params: ProgressNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        self.progress.handle_notification(params).await;
+    }
+
+    async fn on_logging_message(
+        &self,
+        params: LoggingMessageNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        let notification = McpNotification::Log {
+            server: self.server.clone(),
+            level: params.level.into(),
+            logger: params.logger,
+            data: params.data,
+        };
+        trace_log(&notification);
+        self.notify(notification);
+    }
+
+    async fn on_resource_updated(
+        &self,
+        params: ResourceUpdatedNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        tracing::debug!(server = %self.server, uri = %params.uri, "MCP resource updated");
+        self.notify(McpNotification::ResourceUpdated {
+            server: self.server.clone(),
+            uri: params.uri,
+        });
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        ClientInfo {
+            capabilities: ClientCapabilities::builder()
+                .enable_roots()
+                .enable_roots_list_changed()
+                .build(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Route a server log message into `tracing` at the matching level.
+fn trace_log(notification: &McpNotification) {
+    let McpNotification::Log {
+        server,
+        level,
+        logger,
+        ..
+    } = notification
+    else {
+        return;
+    };
+    let message = notification.log_message().unwrap_or_default();
+    let logger = logger.as_deref().unwrap_or_default();
+    match level {
+        McpLogLevel::Debug => {
+            tracing::debug!(server = %server, logger, level = %level, "{}", message)
+        }
+        McpLogLevel::Info | McpLogLevel::Notice => {
+            tracing::info!(server = %server, logger, level = %level, "{}", message)
+        }
+        McpLogLevel::Warning => {
+            tracing::warn!(server = %server, logger, level = %level, "{}", message)
+        }
+        _ => tracing::error!(server = %server, logger, level = %level, "{}", message),
+    }
+}
+
+impl From<LoggingLevel> for McpLogLevel {
+    fn from(level: LoggingLevel) -> Self {
+        match level {
+            LoggingLevel::Debug => Self::Debug,
+            LoggingLevel::Info => Self::Info,
+            LoggingLevel::Notice => Self::Notice,
+            LoggingLevel::Warning => Self::Warning,
+            LoggingLevel::Error => Self::Error,
+            LoggingLevel::Critical => Self::Critical,
+            LoggingLevel::Alert => Self::Alert,
+            LoggingLevel::Emergency => Self::Emergency,
+        }
+    }
+}
+
+impl From<McpLogLevel> for LoggingLevel {
+    fn from(level: McpLogLevel) -> Self {
+        match level {
+            McpLogLevel::Debug => Self::Debug,
+            McpLogLevel::Info => Self::Info,
+            McpLogLevel::Notice => Self::Notice,
+            McpLogLevel::Warning => Self::Warning,
+            McpLogLevel::Error => Self::Error,
+            McpLogLevel::Critical => Self::Critical,
+            McpLogLevel::Alert => Self::Alert,
+            McpLogLevel::Emergency => Self::Emergency,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handler() -> (McpClientHandler, broadcast::Receiver<McpNotification>) {
+        let (tx, rx) = broadcast::channel(8);
+        (
+            McpClientHandler::new(
+                "files",
+                SharedRoots::default(),
+                tx,
+                ProgressDispatcher::new(),
+            ),
+            rx,
+        )
+    }
+
+    #[test]
+    fn test_advertises_roots_capability() {
+        let info = handler().0.get_info();
+        let roots = info.capabilities.roots.unwrap();
+        assert_eq!(roots.list_changed, Some(true));
+    }
+
+    #[test]
+    fn test_forwards_notifications() {
+        let (handler, mut rx) = handler();
+        let updated = McpNotification::ResourceUpdated {
+            server: "files".to_string(),
+            uri: "file:///notes.md".to_string(),
+        };
+        handler.notify(updated.clone());
+        assert_eq!(rx.try_recv().unwrap(), updated);
+
+        drop(rx);
+        handler.notify(updated);
+    }
+
+    #[test]
+    fn test_log_levels_roundtrip() {
+        for level in [
+            McpLogLevel::Debug,
+            McpLogLevel::Notice,
+            McpLogLevel::Warning,
+            McpLogLevel::Emergency,
+        ] {
+            assert_eq!(McpLogLevel::from(LoggingLevel::from(level)), level);
+        }
+        assert!(McpLogLevel::Error > McpLogLevel::Warning);
+    }
+}
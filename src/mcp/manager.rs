@@ -6,22 +6,35 @@ use std::collections::HashMap;
 use std::sync::Arc;
 #[cfg(feature = "mcp")]
 use tokio::sync::RwLock;
+use tokio::sync::broadcast;
 
+use super::reload::McpConfigDiff;
 use super::{
-    McpContent, McpError, McpResourceDefinition, McpResult, McpServerConfig, McpServerState,
-    McpToolDefinition, McpToolResult,
+    McpContent, McpError, McpNotification, McpResourceDefinition, McpResult, McpRoot,
+    McpServerConfig, McpServerState, McpToolDefinition, McpToolResult,
 };
 #[cfg(feature = "mcp")]
-use super::{ReconnectPolicy, make_mcp_name, parse_mcp_name};
+use super::{McpLogLevel, ReconnectPolicy, make_mcp_name, parse_mcp_name};
 
 #[cfg(feature = "mcp")]
 use super::client::McpClient;
+#[cfg(feature = "mcp")]
+use super::handler::SharedRoots;
+#[cfg(feature = "mcp")]
+use crate::security::bash::EnvPolicy;
 
 pub struct McpManager {
     #[cfg(feature = "mcp")]
     servers: Arc<RwLock<HashMap<String, McpClient>>>,
     #[cfg(feature = "mcp")]
     reconnect_policy: ReconnectPolicy,
+    #[cfg(feature = "mcp")]
+    log_level: Option<McpLogLevel>,
+    #[cfg(feature = "mcp")]
+    env_policy: Option<EnvPolicy>,
+    #[cfg(feature = "mcp")]
+    roots: SharedRoots,
+    notifications: broadcast::Sender<McpNotification>,
     #[cfg(not(feature = "mcp"))]
     _phantom: std::marker::PhantomData<()>,
 }
@@ -38,12 +51,17 @@ impl McpManager {
         Self {
             servers: Arc::new(RwLock::new(HashMap::new())),
             reconnect_policy: ReconnectPolicy::default(),
+            log_level: None,
+            env_policy: None,
+            roots: SharedRoots::default(),
+            notifications: broadcast::channel(super::MCP_NOTIFICATION_CAPACITY).0,
         }
     }
 
     #[cfg(not(feature = "mcp"))]
     pub fn new() -> Self {
         Self {
+            notifications: broadcast::channel(super::MCP_NOTIFICATION_CAPACITY).0,
             _phantom: std::marker::PhantomData,
         }
     }
@@ -54,6 +72,22 @@ impl McpManager {
         self
     }
 
+    /// Ask servers with the logging capability to send messages at `level`
+    /// and above. Without this, servers use their own default.
+    #[cfg(feature = "mcp")]
+    pub fn log_level(mut self, level: McpLogLevel) -> Self {
+        self.log_level = Some(level);
+        self
+    }
+
+    /// Sanitize the environment of stdio servers added from now on; see
+    /// [`McpClient::with_env_policy`].
+    #[cfg(feature = "mcp")]
+    pub fn env_policy(mut self, policy: EnvPolicy) -> Self {
+        self.env_policy = Some(policy);
+        self
+    }
+
     #[cfg(feature = "mcp")]
     pub async fn add_server(
         &self,
@@ -71,8 +105,23 @@ impl McpManager {
             }
         }
 
-        let mut client = McpClient::new(name.clone(), config);
-        client.connect().await?;
+        let mut client = McpClient::new(name.clone(), config)
+            .with_roots(self.roots.clone())
+            .with_notifications(self.notifications.clone());
+        if let Some(level) = self.log_level {
+            client = client.with_log_level(level);
+        }
+        if let Some(policy) = &self.env_policy {
+            client = client.with_env_policy(policy.clone());
+        }
+        match client.connect().await {
+            Ok(()) => {}
+            // Keep the server so the caller can authorize it later.
+            Err(McpError::AuthRequired { .. }) => {
+                tracing::warn!(server = %name, "MCP server requires authorization");
+            }
+            Err(e) => return Err(e),
+        }
 
         // Re-check after acquiring write lock to prevent race
         let mut servers = self.servers.write().await;
@@ -117,6 +166,78 @@ impl McpManager {
         })
     }
 
+    /// Apply a configuration change without restarting: disconnect removed
+    /// servers, reconnect changed ones with their new config and connect
+    /// added ones. Added servers that are already connected with the same
+    /// config are left alone.
+    ///
+    /// Every entry of the diff is attempted; failures are reported together.
+    #[cfg(feature = "mcp")]
+    pub async fn apply_config(
+        &self,
+        diff: &McpConfigDiff,
+        configs: &HashMap<String, McpServerConfig>,
+    ) -> McpResult<()> {
+        let mut failures = Vec::new();
+
+        for name in diff.removed.iter().chain(&diff.changed) {
+            match self.remove_server(name).await {
+                Ok(()) | Err(McpError::ServerNotFound { .. }) => {}
+                Err(e) => failures.push(format!("{}: {}", name, e)),
+            }
+        }
+
+        for name in diff.changed.iter().chain(&diff.added) {
+            let Some(config) = configs.get(name) else {
+                continue;
+            };
+            let current = self
+                .servers
+                .read()
+                .await
+                .get(name)
+                .map(|client| client.state().config.clone());
+            match current {
+                Some(current) if current == *config => continue,
+                Some(_) => {
+                    if let Err(e) = self.remove_server(name).await {
+                        failures.push(format!("{}: {}", name, e));
+                        continue;
+                    }
+                }
+                None => {}
+            }
+            if let Err(e) = self.add_server(name, config.clone()).await {
+                failures.push(format!("{}: {}", name, e));
+            }
+        }
+
+        if failures.is_empty() {
+            tracing::info!(
+                added = diff.added.len(),
+                removed = diff.removed.len(),
+                changed = diff.changed.len(),
+                "Applied MCP configuration"
+            );
+            Ok(())
+        } else {
+            Err(McpError::ConnectionFailed {
+                message: failures.join("; "),
+            })
+        }
+    }
+
+    #[cfg(not(feature = "mcp"))]
+    pub async fn apply_config(
+        &self,
+        _diff: &McpConfigDiff,
+        _configs: &std::collections::HashMap<String, McpServerConfig>,
+    ) -> McpResult<()> {
+        Err(McpError::Protocol {
+            message: "MCP feature not enabled".to_string(),
+        })
+    }
+
     #[cfg(feature = "mcp")]
     pub async fn list_servers(&self) -> Vec<String> {
         let servers = self.servers.read().await;
@@ -139,6 +260,113 @@ impl McpManager {
         None
     }
 
+    /// Receive notifications from all servers, such as resource updates.
+    ///
+    /// Each receiver sees notifications sent after it was created.
+    pub fn notifications(&self) -> broadcast::Receiver<McpNotification> {
+        self.notifications.subscribe()
+    }
+
+    /// Roots advertised to servers through the MCP roots capability.
+    #[cfg(feature = "mcp")]
+    pub async fn roots(&self) -> Vec<McpRoot> {
+        self.roots.read().await.clone()
+    }
+
+    #[cfg(not(feature = "mcp"))]
+    pub async fn roots(&self) -> Vec<McpRoot> {
+        Vec::new()
+    }
+
+    /// Replace the advertised roots and notify connected servers.
+    ///
+    /// Filesystem servers use roots to scope themselves, so this should
+    /// follow the agent's working directory and allowed paths.
+    #[cfg(feature = "mcp")]
+    pub async fn set_roots(&self, roots: Vec<McpRoot>) {
+        {
+            let mut current = self.roots.write().await;
+            if *current == roots {
+                return;
+            }
+            *current = roots;
+        }
+
+        let servers = self.servers.read().await;
+        for (name, client) in servers.iter() {
+            if let Err(e) = client.notify_roots_changed().await {
+                tracing::debug!(server = %name, error = %e, "Failed to notify roots change");
+            }
+        }
+    }
+
+    #[cfg(not(feature = "mcp"))]
+    pub async fn set_roots(&self, _roots: Vec<McpRoot>) {}
+
+    /// Servers waiting for OAuth authorization.
+    #[cfg(feature = "mcp")]
+    pub async fn servers_needing_auth(&self) -> Vec<String> {
+        let servers = self.servers.read().await;
+        servers
+            .iter()
+            .filter(|(_, client)| client.state().needs_auth())
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    #[cfg(not(feature = "mcp"))]
+    pub async fn servers_needing_auth(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Begin OAuth authorization for a server; see [`McpClient::start_authorization`].
+    #[cfg(feature = "mcp")]
+    pub async fn start_authorization(&self, name: &str, redirect_uri: &str) -> McpResult<String> {
+        let mut servers = self.servers.write().await;
+        let client = servers
+            .get_mut(name)
+            .ok_or_else(|| McpError::ServerNotFound {
+                name: name.to_string(),
+            })?;
+        client.start_authorization(redirect_uri).await
+    }
+
+    #[cfg(not(feature = "mcp"))]
+    pub async fn start_authorization(&self, _name: &str, _redirect_uri: &str) -> McpResult<String> {
+        Err(McpError::Protocol {
+            message: "MCP feature not enabled".to_string(),
+        })
+    }
+
+    /// Finish OAuth authorization with the redirect's `code` and `state`, then connect.
+    #[cfg(feature = "mcp")]
+    pub async fn complete_authorization(
+        &self,
+        name: &str,
+        code: &str,
+        state: &str,
+    ) -> McpResult<()> {
+        let mut servers = self.servers.write().await;
+        let client = servers
+            .get_mut(name)
+            .ok_or_else(|| McpError::ServerNotFound {
+                name: name.to_string(),
+            })?;
+        client.complete_authorization(code, state).await
+    }
+
+    #[cfg(not(feature = "mcp"))]
+    pub async fn complete_authorization(
+        &self,
+        _name: &str,
+        _code: &str,
+        _state: &str,
+    ) -> McpResult<()> {
+        Err(McpError::Protocol {
+            message: "MCP feature not enabled".to_string(),
+        })
+    }
+
     #[cfg(feature = "mcp")]
     pub async fn list_tools(&self) -> Vec<(String, McpToolDefinition)> {
         let servers = self.servers.read().await;
@@ -188,10 +416,19 @@ impl McpManager {
             return Ok(());
         }
 
+        // Retrying cannot help until the user authorizes the server
+        if client.state().needs_auth() {
+            return Err(McpError::AuthRequired {
+                name: server_name.to_string(),
+            });
+        }
+
         // Try to connect first, then apply backoff between retries
         for attempt in 0..self.reconnect_policy.max_retries {
-            if client.connect().await.is_ok() {
-                return Ok(());
+            match client.connect().await {
+                Ok(()) => return Ok(()),
+                Err(e @ McpError::AuthRequired { .. }) => return Err(e),
+                Err(_) => {}
             }
 
             // Only sleep between retries, not before first attempt
@@ -285,6 +522,45 @@ impl McpManager {
         })
     }
 
+    /// Subscribe to updates of a server's resource; see [`McpClient::subscribe_resource`].
+    #[cfg(feature = "mcp")]
+    pub async fn subscribe_resource(&self, server_name: &str, uri: &str) -> McpResult<()> {
+        self.ensure_connected(server_name).await?;
+
+        let mut servers = self.servers.write().await;
+        let client = servers
+            .get_mut(server_name)
+            .ok_or_else(|| McpError::ServerNotFound {
+                name: server_name.to_string(),
+            })?;
+        client.subscribe_resource(uri).await
+    }
+
+    #[cfg(not(feature = "mcp"))]
+    pub async fn subscribe_resource(&self, _server_name: &str, _uri: &str) -> McpResult<()> {
+        Err(McpError::Protocol {
+            message: "MCP feature not enabled".to_string(),
+        })
+    }
+
+    #[cfg(feature = "mcp")]
+    pub async fn unsubscribe_resource(&self, server_name: &str, uri: &str) -> McpResult<()> {
+        let mut servers = self.servers.write().await;
+        let client = servers
+            .get_mut(server_name)
+            .ok_or_else(|| McpError::ServerNotFound {
+                name: server_name.to_string(),
+            })?;
+        client.unsubscribe_resource(uri).await
+    }
+
+    #[cfg(not(feature = "mcp"))]
+    pub async fn unsubscribe_resource(&self, _server_name: &str, _uri: &str) -> McpResult<()> {
+        Err(McpError::Protocol {
+            message: "MCP feature not enabled".to_string(),
+        })
+    }
+
     #[cfg(feature = "mcp")]
     pub async fn close_all(&self) -> McpResult<()> {
         let mut servers = self.servers.write().await;
@@ -408,4 +684,25 @@ mod tests {
         let manager = McpManager::new().reconnect_policy(policy);
         assert!(manager.list_servers().await.is_empty());
     }
+
+    #[cfg(feature = "mcp")]
+    #[tokio::test]
+    async fn test_set_roots() {
+        let manager = McpManager::new();
+        assert!(manager.roots().await.is_empty());
+
+        let roots = vec![McpRoot::from_path("/workspace/project")];
+        manager.set_roots(roots.clone()).await;
+        assert_eq!(manager.roots().await, roots);
+    }
+
+    #[cfg(feature = "mcp")]
+    #[tokio::test]
+    async fn test_subscribe_resource_server_not_found() {
+        let manager = McpManager::new();
+        let result = manager
+            .subscribe_resource("nonexistent", "file:///notes.md")
+            .await;
+        assert!(matches!(result, Err(McpError::ServerNotFound { .. })));
+    }
 }
@@ -1,14 +1,22 @@
 //! MCP (Model Context Protocol) server integration.
 
 pub mod client;
+#[cfg(feature = "mcp")]
+mod handler;
 pub mod manager;
+#[cfg(feature = "mcp")]
+pub mod oauth;
+pub mod reload;
 pub mod resources;
 pub mod toolset;
 
 pub use client::McpClient;
 pub use manager::McpManager;
+#[cfg(feature = "mcp")]
+pub use oauth::McpTokenStore;
+pub use reload::{McpConfigDiff, McpConfigWatcher, McpWatchHandle};
 pub use resources::{ResourceManager, ResourceQuery};
-pub use toolset::{McpToolset, McpToolsetRegistry, ToolLoadConfig};
+pub use toolset::{McpToolFilter, McpToolset, McpToolsetRegistry, ToolLoadConfig};
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -22,11 +30,17 @@ pub(crate) const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2024-11-05", "2025-03
 pub(crate) const MCP_CONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
 #[cfg(feature = "mcp")]
 pub(crate) const MCP_CALL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+/// Upper bound for a tool call whose timeout keeps being extended by progress.
+pub(crate) const MCP_MAX_CALL_DURATION: std::time::Duration =
+    std::time::Duration::from_secs(30 * 60);
+/// Buffered notifications per receiver before the oldest are dropped.
+pub(crate) const MCP_NOTIFICATION_CAPACITY: usize = 256;
+
 #[cfg(feature = "mcp")]
 pub(crate) const MCP_RESOURCE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
 
 /// MCP server configuration
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum McpServerConfig {
     /// stdio transport - communicates with server via stdin/stdout
@@ -45,6 +59,36 @@ pub enum McpServerConfig {
         #[serde(default)]
         headers: HashMap<String, String>,
     },
+    /// Streamable HTTP transport for remote servers, with OAuth when required
+    Http {
+        url: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+}
+
+/// Parse CLI-style MCP server entries, where `type` defaults to `stdio`.
+///
+/// Entries using transports this crate does not support are skipped.
+pub(crate) fn parse_mcp_servers<'a>(
+    servers: impl IntoIterator<Item = (&'a String, &'a serde_json::Value)>,
+) -> impl Iterator<Item = (String, McpServerConfig)> {
+    servers.into_iter().filter_map(|(name, value)| {
+        let mut value = value.clone();
+        if let Some(obj) = value.as_object_mut()
+            && !obj.contains_key("type")
+            && obj.contains_key("command")
+        {
+            obj.insert("type".into(), serde_json::Value::from("stdio"));
+        }
+        match serde_json::from_value(value) {
+            Ok(config) => Some((name.clone(), config)),
+            Err(e) => {
+                tracing::debug!(server = %name, error = %e, "Skipping unsupported MCP server");
+                None
+            }
+        }
+    })
 }
 
 /// Reconnection policy with exponential backoff and jitter
@@ -96,6 +140,8 @@ pub enum McpConnectionStatus {
     Connecting,
     Connected,
     Disconnected,
+    /// The server requires OAuth authorization before it can be used.
+    NeedsAuth,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -108,6 +154,122 @@ pub struct McpServerInfo {
     pub protocol_version: String,
 }
 
+/// Severity of an MCP server log message (RFC 5424 levels, least severe first).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum McpLogLevel {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+impl std::fmt::Display for McpLogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Debug => "debug",
+            Self::Info => "info",
+            Self::Notice => "notice",
+            Self::Warning => "warning",
+            Self::Error => "error",
+            Self::Critical => "critical",
+            Self::Alert => "alert",
+            Self::Emergency => "emergency",
+        })
+    }
+}
+
+/// Notification received from a connected MCP server.
+///
+/// Log messages are also written to `tracing` with the server name as a field.
+#[derive(Clone, Debug, PartialEq)]
+pub enum McpNotification {
+    /// A subscribed resource changed; read it again for the new content.
+    ResourceUpdated { server: String, uri: String },
+    /// Progress reported by a server during a tool call.
+    Progress {
+        server: String,
+        tool: String,
+        progress: f64,
+        total: Option<f64>,
+        message: Option<String>,
+    },
+    /// A log message sent through the server's logging capability.
+    Log {
+        server: String,
+        level: McpLogLevel,
+        logger: Option<String>,
+        data: serde_json::Value,
+    },
+}
+
+impl McpNotification {
+    /// Text of a [`McpNotification::Log`]: string data as is, anything else as JSON.
+    pub fn log_message(&self) -> Option<String> {
+        match self {
+            Self::Log { data, .. } => Some(match data {
+                serde_json::Value::String(text) => text.clone(),
+                other => other.to_string(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Completion percentage (0-100) of a [`McpNotification::Progress`] with a known total.
+    pub fn percentage(&self) -> Option<f64> {
+        match self {
+            Self::Progress {
+                progress,
+                total: Some(total),
+                ..
+            } if *total > 0.0 => Some((progress / total * 100.0).clamp(0.0, 100.0)),
+            _ => None,
+        }
+    }
+}
+
+/// A filesystem root advertised to servers through the MCP roots capability.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct McpRoot {
+    /// `file://` URI of the root directory.
+    pub uri: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl McpRoot {
+    pub fn new(uri: impl Into<String>) -> Self {
+        Self {
+            uri: uri.into(),
+            name: None,
+        }
+    }
+
+    /// Root for a local directory; relative paths resolve against the current directory.
+    pub fn from_path(path: impl AsRef<std::path::Path>) -> Self {
+        let path = path.as_ref();
+        let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        let uri = url::Url::from_directory_path(&absolute)
+            .map(String::from)
+            .unwrap_or_else(|_| format!("file://{}", absolute.display()));
+        Self {
+            uri,
+            name: absolute
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned()),
+        }
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct McpToolDefinition {
@@ -154,6 +316,10 @@ impl McpServerState {
     pub fn is_connected(&self) -> bool {
         self.status == McpConnectionStatus::Connected
     }
+
+    pub fn needs_auth(&self) -> bool {
+        self.status == McpConnectionStatus::NeedsAuth
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -179,6 +345,12 @@ pub enum McpError {
     #[error("Resource not found: {uri}")]
     ResourceNotFound { uri: String },
 
+    #[error("Server '{name}' requires authorization")]
+    AuthRequired { name: String },
+
+    #[error("Authorization failed: {message}")]
+    AuthFailed { message: String },
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -239,6 +411,21 @@ impl McpToolResult {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_progress_percentage() {
+        let progress = |progress, total| McpNotification::Progress {
+            server: "build".to_string(),
+            tool: "compile".to_string(),
+            progress,
+            total,
+            message: None,
+        };
+        assert_eq!(progress(25.0, Some(50.0)).percentage(), Some(50.0));
+        assert_eq!(progress(80.0, Some(40.0)).percentage(), Some(100.0));
+        assert_eq!(progress(3.0, Some(0.0)).percentage(), None);
+        assert_eq!(progress(3.0, None).percentage(), None);
+    }
+
     #[test]
     fn test_parse_mcp_name() {
         assert_eq!(
@@ -292,6 +479,11 @@ mod tests {
         let json = serde_json::to_string(&config).unwrap();
         assert!(json.contains("stdio"));
         assert!(json.contains("npx"));
+
+        let config: McpServerConfig =
+            serde_json::from_str(r#"{"type": "http", "url": "https://mcp.example.com/mcp"}"#)
+                .unwrap();
+        assert!(matches!(config, McpServerConfig::Http { url, .. } if url.ends_with("/mcp")));
     }
 
     #[test]
@@ -324,4 +516,14 @@ mod tests {
         };
         assert_eq!(image.as_text(), None);
     }
+
+    #[test]
+    fn test_mcp_root_from_path() {
+        let root = McpRoot::from_path("/home/user/project");
+        assert_eq!(root.uri, "file:///home/user/project/");
+        assert_eq!(root.name.as_deref(), Some("project"));
+
+        let root = McpRoot::new("file:///tmp").name("scratch");
+        assert_eq!(root.name.as_deref(), Some("scratch"));
+    }
 }
@@ -0,0 +1,311 @@
+//! OAuth authorization for remote MCP servers.
+//!
+//! Implements the MCP authorization flow on top of rmcp's OAuth client:
+//! metadata discovery, dynamic client registration and PKCE are handled by
+//! rmcp, while tokens are persisted through [`McpTokenStore`].
+
+use async_trait::async_trait;
+use chrono::Utc;
+use rmcp::service::ClientInitializeError;
+use rmcp::transport::auth::{AuthError, CredentialStore, OAuthTokenResponse, StoredCredentials};
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::auth::McpOAuthCredential;
+
+/// Access tokens this close to expiry are refreshed before use.
+const REFRESH_MARGIN_SECS: i64 = 60;
+
+/// Client name sent during dynamic client registration.
+pub(crate) const CLIENT_NAME: &str = "claude-agent";
+
+/// Token storage for one remote MCP server.
+///
+/// With the `cli-integration` feature, tokens are kept in the Claude Code CLI
+/// credentials store (`~/.claude/.credentials.json`, or the Keychain on macOS)
+/// under `mcpOAuth`, so an authorization survives restarts and is shared with
+/// the CLI. Otherwise they live in memory for the lifetime of the store.
+///
+/// The store records absolute expiry times and reports tokens about to expire
+/// as already expired, which makes rmcp refresh them before the next request.
+pub struct McpTokenStore {
+    server_name: String,
+    server_url: String,
+    persistent: bool,
+    memory: RwLock<Option<McpOAuthCredential>>,
+}
+
+impl McpTokenStore {
+    pub fn new(server_name: impl Into<String>, server_url: impl Into<String>) -> Self {
+        Self {
+            server_name: server_name.into(),
+            server_url: server_url.into(),
+            persistent: cfg!(feature = "cli-integration"),
+            memory: RwLock::new(None),
+        }
+    }
+
+    /// A store that never touches the CLI credentials store.
+    pub fn in_memory(server_name: impl Into<String>, server_url: impl Into<String>) -> Self {
+        Self {
+            persistent: false,
+            ..Self::new(server_name, server_url)
+        }
+    }
+
+    fn key(&self) -> String {
+        format!("{}|{}", self.server_name, self.server_url)
+    }
+
+    /// Stored credential for this server, if any.
+    pub async fn credential(&self) -> Result<Option<McpOAuthCredential>, AuthError> {
+        #[cfg(feature = "cli-integration")]
+        if self.persistent {
+            let creds = crate::auth::storage::load_cli_credentials()
+                .await
+                .map_err(|e| AuthError::InternalError(e.to_string()))?;
+            return Ok(creds.and_then(|mut c| c.mcp_oauth.remove(&self.key())));
+        }
+        Ok(self.memory.read().await.clone())
+    }
+
+    async fn write(&self, credential: Option<McpOAuthCredential>) -> Result<(), AuthError> {
+        #[cfg(feature = "cli-integration")]
+        if self.persistent {
+            use crate::auth::storage::{load_cli_credentials, save_cli_credentials};
+
+            let mut creds = load_cli_credentials()
+                .await
+                .map_err(|e| AuthError::InternalError(e.to_string()))?
+                .unwrap_or_default();
+            match credential {
+                Some(credential) => creds.mcp_oauth.insert(self.key(), credential),
+                None => creds.mcp_oauth.remove(&self.key()),
+            };
+            return save_cli_credentials(&creds)
+                .await
+                .map_err(|e| AuthError::InternalError(e.to_string()));
+        }
+        *self.memory.write().await = credential;
+        Ok(())
+    }
+}
+
+/// Wire format of an OAuth token response (RFC 6749 section 5.1).
+#[derive(Serialize, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    token_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expires_in: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    refresh_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
+}
+
+fn to_token_response(credential: &McpOAuthCredential) -> Result<OAuthTokenResponse, AuthError> {
+    let expires_in = credential.expires_at.map(|at| {
+        let remaining = at - Utc::now().timestamp();
+        if remaining <= REFRESH_MARGIN_SECS {
+            0
+        } else {
+            remaining
+        }
+    });
+    let response = TokenResponse {
+        access_token: credential.access_token.expose_secret().to_string(),
+        token_type: "bearer".to_string(),
+        expires_in,
+        refresh_token: credential
+            .refresh_token
+            .as_ref()
+            .map(|t| t.expose_secret().to_string()),
+        scope: credential.scope.clone(),
+    };
+    serde_json::to_value(response)
+        .and_then(serde_json::from_value)
+        .map_err(|e| AuthError::InternalError(format!("Invalid stored token: {}", e)))
+}
+
+#[async_trait]
+impl CredentialStore for McpTokenStore {
+    async fn load(&self) -> Result<Option<StoredCredentials>, AuthError> {
+        let Some(credential) = self.credential().await? else {
+            return Ok(None);
+        };
+        Ok(Some(StoredCredentials {
+            client_id: credential.client_id.clone(),
+            token_response: Some(to_token_response(&credential)?),
+        }))
+    }
+
+    async fn save(&self, credentials: StoredCredentials) -> Result<(), AuthError> {
+        let Some(token) = credentials.token_response else {
+            return self.clear().await;
+        };
+        let token: TokenResponse = serde_json::to_value(token)
+            .and_then(serde_json::from_value)
+            .map_err(|e| AuthError::InternalError(format!("Invalid token response: {}", e)))?;
+
+        // Refresh responses may omit the refresh token; keep the one we have.
+        let refresh_token = match token.refresh_token {
+            Some(token) => Some(SecretString::from(token)),
+            None => self.credential().await?.and_then(|c| c.refresh_token),
+        };
+
+        self.write(Some(McpOAuthCredential {
+            server_name: self.server_name.clone(),
+            server_url: self.server_url.clone(),
+            client_id: credentials.client_id,
+            access_token: SecretString::from(token.access_token),
+            refresh_token,
+            expires_at: token.expires_in.map(|secs| Utc::now().timestamp() + secs),
+            scope: token.scope,
+        }))
+        .await
+    }
+
+    async fn clear(&self) -> Result<(), AuthError> {
+        self.write(None).await
+    }
+}
+
+/// Whether an rmcp error chain means the server wants (re)authorization.
+pub(crate) fn is_auth_error(error: &(dyn std::error::Error + 'static)) -> bool {
+    use rmcp::transport::streamable_http_client::StreamableHttpError;
+
+    let mut current = Some(error);
+    while let Some(e) = current {
+        if let Some(e) = e.downcast_ref::<StreamableHttpError<rmcp_reqwest::Error>>() {
+            match e {
+                StreamableHttpError::AuthRequired(_) => return true,
+                StreamableHttpError::Auth(auth) => return is_auth_required(auth),
+                StreamableHttpError::Client(e)
+                    if e.status() == Some(rmcp_reqwest::StatusCode::UNAUTHORIZED) =>
+                {
+                    return true;
+                }
+                _ => {}
+            }
+        }
+        if let Some(auth) = e.downcast_ref::<AuthError>() {
+            return is_auth_required(auth);
+        }
+        // rmcp does not expose the transport error as the error source.
+        if let Some(ClientInitializeError::TransportError { error, .. }) =
+            e.downcast_ref::<ClientInitializeError>()
+        {
+            current = Some(error.error.as_ref());
+            continue;
+        }
+        current = e.source();
+    }
+    false
+}
+
+fn is_auth_required(error: &AuthError) -> bool {
+    matches!(
+        error,
+        AuthError::AuthorizationRequired | AuthError::TokenRefreshFailed(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::transport::streamable_http_client::{AuthRequiredError, StreamableHttpError};
+
+    fn token(json: serde_json::Value) -> OAuthTokenResponse {
+        serde_json::from_value(json).unwrap()
+    }
+
+    fn stored(json: serde_json::Value) -> StoredCredentials {
+        StoredCredentials {
+            client_id: "client-1".to_string(),
+            token_response: Some(token(json)),
+        }
+    }
+
+    fn expires_in(store: &StoredCredentials) -> Option<u64> {
+        let value = serde_json::to_value(store.token_response.as_ref().unwrap()).unwrap();
+        value["expires_in"].as_u64()
+    }
+
+    #[tokio::test]
+    async fn test_token_store_roundtrip() {
+        let store = McpTokenStore::in_memory("linear", "https://mcp.linear.app/mcp");
+        assert!(store.load().await.unwrap().is_none());
+
+        store
+            .save(stored(serde_json::json!({
+                "access_token": "at-1",
+                "token_type": "Bearer",
+                "expires_in": 3600,
+                "refresh_token": "rt-1",
+                "scope": "read"
+            })))
+            .await
+            .unwrap();
+
+        let credential = store.credential().await.unwrap().unwrap();
+        assert_eq!(credential.server_name, "linear");
+        assert_eq!(credential.access_token.expose_secret(), "at-1");
+        assert_eq!(credential.scope.as_deref(), Some("read"));
+
+        let loaded = store.load().await.unwrap().unwrap();
+        assert_eq!(loaded.client_id, "client-1");
+        assert!(expires_in(&loaded).unwrap() > 3500);
+
+        store.clear().await.unwrap();
+        assert!(store.load().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_token_store_expiry_triggers_refresh() {
+        let store = McpTokenStore::in_memory("linear", "https://mcp.linear.app/mcp");
+        store
+            .save(stored(serde_json::json!({
+                "access_token": "at-1",
+                "token_type": "bearer",
+                "expires_in": 30,
+                "refresh_token": "rt-1"
+            })))
+            .await
+            .unwrap();
+
+        // Inside the refresh margin the token reports no remaining lifetime.
+        let loaded = store.load().await.unwrap().unwrap();
+        assert_eq!(expires_in(&loaded), Some(0));
+
+        // A refresh response without a refresh token keeps the previous one.
+        store
+            .save(stored(serde_json::json!({
+                "access_token": "at-2",
+                "token_type": "bearer",
+                "expires_in": 3600
+            })))
+            .await
+            .unwrap();
+        let credential = store.credential().await.unwrap().unwrap();
+        assert_eq!(credential.access_token.expose_secret(), "at-2");
+        assert_eq!(credential.refresh_token.unwrap().expose_secret(), "rt-1");
+    }
+
+    #[test]
+    fn test_is_auth_error() {
+        assert!(is_auth_error(&AuthError::AuthorizationRequired));
+        assert!(is_auth_error(&AuthError::TokenRefreshFailed(
+            "invalid_grant".to_string()
+        )));
+        assert!(!is_auth_error(&AuthError::NoAuthorizationSupport));
+
+        let error: StreamableHttpError<rmcp_reqwest::Error> =
+            StreamableHttpError::AuthRequired(AuthRequiredError {
+                www_authenticate_header: "Bearer".to_string(),
+            });
+        assert!(is_auth_error(&error));
+        assert!(!is_auth_error(&std::io::Error::other("connection refused")));
+    }
+}
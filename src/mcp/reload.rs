@@ -0,0 +1,349 @@
+//! Hot-reload of MCP server configuration files.
+//!
+//! An [`McpConfigWatcher`] polls `.mcp.json`-style files (and, with the
+//! `plugins` feature, plugin MCP configs) for changes. On every change the
+//! merged server set is compared with the previous one and the resulting
+//! [`McpConfigDiff`] is applied through [`McpManager::apply_config`]:
+//! new servers are connected, removed ones disconnected and servers whose
+//! command, args, env or URL changed are reconnected.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use serde_json::Value;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+#[cfg(feature = "plugins")]
+use super::McpError;
+use super::{McpManager, McpResult, McpServerConfig, parse_mcp_servers};
+#[cfg(feature = "plugins")]
+use crate::plugins::{PluginDescriptor, PluginLoader};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Servers added, removed and changed between two configurations.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct McpConfigDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl McpConfigDiff {
+    /// Compare two server sets. Names are sorted.
+    pub fn between(
+        old: &HashMap<String, McpServerConfig>,
+        new: &HashMap<String, McpServerConfig>,
+    ) -> Self {
+        let mut diff = Self::default();
+        for (name, config) in new {
+            match old.get(name) {
+                None => diff.added.push(name.clone()),
+                Some(previous) if previous != config => diff.changed.push(name.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed = old
+            .keys()
+            .filter(|name| !new.contains_key(*name))
+            .cloned()
+            .collect();
+        diff.added.sort();
+        diff.removed.sort();
+        diff.changed.sort();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[derive(Clone, Debug)]
+enum ConfigSource {
+    /// `{"mcpServers": {...}}`, where `type` defaults to `stdio`.
+    File(PathBuf),
+    #[cfg(feature = "plugins")]
+    Plugin(Box<PluginDescriptor>),
+}
+
+impl ConfigSource {
+    fn path(&self) -> PathBuf {
+        match self {
+            Self::File(path) => path.clone(),
+            #[cfg(feature = "plugins")]
+            Self::Plugin(plugin) => plugin.mcp_config_path(),
+        }
+    }
+
+    async fn load(&self) -> McpResult<HashMap<String, McpServerConfig>> {
+        match self {
+            Self::File(path) => load_file(path).await,
+            #[cfg(feature = "plugins")]
+            Self::Plugin(plugin) => PluginLoader::load_mcp_servers(plugin, plugin.name())
+                .await
+                .map_err(|e| McpError::Protocol {
+                    message: e.to_string(),
+                }),
+        }
+    }
+}
+
+async fn load_file(path: &Path) -> McpResult<HashMap<String, McpServerConfig>> {
+    let content = match tokio::fs::read_to_string(path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let file: Value = serde_json::from_str(&content)?;
+    Ok(file
+        .get("mcpServers")
+        .and_then(Value::as_object)
+        .map(|servers| parse_mcp_servers(servers).collect())
+        .unwrap_or_default())
+}
+
+/// Watches MCP config files and applies their changes to an [`McpManager`].
+///
+/// Only server connections are reloaded. An agent registers MCP tools once,
+/// when it is built, so a running agent never gains tools from added or
+/// changed servers, and its calls to removed servers fail with
+/// `ServerNotFound`. Build a new agent to pick up the new tool set.
+///
+/// ```rust,no_run
+/// # use std::sync::Arc;
+/// # use claude_agent::mcp::{McpConfigWatcher, McpManager};
+/// # async fn example() {
+/// let manager = Arc::new(McpManager::new());
+/// let watcher = McpConfigWatcher::new()
+///     .file(".mcp.json")
+///     .spawn(Arc::clone(&manager))
+///     .await;
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct McpConfigWatcher {
+    sources: Vec<ConfigSource>,
+    interval: Duration,
+}
+
+impl Default for McpConfigWatcher {
+    fn default() -> Self {
+        Self {
+            sources: Vec::new(),
+            interval: DEFAULT_INTERVAL,
+        }
+    }
+}
+
+impl McpConfigWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Watch a `.mcp.json`-style file. A missing file counts as no servers.
+    pub fn file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.sources.push(ConfigSource::File(path.into()));
+        self
+    }
+
+    /// Watch a plugin's `.mcp.json`; its servers are namespaced by plugin name.
+    #[cfg(feature = "plugins")]
+    pub fn plugin(mut self, plugin: PluginDescriptor) -> Self {
+        self.sources.push(ConfigSource::Plugin(Box::new(plugin)));
+        self
+    }
+
+    /// Time between checks for modified files (default: 2 seconds).
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Paths being watched.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.sources.iter().map(ConfigSource::path).collect()
+    }
+
+    /// Load and merge all sources. Later sources win on name conflicts.
+    pub async fn load(&self) -> McpResult<HashMap<String, McpServerConfig>> {
+        let mut servers = HashMap::new();
+        for source in &self.sources {
+            servers.extend(source.load().await?);
+        }
+        Ok(servers)
+    }
+
+    fn modified(&self) -> Vec<Option<SystemTime>> {
+        self.sources
+            .iter()
+            .map(|source| {
+                std::fs::metadata(source.path())
+                    .and_then(|m| m.modified())
+                    .ok()
+            })
+            .collect()
+    }
+
+    /// Connect the configured servers, then keep applying changes until the
+    /// returned handle is stopped or dropped.
+    ///
+    /// Servers already connected with the same config are kept. A file that
+    /// fails to parse, e.g. while it is being written, is skipped until it
+    /// changes again, leaving the running servers untouched.
+    pub async fn spawn(self, manager: Arc<McpManager>) -> McpWatchHandle {
+        let mut modified = self.modified();
+        let mut current = match self.load().await {
+            Ok(servers) => {
+                let diff = McpConfigDiff::between(&HashMap::new(), &servers);
+                if let Err(e) = manager.apply_config(&diff, &servers).await {
+                    tracing::warn!(error = %e, "Failed to apply MCP configuration");
+                }
+                servers
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to load MCP configuration");
+                HashMap::new()
+            }
+        };
+
+        let token = CancellationToken::new();
+        let stop = token.clone();
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = stop.cancelled() => break,
+                    _ = ticker.tick() => {}
+                }
+                let latest = self.modified();
+                if latest == modified {
+                    continue;
+                }
+                modified = latest;
+
+                let servers = match self.load().await {
+                    Ok(servers) => servers,
+                    Err(e) => {
+                        tracing::warn!(error = %e, "Ignoring invalid MCP configuration");
+                        continue;
+                    }
+                };
+                let diff = McpConfigDiff::between(&current, &servers);
+                if diff.is_empty() {
+                    continue;
+                }
+                tracing::info!(
+                    added = ?diff.added,
+                    removed = ?diff.removed,
+                    changed = ?diff.changed,
+                    "MCP configuration changed"
+                );
+                if let Err(e) = manager.apply_config(&diff, &servers).await {
+                    tracing::warn!(error = %e, "Failed to apply MCP configuration");
+                }
+                current = servers;
+            }
+        });
+        McpWatchHandle::new(token, task)
+    }
+}
+
+/// Running config watcher. Stops when dropped.
+pub struct McpWatchHandle {
+    token: CancellationToken,
+    task: Option<JoinHandle<()>>,
+}
+
+impl McpWatchHandle {
+    fn new(token: CancellationToken, task: JoinHandle<()>) -> Self {
+        Self {
+            token,
+            task: Some(task),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.task.as_ref().is_some_and(|t| !t.is_finished())
+    }
+
+    /// Stop watching and wait for the task to exit. Connected servers stay up.
+    pub async fn stop(mut self) {
+        self.token.cancel();
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
+    }
+}
+
+impl Drop for McpWatchHandle {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stdio(command: &str, args: &[&str]) -> McpServerConfig {
+        McpServerConfig::Stdio {
+            command: command.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            env: HashMap::new(),
+            cwd: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_between() {
+        let old = HashMap::from([
+            ("kept".to_string(), stdio("kept", &[])),
+            ("gone".to_string(), stdio("gone", &[])),
+            ("edited".to_string(), stdio("edited", &["--v1"])),
+        ]);
+        let new = HashMap::from([
+            ("kept".to_string(), stdio("kept", &[])),
+            ("edited".to_string(), stdio("edited", &["--v2"])),
+            ("fresh".to_string(), stdio("fresh", &[])),
+        ]);
+
+        let diff = McpConfigDiff::between(&old, &new);
+        assert_eq!(diff.added, vec!["fresh"]);
+        assert_eq!(diff.removed, vec!["gone"]);
+        assert_eq!(diff.changed, vec!["edited"]);
+        assert!(McpConfigDiff::between(&new, &new).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_load_merges_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join(".mcp.json");
+        let local = dir.path().join("local.json");
+        tokio::fs::write(
+            &project,
+            r#"{"mcpServers": {"fs": {"command": "fs-server"}, "db": {"command": "db-v1"}}}"#,
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(&local, r#"{"mcpServers": {"db": {"command": "db-v2"}}}"#)
+            .await
+            .unwrap();
+
+        let watcher = McpConfigWatcher::new()
+            .file(&project)
+            .file(&local)
+            .file(dir.path().join("missing.json"));
+        let servers = watcher.load().await.unwrap();
+        assert_eq!(servers.len(), 2);
+        assert_eq!(servers["fs"], stdio("fs-server", &[]));
+        assert_eq!(servers["db"], stdio("db-v2", &[]));
+
+        tokio::fs::write(&local, "{not json").await.unwrap();
+        assert!(watcher.load().await.is_err());
+    }
+}
@@ -2,7 +2,9 @@
 //!
 //! This module provides utilities for working with MCP resources.
 
-use super::{McpContent, McpError, McpManager, McpResourceDefinition, McpResult};
+use tokio::sync::broadcast;
+
+use super::{McpContent, McpError, McpManager, McpNotification, McpResourceDefinition, McpResult};
 
 /// Resource manager for handling MCP resource operations
 pub struct ResourceManager {
@@ -77,6 +79,21 @@ impl ResourceManager {
         }
     }
 
+    /// Subscribe to change notifications for a resource
+    pub async fn subscribe(&self, server_name: &str, uri: &str) -> McpResult<()> {
+        self.manager.subscribe_resource(server_name, uri).await
+    }
+
+    /// Stop receiving change notifications for a resource
+    pub async fn unsubscribe(&self, server_name: &str, uri: &str) -> McpResult<()> {
+        self.manager.unsubscribe_resource(server_name, uri).await
+    }
+
+    /// Receive [`McpNotification::ResourceUpdated`] for subscribed resources
+    pub fn changes(&self) -> broadcast::Receiver<McpNotification> {
+        self.manager.notifications()
+    }
+
     /// Find resources by URI pattern
     pub async fn find_by_pattern(&self, pattern: &str) -> Vec<(String, McpResourceDefinition)> {
         let all_resources = self.manager.list_resources().await;
@@ -4,6 +4,8 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
+use super::{McpToolDefinition, make_mcp_name, parse_mcp_name};
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ToolLoadConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -24,6 +26,42 @@ impl ToolLoadConfig {
     }
 }
 
+/// Which of a server's tools are registered, and under what names.
+///
+/// Patterns are globs over the server's own tool names (`search_*`). Deny
+/// patterns win over allow patterns; with no allow patterns every tool not
+/// denied is kept. `max_tools` caps the result in the server's listing order.
+#[derive(Debug, Clone, Default)]
+pub struct McpToolFilter {
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+    pub max_tools: Option<usize>,
+    /// Server tool name to the name it is exposed under.
+    pub renames: HashMap<String, String>,
+}
+
+impl McpToolFilter {
+    pub fn allows(&self, tool_name: &str) -> bool {
+        let matches = |pattern: &String| {
+            glob::Pattern::new(pattern)
+                .map(|p| p.matches(tool_name))
+                .unwrap_or_else(|_| pattern == tool_name)
+        };
+        if self.deny.iter().any(matches) {
+            return false;
+        }
+        self.allow.is_empty() || self.allow.iter().any(matches)
+    }
+
+    /// Name a server tool is exposed under, after renaming.
+    pub fn exposed_name<'a>(&'a self, tool_name: &'a str) -> &'a str {
+        self.renames
+            .get(tool_name)
+            .map(String::as_str)
+            .unwrap_or(tool_name)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpToolset {
     #[serde(rename = "type")]
@@ -31,8 +69,12 @@ pub struct McpToolset {
     pub mcp_server_name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub default_config: Option<ToolLoadConfig>,
+    /// Per-tool load configs, keyed by exposed tool name.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub configs: Option<HashMap<String, ToolLoadConfig>>,
+    /// Applied locally when registering tools; never sent to the API.
+    #[serde(skip)]
+    pub filter: McpToolFilter,
 }
 
 impl McpToolset {
@@ -42,9 +84,40 @@ impl McpToolset {
             mcp_server_name: server_name.into(),
             default_config: None,
             configs: None,
+            filter: McpToolFilter::default(),
         }
     }
 
+    /// Register only tools matching one of these glob patterns.
+    pub fn allow_tools(mut self, patterns: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.filter
+            .allow
+            .extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Never register tools matching these glob patterns.
+    pub fn deny_tools(mut self, patterns: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.filter
+            .deny
+            .extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Register at most `max` tools from this server.
+    pub fn max_tools(mut self, max: usize) -> Self {
+        self.filter.max_tools = Some(max);
+        self
+    }
+
+    /// Expose a server tool as `mcp__<server>__<alias>`.
+    ///
+    /// Permission rules, `keep_loaded` and `defer_tools` see the alias.
+    pub fn rename_tool(mut self, tool_name: impl Into<String>, alias: impl Into<String>) -> Self {
+        self.filter.renames.insert(tool_name.into(), alias.into());
+        self
+    }
+
     pub fn defer_all(mut self) -> Self {
         self.default_config = Some(ToolLoadConfig::deferred());
         self
@@ -115,6 +188,41 @@ impl McpToolsetRegistry {
     pub fn iter(&self) -> impl Iterator<Item = &McpToolset> {
         self.toolsets.values()
     }
+
+    /// Apply each server's [`McpToolFilter`] to `(qualified name, definition)`
+    /// pairs as returned by `McpManager::list_tools`.
+    ///
+    /// Returned names are the exposed qualified names; definitions keep the
+    /// server's tool name so calls still reach the right tool.
+    pub fn filter_tools(
+        &self,
+        tools: Vec<(String, McpToolDefinition)>,
+    ) -> Vec<(String, McpToolDefinition)> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        let mut selected = Vec::with_capacity(tools.len());
+
+        for (qualified_name, tool) in tools {
+            let Some((server, _)) = parse_mcp_name(&qualified_name) else {
+                continue;
+            };
+            let Some(filter) = self.toolsets.get(server).map(|ts| &ts.filter) else {
+                selected.push((qualified_name, tool));
+                continue;
+            };
+            if !filter.allows(&tool.name) {
+                continue;
+            }
+            let count = counts.entry(server.to_string()).or_default();
+            if filter.max_tools.is_some_and(|max| *count >= max) {
+                tracing::debug!(server, tool = %tool.name, "MCP tool limit reached, skipping");
+                continue;
+            }
+            *count += 1;
+            selected.push((make_mcp_name(server, filter.exposed_name(&tool.name)), tool));
+        }
+
+        selected
+    }
 }
 
 #[cfg(test)]
@@ -158,4 +266,72 @@ mod tests {
         assert!(!registry.is_deferred("server2", "any_tool"));
         assert!(!registry.is_deferred("server3", "any_tool"));
     }
+
+    fn tool(name: &str) -> (String, McpToolDefinition) {
+        (
+            make_mcp_name("github", name),
+            McpToolDefinition {
+                name: name.to_string(),
+                description: String::new(),
+                input_schema: serde_json::json!({"type": "object"}),
+            },
+        )
+    }
+
+    #[test]
+    fn test_tool_filter_allow_deny() {
+        let filter = McpToolset::new("github")
+            .allow_tools(["search_*", "get_issue"])
+            .deny_tools(["search_code"])
+            .filter;
+
+        assert!(filter.allows("search_issues"));
+        assert!(filter.allows("get_issue"));
+        assert!(!filter.allows("search_code"));
+        assert!(!filter.allows("delete_repo"));
+        assert!(McpToolFilter::default().allows("anything"));
+    }
+
+    #[test]
+    fn test_filter_tools_limit_and_rename() {
+        let mut registry = McpToolsetRegistry::new();
+        registry.register(
+            McpToolset::new("github")
+                .deny_tools(["delete_*"])
+                .max_tools(2)
+                .rename_tool("search_issues", "issues"),
+        );
+
+        let mut tools: Vec<_> = ["search_issues", "delete_repo", "get_issue", "list_prs"]
+            .into_iter()
+            .map(tool)
+            .collect();
+        tools.push((
+            make_mcp_name("slack", "post"),
+            McpToolDefinition {
+                name: "post".to_string(),
+                description: String::new(),
+                input_schema: serde_json::json!({}),
+            },
+        ));
+
+        let selected = registry.filter_tools(tools);
+        let names: Vec<_> = selected.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "mcp__github__issues",
+                "mcp__github__get_issue",
+                "mcp__slack__post"
+            ]
+        );
+        assert_eq!(selected[0].1.name, "search_issues");
+    }
+
+    #[test]
+    fn test_filter_not_serialized() {
+        let toolset = McpToolset::new("github").deny_tools(["delete_*"]);
+        let json = serde_json::to_value(&toolset).unwrap();
+        assert!(json.get("filter").is_none());
+    }
 }
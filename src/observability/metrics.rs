@@ -9,6 +9,7 @@ use std::time::Duration;
 use rust_decimal::Decimal;
 
 use crate::budget::COST_SCALE_FACTOR;
+use crate::security::sandbox::SandboxViolation;
 
 #[cfg(feature = "otel")]
 use super::otel::{OtelConfig, OtelMetricsBridge, SERVICE_NAME_DEFAULT};
@@ -164,6 +165,7 @@ pub struct MetricsRegistry {
     pub active_sessions: Gauge,
     pub request_latency_ms: Histogram,
     pub cost_total_micros: Counter,
+    pub sandbox_violations: Counter,
     #[cfg(feature = "otel")]
     otel_bridge: Option<OtelMetricsBridge>,
 }
@@ -183,6 +185,7 @@ impl MetricsRegistry {
             active_sessions: Gauge::new(),
             request_latency_ms: Histogram::default_latency(),
             cost_total_micros: Counter::new(),
+            sandbox_violations: Counter::new(),
             #[cfg(feature = "otel")]
             otel_bridge: None,
         }
@@ -207,6 +210,7 @@ impl MetricsRegistry {
             active_sessions: Gauge::new(),
             request_latency_ms: Histogram::default_latency(),
             cost_total_micros: Counter::new(),
+            sandbox_violations: Counter::new(),
             otel_bridge: Some(bridge),
         }
     }
@@ -268,6 +272,17 @@ impl MetricsRegistry {
         }
     }
 
+    pub fn record_sandbox_violation(&self, violation: &SandboxViolation) {
+        self.sandbox_violations.inc();
+
+        #[cfg(feature = "otel")]
+        if let Some(ref bridge) = self.otel_bridge {
+            bridge.record_sandbox_violation(violation.kind.as_str());
+        }
+        #[cfg(not(feature = "otel"))]
+        let _ = violation;
+    }
+
     pub fn record_cost(&self, cost_usd: Decimal) {
         let scaled = cost_usd * COST_SCALE_FACTOR;
         let micros = scaled
@@ -308,6 +323,7 @@ pub struct MetricsSummary {
     pub cache_creation_tokens: u64,
     pub total_tool_calls: u64,
     pub failed_tool_calls: u64,
+    pub sandbox_violations: u64,
     pub total_cost_usd: Decimal,
     pub avg_latency_ms: f64,
 }
@@ -331,6 +347,7 @@ impl MetricsSummary {
             cache_creation_tokens: registry.cache_creation_tokens.get(),
             total_tool_calls: registry.tool_calls_total.get(),
             failed_tool_calls: registry.tool_errors.get(),
+            sandbox_violations: registry.sandbox_violations.get(),
             total_cost_usd: registry.total_cost_usd(),
             avg_latency_ms: avg_latency,
         }
@@ -385,11 +402,17 @@ mod tests {
         registry.record_tool_call(true);
         registry.record_cost(dec!(0.001));
         registry.record_request_end(true, 250.0);
+        registry.record_sandbox_violation(&SandboxViolation::new(
+            crate::security::sandbox::ViolationKind::Network,
+            "curl evil.io",
+            "evil.io:443",
+        ));
 
         let metrics = MetricsSummary::from_registry(&registry);
         assert_eq!(metrics.total_requests, 1);
         assert_eq!(metrics.total_input_tokens, 100);
         assert_eq!(metrics.total_output_tokens, 50);
         assert_eq!(metrics.total_tool_calls, 1);
+        assert_eq!(metrics.sandbox_violations, 1);
     }
 }
@@ -324,6 +324,7 @@ pub struct OtelMetricsBridge {
     active_sessions: opentelemetry::metrics::UpDownCounter<i64>,
     request_latency: opentelemetry::metrics::Histogram<f64>,
     cost_total: opentelemetry::metrics::Counter<f64>,
+    sandbox_violations: opentelemetry::metrics::Counter<u64>,
 }
 
 impl OtelMetricsBridge {
@@ -379,6 +380,10 @@ impl OtelMetricsBridge {
                 .with_description("Total cost in USD")
                 .with_unit("USD")
                 .build(),
+            sandbox_violations: meter
+                .u64_counter("agent.sandbox.violations")
+                .with_description("Operations denied by the sandbox, by kind")
+                .build(),
         }
     }
 
@@ -414,6 +419,11 @@ impl OtelMetricsBridge {
         }
     }
 
+    pub fn record_sandbox_violation(&self, kind: &'static str) {
+        self.sandbox_violations
+            .add(1, &[KeyValue::new("kind", kind)]);
+    }
+
     pub fn record_cost(&self, cost_usd: Decimal) {
         // Convert Decimal to f64 at OpenTelemetry boundary
         let cost_f64 = cost_usd.to_f64().unwrap_or(0.0);
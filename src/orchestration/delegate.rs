@@ -0,0 +1,162 @@
+//! Tool for supervisor agents to hand tasks to orchestrated workers.
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{OrchestrationRun, Shared};
+use crate::agent::Agent;
+use crate::tools::{ExecutionContext, SchemaTool};
+use crate::types::ToolResult;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct DelegateInput {
+    /// Name of the agent to hand the task to
+    pub agent: String,
+    /// Complete, self-contained description of the task
+    pub task: String,
+}
+
+/// Runs a task on another agent of the orchestration and returns its answer.
+///
+/// Created with [`Orchestration::delegate_tool`](super::Orchestration::delegate_tool).
+/// Clones share the record of delegated runs.
+#[derive(Clone)]
+pub struct DelegateTool {
+    shared: Arc<Shared>,
+    workers: Vec<(String, Arc<Agent>)>,
+    /// Runs started through this tool, drained by
+    /// [`Orchestration::supervise`](super::Orchestration::supervise).
+    delegated: Arc<Mutex<Vec<OrchestrationRun>>>,
+}
+
+impl DelegateTool {
+    pub(super) fn new(shared: Arc<Shared>, workers: Vec<(String, Arc<Agent>)>) -> Self {
+        Self {
+            shared,
+            workers,
+            delegated: Arc::default(),
+        }
+    }
+
+    pub(super) fn take_runs(&self) -> Vec<OrchestrationRun> {
+        std::mem::take(&mut *self.delegated.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+#[async_trait]
+impl SchemaTool for DelegateTool {
+    type Input = DelegateInput;
+
+    const NAME: &'static str = "Delegate";
+    const DESCRIPTION: &'static str = "Hand a task to another agent and wait for its answer.";
+
+    fn custom_description(&self) -> Option<String> {
+        let names: Vec<_> = self.workers.iter().map(|(n, _)| n.as_str()).collect();
+        Some(format!(
+            "{} The agent does not see this conversation, so include all context it needs.\n\nAvailable agents: {}",
+            Self::DESCRIPTION,
+            names.join(", ")
+        ))
+    }
+
+    async fn handle(&self, input: DelegateInput, _context: &ExecutionContext) -> ToolResult {
+        let Some((name, agent)) = self.workers.iter().find(|(n, _)| *n == input.agent) else {
+            return ToolResult::error(format!("Unknown agent: {}", input.agent));
+        };
+
+        match self.shared.run(name, agent, &input.task).await {
+            Ok(run) => {
+                let output = run.result.text().to_string();
+                self.delegated
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .push(run);
+                ToolResult::success(output)
+            }
+            Err(e) => ToolResult::error(format!("Agent {name} failed: {e}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Orchestration;
+    use crate::agent::Agent;
+    use crate::agent::tests::helpers::{message_response, mock_client};
+    use crate::hooks::HookManager;
+    use crate::tools::{ExecutionContext, ToolRegistry};
+    use std::sync::Arc;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer};
+
+    #[tokio::test]
+    async fn test_supervisor_delegates_to_worker() {
+        let worker = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("count the files"))
+            .respond_with(message_response(
+                serde_json::json!([{"type": "text", "text": "42 files"}]),
+                "end_turn",
+            ))
+            .mount(&worker)
+            .await;
+
+        let supervisor = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .respond_with(message_response(
+                serde_json::json!([{
+                    "type": "tool_use",
+                    "id": "toolu_1",
+                    "name": "Delegate",
+                    "input": {"agent": "counter", "task": "count the files"}
+                }]),
+                "tool_use",
+            ))
+            .up_to_n_times(1)
+            .mount(&supervisor)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("42 files"))
+            .respond_with(message_response(
+                serde_json::json!([{"type": "text", "text": "There are 42 files."}]),
+                "end_turn",
+            ))
+            .mount(&supervisor)
+            .await;
+
+        let team = Orchestration::builder()
+            .agent(
+                "counter",
+                Agent::new(mock_client(&worker), crate::AgentConfig::default()),
+            )
+            .build()
+            .await
+            .unwrap();
+
+        let delegate = team.delegate_tool();
+        let mut tools = ToolRegistry::from_context(ExecutionContext::permissive());
+        tools.register(Arc::new(delegate.clone()));
+        let boss = Agent::from_parts(
+            Arc::new(mock_client(&supervisor)),
+            Arc::new(crate::AgentConfig::default()),
+            Arc::new(tools),
+            Arc::new(HookManager::new()),
+            None,
+        );
+
+        let result = team
+            .supervise(boss, &delegate, "How many files?")
+            .await
+            .unwrap();
+
+        assert_eq!(result.output(), "There are 42 files.");
+        let agents: Vec<_> = result.runs.iter().map(|r| r.agent.as_str()).collect();
+        assert_eq!(agents, vec!["counter", "supervisor"]);
+        assert!(delegate.take_runs().is_empty());
+    }
+}
@@ -0,0 +1,384 @@
+//! Message passing between running agents.
+//!
+//! A [`Mailbox`] routes [`AgentMessage`]s by session ID. Delivered messages are
+//! injected into the recipient's conversation at its next iteration boundary
+//! (see [`Agent::interject`]); an agent that is idle sees them on its next run.
+//! Agents talk through [`SendMessageTool`]: a request blocks until the recipient
+//! answers with `in_reply_to`, and the answer comes back as the tool result.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::agent::Agent;
+use crate::common::clock;
+use crate::session::{SessionId, ToolState};
+use crate::tools::{ExecutionContext, SchemaTool};
+use crate::types::ToolResult;
+
+/// Default time a sender waits for a reply.
+pub const DEFAULT_REPLY_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// A structured message from one agent session to another.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentMessage {
+    pub id: Uuid,
+    pub from: SessionId,
+    pub to: SessionId,
+    pub content: Value,
+    /// Set when this message answers an earlier one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub in_reply_to: Option<Uuid>,
+    /// Whether the sender is waiting for a reply.
+    #[serde(default)]
+    pub expects_reply: bool,
+    pub sent_at: DateTime<Utc>,
+}
+
+impl AgentMessage {
+    pub fn new(from: SessionId, to: SessionId, content: impl Into<Value>) -> Self {
+        Self {
+            id: clock::new_uuid(),
+            from,
+            to,
+            content: content.into(),
+            in_reply_to: None,
+            expects_reply: false,
+            sent_at: clock::now_utc(),
+        }
+    }
+
+    /// A reply to this message, addressed back to its sender.
+    pub fn reply(&self, content: impl Into<Value>) -> Self {
+        Self {
+            in_reply_to: Some(self.id),
+            ..Self::new(self.to, self.from, content)
+        }
+    }
+
+    fn content_text(&self) -> String {
+        match &self.content {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        }
+    }
+}
+
+struct Mailslot {
+    name: String,
+    state: ToolState,
+}
+
+#[derive(Default)]
+struct MailboxInner {
+    slots: RwLock<HashMap<SessionId, Mailslot>>,
+    waiters: Mutex<HashMap<Uuid, oneshot::Sender<AgentMessage>>>,
+}
+
+/// Routes messages between registered agents. Clones share the same registry.
+#[derive(Clone)]
+pub struct Mailbox {
+    inner: Arc<MailboxInner>,
+    reply_timeout: Duration,
+}
+
+impl Default for Mailbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Mailbox {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::default(),
+            reply_timeout: DEFAULT_REPLY_TIMEOUT,
+        }
+    }
+
+    pub fn reply_timeout(mut self, timeout: Duration) -> Self {
+        self.reply_timeout = timeout;
+        self
+    }
+
+    /// Register an agent under `name`, which must match the name its
+    /// [`SendMessageTool`] was created with.
+    pub fn register(&self, name: impl Into<String>, agent: &Agent) -> SessionId {
+        let session_id = agent.state().session_id();
+        self.write_slots().insert(
+            session_id,
+            Mailslot {
+                name: name.into(),
+                state: agent.state().clone(),
+            },
+        );
+        session_id
+    }
+
+    pub fn unregister(&self, session_id: &SessionId) -> bool {
+        self.write_slots().remove(session_id).is_some()
+    }
+
+    /// Look up a registered agent by name or session ID.
+    pub fn resolve(&self, name_or_id: &str) -> Option<SessionId> {
+        let parsed = SessionId::parse(name_or_id);
+        let slots = self.inner.slots.read().unwrap_or_else(|e| e.into_inner());
+        slots
+            .iter()
+            .find(|(id, slot)| slot.name == name_or_id || parsed.as_ref() == Some(*id))
+            .map(|(id, _)| *id)
+    }
+
+    pub fn name_of(&self, session_id: &SessionId) -> Option<String> {
+        let slots = self.inner.slots.read().unwrap_or_else(|e| e.into_inner());
+        slots.get(session_id).map(|slot| slot.name.clone())
+    }
+
+    /// Deliver a message without waiting for an answer.
+    ///
+    /// Replies complete the matching pending [`request`](Self::request) instead of
+    /// being injected into the recipient's conversation.
+    pub async fn send(&self, message: AgentMessage) -> crate::Result<()> {
+        if let Some(id) = message.in_reply_to {
+            let waiter = self.lock_waiters().remove(&id);
+            if let Some(waiter) = waiter {
+                let _ = waiter.send(message);
+                return Ok(());
+            }
+        }
+
+        let (state, from) = {
+            let slots = self.inner.slots.read().unwrap_or_else(|e| e.into_inner());
+            let slot = slots.get(&message.to).ok_or_else(|| {
+                crate::Error::Session(format!("Unknown message recipient: {}", message.to))
+            })?;
+            let from = slots
+                .get(&message.from)
+                .map(|s| s.name.clone())
+                .unwrap_or_else(|| message.from.to_string());
+            (slot.state.clone(), from)
+        };
+
+        state
+            .interject(format_delivery(&message, &from))
+            .await
+            .map_err(|e| crate::Error::Session(format!("Queue full: {}", e)))?;
+        Ok(())
+    }
+
+    /// Deliver a message and wait for the recipient's reply.
+    pub async fn request(&self, mut message: AgentMessage) -> crate::Result<AgentMessage> {
+        message.expects_reply = true;
+        let id = message.id;
+        let (tx, rx) = oneshot::channel();
+        self.lock_waiters().insert(id, tx);
+
+        if let Err(e) = self.send(message).await {
+            self.lock_waiters().remove(&id);
+            return Err(e);
+        }
+
+        match tokio::time::timeout(self.reply_timeout, rx).await {
+            Ok(Ok(reply)) => Ok(reply),
+            Ok(Err(_)) => Err(crate::Error::Session("Mailbox closed".into())),
+            Err(_) => {
+                self.lock_waiters().remove(&id);
+                Err(crate::Error::Timeout(self.reply_timeout))
+            }
+        }
+    }
+
+    /// A `SendMessage` tool for the agent that will be registered as `name`.
+    pub fn tool(&self, name: impl Into<String>) -> SendMessageTool {
+        SendMessageTool {
+            mailbox: self.clone(),
+            name: name.into(),
+        }
+    }
+
+    fn write_slots(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<SessionId, Mailslot>> {
+        self.inner.slots.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_waiters(
+        &self,
+    ) -> std::sync::MutexGuard<'_, HashMap<Uuid, oneshot::Sender<AgentMessage>>> {
+        self.inner.waiters.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn format_delivery(message: &AgentMessage, from: &str) -> String {
+    let mut text = format!(
+        "<agent-message id=\"{}\" from=\"{}\">\n{}\n</agent-message>",
+        message.id,
+        from,
+        message.content_text()
+    );
+    if message.expects_reply {
+        text.push_str(&format!(
+            "\n{from} is waiting for your answer. Reply with the SendMessage tool, \
+             setting in_reply_to to \"{}\".",
+            message.id
+        ));
+    }
+    text
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct SendMessageInput {
+    /// Name or session ID of the receiving agent
+    pub to: String,
+    /// Message body: text or any JSON value
+    pub content: Value,
+    /// ID of the message being answered, if this is a reply
+    #[serde(default)]
+    pub in_reply_to: Option<String>,
+    /// Wait for the recipient's reply and return it (default true, ignored for replies)
+    #[serde(default)]
+    pub await_reply: Option<bool>,
+}
+
+/// Lets an agent message other agents registered in a [`Mailbox`].
+pub struct SendMessageTool {
+    mailbox: Mailbox,
+    name: String,
+}
+
+#[async_trait]
+impl SchemaTool for SendMessageTool {
+    type Input = SendMessageInput;
+
+    const NAME: &'static str = "SendMessage";
+    const DESCRIPTION: &'static str = "Send a message to another agent. By default waits for \
+        and returns its reply. To answer an <agent-message>, set in_reply_to to its id.";
+
+    async fn handle(&self, input: SendMessageInput, _context: &ExecutionContext) -> ToolResult {
+        let Some(from) = self.mailbox.resolve(&self.name) else {
+            return ToolResult::error(format!("Agent {} is not registered", self.name));
+        };
+        let Some(to) = self.mailbox.resolve(&input.to) else {
+            return ToolResult::error(format!("Unknown agent: {}", input.to));
+        };
+
+        let mut message = AgentMessage::new(from, to, input.content);
+        if let Some(ref reply_to) = input.in_reply_to {
+            match Uuid::parse_str(reply_to) {
+                Ok(id) => message.in_reply_to = Some(id),
+                Err(_) => return ToolResult::error(format!("Invalid message id: {reply_to}")),
+            }
+        }
+
+        if message.in_reply_to.is_some() || input.await_reply == Some(false) {
+            return match self.mailbox.send(message).await {
+                Ok(()) => ToolResult::success(format!("Message sent to {}", input.to)),
+                Err(e) => ToolResult::error(e.to_string()),
+            };
+        }
+
+        match self.mailbox.request(message).await {
+            Ok(reply) => ToolResult::success(reply.content_text()),
+            Err(e) => ToolResult::error(format!("No reply from {}: {e}", input.to)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Tool;
+
+    fn agent() -> Agent {
+        let adapter =
+            crate::client::AnthropicAdapter::new(crate::client::ProviderConfig::default())
+                .api_key("test-key");
+        Agent::new(
+            crate::Client::new(adapter).unwrap(),
+            crate::AgentConfig::default(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_request_reply_round_trip() {
+        let mailbox = Mailbox::new();
+        let alice = agent();
+        let bob = agent();
+        let alice_id = mailbox.register("alice", &alice);
+        let bob_id = mailbox.register("bob", &bob);
+        assert_eq!(mailbox.resolve("bob"), Some(bob_id));
+        assert_eq!(mailbox.resolve(&bob_id.to_string()), Some(bob_id));
+
+        let tool = mailbox.tool("bob");
+        let bob_state = bob.state().clone();
+        let responder = tokio::spawn(async move {
+            loop {
+                if let Some(merged) = bob_state.take_interjections().await {
+                    let id = merged
+                        .content
+                        .split("id=\"")
+                        .nth(1)
+                        .and_then(|s| s.split('"').next())
+                        .unwrap()
+                        .to_string();
+                    assert!(merged.content.contains("from=\"alice\""));
+                    assert!(merged.content.contains("how many?"));
+                    return tool
+                        .execute(
+                            serde_json::json!({"to": "alice", "content": {"count": 3}, "in_reply_to": id}),
+                            &ExecutionContext::default(),
+                        )
+                        .await;
+                }
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        });
+
+        let reply = mailbox
+            .request(AgentMessage::new(alice_id, bob_id, "how many?"))
+            .await
+            .unwrap();
+        assert_eq!(reply.content, serde_json::json!({"count": 3}));
+        assert_eq!(reply.from, bob_id);
+        assert!(!responder.await.unwrap().is_error());
+    }
+
+    #[tokio::test]
+    async fn test_send_to_unknown_and_timeout() {
+        let mailbox = Mailbox::new().reply_timeout(Duration::from_millis(20));
+        let alice = agent();
+        let bob = agent();
+        let alice_id = mailbox.register("alice", &alice);
+
+        let stray = AgentMessage::new(alice_id, bob.state().session_id(), "hi");
+        assert!(matches!(
+            mailbox.send(stray).await,
+            Err(crate::Error::Session(_))
+        ));
+
+        let bob_id = mailbox.register("bob", &bob);
+        assert!(matches!(
+            mailbox
+                .request(AgentMessage::new(alice_id, bob_id, "hello?"))
+                .await,
+            Err(crate::Error::Timeout(_))
+        ));
+        assert_eq!(bob.state().pending_interjections().await, 1);
+
+        let result = mailbox
+            .tool("carol")
+            .execute(
+                serde_json::json!({"to": "bob", "content": "hi"}),
+                &ExecutionContext::default(),
+            )
+            .await;
+        assert!(result.is_error());
+    }
+}
@@ -0,0 +1,547 @@
+//! Multi-agent orchestration.
+//!
+//! An [`Orchestration`] runs a set of named [`Agent`]s as a pipeline, a
+//! fan-out/fan-in, or a supervisor delegating to workers. All agents share:
+//!
+//! - a parent session: every agent's session is re-parented under one root, so
+//!   the session tree records which runs belong together;
+//! - optionally a budget: one [`BudgetTracker`] is charged for every API call;
+//! - optionally an event stream: events from all agents are forwarded to a
+//!   single channel, tagged with the agent name.
+//!
+//! Running agents can also exchange messages directly through a [`Mailbox`].
+//!
+//! ```rust,no_run
+//! use claude_agent::Agent;
+//! use claude_agent::orchestration::Orchestration;
+//! use rust_decimal_macros::dec;
+//!
+//! # async fn example() -> claude_agent::Result<()> {
+//! let team = Orchestration::builder()
+//!     .agent("researcher", Agent::builder().build().await?)
+//!     .agent("writer", Agent::builder().build().await?)
+//!     .shared_budget(dec!(5))
+//!     .build()
+//!     .await?;
+//!
+//! let result = team.pipeline(&["researcher", "writer"], "Summarize RFC 9110").await?;
+//! println!("{}", result.output());
+//! # Ok(())
+//! # }
+//! ```
+
+mod delegate;
+mod mailbox;
+
+pub use delegate::{DelegateInput, DelegateTool};
+pub use mailbox::{
+    AgentMessage, DEFAULT_REPLY_TIMEOUT, Mailbox, SendMessageInput, SendMessageTool,
+};
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use crate::agent::{Agent, AgentResult, EventRecord, EventSink};
+use crate::budget::BudgetTracker;
+use crate::session::{Persistence, Session, SessionConfig, SessionId, SessionType};
+
+/// An event from one agent of an orchestration.
+#[derive(Debug, Clone)]
+pub struct OrchestrationEvent {
+    pub agent: String,
+    pub record: EventRecord,
+}
+
+/// One agent execution within an orchestration.
+#[derive(Debug, Clone)]
+pub struct OrchestrationRun {
+    pub agent: String,
+    pub session_id: SessionId,
+    pub result: AgentResult,
+}
+
+/// Outcome of an orchestration, with every agent run in completion order.
+#[derive(Debug, Clone)]
+pub struct OrchestrationResult {
+    pub root_session_id: SessionId,
+    pub runs: Vec<OrchestrationRun>,
+}
+
+impl OrchestrationResult {
+    /// Text of the final run: the last pipeline stage, the aggregator, or the supervisor.
+    pub fn output(&self) -> &str {
+        self.runs.last().map(|r| r.result.text()).unwrap_or("")
+    }
+
+    /// The most recent run of `agent`.
+    pub fn run(&self, agent: &str) -> Option<&OrchestrationRun> {
+        self.runs.iter().rev().find(|r| r.agent == agent)
+    }
+
+    pub fn total_cost_usd(&self) -> Decimal {
+        self.runs
+            .iter()
+            .map(|r| r.result.metrics.total_cost_usd)
+            .sum()
+    }
+}
+
+/// Builder for [`Orchestration`].
+#[derive(Default)]
+pub struct OrchestrationBuilder {
+    agents: Vec<(String, Agent)>,
+    max_cost_usd: Option<Decimal>,
+    persistence: Option<Arc<dyn Persistence>>,
+    events: Option<mpsc::UnboundedSender<OrchestrationEvent>>,
+    mailbox: Option<Mailbox>,
+}
+
+impl OrchestrationBuilder {
+    /// Adds an agent under `name`. A later agent with the same name replaces the earlier one.
+    pub fn agent(mut self, name: impl Into<String>, agent: Agent) -> Self {
+        let name = name.into();
+        self.agents.retain(|(n, _)| *n != name);
+        self.agents.push((name, agent));
+        self
+    }
+
+    /// Charges all agents against one budget instead of their own.
+    pub fn shared_budget(mut self, max_cost_usd: Decimal) -> Self {
+        self.max_cost_usd = Some(max_cost_usd);
+        self
+    }
+
+    /// Saves the root session and each agent's session after it runs.
+    pub fn persistence(mut self, persistence: Arc<dyn Persistence>) -> Self {
+        self.persistence = Some(persistence);
+        self
+    }
+
+    /// Forwards every agent's events to `sender`.
+    pub fn events(mut self, sender: mpsc::UnboundedSender<OrchestrationEvent>) -> Self {
+        self.events = Some(sender);
+        self
+    }
+
+    /// Registers every agent in `mailbox` under its orchestration name, so agents
+    /// given a [`SendMessageTool`] for that name can message each other.
+    pub fn mailbox(mut self, mailbox: Mailbox) -> Self {
+        self.mailbox = Some(mailbox);
+        self
+    }
+
+    pub async fn build(self) -> crate::Result<Orchestration> {
+        let root = Session::new(SessionConfig::default());
+        if let Some(ref persistence) = self.persistence {
+            persistence.save(&root).await?;
+        }
+
+        let shared = Arc::new(Shared {
+            root_session_id: root.id,
+            budget: self
+                .max_cost_usd
+                .map(|max| Arc::new(BudgetTracker::new(max))),
+            persistence: self.persistence,
+            events: self.events,
+        });
+
+        let mut agents = Vec::with_capacity(self.agents.len());
+        for (name, agent) in self.agents {
+            let agent = shared.attach(&name, agent, "Orchestrated agent").await;
+            if let Some(ref mailbox) = self.mailbox {
+                mailbox.register(&name, &agent);
+            }
+            agents.push((name, Arc::new(agent)));
+        }
+
+        Ok(Orchestration { shared, agents })
+    }
+}
+
+/// A group of agents run together under one root session.
+pub struct Orchestration {
+    shared: Arc<Shared>,
+    agents: Vec<(String, Arc<Agent>)>,
+}
+
+impl Orchestration {
+    pub fn builder() -> OrchestrationBuilder {
+        OrchestrationBuilder::default()
+    }
+
+    pub fn root_session_id(&self) -> SessionId {
+        self.shared.root_session_id
+    }
+
+    pub fn agent_names(&self) -> impl Iterator<Item = &str> {
+        self.agents.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn agent(&self, name: &str) -> Option<&Arc<Agent>> {
+        self.agents.iter().find(|(n, _)| n == name).map(|(_, a)| a)
+    }
+
+    /// Cost charged to the shared budget so far, if one is configured.
+    pub fn budget_used(&self) -> Option<Decimal> {
+        self.shared.budget.as_ref().map(|b| b.used_cost_usd())
+    }
+
+    /// Runs `stages` in order, feeding each stage's output to the next as its prompt.
+    pub async fn pipeline(
+        &self,
+        stages: &[&str],
+        prompt: &str,
+    ) -> crate::Result<OrchestrationResult> {
+        let mut runs = Vec::with_capacity(stages.len());
+        let mut input = prompt.to_string();
+        for stage in stages {
+            let agent = self.require(stage)?;
+            let run = self.shared.run(stage, agent, &input).await?;
+            input = run.result.text().to_string();
+            runs.push(run);
+        }
+        Ok(self.result(runs))
+    }
+
+    /// Runs `workers` concurrently on the same prompt.
+    ///
+    /// Returns each worker's outcome in the order given; a worker that fails
+    /// does not cancel the others. Fails up front only for unknown names.
+    pub async fn fan_out(
+        &self,
+        workers: &[&str],
+        prompt: &str,
+    ) -> crate::Result<Vec<crate::Result<OrchestrationRun>>> {
+        let agents = workers
+            .iter()
+            .map(|name| self.require(name).map(|agent| (*name, agent)))
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        Ok(futures::future::join_all(
+            agents
+                .into_iter()
+                .map(|(name, agent)| self.shared.run(name, agent, prompt)),
+        )
+        .await)
+    }
+
+    /// Fans out to `workers`, then asks `aggregator` to combine their outputs.
+    ///
+    /// Workers that fail are reported to the aggregator as failed and left
+    /// out of the result's runs.
+    pub async fn fan_in(
+        &self,
+        workers: &[&str],
+        aggregator: &str,
+        prompt: &str,
+    ) -> crate::Result<OrchestrationResult> {
+        let aggregator_agent = self.require(aggregator)?;
+        let outcomes = self.fan_out(workers, prompt).await?;
+
+        let mut combined = format!("{prompt}\n\nResults from each worker:\n");
+        let mut runs = Vec::with_capacity(outcomes.len() + 1);
+        for (name, outcome) in workers.iter().zip(outcomes) {
+            match outcome {
+                Ok(run) => {
+                    combined.push_str(&format!("\n## {}\n{}\n", name, run.result.text()));
+                    runs.push(run);
+                }
+                Err(e) => combined.push_str(&format!("\n## {}\n(failed: {})\n", name, e)),
+            }
+        }
+
+        let run = self
+            .shared
+            .run(aggregator, aggregator_agent, &combined)
+            .await?;
+        runs.push(run);
+        Ok(self.result(runs))
+    }
+
+    /// A tool that lets a supervisor agent delegate tasks to this orchestration's agents.
+    ///
+    /// Register a clone on the supervisor (`Agent::builder().tool(delegate.clone())`)
+    /// and pass the tool to [`supervise`](Self::supervise). Each tool records only
+    /// its own delegated runs, so create one per supervisor.
+    pub fn delegate_tool(&self) -> DelegateTool {
+        DelegateTool::new(Arc::clone(&self.shared), self.agents.clone())
+    }
+
+    /// Runs a supervisor agent that delegates to workers through `delegate`.
+    ///
+    /// The result lists each run delegated during this call followed by the
+    /// supervisor's own run.
+    pub async fn supervise(
+        &self,
+        supervisor: Agent,
+        delegate: &DelegateTool,
+        prompt: &str,
+    ) -> crate::Result<OrchestrationResult> {
+        let supervisor = self
+            .shared
+            .attach("supervisor", supervisor, "Orchestration supervisor")
+            .await;
+        let run = self.shared.run("supervisor", &supervisor, prompt).await;
+
+        let mut runs = delegate.take_runs();
+        runs.push(run?);
+        Ok(self.result(runs))
+    }
+
+    fn require(&self, name: &str) -> crate::Result<&Agent> {
+        self.agent(name)
+            .map(Arc::as_ref)
+            .ok_or_else(|| crate::Error::Config(format!("Unknown agent in orchestration: {name}")))
+    }
+
+    fn result(&self, runs: Vec<OrchestrationRun>) -> OrchestrationResult {
+        OrchestrationResult {
+            root_session_id: self.shared.root_session_id,
+            runs,
+        }
+    }
+}
+
+/// State shared by an orchestration and the delegate tools it hands out.
+struct Shared {
+    root_session_id: SessionId,
+    budget: Option<Arc<BudgetTracker>>,
+    persistence: Option<Arc<dyn Persistence>>,
+    events: Option<mpsc::UnboundedSender<OrchestrationEvent>>,
+}
+
+impl Shared {
+    async fn attach(&self, name: &str, mut agent: Agent, description: &str) -> Agent {
+        let root = self.root_session_id;
+        let agent_type = name.to_string();
+        let description = description.to_string();
+        agent
+            .state
+            .with_session_mut(move |session| {
+                session.parent_id = Some(root);
+                session.session_type = SessionType::Subagent {
+                    agent_type,
+                    description,
+                };
+            })
+            .await;
+
+        if let Some(ref budget) = self.budget {
+            agent.budget_tracker = Arc::clone(budget);
+        }
+        if let Some(ref sender) = self.events {
+            let mut sinks = agent.event_sinks.to_vec();
+            sinks.push(Arc::new(TaggedSink {
+                agent: name.to_string(),
+                sender: sender.clone(),
+            }));
+            agent.event_sinks = sinks.into();
+        }
+        agent
+    }
+
+    async fn run(
+        &self,
+        name: &str,
+        agent: &Agent,
+        prompt: &str,
+    ) -> crate::Result<OrchestrationRun> {
+        let result = agent.execute(prompt).await?;
+        let session_id = agent.state.session_id();
+
+        if let Some(ref persistence) = self.persistence
+            && let Err(e) = persistence.save(&agent.state.session().await).await
+        {
+            warn!(agent = %name, session_id = %session_id, error = %e, "Failed to save orchestrated session");
+        }
+
+        Ok(OrchestrationRun {
+            agent: name.to_string(),
+            session_id,
+            result,
+        })
+    }
+}
+
+struct TaggedSink {
+    agent: String,
+    sender: mpsc::UnboundedSender<OrchestrationEvent>,
+}
+
+#[async_trait]
+impl EventSink for TaggedSink {
+    async fn record(&self, record: &EventRecord) -> crate::Result<()> {
+        self.sender
+            .send(OrchestrationEvent {
+                agent: self.agent.clone(),
+                record: record.clone(),
+            })
+            .map_err(|_| crate::Error::Stream("Orchestration event channel closed".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AgentEvent;
+    use crate::agent::tests::helpers::{mock_agent, text_response};
+    use crate::session::MemoryPersistence;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer};
+
+    async fn replying(text: &str) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .respond_with(text_response(text))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_feeds_output_forward() {
+        let research = replying("fact: water is wet").await;
+        let writer = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("fact: water is wet"))
+            .respond_with(text_response("Water is wet."))
+            .expect(1)
+            .mount(&writer)
+            .await;
+
+        let persistence = Arc::new(MemoryPersistence::new());
+        let team = Orchestration::builder()
+            .agent("researcher", mock_agent(&research))
+            .agent("writer", mock_agent(&writer))
+            .persistence(persistence.clone())
+            .build()
+            .await
+            .unwrap();
+
+        let result = team
+            .pipeline(&["researcher", "writer"], "Is water wet?")
+            .await
+            .unwrap();
+
+        assert_eq!(result.output(), "Water is wet.");
+        assert_eq!(result.runs.len(), 2);
+
+        let saved = persistence
+            .load(&result.run("writer").unwrap().session_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.parent_id, Some(team.root_session_id()));
+        assert!(
+            persistence
+                .load(&team.root_session_id())
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fan_in_shares_budget_and_events() {
+        let a = replying("alpha").await;
+        let b = replying("beta").await;
+        let judge = replying("alpha and beta").await;
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        let team = Orchestration::builder()
+            .agent("a", mock_agent(&a))
+            .agent("b", mock_agent(&b))
+            .agent("judge", mock_agent(&judge))
+            .shared_budget(Decimal::ONE)
+            .events(tx)
+            .build()
+            .await
+            .unwrap();
+
+        let result = team.fan_in(&["a", "b"], "judge", "Pick one").await.unwrap();
+        assert_eq!(result.output(), "alpha and beta");
+        assert_eq!(result.runs.len(), 3);
+        assert_eq!(team.budget_used(), Some(result.total_cost_usd()));
+        assert!(result.total_cost_usd() > Decimal::ZERO);
+
+        drop(team);
+        let mut completed = Vec::new();
+        while let Some(event) = rx.recv().await {
+            if matches!(event.record.event, AgentEvent::Complete(_)) {
+                completed.push(event.agent);
+            }
+        }
+        completed.sort();
+        assert_eq!(completed, vec!["a", "b", "judge"]);
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_keeps_results_of_other_workers() {
+        let ok = replying("alpha").await;
+        let failing = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .respond_with(
+                wiremock::ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                    "type": "error",
+                    "error": {"type": "invalid_request_error", "message": "bad request"}
+                })),
+            )
+            .mount(&failing)
+            .await;
+        let judge = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("failed:"))
+            .respond_with(text_response("only alpha"))
+            .expect(1)
+            .mount(&judge)
+            .await;
+
+        let team = Orchestration::builder()
+            .agent("a", mock_agent(&ok))
+            .agent("b", mock_agent(&failing))
+            .agent("judge", mock_agent(&judge))
+            .build()
+            .await
+            .unwrap();
+
+        let outcomes = team.fan_out(&["a", "b"], "Pick one").await.unwrap();
+        assert_eq!(outcomes[0].as_ref().unwrap().result.text(), "alpha");
+        assert!(outcomes[1].is_err());
+
+        let result = team.fan_in(&["a", "b"], "judge", "Pick one").await.unwrap();
+        assert_eq!(result.output(), "only alpha");
+        let agents: Vec<_> = result.runs.iter().map(|r| r.agent.as_str()).collect();
+        assert_eq!(agents, vec!["a", "judge"]);
+    }
+
+    #[tokio::test]
+    async fn test_mailbox_registration() {
+        let server = replying("ok").await;
+        let mailbox = Mailbox::new();
+        let team = Orchestration::builder()
+            .agent("a", mock_agent(&server))
+            .mailbox(mailbox.clone())
+            .build()
+            .await
+            .unwrap();
+
+        let session_id = team.agent("a").unwrap().state().session_id();
+        assert_eq!(mailbox.resolve("a"), Some(session_id));
+        assert_eq!(mailbox.name_of(&session_id).as_deref(), Some("a"));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_agent() {
+        let team = Orchestration::builder().build().await.unwrap();
+        assert!(matches!(
+            team.pipeline(&["missing"], "hi").await,
+            Err(crate::Error::Config(_))
+        ));
+    }
+}
@@ -0,0 +1,131 @@
+//! Audit trail of permission decisions, kept with the session.
+
+use std::hash::{Hash, Hasher};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{PermissionDecision, PermissionResult, PermissionStatus};
+
+/// What decided a permission check.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionSource {
+    /// The default of the permission mode.
+    #[default]
+    Mode,
+    /// A deny, ask or allow rule of the policy.
+    Rule,
+    /// A temporary [`PermissionGrant`](super::PermissionGrant).
+    Grant,
+    /// A risk threshold on Bash commands; see
+    /// [`BashRisk`](crate::security::bash::BashRisk).
+    Risk,
+    /// A rule a handler asked to remember earlier in the session.
+    Remembered,
+    /// The agent's [`PermissionHandler`](super::PermissionHandler).
+    Handler,
+    /// A `PreToolUse` hook that blocked the call.
+    Hook,
+}
+
+/// One permission check of a tool call.
+///
+/// A call the policy sends to the handler is recorded twice: once with the
+/// [`PermissionDecision::Ask`] of the policy and once with the answer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionAuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub tool_name: String,
+    /// [`PermissionAuditEntry::digest`] of the input, so entries can be
+    /// matched to calls without storing their content.
+    pub input_digest: String,
+    pub decision: PermissionDecision,
+    pub source: DecisionSource,
+    /// Pattern of the deciding rule or grant.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    /// Name of the deciding hook.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hook: Option<String>,
+    pub reason: String,
+}
+
+impl PermissionAuditEntry {
+    pub fn new(tool_name: impl Into<String>, input: &Value, result: &PermissionResult) -> Self {
+        let decision = match result.status {
+            PermissionStatus::Allowed => PermissionDecision::Allow,
+            PermissionStatus::Denied => PermissionDecision::Deny,
+            PermissionStatus::Ask => PermissionDecision::Ask,
+        };
+        Self {
+            timestamp: Utc::now(),
+            tool_name: tool_name.into(),
+            input_digest: Self::digest(input),
+            decision,
+            source: result.source,
+            rule: result.rule.clone(),
+            hook: None,
+            reason: result.reason.clone(),
+        }
+    }
+
+    /// A call blocked by the `PreToolUse` hook `hook`.
+    pub fn hook_block(
+        tool_name: impl Into<String>,
+        input: &Value,
+        hook: Option<String>,
+        reason: impl Into<String>,
+    ) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            tool_name: tool_name.into(),
+            input_digest: Self::digest(input),
+            decision: PermissionDecision::Deny,
+            source: DecisionSource::Hook,
+            rule: None,
+            hook,
+            reason: reason.into(),
+        }
+    }
+
+    /// Hex digest of a tool input, as stored in `input_digest`.
+    pub fn digest(input: &Value) -> String {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        input.to_string().hash(&mut hasher);
+        format!("{:016x}", hasher.finish())
+    }
+
+    /// Whether this entry is for the call `tool_name` with `input`.
+    pub fn is_for(&self, tool_name: &str, input: &Value) -> bool {
+        self.tool_name == tool_name && self.input_digest == Self::digest(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_entry_from_result() {
+        let input = json!({"command": "rm -rf /"});
+        let result = PermissionResult::denied("Denied by rule: Bash(rm:*)")
+            .decided_by(DecisionSource::Rule, Some("Bash(rm:*)".into()));
+        let entry = PermissionAuditEntry::new("Bash", &input, &result);
+
+        assert_eq!(entry.decision, PermissionDecision::Deny);
+        assert_eq!(entry.source, DecisionSource::Rule);
+        assert_eq!(entry.rule.as_deref(), Some("Bash(rm:*)"));
+        assert!(entry.is_for("Bash", &input));
+        assert!(!entry.is_for("Bash", &json!({"command": "ls"})));
+        assert_eq!(entry.input_digest.len(), 16);
+
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["source"], "rule");
+        assert!(json.get("hook").is_none());
+        let parsed: PermissionAuditEntry = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, entry);
+    }
+}
@@ -0,0 +1,195 @@
+//! Temporary allow rules that expire after a time or a number of uses.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use super::PermissionRule;
+use crate::common::clock;
+
+/// An allow rule limited in time, uses, or both.
+///
+/// While active, a grant allows matching calls even where an ask rule or the
+/// mode would not; deny rules still win. Once expired, matching calls need
+/// approval again instead of falling through to the policy.
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use claude_agent::permissions::PermissionGrant;
+/// // Allow npm for the next 10 minutes, at most 5 times.
+/// let grant = PermissionGrant::new("Bash(npm:*)")
+///     .for_duration(Duration::from_secs(600))
+///     .uses(5);
+/// ```
+#[derive(Clone, Debug)]
+pub struct PermissionGrant {
+    rule: PermissionRule,
+    expires_at: Option<Instant>,
+    remaining_uses: Option<u32>,
+}
+
+impl PermissionGrant {
+    /// A grant for `pattern`, e.g. `Bash(npm:*)`, with no limit yet.
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self {
+            rule: PermissionRule::allow_pattern(pattern),
+            expires_at: None,
+            remaining_uses: None,
+        }
+    }
+
+    /// Expire `duration` from now.
+    pub fn for_duration(mut self, duration: Duration) -> Self {
+        self.expires_at = Some(clock::now() + duration);
+        self
+    }
+
+    /// Expire after `uses` allowed calls.
+    pub fn uses(mut self, uses: u32) -> Self {
+        self.remaining_uses = Some(uses);
+        self
+    }
+
+    pub fn pattern(&self) -> String {
+        self.rule.scoped_pattern()
+    }
+
+    pub fn remaining_uses(&self) -> Option<u32> {
+        self.remaining_uses
+    }
+
+    /// Time left, `None` without a time limit.
+    pub fn remaining_time(&self) -> Option<Duration> {
+        self.expires_at
+            .map(|at| at.saturating_duration_since(clock::now()))
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining_uses == Some(0) || self.expires_at.is_some_and(|at| clock::now() >= at)
+    }
+}
+
+/// What the grants say about a call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum GrantCheck {
+    /// An active grant allowed the call and used it up by one.
+    Allowed(String),
+    /// Only expired grants match; the call needs approval.
+    Expired(String),
+}
+
+/// Grants of one session, shared by clones.
+#[derive(Clone, Debug, Default)]
+pub struct PermissionGrants(Arc<Mutex<Vec<PermissionGrant>>>);
+
+impl PermissionGrants {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `grant`, replacing any grant with the same pattern.
+    pub fn add(&self, grant: PermissionGrant) {
+        let mut grants = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let pattern = grant.pattern();
+        grants.retain(|g| g.pattern() != pattern);
+        grants.push(grant);
+    }
+
+    pub fn revoke(&self, pattern: &str) {
+        let mut grants = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        grants.retain(|g| g.pattern() != pattern);
+    }
+
+    /// Grants that have not expired.
+    pub fn active(&self) -> Vec<PermissionGrant> {
+        let grants = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        grants.iter().filter(|g| !g.is_expired()).cloned().collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).is_empty()
+    }
+
+    /// Check a call against the grants, using up one use of the first active
+    /// grant that matches.
+    pub(crate) fn check(
+        &self,
+        tool_name: &str,
+        input: &Value,
+        working_dir: Option<&Path>,
+    ) -> Option<GrantCheck> {
+        let mut grants = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let mut expired = None;
+        for grant in grants.iter_mut() {
+            if !grant.rule.matches_in(tool_name, input, working_dir) {
+                continue;
+            }
+            if grant.is_expired() {
+                expired.get_or_insert_with(|| grant.pattern());
+                continue;
+            }
+            if let Some(uses) = &mut grant.remaining_uses {
+                *uses -= 1;
+            }
+            return Some(GrantCheck::Allowed(grant.pattern()));
+        }
+        expired.map(GrantCheck::Expired)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn npm() -> Value {
+        serde_json::json!({"command": "npm install"})
+    }
+
+    #[test]
+    fn test_use_count() {
+        let grants = PermissionGrants::new();
+        grants.add(PermissionGrant::new("Bash(npm:*)").uses(2));
+
+        for _ in 0..2 {
+            assert_eq!(
+                grants.check("Bash", &npm(), None),
+                Some(GrantCheck::Allowed("Bash(npm:*)".into()))
+            );
+        }
+        assert_eq!(
+            grants.check("Bash", &npm(), None),
+            Some(GrantCheck::Expired("Bash(npm:*)".into()))
+        );
+        assert!(grants.active().is_empty());
+        let git = serde_json::json!({"command": "git status"});
+        assert_eq!(grants.check("Bash", &git, None), None);
+    }
+
+    #[test]
+    fn test_duration() {
+        let clock = crate::testing::Deterministic::new(1).install();
+        let grants = PermissionGrants::new();
+        grants.add(PermissionGrant::new("Bash(npm:*)").for_duration(Duration::from_secs(600)));
+        assert!(matches!(
+            grants.check("Bash", &npm(), None),
+            Some(GrantCheck::Allowed(_))
+        ));
+        clock.advance(Duration::from_secs(599));
+        assert_eq!(
+            grants.active()[0].remaining_time(),
+            Some(Duration::from_secs(1))
+        );
+        clock.advance(Duration::from_secs(1));
+        assert!(matches!(
+            grants.check("Bash", &npm(), None),
+            Some(GrantCheck::Expired(_))
+        ));
+
+        grants.add(PermissionGrant::new("Bash(npm:*)").uses(1));
+        assert_eq!(grants.active().len(), 1);
+        grants.revoke("Bash(npm:*)");
+        assert!(grants.is_empty());
+    }
+}
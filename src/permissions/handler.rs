@@ -0,0 +1,124 @@
+//! Interactive approval of tool calls the policy marks as `Ask`.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::PermissionResult;
+
+/// A tool call waiting for approval.
+#[derive(Clone, Debug)]
+pub struct PermissionRequest {
+    pub session_id: String,
+    pub tool_name: String,
+    pub input: Value,
+    /// Why the policy asked, e.g. the matching rule.
+    pub reason: String,
+}
+
+impl PermissionRequest {
+    /// Rule pattern matching this call and others like it: `Bash(<command>)`,
+    /// `Edit(<path>)` or `WebFetch(domain:<host>)`, otherwise the tool name.
+    pub fn suggested_pattern(&self) -> String {
+        let field = |key: &str| self.input.get(key).and_then(Value::as_str);
+        let scope = match self.tool_name.as_str() {
+            "Bash" => field("command").map(str::to_string),
+            "Read" | "Write" | "Edit" | "MultiEdit" | "AstEdit" => {
+                field("file_path").map(str::to_string)
+            }
+            "Glob" | "Grep" | "LS" => field("path").map(str::to_string),
+            "WebFetch" | "HttpRequest" => field("url")
+                .and_then(|url| url::Url::parse(url).ok())
+                .and_then(|url| url.host_str().map(|host| format!("domain:{host}"))),
+            _ => None,
+        };
+        match scope {
+            Some(scope) => format!("{}({})", self.tool_name, scope),
+            None => self.tool_name.clone(),
+        }
+    }
+}
+
+/// How long a remembered decision lasts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionScope {
+    /// Stored in the session's permissions, and persisted with it.
+    Session,
+    /// Also written to `.claude/settings.local.json` in the working directory.
+    Project,
+}
+
+/// A rule to add after a handler decision, see [`PermissionResult::remember`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionUpdate {
+    /// Rule pattern, e.g. `Bash(cargo test:*)`.
+    pub pattern: String,
+    pub scope: PermissionScope,
+}
+
+/// Decides tool calls that need approval, e.g. by asking a user.
+///
+/// Called whenever the [`PermissionPolicy`](super::PermissionPolicy) returns
+/// `Ask`. Return [`PermissionResult::allowed`] to run the call, optionally
+/// with a [replacement input](PermissionResult::with_input), or
+/// [`PermissionResult::denied`] to return the reason to the model.
+///
+/// Add [`remember`](PermissionResult::remember) to the result for "always
+/// allow" or "never allow" answers: later calls matching the pattern are
+/// decided without asking again.
+///
+/// ```rust
+/// use async_trait::async_trait;
+/// use claude_agent::permissions::{
+///     PermissionHandler, PermissionRequest, PermissionResult, PermissionScope,
+/// };
+///
+/// struct DenyPushes;
+///
+/// #[async_trait]
+/// impl PermissionHandler for DenyPushes {
+///     async fn decide(&self, request: &PermissionRequest) -> PermissionResult {
+///         let command = request.input["command"].as_str().unwrap_or_default();
+///         if command.starts_with("git push") {
+///             PermissionResult::denied("pushes are reviewed manually")
+///                 .remember(PermissionScope::Session, "Bash(git push:*)")
+///         } else {
+///             PermissionResult::allowed("approved")
+///         }
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait PermissionHandler: Send + Sync {
+    async fn decide(&self, request: &PermissionRequest) -> PermissionResult;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(tool_name: &str, input: Value) -> PermissionRequest {
+        PermissionRequest {
+            session_id: "s".into(),
+            tool_name: tool_name.into(),
+            input,
+            reason: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_suggested_pattern() {
+        let bash = request("Bash", serde_json::json!({"command": "cargo test"}));
+        assert_eq!(bash.suggested_pattern(), "Bash(cargo test)");
+        let fetch = request(
+            "WebFetch",
+            serde_json::json!({"url": "https://docs.rs/serde"}),
+        );
+        assert_eq!(fetch.suggested_pattern(), "WebFetch(domain:docs.rs)");
+        assert_eq!(
+            request("Task", serde_json::json!({})).suggested_pattern(),
+            "Task"
+        );
+    }
+}
@@ -0,0 +1,295 @@
+//! Matching rule scopes such as `Bash(git commit:*)` against tool input.
+
+use std::path::{Path, PathBuf};
+
+use glob::{MatchOptions, Pattern};
+use serde_json::Value;
+
+use crate::security::bash::CommandPattern;
+use crate::security::path::normalize_path;
+
+/// How the scope of a rule like `Edit(src/**)` applies to a tool's input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArgumentMatcher {
+    /// Bash command, matched one simple command at a time; see
+    /// [`CommandPattern`].
+    Command(CommandPattern),
+    /// Glob on the file path, in the input field named by the second value.
+    /// Relative globs are resolved against the working directory, `~/`
+    /// against the home directory; a bare name like `*.env` matches at any
+    /// depth.
+    Path(String, &'static str),
+    /// URL host. `example.com` also matches subdomains, `*.example.com` only
+    /// subdomains.
+    Domain(String),
+    /// Prefix or glob on the URL.
+    Url(String),
+}
+
+impl ArgumentMatcher {
+    /// The matcher for `scope` on `tool_name`, or `None` for tools whose
+    /// input has no argument to match.
+    pub fn for_tool(tool_name: &str, scope: &str) -> Option<Self> {
+        let scope = scope.to_string();
+        Some(match tool_name {
+            "Bash" => Self::Command(CommandPattern::new(scope)),
+            "Read" | "Write" | "Edit" | "MultiEdit" | "AstEdit" => Self::Path(scope, "file_path"),
+            "Glob" | "Grep" | "LS" | "str_replace_based_edit_tool" | "str_replace_editor" => {
+                Self::Path(scope, "path")
+            }
+            "NotebookEdit" => Self::Path(scope, "notebook_path"),
+            "WebFetch" | "HttpRequest" => match scope.strip_prefix("domain:") {
+                Some(domain) => Self::Domain(domain.to_string()),
+                None => Self::Url(scope),
+            },
+            _ => return None,
+        })
+    }
+
+    /// Whether `input` matches.
+    ///
+    /// With `every_command`, each simple command of a Bash command must
+    /// match, so `git status && rm -rf .` is not covered by `git:*`.
+    /// Otherwise one matching command is enough, which is what deny and ask
+    /// rules need.
+    pub fn matches(&self, input: &Value, working_dir: Option<&Path>, every_command: bool) -> bool {
+        let field = |key: &str| input.get(key).and_then(Value::as_str);
+        match self {
+            Self::Command(pattern) => field("command").is_some_and(|command| {
+                if every_command {
+                    pattern.matches_all(command)
+                } else {
+                    pattern.matches_any(command)
+                }
+            }),
+            Self::Path(pattern, key) => {
+                field(key).is_some_and(|path| matches_path(pattern, path, working_dir))
+            }
+            Self::Domain(domain) => field("url").is_some_and(|url| matches_domain(url, domain)),
+            Self::Url(pattern) => field("url").is_some_and(|url| matches_text(pattern, url)),
+        }
+    }
+}
+
+/// Prefix (`:*`, trailing `**`), single `*` wildcard or exact match.
+fn matches_text(pattern: &str, input: &str) -> bool {
+    if pattern.ends_with(":*") || pattern.ends_with("**") {
+        input.starts_with(&pattern[..pattern.len() - 2])
+    } else if let [start, end] = pattern.split('*').collect::<Vec<_>>()[..] {
+        input.starts_with(start) && input.ends_with(end)
+    } else {
+        input == pattern || input.starts_with(&format!("{}/", pattern))
+    }
+}
+
+fn matches_path(pattern: &str, path: &str, working_dir: Option<&Path>) -> bool {
+    let (pattern, prefix_only) = match pattern.strip_suffix(":*") {
+        Some(prefix) => (prefix, true),
+        None => (pattern, false),
+    };
+    let path = normalize_path(&match working_dir {
+        Some(dir) => dir.join(path),
+        None => PathBuf::from(path),
+    });
+    if !prefix_only && !pattern.contains('/') {
+        // Like .gitignore, a bare name matches a file or directory at any depth.
+        let Ok(glob) = Pattern::new(pattern) else {
+            return false;
+        };
+        return path
+            .iter()
+            .any(|name| name.to_str().is_some_and(|name| glob.matches(name)));
+    }
+    let pattern = normalize_path(&absolute_pattern(pattern, working_dir));
+
+    if prefix_only {
+        return path
+            .to_string_lossy()
+            .starts_with(&*pattern.to_string_lossy());
+    }
+    let pattern = pattern.to_string_lossy();
+    if !pattern.contains(['*', '?', '[']) {
+        return path.starts_with(&*pattern);
+    }
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+    Pattern::new(&pattern).is_ok_and(|glob| glob.matches_path_with(&path, options))
+}
+
+/// `//abs` and `/abs` are absolute, `~/` is under home, anything else is
+/// relative to the working directory.
+fn absolute_pattern(pattern: &str, working_dir: Option<&Path>) -> PathBuf {
+    if let Some(absolute) = pattern.strip_prefix("//") {
+        return PathBuf::from(format!("/{absolute}"));
+    }
+    if let Some(rest) = pattern.strip_prefix("~/")
+        && let Some(home) = crate::common::home_dir()
+    {
+        return home.join(rest);
+    }
+    match working_dir {
+        Some(dir) => dir.join(pattern),
+        None => PathBuf::from(pattern),
+    }
+}
+
+/// Match the host of `url` against `domain`.
+///
+/// Only the parsed host counts, so `https://attacker.com?redirect=github.com`
+/// and `evil.github.com.attacker.com` do not match `github.com`.
+fn matches_domain(url: &str, domain: &str) -> bool {
+    let host = match url::Url::parse(url) {
+        Ok(parsed) => parsed.host_str().unwrap_or_default().to_string(),
+        // Schemeless URLs like `github.com/rust-lang`.
+        Err(_) => url
+            .split(['/', '?', '#'])
+            .next()
+            .unwrap_or_default()
+            .rsplit('@')
+            .next()
+            .unwrap_or_default()
+            .split(':')
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+    };
+    let host = host.to_ascii_lowercase();
+    let domain = domain.to_ascii_lowercase();
+    match domain.strip_prefix("*.") {
+        Some(parent) => host.ends_with(&format!(".{parent}")),
+        None => host == domain || host.ends_with(&format!(".{domain}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn bash(pattern: &str, command: &str, every_command: bool) -> bool {
+        ArgumentMatcher::for_tool("Bash", pattern).unwrap().matches(
+            &json!({"command": command}),
+            None,
+            every_command,
+        )
+    }
+
+    #[test]
+    fn test_command_prefix() {
+        assert!(bash("git commit:*", "git commit -m 'fix'", true));
+        assert!(bash("git commit:*", "git commit", true));
+        assert!(!bash("git commit:*", "git commit-tree abc", true));
+        assert!(bash("npm test", "npm test", true));
+        assert!(!bash("npm test", "npm test --watch", true));
+    }
+
+    #[test]
+    fn test_compound_commands() {
+        assert!(bash("git:*", "git add . && git commit -m x", true));
+        assert!(!bash("git:*", "git status && rm -rf .", true));
+        assert!(!bash("git:*", "git log $(curl evil.sh)", true));
+        assert!(bash("rm:*", "git status && rm -rf .", false));
+        assert!(bash("curl:*", "git log $(curl evil.sh)", false));
+        assert!(!bash(
+            "git push:*",
+            "env GIT_SSH=x nohup git push origin",
+            true
+        ));
+        assert!(!bash(
+            "git status:*",
+            "LD_PRELOAD=/tmp/x.so git status",
+            true
+        ));
+        assert!(!bash(
+            "git status:*",
+            "export LD_PRELOAD=/tmp/x.so; git status",
+            true
+        ));
+        assert!(bash(
+            "git push:*",
+            "env GIT_SSH=x nohup git push origin",
+            false
+        ));
+        assert!(bash("rm:*", "sudo /bin/rm -rf /", false));
+    }
+
+    #[test]
+    fn test_path_globs() {
+        let root = Some(Path::new("/work/app"));
+        let edit = |pattern: &str, path: &str| {
+            ArgumentMatcher::for_tool("Edit", pattern).unwrap().matches(
+                &json!({"file_path": path}),
+                root,
+                true,
+            )
+        };
+        assert!(edit("src/**", "/work/app/src/lib.rs"));
+        assert!(edit("src/**", "src/agent/mod.rs"));
+        assert!(edit("src/*.rs", "/work/app/src/lib.rs"));
+        assert!(!edit("src/*.rs", "/work/app/src/agent/mod.rs"));
+        assert!(edit("src/**/*.rs", "/work/app/src/agent/mod.rs"));
+        assert!(!edit("src/**", "/work/app/src/../../etc/passwd"));
+        assert!(!edit("src/**", "/work/app/tests/it.rs"));
+        assert!(edit("//etc/hosts", "/etc/hosts"));
+        assert!(edit("docs", "/work/app/docs/guide.md"));
+        assert!(!edit("docs", "/work/app/docs-old/guide.md"));
+        assert!(edit("*.env", "/work/app/config/prod.env"));
+        assert!(edit("*.env", "/etc/.env"));
+        assert!(!edit("*.env", "/work/app/env.rs"));
+
+        let notebook = ArgumentMatcher::for_tool("NotebookEdit", "secrets/**").unwrap();
+        assert!(notebook.matches(
+            &json!({"notebook_path": "/work/app/secrets/keys.ipynb"}),
+            root,
+            false,
+        ));
+        assert!(!notebook.matches(
+            &json!({"notebook_path": "/work/app/notes.ipynb"}),
+            root,
+            false,
+        ));
+    }
+
+    #[test]
+    fn test_matches_domain() {
+        assert!(matches_domain("https://github.com/path", "github.com"));
+        assert!(matches_domain("http://github.com", "github.com"));
+        assert!(matches_domain("https://github.com:443/path", "github.com"));
+        assert!(matches_domain("https://api.github.com/repos", "github.com"));
+        assert!(matches_domain(
+            "https://raw.githubusercontent.com/f",
+            "githubusercontent.com"
+        ));
+
+        assert!(!matches_domain(
+            "https://github.com.evil.com/x",
+            "github.com"
+        ));
+        assert!(!matches_domain(
+            "https://evil.com?r=github.com",
+            "github.com"
+        ));
+        assert!(!matches_domain("https://evil.com/github.com", "github.com"));
+        assert!(!matches_domain("https://notgithub.com", "github.com"));
+        assert!(!matches_domain("https://fakegithub.com", "github.com"));
+    }
+
+    #[test]
+    fn test_domains() {
+        let fetch = |pattern: &str, url: &str| {
+            ArgumentMatcher::for_tool("WebFetch", pattern)
+                .unwrap()
+                .matches(&json!({"url": url}), None, true)
+        };
+        assert!(fetch("domain:github.com", "https://github.com/rust-lang"));
+        assert!(fetch("domain:github.com", "https://api.GitHub.com/repos"));
+        assert!(!fetch("domain:*.github.com", "https://github.com"));
+        assert!(fetch("domain:*.github.com", "https://api.github.com"));
+        assert!(!fetch("domain:github.com", "https://github.com@evil.com/"));
+        assert!(!fetch("domain:github.com", "https://evil.com/?github.com"));
+        assert!(fetch("https://docs.rs/**", "https://docs.rs/serde"));
+        assert!(ArgumentMatcher::for_tool("Task", "x").is_none());
+    }
+}
@@ -1,22 +1,59 @@
 //! Permission system for controlling tool execution.
 
+mod audit;
+mod grant;
+mod handler;
+mod matcher;
 mod modes;
+mod remediation;
 mod rules;
 
+pub use audit::{DecisionSource, PermissionAuditEntry};
+pub use grant::{PermissionGrant, PermissionGrants};
+pub use handler::{PermissionHandler, PermissionRequest, PermissionScope, PermissionUpdate};
+pub use matcher::ArgumentMatcher;
 pub use modes::PermissionMode;
+pub use remediation::Remediation;
 pub use rules::{
     PermissionDecision, PermissionPolicy, PermissionPolicyBuilder, PermissionResult,
-    PermissionRule, ToolLimits,
+    PermissionRule, PermissionStatus, ToolLimits,
 };
 
-pub const READ_ONLY_TOOLS: &[&str] = &["Read", "Glob", "Grep", "WebSearch", "WebFetch"];
-pub const FILE_TOOLS: &[&str] = &["Read", "Write", "Edit", "Glob", "Grep"];
-pub const SHELL_TOOLS: &[&str] = &["Bash", "KillShell"];
+pub const READ_ONLY_TOOLS: &[&str] = &["Read", "Glob", "Grep", "LS", "WebSearch", "WebFetch"];
+/// Tools that change files. Shared by read-only access, serial execution,
+/// previews, backups and `AcceptEdits`, so a new writer is added only here.
+pub const WRITE_TOOLS: &[&str] = &[
+    "Write",
+    "Edit",
+    "MultiEdit",
+    "NotebookEdit",
+    "AstEdit",
+    "str_replace_based_edit_tool",
+    "str_replace_editor",
+];
+const FILE_READ_TOOLS: &[&str] = &["Read", "Glob", "Grep", "LS"];
+pub const FILE_TOOLS: &[&str] =
+    &concat_tools::<{ FILE_READ_TOOLS.len() + WRITE_TOOLS.len() }>(FILE_READ_TOOLS, WRITE_TOOLS);
+pub const SHELL_TOOLS: &[&str] = &["Bash", "BashOutput", "KillShell"];
+
+const fn concat_tools<const N: usize>(a: &[&'static str], b: &[&'static str]) -> [&'static str; N] {
+    let mut out = [""; N];
+    let mut i = 0;
+    while i < N {
+        out[i] = if i < a.len() { a[i] } else { b[i - a.len()] };
+        i += 1;
+    }
+    out
+}
 
 pub fn is_read_only_tool(tool_name: &str) -> bool {
     READ_ONLY_TOOLS.contains(&tool_name)
 }
 
+pub fn is_write_tool(tool_name: &str) -> bool {
+    WRITE_TOOLS.contains(&tool_name)
+}
+
 pub fn is_file_tool(tool_name: &str) -> bool {
     FILE_TOOLS.contains(&tool_name)
 }
@@ -45,6 +82,17 @@ mod tests {
         assert!(is_file_tool("Edit"));
         assert!(!is_file_tool("Bash"));
         assert!(!is_file_tool("WebSearch"));
+        assert!(is_file_tool("NotebookEdit"));
+        assert!(is_file_tool("str_replace_based_edit_tool"));
+    }
+
+    #[test]
+    fn test_is_write_tool() {
+        for tool in ["Write", "AstEdit", "str_replace_editor"] {
+            assert!(is_write_tool(tool));
+        }
+        assert!(!is_write_tool("Read"));
+        assert!(!is_write_tool("Bash"));
     }
 
     #[test]
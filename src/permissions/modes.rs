@@ -12,7 +12,8 @@ use serde::{Deserialize, Serialize};
 /// - **Default**: Standard permission flow - tools must be explicitly allowed
 ///   or will be denied. Use allow/deny rules to control access.
 ///
-/// - **AcceptEdits**: Auto-approve file operations (Read, Write, Edit, Glob, Grep).
+/// - **AcceptEdits**: Auto-approve file operations (Read, Glob, Grep, LS and the
+///   tools in [`WRITE_TOOLS`](crate::permissions::WRITE_TOOLS)).
 ///   Useful for development scenarios where file access is expected.
 ///
 /// - **BypassPermissions**: Allow all tool executions without permission checks.
@@ -46,7 +47,8 @@ pub enum PermissionMode {
     /// Auto-approve file operations
     ///
     /// This mode automatically approves file-related tools:
-    /// - Read, Write, Edit, Glob, Grep
+    /// - Read, Glob, Grep, LS
+    /// - Write, Edit, MultiEdit, NotebookEdit, AstEdit and the text editor
     ///
     /// Other tools still require explicit allow rules.
     AcceptEdits,
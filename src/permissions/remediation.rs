@@ -0,0 +1,56 @@
+//! What a denied call could do instead.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// Machine-readable alternatives to a denied call, fed back to the model so
+/// it changes course instead of retrying the same call.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Remediation {
+    /// Allow rules of the tool; a call within one of them would run, e.g.
+    /// `Bash(git status:*)`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_rules: Vec<String>,
+    /// Directories the tool may access.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_directories: Vec<PathBuf>,
+    /// Other tools that would be allowed, e.g. read-only tools in plan mode.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_tools: Vec<String>,
+}
+
+impl Remediation {
+    pub fn is_empty(&self) -> bool {
+        self.allowed_rules.is_empty()
+            && self.allowed_directories.is_empty()
+            && self.allowed_tools.is_empty()
+    }
+
+    /// The remediation as a `<remediation>` block for a tool result.
+    pub fn guidance(&self) -> String {
+        format!(
+            "<remediation>{}</remediation>",
+            serde_json::to_string(self).unwrap_or_default()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guidance() {
+        let remediation = Remediation {
+            allowed_rules: vec!["Bash(git status)".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            remediation.guidance(),
+            r#"<remediation>{"allowed_rules":["Bash(git status)"]}</remediation>"#
+        );
+        assert!(!remediation.is_empty());
+        assert!(Remediation::default().is_empty());
+    }
+}
@@ -313,9 +313,21 @@ impl PluginLoader {
         plugin_name: &str,
         resources: &mut PluginResources,
     ) -> Result<(), PluginError> {
+        resources
+            .mcp_servers
+            .extend(Self::load_mcp_servers(plugin, plugin_name).await?);
+        Ok(())
+    }
+
+    /// Namespaced MCP servers from the plugin's `.mcp.json`, with
+    /// `${CLAUDE_PLUGIN_ROOT}` resolved.
+    pub(crate) async fn load_mcp_servers(
+        plugin: &PluginDescriptor,
+        plugin_name: &str,
+    ) -> Result<HashMap<String, McpServerConfig>, PluginError> {
         let mcp_path = plugin.mcp_config_path();
         if !mcp_path.exists() {
-            return Ok(());
+            return Ok(HashMap::new());
         }
 
         let content = tokio::fs::read_to_string(&mcp_path).await?;
@@ -333,13 +345,16 @@ impl PluginLoader {
             })?;
 
         let plugin_root = plugin.root_dir();
-        for (name, server_config) in config.mcp_servers {
-            let namespaced_name = namespace::namespaced(plugin_name, &name);
-            let resolved = resolve_mcp_config(server_config, plugin_root);
-            resources.mcp_servers.insert(namespaced_name, resolved);
-        }
-
-        Ok(())
+        Ok(config
+            .mcp_servers
+            .into_iter()
+            .map(|(name, server_config)| {
+                (
+                    namespace::namespaced(plugin_name, &name),
+                    resolve_mcp_config(server_config, plugin_root),
+                )
+            })
+            .collect())
     }
 }
 
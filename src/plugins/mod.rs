@@ -41,3 +41,5 @@ pub use error::PluginError;
 pub use loader::PluginHookEntry;
 pub use manager::PluginManager;
 pub use manifest::{PluginAuthor, PluginDescriptor, PluginManifest};
+
+pub(crate) use loader::PluginLoader;
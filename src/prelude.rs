@@ -29,6 +29,7 @@ pub use crate::ClientBuilder;
 pub use crate::common::{ContentSource, Index, IndexRegistry, Named, SourceType, ToolRestricted};
 
 // Tools
+pub use crate::tool;
 pub use crate::tools::{ExecutionContext, SchemaTool, Tool, ToolAccess, ToolRegistry};
 pub use crate::types::ToolResult;
 
@@ -3,6 +3,7 @@
 use std::path::Path;
 
 use crate::client::FRONTIER_MODEL;
+use crate::common::clock;
 
 /// Generates the environment block with runtime information.
 pub fn environment_block(
@@ -17,7 +18,7 @@ pub fn environment_block(
         .map(|p| p.display().to_string())
         .unwrap_or_else(|| ".".to_string());
 
-    let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let date = clock::today().format("%Y-%m-%d").to_string();
     let git_status = if is_git_repo { "Yes" } else { "No" };
 
     format!(
@@ -0,0 +1,587 @@
+//! Structured audit log of security violations.
+//!
+//! Every tool call blocked by the [`SecurityGuard`](super::SecurityGuard) —
+//! a path escaping the sandbox, a denied path, a blocked bash command or a
+//! symlink pointing outside the sandbox — becomes an [`AuditEvent`] tagged
+//! with the session and tool, and is written to each [`AuditSink`] of the
+//! agent's [`AuditLog`]. Failing sinks are logged and never block the call.
+//!
+//! ```rust,no_run
+//! use claude_agent::security::audit::{AuditEventKind, AuditLog, AuditQuery, JsonlAuditSink};
+//!
+//! # async fn example() -> claude_agent::Result<()> {
+//! let log = AuditLog::new().sink(JsonlAuditSink::open("audit.jsonl").await?);
+//! // Agent::builder().audit_log(log.clone()) ...
+//! let escapes = log
+//!     .query(&AuditQuery::new().kind(AuditEventKind::SymlinkEscape))
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::AsyncWriteExt;
+use tracing::warn;
+
+use super::SecurityError;
+use crate::common::clock;
+
+/// Events kept by [`MemoryAuditSink::new`].
+const DEFAULT_MEMORY_CAPACITY: usize = 10_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEventKind {
+    /// A path outside the sandbox root and allowed directories.
+    SandboxViolation,
+    /// A path matching a denied pattern or missing from a tool's allowed paths.
+    DeniedPath,
+    /// A bash command rejected by the [`BashPolicy`](super::bash::BashPolicy).
+    BlockedCommand,
+    /// A symlink resolving outside the sandbox, or nested too deeply.
+    SymlinkEscape,
+    /// A write, or a bash command off the allowlist, under a read-only
+    /// [`SecurityPolicy`](super::SecurityPolicy).
+    ReadOnly,
+}
+
+impl AuditEventKind {
+    /// Kind of violation `error` reports; `None` for errors that are not
+    /// violations (I/O failures, invalid input).
+    pub fn of(error: &SecurityError) -> Option<Self> {
+        match error {
+            SecurityError::PathEscape(_) | SecurityError::NotWithinSandbox(_) => {
+                Some(Self::SandboxViolation)
+            }
+            SecurityError::DeniedPath(_) => Some(Self::DeniedPath),
+            SecurityError::BashBlocked(_) => Some(Self::BlockedCommand),
+            SecurityError::AbsoluteSymlink(_) | SecurityError::SymlinkDepthExceeded { .. } => {
+                Some(Self::SymlinkEscape)
+            }
+            SecurityError::ReadOnly(_) => Some(Self::ReadOnly),
+            SecurityError::InvalidPath(_)
+            | SecurityError::Io(_)
+            | SecurityError::ResourceLimit(_) => None,
+        }
+    }
+}
+
+/// One blocked operation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub timestamp: DateTime<Utc>,
+    pub kind: AuditEventKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+    /// Offending path, for path and symlink violations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// Bash command of the blocked call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    pub reason: String,
+}
+
+impl AuditEvent {
+    pub fn new(kind: AuditEventKind, reason: impl Into<String>) -> Self {
+        Self {
+            timestamp: clock::now_utc(),
+            kind,
+            session_id: None,
+            tool_name: None,
+            path: None,
+            command: None,
+            reason: reason.into(),
+        }
+    }
+
+    /// Event for `error`, or `None` if it is not a violation.
+    pub fn from_error(error: &SecurityError) -> Option<Self> {
+        let kind = AuditEventKind::of(error)?;
+        let path = match error {
+            SecurityError::PathEscape(p)
+            | SecurityError::NotWithinSandbox(p)
+            | SecurityError::DeniedPath(p)
+            | SecurityError::AbsoluteSymlink(p)
+            | SecurityError::SymlinkDepthExceeded { path: p, .. } => Some(p.clone()),
+            _ => None,
+        };
+        Some(Self {
+            path,
+            ..Self::new(kind, error.to_string())
+        })
+    }
+
+    /// Event for a call of `tool_name` with `input` that failed with `error`.
+    pub fn for_tool_call(
+        session_id: impl Into<String>,
+        tool_name: impl Into<String>,
+        input: &Value,
+        error: &SecurityError,
+    ) -> Option<Self> {
+        let mut event = Self::from_error(error)?.session(session_id).tool(tool_name);
+        event.command = input
+            .get("command")
+            .and_then(Value::as_str)
+            .map(String::from);
+        Some(event)
+    }
+
+    pub fn session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    pub fn tool(mut self, tool_name: impl Into<String>) -> Self {
+        self.tool_name = Some(tool_name.into());
+        self
+    }
+
+    pub fn path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub fn command(mut self, command: impl Into<String>) -> Self {
+        self.command = Some(command.into());
+        self
+    }
+}
+
+/// Filter for [`AuditSink::query`]. Results are oldest first.
+#[derive(Clone, Debug, Default)]
+pub struct AuditQuery {
+    pub session_id: Option<String>,
+    pub kind: Option<AuditEventKind>,
+    pub tool_name: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    /// Return at most this many of the most recent matches.
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    pub fn kind(mut self, kind: AuditEventKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    pub fn tool(mut self, tool_name: impl Into<String>) -> Self {
+        self.tool_name = Some(tool_name.into());
+        self
+    }
+
+    pub fn since(mut self, since: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn matches(&self, event: &AuditEvent) -> bool {
+        self.session_id
+            .as_ref()
+            .is_none_or(|id| event.session_id.as_ref() == Some(id))
+            && self.kind.is_none_or(|kind| event.kind == kind)
+            && self
+                .tool_name
+                .as_ref()
+                .is_none_or(|name| event.tool_name.as_ref() == Some(name))
+            && self.since.is_none_or(|since| event.timestamp >= since)
+    }
+
+    fn apply<'a>(&self, events: impl Iterator<Item = &'a AuditEvent>) -> Vec<AuditEvent> {
+        let mut matches: Vec<AuditEvent> = events.filter(|e| self.matches(e)).cloned().collect();
+        if let Some(limit) = self.limit {
+            matches.drain(..matches.len().saturating_sub(limit));
+        }
+        matches
+    }
+}
+
+/// Destination for [`AuditEvent`]s that can be searched later.
+#[async_trait]
+pub trait AuditSink: Send + Sync {
+    async fn record(&self, event: &AuditEvent) -> crate::Result<()>;
+
+    async fn query(&self, query: &AuditQuery) -> crate::Result<Vec<AuditEvent>>;
+}
+
+/// Keeps the most recent events in memory.
+pub struct MemoryAuditSink {
+    events: Mutex<VecDeque<AuditEvent>>,
+    capacity: usize,
+}
+
+impl MemoryAuditSink {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_MEMORY_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            events: Mutex::new(VecDeque::new()),
+            capacity: capacity.max(1),
+        }
+    }
+}
+
+impl Default for MemoryAuditSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl AuditSink for MemoryAuditSink {
+    async fn record(&self, event: &AuditEvent) -> crate::Result<()> {
+        let mut events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+        if events.len() == self.capacity {
+            events.pop_front();
+        }
+        events.push_back(event.clone());
+        Ok(())
+    }
+
+    async fn query(&self, query: &AuditQuery) -> crate::Result<Vec<AuditEvent>> {
+        let events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(query.apply(events.iter()))
+    }
+}
+
+/// Appends events as JSON lines to a file; queries scan the file.
+pub struct JsonlAuditSink {
+    path: PathBuf,
+    file: tokio::sync::Mutex<tokio::fs::File>,
+}
+
+impl JsonlAuditSink {
+    /// Open `path` for appending, creating it and its parent directories if needed.
+    pub async fn open(path: impl Into<PathBuf>) -> crate::Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+        Ok(Self {
+            path,
+            file: tokio::sync::Mutex::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[async_trait]
+impl AuditSink for JsonlAuditSink {
+    async fn record(&self, event: &AuditEvent) -> crate::Result<()> {
+        let mut line = serde_json::to_vec(event)?;
+        line.push(b'\n');
+        let mut file = self.file.lock().await;
+        file.write_all(&line).await?;
+        file.flush().await?;
+        Ok(())
+    }
+
+    async fn query(&self, query: &AuditQuery) -> crate::Result<Vec<AuditEvent>> {
+        // Hold the writer lock so a half-written line is never read.
+        let _file = self.file.lock().await;
+        let content = tokio::fs::read_to_string(&self.path).await?;
+        let events: Vec<AuditEvent> = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(event) => Some(event),
+                Err(e) => {
+                    warn!(error = %e, path = %self.path.display(), "Skipping malformed audit line");
+                    None
+                }
+            })
+            .collect();
+        Ok(query.apply(events.iter()))
+    }
+}
+
+/// Stores events in a `security_audit` table, e.g. in the database of a
+/// [`SqlitePersistence`](crate::session::SqlitePersistence).
+#[cfg(feature = "sqlite")]
+pub struct SqliteAuditSink {
+    pool: sqlx::SqlitePool,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteAuditSink {
+    /// Use `pool`, creating the table and its indexes if needed.
+    pub async fn new(pool: sqlx::SqlitePool) -> crate::Result<Self> {
+        for statement in [
+            "CREATE TABLE IF NOT EXISTS security_audit (\
+                 id INTEGER PRIMARY KEY AUTOINCREMENT, \
+                 timestamp INTEGER NOT NULL, \
+                 kind TEXT NOT NULL, \
+                 session_id TEXT, \
+                 tool_name TEXT, \
+                 event TEXT NOT NULL)",
+            "CREATE INDEX IF NOT EXISTS idx_security_audit_session \
+                 ON security_audit (session_id, timestamp)",
+            "CREATE INDEX IF NOT EXISTS idx_security_audit_kind \
+                 ON security_audit (kind, timestamp)",
+        ] {
+            sqlx::query(statement)
+                .execute(&pool)
+                .await
+                .map_err(sqlite_err)?;
+        }
+        Ok(Self { pool })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn sqlite_err(e: sqlx::Error) -> crate::Error {
+    crate::Error::Io(std::io::Error::other(e))
+}
+
+#[cfg(feature = "sqlite")]
+#[async_trait]
+impl AuditSink for SqliteAuditSink {
+    async fn record(&self, event: &AuditEvent) -> crate::Result<()> {
+        let kind = serde_json::to_value(event.kind)?;
+        sqlx::query(
+            "INSERT INTO security_audit (timestamp, kind, session_id, tool_name, event) \
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )
+        .bind(event.timestamp.timestamp_millis())
+        .bind(kind.as_str())
+        .bind(event.session_id.as_deref())
+        .bind(event.tool_name.as_deref())
+        .bind(serde_json::to_string(event)?)
+        .execute(&self.pool)
+        .await
+        .map_err(sqlite_err)?;
+        Ok(())
+    }
+
+    async fn query(&self, query: &AuditQuery) -> crate::Result<Vec<AuditEvent>> {
+        use sqlx::Row;
+
+        let kind = query.kind.map(serde_json::to_value).transpose()?;
+        let rows = sqlx::query(
+            "SELECT event FROM (\
+                 SELECT id, event FROM security_audit \
+                 WHERE (?1 IS NULL OR session_id = ?1) AND (?2 IS NULL OR kind = ?2) \
+                   AND (?3 IS NULL OR tool_name = ?3) AND (?4 IS NULL OR timestamp >= ?4) \
+                 ORDER BY id DESC LIMIT ?5) \
+             ORDER BY id",
+        )
+        .bind(query.session_id.as_deref())
+        .bind(kind.as_ref().and_then(Value::as_str))
+        .bind(query.tool_name.as_deref())
+        .bind(query.since.map(|t| t.timestamp_millis()))
+        .bind(query.limit.map_or(-1, |l| l as i64))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(sqlite_err)?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                let event: &str = row.try_get("event").ok()?;
+                serde_json::from_str(event).ok()
+            })
+            .collect())
+    }
+}
+
+/// Fans events out to its sinks; cheap to clone.
+#[derive(Clone, Default)]
+pub struct AuditLog {
+    sinks: Arc<[Arc<dyn AuditSink>]>,
+}
+
+impl AuditLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `sink`; the first sink answers [`query`](Self::query).
+    pub fn sink<S: AuditSink + 'static>(self, sink: S) -> Self {
+        self.shared_sink(Arc::new(sink))
+    }
+
+    pub fn shared_sink(self, sink: Arc<dyn AuditSink>) -> Self {
+        let sinks = self.sinks.iter().cloned().chain([sink]).collect();
+        Self { sinks }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.sinks.is_empty()
+    }
+
+    pub async fn record(&self, event: AuditEvent) {
+        warn!(
+            kind = ?event.kind,
+            session_id = event.session_id.as_deref().unwrap_or_default(),
+            tool = event.tool_name.as_deref().unwrap_or_default(),
+            reason = %event.reason,
+            "Security violation"
+        );
+        for sink in self.sinks.iter() {
+            if let Err(e) = sink.record(&event).await {
+                warn!(error = %e, "Failed to record audit event");
+            }
+        }
+    }
+
+    /// Events of the first sink matching `query`; empty without sinks.
+    pub async fn query(&self, query: &AuditQuery) -> crate::Result<Vec<AuditEvent>> {
+        match self.sinks.first() {
+            Some(sink) => sink.query(query).await,
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+impl std::fmt::Debug for AuditLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuditLog")
+            .field("sinks", &self.sinks.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_error() {
+        let event = AuditEvent::for_tool_call(
+            "s1",
+            "Bash",
+            &json!({"command": "rm -rf /"}),
+            &SecurityError::BashBlocked("dangerous".into()),
+        )
+        .unwrap();
+        assert_eq!(event.kind, AuditEventKind::BlockedCommand);
+        assert_eq!(event.command.as_deref(), Some("rm -rf /"));
+        assert_eq!(event.session_id.as_deref(), Some("s1"));
+
+        let event =
+            AuditEvent::from_error(&SecurityError::AbsoluteSymlink("/tmp/x".into())).unwrap();
+        assert_eq!(event.kind, AuditEventKind::SymlinkEscape);
+        assert_eq!(event.path, Some(PathBuf::from("/tmp/x")));
+
+        assert!(AuditEvent::from_error(&SecurityError::InvalidPath("".into())).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_memory_query() {
+        let sink = MemoryAuditSink::with_capacity(3);
+        for (session, kind) in [
+            ("a", AuditEventKind::DeniedPath),
+            ("a", AuditEventKind::SandboxViolation),
+            ("b", AuditEventKind::DeniedPath),
+            ("a", AuditEventKind::DeniedPath),
+        ] {
+            sink.record(&AuditEvent::new(kind, "x").session(session))
+                .await
+                .unwrap();
+        }
+
+        let all = sink.query(&AuditQuery::new()).await.unwrap();
+        assert_eq!(all.len(), 3);
+        let denied = sink
+            .query(
+                &AuditQuery::new()
+                    .session("a")
+                    .kind(AuditEventKind::DeniedPath),
+            )
+            .await
+            .unwrap();
+        assert_eq!(denied.len(), 1);
+        let latest = sink.query(&AuditQuery::new().limit(1)).await.unwrap();
+        assert_eq!(latest[0].session_id.as_deref(), Some("a"));
+    }
+
+    #[tokio::test]
+    async fn test_jsonl_sink() {
+        let dir = tempfile::tempdir().unwrap();
+        let sink = JsonlAuditSink::open(dir.path().join("logs/audit.jsonl"))
+            .await
+            .unwrap();
+        let log = AuditLog::new().sink(sink);
+        log.record(AuditEvent::new(AuditEventKind::DeniedPath, "denied").path("/etc/shadow"))
+            .await;
+        log.record(AuditEvent::new(AuditEventKind::BlockedCommand, "blocked").tool("Bash"))
+            .await;
+
+        let events = log.query(&AuditQuery::new().tool("Bash")).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].reason, "blocked");
+
+        let content = std::fs::read_to_string(dir.path().join("logs/audit.jsonl")).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(content.contains(r#""kind":"denied_path""#));
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_sqlite_sink() {
+        let dir = tempfile::tempdir().unwrap();
+        let persistence = crate::session::SqlitePersistence::open(dir.path().join("s.db"))
+            .await
+            .unwrap();
+        let sink = SqliteAuditSink::new(persistence.pool().clone())
+            .await
+            .unwrap();
+        for (session, kind) in [
+            ("a", AuditEventKind::DeniedPath),
+            ("b", AuditEventKind::SymlinkEscape),
+            ("a", AuditEventKind::BlockedCommand),
+        ] {
+            sink.record(&AuditEvent::new(kind, "x").session(session))
+                .await
+                .unwrap();
+        }
+
+        let events = sink.query(&AuditQuery::new().session("a")).await.unwrap();
+        let kinds: Vec<_> = events.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            [AuditEventKind::DeniedPath, AuditEventKind::BlockedCommand]
+        );
+        let escapes = sink
+            .query(&AuditQuery::new().kind(AuditEventKind::SymlinkEscape))
+            .await
+            .unwrap();
+        assert_eq!(escapes[0].session_id.as_deref(), Some("b"));
+        let latest = sink.query(&AuditQuery::new().limit(1)).await.unwrap();
+        assert_eq!(latest[0].kind, AuditEventKind::BlockedCommand);
+    }
+}
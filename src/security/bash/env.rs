@@ -167,6 +167,65 @@ const BLOCKED_ENV_PATTERNS: &[&str] = &[
 
 const SAFE_PATH: &str = "/usr/local/bin:/usr/bin:/bin:/usr/sbin:/sbin";
 
+/// Which variables spawned commands, hooks and MCP stdio servers inherit,
+/// on top of the built-in allowlist and blocklist.
+///
+/// Names ending in `*` match by prefix (`AWS_*`). Removals win over the
+/// allowlist, and the built-in blocklist wins over both and over injected
+/// values. `PATH` is reset to a safe default unless injected.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnvPolicy {
+    allowed: Vec<String>,
+    removed: Vec<String>,
+    injected: HashMap<String, String>,
+}
+
+impl EnvPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inherit `name` from the parent environment.
+    pub fn allow(mut self, name: impl Into<String>) -> Self {
+        self.allowed.push(name.into());
+        self
+    }
+
+    /// Never inherit `name`, even when allowlisted.
+    pub fn remove(mut self, name: impl Into<String>) -> Self {
+        self.removed.push(name.into());
+        self
+    }
+
+    /// Set `key` to `value` regardless of the parent environment.
+    pub fn set(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.injected.insert(key.into(), value.into());
+        self
+    }
+
+    /// Sanitize the current process environment.
+    pub fn sanitize_current(&self) -> SanitizedEnv {
+        self.sanitize(std::env::vars())
+    }
+
+    pub fn sanitize(&self, env: impl Iterator<Item = (String, String)>) -> SanitizedEnv {
+        let inherited = env.filter(|(key, _)| {
+            !matches_any(&self.removed, key)
+                && (SanitizedEnv::is_safe(key) || matches_any(&self.allowed, key))
+        });
+        SanitizedEnv::filtered(inherited).vars(self.injected.clone())
+    }
+}
+
+fn matches_any(patterns: &[String], key: &str) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| match pattern.strip_suffix('*') {
+            Some(prefix) => key.starts_with(prefix),
+            None => key == pattern,
+        })
+}
+
 #[derive(Debug, Clone)]
 pub struct SanitizedEnv {
     vars: HashMap<String, String>,
@@ -178,19 +237,12 @@ impl SanitizedEnv {
     }
 
     pub fn from_env(env: impl Iterator<Item = (String, String)>) -> Self {
-        let mut vars = HashMap::new();
-
-        for (key, value) in env {
-            if Self::is_blocked(&key) {
-                continue;
-            }
-            if Self::is_safe(&key) {
-                vars.insert(key, value);
-            }
-        }
+        Self::filtered(env.filter(|(key, _)| Self::is_safe(key)))
+    }
 
+    fn filtered(env: impl Iterator<Item = (String, String)>) -> Self {
+        let mut vars: HashMap<_, _> = env.filter(|(key, _)| !Self::is_blocked(key)).collect();
         vars.insert("PATH".to_string(), SAFE_PATH.to_string());
-
         Self { vars }
     }
 
@@ -341,4 +393,51 @@ mod tests {
         assert!(!sanitized.vars.contains_key("YARN_REGISTRY"));
         assert!(!sanitized.vars.contains_key("CARGO_REGISTRIES_EVIL"));
     }
+
+    #[test]
+    fn test_env_policy() {
+        let env = vec![
+            ("HOME".to_string(), "/home/user".to_string()),
+            ("USER".to_string(), "user".to_string()),
+            ("AWS_SECRET_ACCESS_KEY".to_string(), "secret".to_string()),
+            ("AWS_REGION".to_string(), "us-east-1".to_string()),
+            ("GITHUB_TOKEN".to_string(), "token".to_string()),
+            ("RUSTFLAGS".to_string(), "-C evil".to_string()),
+        ];
+        let policy = EnvPolicy::new()
+            .allow("AWS_*")
+            .allow("RUSTFLAGS")
+            .remove("AWS_SECRET_*")
+            .remove("USER")
+            .set("CI", "true")
+            .set("PATH", "/opt/bin:/usr/bin")
+            .set("LD_PRELOAD", "/evil.so");
+        let sanitized = policy.sanitize(env.into_iter());
+        let vars = sanitized.get_vars();
+
+        assert!(vars.contains_key("HOME"));
+        assert!(vars.contains_key("AWS_REGION"));
+        assert!(!vars.contains_key("AWS_SECRET_ACCESS_KEY"));
+        assert!(!vars.contains_key("USER"));
+        assert!(!vars.contains_key("GITHUB_TOKEN"));
+        assert!(!vars.contains_key("RUSTFLAGS"));
+        assert!(!vars.contains_key("LD_PRELOAD"));
+        assert_eq!(vars.get("CI").unwrap(), "true");
+        assert_eq!(vars.get("PATH").unwrap(), "/opt/bin:/usr/bin");
+    }
+
+    #[test]
+    fn test_default_policy_matches_from_env() {
+        let env = || {
+            vec![
+                ("HOME".to_string(), "/home/user".to_string()),
+                ("SECRET".to_string(), "x".to_string()),
+            ]
+            .into_iter()
+        };
+        assert_eq!(
+            EnvPolicy::new().sanitize(env()).get_vars(),
+            SanitizedEnv::from_env(env()).get_vars()
+        );
+    }
 }
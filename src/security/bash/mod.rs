@@ -2,6 +2,14 @@
 
 mod env;
 mod parser;
+mod pattern;
+mod policy;
+mod read_only;
+mod risk;
 
-pub use env::SanitizedEnv;
-pub use parser::{BashAnalysis, BashAnalyzer, BashPolicy, ReferencedPath, SecurityConcern};
+pub use env::{EnvPolicy, SanitizedEnv};
+pub use parser::{BashAnalysis, BashAnalyzer, ReferencedPath, SecurityConcern};
+pub use pattern::{CommandPattern, command_name, matches_all, simple_commands, unprivileged};
+pub use policy::{BashPolicy, CommandRule, RiskLevel};
+pub use read_only::{READ_ONLY_COMMANDS, check_read_only};
+pub use risk::{BashRisk, RiskCategory};
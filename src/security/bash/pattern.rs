@@ -0,0 +1,362 @@
+//! Command patterns such as `git commit:*`, matched one simple command at a
+//! time.
+//!
+//! Permission rules, skill and subagent tool scopes and [`BashPolicy`](super::BashPolicy)
+//! all split a command line with [`simple_commands`], so pipes, `&&`,
+//! subshells and substitutions are treated the same everywhere. Allow rules
+//! match each command as written; only deny and ask rules look through
+//! variable assignments and wrappers like `env` and `nohup`.
+
+use tree_sitter::{Node, Parser};
+
+use super::parser::bash_language;
+
+/// Commands that run the command following their own arguments unchanged,
+/// with those of their options that take a value.
+const WRAPPERS: &[(&str, &[&str])] = &[
+    ("env", &["-u", "--unset", "-C", "--chdir"]),
+    ("command", &[]),
+    ("builtin", &[]),
+    ("exec", &["-a"]),
+    ("nohup", &[]),
+    ("time", &["-f", "--format", "-o", "--output"]),
+    ("nice", &["-n", "--adjustment"]),
+    ("timeout", &["-s", "--signal", "-k", "--kill-after"]),
+];
+
+/// Wrappers that change who runs the command. Looked through only when
+/// searching for a command, never when deciding that a command is allowed.
+const PRIVILEGE_WRAPPERS: &[(&str, &[&str])] = &[
+    (
+        "sudo",
+        &[
+            "-u", "--user", "-g", "--group", "-C", "-D", "-h", "-p", "-r", "-t", "-U",
+        ],
+    ),
+    ("doas", &["-u", "-C"]),
+];
+
+/// The simple commands in `command`, as written: leading variable
+/// assignments and wrappers such as `env` stay in place.
+///
+/// `a && b | c` yields `a`, `b` and `c`; commands inside subshells and
+/// substitutions are listed after the command containing them. `export`,
+/// `declare`, `unset` and bare assignments are commands of their own, so an
+/// allow rule must cover them too. Commands that fail to parse are returned
+/// whole.
+pub fn simple_commands(command: &str) -> Vec<String> {
+    let mut parser = Parser::new();
+    let tree = parser
+        .set_language(&bash_language())
+        .ok()
+        .and_then(|_| parser.parse(command, None));
+    let Some(tree) = tree.filter(|tree| !tree.root_node().has_error()) else {
+        return vec![command.trim().to_string()];
+    };
+
+    let mut commands = Vec::new();
+    collect_commands(tree.root_node(), command, &mut commands);
+    commands
+}
+
+fn collect_commands(node: Node<'_>, source: &str, commands: &mut Vec<String>) {
+    let standalone = match node.kind() {
+        "command" | "declaration_command" | "unset_command" | "variable_assignments" => true,
+        "variable_assignment" => node.parent().is_none_or(|parent| {
+            !matches!(
+                parent.kind(),
+                "command" | "declaration_command" | "variable_assignments"
+            )
+        }),
+        _ => false,
+    };
+    if standalone {
+        commands.push(source[node.byte_range()].trim().to_string());
+    }
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        collect_commands(child, source, commands);
+    }
+}
+
+/// A pattern for one simple command.
+///
+/// `git commit:*` matches `git commit` and `git commit -m x` but not
+/// `git commit-tree`. A `*` elsewhere matches any text, so `npm run *` and
+/// `* --version` work. Anything else must match exactly.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CommandPattern(String);
+
+impl CommandPattern {
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self(pattern.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether the simple command `command` matches.
+    pub fn matches(&self, command: &str) -> bool {
+        match self.0.strip_suffix(":*") {
+            Some(prefix) => {
+                command == prefix
+                    || command
+                        .strip_prefix(prefix)
+                        .is_some_and(|rest| prefix.ends_with(' ') || rest.starts_with(' '))
+            }
+            None if self.0.contains('*') => matches_wildcard(&self.0, command),
+            None => command == self.0,
+        }
+    }
+
+    /// Whether every simple command of `command` matches, as an allow rule
+    /// needs: `git status && rm -rf .` is not covered by `git:*`.
+    pub fn matches_all(&self, command: &str) -> bool {
+        matches_all(std::slice::from_ref(self), command)
+    }
+
+    /// Whether any simple command of `command` matches, as deny and ask
+    /// rules need. Also looks through variable assignments, wrappers such as
+    /// `env`, `sudo` and `doas`, and at the bare name of commands run by
+    /// path, so `FOO=1 sudo /bin/rm -rf x` matches `rm:*`.
+    pub fn matches_any(&self, command: &str) -> bool {
+        simple_commands(command).iter().any(|cmd| {
+            [cmd.as_str(), unwrapped(cmd), unprivileged(cmd)]
+                .into_iter()
+                .any(|cmd| self.matches(cmd) || self.matches(&strip_command_path(cmd)))
+        })
+    }
+}
+
+impl From<&str> for CommandPattern {
+    fn from(pattern: &str) -> Self {
+        Self::new(pattern)
+    }
+}
+
+/// Whether every simple command of `command`, as written, matches one of
+/// `patterns`.
+pub fn matches_all(patterns: &[CommandPattern], command: &str) -> bool {
+    let commands = simple_commands(command);
+    !commands.is_empty()
+        && commands
+            .iter()
+            .all(|cmd| patterns.iter().any(|pattern| pattern.matches(cmd)))
+}
+
+/// The simple command `command` runs as another user through `sudo` or
+/// `doas`, without variable assignments and wrappers, or `command` itself.
+pub fn unprivileged(command: &str) -> &str {
+    let mut rest = command;
+    loop {
+        let next = unwrap(unwrapped(rest), PRIVILEGE_WRAPPERS);
+        if next.len() == rest.len() {
+            return rest;
+        }
+        rest = next;
+    }
+}
+
+/// `command` without leading variable assignments and [wrappers](WRAPPERS).
+fn unwrapped(command: &str) -> &str {
+    let mut rest = command;
+    loop {
+        let words = words(rest);
+        let assignments = words.iter().take_while(|(_, w)| is_assignment(w)).count();
+        let next = match words.get(assignments) {
+            Some(&(offset, _)) if assignments > 0 => unwrap(&rest[offset..], WRAPPERS),
+            _ => unwrap(rest, WRAPPERS),
+        };
+        if next.len() == rest.len() {
+            return rest;
+        }
+        rest = next;
+    }
+}
+
+/// The name `command` runs, without any directory: `git` for `/usr/bin/git log`.
+pub fn command_name(command: &str) -> &str {
+    let first = words(command).first().map_or("", |&(_, word)| word);
+    first.rsplit('/').next().unwrap_or(first)
+}
+
+fn strip_command_path(command: &str) -> String {
+    match words(command).first() {
+        Some(&(_, first)) if first.contains('/') => {
+            format!("{}{}", command_name(command), &command[first.len()..])
+        }
+        _ => command.to_string(),
+    }
+}
+
+fn matches_wildcard(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    let last = parts.pop().unwrap_or_default();
+    for part in parts {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// `command` without the leading `wrappers` and their arguments.
+fn unwrap<'a>(command: &'a str, wrappers: &[(&str, &[&str])]) -> &'a str {
+    let mut rest = command;
+    loop {
+        let words = words(rest);
+        let Some(&(_, name)) = words.first() else {
+            return rest;
+        };
+        let name = name.rsplit('/').next().unwrap_or(name);
+        let Some(&(_, valued)) = wrappers.iter().find(|(wrapper, _)| *wrapper == name) else {
+            return rest;
+        };
+        // `timeout` takes a duration before the command.
+        let mut positional = usize::from(name == "timeout");
+        let mut i = 1;
+        while let Some(&(_, word)) = words.get(i) {
+            if word == "--" {
+                i += 1;
+                break;
+            }
+            if word.starts_with('-') {
+                i += if valued.contains(&word) { 2 } else { 1 };
+            } else if name == "env" && is_assignment(word) {
+                i += 1;
+            } else if positional > 0 {
+                positional -= 1;
+                i += 1;
+            } else {
+                break;
+            }
+        }
+        match words.get(i) {
+            Some(&(offset, _)) => rest = &rest[offset..],
+            None => return rest,
+        }
+    }
+}
+
+fn is_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+/// Shell words of `command` with their byte offsets, quotes left in place.
+fn words(command: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in command.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match (c, quote) {
+            ('\\', q) if q != Some('\'') => escaped = true,
+            ('\'' | '"', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            (c, None) if c.is_whitespace() => {
+                if let Some(s) = start.take() {
+                    words.push((s, &command[s..i]));
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(i);
+    }
+    if let Some(s) = start {
+        words.push((s, &command[s..]));
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simple_commands() {
+        assert_eq!(
+            simple_commands("git add . && FOO=1 git commit -m 'a; b' | tee log"),
+            ["git add .", "FOO=1 git commit -m 'a; b'", "tee log"]
+        );
+        assert_eq!(
+            simple_commands("echo $(rm -rf /tmp/x) > out.txt"),
+            ["echo $(rm -rf /tmp/x)", "rm -rf /tmp/x"]
+        );
+        assert_eq!(
+            simple_commands(
+                "(cd src && env -u HOME A='b c' nohup cargo build) || timeout -s KILL 5 make"
+            ),
+            [
+                "cd src",
+                "env -u HOME A='b c' nohup cargo build",
+                "timeout -s KILL 5 make"
+            ]
+        );
+        assert_eq!(
+            simple_commands("export A=1 B=$(id); X=2; unset Y; ls"),
+            ["export A=1 B=$(id)", "id", "X=2", "unset Y", "ls"]
+        );
+        assert_eq!(simple_commands("sudo -u root rm x"), ["sudo -u root rm x"]);
+        assert_eq!(simple_commands("env"), ["env"]);
+    }
+
+    #[test]
+    fn test_pattern_matching() {
+        let commit = CommandPattern::new("git commit:*");
+        assert!(commit.matches("git commit"));
+        assert!(commit.matches("git commit -m x"));
+        assert!(!commit.matches("git commit-tree abc"));
+
+        assert!(CommandPattern::new("npm run *").matches("npm run test"));
+        assert!(CommandPattern::new("* --version").matches("cargo --version"));
+        assert!(!CommandPattern::new("a*b*b").matches("ab"));
+        assert!(CommandPattern::new("ls").matches("ls"));
+        assert!(!CommandPattern::new("ls").matches("ls -la"));
+    }
+
+    #[test]
+    fn test_compound_commands() {
+        let git = CommandPattern::new("git:*");
+        assert!(git.matches_all("git add . && git log | git show"));
+        let status = CommandPattern::new("git status:*");
+        for command in [
+            "LD_PRELOAD=/tmp/x.so git status",
+            "env LD_PRELOAD=/tmp/x.so git status",
+            "export LD_PRELOAD=/tmp/x.so; git status",
+            "nohup git status",
+        ] {
+            assert!(!status.matches_all(command), "{command}");
+        }
+        assert!(!git.matches_all("git status; (rm -rf .)"));
+        assert!(!git.matches_all("sudo git push"));
+
+        let rm = CommandPattern::new("rm:*");
+        assert!(rm.matches_any("ls && sudo /bin/rm -rf x"));
+        assert!(rm.matches_any("nohup rm -rf x &"));
+        assert!(rm.matches_any("FOO=1 env -u HOME BAR=2 rm x"));
+        assert!(rm.matches_any("X=1 sudo Y=2 nohup rm x"));
+        assert!(CommandPattern::new("export:*").matches_any("export A=1; ls"));
+        assert!(!rm.matches_any("echo rm"));
+
+        let patterns = ["git:*".into(), "cargo:*".into()];
+        assert!(matches_all(&patterns, "cargo fmt && git diff"));
+        assert!(!matches_all(&patterns, "cargo fmt && npm test"));
+        assert_eq!(command_name("/usr/bin/git log"), "git");
+        assert_eq!(unprivileged("sudo -u root env A=1 rm x"), "rm x");
+        assert_eq!(unprivileged("A=1 sudo B=2 rm x"), "rm x");
+    }
+}
@@ -0,0 +1,316 @@
+//! Bash command policy, loadable from JSON or YAML.
+//!
+//! ```json
+//! {
+//!   "blockedCommands": ["curl", "wget"],
+//!   "allowedCommands": ["git", "cargo", "ls", "cat", "grep"],
+//!   "commands": {
+//!     "git": {"deniedArgs": ["push --force", "-f"], "risk": "medium"},
+//!     "cargo": {"allowedSubcommands": ["build", "test", "check"]}
+//!   },
+//!   "maxRisk": "medium"
+//! }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::parser::SecurityConcern;
+
+/// How much harm a command can do, as judged by the policy author.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskLevel {
+    #[default]
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl fmt::Display for RiskLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+            Self::Critical => "critical",
+        })
+    }
+}
+
+/// Constraints on one command's arguments.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CommandRule {
+    /// Arguments the command may not be given. An entry of several words,
+    /// such as `push --force`, matches those words in sequence; `--opt`
+    /// also matches `--opt=value`.
+    pub denied_args: Vec<String>,
+    /// When non-empty, the first non-option argument must be one of these.
+    pub allowed_subcommands: Vec<String>,
+    pub risk: RiskLevel,
+}
+
+impl CommandRule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn deny_arg(mut self, arg: impl Into<String>) -> Self {
+        self.denied_args.push(arg.into());
+        self
+    }
+
+    pub fn allow_subcommand(mut self, subcommand: impl Into<String>) -> Self {
+        self.allowed_subcommands.push(subcommand.into());
+        self
+    }
+
+    pub fn risk(mut self, risk: RiskLevel) -> Self {
+        self.risk = risk;
+        self
+    }
+
+    fn check(&self, name: &str, args: &[&str]) -> Result<(), String> {
+        for denied in &self.denied_args {
+            let pattern: Vec<&str> = denied.split_whitespace().collect();
+            let matched = match pattern.as_slice() {
+                [] => false,
+                [single] => args.iter().any(|arg| {
+                    arg == single
+                        || (single.starts_with("--")
+                            && arg
+                                .strip_prefix(single)
+                                .is_some_and(|rest| rest.starts_with('=')))
+                }),
+                words => args.windows(words.len()).any(|window| window == words),
+            };
+            if matched {
+                return Err(format!("Denied argument for {}: {}", name, denied));
+            }
+        }
+
+        if !self.allowed_subcommands.is_empty() {
+            let subcommand = args.iter().find(|arg| !arg.starts_with('-'));
+            if !subcommand.is_some_and(|sub| self.allowed_subcommands.iter().any(|a| a == sub)) {
+                return Err(format!(
+                    "Subcommand not allowed for {}: {}",
+                    name,
+                    subcommand.unwrap_or(&"(none)")
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BashPolicy {
+    pub allow_command_substitution: bool,
+    pub allow_process_substitution: bool,
+    pub allow_eval: bool,
+    pub allow_remote_exec: bool,
+    pub allow_privilege_escalation: bool,
+    pub allow_variable_expansion: bool,
+    pub blocked_commands: HashSet<String>,
+    /// When non-empty, only these commands may run.
+    pub allowed_commands: HashSet<String>,
+    /// Argument constraints and risk levels by command name.
+    pub commands: HashMap<String, CommandRule>,
+    /// Commands whose rule rates them above this are blocked, as are
+    /// security concerns of higher [severity](SecurityConcern::severity).
+    pub max_risk: Option<RiskLevel>,
+}
+
+impl BashPolicy {
+    pub fn strict() -> Self {
+        Self {
+            blocked_commands: Self::default_blocked_commands(),
+            ..Default::default()
+        }
+    }
+
+    pub fn permissive() -> Self {
+        Self {
+            allow_command_substitution: true,
+            allow_process_substitution: true,
+            allow_eval: true,
+            allow_remote_exec: true,
+            allow_privilege_escalation: true,
+            allow_variable_expansion: true,
+            ..Default::default()
+        }
+    }
+
+    /// Load a policy from a `.json`, `.yaml` or `.yml` file. Fields left
+    /// out take their [`Default`] values.
+    pub fn from_file(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            crate::Error::Config(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        let invalid = |e: &dyn fmt::Display| {
+            crate::Error::Config(format!("Invalid bash policy {}: {}", path.display(), e))
+        };
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => serde_yaml_bw::from_str(&content).map_err(|e| invalid(&e)),
+            _ => serde_json::from_str(&content).map_err(|e| invalid(&e)),
+        }
+    }
+
+    pub fn default_blocked_commands() -> HashSet<String> {
+        [
+            "curl", "wget", "nc", "ncat", "netcat", "telnet", "ftp", "sftp", "scp", "rsync",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect()
+    }
+
+    pub fn blocked_commands(
+        mut self,
+        commands: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.blocked_commands = commands.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn allowed_commands(
+        mut self,
+        commands: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.allowed_commands = commands.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn command(mut self, name: impl Into<String>, rule: CommandRule) -> Self {
+        self.commands.insert(name.into(), rule);
+        self
+    }
+
+    pub fn max_risk(mut self, risk: RiskLevel) -> Self {
+        self.max_risk = Some(risk);
+        self
+    }
+
+    pub fn is_command_blocked(&self, command: &str) -> bool {
+        let base_command = command.split_whitespace().next().unwrap_or(command);
+        self.blocked_commands.contains(base_command)
+    }
+
+    /// Check one simple command, such as `git push --force origin`, against
+    /// the allowlist, the argument rules and the risk ceiling.
+    pub fn check_command(&self, command: &str) -> Result<(), String> {
+        let words: Vec<&str> = command
+            .split_whitespace()
+            .map(|w| w.trim_matches(['\'', '"']))
+            .collect();
+        let Some((first, args)) = words.split_first() else {
+            return Ok(());
+        };
+        let name = first.rsplit('/').next().unwrap_or(first);
+
+        if !self.allowed_commands.is_empty() && !self.allowed_commands.contains(name) {
+            return Err(format!("Command not in allowlist: {}", name));
+        }
+        let Some(rule) = self.commands.get(name) else {
+            return Ok(());
+        };
+        if let Some(max) = self.max_risk
+            && rule.risk > max
+        {
+            return Err(format!(
+                "Command {} is {} risk, above the allowed {}",
+                name, rule.risk, max
+            ));
+        }
+        rule.check(name, args)
+    }
+
+    pub fn allows(&self, concern: &SecurityConcern) -> bool {
+        match concern {
+            SecurityConcern::CommandSubstitution | SecurityConcern::BacktickSubstitution => {
+                self.allow_command_substitution
+            }
+            SecurityConcern::ProcessSubstitution => self.allow_process_substitution,
+            SecurityConcern::EvalUsage
+            | SecurityConcern::HeredocExecution
+            | SecurityConcern::IndirectCommand
+            | SecurityConcern::DynamicCode
+            | SecurityConcern::PipeToShell
+            | SecurityConcern::ObfuscatedCode => self.allow_eval,
+            // Scored, not blocked: `python -c` is routine.
+            SecurityConcern::InlineCode(_) => true,
+            SecurityConcern::RemoteExecution => self.allow_remote_exec,
+            SecurityConcern::PrivilegeEscalation => self.allow_privilege_escalation,
+            SecurityConcern::VariableExpansion => self.allow_variable_expansion,
+            SecurityConcern::DangerousCommand(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_policy() {
+        let policy: BashPolicy = serde_json::from_str(
+            r#"{
+                "allowVariableExpansion": true,
+                "blockedCommands": ["curl"],
+                "allowedCommands": ["git", "cargo", "ls"],
+                "commands": {
+                    "git": {"deniedArgs": ["push --force", "--force-with-lease"], "risk": "medium"},
+                    "cargo": {"allowedSubcommands": ["build", "test"], "risk": "high"}
+                },
+                "maxRisk": "medium"
+            }"#,
+        )
+        .unwrap();
+
+        assert!(policy.allow_variable_expansion);
+        assert!(!policy.allow_eval);
+        assert!(policy.is_command_blocked("curl"));
+        assert_eq!(policy.commands["git"].risk, RiskLevel::Medium);
+
+        assert!(policy.check_command("git push origin main").is_ok());
+        assert!(policy.check_command("/usr/bin/git log").is_ok());
+        assert!(policy.check_command("git push --force origin").is_err());
+        assert!(
+            policy
+                .check_command("git push --force-with-lease=main")
+                .is_err()
+        );
+        assert!(policy.check_command("cargo build").is_err()); // high risk
+        assert!(policy.check_command("python3 -c 'print(1)'").is_err());
+        assert!(policy.check_command("ls -la").is_ok());
+    }
+
+    #[test]
+    fn test_subcommands_and_yaml_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bash-policy.yaml");
+        std::fs::write(
+            &path,
+            "commands:\n  npm:\n    allowedSubcommands: [test, ci]\n    deniedArgs: [--ignore-scripts=false]\n",
+        )
+        .unwrap();
+        let policy = BashPolicy::from_file(&path).unwrap();
+
+        assert!(policy.check_command("npm --silent test").is_ok());
+        assert!(policy.check_command("npm publish").is_err());
+        assert!(policy.check_command("npm").is_err());
+        assert!(policy.check_command("make all").is_ok());
+
+        std::fs::write(&path, "commands: [").unwrap();
+        assert!(BashPolicy::from_file(&path).is_err());
+    }
+}
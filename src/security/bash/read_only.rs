@@ -0,0 +1,382 @@
+//! Allowlist of shell commands that cannot change the filesystem, enforced in
+//! read-only mode.
+
+use tree_sitter::{Node, Parser, Query, QueryCursor, StreamingIterator};
+
+use super::parser::bash_language;
+
+/// Commands allowed in read-only mode. Entries of two words allow only that
+/// subcommand.
+pub const READ_ONLY_COMMANDS: &[&str] = &[
+    "cat",
+    "head",
+    "tail",
+    "ls",
+    "tree",
+    "pwd",
+    "cd",
+    "echo",
+    "printf",
+    "true",
+    "false",
+    "test",
+    "[",
+    "wc",
+    "grep",
+    "egrep",
+    "fgrep",
+    "rg",
+    "find",
+    "file",
+    "stat",
+    "du",
+    "df",
+    "diff",
+    "cmp",
+    "cut",
+    "tr",
+    "sort",
+    "nl",
+    "jq",
+    "basename",
+    "dirname",
+    "realpath",
+    "readlink",
+    "which",
+    "whoami",
+    "id",
+    "uname",
+    "md5sum",
+    "sha1sum",
+    "sha256sum",
+    "git status",
+    "git log",
+    "git diff",
+    "git show",
+    "git blame",
+    "git ls-files",
+    "git rev-parse",
+    "git grep",
+];
+
+/// Arguments that make an allowed command write files or run other commands.
+const DENIED_ARGS: &[(&str, &[&str])] = &[
+    (
+        "find",
+        &[
+            "-delete", "-exec", "-execdir", "-ok", "-okdir", "-fprint", "-fprint0", "-fprintf",
+            "-fls",
+        ],
+    ),
+    ("sort", &["-o", "--output", "--compress-program"]),
+    ("rg", &["--pre"]),
+    ("file", &["-C", "--compile"]),
+    ("tree", &["-o"]),
+    ("printf", &["-v"]),
+    (
+        "git",
+        &[
+            "--output",
+            "--ext-diff",
+            "--textconv",
+            "-O",
+            "--open-files-in-pager",
+        ],
+    ),
+];
+
+/// Syntax allowed in read-only mode. Everything else is rejected: variable
+/// assignments, `export`/`declare`/`unset`, loops, functions and arithmetic
+/// can set variables such as `PAGER` or `GIT_EXTERNAL_DIFF` that allowed
+/// commands then run.
+const ALLOWED_NODES: &[&str] = &[
+    "program",
+    "list",
+    "pipeline",
+    "subshell",
+    "compound_statement",
+    "negated_command",
+    "redirected_statement",
+    "command",
+    "command_name",
+    "word",
+    "number",
+    "string",
+    "string_content",
+    "raw_string",
+    "ansi_c_string",
+    "translated_string",
+    "concatenation",
+    "brace_expression",
+    "simple_expansion",
+    "expansion",
+    "variable_name",
+    "special_variable_name",
+    "command_substitution",
+    "process_substitution",
+    "file_redirect",
+    "file_descriptor",
+    "heredoc_redirect",
+    "heredoc_start",
+    "heredoc_body",
+    "heredoc_content",
+    "heredoc_end",
+    "herestring_redirect",
+    "test_command",
+    "test_operator",
+    "binary_expression",
+    "unary_expression",
+    "parenthesized_expression",
+    "extglob_pattern",
+    "regex",
+    "comment",
+];
+
+/// Check that `command` only runs [`READ_ONLY_COMMANDS`] and redirects output
+/// nowhere but `/dev/null` or another descriptor.
+///
+/// Commands inside substitutions are checked too. Commands that fail to
+/// parse, syntax outside [`ALLOWED_NODES`] (which can point tools such as git
+/// at other programs), and expansions in the arguments of commands with denied
+/// arguments are rejected.
+pub fn check_read_only(command: &str) -> Result<(), String> {
+    let mut parser = Parser::new();
+    let tree = parser
+        .set_language(&bash_language())
+        .ok()
+        .and_then(|_| parser.parse(command, None))
+        .filter(|tree| !tree.root_node().has_error())
+        .ok_or("command could not be parsed")?;
+    check_syntax(tree.root_node(), command)?;
+    let query = Query::new(&bash_language(), "(command) @cmd (file_redirect) @redirect")
+        .map_err(|e| e.to_string())?;
+
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(&query, tree.root_node(), command.as_bytes());
+    while let Some(m) = matches.next() {
+        for capture in m.captures {
+            match capture.index {
+                0 => check_command(capture.node, command)?,
+                _ => check_redirect(&command[capture.node.byte_range()])?,
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_syntax(node: Node<'_>, source: &str) -> Result<(), String> {
+    let text = || source[node.byte_range()].trim();
+    if node.is_named() && !ALLOWED_NODES.contains(&node.kind()) {
+        return Err(match node.kind() {
+            "variable_assignment"
+            | "variable_assignments"
+            | "declaration_command"
+            | "unset_command" => format!("variable assignments are not allowed: {}", text()),
+            kind => format!("{} is not allowed in read-only mode: {}", kind, text()),
+        });
+    }
+    let mut cursor = node.walk();
+    let children: Vec<Node<'_>> = node.children(&mut cursor).collect();
+    // `${VAR:=value}` assigns as it expands.
+    if node.kind() == "expansion" && children.iter().any(|c| matches!(c.kind(), "=" | ":=")) {
+        return Err(format!("variable assignments are not allowed: {}", text()));
+    }
+    children
+        .into_iter()
+        .try_for_each(|child| check_syntax(child, source))
+}
+
+fn check_command(node: Node<'_>, source: &str) -> Result<(), String> {
+    let mut cursor = node.walk();
+    let Some(name_node) = node.child_by_field_name("name") else {
+        return Ok(());
+    };
+    let text = source[name_node.start_byte()..node.end_byte()].trim();
+    let Some(name) = literal(name_node, source) else {
+        return Err(format!("'{}' is not a read-only command", text));
+    };
+    let args: Vec<Option<String>> = node
+        .children_by_field_name("argument", &mut cursor)
+        .map(|arg| literal(arg, source))
+        .collect();
+
+    let allowed = READ_ONLY_COMMANDS.iter().any(|entry| {
+        let mut entry = entry.split(' ');
+        entry.next() == Some(name.as_str())
+            && entry
+                .next()
+                .is_none_or(|sub| args.first().is_some_and(|a| a.as_deref() == Some(sub)))
+    });
+    if !allowed {
+        return Err(format!("'{}' is not a read-only command", text));
+    }
+
+    let denied: Vec<&str> = DENIED_ARGS
+        .iter()
+        .filter(|(command, _)| *command == name)
+        .flat_map(|(_, args)| args.iter().copied())
+        .collect();
+    if denied.is_empty() {
+        return Ok(());
+    }
+    // An expansion could produce any of the denied arguments at run time.
+    if args.iter().any(Option::is_none) {
+        return Err(format!(
+            "expansions in '{name}' arguments are not allowed: {}",
+            text
+        ));
+    }
+    for arg in denied {
+        if args.iter().flatten().any(|a| passes_option(a, arg)) {
+            return Err(format!("'{name} {arg}' is not allowed"));
+        }
+    }
+    Ok(())
+}
+
+/// The value of a command name or argument with quotes and escapes removed,
+/// built from its `word`, `string` and `raw_string` nodes. `None` when it
+/// contains an expansion or another part only known at run time.
+fn literal(node: Node<'_>, source: &str) -> Option<String> {
+    let text = &source[node.byte_range()];
+    let mut cursor = node.walk();
+    match node.kind() {
+        "word" | "number" => Some(unescape(text)),
+        "raw_string" => text
+            .strip_prefix('\'')
+            .and_then(|t| t.strip_suffix('\''))
+            .map(str::to_string),
+        "string" => node
+            .named_children(&mut cursor)
+            .map(|part| {
+                (part.kind() == "string_content").then(|| unescape(&source[part.byte_range()]))
+            })
+            .collect(),
+        "command_name" | "concatenation" => node
+            .named_children(&mut cursor)
+            .map(|part| literal(part, source))
+            .collect(),
+        _ => None,
+    }
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.extend(chars.next()),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Whether `arg` passes the option `denied`: exactly, with an `=value`, as an
+/// abbreviated long option (`--out` for `--output`), or as one letter of a
+/// short option cluster (`-uo` for `-o`).
+fn passes_option(arg: &str, denied: &str) -> bool {
+    if let Some(long) = denied.strip_prefix("--") {
+        let given = arg.split_once('=').map_or(arg, |(name, _)| name);
+        given
+            .strip_prefix("--")
+            .is_some_and(|given| !given.is_empty() && long.starts_with(given))
+    } else if let Some(short) = denied.strip_prefix('-').filter(|s| s.len() == 1) {
+        arg.len() > 1 && arg.starts_with('-') && !arg.starts_with("--") && arg[1..].contains(short)
+    } else {
+        arg == denied
+    }
+}
+
+fn check_redirect(redirect: &str) -> Result<(), String> {
+    let op = redirect.trim_start_matches(|c: char| c.is_ascii_digit());
+    if op.starts_with('<') && !op.starts_with("<>") {
+        return Ok(());
+    }
+    let target = op.trim_start_matches(['<', '>', '&', '|']).trim();
+    let duplicates_fd =
+        op.starts_with(">&") && target.chars().all(|c| c.is_ascii_digit() || c == '-');
+    if target == "/dev/null" || duplicates_fd {
+        return Ok(());
+    }
+    Err(format!("output redirection is not allowed: {}", redirect))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_only_commands_allowed() {
+        for command in [
+            "ls -la src",
+            "cat Cargo.toml | grep version",
+            "git status && git log --oneline -5",
+            "find . -name '*.rs' 2>/dev/null | wc -l",
+            "rg TODO src 2>&1 | head",
+            "echo $(git rev-parse HEAD)",
+            "sort < input.txt",
+            "find . -name \"*.rs\" -type f",
+            "git grep -n 'TODO' -- src",
+            "rg --pre-glob '*.gz' needle",
+            "sort -u -k2 data.txt",
+            "tree -L 2 src",
+            "echo \"$HOME\"",
+        ] {
+            assert!(check_read_only(command).is_ok(), "{command}");
+        }
+    }
+
+    #[test]
+    fn test_writes_rejected() {
+        for command in [
+            "rm -rf target",
+            "echo hi > notes.txt",
+            "cat a >> b",
+            "ls | tee out.txt",
+            "find . -name '*.tmp' -delete",
+            "sort -o sorted.txt input.txt",
+            "git checkout main",
+            "git diff --output=patch.diff",
+            "GIT_EXTERNAL_DIFF=./x.sh git diff",
+            "echo $(touch marker)",
+            "/bin/rm file",
+            "cat <<EOF > file\nhi\nEOF",
+            "echo 'unterminated",
+        ] {
+            assert!(check_read_only(command).is_err(), "{command}");
+        }
+    }
+
+    #[test]
+    fn test_quoted_and_expanded_arguments_rejected() {
+        for command in [
+            "find . '-delete'",
+            "find . \"-exec\" rm {} \\;",
+            "find . \\-delete",
+            "rg \"--pre=sh\" x",
+            "rg \"--pre\"=sh x",
+            "find . -$(echo delete)",
+            "find . $ACTION",
+            "git diff \"$OPTS\"",
+            "sort -uo out.txt in.txt",
+            "sort --out=sorted.txt data.txt",
+            "tree -o listing.txt .",
+            "git grep -O x",
+            "git grep --open-files-in-pager=vim x",
+            "git grep --open-files x",
+            "\"rm\" file",
+            "export GIT_EXTERNAL_DIFF=/tmp/x; git diff",
+            "declare -x PAGER='sh -c id'; git log",
+            "readonly GIT_PAGER=sh; git log",
+            "unset GIT_CONFIG_NOSYSTEM; git log",
+            "PAGER='sh -c id'; git log",
+            "echo ${GIT_PAGER:=sh}; git log",
+            "printf -v PAGER sh; git log",
+            "for PAGER in sh; do git log; done",
+            "f() { ls; }; f",
+        ] {
+            assert!(check_read_only(command).is_err(), "{command}");
+        }
+    }
+}
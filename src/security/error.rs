@@ -31,4 +31,7 @@ pub enum SecurityError {
 
     #[error("resource limit error: {0}")]
     ResourceLimit(String),
+
+    #[error("read-only mode: {0}")]
+    ReadOnly(String),
 }
@@ -1,7 +1,7 @@
 //! Secure file handle with TOCTOU protection.
 
 use std::ffi::CString;
-use std::io::{Read, Write};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
 use std::mem::ManuallyDrop;
 use std::os::unix::ffi::OsStrExt;
 use std::os::unix::io::{AsFd, AsRawFd, FromRawFd, OwnedFd};
@@ -11,8 +11,10 @@ use uuid::Uuid;
 
 use super::super::SecurityError;
 use super::super::path::SafePath;
+use super::limits::{FileLimits, TextWindow};
 
-const MAX_FILE_SIZE: u64 = 100 * 1024 * 1024;
+/// Bytes inspected by [`SecureFileHandle::is_binary`].
+const BINARY_SNIFF_BYTES: usize = 8192;
 
 fn with_borrowed_file<T>(fd: &OwnedFd, f: impl FnOnce(&mut std::fs::File) -> T) -> T {
     let mut file = ManuallyDrop::new(unsafe { std::fs::File::from_raw_fd(fd.as_raw_fd()) });
@@ -22,24 +24,37 @@ fn with_borrowed_file<T>(fd: &OwnedFd, f: impl FnOnce(&mut std::fs::File) -> T)
 pub struct SecureFileHandle {
     fd: OwnedFd,
     path: SafePath,
+    limits: FileLimits,
 }
 
 impl SecureFileHandle {
     pub fn open_read(path: SafePath) -> Result<Self, SecurityError> {
         let fd = path.open(OFlags::RDONLY)?;
-        Ok(Self { fd, path })
+        Ok(Self {
+            fd,
+            path,
+            limits: FileLimits::default(),
+        })
     }
 
     pub fn open_write(path: SafePath) -> Result<Self, SecurityError> {
         path.create_parent_dirs()?;
         let fd = path.open(OFlags::WRONLY | OFlags::CREATE | OFlags::TRUNC)?;
-        Ok(Self { fd, path })
+        Ok(Self {
+            fd,
+            path,
+            limits: FileLimits::default(),
+        })
     }
 
     pub fn open_append(path: SafePath) -> Result<Self, SecurityError> {
         path.create_parent_dirs()?;
         let fd = path.open(OFlags::WRONLY | OFlags::CREATE | OFlags::APPEND)?;
-        Ok(Self { fd, path })
+        Ok(Self {
+            fd,
+            path,
+            limits: FileLimits::default(),
+        })
     }
 
     pub fn for_atomic_write(path: SafePath) -> Result<Self, SecurityError> {
@@ -47,7 +62,17 @@ impl SecureFileHandle {
         let fd = path
             .open(OFlags::RDONLY)
             .or_else(|_| path.open(OFlags::WRONLY | OFlags::CREATE))?;
-        Ok(Self { fd, path })
+        Ok(Self {
+            fd,
+            path,
+            limits: FileLimits::default(),
+        })
+    }
+
+    /// Replace the default [`FileLimits`].
+    pub fn limits(mut self, limits: FileLimits) -> Self {
+        self.limits = limits;
+        self
     }
 
     pub fn path(&self) -> &SafePath {
@@ -72,21 +97,101 @@ impl SecureFileHandle {
         Ok(content)
     }
 
-    fn check_file_size(&self) -> Result<(), SecurityError> {
+    /// Lines `offset..offset + limit`, reading only as far as they reach.
+    ///
+    /// Lines are cut to the line-length limit and the window stops early
+    /// once it holds `max_read_bytes`, so any file size can be paged through.
+    /// Invalid UTF-8 is replaced.
+    pub fn read_lines(&self, offset: usize, limit: usize) -> Result<TextWindow, SecurityError> {
+        let file_size = self.size()?;
+        let max_line = self.limits.max_line_length;
+        let max_bytes = self.limits.max_read_bytes;
+
+        let window = with_borrowed_file(&self.fd, |file| -> std::io::Result<TextWindow> {
+            file.seek(SeekFrom::Start(0))?;
+            let mut reader = BufReader::new(file);
+            let mut window = TextWindow {
+                lines: Vec::new(),
+                offset,
+                cut_lines: Vec::new(),
+                file_size,
+                total_lines: None,
+            };
+            let mut line = Vec::new();
+            let mut index = 0;
+            let mut bytes = 0u64;
+            loop {
+                if window.lines.len() >= limit {
+                    if reader.fill_buf()?.is_empty() {
+                        window.total_lines = Some(index);
+                    }
+                    break;
+                }
+                let cap = if index < offset { 0 } else { max_line };
+                let Some(cut) = read_line_capped(&mut reader, &mut line, cap)? else {
+                    window.total_lines = Some(index);
+                    break;
+                };
+                index += 1;
+                if index <= offset {
+                    continue;
+                }
+                bytes += line.len() as u64;
+                if bytes > max_bytes && !window.lines.is_empty() {
+                    break;
+                }
+                if cut {
+                    window.cut_lines.push(window.lines.len());
+                }
+                window.lines.push(decode_line(&line, cut));
+            }
+            Ok(window)
+        })?;
+        Ok(window)
+    }
+
+    /// Whether the file starts with a NUL byte in its first 8 KiB, as
+    /// binary formats do and text almost never does.
+    pub fn is_binary(&self) -> Result<bool, SecurityError> {
+        let mut buf = [0u8; BINARY_SNIFF_BYTES];
+        let n = rustix::io::pread(&self.fd, &mut buf, 0)
+            .map_err(|e| SecurityError::Io(std::io::Error::from_raw_os_error(e.raw_os_error())))?;
+        Ok(buf[..n].contains(&0))
+    }
+
+    /// Size of the file in bytes.
+    pub fn size(&self) -> Result<u64, SecurityError> {
         let stat = rustix::fs::fstat(&self.fd)
             .map_err(|e| SecurityError::Io(std::io::Error::from_raw_os_error(e.raw_os_error())))?;
+        Ok(stat.st_size as u64)
+    }
 
-        let size = stat.st_size as u64;
-        if size > MAX_FILE_SIZE {
+    fn check_file_size(&self) -> Result<(), SecurityError> {
+        let size = self.size()?;
+        let max = self.limits.max_file_size;
+        if size > max {
             return Err(SecurityError::InvalidPath(format!(
                 "File too large: {} bytes (max {} bytes)",
-                size, MAX_FILE_SIZE
+                size, max
+            )));
+        }
+        Ok(())
+    }
+
+    fn check_write_size(&self, content: &[u8]) -> Result<(), SecurityError> {
+        let max = self.limits.max_file_size;
+        if content.len() as u64 > max {
+            return Err(SecurityError::ResourceLimit(format!(
+                "Content too large: {} bytes (max {} bytes)",
+                content.len(),
+                max
             )));
         }
         Ok(())
     }
 
     pub fn write_all(&self, content: &[u8]) -> Result<(), SecurityError> {
+        self.check_write_size(content)?;
         with_borrowed_file(&self.fd, |file| -> std::io::Result<()> {
             file.write_all(content)?;
             file.sync_all()
@@ -95,6 +200,8 @@ impl SecureFileHandle {
     }
 
     pub fn atomic_write(&self, content: &[u8]) -> Result<(), SecurityError> {
+        self.path.check_writable()?;
+        self.check_write_size(content)?;
         let filename = self
             .path
             .filename()
@@ -104,7 +211,7 @@ impl SecureFileHandle {
         let temp_cname = CString::new(temp_name.as_bytes())
             .map_err(|_| SecurityError::InvalidPath("invalid temp name".into()))?;
 
-        let parent_fd = self.get_parent_fd()?;
+        let parent_fd = self.path.open_parent()?;
 
         let temp_fd = openat(
             parent_fd.as_fd(),
@@ -140,45 +247,51 @@ impl SecureFileHandle {
 
         Ok(())
     }
+}
 
-    fn get_parent_fd(&self) -> Result<OwnedFd, SecurityError> {
-        let parent_components = self.path.parent_components();
-
-        if parent_components.is_empty() {
-            return rustix::fs::openat(
-                self.path.root_fd(),
-                c".",
-                OFlags::RDONLY | OFlags::DIRECTORY | OFlags::CLOEXEC,
-                Mode::empty(),
-            )
-            .map_err(|e| SecurityError::Io(std::io::Error::from_raw_os_error(e.raw_os_error())));
+/// Read one line into `line`, keeping at most `cap` bytes of it. Returns
+/// whether the line was cut, or `None` at end of file.
+fn read_line_capped(
+    reader: &mut impl BufRead,
+    line: &mut Vec<u8>,
+    cap: usize,
+) -> std::io::Result<Option<bool>> {
+    line.clear();
+    let mut seen = false;
+    let mut cut = false;
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(seen.then_some(cut));
         }
-
-        let mut current_fd = self.path.root_fd();
-        let mut owned_fds: Vec<OwnedFd> = Vec::new();
-
-        for component in parent_components {
-            let c_name = CString::new(component.as_bytes())
-                .map_err(|_| SecurityError::InvalidPath("null byte".into()))?;
-
-            let fd = openat(
-                current_fd,
-                &c_name,
-                OFlags::RDONLY | OFlags::DIRECTORY | OFlags::NOFOLLOW | OFlags::CLOEXEC,
-                Mode::empty(),
-            )
-            .map_err(|e| SecurityError::Io(std::io::Error::from_raw_os_error(e.raw_os_error())))?;
-
-            owned_fds.push(fd);
-            current_fd = owned_fds.last().expect("just pushed").as_fd();
+        seen = true;
+        let (chunk, done) = match buf.iter().position(|&b| b == b'\n') {
+            Some(end) => (&buf[..end], Some(end + 1)),
+            None => (buf, None),
+        };
+        let room = cap.saturating_sub(line.len());
+        cut |= chunk.len() > room;
+        line.extend_from_slice(&chunk[..chunk.len().min(room)]);
+        let consumed = done.unwrap_or(buf.len());
+        reader.consume(consumed);
+        if done.is_some() {
+            if !cut && line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            return Ok(Some(cut));
         }
-
-        owned_fds
-            .pop()
-            .ok_or_else(|| SecurityError::InvalidPath("no parent".into()))
     }
 }
 
+/// Decode a line, dropping a character split by the cut.
+fn decode_line(line: &[u8], cut: bool) -> String {
+    let end = match std::str::from_utf8(line) {
+        Err(e) if cut && e.error_len().is_none() => e.valid_up_to(),
+        _ => line.len(),
+    };
+    String::from_utf8_lossy(&line[..end]).into_owned()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -205,6 +318,51 @@ mod tests {
         assert_eq!(content, "hello world");
     }
 
+    #[test]
+    fn test_read_lines_window() {
+        let dir = tempdir().unwrap();
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        let long = "é".repeat(20);
+        fs::write(root.join("log.txt"), format!("a\r\nb\n{long}\nd\ne")).unwrap();
+        let limits = FileLimits::new().max_line_length(9).max_read_bytes(10);
+        let handle = SecureFileHandle::open_read(create_safe_path(&root, "log.txt"))
+            .unwrap()
+            .limits(limits);
+
+        let window = handle.read_lines(0, 2).unwrap();
+        assert_eq!(window.lines, ["a", "b"]);
+        assert!(window.is_partial());
+
+        let window = handle.read_lines(1, 10).unwrap();
+        assert_eq!(window.lines, ["b", "éééé"]);
+        assert_eq!(window.cut_lines, [1]);
+        assert!(window.is_partial());
+
+        let window = handle.read_lines(3, 10).unwrap();
+        assert_eq!(window.lines, ["d", "e"]);
+        assert_eq!(window.total_lines, Some(5));
+        assert!(!window.is_partial());
+
+        assert!(!handle.is_binary().unwrap());
+        fs::write(root.join("blob.bin"), b"\x7fELF\x00\x01").unwrap();
+        let blob = SecureFileHandle::open_read(create_safe_path(&root, "blob.bin")).unwrap();
+        assert!(blob.is_binary().unwrap());
+    }
+
+    #[test]
+    fn test_write_size_limit() {
+        let dir = tempdir().unwrap();
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        let handle = SecureFileHandle::for_atomic_write(create_safe_path(&root, "big.txt"))
+            .unwrap()
+            .limits(FileLimits::new().max_file_size(4));
+        assert!(matches!(
+            handle.atomic_write(b"too long"),
+            Err(SecurityError::ResourceLimit(_))
+        ));
+        assert!(handle.atomic_write(b"ok").is_ok());
+    }
+
     #[test]
     fn test_write_file() {
         let dir = tempdir().unwrap();
@@ -0,0 +1,78 @@
+//! Size limits for reading and writing files.
+
+const KB: u64 = 1024;
+const MB: u64 = 1024 * KB;
+
+/// How much of a file tools may load, return or write.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileLimits {
+    /// Largest file read whole into memory, and largest content written.
+    pub max_file_size: u64,
+    /// Most bytes of text a windowed read returns; the rest is left for a
+    /// later read at a higher offset.
+    pub max_read_bytes: u64,
+    /// Lines longer than this many bytes are cut.
+    pub max_line_length: usize,
+    /// Refuse to return files that look binary as text.
+    pub detect_binary: bool,
+}
+
+impl Default for FileLimits {
+    fn default() -> Self {
+        Self {
+            max_file_size: 100 * MB,
+            max_read_bytes: 256 * KB,
+            max_line_length: 2000,
+            detect_binary: true,
+        }
+    }
+}
+
+impl FileLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = bytes;
+        self
+    }
+
+    pub fn max_read_bytes(mut self, bytes: u64) -> Self {
+        self.max_read_bytes = bytes;
+        self
+    }
+
+    pub fn max_line_length(mut self, bytes: usize) -> Self {
+        self.max_line_length = bytes;
+        self
+    }
+
+    pub fn detect_binary(mut self, enabled: bool) -> Self {
+        self.detect_binary = enabled;
+        self
+    }
+}
+
+/// A run of lines read from a file, possibly short of its end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextWindow {
+    /// The lines, without terminators, each cut to the line-length limit.
+    pub lines: Vec<String>,
+    /// Zero-based number of the first line.
+    pub offset: usize,
+    /// Indices into `lines` of the lines that were cut.
+    pub cut_lines: Vec<usize>,
+    /// Size of the file in bytes.
+    pub file_size: u64,
+    /// Lines in the file, known when the read reached its end.
+    pub total_lines: Option<usize>,
+}
+
+impl TextWindow {
+    /// The window stops short of the end of the file.
+    pub fn is_partial(&self) -> bool {
+        self.total_lines
+            .is_none_or(|total| self.offset + self.lines.len() < total)
+    }
+}
@@ -1,8 +1,10 @@
 //! Secure filesystem operations with TOCTOU protection.
 
 mod handle;
+mod limits;
 
 pub use handle::SecureFileHandle;
+pub use limits::{FileLimits, TextWindow};
 
 use std::os::unix::io::OwnedFd;
 use std::path::{Path, PathBuf};
@@ -22,6 +24,8 @@ pub struct SecureFs {
     denied_patterns: Vec<Pattern>,
     max_symlink_depth: u8,
     permissive: bool,
+    read_only: bool,
+    file_limits: FileLimits,
 }
 
 impl SecureFs {
@@ -60,6 +64,8 @@ impl SecureFs {
             denied_patterns: compiled_patterns,
             max_symlink_depth,
             permissive: false,
+            read_only: false,
+            file_limits: FileLimits::default(),
         })
     }
 
@@ -76,6 +82,8 @@ impl SecureFs {
             denied_patterns: Vec::new(),
             max_symlink_depth: 255,
             permissive: true,
+            read_only: false,
+            file_limits: FileLimits::default(),
         }
     }
 
@@ -83,11 +91,45 @@ impl SecureFs {
         self.permissive
     }
 
+    /// Make every path this resolves read-only, so opening one for writing fails.
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Limits applied to the handles this opens.
+    pub fn with_file_limits(mut self, limits: FileLimits) -> Self {
+        self.file_limits = limits;
+        self
+    }
+
+    pub fn file_limits(&self) -> &FileLimits {
+        &self.file_limits
+    }
+
     pub fn root(&self) -> &Path {
         &self.root_path
     }
 
+    /// Directories outside the root that may be accessed.
+    pub fn allowed_paths(&self) -> &[PathBuf] {
+        &self.allowed_paths
+    }
+
     pub fn resolve(&self, input_path: &str) -> Result<SafePath, SecurityError> {
+        let path = self.resolve_path(input_path)?;
+        Ok(if self.read_only {
+            path.into_read_only()
+        } else {
+            path
+        })
+    }
+
+    fn resolve_path(&self, input_path: &str) -> Result<SafePath, SecurityError> {
         if input_path.contains('\0') {
             return Err(SecurityError::InvalidPath("null byte in path".into()));
         }
@@ -116,6 +158,8 @@ impl SecureFs {
             return Ok(SafePath::unchecked(Arc::clone(&self.root_fd), normalized));
         }
 
+        // Paths under an allowed directory resolve against that directory.
+        let mut base = None;
         let relative = if input_path.starts_with('/') {
             let input = PathBuf::from(input_path);
             let normalized_input = if input.exists() {
@@ -136,22 +180,18 @@ impl SecureFs {
                     .map(|p| p.to_path_buf())
                     .unwrap_or_default()
             } else {
-                let mut found = None;
-                for allowed in &self.allowed_paths {
-                    if normalized_input.starts_with(allowed) {
-                        found = Some(
-                            normalized_input
-                                .strip_prefix(allowed)
-                                .map(|p| p.to_path_buf())
-                                .unwrap_or_default(),
-                        );
-                        break;
-                    }
-                }
-                match found {
-                    Some(rel) => rel,
-                    None => return Err(SecurityError::PathEscape(normalized_input)),
-                }
+                let Some(allowed) = self
+                    .allowed_paths
+                    .iter()
+                    .find(|allowed| normalized_input.starts_with(allowed))
+                else {
+                    return Err(SecurityError::PathEscape(normalized_input));
+                };
+                base = Some(allowed);
+                normalized_input
+                    .strip_prefix(allowed)
+                    .map(|p| p.to_path_buf())
+                    .unwrap_or_default()
             }
         } else {
             normalize_path(&PathBuf::from(input_path))
@@ -160,17 +200,20 @@ impl SecureFs {
                 .unwrap_or_else(|_| PathBuf::from(input_path))
         };
 
-        let expected_path = self.root_path.join(&relative);
+        let (base_fd, base_path) = match base {
+            Some(allowed) => (
+                Arc::new(OwnedFd::from(std::fs::File::open(allowed)?)),
+                allowed.clone(),
+            ),
+            None => (Arc::clone(&self.root_fd), self.root_path.clone()),
+        };
+
+        let expected_path = base_path.join(&relative);
         if self.is_path_denied(&expected_path) {
             return Err(SecurityError::DeniedPath(expected_path));
         }
 
-        let safe_path = SafePath::resolve(
-            Arc::clone(&self.root_fd),
-            self.root_path.clone(),
-            &relative,
-            self.max_symlink_depth,
-        )?;
+        let safe_path = SafePath::resolve(base_fd, base_path, &relative, self.max_symlink_depth)?;
 
         let resolved = safe_path.as_path();
         if !self.is_within(resolved) {
@@ -217,12 +260,23 @@ impl SecureFs {
 
     pub fn open_read(&self, input_path: &str) -> Result<SecureFileHandle, SecurityError> {
         let path = self.resolve(input_path)?;
-        SecureFileHandle::open_read(path)
+        Ok(SecureFileHandle::open_read(path)?.limits(self.file_limits.clone()))
     }
 
     pub fn open_write(&self, input_path: &str) -> Result<SecureFileHandle, SecurityError> {
         let path = self.resolve(input_path)?;
-        SecureFileHandle::open_write(path)
+        Ok(SecureFileHandle::open_write(path)?.limits(self.file_limits.clone()))
+    }
+
+    /// Removes a file or directory tree without following symlinks.
+    pub fn remove(&self, input_path: &str) -> Result<(), SecurityError> {
+        self.resolve(input_path)?.remove()
+    }
+
+    /// Moves a file or directory without following symlinks or replacing
+    /// an existing target.
+    pub fn rename(&self, from: &str, to: &str) -> Result<(), SecurityError> {
+        self.resolve(from)?.rename_to(&self.resolve(to)?)
     }
 
     pub fn is_within(&self, path: &Path) -> bool {
@@ -325,5 +379,9 @@ mod tests {
 
         let secure_fs = SecureFs::new(&root1, vec![root2.clone()], &[], 10).unwrap();
         assert!(secure_fs.is_within(&root2.join("file.txt")));
+
+        let file = root2.join("file.txt");
+        let resolved = secure_fs.resolve(file.to_str().unwrap()).unwrap();
+        assert_eq!(resolved.as_path(), file);
     }
 }
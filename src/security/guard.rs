@@ -48,6 +48,10 @@ impl SecurityGuard {
             .and_then(|v| v.as_str())
             .ok_or_else(|| SecurityError::InvalidPath("missing command".into()))?;
 
+        if security.policy.is_read_only() {
+            super::bash::check_read_only(command).map_err(SecurityError::ReadOnly)?;
+        }
+
         let bypass = input
             .get("dangerouslyDisableSandbox")
             .and_then(|v| v.as_bool())
@@ -118,11 +122,11 @@ struct ToolPathSchema {
 impl ToolPathSchema {
     fn for_tool(name: &str) -> Self {
         match name {
-            "Read" | "Write" | "Edit" => Self {
+            "Read" | "Write" | "Edit" | "MultiEdit" | "AstEdit" => Self {
                 path_fields: &["file_path"],
                 is_shell: false,
             },
-            "Glob" | "Grep" => Self {
+            "Glob" | "Grep" | "LS" => Self {
                 path_fields: &["path"],
                 is_shell: false,
             },
@@ -141,6 +145,7 @@ impl ToolPathSchema {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::security::{SecureFileHandle, SecurityPolicy};
     use tempfile::tempdir;
 
     fn create_test_context(root: &Path) -> SecurityContext {
@@ -219,6 +224,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_read_only_policy() {
+        let dir = tempdir().unwrap();
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        std::fs::write(root.join("test.txt"), "content").unwrap();
+        let security = SecurityContext::builder()
+            .root(&root)
+            .policy(SecurityPolicy::read_only())
+            .build()
+            .unwrap();
+
+        let bash = |command: &str| {
+            SecurityGuard::validate(&security, "Bash", &serde_json::json!({"command": command}))
+        };
+        assert!(bash("cat test.txt | wc -l").is_ok());
+        assert!(matches!(
+            bash("echo changed > test.txt"),
+            Err(SecurityError::ReadOnly(_))
+        ));
+        assert!(matches!(
+            bash("npm install"),
+            Err(SecurityError::ReadOnly(_))
+        ));
+
+        let path = security.fs.resolve("test.txt").unwrap();
+        assert!(SecureFileHandle::open_read(path.clone()).is_ok());
+        assert!(matches!(
+            SecureFileHandle::for_atomic_write(path),
+            Err(SecurityError::ReadOnly(_))
+        ));
+        assert!(security.fs.open_write("new.txt").is_err());
+        assert!(!root.join("new.txt").exists());
+    }
+
     #[test]
     fn test_glob_path_optional() {
         let dir = tempdir().unwrap();
@@ -0,0 +1,339 @@
+//! Per-command cgroup v2 limits.
+//!
+//! `setrlimit` bounds each process on its own, so a command that forks many
+//! children (`npm install`, `make -j`) can still exhaust the host. A cgroup
+//! bounds the whole process tree and lets every straggler be killed at once.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::security::SecurityError;
+
+/// Limits for the cgroup each command runs in.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CgroupLimits {
+    /// `memory.max` in bytes. The whole tree is OOM-killed when it is exceeded.
+    pub memory_max: Option<u64>,
+    /// `cpu.max` as a number of CPUs, e.g. `1.5`.
+    pub cpu_max: Option<f64>,
+    /// `pids.max`: processes and threads in the tree.
+    pub pids_max: Option<u64>,
+    /// Delegated cgroup to create command cgroups under. Defaults to the
+    /// cgroup of the current process.
+    pub parent: Option<PathBuf>,
+}
+
+impl CgroupLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn memory(mut self, bytes: u64) -> Self {
+        self.memory_max = Some(bytes);
+        self
+    }
+
+    pub fn cpus(mut self, cpus: f64) -> Self {
+        self.cpu_max = Some(cpus);
+        self
+    }
+
+    pub fn pids(mut self, count: u64) -> Self {
+        self.pids_max = Some(count);
+        self
+    }
+
+    pub fn parent(mut self, path: impl Into<PathBuf>) -> Self {
+        self.parent = Some(path.into());
+        self
+    }
+
+    #[cfg(target_os = "linux")]
+    fn controllers(&self) -> Vec<&'static str> {
+        let mut controllers = Vec::new();
+        if self.memory_max.is_some() {
+            controllers.push("memory");
+        }
+        if self.cpu_max.is_some() {
+            controllers.push("cpu");
+        }
+        if self.pids_max.is_some() {
+            controllers.push("pids");
+        }
+        controllers
+    }
+}
+
+/// `cpu.max` period in microseconds.
+#[cfg(target_os = "linux")]
+const CPU_PERIOD: u64 = 100_000;
+/// Leaf the agent moves itself into when its own cgroup must stay free of
+/// processes to delegate controllers.
+#[cfg(target_os = "linux")]
+const AGENT_LEAF: &str = "claude-agent";
+
+/// A cgroup created for one command. Dropping it kills whatever is left of
+/// the process tree and removes the cgroup.
+#[derive(Debug)]
+pub struct CgroupScope {
+    path: PathBuf,
+}
+
+impl CgroupScope {
+    /// Create a cgroup with `limits` under the delegated parent.
+    #[cfg(target_os = "linux")]
+    pub fn create(limits: &CgroupLimits) -> Result<Self, SecurityError> {
+        let parent = match &limits.parent {
+            Some(parent) => parent.clone(),
+            None => default_parent()?,
+        };
+        enable_controllers(&parent, &limits.controllers()).map_err(|e| {
+            SecurityError::ResourceLimit(format!("cgroup controllers in {}: {e}", parent.display()))
+        })?;
+
+        let path = parent.join(format!("claude-agent-{}", crate::common::clock::new_uuid()));
+        std::fs::create_dir(&path)
+            .map_err(|e| SecurityError::ResourceLimit(format!("cgroup {}: {e}", path.display())))?;
+        let scope = Self { path };
+
+        let write = |file: &str, value: String| {
+            std::fs::write(scope.path.join(file), value)
+                .map_err(|e| SecurityError::ResourceLimit(format!("{file}: {e}")))
+        };
+        if let Some(bytes) = limits.memory_max {
+            write("memory.max", bytes.to_string())?;
+            // Kill the whole tree rather than one arbitrary process.
+            write("memory.oom.group", "1".into())?;
+        }
+        if let Some(cpus) = limits.cpu_max {
+            let quota = ((cpus * CPU_PERIOD as f64) as u64).max(1_000);
+            write("cpu.max", format!("{quota} {CPU_PERIOD}"))?;
+        }
+        if let Some(count) = limits.pids_max {
+            write("pids.max", count.to_string())?;
+        }
+        Ok(scope)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn create(_limits: &CgroupLimits) -> Result<Self, SecurityError> {
+        Err(SecurityError::ResourceLimit(
+            "cgroups are only available on Linux".into(),
+        ))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Open the cgroup for a child to join with [`CgroupEntry::enter`].
+    pub fn entry(&self) -> io::Result<CgroupEntry> {
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(self.path.join("cgroup.procs"))?;
+        Ok(CgroupEntry { file })
+    }
+
+    /// Whether the memory limit was hit and the tree OOM-killed.
+    pub fn oom_killed(&self) -> bool {
+        std::fs::read_to_string(self.path.join("memory.events"))
+            .ok()
+            .and_then(|events| {
+                events.lines().find_map(|line| {
+                    line.strip_prefix("oom_group_kill ")
+                        .or_else(|| line.strip_prefix("oom_kill "))
+                        .and_then(|n| n.trim().parse::<u64>().ok())
+                        .filter(|n| *n > 0)
+                })
+            })
+            .is_some()
+    }
+
+    /// Kill every process still in the cgroup.
+    pub fn kill(&self) {
+        // `cgroup.kill` needs Linux 5.14; signal the members one by one otherwise.
+        if std::fs::write(self.path.join("cgroup.kill"), "1").is_ok() {
+            return;
+        }
+        #[cfg(unix)]
+        for pid in self.pids() {
+            if let Some(pid) = rustix::process::Pid::from_raw(pid) {
+                let _ = rustix::process::kill_process(pid, rustix::process::Signal::KILL);
+            }
+        }
+    }
+
+    fn pids(&self) -> Vec<i32> {
+        std::fs::read_to_string(self.path.join("cgroup.procs"))
+            .map(|procs| {
+                procs
+                    .lines()
+                    .filter_map(|l| l.trim().parse().ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for CgroupScope {
+    fn drop(&mut self) {
+        self.kill();
+        // Killed processes leave the cgroup asynchronously.
+        for _ in 0..50 {
+            match std::fs::remove_dir(&self.path) {
+                Err(e) if e.kind() == io::ErrorKind::ResourceBusy => {
+                    std::thread::sleep(std::time::Duration::from_millis(2))
+                }
+                _ => return,
+            }
+        }
+        tracing::warn!(path = %self.path.display(), "Failed to remove command cgroup");
+    }
+}
+
+/// Handle a child uses to move itself into a [`CgroupScope`].
+#[derive(Debug)]
+pub struct CgroupEntry {
+    file: std::fs::File,
+}
+
+impl CgroupEntry {
+    /// Move the calling process into the cgroup.
+    ///
+    /// Only makes a `write` syscall, so it is safe to call between `fork` and
+    /// `exec` (`CommandExt::pre_exec`).
+    pub fn enter(&self) -> io::Result<()> {
+        // "0" names the writing process.
+        rustix::io::write(&self.file, b"0")?;
+        Ok(())
+    }
+}
+
+/// The current process's cgroup, resolved once. If it holds processes and so
+/// cannot delegate controllers, the agent first moves itself into a leaf.
+#[cfg(target_os = "linux")]
+fn default_parent() -> Result<PathBuf, SecurityError> {
+    use std::sync::OnceLock;
+
+    static PARENT: OnceLock<Result<PathBuf, String>> = OnceLock::new();
+    PARENT
+        .get_or_init(|| {
+            let parent = own_cgroup().ok_or("cgroup v2 is not mounted")?;
+            let available = std::fs::read_to_string(parent.join("cgroup.controllers"))
+                .map_err(|e| format!("cgroup {}: {e}", parent.display()))?;
+            let controllers: Vec<_> = ["memory", "cpu", "pids"]
+                .into_iter()
+                .filter(|c| available.split_whitespace().any(|a| a == *c))
+                .collect();
+            match enable_controllers(&parent, &controllers) {
+                // EBUSY: the no-internal-processes rule.
+                Err(e) if e.kind() == io::ErrorKind::ResourceBusy => {
+                    let leaf = parent.join(AGENT_LEAF);
+                    std::fs::create_dir_all(&leaf)
+                        .and_then(|()| {
+                            std::fs::write(
+                                leaf.join("cgroup.procs"),
+                                std::process::id().to_string(),
+                            )
+                        })
+                        .and_then(|()| enable_controllers(&parent, &controllers))
+                        .map_err(|e| format!("cgroup {}: {e}", parent.display()))?;
+                    Ok(parent)
+                }
+                Err(e) => Err(format!("cgroup {}: {e}", parent.display())),
+                Ok(()) => Ok(parent),
+            }
+        })
+        .clone()
+        .map_err(SecurityError::ResourceLimit)
+}
+
+/// Directory of the current process's cgroup on the cgroup2 mount.
+#[cfg(target_os = "linux")]
+fn own_cgroup() -> Option<PathBuf> {
+    let mountinfo = std::fs::read_to_string("/proc/self/mountinfo").ok()?;
+    let mount = mountinfo.lines().find_map(|line| {
+        let (fields, fs) = line.split_once(" - ")?;
+        fs.starts_with("cgroup2 ")
+            .then(|| fields.split(' ').nth(4))
+            .flatten()
+    })?;
+    let own = std::fs::read_to_string("/proc/self/cgroup").ok()?;
+    let own = own.lines().find_map(|line| line.strip_prefix("0::"))?;
+    Some(Path::new(mount).join(own.trim().trim_start_matches('/')))
+}
+
+/// Enable `controllers` for the children of `dir` that are not enabled yet.
+#[cfg(target_os = "linux")]
+fn enable_controllers(dir: &Path, controllers: &[&str]) -> io::Result<()> {
+    let available = std::fs::read_to_string(dir.join("cgroup.controllers"))?;
+    let enabled = std::fs::read_to_string(dir.join("cgroup.subtree_control"))?;
+    let missing: Vec<String> = controllers
+        .iter()
+        .filter(|c| !enabled.split_whitespace().any(|e| e == **c))
+        .map(|c| {
+            if available.split_whitespace().any(|a| a == *c) {
+                Ok(format!("+{c}"))
+            } else {
+                Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("{c} controller not available"),
+                ))
+            }
+        })
+        .collect::<io::Result<_>>()?;
+    if missing.is_empty() {
+        return Ok(());
+    }
+    std::fs::write(dir.join("cgroup.subtree_control"), missing.join(" "))
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use std::os::unix::process::CommandExt;
+    use std::process::Command;
+
+    #[test]
+    fn test_builder() {
+        let limits = CgroupLimits::new().memory(512 << 20).pids(64);
+        assert_eq!(limits.memory_max, Some(512 << 20));
+        assert_eq!(limits.controllers(), vec!["memory", "pids"]);
+    }
+
+    #[test]
+    fn test_scope_limits_and_cleanup() {
+        let limits = CgroupLimits::new().pids(8).cpus(0.5);
+        let Ok(scope) = CgroupScope::create(&limits) else {
+            return; // cgroup v2 not delegated to us
+        };
+        let path = scope.path().to_path_buf();
+        assert_eq!(
+            std::fs::read_to_string(path.join("pids.max"))
+                .unwrap()
+                .trim(),
+            "8"
+        );
+        assert_eq!(
+            std::fs::read_to_string(path.join("cpu.max"))
+                .unwrap()
+                .trim(),
+            "50000 100000"
+        );
+
+        let entry = scope.entry().unwrap();
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "sleep 30 >/dev/null 2>&1 & cat /proc/self/cgroup"]);
+        // SAFETY: `enter` only makes a `write` syscall.
+        unsafe {
+            cmd.pre_exec(move || entry.enter());
+        }
+        let output = cmd.output().unwrap();
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        assert!(String::from_utf8_lossy(&output.stdout).contains(&name));
+        assert!(!scope.pids().is_empty()); // the orphaned sleep
+
+        drop(scope);
+        assert!(!path.exists());
+    }
+}
@@ -1,5 +1,7 @@
-//! Process resource limits using setrlimit.
+//! Process resource limits using setrlimit and cgroup v2.
 
+mod cgroup;
 mod rlimit;
 
+pub use cgroup::{CgroupEntry, CgroupLimits, CgroupScope};
 pub use rlimit::ResourceLimits;
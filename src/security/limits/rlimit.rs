@@ -1,5 +1,6 @@
 //! Resource limits using setrlimit.
 
+use super::CgroupLimits;
 use crate::security::SecurityError;
 
 const KB: u64 = 1024;
@@ -15,6 +16,8 @@ pub struct ResourceLimits {
     pub virtual_memory: Option<u64>,
     pub data_size: Option<u64>,
     pub stack_size: Option<u64>,
+    /// Run each command in its own cgroup, bounding the whole process tree.
+    pub cgroup: Option<CgroupLimits>,
 }
 
 impl Default for ResourceLimits {
@@ -27,6 +30,7 @@ impl Default for ResourceLimits {
             virtual_memory: Some(2 * GB), // 2 GB
             data_size: Some(GB),          // 1 GB
             stack_size: Some(8 * MB),     // 8 MB
+            cgroup: None,
         }
     }
 }
@@ -41,6 +45,7 @@ impl ResourceLimits {
             virtual_memory: None,
             data_size: None,
             stack_size: None,
+            cgroup: None,
         }
     }
 
@@ -53,6 +58,7 @@ impl ResourceLimits {
             virtual_memory: Some(512 * MB), // 512 MB
             data_size: Some(256 * MB),      // 256 MB
             stack_size: Some(MB),           // 1 MB
+            cgroup: None,
         }
     }
 
@@ -81,6 +87,11 @@ impl ResourceLimits {
         self
     }
 
+    pub fn cgroup(mut self, limits: CgroupLimits) -> Self {
+        self.cgroup = Some(limits);
+        self
+    }
+
     #[cfg(unix)]
     pub fn apply(&self) -> Result<(), SecurityError> {
         use rustix::process::{Resource, Rlimit, setrlimit};
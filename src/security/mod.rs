@@ -5,25 +5,35 @@
 //! - Symlink attack prevention with depth limiting
 //! - AST-based bash command analysis
 //! - Environment variable sanitization
-//! - Process resource limits via `setrlimit`
+//! - Process resource limits via `setrlimit` and cgroup v2
 //! - OS-level sandboxing (Landlock on Linux, Seatbelt on macOS)
+//! - Secret redaction for tool output and streamed text
+//! - Reversible PII scrubbing for outbound requests and stored sessions
+//! - Audit log of blocked operations with file and database sinks
+//! - Read-only mode refusing filesystem writes and non-read-only commands
 
+pub mod audit;
 pub mod bash;
 pub mod fs;
 pub mod guard;
 pub mod limits;
 pub mod path;
+pub mod pii;
 pub mod policy;
+pub mod redact;
 pub mod sandbox;
 
 mod error;
 
+pub use audit::{AuditEvent, AuditEventKind, AuditLog, AuditQuery, AuditSink};
 pub use error::SecurityError;
-pub use fs::{SecureFileHandle, SecureFs};
+pub use fs::{FileLimits, SecureFileHandle, SecureFs};
 pub use guard::SecurityGuard;
-pub use limits::ResourceLimits;
+pub use limits::{CgroupLimits, ResourceLimits};
 pub use path::SafePath;
+pub use pii::{PiiScrubber, PiiStreamRestorer};
 pub use policy::SecurityPolicy;
+pub use redact::{SecretRedactor, StreamRedactor};
 pub use sandbox::{DomainCheck, NetworkConfig, NetworkSandbox, Sandbox, SandboxConfig};
 
 use std::path::{Path, PathBuf};
@@ -37,6 +47,7 @@ pub struct SecurityContext {
     pub policy: SecurityPolicy,
     pub network: Arc<NetworkSandbox>,
     pub sandbox: Arc<Sandbox>,
+    pub env: bash::EnvPolicy,
 }
 
 impl SecurityContext {
@@ -60,9 +71,17 @@ impl SecurityContext {
             policy: SecurityPolicy::permissive(),
             network: Arc::new(NetworkSandbox::permissive()),
             sandbox: Arc::new(Sandbox::disabled()),
+            env: bash::EnvPolicy::default(),
         }
     }
 
+    /// Switch to read-only mode: see [`SecurityPolicy::read_only`].
+    pub fn into_read_only(mut self) -> Self {
+        self.policy.read_only = true;
+        self.fs = self.fs.read_only(true);
+        self
+    }
+
     pub fn root(&self) -> &Path {
         self.fs.root()
     }
@@ -86,6 +105,9 @@ pub struct SecurityContextBuilder {
     max_symlink_depth: Option<u8>,
     network: Option<NetworkSandbox>,
     sandbox_config: Option<SandboxConfig>,
+    policy: Option<SecurityPolicy>,
+    file_limits: Option<FileLimits>,
+    env: Option<bash::EnvPolicy>,
 }
 
 impl SecurityContextBuilder {
@@ -109,6 +131,17 @@ impl SecurityContextBuilder {
         self
     }
 
+    pub fn file_limits(mut self, limits: FileLimits) -> Self {
+        self.file_limits = Some(limits);
+        self
+    }
+
+    /// Environment given to Bash commands, hooks and MCP stdio servers.
+    pub fn env_policy(mut self, policy: bash::EnvPolicy) -> Self {
+        self.env = Some(policy);
+        self
+    }
+
     pub fn bash_policy(mut self, policy: bash::BashPolicy) -> Self {
         self.bash_policy = Some(policy);
         self
@@ -124,6 +157,12 @@ impl SecurityContextBuilder {
         self
     }
 
+    /// A [`SecurityPolicy::read_only`] policy also makes the filesystem read-only.
+    pub fn policy(mut self, policy: SecurityPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
     pub fn sandbox(mut self, config: SandboxConfig) -> Self {
         self.sandbox_config = Some(config);
         self
@@ -160,13 +199,16 @@ impl SecurityContextBuilder {
             .root
             .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
 
+        let policy = self.policy.unwrap_or_default();
         let fs = SecureFs::new(
             &root,
             self.allowed_paths.clone(),
             &self.denied_patterns,
             self.max_symlink_depth
                 .unwrap_or(crate::security::path::DEFAULT_MAX_SYMLINK_DEPTH),
-        )?;
+        )?
+        .read_only(policy.is_read_only())
+        .with_file_limits(self.file_limits.unwrap_or_default());
 
         let sandbox_config = self.sandbox_config.unwrap_or_else(|| {
             SandboxConfig::disabled()
@@ -183,9 +225,10 @@ impl SecurityContextBuilder {
             fs,
             bash: bash::BashAnalyzer::new(self.bash_policy.unwrap_or_default()),
             limits: self.limits.unwrap_or_default(),
-            policy: SecurityPolicy::default(),
+            policy,
             network: Arc::new(network),
             sandbox: Arc::new(Sandbox::new(sandbox_config)),
+            env: self.env.unwrap_or_default(),
         })
     }
 }
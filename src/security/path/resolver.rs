@@ -6,7 +6,7 @@ use std::os::unix::io::{AsFd, BorrowedFd, OwnedFd};
 use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
 
-use rustix::fs::{Mode, OFlags, openat};
+use rustix::fs::{AtFlags, Dir, FileType, Mode, OFlags, openat, renameat, statat, unlinkat};
 use rustix::io::Errno;
 
 use crate::security::SecurityError;
@@ -18,6 +18,7 @@ pub struct SafePath {
     components: Vec<OsString>,
     resolved_path: PathBuf,
     permissive: bool,
+    read_only: bool,
 }
 
 impl SafePath {
@@ -148,6 +149,7 @@ impl SafePath {
             components: validated_components,
             resolved_path,
             permissive: false,
+            read_only: false,
         })
     }
 
@@ -171,6 +173,7 @@ impl SafePath {
             components,
             resolved_path,
             permissive: true,
+            read_only: false,
         }
     }
 
@@ -178,6 +181,27 @@ impl SafePath {
         self.permissive
     }
 
+    /// Refuse to open this path for writing or to create its parents.
+    pub fn into_read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Fails if this path is read-only.
+    pub fn check_writable(&self) -> Result<(), SecurityError> {
+        if self.read_only {
+            return Err(SecurityError::ReadOnly(format!(
+                "cannot write {}",
+                self.resolved_path.display()
+            )));
+        }
+        Ok(())
+    }
+
     pub fn root_fd(&self) -> BorrowedFd<'_> {
         self.root_fd.as_fd()
     }
@@ -207,6 +231,12 @@ impl SafePath {
     }
 
     pub fn open(&self, flags: OFlags) -> Result<OwnedFd, SecurityError> {
+        if flags.intersects(
+            OFlags::WRONLY | OFlags::RDWR | OFlags::CREATE | OFlags::TRUNC | OFlags::APPEND,
+        ) {
+            self.check_writable()?;
+        }
+
         // In permissive mode, use standard library to handle symlinks
         if self.permissive {
             use std::fs::OpenOptions;
@@ -284,6 +314,7 @@ impl SafePath {
     }
 
     pub fn create_parent_dirs(&self) -> Result<(), SecurityError> {
+        self.check_writable()?;
         if self.components.len() <= 1 {
             return Ok(());
         }
@@ -351,6 +382,132 @@ impl SafePath {
     }
 }
 
+impl SafePath {
+    /// Opens the directory holding this path, following no symlinks.
+    pub fn open_parent(&self) -> Result<OwnedFd, SecurityError> {
+        let mut current = rustix::fs::openat(
+            self.root_fd.as_fd(),
+            c".",
+            OFlags::RDONLY | OFlags::DIRECTORY | OFlags::CLOEXEC,
+            Mode::empty(),
+        )
+        .map_err(io_error)?;
+        for component in self.parent_components() {
+            let c_name = CString::new(component.as_bytes())
+                .map_err(|_| SecurityError::InvalidPath("null byte".into()))?;
+            current = openat(
+                &current,
+                &c_name,
+                OFlags::RDONLY | OFlags::DIRECTORY | OFlags::NOFOLLOW | OFlags::CLOEXEC,
+                Mode::empty(),
+            )
+            .map_err(io_error)?;
+        }
+        Ok(current)
+    }
+
+    fn c_filename(&self) -> Result<CString, SecurityError> {
+        let filename = self
+            .filename()
+            .ok_or_else(|| SecurityError::InvalidPath("no filename".into()))?;
+        CString::new(filename.as_bytes())
+            .map_err(|_| SecurityError::InvalidPath("null byte".into()))
+    }
+
+    /// Removes the file, or the directory and everything in it. Symlinks
+    /// are removed, never followed.
+    pub fn remove(&self) -> Result<(), SecurityError> {
+        self.check_writable()?;
+        let parent = self.open_parent()?;
+        remove_at(parent.as_fd(), &self.c_filename()?).map_err(io_error)
+    }
+
+    /// Moves the file or directory to `to`, creating its parents. Fails with
+    /// [`ErrorKind::AlreadyExists`](std::io::ErrorKind::AlreadyExists) if
+    /// `to` exists.
+    pub fn rename_to(&self, to: &SafePath) -> Result<(), SecurityError> {
+        self.check_writable()?;
+        let (from_parent, from_name) = (self.open_parent()?, self.c_filename()?);
+        statat(&from_parent, &from_name, AtFlags::SYMLINK_NOFOLLOW).map_err(io_error)?;
+        to.create_parent_dirs()?;
+        let (to_parent, to_name) = (to.open_parent()?, to.c_filename()?);
+        if statat(&to_parent, &to_name, AtFlags::SYMLINK_NOFOLLOW).is_ok() {
+            return Err(SecurityError::Io(std::io::ErrorKind::AlreadyExists.into()));
+        }
+        renameat(&from_parent, &from_name, &to_parent, &to_name).map_err(io_error)
+    }
+
+    /// Files and directories under this directory, recursively and sorted,
+    /// as `(path relative to it, is a directory, size)`. Symlinks are
+    /// skipped, never followed.
+    pub fn walk(&self) -> Result<Vec<(PathBuf, bool, u64)>, SecurityError> {
+        let dir = self.open(OFlags::RDONLY | OFlags::DIRECTORY)?;
+        let mut entries = Vec::new();
+        walk_at(dir.as_fd(), Path::new(""), &mut entries).map_err(io_error)?;
+        Ok(entries)
+    }
+}
+
+fn io_error(e: Errno) -> SecurityError {
+    SecurityError::Io(std::io::Error::from_raw_os_error(e.raw_os_error()))
+}
+
+/// Names in the directory `dir`, without `.` and `..`, sorted.
+fn children(dir: BorrowedFd<'_>) -> rustix::io::Result<Vec<CString>> {
+    let mut names = Vec::new();
+    for entry in Dir::read_from(dir)? {
+        let name = entry?.file_name().to_owned();
+        if name.as_bytes() != b"." && name.as_bytes() != b".." {
+            names.push(name);
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+fn remove_at(parent: BorrowedFd<'_>, name: &CString) -> rustix::io::Result<()> {
+    let stat = statat(parent, name, AtFlags::SYMLINK_NOFOLLOW)?;
+    if FileType::from_raw_mode(stat.st_mode) != FileType::Directory {
+        return unlinkat(parent, name, AtFlags::empty());
+    }
+    let dir = openat(
+        parent,
+        name,
+        OFlags::RDONLY | OFlags::DIRECTORY | OFlags::NOFOLLOW | OFlags::CLOEXEC,
+        Mode::empty(),
+    )?;
+    for child in children(dir.as_fd())? {
+        remove_at(dir.as_fd(), &child)?;
+    }
+    unlinkat(parent, name, AtFlags::REMOVEDIR)
+}
+
+fn walk_at(
+    dir: BorrowedFd<'_>,
+    prefix: &Path,
+    entries: &mut Vec<(PathBuf, bool, u64)>,
+) -> rustix::io::Result<()> {
+    for name in children(dir)? {
+        let stat = statat(dir, &name, AtFlags::SYMLINK_NOFOLLOW)?;
+        let path = prefix.join(OsStr::from_bytes(name.as_bytes()));
+        match FileType::from_raw_mode(stat.st_mode) {
+            FileType::Directory => {
+                entries.push((path.clone(), true, stat.st_size as u64));
+                let child = openat(
+                    dir,
+                    &name,
+                    OFlags::RDONLY | OFlags::DIRECTORY | OFlags::NOFOLLOW | OFlags::CLOEXEC,
+                    Mode::empty(),
+                )?;
+                walk_at(child.as_fd(), &path, entries)?;
+            }
+            FileType::RegularFile => entries.push((path, false, stat.st_size as u64)),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
 impl Clone for SafePath {
     fn clone(&self) -> Self {
         Self {
@@ -359,6 +516,7 @@ impl Clone for SafePath {
             components: self.components.clone(),
             resolved_path: self.resolved_path.clone(),
             permissive: self.permissive,
+            read_only: self.read_only,
         }
     }
 }
@@ -374,6 +532,27 @@ mod tests {
         Arc::new(fd.into())
     }
 
+    #[test]
+    fn test_read_only_refuses_writes() {
+        let dir = tempdir().unwrap();
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        fs::write(root.join("test.txt"), "content").unwrap();
+
+        let path = SafePath::resolve(open_dir(&root), root.clone(), Path::new("test.txt"), 10)
+            .unwrap()
+            .into_read_only();
+        assert!(path.open(OFlags::RDONLY).is_ok());
+        assert!(matches!(
+            path.open(OFlags::WRONLY | OFlags::TRUNC),
+            Err(SecurityError::ReadOnly(_))
+        ));
+        assert!(path.create_parent_dirs().is_err());
+        assert_eq!(
+            fs::read_to_string(root.join("test.txt")).unwrap(),
+            "content"
+        );
+    }
+
     #[test]
     fn test_resolve_simple() {
         let dir = tempdir().unwrap();
@@ -0,0 +1,406 @@
+//! PII scrubbing with reversible tokenization.
+//!
+//! Personal data is replaced with stable tokens such as `[EMAIL_1]` before it
+//! leaves the process, and the tokens are mapped back when the model uses
+//! them, so tools still act on the real values.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, LazyLock, Mutex};
+
+use regex::{Captures, Regex};
+use serde_json::Value;
+
+use crate::types::{ContentBlock, Message, ToolResultContent, ToolResultContentBlock};
+
+static TOKEN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\[[A-Z][A-Z0-9_]*_\d+\]").unwrap());
+
+/// Longest token a streamed delta may end in the middle of.
+const MAX_TOKEN_LEN: usize = 48;
+
+#[derive(Clone)]
+struct PiiPattern {
+    label: String,
+    regex: Regex,
+    validate: Option<fn(&str) -> bool>,
+}
+
+fn default_patterns() -> Vec<PiiPattern> {
+    vec![
+        PiiPattern {
+            label: "EMAIL".into(),
+            regex: Regex::new(r"\b[A-Za-z0-9._%+\-]+@[A-Za-z0-9.\-]+\.[A-Za-z]{2,}\b").unwrap(),
+            validate: None,
+        },
+        PiiPattern {
+            label: "CREDIT_CARD".into(),
+            regex: Regex::new(r"\b\d(?:[ \-]?\d){12,18}\b").unwrap(),
+            validate: Some(is_luhn_valid),
+        },
+        PiiPattern {
+            label: "PHONE".into(),
+            regex: Regex::new(r"(?:\+\d{1,3}[\s.\-]?)?\(?\d{2,4}\)?[\s.\-]\d{3,4}[\s.\-]\d{4}\b")
+                .unwrap(),
+            validate: None,
+        },
+    ]
+}
+
+/// Card numbers are 13-19 digits with a valid Luhn checksum.
+fn is_luhn_valid(candidate: &str) -> bool {
+    let digits: Vec<u32> = candidate.chars().filter_map(|c| c.to_digit(10)).collect();
+    if !(13..=19).contains(&digits.len()) {
+        return false;
+    }
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match (i % 2 == 1, d * 2) {
+            (true, doubled) if doubled > 9 => doubled - 9,
+            (true, doubled) => doubled,
+            (false, _) => d,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+#[derive(Default)]
+struct Vault {
+    by_value: HashMap<String, String>,
+    by_token: HashMap<String, String>,
+    counts: HashMap<String, usize>,
+}
+
+impl Vault {
+    fn tokenize(&mut self, label: &str, value: &str) -> String {
+        if let Some(token) = self.by_value.get(value) {
+            return token.clone();
+        }
+        let count = self.counts.entry(label.to_string()).or_default();
+        *count += 1;
+        let token = format!("[{label}_{count}]");
+        self.by_value.insert(value.to_string(), token.clone());
+        self.by_token.insert(token.clone(), value.to_string());
+        token
+    }
+
+    fn insert(&mut self, token: String, value: String) {
+        // Keep numbering past seeded tokens so new ones never collide.
+        if let Some((label, n)) = token
+            .trim_matches(['[', ']'])
+            .rsplit_once('_')
+            .and_then(|(label, n)| Some((label, n.parse::<usize>().ok()?)))
+        {
+            let count = self.counts.entry(label.to_string()).or_default();
+            *count = (*count).max(n);
+        }
+        self.by_value.insert(value.clone(), token.clone());
+        self.by_token.insert(token, value);
+    }
+}
+
+/// Replaces emails, phone numbers, credit card numbers and custom patterns
+/// with tokens like `[EMAIL_1]`, and maps tokens back with [`restore`](Self::restore).
+///
+/// The same value always gets the same token. Clones share one vault, so a
+/// scrubber set on the agent and one wrapping session persistence (see
+/// [`ScrubbedPersistence`](crate::session::ScrubbedPersistence)) can restore
+/// each other's tokens. The vault lives in memory; use [`vault`](Self::vault)
+/// and [`with_vault`](Self::with_vault) to keep tokens restorable across
+/// restarts.
+///
+/// ```
+/// # use claude_agent::security::PiiScrubber;
+/// let scrubber = PiiScrubber::new();
+/// let scrubbed = scrubber.scrub("Mail alice@example.com or call +1 415-555-0100");
+/// assert_eq!(scrubbed, "Mail [EMAIL_1] or call [PHONE_1]");
+/// assert_eq!(
+///     scrubber.restore("Sent to [EMAIL_1]"),
+///     "Sent to alice@example.com"
+/// );
+/// ```
+#[derive(Clone)]
+pub struct PiiScrubber {
+    patterns: Vec<PiiPattern>,
+    vault: Arc<Mutex<Vault>>,
+}
+
+impl PiiScrubber {
+    /// Emails, phone numbers and Luhn-valid credit card numbers.
+    pub fn new() -> Self {
+        Self {
+            patterns: default_patterns(),
+            vault: Arc::default(),
+        }
+    }
+
+    /// No patterns; add them with [`pattern`](Self::pattern).
+    pub fn empty() -> Self {
+        Self {
+            patterns: Vec::new(),
+            vault: Arc::default(),
+        }
+    }
+
+    /// Tokenizes matches of `regex` as `[<LABEL>_n]`, e.g. label `"ssn"`
+    /// gives `[SSN_1]`. If the pattern has a capture group, only the first
+    /// group is tokenized.
+    pub fn pattern(mut self, label: &str, regex: Regex) -> Self {
+        let label = label
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_uppercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.patterns.push(PiiPattern {
+            label,
+            regex,
+            validate: None,
+        });
+        self
+    }
+
+    /// Seeds the vault with `token -> value` pairs saved from [`vault`](Self::vault).
+    pub fn with_vault(self, entries: impl IntoIterator<Item = (String, String)>) -> Self {
+        {
+            let mut vault = self.lock();
+            for (token, value) in entries {
+                vault.insert(token, value);
+            }
+        }
+        self
+    }
+
+    /// Current `token -> value` pairs.
+    ///
+    /// The values are the personal data itself; store them accordingly.
+    pub fn vault(&self) -> HashMap<String, String> {
+        self.lock().by_token.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vault> {
+        self.vault.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn scrub(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        let mut vault = self.lock();
+        for pattern in &self.patterns {
+            let replaced = pattern.regex.replace_all(&text, |caps: &Captures| {
+                let whole = caps.get(0).expect("match");
+                let target = caps.get(1).unwrap_or(whole);
+                if pattern
+                    .validate
+                    .is_some_and(|valid| !valid(target.as_str()))
+                {
+                    return whole.as_str().to_string();
+                }
+                format!(
+                    "{}{}{}",
+                    &text[whole.start()..target.start()],
+                    vault.tokenize(&pattern.label, target.as_str()),
+                    &text[target.end()..whole.end()]
+                )
+            });
+            text = replaced.into_owned();
+        }
+        text
+    }
+
+    /// Replaces known tokens with their values; unknown tokens are left as is.
+    pub fn restore(&self, text: &str) -> String {
+        let vault = self.lock();
+        TOKEN
+            .replace_all(text, |caps: &Captures| {
+                vault
+                    .by_token
+                    .get(&caps[0])
+                    .cloned()
+                    .unwrap_or_else(|| caps[0].to_string())
+            })
+            .into_owned()
+    }
+
+    /// Scrubs text, tool inputs and tool results in outbound messages.
+    /// Thinking blocks are left alone since their signatures cover the text.
+    pub fn scrub_messages(&self, messages: &mut [Message]) {
+        for message in messages {
+            self.scrub_blocks(&mut message.content);
+        }
+    }
+
+    pub fn scrub_blocks(&self, blocks: &mut [ContentBlock]) {
+        self.map_blocks(blocks, &|text| self.scrub(text));
+    }
+
+    pub fn restore_blocks(&self, blocks: &mut [ContentBlock]) {
+        self.map_blocks(blocks, &|text| self.restore(text));
+    }
+
+    /// Restores tokens in the string values of a tool input.
+    pub fn restore_value(&self, value: &mut Value) {
+        map_value(value, &|text| self.restore(text));
+    }
+
+    fn map_blocks(&self, blocks: &mut [ContentBlock], f: &dyn Fn(&str) -> String) {
+        for block in blocks {
+            match block {
+                ContentBlock::Text { text, .. } => *text = f(text),
+                ContentBlock::ToolUse(tool_use) => map_value(&mut tool_use.input, f),
+                ContentBlock::ToolResult(result) => match &mut result.content {
+                    Some(ToolResultContent::Text(text)) => *text = f(text),
+                    Some(ToolResultContent::Blocks(blocks)) => {
+                        for block in blocks {
+                            if let ToolResultContentBlock::Text { text } = block {
+                                *text = f(text);
+                            }
+                        }
+                    }
+                    None => {}
+                },
+                _ => {}
+            }
+        }
+    }
+}
+
+fn map_value(value: &mut Value, f: &dyn Fn(&str) -> String) {
+    match value {
+        Value::String(s) => *s = f(s),
+        Value::Array(items) => items.iter_mut().for_each(|item| map_value(item, f)),
+        Value::Object(map) => map.values_mut().for_each(|item| map_value(item, f)),
+        _ => {}
+    }
+}
+
+impl Default for PiiScrubber {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for PiiScrubber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let labels: Vec<&str> = self.patterns.iter().map(|p| p.label.as_str()).collect();
+        f.debug_struct("PiiScrubber")
+            .field("patterns", &labels)
+            .field("tokens", &self.lock().by_token.len())
+            .finish()
+    }
+}
+
+/// Restores tokens in streamed text deltas, holding back a token cut off at
+/// the end of a delta until the rest arrives.
+#[derive(Debug)]
+pub struct PiiStreamRestorer {
+    scrubber: PiiScrubber,
+    pending: String,
+}
+
+impl PiiStreamRestorer {
+    pub fn new(scrubber: PiiScrubber) -> Self {
+        Self {
+            scrubber,
+            pending: String::new(),
+        }
+    }
+
+    pub fn push(&mut self, chunk: &str) -> String {
+        self.pending.push_str(chunk);
+        let hold_from = self
+            .pending
+            .rfind('[')
+            .filter(|&i| !self.pending[i..].contains(']') && self.pending.len() - i < MAX_TOKEN_LEN)
+            .unwrap_or(self.pending.len());
+        let rest = self.pending.split_off(hold_from);
+        let ready = std::mem::replace(&mut self.pending, rest);
+        self.scrubber.restore(&ready)
+    }
+
+    pub fn finish(&mut self) -> String {
+        let rest = std::mem::take(&mut self.pending);
+        self.scrubber.restore(&rest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ToolResultBlock, ToolUseBlock};
+
+    #[test]
+    fn test_scrub_and_restore() {
+        let scrubber = PiiScrubber::new();
+        let text = "alice@example.com paid with 4111 1111 1111 1111, call (415) 555-0100; \
+                    alice@example.com again. Order 1234567890123 on 2024-10-17.";
+        let scrubbed = scrubber.scrub(text);
+        assert_eq!(
+            scrubbed,
+            "[EMAIL_1] paid with [CREDIT_CARD_1], call [PHONE_1]; \
+             [EMAIL_1] again. Order 1234567890123 on 2024-10-17."
+        );
+        assert_eq!(scrubber.restore(&scrubbed), text);
+        assert_eq!(scrubber.restore("[EMAIL_9]"), "[EMAIL_9]");
+    }
+
+    #[test]
+    fn test_custom_pattern_and_vault() {
+        let scrubber = PiiScrubber::empty()
+            .pattern("ssn", Regex::new(r"SSN (\d{3}-\d{2}-\d{4})").unwrap())
+            .with_vault([("[SSN_4]".to_string(), "000-00-0000".to_string())]);
+        assert_eq!(
+            scrubber.scrub("SSN 123-45-6789, bob@example.com"),
+            "SSN [SSN_5], bob@example.com"
+        );
+        assert_eq!(scrubber.vault().len(), 2);
+        assert_eq!(scrubber.restore("[SSN_4]"), "000-00-0000");
+    }
+
+    #[test]
+    fn test_blocks() {
+        let scrubber = PiiScrubber::new();
+        let mut messages = vec![
+            Message::user("Email bob@example.com"),
+            Message {
+                role: crate::types::Role::Assistant,
+                content: vec![ContentBlock::ToolUse(ToolUseBlock {
+                    id: "toolu_1".into(),
+                    name: "Send".into(),
+                    input: serde_json::json!({"to": ["bob@example.com"]}),
+                })],
+            },
+            Message {
+                role: crate::types::Role::User,
+                content: vec![ContentBlock::ToolResult(ToolResultBlock::success(
+                    "toolu_1",
+                    "sent to bob@example.com",
+                ))],
+            },
+        ];
+        scrubber.scrub_messages(&mut messages);
+        let json = serde_json::to_string(&messages).unwrap();
+        assert!(!json.contains("bob@example.com"));
+        assert_eq!(json.matches("[EMAIL_1]").count(), 3);
+
+        let mut input = serde_json::json!({"to": "[EMAIL_1]"});
+        scrubber.restore_value(&mut input);
+        assert_eq!(input["to"], "bob@example.com");
+    }
+
+    #[test]
+    fn test_stream_restorer() {
+        let scrubber = PiiScrubber::new();
+        scrubber.scrub("carol@example.com");
+        let mut stream = PiiStreamRestorer::new(scrubber);
+        let mut out = stream.push("Write to [EMA");
+        assert_eq!(out, "Write to ");
+        out.push_str(&stream.push("IL_1] now ["));
+        out.push_str(&stream.finish());
+        assert_eq!(out, "Write to carol@example.com now [");
+    }
+}
@@ -7,6 +7,8 @@ pub struct SecurityPolicy {
     pub permission: PermissionPolicy,
     pub allow_sandbox_bypass: bool,
     pub max_symlink_depth: u8,
+    /// Refuse every filesystem write and restrict Bash to read-only commands.
+    pub read_only: bool,
 }
 
 impl SecurityPolicy {
@@ -15,6 +17,7 @@ impl SecurityPolicy {
             permission,
             allow_sandbox_bypass: false,
             max_symlink_depth: 10,
+            read_only: false,
         }
     }
 
@@ -23,6 +26,7 @@ impl SecurityPolicy {
             permission: PermissionPolicy::permissive(),
             allow_sandbox_bypass: true,
             max_symlink_depth: 255,
+            read_only: false,
         }
     }
 
@@ -31,6 +35,16 @@ impl SecurityPolicy {
             permission: PermissionPolicy::new(),
             allow_sandbox_bypass: false,
             max_symlink_depth: 5,
+            read_only: false,
+        }
+    }
+
+    /// The default policy with writes refused at the filesystem layer and
+    /// Bash limited to [`READ_ONLY_COMMANDS`](crate::security::bash::READ_ONLY_COMMANDS).
+    pub fn read_only() -> Self {
+        Self {
+            read_only: true,
+            ..Self::default()
         }
     }
 
@@ -49,6 +63,10 @@ impl SecurityPolicy {
         self
     }
 
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
     pub fn can_bypass_sandbox(&self) -> bool {
         self.allow_sandbox_bypass
     }
@@ -87,4 +105,15 @@ mod tests {
         assert!(!policy.allow_sandbox_bypass);
         assert_eq!(policy.max_symlink_depth, 5);
     }
+
+    #[test]
+    fn test_read_only_policy() {
+        assert!(SecurityPolicy::read_only().is_read_only());
+        assert!(!SecurityPolicy::default().is_read_only());
+        assert!(
+            SecurityPolicy::read_only()
+                .permission(PermissionPolicy::permissive())
+                .is_read_only()
+        );
+    }
 }
@@ -0,0 +1,224 @@
+//! Unix-socket bridge from private network namespaces to the proxy.
+//!
+//! bubblewrap and container commands behind a proxy get a network namespace
+//! with nothing but loopback, so they cannot connect anywhere directly. The
+//! proxy's ports on the host are exposed as Unix sockets, mounted into the
+//! namespace, where `socat` serves them on the `127.0.0.1` ports the proxy
+//! variables point at. With the embedded proxy each
+//! [`CommandNetworkRule`](super::CommandNetworkRule) gets a bridge of its own
+//! to ports that only apply its domain lists, so a command cannot reach
+//! another rule's lists even with its credentials.
+
+use std::io;
+use std::net::{Ipv4Addr, Shutdown, TcpStream};
+use std::os::unix::fs::DirBuilderExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use super::{NetworkConfig, shell_escape};
+
+/// Where the sockets are mounted inside the namespace.
+pub(super) const MOUNT_DIR: &str = "/tmp/.claude-proxy";
+
+/// How often the namespace polls for `socat` to listen, 10ms apart.
+const READY_ATTEMPTS: u32 = 200;
+
+/// The bridges of a sandbox, one per scope.
+#[derive(Default)]
+pub struct ProxyBridges {
+    /// For commands no rule covers, or every command without the embedded
+    /// proxy.
+    pub default: Option<ProxyBridge>,
+    /// For the commands each rule covers, by rule index; empty without the
+    /// embedded proxy.
+    pub rules: Vec<Option<ProxyBridge>>,
+}
+
+impl ProxyBridges {
+    /// The bridge `command` reaches the proxy through, if it may reach it.
+    pub fn for_command(&self, network: &NetworkConfig, command: &str) -> Option<&ProxyBridge> {
+        if network.is_offline(command) {
+            return None;
+        }
+        match network.rule_for(command) {
+            Some(rule) if !self.rules.is_empty() => self.rules.get(rule)?.as_ref(),
+            _ => self.default.as_ref(),
+        }
+    }
+}
+
+/// Forwards connections on Unix sockets to proxy ports on `127.0.0.1`.
+///
+/// Runs on its own threads; the sockets are removed when it is dropped.
+pub struct ProxyBridge {
+    dir: PathBuf,
+    /// `(port, socket file name)` of each port served in the namespace.
+    ports: Vec<(u16, String)>,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl ProxyBridge {
+    /// Binds a socket in a new private directory for each
+    /// `(namespace port, host port)` pair.
+    pub fn start(ports: impl IntoIterator<Item = (u16, u16)>) -> io::Result<Self> {
+        let dir = std::env::temp_dir().join(format!("claude-proxy-{}", uuid::Uuid::new_v4()));
+        std::fs::DirBuilder::new().mode(0o700).create(&dir)?;
+        let mut bridge = Self {
+            dir,
+            ports: Vec::new(),
+            shutdown: Arc::new(AtomicBool::new(false)),
+        };
+        for (port, target) in ports {
+            let name = format!("{port}.sock");
+            let listener = UnixListener::bind(bridge.dir.join(&name))?;
+            bridge.serve(listener, target)?;
+            bridge.ports.push((port, name));
+        }
+        Ok(bridge)
+    }
+
+    fn serve(&self, listener: UnixListener, port: u16) -> io::Result<()> {
+        let shutdown = Arc::clone(&self.shutdown);
+        thread::Builder::new()
+            .name("sandbox-proxy-bridge".into())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::Acquire) {
+                        break;
+                    }
+                    let Ok(stream) = stream else { continue };
+                    thread::spawn(move || forward(stream, port));
+                }
+            })?;
+        Ok(())
+    }
+
+    /// Host directory holding the sockets.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Shell lines that start `socat` for each port inside the namespace and
+    /// wait until it listens. Fails the command if `socat` is missing, since
+    /// nothing else can reach the proxy.
+    pub fn script(&self, command: &str) -> String {
+        let mut script = String::from(
+            "command -v socat >/dev/null 2>&1 || \
+             { echo 'sandbox: socat is required to reach the network proxy' >&2; exit 126; }\n",
+        );
+        for (port, name) in &self.ports {
+            let socket = shell_escape(&format!("UNIX-CONNECT:{MOUNT_DIR}/{name}"));
+            script.push_str(&format!(
+                "socat TCP-LISTEN:{port},bind=127.0.0.1,reuseaddr,fork {socket} >/dev/null 2>&1 &\n\
+                 i=0; until socat -u /dev/null TCP:127.0.0.1:{port} 2>/dev/null || \
+                 [ $i -ge {READY_ATTEMPTS} ]; do i=$((i+1)); sleep 0.01; done\n"
+            ));
+        }
+        script.push_str(command);
+        script
+    }
+}
+
+impl Drop for ProxyBridge {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Release);
+        // Wake the accept loops so they observe the flag.
+        for (_, name) in &self.ports {
+            let _ = UnixStream::connect(self.dir.join(name));
+        }
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Copies both directions between `client` and the proxy on `port`.
+fn forward(client: UnixStream, port: u16) {
+    let Ok(mut upstream) = TcpStream::connect((Ipv4Addr::LOCALHOST, port)) else {
+        return;
+    };
+    let (Ok(mut client_read), Ok(mut upstream_write)) = (client.try_clone(), upstream.try_clone())
+    else {
+        return;
+    };
+    let outbound = thread::spawn(move || {
+        let _ = io::copy(&mut client_read, &mut upstream_write);
+        let _ = upstream_write.shutdown(Shutdown::Write);
+    });
+
+    let mut client_write = client;
+    let _ = io::copy(&mut upstream, &mut client_write);
+    let _ = client_write.shutdown(Shutdown::Write);
+    let _ = outbound.join();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::sandbox::CommandNetworkRule;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_forwarding() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0; 4];
+            stream.read_exact(&mut buf).unwrap();
+            stream.write_all(b"pong").unwrap();
+        });
+
+        let bridge = ProxyBridge::start([(8080, port)]).unwrap();
+        let mut stream = UnixStream::connect(bridge.dir().join("8080.sock")).unwrap();
+        stream.write_all(b"ping").unwrap();
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).unwrap();
+        assert_eq!(response, b"pong");
+
+        let dir = bridge.dir().to_path_buf();
+        drop(bridge);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_script() {
+        let bridge = ProxyBridge::start([(41000, 42000)]).unwrap();
+        let script = bridge.script("curl x.io");
+        assert!(script.starts_with("command -v socat"));
+        assert!(script.contains(
+            "socat TCP-LISTEN:41000,bind=127.0.0.1,reuseaddr,fork \
+             'UNIX-CONNECT:/tmp/.claude-proxy/41000.sock'"
+        ));
+        assert!(script.ends_with("\ncurl x.io"));
+    }
+
+    #[test]
+    fn test_for_command() {
+        let bridge = || ProxyBridge::start([(8080, 8080)]).ok();
+        let bridges = ProxyBridges {
+            default: bridge(),
+            rules: vec![bridge()],
+        };
+        let network = NetworkConfig::proxy(Some(8080), None)
+            .command_rule(CommandNetworkRule::new(["cargo:*"]));
+        let rule = bridges.rules[0].as_ref().map(ProxyBridge::dir);
+        let default = bridges.default.as_ref().map(ProxyBridge::dir);
+        let dir = |command| bridges.for_command(&network, command).map(ProxyBridge::dir);
+        assert_eq!(dir("cargo fetch"), rule);
+        assert_eq!(dir("curl x.io"), default);
+        assert_eq!(dir("cargo fetch && curl x.io"), default);
+
+        let offline = network.clone().offline_outside_rules();
+        assert!(bridges.for_command(&offline, "curl x.io").is_none());
+        assert!(bridges.for_command(&offline, "cargo fetch").is_some());
+
+        let external = ProxyBridges {
+            default: bridge(),
+            rules: Vec::new(),
+        };
+        assert!(external.for_command(&network, "cargo fetch").is_some());
+    }
+}
@@ -0,0 +1,304 @@
+//! Linux bubblewrap sandbox running each command in fresh namespaces.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use tracing::warn;
+
+use super::bridge::{MOUNT_DIR, ProxyBridges};
+use super::seccomp::{self, SeccompFile};
+use super::{
+    SandboxConfig, SandboxError, SandboxResult, SandboxRuntime, expand_home, find_in_path,
+    shell_escape,
+};
+
+/// Mounted read-only; symlinks such as `/bin -> usr/bin` are recreated.
+const SYSTEM_PATHS: &[&str] = &[
+    "/usr", "/bin", "/sbin", "/lib", "/lib64", "/lib32", "/etc", "/opt",
+];
+
+/// Descriptor `bwrap --seccomp` reads the filter from.
+const SECCOMP_FD: &str = "9";
+
+/// Toolchains under the home directory, mounted read-only when present.
+const HOME_TOOL_PATHS: &[&str] = &[".cargo", ".rustup", ".npm", ".nvm", ".local"];
+
+/// Wraps commands in `bwrap`.
+///
+/// Unlike Landlock, which only restricts access, the command sees a separate
+/// mount, PID, IPC and UTS namespace: only system paths, home toolchains,
+/// `allowed_paths` (read-only) and the working directory (read-write) exist,
+/// and `/tmp` is empty and private. With [`NetworkConfig::isolate`](super::NetworkConfig::isolate)
+/// or a proxy it also gets its own network namespace, where a
+/// [`ProxyBridge`](super::bridge::ProxyBridge) makes the proxy reachable and
+/// nothing else. A [`SeccompProfile`](super::SeccompProfile)
+/// is handed to `bwrap --seccomp`, which installs it right before the command.
+pub struct BubblewrapSandbox {
+    config: SandboxConfig,
+    available: bool,
+    seccomp: Option<SeccompFile>,
+    bridges: ProxyBridges,
+}
+
+impl BubblewrapSandbox {
+    pub fn new(config: SandboxConfig) -> Self {
+        let seccomp = seccomp::from_config(&config).and_then(|filter| {
+            SeccompFile::create(&filter)
+                .inspect_err(|e| {
+                    warn!(
+                        error = %e,
+                        "Failed to write the seccomp filter for bwrap. \
+                         Commands will execute without syscall filtering."
+                    )
+                })
+                .ok()
+        });
+        Self {
+            config,
+            available: is_bubblewrap_supported(),
+            seccomp,
+            bridges: ProxyBridges::default(),
+        }
+    }
+
+    /// Reach the proxy through `bridges`. Commands behind a proxy without a
+    /// bridge get no network.
+    pub(super) fn proxy_bridges(mut self, bridges: ProxyBridges) -> Self {
+        self.bridges = bridges;
+        self
+    }
+
+    fn args(&self, command: &str) -> Vec<String> {
+        let mut args: Vec<String> = [
+            "--die-with-parent",
+            "--new-session",
+            "--unshare-user-try",
+            "--unshare-pid",
+            "--unshare-ipc",
+            "--unshare-uts",
+            "--unshare-cgroup-try",
+        ]
+        .map(String::from)
+        .to_vec();
+        let network = &self.config.network;
+        if network.is_offline(command) || network.has_proxy() {
+            args.push("--unshare-net".into());
+        }
+        let bridge = self
+            .bridges
+            .for_command(network, command)
+            .filter(|_| network.has_proxy());
+
+        for path in SYSTEM_PATHS.iter().map(Path::new) {
+            match std::fs::read_link(path) {
+                Ok(target) => args.extend([
+                    "--symlink".into(),
+                    target.display().to_string(),
+                    path.display().to_string(),
+                ]),
+                Err(_) if path.exists() => args.extend(ro_bind(path)),
+                Err(_) => {}
+            }
+        }
+        if let Some(home) = crate::common::home_dir() {
+            for path in HOME_TOOL_PATHS.iter().map(|p| home.join(p)) {
+                if path.exists() {
+                    args.extend(ro_bind(&path));
+                }
+            }
+        }
+        args.extend(["--proc", "/proc", "--dev", "/dev", "--tmpfs", "/tmp"].map(String::from));
+        if let Some(bridge) = bridge {
+            args.extend([
+                "--bind".into(),
+                bridge.dir().display().to_string(),
+                MOUNT_DIR.into(),
+            ]);
+        }
+
+        for path in &self.config.allowed_paths {
+            if path.exists() {
+                args.extend(ro_bind(path));
+            }
+        }
+        for socket in &self.config.network.allow_unix_sockets {
+            let socket = expand_home(socket);
+            if socket.exists() {
+                let socket = socket.display().to_string();
+                args.extend(["--bind".into(), socket.clone(), socket]);
+            }
+        }
+
+        if self.seccomp.is_some() {
+            args.extend(["--seccomp".into(), SECCOMP_FD.into()]);
+        }
+
+        let working_dir = self.config.working_dir.display().to_string();
+        args.extend([
+            "--bind".into(),
+            working_dir.clone(),
+            working_dir.clone(),
+            "--chdir".into(),
+            working_dir,
+            "--".into(),
+            "bash".into(),
+            "-c".into(),
+            bridge.map_or_else(|| command.to_string(), |bridge| bridge.script(command)),
+        ]);
+        args
+    }
+}
+
+fn ro_bind(path: &Path) -> [String; 3] {
+    let path = path.display().to_string();
+    ["--ro-bind".into(), path.clone(), path]
+}
+
+/// Whether `bwrap` is installed and can create namespaces, which some
+/// distributions restrict for unprivileged users.
+pub fn is_bubblewrap_supported() -> bool {
+    let Some(bwrap) = find_in_path("bwrap") else {
+        return false;
+    };
+    Command::new(bwrap)
+        .args(["--unshare-user-try", "--ro-bind", "/", "/", "true"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+impl SandboxRuntime for BubblewrapSandbox {
+    fn is_available(&self) -> bool {
+        self.available
+    }
+
+    fn apply(&self) -> SandboxResult<()> {
+        Err(SandboxError::InvalidConfig(
+            "bubblewrap requires command wrapping, cannot apply to current process".into(),
+        ))
+    }
+
+    fn wrap_command(&self, command: &str) -> SandboxResult<String> {
+        let args: Vec<String> = self.args(command).iter().map(|a| shell_escape(a)).collect();
+        let mut wrapped = format!("bwrap {}", args.join(" "));
+        if let Some(file) = &self.seccomp {
+            wrapped.push_str(&format!(
+                " {SECCOMP_FD}< {}",
+                shell_escape(&file.path().display().to_string())
+            ));
+        }
+        Ok(wrapped)
+    }
+
+    fn environment_vars(&self) -> HashMap<String, String> {
+        HashMap::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::sandbox::bridge::ProxyBridge;
+    use crate::security::sandbox::{CommandNetworkRule, NetworkConfig};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_args() {
+        let allowed = tempfile::tempdir().unwrap();
+        let config = SandboxConfig::new(PathBuf::from("/work/app"))
+            .allowed_paths([allowed.path().to_path_buf(), PathBuf::from("/missing")]);
+        let args = BubblewrapSandbox::new(config).args("cargo test").join(" ");
+
+        let allowed = allowed.path().display();
+        assert!(args.starts_with("--die-with-parent --new-session"));
+        assert!(!args.contains("--unshare-net"));
+        assert!(args.contains("--ro-bind /usr /usr"));
+        assert!(args.contains("--tmpfs /tmp"));
+        assert!(args.contains(&format!("--ro-bind {allowed} {allowed}")));
+        assert!(!args.contains("/missing"));
+        assert!(
+            args.ends_with("--bind /work/app /work/app --chdir /work/app -- bash -c cargo test")
+        );
+    }
+
+    #[test]
+    fn test_isolated_network() {
+        let config =
+            SandboxConfig::new(PathBuf::from("/work")).network(NetworkConfig::new().isolated());
+        let sandbox = BubblewrapSandbox::new(config);
+        assert!(sandbox.args("ls").contains(&"--unshare-net".to_string()));
+
+        let wrapped = sandbox.wrap_command("echo 'hi'").unwrap();
+        assert!(wrapped.starts_with("bwrap '--die-with-parent'"));
+        assert!(wrapped.ends_with("'bash' '-c' 'echo '\\''hi'\\'''"));
+    }
+
+    #[test]
+    fn test_proxy_bridge() {
+        let network = NetworkConfig::proxy(Some(8080), Some(1080));
+        let config = SandboxConfig::new(PathBuf::from("/work")).network(network);
+        let bridge = ProxyBridge::start([(8080, 8080), (1080, 1080)]).unwrap();
+        let dir = bridge.dir().display().to_string();
+        let args = BubblewrapSandbox::new(config.clone())
+            .proxy_bridges(ProxyBridges {
+                default: Some(bridge),
+                rules: Vec::new(),
+            })
+            .args("curl x.io");
+
+        let joined = args.join(" ");
+        assert!(joined.contains("--unshare-net"));
+        assert!(joined.contains(&format!("--tmpfs /tmp --bind {dir} {MOUNT_DIR}")));
+        let script = args.last().unwrap();
+        assert!(script.contains("socat TCP-LISTEN:8080"));
+        assert!(script.contains("socat TCP-LISTEN:1080"));
+        assert!(script.ends_with("\ncurl x.io"));
+
+        // Without a bridge the proxy is unreachable, and so is everything else.
+        let args = BubblewrapSandbox::new(config).args("curl x.io");
+        assert!(args.contains(&"--unshare-net".to_string()));
+        assert_eq!(args.last().unwrap(), "curl x.io");
+    }
+
+    #[test]
+    fn test_offline_outside_rules() {
+        let network = NetworkConfig::new()
+            .command_rule(CommandNetworkRule::new(["cargo:*"]))
+            .offline_outside_rules();
+        let sandbox =
+            BubblewrapSandbox::new(SandboxConfig::new(PathBuf::from("/work")).network(network));
+        assert!(
+            !sandbox
+                .args("cargo fetch")
+                .contains(&"--unshare-net".to_string())
+        );
+        assert!(
+            sandbox
+                .args("curl x.io")
+                .contains(&"--unshare-net".to_string())
+        );
+    }
+
+    #[test]
+    fn test_seccomp_fd() {
+        let config = SandboxConfig::new(PathBuf::from("/work"))
+            .seccomp(crate::security::sandbox::SeccompProfile::new());
+        let sandbox = BubblewrapSandbox::new(config);
+        let Some(file) = &sandbox.seccomp else {
+            return; // Unsupported architecture
+        };
+        let path = file.path().to_path_buf();
+        assert!(path.exists());
+
+        let args = sandbox.args("ls").join(" ");
+        assert!(args.contains("--seccomp 9 --bind /work /work"));
+        let wrapped = sandbox.wrap_command("ls").unwrap();
+        assert!(wrapped.ends_with(&format!(" 9< '{}'", path.display())));
+
+        drop(sandbox);
+        assert!(!path.exists());
+    }
+}
@@ -2,11 +2,13 @@
 //!
 //! Reference: <https://code.claude.com/docs/en/sandboxing>
 
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
+use crate::security::bash::{CommandPattern, matches_all};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SandboxConfig {
@@ -28,6 +30,9 @@ pub struct SandboxConfig {
     #[serde(default)]
     pub enable_weaker_nested_sandbox: bool,
 
+    #[serde(default)]
+    pub backend: SandboxBackend,
+
     #[serde(skip)]
     pub working_dir: PathBuf,
 
@@ -42,6 +47,15 @@ pub struct SandboxConfig {
 
     #[serde(default)]
     pub blocked_domains: HashSet<String>,
+
+    /// Run Bash commands in containers instead of the OS sandbox.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<ContainerConfig>,
+
+    /// Syscall filter for Bash commands on Linux, on top of the filesystem
+    /// sandbox.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seccomp: Option<SeccompProfile>,
 }
 
 fn default_auto_allow_bash() -> bool {
@@ -61,11 +75,14 @@ impl Default for SandboxConfig {
             allow_unsandboxed_commands: true,
             network: NetworkConfig::default(),
             enable_weaker_nested_sandbox: false,
+            backend: SandboxBackend::default(),
             working_dir: PathBuf::new(),
             allowed_paths: Vec::new(),
             denied_paths: Vec::new(),
             allowed_domains: HashSet::new(),
             blocked_domains: HashSet::new(),
+            container: None,
+            seccomp: None,
         }
     }
 }
@@ -133,6 +150,23 @@ impl SandboxConfig {
         self
     }
 
+    pub fn backend(mut self, backend: SandboxBackend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Run Bash commands in containers described by `container`.
+    pub fn container(mut self, container: ContainerConfig) -> Self {
+        self.container = Some(container);
+        self
+    }
+
+    /// Install `profile` as a seccomp filter in Bash commands (Linux only).
+    pub fn seccomp(mut self, profile: SeccompProfile) -> Self {
+        self.seccomp = Some(profile);
+        self
+    }
+
     pub fn to_network_sandbox(&self) -> super::NetworkSandbox {
         super::NetworkSandbox::new()
             .allowed_domains(self.allowed_domains.iter().cloned())
@@ -153,6 +187,18 @@ impl SandboxConfig {
     }
 }
 
+/// OS sandbox used when no container is configured.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SandboxBackend {
+    /// Landlock on Linux, Seatbelt on macOS.
+    #[default]
+    Auto,
+    /// bubblewrap namespaces on Linux, falling back to `Auto` where `bwrap`
+    /// is missing or cannot create namespaces.
+    Bubblewrap,
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct NetworkConfig {
@@ -167,6 +213,25 @@ pub struct NetworkConfig {
 
     #[serde(default)]
     pub socks_proxy_port: Option<u16>,
+
+    /// No network at all: a separate network namespace with bubblewrap,
+    /// `--network none` with containers. Proxy ports are unreachable then.
+    #[serde(default)]
+    pub isolate: bool,
+
+    /// Run the built-in filtering proxy and point commands at it. Its ports
+    /// replace `http_proxy_port` and `socks_proxy_port`.
+    #[serde(default)]
+    pub embedded_proxy: bool,
+
+    /// Domain lists for particular commands, in place of the sandbox-wide
+    /// ones. The first rule covering a command applies.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub command_rules: Vec<CommandNetworkRule>,
+
+    /// Commands no rule covers get no network at all.
+    #[serde(default)]
+    pub offline_outside_rules: bool,
 }
 
 impl NetworkConfig {
@@ -192,6 +257,42 @@ impl NetworkConfig {
         self
     }
 
+    pub fn isolated(mut self) -> Self {
+        self.isolate = true;
+        self
+    }
+
+    /// Enforce the domain allowlist with [`EgressProxy`](super::EgressProxy).
+    pub fn embedded_proxy(mut self) -> Self {
+        self.embedded_proxy = true;
+        self
+    }
+
+    /// Give commands matching `rule` its own domain lists.
+    pub fn command_rule(mut self, rule: CommandNetworkRule) -> Self {
+        self.command_rules.push(rule);
+        self
+    }
+
+    /// Cut commands no [`command_rule`](Self::command_rule) covers off the
+    /// network.
+    pub fn offline_outside_rules(mut self) -> Self {
+        self.offline_outside_rules = true;
+        self
+    }
+
+    /// Index of the first rule covering `command`.
+    pub fn rule_for(&self, command: &str) -> Option<usize> {
+        self.command_rules
+            .iter()
+            .position(|rule| rule.matches(command))
+    }
+
+    /// Whether `command` must run without network access.
+    pub fn is_offline(&self, command: &str) -> bool {
+        self.isolate || (self.offline_outside_rules && self.rule_for(command).is_none())
+    }
+
     pub fn has_proxy(&self) -> bool {
         self.http_proxy_port.is_some() || self.socks_proxy_port.is_some()
     }
@@ -211,6 +312,299 @@ impl NetworkConfig {
     }
 }
 
+/// Network access for commands matching a set of patterns, e.g. `npm:*`
+/// reaching `registry.npmjs.org` while everything else stays offline.
+///
+/// A command is covered when every simple command in it matches one of
+/// `commands`, so `npm install && curl x` is not covered by `npm:*`. The
+/// embedded proxy enforces the rule's domain lists.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandNetworkRule {
+    /// Patterns like `cargo:*`; see [`CommandPattern`].
+    pub commands: Vec<String>,
+
+    #[serde(default)]
+    pub allowed_domains: HashSet<String>,
+
+    #[serde(default)]
+    pub blocked_domains: HashSet<String>,
+}
+
+impl CommandNetworkRule {
+    pub fn new(commands: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            commands: commands.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn allow_domain(mut self, domain: impl Into<String>) -> Self {
+        self.allowed_domains.insert(domain.into());
+        self
+    }
+
+    pub fn deny_domain(mut self, domain: impl Into<String>) -> Self {
+        self.blocked_domains.insert(domain.into());
+        self
+    }
+
+    pub fn matches(&self, command: &str) -> bool {
+        let patterns: Vec<CommandPattern> = self
+            .commands
+            .iter()
+            .map(|c| CommandPattern::new(c.as_str()))
+            .collect();
+        matches_all(&patterns, command)
+    }
+
+    pub fn to_network_sandbox(&self) -> super::NetworkSandbox {
+        super::NetworkSandbox::new()
+            .allowed_domains(self.allowed_domains.iter().cloned())
+            .blocked_domains(self.blocked_domains.iter().cloned())
+    }
+}
+
+/// Syscalls that sandboxed commands cannot make; they fail with `EPERM`.
+///
+/// The default denies debugging other processes, mounting, namespaces,
+/// kernel modules and keyrings, eBPF, io_uring, rebooting and raw sockets —
+/// calls ordinary builds and tests do not need.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeccompProfile {
+    /// Syscall names, e.g. `ptrace`. Names unknown on the running
+    /// architecture are skipped.
+    #[serde(default = "default_denied_syscalls")]
+    pub denied_syscalls: BTreeSet<String>,
+
+    /// Deny `AF_PACKET` sockets and `SOCK_RAW` IPv4/IPv6 sockets.
+    #[serde(default = "default_deny_raw_sockets")]
+    pub deny_raw_sockets: bool,
+}
+
+/// Denied by [`SeccompProfile::default`].
+pub const DEFAULT_DENIED_SYSCALLS: &[&str] = &[
+    "ptrace",
+    "process_vm_readv",
+    "process_vm_writev",
+    "mount",
+    "umount2",
+    "pivot_root",
+    "chroot",
+    "move_mount",
+    "open_tree",
+    "fsopen",
+    "fsmount",
+    "fsconfig",
+    "unshare",
+    "setns",
+    "init_module",
+    "finit_module",
+    "delete_module",
+    "kexec_load",
+    "kexec_file_load",
+    "reboot",
+    "swapon",
+    "swapoff",
+    "bpf",
+    "perf_event_open",
+    "userfaultfd",
+    "add_key",
+    "request_key",
+    "keyctl",
+    "acct",
+    "open_by_handle_at",
+    "io_uring_setup",
+    "io_uring_enter",
+    "io_uring_register",
+    "iopl",
+    "ioperm",
+];
+
+fn default_denied_syscalls() -> BTreeSet<String> {
+    DEFAULT_DENIED_SYSCALLS
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn default_deny_raw_sockets() -> bool {
+    true
+}
+
+impl Default for SeccompProfile {
+    fn default() -> Self {
+        Self {
+            denied_syscalls: default_denied_syscalls(),
+            deny_raw_sockets: true,
+        }
+    }
+}
+
+impl SeccompProfile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A profile denying nothing, to build up with [`deny`](Self::deny).
+    pub fn empty() -> Self {
+        Self {
+            denied_syscalls: BTreeSet::new(),
+            deny_raw_sockets: false,
+        }
+    }
+
+    pub fn deny(mut self, syscall: impl Into<String>) -> Self {
+        self.denied_syscalls.insert(syscall.into());
+        self
+    }
+
+    /// Remove `syscall` from the denied set, e.g. `ptrace` for debuggers.
+    pub fn allow(mut self, syscall: &str) -> Self {
+        self.denied_syscalls.remove(syscall);
+        self
+    }
+
+    pub fn raw_sockets(mut self, allow: bool) -> Self {
+        self.deny_raw_sockets = !allow;
+        self
+    }
+}
+
+/// Container engine running sandboxed commands.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContainerEngine {
+    #[default]
+    Docker,
+    Podman,
+}
+
+impl ContainerEngine {
+    pub fn binary(&self) -> &'static str {
+        match self {
+            Self::Docker => "docker",
+            Self::Podman => "podman",
+        }
+    }
+}
+
+/// Ephemeral container each sandboxed command runs in.
+///
+/// The working directory is mounted read-write at the same path and
+/// `allowed_paths` read-only, so paths in commands mean the same inside.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerConfig {
+    #[serde(default)]
+    pub engine: ContainerEngine,
+
+    #[serde(default = "default_container_image")]
+    pub image: String,
+
+    /// Shell inside the image that runs the command.
+    #[serde(default = "default_container_shell")]
+    pub shell: String,
+
+    /// CPU limit, e.g. `1.5`.
+    #[serde(default)]
+    pub cpus: Option<f64>,
+
+    /// Memory limit, e.g. `512m`.
+    #[serde(default)]
+    pub memory: Option<String>,
+
+    #[serde(default)]
+    pub pids_limit: Option<u32>,
+
+    /// Network mode, e.g. `bridge`. Derived from the sandbox's network
+    /// settings when unset.
+    #[serde(default)]
+    pub network: Option<String>,
+
+    /// Environment variables passed from the tool's environment.
+    #[serde(default)]
+    pub env: Vec<String>,
+
+    /// Extra arguments for `run`, before the image.
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+}
+
+fn default_container_image() -> String {
+    "debian:stable-slim".into()
+}
+
+fn default_container_shell() -> String {
+    "sh".into()
+}
+
+impl Default for ContainerConfig {
+    fn default() -> Self {
+        Self {
+            engine: ContainerEngine::default(),
+            image: default_container_image(),
+            shell: default_container_shell(),
+            cpus: None,
+            memory: None,
+            pids_limit: None,
+            network: None,
+            env: Vec::new(),
+            extra_args: Vec::new(),
+        }
+    }
+}
+
+impl ContainerConfig {
+    pub fn new(image: impl Into<String>) -> Self {
+        Self {
+            image: image.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn engine(mut self, engine: ContainerEngine) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    pub fn shell(mut self, shell: impl Into<String>) -> Self {
+        self.shell = shell.into();
+        self
+    }
+
+    pub fn cpus(mut self, cpus: f64) -> Self {
+        self.cpus = Some(cpus);
+        self
+    }
+
+    pub fn memory(mut self, memory: impl Into<String>) -> Self {
+        self.memory = Some(memory.into());
+        self
+    }
+
+    pub fn pids_limit(mut self, limit: u32) -> Self {
+        self.pids_limit = Some(limit);
+        self
+    }
+
+    pub fn network(mut self, mode: impl Into<String>) -> Self {
+        self.network = Some(mode.into());
+        self
+    }
+
+    pub fn env(mut self, names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.env = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn extra_args(mut self, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.extra_args = args.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -271,6 +665,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_command_rules() {
+        let network = NetworkConfig::new()
+            .command_rule(CommandNetworkRule::new(["npm:*", "npx:*"]).allow_domain("npmjs.org"))
+            .command_rule(CommandNetworkRule::new(["cargo:*"]).allow_domain("crates.io"))
+            .offline_outside_rules();
+
+        assert_eq!(network.rule_for("npm ci && npx tsc"), Some(0));
+        assert_eq!(network.rule_for("cd app && cargo build"), None);
+        assert_eq!(network.rule_for("cargo build --release"), Some(1));
+        assert!(!network.is_offline("cargo fetch"));
+        assert!(network.is_offline("curl https://crates.io"));
+        assert!(NetworkConfig::new().isolated().is_offline("cargo fetch"));
+        assert!(!NetworkConfig::new().is_offline("curl example.com"));
+
+        let json = r#"{"commandRules": [{"commands": ["pip:*"], "allowedDomains": ["pypi.org"]}],
+                       "offlineOutsideRules": true}"#;
+        let parsed: NetworkConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed.rule_for("pip install x"), Some(0));
+        assert!(parsed.command_rules[0].allowed_domains.contains("pypi.org"));
+        assert!(parsed.offline_outside_rules);
+    }
+
     #[test]
     fn test_unix_sockets() {
         let network = NetworkConfig::new().unix_sockets(vec!["~/.ssh/agent-socket".into()]);
@@ -296,5 +713,34 @@ mod tests {
         assert!(config.excluded_commands.contains("docker"));
         assert!(!config.allow_unsandboxed_commands);
         assert_eq!(config.network.http_proxy_port, Some(8080));
+        assert!(config.container.is_none());
+    }
+
+    #[test]
+    fn test_container_serde() {
+        let json = r#"{
+            "enabled": true,
+            "container": {"engine": "podman", "image": "node:22", "memory": "1g"}
+        }"#;
+        let config: SandboxConfig = serde_json::from_str(json).unwrap();
+        let container = config.container.unwrap();
+        assert_eq!(container.engine, ContainerEngine::Podman);
+        assert_eq!(container.image, "node:22");
+        assert_eq!(container.memory.as_deref(), Some("1g"));
+        assert_eq!(container.shell, "sh");
+    }
+
+    #[test]
+    fn test_seccomp_serde() {
+        let json = r#"{"enabled": true, "seccomp": {"deniedSyscalls": ["ptrace"]}}"#;
+        let config: SandboxConfig = serde_json::from_str(json).unwrap();
+        let profile = config.seccomp.unwrap();
+        assert_eq!(profile.denied_syscalls.len(), 1);
+        assert!(profile.deny_raw_sockets);
+
+        let profile = SeccompProfile::new().allow("ptrace").raw_sockets(true);
+        assert!(!profile.denied_syscalls.contains("ptrace"));
+        assert!(profile.denied_syscalls.contains("mount"));
+        assert!(!profile.deny_raw_sockets);
     }
 }
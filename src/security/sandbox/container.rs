@@ -0,0 +1,271 @@
+//! Container sandbox running each command in an ephemeral Docker or Podman container.
+
+use std::collections::HashMap;
+
+use super::bridge::{MOUNT_DIR, ProxyBridge, ProxyBridges};
+use super::{
+    ContainerConfig, ContainerEngine, SandboxConfig, SandboxError, SandboxResult, SandboxRuntime,
+    expand_home, find_in_path, shell_escape,
+};
+
+/// Proxy variables set by [`Sandbox`](super::Sandbox) and passed into the container.
+const PROXY_VARS: &[&str] = &[
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "http_proxy",
+    "https_proxy",
+    "ALL_PROXY",
+    "all_proxy",
+    "NO_PROXY",
+    "no_proxy",
+];
+
+/// Wraps commands in `docker run --rm` (or `podman run --rm`).
+///
+/// Containers are named `claude-sandbox-<uuid>` so any left running after the
+/// engine CLI is killed, e.g. on a tool timeout, can be found and removed.
+///
+/// Unless [`ContainerConfig::network`] says otherwise, containers get
+/// `--network none`; behind a proxy, a [`ProxyBridge`] makes the proxy
+/// reachable and nothing else.
+pub struct ContainerSandbox {
+    config: SandboxConfig,
+    container: ContainerConfig,
+    bridges: ProxyBridges,
+}
+
+impl ContainerSandbox {
+    pub fn new(config: SandboxConfig) -> Self {
+        let container = config.container.clone().unwrap_or_default();
+        Self {
+            config,
+            container,
+            bridges: ProxyBridges::default(),
+        }
+    }
+
+    /// Reach the proxy through `bridges`. Commands behind a proxy without a
+    /// bridge get no network.
+    pub(super) fn proxy_bridges(mut self, bridges: ProxyBridges) -> Self {
+        self.bridges = bridges;
+        self
+    }
+
+    /// The bridge `command` reaches the proxy through, if it may.
+    fn bridge_for(&self, command: &str) -> Option<&ProxyBridge> {
+        if self.container.network.is_some() {
+            return None;
+        }
+        self.bridges.for_command(&self.config.network, command)
+    }
+
+    fn run_args(&self, command: &str) -> Vec<String> {
+        let working_dir = self.config.working_dir.display().to_string();
+        let mut args = vec![
+            "run".to_string(),
+            "--rm".into(),
+            "--init".into(),
+            "--name".into(),
+            format!("claude-sandbox-{}", uuid::Uuid::new_v4()),
+            "--network".into(),
+            self.container
+                .network
+                .clone()
+                .unwrap_or_else(|| "none".into()),
+            "--volume".into(),
+            format!("{working_dir}:{working_dir}"),
+            "--workdir".into(),
+            working_dir,
+        ];
+        for path in &self.config.allowed_paths {
+            let path = path.display();
+            args.extend(["--volume".into(), format!("{path}:{path}:ro")]);
+        }
+        for socket in &self.config.network.allow_unix_sockets {
+            let socket = expand_home(socket);
+            let socket = socket.display();
+            args.extend(["--volume".into(), format!("{socket}:{socket}")]);
+        }
+        let bridge = self.bridge_for(command);
+        if let Some(bridge) = bridge {
+            let dir = bridge.dir().display();
+            args.extend(["--volume".into(), format!("{dir}:{MOUNT_DIR}")]);
+        }
+
+        // Files written to the working directory stay owned by the host user.
+        match self.container.engine {
+            ContainerEngine::Docker => {
+                let uid = rustix::process::getuid().as_raw();
+                let gid = rustix::process::getgid().as_raw();
+                args.extend(["--user".into(), format!("{uid}:{gid}")]);
+            }
+            ContainerEngine::Podman => args.push("--userns=keep-id".into()),
+        }
+
+        if let Some(cpus) = self.container.cpus {
+            args.extend(["--cpus".into(), cpus.to_string()]);
+        }
+        if let Some(memory) = &self.container.memory {
+            args.extend(["--memory".into(), memory.clone()]);
+        }
+        if let Some(limit) = self.container.pids_limit {
+            args.extend(["--pids-limit".into(), limit.to_string()]);
+        }
+
+        // `--env NAME` copies the variable from the engine CLI's environment.
+        let proxy_vars = PROXY_VARS
+            .iter()
+            .copied()
+            .filter(|_| self.config.network.has_proxy());
+        for name in self
+            .container
+            .env
+            .iter()
+            .map(String::as_str)
+            .chain(proxy_vars)
+        {
+            args.extend(["--env".into(), name.to_string()]);
+        }
+
+        args.extend(self.container.extra_args.iter().cloned());
+        args.extend([
+            self.container.image.clone(),
+            self.container.shell.clone(),
+            "-c".into(),
+            bridge.map_or_else(|| command.to_string(), |bridge| bridge.script(command)),
+        ]);
+        args
+    }
+}
+
+impl SandboxRuntime for ContainerSandbox {
+    fn is_available(&self) -> bool {
+        find_in_path(self.container.engine.binary()).is_some()
+    }
+
+    fn apply(&self) -> SandboxResult<()> {
+        Err(SandboxError::InvalidConfig(
+            "container sandbox requires command wrapping, cannot apply to current process".into(),
+        ))
+    }
+
+    fn wrap_command(&self, command: &str) -> SandboxResult<String> {
+        let binary = self.container.engine.binary();
+        if self.container.image.is_empty() {
+            return Err(SandboxError::InvalidConfig(
+                "container sandbox has no image".into(),
+            ));
+        }
+        let args: Vec<String> = self
+            .run_args(command)
+            .iter()
+            .map(|arg| shell_escape(arg))
+            .collect();
+        Ok(format!("{} {}", binary, args.join(" ")))
+    }
+
+    fn environment_vars(&self) -> HashMap<String, String> {
+        HashMap::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::sandbox::{CommandNetworkRule, NetworkConfig};
+    use std::path::PathBuf;
+
+    fn sandbox(config: SandboxConfig) -> ContainerSandbox {
+        ContainerSandbox::new(config)
+    }
+
+    #[test]
+    fn test_run_args() {
+        let config = SandboxConfig::new(PathBuf::from("/work/app"))
+            .allowed_paths([PathBuf::from("/opt/tools")])
+            .container(
+                ContainerConfig::new("rust:1")
+                    .cpus(1.5)
+                    .memory("512m")
+                    .pids_limit(256)
+                    .env(["CARGO_HOME"]),
+            );
+        let args = sandbox(config).run_args("cargo test");
+
+        let joined = args.join(" ");
+        assert!(joined.starts_with("run --rm --init --name claude-sandbox-"));
+        assert!(joined.contains("--network none"));
+        assert!(joined.contains("--volume /work/app:/work/app --workdir /work/app"));
+        assert!(joined.contains("--volume /opt/tools:/opt/tools:ro"));
+        assert!(joined.contains("--cpus 1.5 --memory 512m --pids-limit 256"));
+        assert!(joined.contains("--env CARGO_HOME"));
+        assert!(!joined.contains("HTTP_PROXY"));
+        assert_eq!(args[args.len() - 4..], ["rust:1", "sh", "-c", "cargo test"]);
+    }
+
+    #[test]
+    fn test_network_mode() {
+        let base = SandboxConfig::new(PathBuf::from("/work"));
+        let proxied = base
+            .clone()
+            .network(NetworkConfig::proxy(Some(8080), None))
+            .container(ContainerConfig::default());
+        let bridge = ProxyBridge::start([(8080, 8080)]).unwrap();
+        let dir = bridge.dir().display().to_string();
+        let args = sandbox(proxied.clone())
+            .proxy_bridges(ProxyBridges {
+                default: Some(bridge),
+                rules: Vec::new(),
+            })
+            .run_args("curl example.com");
+        let joined = args.join(" ");
+        assert!(joined.contains("--network none"));
+        assert!(joined.contains(&format!("--volume {dir}:{MOUNT_DIR}")));
+        assert!(joined.contains("--env HTTP_PROXY"));
+        assert!(args.last().unwrap().contains("socat TCP-LISTEN:8080"));
+        assert!(args.last().unwrap().ends_with("\ncurl example.com"));
+
+        // Without a bridge the proxy is unreachable, and so is everything else.
+        let args = sandbox(proxied).run_args("curl example.com");
+        assert!(args.join(" ").contains("--network none"));
+        assert_eq!(args.last().unwrap(), "curl example.com");
+
+        let ruled = base.clone().container(ContainerConfig::default()).network(
+            NetworkConfig::proxy(Some(8080), None)
+                .command_rule(CommandNetworkRule::new(["cargo:*"]))
+                .offline_outside_rules(),
+        );
+        let ruled = sandbox(ruled).proxy_bridges(ProxyBridges {
+            default: ProxyBridge::start([(8080, 8080)]).ok(),
+            rules: vec![ProxyBridge::start([(8080, 9080)]).ok()],
+        });
+        assert!(
+            ruled
+                .run_args("cargo fetch")
+                .last()
+                .unwrap()
+                .contains("socat")
+        );
+        let args = ruled.run_args("curl x.io");
+        assert!(!args.join(" ").contains(MOUNT_DIR));
+        assert_eq!(args.last().unwrap(), "curl x.io");
+
+        let bridged = base.container(
+            ContainerConfig::default()
+                .engine(ContainerEngine::Podman)
+                .network("bridge"),
+        );
+        let args = sandbox(bridged).run_args("ls").join(" ");
+        assert!(args.contains("--network bridge"));
+        assert!(args.contains("--userns=keep-id"));
+    }
+
+    #[test]
+    fn test_wrap_command_escapes() {
+        let config =
+            SandboxConfig::new(PathBuf::from("/work")).container(ContainerConfig::default());
+        let wrapped = sandbox(config).wrap_command("echo 'hi'").unwrap();
+        assert!(wrapped.starts_with("docker 'run' '--rm'"));
+        assert!(wrapped.ends_with("'sh' '-c' 'echo '\\''hi'\\'''"));
+    }
+}
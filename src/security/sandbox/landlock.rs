@@ -2,7 +2,11 @@
 
 use std::collections::HashMap;
 use std::path::Path;
+#[cfg(target_os = "linux")]
+use std::sync::Arc;
 
+#[cfg(target_os = "linux")]
+use super::seccomp::{self, SeccompFilter};
 use super::{SandboxConfig, SandboxError, SandboxResult, SandboxRuntime};
 
 #[cfg(target_os = "linux")]
@@ -14,12 +18,18 @@ use landlock::{
 pub struct LandlockSandbox {
     config: SandboxConfig,
     abi: Option<ABI>,
+    seccomp: Option<Arc<SeccompFilter>>,
 }
 
 impl LandlockSandbox {
     pub fn new(config: SandboxConfig) -> Self {
         let abi = best_effort_abi();
-        Self { config, abi }
+        let seccomp = seccomp::from_config(&config).map(Arc::new);
+        Self {
+            config,
+            abi,
+            seccomp,
+        }
     }
 }
 
@@ -98,6 +108,38 @@ impl SandboxRuntime for LandlockSandbox {
     fn environment_vars(&self) -> HashMap<String, String> {
         HashMap::new()
     }
+
+    fn seccomp_filter(&self) -> Option<Arc<SeccompFilter>> {
+        self.seccomp.clone()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl LandlockSandbox {
+    /// TCP ports connections may go to: none when isolated, only the proxy
+    /// ports with the embedded proxy, otherwise DNS, HTTP, HTTPS and any
+    /// configured proxy ports.
+    fn connect_ports(&self) -> Vec<u16> {
+        let network = &self.config.network;
+        if network.isolate {
+            return Vec::new();
+        }
+        let defaults: &[u16] = if network.embedded_proxy {
+            &[]
+        } else {
+            &[
+                53,  // DNS
+                80,  // HTTP
+                443, // HTTPS
+            ]
+        };
+        defaults
+            .iter()
+            .copied()
+            .chain(network.http_proxy_port)
+            .chain(network.socks_proxy_port)
+            .collect()
+    }
 }
 
 #[cfg(target_os = "linux")]
@@ -143,29 +185,13 @@ impl LandlockSandbox {
         &self,
         mut ruleset: landlock::RulesetCreated,
     ) -> SandboxResult<landlock::RulesetCreated> {
-        let allowed_ports: &[u16] = &[
-            53,  // DNS
-            80,  // HTTP
-            443, // HTTPS
-        ];
-
-        for &port in allowed_ports {
+        for port in self.connect_ports() {
             ruleset = ruleset
                 .add_rule(NetPort::new(port, AccessNet::ConnectTcp))
                 .map_err(|e| SandboxError::RuleApplication(e.to_string()))?;
         }
 
         let network = &self.config.network;
-        if let Some(http_port) = network.http_proxy_port {
-            ruleset = ruleset
-                .add_rule(NetPort::new(http_port, AccessNet::ConnectTcp))
-                .map_err(|e| SandboxError::RuleApplication(e.to_string()))?;
-        }
-        if let Some(socks_port) = network.socks_proxy_port {
-            ruleset = ruleset
-                .add_rule(NetPort::new(socks_port, AccessNet::ConnectTcp))
-                .map_err(|e| SandboxError::RuleApplication(e.to_string()))?;
-        }
 
         if network.allow_local_binding {
             ruleset = ruleset
@@ -234,6 +260,7 @@ impl SandboxRuntime for LandlockSandbox {
 #[cfg(all(test, target_os = "linux"))]
 mod tests {
     use super::*;
+    use crate::security::sandbox::NetworkConfig;
     use std::path::PathBuf;
 
     #[test]
@@ -247,4 +274,91 @@ mod tests {
         let sandbox = LandlockSandbox::new(config);
         let _available = sandbox.is_available();
     }
+
+    #[test]
+    fn test_connect_ports() {
+        let ports = |network: NetworkConfig| {
+            LandlockSandbox::new(SandboxConfig::new(PathBuf::from("/tmp")).network(network))
+                .connect_ports()
+        };
+        assert_eq!(ports(NetworkConfig::new()), [53, 80, 443]);
+        assert_eq!(
+            ports(NetworkConfig::proxy(Some(8080), Some(1080))),
+            [53, 80, 443, 8080, 1080]
+        );
+        let mut embedded = NetworkConfig::proxy(Some(41000), Some(41001)).embedded_proxy();
+        assert_eq!(ports(embedded.clone()), [41000, 41001]);
+        embedded.http_proxy_port = None;
+        embedded.socks_proxy_port = None;
+        assert!(ports(embedded).is_empty());
+        assert!(ports(NetworkConfig::new().isolated()).is_empty());
+    }
+
+    #[test]
+    fn test_direct_connection_blocked() {
+        use std::net::{Ipv4Addr, TcpListener, TcpStream};
+
+        let proxy = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let other = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let proxy_port = proxy.local_addr().unwrap().port();
+        let other_port = other.local_addr().unwrap().port();
+        let network = NetworkConfig::proxy(Some(proxy_port), None).embedded_proxy();
+        let sandbox =
+            LandlockSandbox::new(SandboxConfig::new(PathBuf::from("/tmp")).network(network));
+        if sandbox
+            .abi
+            .is_none_or(|abi| AccessNet::from_all(abi).is_empty())
+        {
+            return; // No Landlock network support
+        }
+
+        // Landlock restricts only the calling thread and its children.
+        std::thread::spawn(move || {
+            sandbox.apply().unwrap();
+            assert!(TcpStream::connect((Ipv4Addr::LOCALHOST, proxy_port)).is_ok());
+            assert!(TcpStream::connect((Ipv4Addr::LOCALHOST, other_port)).is_err());
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_scoped_ports_blocked() {
+        use crate::security::sandbox::{CommandNetworkRule, Sandbox};
+        use std::net::{Ipv4Addr, TcpStream};
+
+        let network = NetworkConfig::new()
+            .embedded_proxy()
+            .command_rule(CommandNetworkRule::new(["curl:*"]))
+            .offline_outside_rules();
+        let sandbox = Sandbox::new(SandboxConfig::new(PathBuf::from("/tmp")).network(network));
+        let proxy = sandbox.proxy.as_ref().unwrap();
+        let http_port = proxy.http_port();
+        let scoped: Vec<u16> = [None, Some(sandbox.rule_credentials[0].as_str())]
+            .into_iter()
+            .flat_map(|credentials| {
+                let (http, socks) = proxy.scoped_ports(credentials).unwrap();
+                [http, socks]
+            })
+            .collect();
+        let landlock = LandlockSandbox::new(sandbox.config().clone());
+        if landlock
+            .abi
+            .is_none_or(|abi| AccessNet::from_all(abi).is_empty())
+        {
+            return; // No Landlock network support
+        }
+
+        // Only the ports that honor rule credentials are reachable; the ports
+        // bridged into namespaces, which apply a rule without them, are not.
+        std::thread::spawn(move || {
+            landlock.apply().unwrap();
+            assert!(TcpStream::connect((Ipv4Addr::LOCALHOST, http_port)).is_ok());
+            for port in scoped {
+                assert!(TcpStream::connect((Ipv4Addr::LOCALHOST, port)).is_err());
+            }
+        })
+        .join()
+        .unwrap();
+    }
 }
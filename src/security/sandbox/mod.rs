@@ -1,28 +1,49 @@
 //! OS-level sandboxing for secure command execution.
 //!
 //! Provides filesystem and network isolation using:
-//! - Linux: Landlock LSM (5.13+)
+//! - Linux: Landlock LSM (5.13+) or bubblewrap namespaces, optionally with a
+//!   seccomp-bpf syscall filter
 //! - macOS: Seatbelt (sandbox-exec)
+//! - Any host with Docker or Podman: ephemeral containers
 //!
 //! Reference: <https://code.claude.com/docs/en/sandboxing>
 
+mod bridge;
 mod config;
+mod container;
 mod error;
 mod network;
+mod proxy;
+mod violation;
 
+#[cfg(target_os = "linux")]
+mod bubblewrap;
 #[cfg(target_os = "linux")]
 mod landlock;
 #[cfg(target_os = "macos")]
 mod macos;
+#[cfg(target_os = "linux")]
+mod seccomp;
 
-pub use config::{NetworkConfig, SandboxConfig};
+#[cfg(target_os = "linux")]
+pub use bubblewrap::{BubblewrapSandbox, is_bubblewrap_supported};
+pub use config::{
+    CommandNetworkRule, ContainerConfig, ContainerEngine, DEFAULT_DENIED_SYSCALLS, NetworkConfig,
+    SandboxBackend, SandboxConfig, SeccompProfile,
+};
+pub use container::ContainerSandbox;
 pub use error::{SandboxError, SandboxResult};
 pub use network::{DomainCheck, NetworkSandbox};
+pub use proxy::{EgressAttempt, EgressProtocol, EgressProxy};
+#[cfg(target_os = "linux")]
+pub use seccomp::SeccompFilter;
+pub use violation::{SandboxViolation, ViolationKind, classify_failure};
 
+use bridge::{ProxyBridge, ProxyBridges};
 use std::collections::HashMap;
-use std::path::Path;
-#[cfg(test)]
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+#[cfg(target_os = "linux")]
+use std::sync::Arc;
 use tracing::warn;
 
 pub trait SandboxRuntime: Send + Sync {
@@ -30,31 +51,132 @@ pub trait SandboxRuntime: Send + Sync {
     fn apply(&self) -> SandboxResult<()>;
     fn wrap_command(&self, command: &str) -> SandboxResult<String>;
     fn environment_vars(&self) -> HashMap<String, String>;
+
+    /// Filter the spawner installs in commands before `exec`, for runtimes
+    /// that do not apply it themselves.
+    #[cfg(target_os = "linux")]
+    fn seccomp_filter(&self) -> Option<Arc<SeccompFilter>> {
+        None
+    }
 }
 
 pub struct Sandbox {
     config: SandboxConfig,
     runtime: Option<Box<dyn SandboxRuntime>>,
+    proxy: Option<EgressProxy>,
+    /// Proxy `user:password` of each [`CommandNetworkRule`], by index.
+    rule_credentials: Vec<String>,
 }
 
 impl Sandbox {
-    pub fn new(config: SandboxConfig) -> Self {
-        let runtime = Self::create_runtime(&config);
-        Self { config, runtime }
+    pub fn new(mut config: SandboxConfig) -> Self {
+        let rule_credentials: Vec<String> = (0..config.network.command_rules.len())
+            .map(|i| format!("rule{i}:{}", uuid::Uuid::new_v4().simple()))
+            .collect();
+        let proxy = Self::start_proxy(&mut config, &rule_credentials);
+        let runtime = Self::create_runtime(&config, proxy.as_ref(), &rule_credentials);
+        Self {
+            config,
+            runtime,
+            proxy,
+            rule_credentials,
+        }
     }
 
     pub fn disabled() -> Self {
         Self {
             config: SandboxConfig::disabled(),
             runtime: None,
+            proxy: None,
+            rule_credentials: Vec::new(),
+        }
+    }
+
+    /// Starts the embedded proxy and points the network config at it, so
+    /// runtimes allow exactly its ports and commands get its URLs.
+    fn start_proxy(config: &mut SandboxConfig, rule_credentials: &[String]) -> Option<EgressProxy> {
+        if !config.enabled || config.network.isolate {
+            return None;
+        }
+        if !config.network.embedded_proxy {
+            if !config.network.command_rules.is_empty() {
+                warn!("Per-command network rules need the embedded proxy to restrict domains");
+            }
+            return None;
+        }
+        let network = if config.network.offline_outside_rules {
+            NetworkSandbox::offline()
+        } else {
+            config.to_network_sandbox()
+        };
+        let rules = rule_credentials
+            .iter()
+            .cloned()
+            .zip(
+                config
+                    .network
+                    .command_rules
+                    .iter()
+                    .map(CommandNetworkRule::to_network_sandbox),
+            )
+            .collect();
+        match EgressProxy::start_with_rules(network, rules) {
+            Ok(proxy) => {
+                config.network.http_proxy_port = Some(proxy.http_port());
+                config.network.socks_proxy_port = Some(proxy.socks_port());
+                Some(proxy)
+            }
+            Err(e) => {
+                warn!(error = %e, "Failed to start the embedded egress proxy");
+                None
+            }
         }
     }
 
-    fn create_runtime(config: &SandboxConfig) -> Option<Box<dyn SandboxRuntime>> {
+    fn create_runtime(
+        config: &SandboxConfig,
+        proxy: Option<&EgressProxy>,
+        rule_credentials: &[String],
+    ) -> Option<Box<dyn SandboxRuntime>> {
         if !config.enabled {
             return None;
         }
 
+        if let Some(container) = &config.container {
+            let sandbox = ContainerSandbox::new(config.clone()).proxy_bridges(Self::bridges(
+                config,
+                proxy,
+                rule_credentials,
+            ));
+            if sandbox.is_available() {
+                if config.seccomp.is_some() {
+                    warn!("Seccomp profile ignored: containers use the engine's seccomp profile");
+                }
+                return Some(Box::new(sandbox));
+            }
+            warn!(
+                engine = container.engine.binary(),
+                "Container sandbox requested but the engine is not installed. \
+                 Falling back to the OS sandbox."
+            );
+        }
+
+        #[cfg(target_os = "linux")]
+        if config.backend == SandboxBackend::Bubblewrap {
+            let sandbox = BubblewrapSandbox::new(config.clone()).proxy_bridges(Self::bridges(
+                config,
+                proxy,
+                rule_credentials,
+            ));
+            if sandbox.is_available() {
+                return Some(Box::new(sandbox));
+            }
+            warn!(
+                "Bubblewrap sandbox requested but bwrap is not installed or cannot create \
+                 namespaces. Falling back to Landlock."
+            );
+        }
+
         #[cfg(target_os = "linux")]
         {
             let sandbox = landlock::LandlockSandbox::new(config.clone());
@@ -88,6 +210,54 @@ impl Sandbox {
         None
     }
 
+    /// Bridges into the private network namespaces of bubblewrap and
+    /// container commands, so they reach the proxy and nothing else. With the
+    /// embedded proxy, each scope is bridged to the ports applying only its
+    /// domain lists.
+    fn bridges(
+        config: &SandboxConfig,
+        proxy: Option<&EgressProxy>,
+        rule_credentials: &[String],
+    ) -> ProxyBridges {
+        let network = &config.network;
+        if network.isolate || !network.has_proxy() {
+            return ProxyBridges::default();
+        }
+        let start = |(http, socks): (Option<u16>, Option<u16>)| {
+            let ports = network
+                .http_proxy_port
+                .zip(http)
+                .into_iter()
+                .chain(network.socks_proxy_port.zip(socks));
+            ProxyBridge::start(ports)
+                .inspect_err(|e| {
+                    warn!(
+                        error = %e,
+                        "Failed to bridge the proxy into the sandbox. \
+                         Commands will execute without network access."
+                    )
+                })
+                .ok()
+        };
+        let Some(proxy) = proxy else {
+            return ProxyBridges {
+                default: start((network.http_proxy_port, network.socks_proxy_port)),
+                rules: Vec::new(),
+            };
+        };
+        let scoped = |credentials: Option<&str>| {
+            let (http, socks) = proxy.scoped_ports(credentials)?;
+            start((Some(http), Some(socks)))
+        };
+        ProxyBridges {
+            default: scoped(None),
+            rules: rule_credentials
+                .iter()
+                .map(|credentials| scoped(Some(credentials)))
+                .collect(),
+        }
+    }
+
     pub fn is_enabled(&self) -> bool {
         self.config.enabled && self.runtime.is_some()
     }
@@ -128,6 +298,22 @@ impl Sandbox {
     }
 
     pub fn environment_vars(&self) -> HashMap<String, String> {
+        self.environment(None)
+    }
+
+    /// Environment for running `command`: like [`environment_vars`](Self::environment_vars),
+    /// with proxy URLs carrying the credentials of the
+    /// [`CommandNetworkRule`] covering `command`, if any.
+    pub fn environment_vars_for(&self, command: &str) -> HashMap<String, String> {
+        let credentials = self
+            .config
+            .network
+            .rule_for(command)
+            .and_then(|i| self.rule_credentials.get(i));
+        self.environment(credentials.map(String::as_str))
+    }
+
+    fn environment(&self, credentials: Option<&str>) -> HashMap<String, String> {
         let mut env = HashMap::new();
 
         if let Some(runtime) = &self.runtime {
@@ -135,14 +321,18 @@ impl Sandbox {
         }
 
         let network = &self.config.network;
+        let with_credentials = |url: String| match (credentials, url.split_once("://")) {
+            (Some(credentials), Some((scheme, rest))) => format!("{scheme}://{credentials}@{rest}"),
+            _ => url,
+        };
         if network.has_proxy() {
-            if let Some(url) = network.http_proxy_url() {
+            if let Some(url) = network.http_proxy_url().map(with_credentials) {
                 env.insert("HTTP_PROXY".into(), url.clone());
                 env.insert("HTTPS_PROXY".into(), url.clone());
                 env.insert("http_proxy".into(), url.clone());
                 env.insert("https_proxy".into(), url);
             }
-            if let Some(url) = network.socks_proxy_url() {
+            if let Some(url) = network.socks_proxy_url().map(with_credentials) {
                 env.insert("ALL_PROXY".into(), url.clone());
                 env.insert("all_proxy".into(), url);
             }
@@ -154,6 +344,21 @@ impl Sandbox {
         env
     }
 
+    /// Seccomp filter commands must install before `exec` (see
+    /// [`SandboxConfig::seccomp`]).
+    #[cfg(target_os = "linux")]
+    pub fn seccomp_filter(&self) -> Option<Arc<SeccompFilter>> {
+        self.runtime.as_ref()?.seccomp_filter()
+    }
+
+    /// Egress attempts seen by the embedded proxy, oldest first.
+    pub fn egress_log(&self) -> Vec<EgressAttempt> {
+        self.proxy
+            .as_ref()
+            .map(EgressProxy::attempts)
+            .unwrap_or_default()
+    }
+
     pub fn should_auto_allow_bash(&self) -> bool {
         self.is_enabled() && self.config.should_auto_allow_bash()
     }
@@ -172,7 +377,7 @@ impl Default for Sandbox {
 pub fn is_sandbox_supported() -> bool {
     #[cfg(target_os = "linux")]
     {
-        landlock::is_landlock_supported()
+        landlock::is_landlock_supported() || is_bubblewrap_supported()
     }
     #[cfg(target_os = "macos")]
     {
@@ -184,6 +389,27 @@ pub fn is_sandbox_supported() -> bool {
     }
 }
 
+fn find_in_path(binary: &str) -> Option<PathBuf> {
+    std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join(binary))
+            .find(|path| path.is_file())
+    })
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => crate::common::home_dir()
+            .map(|home| home.join(rest))
+            .unwrap_or_else(|| PathBuf::from(path)),
+        None => PathBuf::from(path),
+    }
+}
+
+fn shell_escape(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
 pub fn create_sandbox(working_dir: &Path, auto_allow_bash: bool) -> Sandbox {
     let config = SandboxConfig::new(working_dir.to_path_buf()).auto_allow_bash(auto_allow_bash);
     Sandbox::new(config)
@@ -231,6 +457,105 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_embedded_proxy_environment() {
+        let config = SandboxConfig::new(PathBuf::from("/tmp"))
+            .network(NetworkConfig::proxy(Some(8080), None).embedded_proxy());
+        let sandbox = Sandbox::new(config);
+
+        let port = sandbox.config().network.http_proxy_port.unwrap();
+        assert_ne!(port, 8080);
+        assert_eq!(
+            sandbox.environment_vars().get("HTTP_PROXY"),
+            Some(&format!("http://127.0.0.1:{port}"))
+        );
+        assert!(sandbox.config().network.socks_proxy_port.is_some());
+        assert!(sandbox.egress_log().is_empty());
+    }
+
+    #[test]
+    fn test_command_rule_environment() {
+        let network = NetworkConfig::new()
+            .embedded_proxy()
+            .command_rule(CommandNetworkRule::new(["npm:*"]).allow_domain("registry.npmjs.org"))
+            .offline_outside_rules();
+        let sandbox = Sandbox::new(SandboxConfig::new(PathBuf::from("/tmp")).network(network));
+        let port = sandbox.config().network.http_proxy_port.unwrap();
+
+        let npm = sandbox.environment_vars_for("npm install");
+        let proxy = npm.get("HTTPS_PROXY").unwrap();
+        assert!(proxy.starts_with("http://rule0:"));
+        assert!(proxy.ends_with(&format!("@127.0.0.1:{port}")));
+        assert!(npm.get("ALL_PROXY").unwrap().starts_with("socks5://rule0:"));
+
+        let curl = sandbox.environment_vars_for("curl example.com");
+        assert_eq!(
+            curl.get("HTTPS_PROXY"),
+            Some(&format!("http://127.0.0.1:{port}"))
+        );
+    }
+
+    #[test]
+    fn test_rule_bridges() {
+        use base64::Engine;
+        use base64::prelude::BASE64_STANDARD;
+        use std::io::{Read, Write};
+        use std::net::{Ipv4Addr, Shutdown, TcpListener};
+        use std::os::unix::net::UnixStream;
+
+        let network = NetworkConfig::new()
+            .embedded_proxy()
+            .command_rule(CommandNetworkRule::new(["curl:*"]).allow_domain("localhost"))
+            .offline_outside_rules();
+        let sandbox = Sandbox::new(SandboxConfig::new(PathBuf::from("/tmp")).network(network));
+        let bridges = Sandbox::bridges(
+            sandbox.config(),
+            sandbox.proxy.as_ref(),
+            &sandbox.rule_credentials,
+        );
+        let network = &sandbox.config().network;
+        let port = network.http_proxy_port.unwrap();
+
+        let upstream = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let target = upstream.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for mut stream in upstream.incoming().flatten() {
+                let mut buf = [0; 1024];
+                let _ = stream.read(&mut buf);
+                let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok");
+            }
+        });
+        let exchange = |bridge: &ProxyBridge, headers: &str| {
+            let socket = bridge.dir().join(format!("{port}.sock"));
+            let mut stream = UnixStream::connect(socket).unwrap();
+            let request = format!(
+                "GET http://localhost:{target}/ HTTP/1.1\r\nHost: localhost\r\n{headers}\r\n"
+            );
+            stream.write_all(request.as_bytes()).unwrap();
+            stream.shutdown(Shutdown::Write).unwrap();
+            let mut response = Vec::new();
+            stream.read_to_end(&mut response).unwrap();
+            response
+        };
+
+        assert!(bridges.for_command(network, "ls").is_none());
+        let rule = bridges.for_command(network, "curl localhost").unwrap();
+        assert!(exchange(rule, "").ends_with(b"ok"));
+
+        // The rule's credentials do not help through the default bridge.
+        let url = &sandbox.environment_vars_for("curl localhost")["HTTP_PROXY"];
+        let credentials = url
+            .strip_prefix("http://")
+            .unwrap()
+            .split('@')
+            .next()
+            .unwrap();
+        let auth = BASE64_STANDARD.encode(credentials);
+        let default = bridges.default.as_ref().unwrap();
+        let response = exchange(default, &format!("Proxy-Authorization: Basic {auth}\r\n"));
+        assert!(response.starts_with(b"HTTP/1.1 403"));
+    }
+
     #[test]
     fn test_auto_allow_bash() {
         let config = SandboxConfig::new(PathBuf::from("/tmp"));
@@ -15,6 +15,7 @@ pub struct NetworkSandbox {
     allowed_domains: HashSet<String>,
     blocked_domains: HashSet<String>,
     permissive: bool,
+    offline: bool,
 }
 
 impl NetworkSandbox {
@@ -23,6 +24,7 @@ impl NetworkSandbox {
             allowed_domains: default_allowed_domains(),
             blocked_domains: HashSet::new(),
             permissive: false,
+            offline: false,
         }
     }
 
@@ -34,6 +36,7 @@ impl NetworkSandbox {
             allowed_domains: allowed,
             blocked_domains: settings.blocked_domains.clone(),
             permissive: false,
+            offline: false,
         }
     }
 
@@ -42,6 +45,17 @@ impl NetworkSandbox {
             allowed_domains: HashSet::new(),
             blocked_domains: HashSet::new(),
             permissive: true,
+            offline: false,
+        }
+    }
+
+    /// Blocks every domain, including the defaults.
+    pub fn offline() -> Self {
+        Self {
+            allowed_domains: HashSet::new(),
+            blocked_domains: HashSet::new(),
+            permissive: false,
+            offline: true,
         }
     }
 
@@ -59,6 +73,9 @@ impl NetworkSandbox {
         if self.permissive {
             return DomainCheck::Allowed;
         }
+        if self.offline {
+            return DomainCheck::Blocked;
+        }
 
         let normalized = normalize_domain(domain);
 
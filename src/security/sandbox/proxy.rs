@@ -0,0 +1,651 @@
+//! Embedded egress proxy enforcing the network domain allowlist.
+//!
+//! Sandboxed commands only reach the network through `HTTP_PROXY` and
+//! `ALL_PROXY`; this proxy decides per connection against a
+//! [`NetworkSandbox`] and records every attempt. Clients presenting the
+//! proxy credentials of a [`CommandNetworkRule`](super::CommandNetworkRule)
+//! are checked against that rule's sandbox instead. Each sandbox also gets
+//! ports of its own that ignore credentials, for commands in a private
+//! network namespace that can only reach one of them.
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, Ipv6Addr, Shutdown, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use super::{DomainCheck, NetworkSandbox};
+
+const MAX_LOG_ENTRIES: usize = 1000;
+const MAX_HEAD_BYTES: usize = 64 * 1024;
+/// Bounds how long a client may take to send its request or handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How a sandboxed command tried to reach the network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EgressProtocol {
+    /// Plain HTTP request forwarded by the proxy.
+    Http,
+    /// HTTP `CONNECT` tunnel, typically HTTPS.
+    Connect,
+    Socks5,
+}
+
+/// One connection attempt seen by the proxy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EgressAttempt {
+    pub timestamp: DateTime<Utc>,
+    pub host: String,
+    pub port: u16,
+    pub protocol: EgressProtocol,
+    pub allowed: bool,
+}
+
+/// Which sandbox a listener checks connections against.
+#[derive(Debug, Clone)]
+enum Scope {
+    /// The one for the credentials the client presents.
+    Presented,
+    /// Always the one for these credentials, or the default one for `None`,
+    /// whatever the client presents.
+    Fixed(Option<String>),
+}
+
+impl Scope {
+    fn credentials<'a>(&'a self, presented: Option<&'a str>) -> Option<&'a str> {
+        match self {
+            Self::Presented => presented,
+            Self::Fixed(credentials) => credentials.as_deref(),
+        }
+    }
+}
+
+struct Filter {
+    network: NetworkSandbox,
+    /// Sandboxes for clients authenticating as `user:password`.
+    rules: HashMap<String, NetworkSandbox>,
+    log: Mutex<VecDeque<EgressAttempt>>,
+}
+
+impl Filter {
+    fn check(
+        &self,
+        host: &str,
+        port: u16,
+        protocol: EgressProtocol,
+        credentials: Option<&str>,
+    ) -> bool {
+        let network = credentials
+            .and_then(|c| self.rules.get(c))
+            .unwrap_or(&self.network);
+        let allowed = network.check(host) == DomainCheck::Allowed;
+        if allowed {
+            info!(host, port, ?protocol, "Sandbox egress allowed");
+        } else {
+            warn!(host, port, ?protocol, "Sandbox egress blocked");
+        }
+
+        let mut log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+        if log.len() == MAX_LOG_ENTRIES {
+            log.pop_front();
+        }
+        log.push_back(EgressAttempt {
+            timestamp: Utc::now(),
+            host: host.to_string(),
+            port,
+            protocol,
+            allowed,
+        });
+        allowed
+    }
+}
+
+/// HTTP and SOCKS5 proxy on localhost, started by [`Sandbox`](super::Sandbox)
+/// when [`NetworkConfig::embedded_proxy`](super::NetworkConfig::embedded_proxy)
+/// is set.
+///
+/// Runs on its own threads, so it works without a Tokio runtime. Both
+/// listeners stop when the proxy is dropped; open tunnels run to completion.
+pub struct EgressProxy {
+    http_port: u16,
+    socks_port: u16,
+    /// HTTP and SOCKS ports checking against one sandbox, keyed by the
+    /// credentials it belongs to.
+    scoped_ports: HashMap<Option<String>, (u16, u16)>,
+    filter: Arc<Filter>,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl EgressProxy {
+    /// Binds both listeners on `127.0.0.1` with ephemeral ports.
+    pub fn start(network: NetworkSandbox) -> io::Result<Self> {
+        Self::start_with_rules(network, HashMap::new())
+    }
+
+    /// Like [`start`](Self::start), but clients authenticating with one of
+    /// the `user:password` keys of `rules` (HTTP `Proxy-Authorization: Basic`
+    /// or SOCKS5 username/password) are checked against its sandbox.
+    /// Unknown credentials fall back to `network`.
+    pub fn start_with_rules(
+        network: NetworkSandbox,
+        rules: HashMap<String, NetworkSandbox>,
+    ) -> io::Result<Self> {
+        let scopes: Vec<Option<String>> = std::iter::once(None)
+            .chain(rules.keys().cloned().map(Some))
+            .collect();
+        let mut proxy = Self {
+            http_port: 0,
+            socks_port: 0,
+            scoped_ports: HashMap::new(),
+            filter: Arc::new(Filter {
+                network,
+                rules,
+                log: Mutex::new(VecDeque::new()),
+            }),
+            shutdown: Arc::new(AtomicBool::new(false)),
+        };
+        (proxy.http_port, proxy.socks_port) = proxy.listen(Scope::Presented)?;
+        for credentials in scopes {
+            let ports = proxy.listen(Scope::Fixed(credentials.clone()))?;
+            proxy.scoped_ports.insert(credentials, ports);
+        }
+        Ok(proxy)
+    }
+
+    /// Binds and serves an HTTP and a SOCKS listener for `scope`.
+    fn listen(&self, scope: Scope) -> io::Result<(u16, u16)> {
+        let http = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let socks = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let ports = (http.local_addr()?.port(), socks.local_addr()?.port());
+        self.serve(http, scope.clone(), handle_http)?;
+        self.serve(socks, scope, handle_socks)?;
+        Ok(ports)
+    }
+
+    fn serve(
+        &self,
+        listener: TcpListener,
+        scope: Scope,
+        handler: fn(TcpStream, &Filter, &Scope),
+    ) -> io::Result<()> {
+        let filter = Arc::clone(&self.filter);
+        let shutdown = Arc::clone(&self.shutdown);
+        thread::Builder::new()
+            .name("sandbox-egress-proxy".into())
+            .spawn(move || {
+                let scope = Arc::new(scope);
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::Acquire) {
+                        break;
+                    }
+                    let Ok(stream) = stream else { continue };
+                    let filter = Arc::clone(&filter);
+                    let scope = Arc::clone(&scope);
+                    thread::spawn(move || handler(stream, &filter, &scope));
+                }
+            })?;
+        Ok(())
+    }
+
+    pub fn http_port(&self) -> u16 {
+        self.http_port
+    }
+
+    pub fn socks_port(&self) -> u16 {
+        self.socks_port
+    }
+
+    /// HTTP and SOCKS ports that check every connection against the sandbox
+    /// of `credentials`, or the default one for `None`, ignoring the
+    /// credentials clients present. `None` for unknown credentials.
+    pub fn scoped_ports(&self, credentials: Option<&str>) -> Option<(u16, u16)> {
+        self.scoped_ports
+            .get(&credentials.map(str::to_string))
+            .copied()
+    }
+
+    /// Attempts so far, oldest first; only the most recent 1000 are kept.
+    pub fn attempts(&self) -> Vec<EgressAttempt> {
+        let log = self.filter.log.lock().unwrap_or_else(|e| e.into_inner());
+        log.iter().cloned().collect()
+    }
+}
+
+impl Drop for EgressProxy {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Release);
+        // Wake the accept loops so they observe the flag.
+        let scoped = self
+            .scoped_ports
+            .values()
+            .flat_map(|&(http, socks)| [http, socks]);
+        for port in [self.http_port, self.socks_port].into_iter().chain(scoped) {
+            let _ = TcpStream::connect((Ipv4Addr::LOCALHOST, port));
+        }
+    }
+}
+
+fn handle_http(stream: TcpStream, filter: &Filter, scope: &Scope) {
+    let _ = stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT));
+    let Ok(reader_stream) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(reader_stream);
+    let mut client = stream;
+
+    let Some((request_line, headers)) = read_head(&mut reader) else {
+        return;
+    };
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target), Some(version)) = (parts.next(), parts.next(), parts.next())
+    else {
+        let _ = respond(&mut client, "400 Bad Request");
+        return;
+    };
+    let presented = basic_credentials(&headers);
+    let credentials = scope.credentials(presented.as_deref());
+
+    if method.eq_ignore_ascii_case("CONNECT") {
+        let Some((host, port)) = split_host_port(target, 443) else {
+            let _ = respond(&mut client, "400 Bad Request");
+            return;
+        };
+        if !filter.check(&host, port, EgressProtocol::Connect, credentials) {
+            let _ = respond(&mut client, "403 Forbidden");
+            return;
+        }
+        let Ok(upstream) = TcpStream::connect((host.as_str(), port)) else {
+            let _ = respond(&mut client, "502 Bad Gateway");
+            return;
+        };
+        if client
+            .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+            .is_err()
+        {
+            return;
+        }
+        let pending = reader.buffer().to_vec();
+        tunnel(client, upstream, &pending);
+        return;
+    }
+
+    let Some(rest) = target.strip_prefix("http://") else {
+        let _ = respond(&mut client, "400 Bad Request");
+        return;
+    };
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let Some((host, port)) = split_host_port(authority, 80) else {
+        let _ = respond(&mut client, "400 Bad Request");
+        return;
+    };
+    if !filter.check(&host, port, EgressProtocol::Http, credentials) {
+        let _ = respond(&mut client, "403 Forbidden");
+        return;
+    }
+    let Ok(mut upstream) = TcpStream::connect((host.as_str(), port)) else {
+        let _ = respond(&mut client, "502 Bad Gateway");
+        return;
+    };
+
+    // One request per connection keeps every request behind a check.
+    let mut head = format!("{method} {path} {version}\r\n");
+    for header in &headers {
+        let name = header.split(':').next().unwrap_or_default().trim();
+        if name.to_ascii_lowercase().starts_with("proxy-")
+            || name.eq_ignore_ascii_case("connection")
+        {
+            continue;
+        }
+        head.push_str(header);
+        head.push_str("\r\n");
+    }
+    head.push_str("Connection: close\r\n\r\n");
+    if upstream.write_all(head.as_bytes()).is_err() {
+        return;
+    }
+    let pending = reader.buffer().to_vec();
+    tunnel(client, upstream, &pending);
+}
+
+/// Reads the request line and header lines, without their line endings.
+fn read_head(reader: &mut BufReader<TcpStream>) -> Option<(String, Vec<String>)> {
+    let mut total = 0;
+    let mut next_line = |reader: &mut BufReader<TcpStream>| -> Option<String> {
+        let mut line = String::new();
+        let n = reader.read_line(&mut line).ok()?;
+        total += n;
+        (n > 0 && total <= MAX_HEAD_BYTES).then(|| line.trim_end_matches(['\r', '\n']).to_string())
+    };
+
+    let request_line = next_line(reader)?;
+    let mut headers = Vec::new();
+    loop {
+        let line = next_line(reader)?;
+        if line.is_empty() {
+            break;
+        }
+        headers.push(line);
+    }
+    Some((request_line, headers))
+}
+
+/// `user:password` from a `Proxy-Authorization: Basic` header.
+fn basic_credentials(headers: &[String]) -> Option<String> {
+    let value = headers.iter().find_map(|header| {
+        let (name, value) = header.split_once(':')?;
+        name.trim()
+            .eq_ignore_ascii_case("proxy-authorization")
+            .then_some(value.trim())
+    })?;
+    let (scheme, encoded) = value.split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("basic") {
+        return None;
+    }
+    let decoded = BASE64_STANDARD.decode(encoded.trim()).ok()?;
+    String::from_utf8(decoded).ok()
+}
+
+fn respond(client: &mut TcpStream, status: &str) -> io::Result<()> {
+    write!(
+        client,
+        "HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+    )
+}
+
+/// Splits `host:port` or `[v6]:port`, falling back to `default_port`.
+fn split_host_port(authority: &str, default_port: u16) -> Option<(String, u16)> {
+    if let Some(rest) = authority.strip_prefix('[') {
+        let (host, rest) = rest.split_once(']')?;
+        let port = match rest.strip_prefix(':') {
+            Some(port) => port.parse().ok()?,
+            None => default_port,
+        };
+        return Some((host.to_string(), port));
+    }
+    match authority.rsplit_once(':') {
+        Some((host, port)) => Some((host.to_string(), port.parse().ok()?)),
+        None if authority.is_empty() => None,
+        None => Some((authority.to_string(), default_port)),
+    }
+}
+
+const SOCKS_VERSION: u8 = 5;
+const SOCKS_NO_AUTH: u8 = 0x00;
+const SOCKS_USER_PASSWORD: u8 = 0x02;
+const SOCKS_NO_METHOD: u8 = 0xff;
+/// Version of the username/password subnegotiation (RFC 1929).
+const SOCKS_AUTH_VERSION: u8 = 1;
+const SOCKS_SUCCEEDED: u8 = 0x00;
+const SOCKS_NOT_ALLOWED: u8 = 0x02;
+const SOCKS_REFUSED: u8 = 0x05;
+const SOCKS_COMMAND_UNSUPPORTED: u8 = 0x07;
+
+fn handle_socks(mut client: TcpStream, filter: &Filter, scope: &Scope) {
+    let _ = client.set_read_timeout(Some(HANDSHAKE_TIMEOUT));
+    let _ = socks_handshake(&mut client, filter, scope);
+}
+
+fn socks_handshake(client: &mut TcpStream, filter: &Filter, scope: &Scope) -> io::Result<()> {
+    let [version, method_count] = read_array(client)?;
+    if version != SOCKS_VERSION {
+        return Ok(());
+    }
+    let mut methods = vec![0; method_count as usize];
+    client.read_exact(&mut methods)?;
+    let credentials = if !filter.rules.is_empty() && methods.contains(&SOCKS_USER_PASSWORD) {
+        client.write_all(&[SOCKS_VERSION, SOCKS_USER_PASSWORD])?;
+        let credentials = socks_credentials(client)?;
+        // Unknown credentials are not rejected; they get the default sandbox.
+        client.write_all(&[SOCKS_AUTH_VERSION, 0])?;
+        Some(credentials)
+    } else if methods.contains(&SOCKS_NO_AUTH) {
+        client.write_all(&[SOCKS_VERSION, SOCKS_NO_AUTH])?;
+        None
+    } else {
+        return client.write_all(&[SOCKS_VERSION, SOCKS_NO_METHOD]);
+    };
+
+    let [_, command, _, address_type] = read_array(client)?;
+    let host = match address_type {
+        1 => Ipv4Addr::from(read_array::<4>(client)?).to_string(),
+        3 => {
+            let [len] = read_array(client)?;
+            let mut name = vec![0; len as usize];
+            client.read_exact(&mut name)?;
+            String::from_utf8_lossy(&name).into_owned()
+        }
+        4 => Ipv6Addr::from(read_array::<16>(client)?).to_string(),
+        _ => return socks_reply(client, SOCKS_COMMAND_UNSUPPORTED),
+    };
+    let port = u16::from_be_bytes(read_array(client)?);
+
+    if command != 1 {
+        return socks_reply(client, SOCKS_COMMAND_UNSUPPORTED);
+    }
+    let credentials = scope.credentials(credentials.as_deref());
+    if !filter.check(&host, port, EgressProtocol::Socks5, credentials) {
+        return socks_reply(client, SOCKS_NOT_ALLOWED);
+    }
+    let Ok(upstream) = TcpStream::connect((host.as_str(), port)) else {
+        return socks_reply(client, SOCKS_REFUSED);
+    };
+    socks_reply(client, SOCKS_SUCCEEDED)?;
+    tunnel(client.try_clone()?, upstream, &[]);
+    Ok(())
+}
+
+/// `user:password` from a username/password subnegotiation.
+fn socks_credentials(client: &mut TcpStream) -> io::Result<String> {
+    let [_, user_len] = read_array(client)?;
+    let mut user = vec![0; user_len as usize];
+    client.read_exact(&mut user)?;
+    let [password_len] = read_array(client)?;
+    let mut password = vec![0; password_len as usize];
+    client.read_exact(&mut password)?;
+    Ok(format!(
+        "{}:{}",
+        String::from_utf8_lossy(&user),
+        String::from_utf8_lossy(&password)
+    ))
+}
+
+fn read_array<const N: usize>(stream: &mut TcpStream) -> io::Result<[u8; N]> {
+    let mut buf = [0; N];
+    stream.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+/// Replies with an unspecified IPv4 bind address, which clients ignore.
+fn socks_reply(client: &mut TcpStream, status: u8) -> io::Result<()> {
+    client.write_all(&[SOCKS_VERSION, status, 0, 1, 0, 0, 0, 0, 0, 0])
+}
+
+/// Copies both directions until each side closes, after sending `pending`
+/// bytes the client already sent upstream.
+fn tunnel(client: TcpStream, mut upstream: TcpStream, pending: &[u8]) {
+    let _ = client.set_read_timeout(None);
+    if upstream.write_all(pending).is_err() {
+        return;
+    }
+    let (Ok(mut client_read), Ok(mut upstream_write)) = (client.try_clone(), upstream.try_clone())
+    else {
+        return;
+    };
+    let outbound = thread::spawn(move || {
+        let _ = io::copy(&mut client_read, &mut upstream_write);
+        let _ = upstream_write.shutdown(Shutdown::Write);
+    });
+
+    let mut client_write = client;
+    let _ = io::copy(&mut upstream, &mut client_write);
+    let _ = client_write.shutdown(Shutdown::Write);
+    let _ = outbound.join();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proxy() -> EgressProxy {
+        EgressProxy::start(NetworkSandbox::new().blocked_domains(vec!["evil.com".into()])).unwrap()
+    }
+
+    /// Upstream server answering every connection with `reply`.
+    fn upstream(reply: &'static [u8]) -> u16 {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut buf = [0; 1024];
+                let _ = stream.read(&mut buf);
+                let _ = stream.write_all(reply);
+            }
+        });
+        port
+    }
+
+    fn exchange(port: u16, request: &[u8]) -> Vec<u8> {
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        stream.write_all(request).unwrap();
+        stream.shutdown(Shutdown::Write).unwrap();
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_http_filtering() {
+        let proxy = proxy();
+        let target = upstream(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok");
+
+        let request = format!("GET http://localhost:{target}/ HTTP/1.1\r\nHost: localhost\r\n\r\n");
+        let response = exchange(proxy.http_port(), request.as_bytes());
+        assert!(response.ends_with(b"\r\n\r\nok"));
+
+        let response = exchange(
+            proxy.http_port(),
+            b"CONNECT evil.com:443 HTTP/1.1\r\nHost: evil.com:443\r\n\r\n",
+        );
+        assert!(response.starts_with(b"HTTP/1.1 403"));
+
+        let attempts = proxy.attempts();
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(attempts[0].protocol, EgressProtocol::Http);
+        assert_eq!(attempts[0].port, target);
+        assert!(attempts[0].allowed);
+        assert_eq!(attempts[1].host, "evil.com");
+        assert_eq!(attempts[1].protocol, EgressProtocol::Connect);
+        assert!(!attempts[1].allowed);
+    }
+
+    #[test]
+    fn test_socks_filtering() {
+        let proxy = proxy();
+        let target = upstream(b"pong");
+
+        let mut request = vec![5, 1, 0, 5, 1, 0, 3, 9];
+        request.extend(b"localhost");
+        request.extend(target.to_be_bytes());
+        request.extend(b"ping");
+        let response = exchange(proxy.socks_port(), &request);
+        assert_eq!(response[..4], [5, 0, 5, SOCKS_SUCCEEDED]);
+        assert_eq!(&response[12..], b"pong");
+
+        let mut request = vec![5, 1, 0, 5, 1, 0, 3, 8];
+        request.extend(b"evil.com");
+        request.extend(443u16.to_be_bytes());
+        let response = exchange(proxy.socks_port(), &request);
+        assert_eq!(response[..4], [5, 0, 5, SOCKS_NOT_ALLOWED]);
+
+        let attempts = proxy.attempts();
+        assert_eq!(attempts.len(), 2);
+        assert!(attempts[0].allowed);
+        assert!(!attempts[1].allowed);
+    }
+
+    #[test]
+    fn test_rule_credentials() {
+        let rules = HashMap::from([("rule0:secret".to_string(), NetworkSandbox::new())]);
+        let proxy = EgressProxy::start_with_rules(NetworkSandbox::offline(), rules).unwrap();
+        let target = upstream(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok");
+
+        let request = format!("GET http://localhost:{target}/ HTTP/1.1\r\nHost: localhost\r\n");
+        let response = exchange(proxy.http_port(), format!("{request}\r\n").as_bytes());
+        assert!(response.starts_with(b"HTTP/1.1 403"));
+
+        let auth = BASE64_STANDARD.encode("rule0:secret");
+        let response = exchange(
+            proxy.http_port(),
+            format!("{request}Proxy-Authorization: Basic {auth}\r\n\r\n").as_bytes(),
+        );
+        assert!(response.ends_with(b"\r\n\r\nok"));
+
+        let mut request = vec![5, 1, SOCKS_USER_PASSWORD, SOCKS_AUTH_VERSION, 5];
+        request.extend(b"rule0");
+        request.push(6);
+        request.extend(b"secret");
+        request.extend([5, 1, 0, 3, 9]);
+        request.extend(b"localhost");
+        request.extend(target.to_be_bytes());
+        let response = exchange(proxy.socks_port(), &request);
+        assert_eq!(
+            response[..6],
+            [
+                5,
+                SOCKS_USER_PASSWORD,
+                SOCKS_AUTH_VERSION,
+                0,
+                5,
+                SOCKS_SUCCEEDED
+            ]
+        );
+
+        let allowed: Vec<bool> = proxy.attempts().iter().map(|a| a.allowed).collect();
+        assert_eq!(allowed, [false, true, true]);
+    }
+
+    #[test]
+    fn test_scoped_ports_ignore_credentials() {
+        let rules = HashMap::from([("rule0:secret".to_string(), NetworkSandbox::new())]);
+        let proxy = EgressProxy::start_with_rules(NetworkSandbox::offline(), rules).unwrap();
+        let target = upstream(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok");
+        let (default_http, _) = proxy.scoped_ports(None).unwrap();
+        let (rule_http, _) = proxy.scoped_ports(Some("rule0:secret")).unwrap();
+        assert!(proxy.scoped_ports(Some("rule1:other")).is_none());
+
+        let request = format!("GET http://localhost:{target}/ HTTP/1.1\r\nHost: localhost\r\n");
+        let auth = BASE64_STANDARD.encode("rule0:secret");
+        let with_auth = format!("{request}Proxy-Authorization: Basic {auth}\r\n\r\n");
+        let response = exchange(default_http, with_auth.as_bytes());
+        assert!(response.starts_with(b"HTTP/1.1 403"));
+
+        let response = exchange(rule_http, format!("{request}\r\n").as_bytes());
+        assert!(response.ends_with(b"\r\n\r\nok"));
+    }
+
+    #[test]
+    fn test_split_host_port() {
+        assert_eq!(
+            split_host_port("example.com:8443", 443),
+            Some(("example.com".into(), 8443))
+        );
+        assert_eq!(
+            split_host_port("example.com", 80),
+            Some(("example.com".into(), 80))
+        );
+        assert_eq!(split_host_port("[::1]:443", 80), Some(("::1".into(), 443)));
+        assert_eq!(split_host_port("host:port", 80), None);
+    }
+}
@@ -0,0 +1,286 @@
+//! seccomp-bpf syscall filter for sandboxed commands on Linux.
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use seccompiler::{
+    BpfProgram, SeccompAction, SeccompCmpArgLen, SeccompCmpOp, SeccompCondition, SeccompRule,
+    TargetArch,
+};
+use tracing::warn;
+
+use super::{SandboxConfig, SandboxError, SandboxResult, SeccompProfile};
+
+/// Syscalls a [`SeccompProfile`] can name, with their numbers on this architecture.
+const SYSCALLS: &[(&str, libc::c_long)] = &[
+    ("ptrace", libc::SYS_ptrace),
+    ("process_vm_readv", libc::SYS_process_vm_readv),
+    ("process_vm_writev", libc::SYS_process_vm_writev),
+    ("mount", libc::SYS_mount),
+    ("umount2", libc::SYS_umount2),
+    ("pivot_root", libc::SYS_pivot_root),
+    ("chroot", libc::SYS_chroot),
+    ("move_mount", libc::SYS_move_mount),
+    ("open_tree", libc::SYS_open_tree),
+    ("fsopen", libc::SYS_fsopen),
+    ("fsmount", libc::SYS_fsmount),
+    ("fsconfig", libc::SYS_fsconfig),
+    ("fspick", libc::SYS_fspick),
+    ("unshare", libc::SYS_unshare),
+    ("setns", libc::SYS_setns),
+    ("init_module", libc::SYS_init_module),
+    ("finit_module", libc::SYS_finit_module),
+    ("delete_module", libc::SYS_delete_module),
+    ("kexec_load", libc::SYS_kexec_load),
+    #[cfg(target_arch = "x86_64")]
+    ("kexec_file_load", libc::SYS_kexec_file_load),
+    ("reboot", libc::SYS_reboot),
+    ("swapon", libc::SYS_swapon),
+    ("swapoff", libc::SYS_swapoff),
+    ("bpf", libc::SYS_bpf),
+    ("perf_event_open", libc::SYS_perf_event_open),
+    ("userfaultfd", libc::SYS_userfaultfd),
+    ("add_key", libc::SYS_add_key),
+    ("request_key", libc::SYS_request_key),
+    ("keyctl", libc::SYS_keyctl),
+    ("acct", libc::SYS_acct),
+    ("quotactl", libc::SYS_quotactl),
+    ("settimeofday", libc::SYS_settimeofday),
+    ("clock_settime", libc::SYS_clock_settime),
+    ("clock_adjtime", libc::SYS_clock_adjtime),
+    ("adjtimex", libc::SYS_adjtimex),
+    ("sethostname", libc::SYS_sethostname),
+    ("setdomainname", libc::SYS_setdomainname),
+    ("personality", libc::SYS_personality),
+    ("syslog", libc::SYS_syslog),
+    ("name_to_handle_at", libc::SYS_name_to_handle_at),
+    ("open_by_handle_at", libc::SYS_open_by_handle_at),
+    ("lookup_dcookie", libc::SYS_lookup_dcookie),
+    ("io_uring_setup", libc::SYS_io_uring_setup),
+    ("io_uring_enter", libc::SYS_io_uring_enter),
+    ("io_uring_register", libc::SYS_io_uring_register),
+    #[cfg(target_arch = "x86_64")]
+    ("iopl", libc::SYS_iopl),
+    #[cfg(target_arch = "x86_64")]
+    ("ioperm", libc::SYS_ioperm),
+];
+
+/// A compiled [`SeccompProfile`].
+pub struct SeccompFilter {
+    program: BpfProgram,
+}
+
+impl SeccompFilter {
+    /// Compile `profile` for the running architecture.
+    ///
+    /// Unknown syscall names are skipped with a warning, so one profile
+    /// works on x86_64 and aarch64.
+    pub fn compile(profile: &SeccompProfile) -> SandboxResult<Self> {
+        let arch = TargetArch::try_from(std::env::consts::ARCH)
+            .map_err(|e| SandboxError::NotAvailable(format!("seccomp: {e}")))?;
+
+        let mut rules: BTreeMap<i64, Vec<SeccompRule>> = BTreeMap::new();
+        for name in &profile.denied_syscalls {
+            match SYSCALLS.iter().find(|(known, _)| known == name) {
+                Some(&(_, nr)) => {
+                    rules.insert(nr, Vec::new());
+                }
+                None => warn!(syscall = %name, "Unknown syscall in seccomp profile, skipping"),
+            }
+        }
+        if profile.deny_raw_sockets {
+            rules.insert(libc::SYS_socket, raw_socket_rules().map_err(creation)?);
+        }
+
+        let filter = seccompiler::SeccompFilter::new(
+            rules,
+            SeccompAction::Allow,
+            SeccompAction::Errno(libc::EPERM as u32),
+            arch,
+        )
+        .map_err(creation)?;
+        let program = BpfProgram::try_from(filter).map_err(creation)?;
+        Ok(Self { program })
+    }
+
+    /// Install the filter in the calling thread, which then cannot remove it;
+    /// its children inherit it.
+    ///
+    /// Only makes the `prctl` and `seccomp` syscalls, so it is safe to call
+    /// between `fork` and `exec` (`CommandExt::pre_exec`).
+    pub fn apply(&self) -> io::Result<()> {
+        match seccompiler::apply_filter(&self.program) {
+            Ok(()) => Ok(()),
+            Err(seccompiler::Error::Prctl(e) | seccompiler::Error::Seccomp(e)) => Err(e),
+            Err(_) => Err(io::ErrorKind::InvalidInput.into()),
+        }
+    }
+
+    /// Number of BPF instructions.
+    pub fn len(&self) -> usize {
+        self.program.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.program.is_empty()
+    }
+
+    /// The program as raw `struct sock_filter`s, the format `bwrap --seccomp` reads.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.program
+            .iter()
+            .flat_map(|insn| {
+                let mut bytes = [0u8; 8];
+                bytes[..2].copy_from_slice(&insn.code.to_ne_bytes());
+                bytes[2] = insn.jt;
+                bytes[3] = insn.jf;
+                bytes[4..].copy_from_slice(&insn.k.to_ne_bytes());
+                bytes
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for SeccompFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SeccompFilter")
+            .field("instructions", &self.program.len())
+            .finish()
+    }
+}
+
+/// Compile the profile of `config`, if any. A profile that cannot be
+/// compiled is logged and skipped, like an unavailable sandbox.
+pub(super) fn from_config(config: &SandboxConfig) -> Option<SeccompFilter> {
+    let profile = config.seccomp.as_ref()?;
+    SeccompFilter::compile(profile)
+        .inspect_err(|e| {
+            warn!(
+                error = %e,
+                "Seccomp profile requested but cannot be compiled. \
+                 Commands will execute without syscall filtering."
+            )
+        })
+        .ok()
+}
+
+/// A compiled filter written to a temporary file, removed on drop.
+pub(super) struct SeccompFile {
+    path: PathBuf,
+}
+
+impl SeccompFile {
+    pub(super) fn create(filter: &SeccompFilter) -> io::Result<Self> {
+        let path = std::env::temp_dir().join(format!(
+            "claude-agent-seccomp-{}.bpf",
+            crate::common::clock::new_uuid()
+        ));
+        std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?
+            .write_all(&filter.to_bytes())?;
+        Ok(Self { path })
+    }
+
+    pub(super) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for SeccompFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn creation(e: impl std::fmt::Display) -> SandboxError {
+    SandboxError::Creation(format!("seccomp: {e}"))
+}
+
+/// `socket(AF_PACKET, ..)` and `socket(AF_INET | AF_INET6, SOCK_RAW, ..)`.
+fn raw_socket_rules() -> seccompiler::Result<Vec<SeccompRule>> {
+    let domain = |family: libc::c_int| {
+        SeccompCondition::new(0, SeccompCmpArgLen::Dword, SeccompCmpOp::Eq, family as u64)
+    };
+    // The type argument also carries SOCK_NONBLOCK and SOCK_CLOEXEC.
+    let raw = || {
+        SeccompCondition::new(
+            1,
+            SeccompCmpArgLen::Dword,
+            SeccompCmpOp::MaskedEq(0xf),
+            libc::SOCK_RAW as u64,
+        )
+    };
+    Ok(vec![
+        SeccompRule::new(vec![domain(libc::AF_PACKET)?])?,
+        SeccompRule::new(vec![domain(libc::AF_INET)?, raw()?])?,
+        SeccompRule::new(vec![domain(libc::AF_INET6)?, raw()?])?,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::CommandExt;
+    use std::process::Command;
+
+    #[test]
+    fn test_default_profile_names_known() {
+        let unknown: Vec<_> = super::super::config::DEFAULT_DENIED_SYSCALLS
+            .iter()
+            .filter(|name| !SYSCALLS.iter().any(|(known, _)| known == *name))
+            .filter(|name| !matches!(**name, "iopl" | "ioperm" | "kexec_file_load"))
+            .collect();
+        assert!(unknown.is_empty(), "{unknown:?}");
+    }
+
+    #[test]
+    fn test_compile_and_serialize() {
+        let Ok(filter) = SeccompFilter::compile(&SeccompProfile::new()) else {
+            return; // Unsupported architecture
+        };
+        assert!(!filter.is_empty());
+        assert_eq!(filter.to_bytes().len(), filter.len() * 8);
+
+        let smaller = SeccompFilter::compile(&SeccompProfile::empty().deny("ptrace")).unwrap();
+        assert!(smaller.len() < filter.len());
+    }
+
+    #[test]
+    fn test_denied_syscall_in_child() {
+        let Ok(filter) = SeccompFilter::compile(&SeccompProfile::empty().deny("unshare")) else {
+            return;
+        };
+        let run = |filter: Option<SeccompFilter>| {
+            let mut cmd = Command::new("python3");
+            cmd.args([
+                "-c",
+                "import ctypes,os\n\
+                 libc = ctypes.CDLL(None, use_errno=True)\n\
+                 rc = libc.unshare(0)\n\
+                 print(rc, ctypes.get_errno())",
+            ]);
+            if let Some(filter) = filter {
+                // SAFETY: `apply` only makes async-signal-safe syscalls.
+                unsafe {
+                    cmd.pre_exec(move || filter.apply());
+                }
+            }
+            cmd.output()
+        };
+        let Ok(unfiltered) = run(None) else {
+            return; // python3 missing
+        };
+        if !unfiltered.status.success() {
+            return;
+        }
+        let filtered = run(Some(filter)).unwrap();
+        assert_eq!(String::from_utf8_lossy(&unfiltered.stdout).trim(), "0 0");
+        assert_eq!(
+            String::from_utf8_lossy(&filtered.stdout).trim(),
+            format!("-1 {}", libc::EPERM)
+        );
+    }
+}
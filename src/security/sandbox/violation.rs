@@ -0,0 +1,201 @@
+//! Classification of sandbox denials seen by sandboxed commands.
+//!
+//! Landlock, Seatbelt and seccomp reject operations with plain errno values,
+//! so a denial only shows up as an error message or exit status. This module
+//! turns those, and connections the egress proxy blocked, into
+//! [`SandboxViolation`]s that can be counted and reported.
+
+use super::EgressAttempt;
+pub use crate::types::{SandboxViolation, ViolationKind};
+
+/// At most this many violations are reported for one command.
+const MAX_VIOLATIONS: usize = 5;
+
+/// Exit status of a shell whose child was killed by `SIGSYS` (128 + 31),
+/// the signal a seccomp filter raises.
+const SIGSYS_EXIT_CODE: i32 = 159;
+
+const SYSCALL_MARKERS: &[&str] = &["Bad system call"];
+const FILESYSTEM_MARKERS: &[&str] = &[
+    "Operation not permitted",
+    "Permission denied",
+    "Read-only file system",
+    // Seatbelt reports denials as `deny(1) file-write-create /path`.
+    "deny(",
+];
+const NETWORK_MARKERS: &[&str] = &[
+    "Could not resolve host",
+    "Network is unreachable",
+    "Temporary failure in name resolution",
+];
+
+/// Commands and paths that only make sense when trying to leave the sandbox.
+const ESCAPE_MARKERS: &[&str] = &[
+    "nsenter",
+    "unshare",
+    "chroot",
+    "pivot_root",
+    "capsh",
+    "mount ",
+    "/proc/1/root",
+    "docker.sock",
+];
+
+impl SandboxViolation {
+    /// Violations for the connections of `attempts` the egress proxy blocked.
+    pub fn from_egress(command: &str, attempts: &[EgressAttempt]) -> Vec<Self> {
+        let mut violations: Vec<Self> = Vec::new();
+        for attempt in attempts.iter().filter(|a| !a.allowed) {
+            let detail = format!("{}:{}", attempt.host, attempt.port);
+            if violations.iter().any(|v| v.detail == detail) {
+                continue;
+            }
+            let mut violation = Self::new(ViolationKind::Network, command, detail);
+            violation.timestamp = attempt.timestamp;
+            violations.push(violation);
+            if violations.len() == MAX_VIOLATIONS {
+                break;
+            }
+        }
+        violations
+    }
+}
+
+/// Violations shown by the exit status and error output of a sandboxed
+/// command. Empty when the command succeeded or failed for other reasons.
+pub fn classify_failure(
+    command: &str,
+    exit_code: Option<i32>,
+    stderr: &str,
+) -> Vec<SandboxViolation> {
+    let escape = ESCAPE_MARKERS.iter().any(|m| command.contains(m));
+    let mut violations = Vec::new();
+    let mut syscall_seen = false;
+    for line in stderr.lines() {
+        let kind = if SYSCALL_MARKERS.iter().any(|m| line.contains(m)) {
+            syscall_seen = true;
+            ViolationKind::Syscall
+        } else if FILESYSTEM_MARKERS.iter().any(|m| line.contains(m)) {
+            ViolationKind::Filesystem
+        } else if NETWORK_MARKERS.iter().any(|m| line.contains(m)) {
+            ViolationKind::Network
+        } else {
+            continue;
+        };
+        push_unique(&mut violations, kind, escape, command, line);
+    }
+    if exit_code == Some(SIGSYS_EXIT_CODE) && !syscall_seen {
+        push_unique(
+            &mut violations,
+            ViolationKind::Syscall,
+            escape,
+            command,
+            "killed by SIGSYS",
+        );
+    }
+    violations
+}
+
+fn push_unique(
+    violations: &mut Vec<SandboxViolation>,
+    kind: ViolationKind,
+    escape: bool,
+    command: &str,
+    detail: &str,
+) {
+    let kind = if escape { ViolationKind::Escape } else { kind };
+    let detail = detail.trim();
+    if violations.len() < MAX_VIOLATIONS
+        && !violations
+            .iter()
+            .any(|v| v.kind == kind && v.detail == detail)
+    {
+        violations.push(SandboxViolation::new(kind, command, detail));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::sandbox::EgressProtocol;
+
+    fn kinds(violations: &[SandboxViolation]) -> Vec<ViolationKind> {
+        violations.iter().map(|v| v.kind).collect()
+    }
+
+    #[test]
+    fn test_classify_failure() {
+        assert!(classify_failure("ls", Some(0), "").is_empty());
+        assert!(classify_failure("cargo build", Some(101), "error[E0308]: mismatched").is_empty());
+
+        let write = classify_failure(
+            "touch /etc/x",
+            Some(1),
+            "touch: cannot touch '/etc/x': Permission denied\n",
+        );
+        assert_eq!(kinds(&write), [ViolationKind::Filesystem]);
+        assert_eq!(
+            write[0].detail,
+            "touch: cannot touch '/etc/x': Permission denied"
+        );
+
+        let seatbelt = classify_failure("cat ~/.ssh/id_rsa", Some(1), "deny(1) file-read-data");
+        assert_eq!(kinds(&seatbelt), [ViolationKind::Filesystem]);
+
+        assert_eq!(
+            kinds(&classify_failure(
+                "curl https://x.io",
+                Some(6),
+                "curl: (6) Could not resolve host: x.io"
+            )),
+            [ViolationKind::Network]
+        );
+        assert_eq!(
+            kinds(&classify_failure("./probe", Some(159), "")),
+            [ViolationKind::Syscall]
+        );
+        assert_eq!(
+            kinds(&classify_failure(
+                "nsenter -t 1 -m sh",
+                Some(1),
+                "nsenter: Operation not permitted"
+            )),
+            [ViolationKind::Escape]
+        );
+    }
+
+    #[test]
+    fn test_classify_failure_dedupes_and_caps() {
+        let repeated = "rm: x: Permission denied\n".repeat(3);
+        assert_eq!(classify_failure("rm x", Some(1), &repeated).len(), 1);
+
+        let many: String = (0..20)
+            .map(|i| format!("rm: f{i}: Permission denied\n"))
+            .collect();
+        assert_eq!(
+            classify_failure("rm -r .", Some(1), &many).len(),
+            MAX_VIOLATIONS
+        );
+    }
+
+    #[test]
+    fn test_from_egress() {
+        let attempt = |host: &str, allowed| EgressAttempt {
+            timestamp: chrono::Utc::now(),
+            host: host.into(),
+            port: 443,
+            protocol: EgressProtocol::Connect,
+            allowed,
+        };
+        let violations = SandboxViolation::from_egress(
+            "curl evil.io",
+            &[
+                attempt("crates.io", true),
+                attempt("evil.io", false),
+                attempt("evil.io", false),
+            ],
+        );
+        assert_eq!(kinds(&violations), [ViolationKind::Network]);
+        assert_eq!(violations[0].detail, "evil.io:443");
+    }
+}
@@ -0,0 +1,137 @@
+//! Session ownership and access control.
+//!
+//! A [`SessionManager`](super::SessionManager) scoped to a [`Principal`] with
+//! [`for_principal`](super::SessionManager::for_principal) checks every
+//! operation against its [`AccessPolicy`]: loading, diffing and exporting need
+//! [`SessionAccess::Read`]; adding messages, saving, branching, snapshots and
+//! deletion need [`SessionAccess::Append`]; resuming a session in an agent
+//! needs [`SessionAccess::Execute`]. Sessions created through a scoped manager
+//! are owned by its principal. An unscoped manager is trusted and checks
+//! nothing.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::state::Session;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionAccess {
+    Read,
+    Append,
+    Execute,
+}
+
+impl fmt::Display for SessionAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Read => "read",
+            Self::Append => "append",
+            Self::Execute => "execute",
+        })
+    }
+}
+
+/// The user or service a scoped manager acts for.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Principal {
+    pub id: String,
+    #[serde(default)]
+    pub admin: bool,
+}
+
+impl Principal {
+    pub fn user(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            admin: false,
+        }
+    }
+
+    pub fn admin(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            admin: true,
+        }
+    }
+}
+
+pub trait AccessPolicy: Send + Sync {
+    fn allows(&self, principal: &Principal, session: &Session, access: SessionAccess) -> bool;
+}
+
+impl<F> AccessPolicy for F
+where
+    F: Fn(&Principal, &Session, SessionAccess) -> bool + Send + Sync,
+{
+    fn allows(&self, principal: &Principal, session: &Session, access: SessionAccess) -> bool {
+        self(principal, session, access)
+    }
+}
+
+/// Owners and admins have full access.
+///
+/// Sessions without an owner (created before ownership was tracked, or by an
+/// unscoped manager) are open to everyone unless `allow_unowned` is false.
+#[derive(Clone, Copy, Debug)]
+pub struct OwnerPolicy {
+    pub allow_unowned: bool,
+}
+
+impl Default for OwnerPolicy {
+    fn default() -> Self {
+        Self {
+            allow_unowned: true,
+        }
+    }
+}
+
+impl OwnerPolicy {
+    /// Unowned sessions are reserved for admins.
+    pub fn strict() -> Self {
+        Self {
+            allow_unowned: false,
+        }
+    }
+}
+
+impl AccessPolicy for OwnerPolicy {
+    fn allows(&self, principal: &Principal, session: &Session, _access: SessionAccess) -> bool {
+        principal.admin
+            || match &session.owner {
+                Some(owner) => *owner == principal.id,
+                None => self.allow_unowned,
+            }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::state::SessionConfig;
+
+    #[test]
+    fn test_owner_policy() {
+        let mut session = Session::new(SessionConfig::default());
+        let alice = Principal::user("alice");
+        let bob = Principal::user("bob");
+        let root = Principal::admin("root");
+
+        assert!(OwnerPolicy::default().allows(&bob, &session, SessionAccess::Read));
+        assert!(!OwnerPolicy::strict().allows(&bob, &session, SessionAccess::Read));
+        assert!(OwnerPolicy::strict().allows(&root, &session, SessionAccess::Read));
+
+        session.owner = Some("alice".into());
+        let policy = OwnerPolicy::default();
+        assert!(policy.allows(&alice, &session, SessionAccess::Execute));
+        assert!(!policy.allows(&bob, &session, SessionAccess::Read));
+        assert!(policy.allows(&root, &session, SessionAccess::Append));
+
+        let read_only = |p: &Principal, s: &Session, access: SessionAccess| {
+            access == SessionAccess::Read || s.owner.as_deref() == Some(p.id.as_str())
+        };
+        assert!(read_only.allows(&bob, &session, SessionAccess::Read));
+        assert!(!read_only.allows(&bob, &session, SessionAccess::Append));
+    }
+}
@@ -0,0 +1,248 @@
+//! Usage rollups over stored sessions.
+//!
+//! [`SessionManager::stats`](super::SessionManager::stats) loads the matching
+//! sessions and aggregates them into [`StatsBucket`]s keyed by tenant and UTC
+//! day. A session counts toward the day it was created; its token usage, cost
+//! and tool calls are attributed to that bucket as a whole. Tool calls are
+//! counted from `tool_use` blocks and failures from `tool_result` blocks
+//! flagged `is_error`.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::state::{Session, SessionState};
+use crate::types::{ContentBlock, TokenUsage};
+
+/// Which sessions to include. Time bounds apply to session creation.
+#[derive(Clone, Debug, Default)]
+pub struct StatsQuery {
+    pub tenant_id: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl StatsQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn tenant(mut self, tenant_id: impl Into<String>) -> Self {
+        self.tenant_id = Some(tenant_id.into());
+        self
+    }
+
+    /// Sessions created at or after `since`.
+    pub fn since(mut self, since: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    /// Sessions created before `until`.
+    pub fn until(mut self, until: DateTime<Utc>) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    pub(crate) fn matches(&self, session: &Session) -> bool {
+        self.since.is_none_or(|since| session.created_at >= since)
+            && self.until.is_none_or(|until| session.created_at < until)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolCallStats {
+    pub calls: usize,
+    pub errors: usize,
+}
+
+/// Aggregates for one tenant and day (or a merge of several).
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct StatsBucket {
+    pub sessions: usize,
+    pub failed_sessions: usize,
+    pub messages: usize,
+    pub usage: TokenUsage,
+    pub cost_usd: Decimal,
+    pub tool_calls: usize,
+    pub tool_errors: usize,
+    pub tools: BTreeMap<String, ToolCallStats>,
+}
+
+impl StatsBucket {
+    pub fn tool_failure_rate(&self) -> f64 {
+        ratio(self.tool_errors, self.tool_calls)
+    }
+
+    pub fn session_failure_rate(&self) -> f64 {
+        ratio(self.failed_sessions, self.sessions)
+    }
+
+    pub(crate) fn add_session(&mut self, session: &Session) {
+        self.sessions += 1;
+        if session.state == SessionState::Failed {
+            self.failed_sessions += 1;
+        }
+        self.messages += session.messages.len();
+        self.usage.add(&session.total_usage);
+        self.cost_usd += session.total_cost_usd;
+
+        let mut names: HashMap<&str, &str> = HashMap::new();
+        for block in session.messages.iter().flat_map(|m| &m.content) {
+            if let ContentBlock::ToolUse(tool_use) = block {
+                names.insert(&tool_use.id, &tool_use.name);
+                self.tool_calls += 1;
+                self.tools.entry(tool_use.name.clone()).or_default().calls += 1;
+            }
+        }
+        for block in session.messages.iter().flat_map(|m| &m.content) {
+            if let ContentBlock::ToolResult(result) = block
+                && result.is_error == Some(true)
+            {
+                self.tool_errors += 1;
+                if let Some(name) = names.get(result.tool_use_id.as_str()) {
+                    self.tools.entry(name.to_string()).or_default().errors += 1;
+                }
+            }
+        }
+    }
+
+    fn merge(&mut self, other: &StatsBucket) {
+        self.sessions += other.sessions;
+        self.failed_sessions += other.failed_sessions;
+        self.messages += other.messages;
+        self.usage.add(&other.usage);
+        self.cost_usd += other.cost_usd;
+        self.tool_calls += other.tool_calls;
+        self.tool_errors += other.tool_errors;
+        for (name, stats) in &other.tools {
+            let entry = self.tools.entry(name.clone()).or_default();
+            entry.calls += stats.calls;
+            entry.errors += stats.errors;
+        }
+    }
+}
+
+fn ratio(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct StatsKey {
+    /// `None` for sessions without a tenant.
+    pub tenant_id: Option<String>,
+    pub day: NaiveDate,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SessionStatsReport {
+    /// Per tenant and day, ordered by tenant then day.
+    pub buckets: BTreeMap<StatsKey, StatsBucket>,
+}
+
+impl SessionStatsReport {
+    pub(crate) fn add(&mut self, session: &Session) {
+        let key = StatsKey {
+            tenant_id: session.tenant_id.clone(),
+            day: session.created_at.date_naive(),
+        };
+        self.buckets.entry(key).or_default().add_session(session);
+    }
+
+    pub fn total(&self) -> StatsBucket {
+        self.rollup(|_| ()).remove(&()).unwrap_or_default()
+    }
+
+    pub fn by_tenant(&self) -> BTreeMap<Option<String>, StatsBucket> {
+        self.rollup(|key| key.tenant_id.clone())
+    }
+
+    pub fn by_day(&self) -> BTreeMap<NaiveDate, StatsBucket> {
+        self.rollup(|key| key.day)
+    }
+
+    fn rollup<K: Ord>(&self, key: impl Fn(&StatsKey) -> K) -> BTreeMap<K, StatsBucket> {
+        let mut out: BTreeMap<K, StatsBucket> = BTreeMap::new();
+        for (k, bucket) in &self.buckets {
+            out.entry(key(k)).or_default().merge(bucket);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::state::{SessionConfig, SessionMessage};
+    use crate::types::{ToolResultBlock, ToolUseBlock};
+    use chrono::TimeZone;
+
+    fn session(tenant: &str, day: u32, failed_tool: bool) -> Session {
+        let mut session = Session::new(SessionConfig::default());
+        session.tenant_id = Some(tenant.into());
+        session.created_at = Utc.with_ymd_and_hms(2025, 6, day, 12, 0, 0).unwrap();
+        session.total_cost_usd = Decimal::new(25, 2);
+        let mut reply = SessionMessage::assistant(vec![ContentBlock::ToolUse(ToolUseBlock {
+            id: "toolu_1".into(),
+            name: "Bash".into(),
+            input: serde_json::json!({}),
+        })]);
+        reply.usage = Some(TokenUsage {
+            input_tokens: 100,
+            output_tokens: 10,
+            ..Default::default()
+        });
+        session.add_message(reply);
+        session.add_message(SessionMessage::user(vec![ContentBlock::ToolResult(
+            ToolResultBlock {
+                tool_use_id: "toolu_1".into(),
+                content: None,
+                is_error: Some(failed_tool),
+            },
+        )]));
+        session
+    }
+
+    #[test]
+    fn test_rollups() {
+        let mut report = SessionStatsReport::default();
+        report.add(&session("acme", 1, false));
+        report.add(&session("acme", 1, true));
+        report.add(&session("acme", 2, false));
+        let mut failed = session("globex", 2, false);
+        failed.state = SessionState::Failed;
+        report.add(&failed);
+
+        assert_eq!(report.buckets.len(), 3);
+        let total = report.total();
+        assert_eq!(total.sessions, 4);
+        assert_eq!(total.usage.input_tokens, 400);
+        assert_eq!(total.cost_usd, Decimal::new(100, 2));
+        assert_eq!(
+            total.tools["Bash"],
+            ToolCallStats {
+                calls: 4,
+                errors: 1
+            }
+        );
+        assert_eq!(total.tool_failure_rate(), 0.25);
+
+        let by_tenant = report.by_tenant();
+        assert_eq!(by_tenant[&Some("acme".to_string())].sessions, 3);
+        assert_eq!(
+            by_tenant[&Some("globex".to_string())].session_failure_rate(),
+            1.0
+        );
+
+        let by_day = report.by_day();
+        let day1 = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+        assert_eq!(by_day[&day1].tool_errors, 1);
+        assert_eq!(by_day.len(), 2);
+    }
+}
@@ -0,0 +1,270 @@
+//! Portable session archives for moving sessions between environments and backends.
+//!
+//! An archive is a single JSON document carrying the complete session
+//! (messages, todos, plan, compact history), its summaries and a manifest of
+//! the attachments it references. Inline (base64/text) attachments travel
+//! inside the messages; URL and file-ID attachments are listed so the
+//! importing side can tell what must still be reachable.
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::state::{MessageId, Session};
+use super::types::SummarySnapshot;
+use super::{SessionError, SessionResult};
+use crate::common::clock;
+use crate::types::{
+    ContentBlock, DocumentContentBlock, DocumentSource, ImageSource, ToolResultContent,
+    ToolResultContentBlock,
+};
+
+/// Archive format written by this version. Bumped on incompatible changes.
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SessionArchive {
+    pub format_version: u32,
+    /// Version of this crate that produced the archive.
+    pub sdk_version: String,
+    pub exported_at: DateTime<Utc>,
+    pub session: Session,
+    #[serde(default)]
+    pub summaries: Vec<SummarySnapshot>,
+    #[serde(default)]
+    pub attachments: Vec<AttachmentEntry>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentKind {
+    Image,
+    Document,
+}
+
+/// Where an attachment's bytes live.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AttachmentLocation {
+    /// Embedded in the message; `size` is the encoded length.
+    Inline {
+        size: usize,
+    },
+    Url {
+        url: String,
+    },
+    File {
+        file_id: String,
+    },
+}
+
+impl AttachmentLocation {
+    pub fn is_inline(&self) -> bool {
+        matches!(self, Self::Inline { .. })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttachmentEntry {
+    pub message_id: MessageId,
+    pub kind: AttachmentKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+    pub location: AttachmentLocation,
+}
+
+impl SessionArchive {
+    pub fn new(session: Session, summaries: Vec<SummarySnapshot>) -> Self {
+        let attachments = attachment_manifest(&session);
+        Self {
+            format_version: ARCHIVE_FORMAT_VERSION,
+            sdk_version: env!("CARGO_PKG_VERSION").to_string(),
+            exported_at: clock::now_utc(),
+            session,
+            summaries,
+            attachments,
+        }
+    }
+
+    /// Attachments that are not embedded and must be reachable after import.
+    pub fn external_attachments(&self) -> impl Iterator<Item = &AttachmentEntry> {
+        self.attachments.iter().filter(|a| !a.location.is_inline())
+    }
+
+    pub fn to_json(&self) -> SessionResult<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Parse an archive, rejecting formats newer than this version understands.
+    pub fn from_json(json: &str) -> SessionResult<Self> {
+        let archive: Self = serde_json::from_str(json)?;
+        archive.check_version()?;
+        Ok(archive)
+    }
+
+    pub async fn write_to(&self, path: impl AsRef<Path>) -> SessionResult<()> {
+        tokio::fs::write(path.as_ref(), self.to_json()?)
+            .await
+            .map_err(|e| SessionError::Storage {
+                message: format!("Failed to write {}: {}", path.as_ref().display(), e),
+            })
+    }
+
+    pub async fn read_from(path: impl AsRef<Path>) -> SessionResult<Self> {
+        let json = tokio::fs::read_to_string(path.as_ref())
+            .await
+            .map_err(|e| SessionError::Storage {
+                message: format!("Failed to read {}: {}", path.as_ref().display(), e),
+            })?;
+        Self::from_json(&json)
+    }
+
+    pub(crate) fn check_version(&self) -> SessionResult<()> {
+        if self.format_version == 0 || self.format_version > ARCHIVE_FORMAT_VERSION {
+            return Err(SessionError::Storage {
+                message: format!(
+                    "Unsupported session archive format {} (supported: 1..={})",
+                    self.format_version, ARCHIVE_FORMAT_VERSION
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+fn attachment_manifest(session: &Session) -> Vec<AttachmentEntry> {
+    let mut entries = Vec::new();
+    for message in &session.messages {
+        let mut push = |kind, media_type: Option<&str>, location| {
+            entries.push(AttachmentEntry {
+                message_id: message.id.clone(),
+                kind,
+                media_type: media_type.map(str::to_string),
+                location,
+            })
+        };
+        for block in &message.content {
+            match block {
+                ContentBlock::Image { source } => {
+                    let (media_type, location) = image_location(source);
+                    push(AttachmentKind::Image, media_type, location);
+                }
+                ContentBlock::Document(doc) => {
+                    let (media_type, location) = document_location(&doc.source);
+                    push(AttachmentKind::Document, media_type, location);
+                }
+                ContentBlock::ToolResult(result) => {
+                    if let Some(ToolResultContent::Blocks(blocks)) = &result.content {
+                        for inner in blocks {
+                            match inner {
+                                ToolResultContentBlock::Image { source } => {
+                                    let (media_type, location) = image_location(source);
+                                    push(AttachmentKind::Image, media_type, location);
+                                }
+                                ToolResultContentBlock::Document { source } => {
+                                    let (media_type, location) = document_location(source);
+                                    push(AttachmentKind::Document, media_type, location);
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    entries
+}
+
+fn image_location(source: &ImageSource) -> (Option<&str>, AttachmentLocation) {
+    match source {
+        ImageSource::Base64 { media_type, data } => (
+            Some(media_type.as_str()),
+            AttachmentLocation::Inline { size: data.len() },
+        ),
+        ImageSource::Url { url } => (None, AttachmentLocation::Url { url: url.clone() }),
+        ImageSource::File { file_id } => (
+            None,
+            AttachmentLocation::File {
+                file_id: file_id.clone(),
+            },
+        ),
+    }
+}
+
+fn document_location(source: &DocumentSource) -> (Option<&str>, AttachmentLocation) {
+    match source {
+        DocumentSource::Text { media_type, data } | DocumentSource::Base64 { media_type, data } => {
+            (
+                Some(media_type.as_str()),
+                AttachmentLocation::Inline { size: data.len() },
+            )
+        }
+        DocumentSource::Content { content } => {
+            let size = content
+                .iter()
+                .map(|block| match block {
+                    DocumentContentBlock::Text { text } => text.len(),
+                })
+                .sum();
+            (None, AttachmentLocation::Inline { size })
+        }
+        DocumentSource::Url { url } => (None, AttachmentLocation::Url { url: url.clone() }),
+        DocumentSource::File { file_id } => (
+            None,
+            AttachmentLocation::File {
+                file_id: file_id.clone(),
+            },
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::state::{SessionConfig, SessionMessage};
+
+    #[test]
+    fn test_manifest_and_roundtrip() {
+        let mut session = Session::new(SessionConfig::default());
+        session.add_message(SessionMessage::user(vec![
+            ContentBlock::text("Look at these"),
+            ContentBlock::Image {
+                source: ImageSource::base64("image/png", "aGVsbG8="),
+            },
+            ContentBlock::Image {
+                source: ImageSource::from_url("https://example.com/cat.png"),
+            },
+        ]));
+
+        let archive = SessionArchive::new(session, Vec::new());
+        assert_eq!(archive.attachments.len(), 2);
+        assert_eq!(
+            archive.attachments[0].media_type.as_deref(),
+            Some("image/png")
+        );
+        assert_eq!(
+            archive.attachments[0].location,
+            AttachmentLocation::Inline { size: 8 }
+        );
+        assert_eq!(archive.external_attachments().count(), 1);
+
+        let restored = SessionArchive::from_json(&archive.to_json().unwrap()).unwrap();
+        assert_eq!(restored.session.id, archive.session.id);
+        assert_eq!(restored.session.messages.len(), 1);
+        assert_eq!(restored.attachments, archive.attachments);
+    }
+
+    #[test]
+    fn test_rejects_newer_format() {
+        let mut archive = SessionArchive::new(Session::new(SessionConfig::default()), Vec::new());
+        archive.format_version = ARCHIVE_FORMAT_VERSION + 1;
+        let json = serde_json::to_string(&archive).unwrap();
+        assert!(matches!(
+            SessionArchive::from_json(&json),
+            Err(SessionError::Storage { .. })
+        ));
+    }
+}
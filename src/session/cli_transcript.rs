@@ -0,0 +1,458 @@
+//! Interop with Claude Code CLI transcripts.
+//!
+//! The CLI writes one JSONL file per session under
+//! `~/.claude/projects/{project}/{session-id}.jsonl`, where `{project}` is the
+//! working directory with every non-alphanumeric character replaced by `-`.
+//! [`CliTranscripts`] reads those files into a [`Session`] so a conversation
+//! started in the CLI can be resumed here, and writes sessions back in the
+//! same layout so `claude --resume` picks them up.
+//!
+//! Only `user`, `assistant` and `summary` entries carry conversation state.
+//! Other entries (`system`, `file-history-snapshot`, ...) are skipped, but
+//! messages parented to them are re-attached to the nearest kept ancestor.
+//! Content blocks this crate does not model are dropped.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{Value, json};
+
+use super::state::{MessageId, Session, SessionConfig, SessionId, SessionMessage};
+use super::types::EnvironmentContext;
+use super::{SessionError, SessionResult};
+use crate::types::{ContentBlock, Role, TokenUsage};
+
+/// Session transcripts in a Claude Code configuration directory.
+#[derive(Clone, Debug)]
+pub struct CliTranscripts {
+    base_dir: PathBuf,
+}
+
+impl CliTranscripts {
+    /// `base_dir` is the CLI configuration directory (usually `~/.claude`).
+    pub fn new(base_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            base_dir: base_dir.into(),
+        }
+    }
+
+    /// Transcripts in `~/.claude`, if the home directory is known.
+    pub fn user() -> Option<Self> {
+        crate::common::home_dir().map(|home| Self::new(home.join(".claude")))
+    }
+
+    pub fn project_dir(&self, project: &Path) -> PathBuf {
+        self.base_dir
+            .join("projects")
+            .join(encode_project_dir(project))
+    }
+
+    pub fn path(&self, project: &Path, session_id: &SessionId) -> PathBuf {
+        self.project_dir(project)
+            .join(format!("{}.jsonl", session_id))
+    }
+
+    /// Sessions recorded for `project`, most recently modified first.
+    pub async fn list(&self, project: &Path) -> SessionResult<Vec<SessionId>> {
+        let dir = self.project_dir(project);
+        let mut entries = match tokio::fs::read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(SessionError::Storage {
+                    message: format!("Failed to read {}: {}", dir.display(), e),
+                });
+            }
+        };
+
+        let mut sessions = Vec::new();
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| SessionError::Storage {
+                message: format!("Failed to read {}: {}", dir.display(), e),
+            })?
+        {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("jsonl") {
+                continue;
+            }
+            let Some(id) = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(SessionId::parse)
+            else {
+                continue;
+            };
+            let modified = entry.metadata().await.and_then(|m| m.modified()).ok();
+            sessions.push((modified, id));
+        }
+        sessions.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+        Ok(sessions.into_iter().map(|(_, id)| id).collect())
+    }
+
+    pub async fn read(&self, project: &Path, session_id: &SessionId) -> SessionResult<Session> {
+        let path = self.path(project, session_id);
+        let jsonl = tokio::fs::read_to_string(&path).await.map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                SessionError::NotFound {
+                    id: session_id.to_string(),
+                }
+            } else {
+                SessionError::Storage {
+                    message: format!("Failed to read {}: {}", path.display(), e),
+                }
+            }
+        })?;
+        Ok(parse_transcript(*session_id, &jsonl))
+    }
+
+    /// Write `session` as a CLI transcript for `project`, replacing any
+    /// existing file. Returns the transcript path.
+    pub async fn write(&self, project: &Path, session: &Session) -> SessionResult<PathBuf> {
+        let path = self.path(project, &session.id);
+        let dir = self.project_dir(project);
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| SessionError::Storage {
+                message: format!("Failed to create {}: {}", dir.display(), e),
+            })?;
+
+        let tmp = path.with_extension("jsonl.tmp");
+        tokio::fs::write(&tmp, render_transcript(session, project)?)
+            .await
+            .map_err(|e| SessionError::Storage {
+                message: format!("Failed to write {}: {}", tmp.display(), e),
+            })?;
+        tokio::fs::rename(&tmp, &path)
+            .await
+            .map_err(|e| SessionError::Storage {
+                message: format!("Failed to write {}: {}", path.display(), e),
+            })?;
+        Ok(path)
+    }
+}
+
+/// Directory name the CLI uses for a project path.
+pub fn encode_project_dir(project: &Path) -> String {
+    project
+        .to_string_lossy()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// Build a session from the contents of a CLI transcript.
+///
+/// Malformed lines are skipped. The current leaf is the last main-chain
+/// message in the file, which is where the CLI resumes.
+pub fn parse_transcript(session_id: SessionId, jsonl: &str) -> Session {
+    let mut session = Session::from_id(session_id, SessionConfig::default());
+    // Skipped entries that other entries may point at, mapped to their parent.
+    let mut skipped: HashMap<String, Option<String>> = HashMap::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut counted_usage: HashSet<String> = HashSet::new();
+    let mut first_timestamp = None;
+    let mut last_timestamp = None;
+
+    for (line_num, line) in jsonl.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: Value = match serde_json::from_str(line) {
+            Ok(entry) => entry,
+            Err(e) => {
+                tracing::warn!(line = line_num + 1, error = %e, "Skipping malformed transcript line");
+                continue;
+            }
+        };
+        let str_field = |key: &str| entry.get(key).and_then(Value::as_str);
+        let parent = resolve_parent(str_field("parentUuid"), &skipped);
+
+        match str_field("type") {
+            Some("summary") => {
+                if let Some(summary) = str_field("summary") {
+                    session.summary = Some(summary.to_string());
+                }
+            }
+            Some(kind @ ("user" | "assistant")) => {
+                let Some(uuid) = str_field("uuid") else {
+                    continue;
+                };
+                if !seen.insert(uuid.to_string()) {
+                    continue;
+                }
+                let mut message = parse_message(&entry, kind == "assistant");
+                message.id = MessageId::from_string(uuid);
+                message.parent_id = parent.map(MessageId::from_string);
+
+                // The CLI splits one API response across entries that repeat its usage.
+                let api_id = entry
+                    .pointer("/message/id")
+                    .and_then(Value::as_str)
+                    .map(str::to_string);
+                if message.usage.is_some() && api_id.is_some_and(|id| !counted_usage.insert(id)) {
+                    message.usage = None;
+                }
+                if let Some(usage) = &message.usage {
+                    session.total_usage.add(usage);
+                }
+
+                first_timestamp.get_or_insert(message.timestamp);
+                last_timestamp = Some(message.timestamp);
+                if !message.is_sidechain {
+                    session.current_leaf_id = Some(message.id.clone());
+                }
+                session.messages.push(message);
+            }
+            _ => {
+                if let Some(uuid) = str_field("uuid") {
+                    skipped.insert(uuid.to_string(), parent);
+                }
+            }
+        }
+    }
+
+    if let Some(created) = first_timestamp {
+        session.created_at = created;
+    }
+    if let Some(updated) = last_timestamp {
+        session.updated_at = updated;
+    }
+    session
+}
+
+/// Render `session` as a CLI transcript. `cwd` is recorded for messages that
+/// carry no environment of their own.
+pub fn render_transcript(session: &Session, cwd: &Path) -> SessionResult<String> {
+    let mut out = String::new();
+    if let Some(summary) = &session.summary {
+        push_line(
+            &mut out,
+            &json!({
+                "type": "summary",
+                "summary": summary,
+                "leafUuid": session.current_leaf_id.as_ref().map(|id| id.to_string()),
+            }),
+        )?;
+    }
+
+    for message in &session.messages {
+        let environment = message.environment.as_ref();
+        let mut entry = json!({
+            "parentUuid": message.parent_id.as_ref().map(|id| id.to_string()),
+            "isSidechain": message.is_sidechain,
+            "userType": "external",
+            "cwd": environment
+                .and_then(|e| e.cwd.as_deref())
+                .unwrap_or(cwd),
+            "sessionId": session.id.to_string(),
+            "version": env!("CARGO_PKG_VERSION"),
+            "gitBranch": environment
+                .and_then(|e| e.git_branch.as_deref())
+                .unwrap_or_default(),
+            "uuid": message.id.to_string(),
+            "timestamp": message.timestamp.to_rfc3339_opts(SecondsFormat::Millis, true),
+        });
+        let content = serde_json::to_value(&message.content)?;
+
+        match message.role {
+            Role::User => {
+                entry["type"] = json!("user");
+                entry["message"] = json!({ "role": "user", "content": content });
+                if message.is_compact_summary {
+                    entry["isCompactSummary"] = json!(true);
+                }
+            }
+            Role::Assistant => {
+                entry["type"] = json!("assistant");
+                entry["message"] = json!({
+                    "id": format!("msg_{}", message.id.to_string().replace('-', "")),
+                    "type": "message",
+                    "role": "assistant",
+                    "model": message.metadata.model,
+                    "content": content,
+                    "stop_reason": null,
+                    "stop_sequence": null,
+                    "usage": message.usage,
+                });
+                if let Some(request_id) = &message.metadata.request_id {
+                    entry["requestId"] = json!(request_id);
+                }
+            }
+        }
+        push_line(&mut out, &entry)?;
+    }
+    Ok(out)
+}
+
+fn push_line(out: &mut String, entry: &Value) -> SessionResult<()> {
+    out.push_str(&serde_json::to_string(entry)?);
+    out.push('\n');
+    Ok(())
+}
+
+fn resolve_parent(
+    parent: Option<&str>,
+    skipped: &HashMap<String, Option<String>>,
+) -> Option<String> {
+    let mut current = parent.map(str::to_string);
+    // Bounded walk in case of a malformed cycle.
+    for _ in 0..=skipped.len() {
+        match current.as_ref().and_then(|id| skipped.get(id)) {
+            Some(grandparent) => current = grandparent.clone(),
+            None => break,
+        }
+    }
+    current
+}
+
+fn parse_message(entry: &Value, assistant: bool) -> SessionMessage {
+    let body = entry.get("message").unwrap_or(&Value::Null);
+    let content = parse_content(body.get("content").unwrap_or(&Value::Null));
+    let mut message = if assistant {
+        SessionMessage::assistant(content)
+    } else {
+        SessionMessage::user(content)
+    };
+
+    if let Some(timestamp) = entry
+        .get("timestamp")
+        .and_then(Value::as_str)
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+    {
+        message.timestamp = timestamp.with_timezone(&Utc);
+    }
+    message.is_sidechain = entry
+        .get("isSidechain")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    message.is_compact_summary = entry
+        .get("isCompactSummary")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    message.environment = Some(EnvironmentContext {
+        cwd: entry.get("cwd").and_then(Value::as_str).map(PathBuf::from),
+        git_branch: entry
+            .get("gitBranch")
+            .and_then(Value::as_str)
+            .filter(|b| !b.is_empty())
+            .map(str::to_string),
+        ..Default::default()
+    });
+
+    if assistant {
+        message.metadata.model = body
+            .get("model")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        message.metadata.request_id = entry
+            .get("requestId")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        message.usage = body
+            .get("usage")
+            .and_then(|u| serde_json::from_value::<TokenUsage>(u.clone()).ok());
+    }
+    message
+}
+
+/// User content may be a plain string; unknown block types are dropped.
+fn parse_content(content: &Value) -> Vec<ContentBlock> {
+    match content {
+        Value::String(text) => vec![ContentBlock::text(text.as_str())],
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|block| match serde_json::from_value(block.clone()) {
+                Ok(block) => Some(block),
+                Err(e) => {
+                    tracing::debug!(error = %e, "Dropping unsupported transcript content block");
+                    None
+                }
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRANSCRIPT: &str = r#"{"type":"summary","summary":"Fix login bug","leafUuid":"a2"}
+{"parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/work/app","sessionId":"6f1c4c2e-8a57-4c43-9a4e-0c3c6c1f7f10","version":"1.0.80","gitBranch":"main","type":"user","message":{"role":"user","content":"Why does login fail?"},"uuid":"u1","timestamp":"2025-06-01T10:00:00.000Z"}
+{"parentUuid":"u1","isSidechain":false,"userType":"external","cwd":"/work/app","sessionId":"6f1c4c2e-8a57-4c43-9a4e-0c3c6c1f7f10","version":"1.0.80","gitBranch":"main","message":{"id":"msg_1","type":"message","role":"assistant","model":"claude-sonnet-4-5","content":[{"type":"text","text":"Checking."}],"stop_reason":null,"usage":{"input_tokens":10,"output_tokens":5,"service_tier":"standard"}},"requestId":"req_1","type":"assistant","uuid":"a1","timestamp":"2025-06-01T10:00:01.000Z"}
+{"parentUuid":"a1","isSidechain":false,"userType":"external","cwd":"/work/app","sessionId":"6f1c4c2e-8a57-4c43-9a4e-0c3c6c1f7f10","version":"1.0.80","gitBranch":"main","message":{"id":"msg_1","type":"message","role":"assistant","model":"claude-sonnet-4-5","content":[{"type":"tool_use","id":"toolu_1","name":"Read","input":{"file_path":"/work/app/login.rs"}}],"usage":{"input_tokens":10,"output_tokens":5}},"requestId":"req_1","type":"assistant","uuid":"a2","timestamp":"2025-06-01T10:00:02.000Z"}
+{"type":"file-history-snapshot","messageId":"a2","snapshot":{}}
+{"parentUuid":"a2","type":"system","subtype":"informational","content":"hook ran","uuid":"s1","timestamp":"2025-06-01T10:00:03.000Z"}
+{"parentUuid":"s1","isSidechain":false,"userType":"external","cwd":"/work/app","sessionId":"6f1c4c2e-8a57-4c43-9a4e-0c3c6c1f7f10","version":"1.0.80","gitBranch":"main","type":"user","message":{"role":"user","content":[{"tool_use_id":"toolu_1","type":"tool_result","content":"fn login() {}"}]},"toolUseResult":{"type":"text"},"uuid":"u2","timestamp":"2025-06-01T10:00:04.000Z"}
+not json
+"#;
+
+    fn session_id() -> SessionId {
+        SessionId::parse("6f1c4c2e-8a57-4c43-9a4e-0c3c6c1f7f10").unwrap()
+    }
+
+    #[test]
+    fn test_parse_cli_transcript() {
+        let session = parse_transcript(session_id(), TRANSCRIPT);
+
+        assert_eq!(session.summary.as_deref(), Some("Fix login bug"));
+        assert_eq!(session.messages.len(), 4);
+        assert_eq!(
+            session.messages[0].content[0].as_text(),
+            Some("Why does login fail?")
+        );
+        assert_eq!(
+            session.messages[1].metadata.request_id.as_deref(),
+            Some("req_1")
+        );
+        // Tool result re-attached across the skipped system entry.
+        assert_eq!(
+            session.messages[3].parent_id,
+            Some(MessageId::from_string("a2"))
+        );
+        assert_eq!(session.current_leaf_id, Some(MessageId::from_string("u2")));
+        assert_eq!(session.current_branch().len(), 4);
+        // Split response usage counted once.
+        assert_eq!(session.total_usage.output_tokens, 5);
+        assert_eq!(
+            session.messages[0]
+                .environment
+                .as_ref()
+                .and_then(|e| e.git_branch.as_deref()),
+            Some("main")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_then_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let transcripts = CliTranscripts::new(dir.path());
+        let project = Path::new("/work/my.app");
+        assert_eq!(
+            transcripts.project_dir(project),
+            dir.path().join("projects").join("-work-my-app")
+        );
+
+        let original = parse_transcript(session_id(), TRANSCRIPT);
+        let path = transcripts.write(project, &original).await.unwrap();
+        assert!(path.exists());
+        assert_eq!(transcripts.list(project).await.unwrap(), vec![original.id]);
+
+        let restored = transcripts.read(project, &original.id).await.unwrap();
+        assert_eq!(restored.messages.len(), original.messages.len());
+        assert_eq!(restored.current_leaf_id, original.current_leaf_id);
+        assert_eq!(restored.summary, original.summary);
+        assert!(matches!(
+            restored.messages[2].content[0],
+            ContentBlock::ToolUse(_)
+        ));
+
+        assert!(matches!(
+            transcripts.read(project, &SessionId::new()).await,
+            Err(SessionError::NotFound { .. })
+        ));
+    }
+}
@@ -3,17 +3,440 @@
 //! Summarizes the entire conversation when context exceeds threshold.
 //! Ported from Claude Code CLI's compact implementation for full compatibility.
 
+use std::collections::{HashMap, HashSet};
+
 use serde::{Deserialize, Serialize};
 
 use super::state::{Session, SessionMessage};
-use super::types::CompactRecord;
+use super::types::{CompactRecord, TodoStatus};
 use super::{SessionError, SessionResult};
-use crate::client::DEFAULT_SMALL_MODEL;
-use crate::types::{CompactResult, ContentBlock, Role};
+use crate::client::{DEFAULT_SMALL_MODEL, ModelConfig, ModelType};
+use crate::common::clock;
+use crate::types::{CompactResult, ContentBlock, Role, ToolResultContent, ToolResultContentBlock};
 
 /// Context usage threshold for triggering compaction (80%).
 pub const DEFAULT_COMPACT_THRESHOLD: f32 = 0.8;
 
+/// Placeholder in [`CompactStrategy::prompt_template`] replaced by the conversation.
+pub const CONVERSATION_PLACEHOLDER: &str = "{conversation}";
+
+/// Marker prefixed to tool results replaced by [`ToolResultPruning`].
+const PRUNED_MARKER: &str = "[Tool result pruned";
+
+/// Replaces old `tool_result` blocks with a short digest before summarizing.
+///
+/// Tool output (file contents, command logs, search hits) usually dominates
+/// the context and is rarely needed verbatim once the model has acted on it.
+/// Pruning keeps the tool name, the original size and the first
+/// `digest_chars` characters, which is often enough to fall back under the
+/// threshold without a lossy summary.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ToolResultPruning {
+    /// Most recent tool results left untouched.
+    pub keep_recent: usize,
+    /// Characters of the original output kept in each digest.
+    pub digest_chars: usize,
+}
+
+impl Default for ToolResultPruning {
+    fn default() -> Self {
+        Self {
+            keep_recent: 4,
+            digest_chars: 200,
+        }
+    }
+}
+
+impl ToolResultPruning {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn keep_recent(mut self, count: usize) -> Self {
+        self.keep_recent = count;
+        self
+    }
+
+    pub fn digest_chars(mut self, chars: usize) -> Self {
+        self.digest_chars = chars;
+        self
+    }
+
+    /// Prune tool results in `session`, oldest first, keeping the most recent
+    /// `keep_recent` intact. Results already short enough are left alone.
+    pub fn apply(&self, session: &mut Session) -> PrunedToolResults {
+        let names: HashMap<String, String> = session
+            .messages
+            .iter()
+            .flat_map(|m| &m.content)
+            .filter_map(|block| match block {
+                ContentBlock::ToolUse(tool_use) => {
+                    Some((tool_use.id.clone(), tool_use.name.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+
+        let total = session
+            .messages
+            .iter()
+            .flat_map(|m| &m.content)
+            .filter(|block| matches!(block, ContentBlock::ToolResult(_)))
+            .count();
+        let prunable = total.saturating_sub(self.keep_recent);
+
+        let mut pruned = PrunedToolResults::default();
+        let results = session
+            .messages
+            .iter_mut()
+            .flat_map(|m| m.content.iter_mut())
+            .filter_map(|block| match block {
+                ContentBlock::ToolResult(result) => Some(result),
+                _ => None,
+            })
+            .take(prunable);
+
+        for result in results {
+            let Some(content) = &result.content else {
+                continue;
+            };
+            let (text, images, documents) = flatten_tool_result(content);
+            if text.starts_with(PRUNED_MARKER) {
+                continue;
+            }
+
+            let name = names
+                .get(&result.tool_use_id)
+                .map(String::as_str)
+                .unwrap_or("unknown");
+            let mut digest = format!(
+                "{}: {}, {} chars",
+                PRUNED_MARKER,
+                name,
+                text.chars().count()
+            );
+            if images > 0 {
+                digest.push_str(&format!(", {} image(s)", images));
+            }
+            if documents > 0 {
+                digest.push_str(&format!(", {} document(s)", documents));
+            }
+            if result.is_error == Some(true) {
+                digest.push_str(", error");
+            }
+            digest.push(']');
+            let preview: String = text.chars().take(self.digest_chars).collect();
+            if !preview.is_empty() {
+                digest.push('\n');
+                digest.push_str(&preview);
+                if preview.len() < text.len() {
+                    digest.push_str("...");
+                }
+            }
+
+            if images == 0 && documents == 0 && digest.len() >= text.len() {
+                continue;
+            }
+
+            pruned.saved_tokens += text.len().saturating_sub(digest.len()) / 4;
+            pruned.count += 1;
+            result.content = Some(ToolResultContent::Text(digest));
+        }
+
+        if pruned.count > 0 {
+            session.updated_at = clock::now_utc();
+        }
+        pruned
+    }
+}
+
+/// Text of a tool result and the number of images and documents it carried.
+fn flatten_tool_result(content: &ToolResultContent) -> (String, usize, usize) {
+    match content {
+        ToolResultContent::Text(text) => (text.clone(), 0, 0),
+        ToolResultContent::Blocks(blocks) => {
+            let mut text = String::new();
+            let mut images = 0;
+            let mut documents = 0;
+            for block in blocks {
+                match block {
+                    ToolResultContentBlock::Text { text: t } => {
+                        if !text.is_empty() {
+                            text.push('\n');
+                        }
+                        text.push_str(t);
+                    }
+                    ToolResultContentBlock::Image { .. } => images += 1,
+                    ToolResultContentBlock::Document { .. } => documents += 1,
+                    ToolResultContentBlock::SearchResult(result) => {
+                        if !text.is_empty() {
+                            text.push('\n');
+                        }
+                        text.push_str(&result.title);
+                    }
+                }
+            }
+            (text, images, documents)
+        }
+    }
+}
+
+/// Outcome of [`ToolResultPruning::apply`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PrunedToolResults {
+    /// Tool results replaced with a digest.
+    pub count: usize,
+    /// Estimated input tokens freed (4 chars per token).
+    pub saved_tokens: usize,
+}
+
+/// Deterministic compaction without a summarization call.
+///
+/// Keeps the first `keep_first` messages (usually the task statement) and the
+/// last `keep_last`, replacing everything in between with a placeholder. The
+/// cut points are widened so a `tool_use` is never separated from its
+/// `tool_result`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SlidingWindow {
+    pub keep_first: usize,
+    pub keep_last: usize,
+}
+
+impl SlidingWindow {
+    pub fn new(keep_first: usize, keep_last: usize) -> Self {
+        Self {
+            keep_first,
+            keep_last,
+        }
+    }
+
+    /// Elide the middle of the conversation. Returns
+    /// [`CompactResult::NotNeeded`] when the window already covers it.
+    pub fn apply(&self, session: &mut Session) -> CompactResult {
+        let messages = &session.messages;
+        let len = messages.len();
+
+        let mut head = self.keep_first.min(len);
+        if head > 0 && head < len && has_tool_use(&messages[head - 1]) {
+            head += 1;
+        }
+        let mut tail = len.saturating_sub(self.keep_last);
+        if tail > 0 && tail < len && has_tool_result(&messages[tail]) {
+            tail -= 1;
+        }
+        if tail <= head {
+            return CompactResult::NotNeeded;
+        }
+
+        let elided = tail - head;
+        let saved_chars: usize = messages[head..tail]
+            .iter()
+            .flat_map(|m| &m.content)
+            .filter_map(|c| c.as_text())
+            .map(str::len)
+            .sum();
+        let marker = format!("[{} earlier messages elided]", elided);
+
+        let mut new_messages = Vec::with_capacity(len - elided + 1);
+        new_messages.extend_from_slice(&messages[..head]);
+        new_messages.push(
+            SessionMessage::user(vec![ContentBlock::text(marker.clone())]).as_compact_summary(),
+        );
+        new_messages.extend_from_slice(&messages[tail..]);
+
+        let mut parent_id = None;
+        for msg in &mut new_messages {
+            msg.parent_id = parent_id;
+            parent_id = Some(msg.id.clone());
+        }
+
+        let original_count = len;
+        session.messages = new_messages;
+        session.current_leaf_id = parent_id;
+        // Reset like a summary compaction; the next response reports the real value.
+        session.current_input_tokens = 0;
+        session.updated_at = clock::now_utc();
+
+        let new_count = session.messages.len();
+        let saved_tokens = saved_chars / 4;
+        let record = CompactRecord::new(session.id)
+            .counts(original_count, new_count)
+            .summary(marker.clone())
+            .saved_tokens(saved_tokens);
+        session.record_compact(record);
+
+        CompactResult::Compacted {
+            original_count,
+            new_count,
+            saved_tokens,
+            summary: marker,
+        }
+    }
+}
+
+fn has_tool_use(message: &SessionMessage) -> bool {
+    message
+        .content
+        .iter()
+        .any(|b| matches!(b, ContentBlock::ToolUse(_)))
+}
+
+fn has_tool_result(message: &SessionMessage) -> bool {
+    message
+        .content
+        .iter()
+        .any(|b| matches!(b, ContentBlock::ToolResult(_)))
+}
+
+/// Checks that a summary still covers open work, retrying with a stronger
+/// model when it does not.
+///
+/// A summary must mention every open todo, the active plan, and each file
+/// that is referenced both in the summarized part and in the kept messages.
+/// Matching is lenient: a todo or plan counts as mentioned when at least half
+/// of its significant words appear. Anything still missing after the retries
+/// is appended to the summary so it is never silently lost.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CompactVerification {
+    /// Model ID or alias for retries (default: `sonnet`, the client's primary model).
+    pub retry_model: String,
+    pub max_retries: u32,
+}
+
+impl Default for CompactVerification {
+    fn default() -> Self {
+        Self {
+            retry_model: "sonnet".to_string(),
+            max_retries: 1,
+        }
+    }
+}
+
+impl CompactVerification {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn retry_model(mut self, model: impl Into<String>) -> Self {
+        self.retry_model = model.into();
+        self
+    }
+
+    pub fn max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+}
+
+/// Context a summary of `messages[..split_point]` must keep.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RequiredMention {
+    Todo(String),
+    Plan(String),
+    File(String),
+}
+
+impl RequiredMention {
+    pub fn collect(session: &Session, split_point: usize) -> Vec<Self> {
+        let mut required: Vec<Self> = session
+            .todos
+            .iter()
+            .filter(|t| t.status != TodoStatus::Completed)
+            .map(|t| Self::Todo(t.content.clone()))
+            .collect();
+
+        if let Some(plan) = session
+            .current_plan
+            .as_ref()
+            .filter(|p| !p.status.is_terminal())
+        {
+            let title = plan.name.clone().or_else(|| {
+                plan.content
+                    .lines()
+                    .map(|l| l.trim_start_matches('#').trim())
+                    .find(|l| !l.is_empty())
+                    .map(String::from)
+            });
+            if let Some(title) = title {
+                required.push(Self::Plan(title));
+            }
+        }
+
+        let split_point = split_point.min(session.messages.len());
+        let earlier = referenced_files(&session.messages[..split_point]);
+        let mut kept: Vec<String> = referenced_files(&session.messages[split_point..])
+            .into_iter()
+            .filter(|f| earlier.contains(f))
+            .collect();
+        kept.sort();
+        required.extend(kept.into_iter().map(Self::File));
+        required
+    }
+
+    pub fn is_mentioned(&self, summary: &str) -> bool {
+        let summary = summary.to_lowercase();
+        match self {
+            Self::Todo(text) | Self::Plan(text) => mostly_mentioned(text, &summary),
+            Self::File(path) => {
+                let name = std::path::Path::new(path)
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or(path);
+                summary.contains(&path.to_lowercase()) || summary.contains(&name.to_lowercase())
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for RequiredMention {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Todo(text) => write!(f, "Open todo: {}", text),
+            Self::Plan(text) => write!(f, "Active plan: {}", text),
+            Self::File(path) => write!(f, "File: {}", path),
+        }
+    }
+}
+
+pub(crate) fn bullet_list(items: &[&RequiredMention]) -> String {
+    items.iter().map(|item| format!("- {}\n", item)).collect()
+}
+
+/// Whether `text`, or at least half of its words of 4+ characters, appears in
+/// the lowercased `summary`.
+fn mostly_mentioned(text: &str, summary: &str) -> bool {
+    let text = text.to_lowercase();
+    if summary.contains(text.trim()) {
+        return true;
+    }
+    let words: Vec<&str> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 4)
+        .collect();
+    if words.is_empty() {
+        return false;
+    }
+    let found = words.iter().filter(|w| summary.contains(**w)).count();
+    found * 2 >= words.len()
+}
+
+/// File paths passed to tools in `messages`.
+fn referenced_files(messages: &[SessionMessage]) -> HashSet<String> {
+    messages
+        .iter()
+        .flat_map(|m| &m.content)
+        .filter_map(|block| match block {
+            ContentBlock::ToolUse(tool_use) => Some(&tool_use.input),
+            _ => None,
+        })
+        .flat_map(|input| {
+            ["file_path", "notebook_path"]
+                .into_iter()
+                .filter_map(|key| input.get(key).and_then(|v| v.as_str()).map(String::from))
+        })
+        .collect()
+}
+
 /// Strategy for context compaction.
 ///
 /// Controls when and how conversation history is summarized to fit within
@@ -26,6 +449,9 @@ pub const DEFAULT_COMPACT_THRESHOLD: f32 = 0.8;
 pub struct CompactStrategy {
     pub enabled: bool,
     pub threshold_percent: f32,
+    /// Model ID or alias (`haiku`, `sonnet`, `opus`) used for the summary.
+    /// The default follows the client's small model, so it maps to the
+    /// right ID on Bedrock, Vertex and Foundry.
     pub summary_model: String,
     pub max_summary_tokens: u32,
     /// When true, includes detailed coding information in summaries:
@@ -47,6 +473,23 @@ pub struct CompactStrategy {
     /// These are user-provided instructions for customizing the summary.
     #[serde(default)]
     pub custom_instructions: Option<String>,
+    /// Prune old tool results first; summarize only if that is not enough.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prune_tool_results: Option<ToolResultPruning>,
+    /// Replaces the built-in summarization prompt. `{conversation}` marks
+    /// where the transcript goes; without it the transcript is appended.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_template: Option<String>,
+    /// Details the summary must keep verbatim, e.g. "ticket IDs (PROJ-123)"
+    /// or "file paths".
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub preserve: Vec<String>,
+    /// Elide the middle of the conversation instead of summarizing it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sliding_window: Option<SlidingWindow>,
+    /// Check the summary for open todos, the active plan and files in use.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify: Option<CompactVerification>,
 }
 
 fn default_keep_coding_instructions() -> bool {
@@ -62,6 +505,11 @@ impl Default for CompactStrategy {
             max_summary_tokens: 4000,
             keep_coding_instructions: true,
             custom_instructions: None,
+            prune_tool_results: None,
+            prompt_template: None,
+            preserve: Vec::new(),
+            sliding_window: None,
+            verify: None,
         }
     }
 }
@@ -98,6 +546,51 @@ impl CompactStrategy {
         self
     }
 
+    pub fn max_summary_tokens(mut self, tokens: u32) -> Self {
+        self.max_summary_tokens = tokens;
+        self
+    }
+
+    /// Use `template` instead of the built-in prompt; see [`CONVERSATION_PLACEHOLDER`].
+    pub fn prompt_template(mut self, template: impl Into<String>) -> Self {
+        self.prompt_template = Some(template.into());
+        self
+    }
+
+    /// Add a detail the summary must preserve. Can be called repeatedly.
+    pub fn preserve(mut self, hint: impl Into<String>) -> Self {
+        self.preserve.push(hint.into());
+        self
+    }
+
+    /// Compact by keeping the first `keep_first` and last `keep_last`
+    /// messages, without calling the model.
+    pub fn sliding_window(mut self, keep_first: usize, keep_last: usize) -> Self {
+        self.sliding_window = Some(SlidingWindow::new(keep_first, keep_last));
+        self
+    }
+
+    /// Verify summaries and retry when they drop open work.
+    pub fn verify(mut self, verification: CompactVerification) -> Self {
+        self.verify = Some(verification);
+        self
+    }
+
+    /// Model ID to request the summary from.
+    pub fn resolve_model<'a>(&'a self, models: &'a ModelConfig) -> &'a str {
+        if self.summary_model == DEFAULT_SMALL_MODEL {
+            models.get(ModelType::Small)
+        } else {
+            models.resolve_alias(&self.summary_model)
+        }
+    }
+
+    /// Prune old tool results before falling back to a summary.
+    pub fn prune_tool_results(mut self, pruning: ToolResultPruning) -> Self {
+        self.prune_tool_results = Some(pruning);
+        self
+    }
+
     /// Create a CompactStrategy that inherits coding instruction preference from OutputStyle.
     #[cfg(feature = "cli-integration")]
     pub fn from_output_style(style: &crate::output_style::OutputStyle) -> Self {
@@ -125,6 +618,21 @@ impl CompactExecutor {
         current_tokens >= threshold
     }
 
+    /// Apply the strategy's tool result pruning, if configured.
+    ///
+    /// Lowers `current_input_tokens` by the estimated savings so a following
+    /// [`needs_compact`](Self::needs_compact) check reflects the pruned context.
+    pub fn prune(&self, session: &mut Session) -> PrunedToolResults {
+        let Some(pruning) = self.strategy.prune_tool_results.as_ref() else {
+            return PrunedToolResults::default();
+        };
+        let pruned = pruning.apply(session);
+        session.current_input_tokens = session
+            .current_input_tokens
+            .saturating_sub(pruned.saved_tokens as u64);
+        pruned
+    }
+
     pub fn prepare_compact(&self, session: &Session) -> SessionResult<PreparedCompact> {
         if !self.strategy.enabled {
             return Err(SessionError::Compact {
@@ -137,7 +645,7 @@ impl CompactExecutor {
             return Ok(PreparedCompact::NotNeeded);
         }
 
-        let summary_prompt = self.format_for_summary(&messages);
+        let summary_prompt = self.summary_prompt(&messages);
 
         Ok(PreparedCompact::Ready {
             summary_prompt,
@@ -172,7 +680,7 @@ impl CompactExecutor {
         session.messages = vec![summary_msg];
         session.current_leaf_id = new_leaf_id;
         session.summary = Some(summary.clone());
-        session.updated_at = chrono::Utc::now();
+        session.updated_at = clock::now_utc();
 
         CompactResult::Compacted {
             original_count,
@@ -198,26 +706,52 @@ impl CompactExecutor {
         }
     }
 
-    fn format_for_summary(&self, messages: &[&SessionMessage]) -> String {
-        let mut formatted = String::new();
+    /// Build the summarization prompt for `messages`.
+    pub fn summary_prompt(&self, messages: &[&SessionMessage]) -> String {
+        let mut instructions = String::new();
+        if let Some(ref custom) = self.strategy.custom_instructions {
+            instructions.push_str("\n\n# Custom Summary Instructions\n\n");
+            instructions.push_str(custom);
+        }
+        if !self.strategy.preserve.is_empty() {
+            instructions.push_str(
+                "\n\n# Must Preserve\n\nThe summary must retain the following verbatim wherever they appear:\n",
+            );
+            for hint in &self.strategy.preserve {
+                instructions.push_str("- ");
+                instructions.push_str(hint);
+                instructions.push('\n');
+            }
+        }
+
+        let conversation = Self::format_conversation(messages);
+
+        if let Some(ref template) = self.strategy.prompt_template
+            && template.contains(CONVERSATION_PLACEHOLDER)
+        {
+            let mut formatted = template.replace(CONVERSATION_PLACEHOLDER, &conversation);
+            formatted.push_str(&instructions);
+            return formatted;
+        }
 
         // Select prompt based on keep_coding_instructions flag
-        let prompt = if self.strategy.keep_coding_instructions {
-            COMPACTION_PROMPT_FULL
-        } else {
-            COMPACTION_PROMPT_MINIMAL
+        let prompt = match self.strategy.prompt_template {
+            Some(ref template) => template.as_str(),
+            None if self.strategy.keep_coding_instructions => COMPACTION_PROMPT_FULL,
+            None => COMPACTION_PROMPT_MINIMAL,
         };
 
+        let mut formatted = String::with_capacity(prompt.len() + conversation.len() + 64);
         formatted.push_str(prompt);
-
-        if let Some(ref instructions) = self.strategy.custom_instructions {
-            formatted.push_str("\n\n");
-            formatted.push_str("# Custom Summary Instructions\n\n");
-            formatted.push_str(instructions);
-        }
-
+        formatted.push_str(&instructions);
         formatted.push_str("\n\n---\n\n");
         formatted.push_str("# Conversation to summarize:\n\n");
+        formatted.push_str(&conversation);
+        formatted
+    }
+
+    fn format_conversation(messages: &[&SessionMessage]) -> String {
+        let mut formatted = String::new();
 
         for msg in messages {
             let role = match msg.role {
@@ -395,6 +929,15 @@ Here's an example of how your output should be structured:
 
 Please provide a focused summary based on the conversation so far."#;
 
+/// The `<summary>` section of a model response, or the whole text if absent.
+pub(crate) fn extract_summary(response: &str) -> &str {
+    response
+        .split_once("<summary>")
+        .and_then(|(_, rest)| rest.split_once("</summary>"))
+        .map(|(summary, _)| summary.trim())
+        .unwrap_or(response)
+}
+
 #[derive(Debug)]
 pub enum PreparedCompact {
     NotNeeded,
@@ -593,4 +1136,320 @@ mod tests {
         assert!(COMPACTION_PROMPT_FULL.contains("<summary>"));
         assert!(COMPACTION_PROMPT_MINIMAL.contains("<summary>"));
     }
+
+    fn tool_session(outputs: &[&str]) -> Session {
+        use crate::types::{ToolResultBlock, ToolUseBlock};
+
+        let mut session = Session::new(SessionConfig::default());
+        for (i, output) in outputs.iter().enumerate() {
+            session.add_message(SessionMessage::assistant(vec![ContentBlock::ToolUse(
+                ToolUseBlock {
+                    id: format!("toolu_{i}"),
+                    name: "Read".into(),
+                    input: serde_json::json!({}),
+                },
+            )]));
+            session.add_message(SessionMessage::user(vec![ContentBlock::ToolResult(
+                ToolResultBlock {
+                    tool_use_id: format!("toolu_{i}"),
+                    content: Some(ToolResultContent::Text(output.to_string())),
+                    is_error: None,
+                },
+            )]));
+        }
+        session
+    }
+
+    fn result_text(session: &Session, index: usize) -> String {
+        let result = session
+            .messages
+            .iter()
+            .flat_map(|m| &m.content)
+            .filter_map(|b| match b {
+                ContentBlock::ToolResult(r) => Some(r),
+                _ => None,
+            })
+            .nth(index)
+            .unwrap();
+        match result.content.as_ref().unwrap() {
+            ToolResultContent::Text(text) => text.clone(),
+            ToolResultContent::Blocks(_) => panic!("Expected text"),
+        }
+    }
+
+    #[test]
+    fn test_prune_tool_results() {
+        let big = "x".repeat(10_000);
+        let mut session = tool_session(&[&big, "short", &big, &big]);
+        session.current_input_tokens = 50_000;
+
+        let executor = CompactExecutor::new(
+            CompactStrategy::default()
+                .prune_tool_results(ToolResultPruning::new().keep_recent(1).digest_chars(10)),
+        );
+        let pruned = executor.prune(&mut session);
+
+        assert_eq!(pruned.count, 2);
+        assert!(pruned.saved_tokens > 4_000);
+        assert_eq!(
+            session.current_input_tokens,
+            50_000 - pruned.saved_tokens as u64
+        );
+        let digest = result_text(&session, 0);
+        assert!(digest.starts_with("[Tool result pruned: Read, 10000 chars]"));
+        assert!(digest.ends_with("xxxxxxxxxx..."));
+        assert_eq!(result_text(&session, 1), "short");
+        assert_eq!(result_text(&session, 3).len(), 10_000);
+
+        // Already-pruned results are left alone.
+        assert_eq!(executor.prune(&mut session).count, 0);
+        assert_eq!(
+            CompactExecutor::new(CompactStrategy::default())
+                .prune(&mut session)
+                .count,
+            0
+        );
+    }
+
+    #[test]
+    fn test_prompt_template_and_preserve() {
+        let session = create_test_session(2);
+        let messages: Vec<_> = session.messages.iter().collect();
+
+        let executor = CompactExecutor::new(
+            CompactStrategy::default()
+                .prompt_template("Summarize for the on-call engineer:\n{conversation}\nEnd.")
+                .preserve("ticket IDs (OPS-123)")
+                .preserve("file paths"),
+        );
+        let prompt = executor.summary_prompt(&messages);
+        assert!(prompt.starts_with("Summarize for the on-call engineer:\n**Human**:"));
+        assert!(prompt.contains("Assistant response 1\n\n\nEnd."));
+        assert!(prompt.contains("# Must Preserve"));
+        assert!(prompt.contains("- ticket IDs (OPS-123)\n- file paths"));
+        assert!(!prompt.contains("<analysis>"));
+
+        // Without the placeholder the transcript is appended.
+        let executor =
+            CompactExecutor::new(CompactStrategy::default().prompt_template("Be brief."));
+        let prompt = executor.summary_prompt(&messages);
+        assert!(prompt.starts_with("Be brief.\n\n---\n\n# Conversation to summarize:"));
+    }
+
+    #[test]
+    fn test_resolve_model() {
+        let models = ModelConfig::anthropic()
+            .small("provider-small")
+            .primary("provider-main");
+        assert_eq!(
+            CompactStrategy::default().resolve_model(&models),
+            "provider-small"
+        );
+        assert_eq!(
+            CompactStrategy::default()
+                .model("sonnet")
+                .resolve_model(&models),
+            "provider-main"
+        );
+        assert_eq!(
+            CompactStrategy::default()
+                .model("claude-opus-4-5")
+                .resolve_model(&models),
+            "claude-opus-4-5"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_session_compact_with_strategy() {
+        use crate::client::MockAdapter;
+
+        let adapter = MockAdapter::new()
+            .text("<analysis>notes</analysis>\n<summary>\nWorked on OPS-42.\n</summary>");
+        let client = crate::Client::new(adapter.clone()).unwrap();
+        let mut session = create_test_session(6);
+
+        let strategy = CompactStrategy::default()
+            .model("claude-opus-4-5")
+            .max_summary_tokens(1234)
+            .preserve("ticket IDs");
+        let result = session
+            .compact_with(&client, 2, Some(&strategy))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            result,
+            CompactResult::Compacted {
+                original_count: 6,
+                new_count: 3,
+                ..
+            }
+        ));
+        assert_eq!(session.summary.as_deref(), Some("Worked on OPS-42."));
+        let requests = adapter.requests();
+        assert_eq!(requests[0].model, "claude-opus-4-5");
+        assert_eq!(requests[0].max_tokens, 1234);
+        assert!(requests[0].messages[0].text().contains("- ticket IDs"));
+    }
+
+    #[tokio::test]
+    async fn test_session_compact_without_strategy() {
+        use crate::client::{MockAdapter, ModelType};
+
+        let adapter = MockAdapter::new().text("Short summary");
+        let client = crate::Client::new(adapter.clone()).unwrap();
+        let mut session = create_test_session(6);
+
+        session.compact(&client, 2).await.unwrap();
+
+        assert_eq!(session.summary.as_deref(), Some("Short summary"));
+        let requests = adapter.requests();
+        assert_eq!(requests[0].model, client.adapter().model(ModelType::Small));
+        assert_eq!(requests[0].max_tokens, 2000);
+        assert!(
+            requests[0].messages[0]
+                .text()
+                .starts_with("Summarize this conversation concisely.")
+        );
+    }
+
+    #[test]
+    fn test_extract_summary() {
+        assert_eq!(
+            extract_summary("<analysis>x</analysis><summary> kept </summary>"),
+            "kept"
+        );
+        assert_eq!(extract_summary("plain summary"), "plain summary");
+    }
+
+    #[tokio::test]
+    async fn test_sliding_window() {
+        use crate::client::MockAdapter;
+
+        // 3 tool round trips: (use, result) pairs at indices 0..6.
+        let big = "x".repeat(400);
+        let mut session = tool_session(&[&big, &big, &big]);
+        session.add_message(SessionMessage::assistant(vec![ContentBlock::text("done")]));
+        let first_id = session.messages[0].id.clone();
+
+        // The head would end on a tool_use and the tail start on a
+        // tool_result; both cut points widen to keep the pairs together.
+        let adapter = MockAdapter::new();
+        let client = crate::Client::new(adapter.clone()).unwrap();
+        let strategy = CompactStrategy::default().sliding_window(1, 2);
+        let result = session
+            .compact_with(&client, 4, Some(&strategy))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            result,
+            CompactResult::Compacted {
+                original_count: 7,
+                new_count: 6,
+                saved_tokens: 0,
+                ..
+            }
+        ));
+        assert!(adapter.requests().is_empty());
+        assert_eq!(session.messages[0].id, first_id);
+        assert!(session.messages[2].is_compact_summary);
+        assert_eq!(
+            session.messages[2].content[0].as_text(),
+            Some("[2 earlier messages elided]")
+        );
+        assert!(has_tool_use(&session.messages[3]));
+        assert_eq!(session.current_branch().len(), 6);
+        assert_eq!(session.compact_history.len(), 1);
+
+        assert!(matches!(
+            SlidingWindow::new(3, 3).apply(&mut session),
+            CompactResult::NotNeeded
+        ));
+    }
+
+    fn session_with_open_work() -> Session {
+        use crate::session::types::{Plan, TodoItem};
+        use crate::types::ToolUseBlock;
+
+        let mut session = Session::new(SessionConfig::default());
+        let read = |id: &str| {
+            SessionMessage::assistant(vec![ContentBlock::ToolUse(ToolUseBlock {
+                id: id.into(),
+                name: "Read".into(),
+                input: serde_json::json!({"file_path": "/repo/src/billing/invoice.rs"}),
+            })])
+        };
+        session.add_message(SessionMessage::user(vec![ContentBlock::text(
+            "Fix invoices",
+        )]));
+        session.add_message(read("toolu_1"));
+        session.add_message(SessionMessage::user(vec![ContentBlock::text("Continue")]));
+        session.add_message(read("toolu_2"));
+
+        session.todos.push(TodoItem::new(
+            session.id,
+            "Add rounding regression test",
+            "Adding test",
+        ));
+        let mut done = TodoItem::new(session.id, "Reproduce bug", "Reproducing");
+        done.status = TodoStatus::Completed;
+        session.todos.push(done);
+        let mut plan = Plan::new(session.id);
+        plan.content = "## Switch invoice totals to Decimal\n1. ...".into();
+        session.current_plan = Some(plan);
+        session
+    }
+
+    #[test]
+    fn test_required_mentions() {
+        let session = session_with_open_work();
+        let required = RequiredMention::collect(&session, 2);
+        assert_eq!(
+            required,
+            vec![
+                RequiredMention::Todo("Add rounding regression test".into()),
+                RequiredMention::Plan("Switch invoice totals to Decimal".into()),
+                RequiredMention::File("/repo/src/billing/invoice.rs".into()),
+            ]
+        );
+
+        assert!(required[0].is_mentioned("Next: add a regression test for rounding."));
+        assert!(!required[0].is_mentioned("Fixed the invoice bug."));
+        assert!(required[1].is_mentioned("Plan: switch totals to decimal"));
+        assert!(required[2].is_mentioned("Edited invoice.rs"));
+        assert!(RequiredMention::collect(&session, 4).len() == 2);
+    }
+
+    #[tokio::test]
+    async fn test_compact_verification_retries() {
+        use crate::client::{MockAdapter, ModelConfig, ProviderConfig};
+
+        let config = ProviderConfig::new(ModelConfig::anthropic().primary("strong-model"));
+        let adapter = MockAdapter::from_config(config)
+            .text("<summary>Worked on invoices.</summary>")
+            .text("<summary>Plan: switch invoice totals to Decimal in invoice.rs.</summary>");
+        let client = crate::Client::new(adapter.clone()).unwrap();
+        let mut session = session_with_open_work();
+
+        let strategy = CompactStrategy::default().verify(CompactVerification::new());
+        session
+            .compact_with(&client, 2, Some(&strategy))
+            .await
+            .unwrap();
+
+        let requests = adapter.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].model, "strong-model");
+        let retry_prompt = requests[1].messages[0].text();
+        assert!(retry_prompt.contains("- Open todo: Add rounding regression test"));
+        assert!(retry_prompt.contains("- File: /repo/src/billing/invoice.rs"));
+
+        // The retry still dropped the todo; it is carried over verbatim.
+        let summary = session.summary.as_deref().unwrap();
+        assert!(summary.starts_with("Plan: switch invoice totals"));
+        assert!(summary.ends_with(
+            "Still relevant (not covered above):\n- Open todo: Add rounding regression test\n"
+        ));
+    }
 }
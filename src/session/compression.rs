@@ -0,0 +1,209 @@
+//! Transparent compression of stored message content.
+//!
+//! Backends that store messages row by row can compress large content
+//! payloads with zstd. Compressed content is stored as a base64 string in
+//! place of the usual JSON array and flagged with
+//! `"content_encoding": "zstd"` in the message metadata, so rows written
+//! before compression was enabled (or below the size threshold) load
+//! unchanged. Reading compressed rows requires the `compression` feature.
+
+use serde_json::Value;
+
+use super::{SessionError, SessionResult};
+use crate::types::ContentBlock;
+
+/// Metadata key flagging how the content column is encoded.
+pub const CONTENT_ENCODING_KEY: &str = "content_encoding";
+/// Encoding name for zstd-compressed, base64-encoded content.
+pub const ZSTD_ENCODING: &str = "zstd";
+
+const DEFAULT_LEVEL: i32 = 3;
+const DEFAULT_MIN_SIZE: usize = 4096;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CompressionConfig {
+    /// zstd level (1-22, default: 3).
+    pub level: i32,
+    /// Serialized content smaller than this is stored as plain JSON (default: 4 KiB).
+    pub min_size: usize,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            level: DEFAULT_LEVEL,
+            min_size: DEFAULT_MIN_SIZE,
+        }
+    }
+}
+
+impl CompressionConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn level(mut self, level: i32) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn min_size(mut self, bytes: usize) -> Self {
+        self.min_size = bytes;
+        self
+    }
+}
+
+/// Serialize `content` for storage, compressing it when `config` is set and
+/// the payload is large enough to benefit.
+///
+/// Returns the value to store and the encoding to record in metadata
+/// (`None` for plain JSON).
+pub fn encode_content(
+    content: &[ContentBlock],
+    config: Option<&CompressionConfig>,
+) -> SessionResult<(Value, Option<&'static str>)> {
+    let Some(config) = config else {
+        return Ok((serde_json::to_value(content)?, None));
+    };
+    let json = serde_json::to_vec(content)?;
+    if json.len() < config.min_size {
+        return Ok((serde_json::from_slice(&json)?, None));
+    }
+    let compressed = zstd_compress(&json, config.level)?;
+    // Base64 adds a third; only keep the compressed form if it still wins.
+    if compressed.len() * 4 / 3 >= json.len() {
+        return Ok((serde_json::from_slice(&json)?, None));
+    }
+    Ok((
+        Value::String(base64_encode(&compressed)),
+        Some(ZSTD_ENCODING),
+    ))
+}
+
+/// Inverse of [`encode_content`]. `encoding` is the value recorded in metadata.
+pub fn decode_content(value: Value, encoding: Option<&str>) -> SessionResult<Vec<ContentBlock>> {
+    match encoding {
+        None => Ok(serde_json::from_value(value)?),
+        Some(ZSTD_ENCODING) => {
+            let Value::String(encoded) = value else {
+                return Err(SessionError::Storage {
+                    message: "zstd-encoded content is not a string".into(),
+                });
+            };
+            let compressed = base64_decode(&encoded)?;
+            Ok(serde_json::from_slice(&zstd_decompress(&compressed)?)?)
+        }
+        Some(other) => Err(SessionError::Storage {
+            message: format!("Unknown content encoding '{}'", other),
+        }),
+    }
+}
+
+/// The content encoding recorded in stored message metadata, if any.
+pub fn content_encoding(metadata: &Value) -> Option<&str> {
+    metadata.get(CONTENT_ENCODING_KEY).and_then(Value::as_str)
+}
+
+/// Record `encoding` in stored message metadata (no-op for plain content).
+pub fn set_content_encoding(metadata: &mut Value, encoding: Option<&str>) {
+    if let (Some(encoding), Value::Object(map)) = (encoding, metadata) {
+        map.insert(CONTENT_ENCODING_KEY.into(), Value::String(encoding.into()));
+    }
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    use base64::Engine;
+    base64::prelude::BASE64_STANDARD.encode(bytes)
+}
+
+fn base64_decode(encoded: &str) -> SessionResult<Vec<u8>> {
+    use base64::Engine;
+    base64::prelude::BASE64_STANDARD
+        .decode(encoded)
+        .map_err(|e| SessionError::Storage {
+            message: format!("Invalid compressed content: {}", e),
+        })
+}
+
+#[cfg(feature = "compression")]
+fn zstd_compress(bytes: &[u8], level: i32) -> SessionResult<Vec<u8>> {
+    zstd::encode_all(bytes, level).map_err(|e| SessionError::Storage {
+        message: format!("Failed to compress content: {}", e),
+    })
+}
+
+#[cfg(feature = "compression")]
+fn zstd_decompress(bytes: &[u8]) -> SessionResult<Vec<u8>> {
+    zstd::decode_all(bytes).map_err(|e| SessionError::Storage {
+        message: format!("Failed to decompress content: {}", e),
+    })
+}
+
+#[cfg(not(feature = "compression"))]
+fn zstd_compress(_bytes: &[u8], _level: i32) -> SessionResult<Vec<u8>> {
+    Err(feature_disabled())
+}
+
+#[cfg(not(feature = "compression"))]
+fn zstd_decompress(_bytes: &[u8]) -> SessionResult<Vec<u8>> {
+    Err(feature_disabled())
+}
+
+#[cfg(not(feature = "compression"))]
+fn feature_disabled() -> SessionError {
+    SessionError::Storage {
+        message: "zstd content compression requires the `compression` feature".into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn large_content() -> Vec<ContentBlock> {
+        vec![ContentBlock::text("cargo test output\n".repeat(1000))]
+    }
+
+    #[test]
+    fn test_small_or_unconfigured_stays_plain() {
+        let content = vec![ContentBlock::text("hi")];
+        let (value, encoding) =
+            encode_content(&content, Some(&CompressionConfig::default())).unwrap();
+        assert!(encoding.is_none());
+        assert!(value.is_array());
+
+        let (value, encoding) = encode_content(&large_content(), None).unwrap();
+        assert!(encoding.is_none());
+        assert_eq!(decode_content(value, None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_legacy_metadata_has_no_encoding() {
+        let mut metadata = serde_json::json!({"model": "claude"});
+        assert_eq!(content_encoding(&metadata), None);
+        set_content_encoding(&mut metadata, None);
+        assert_eq!(content_encoding(&metadata), None);
+        set_content_encoding(&mut metadata, Some(ZSTD_ENCODING));
+        assert_eq!(content_encoding(&metadata), Some(ZSTD_ENCODING));
+    }
+
+    #[test]
+    fn test_unknown_encoding_rejected() {
+        assert!(decode_content(Value::String(String::new()), Some("lz4")).is_err());
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_roundtrip_compressed() {
+        let content = large_content();
+        let plain = serde_json::to_vec(&content).unwrap().len();
+        let (value, encoding) =
+            encode_content(&content, Some(&CompressionConfig::new().level(1))).unwrap();
+        assert_eq!(encoding, Some(ZSTD_ENCODING));
+        assert!(value.as_str().unwrap().len() < plain / 10);
+
+        let decoded = decode_content(value, encoding).unwrap();
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].as_text(), content[0].as_text());
+    }
+}
@@ -0,0 +1,208 @@
+//! Live session lifecycle events.
+//!
+//! Every write through a [`SessionManager`](super::SessionManager) (and its
+//! clones) is published on an in-process broadcast channel;
+//! [`subscribe`](super::SessionManager::subscribe) yields the events of one
+//! session. Changes are derived by comparing the stored session with the new
+//! one, which costs an extra load per save only while someone is subscribed.
+//! To follow a session driven by another process, forward that process's
+//! events (e.g. over Redis pub/sub) into
+//! [`publish`](super::SessionManager::publish).
+
+use std::collections::HashSet;
+
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use super::state::{MessageId, Session, SessionId, SessionState};
+use super::types::{CompactRecord, TodoItem};
+use crate::types::Role;
+
+/// Events buffered per subscriber before the slowest one starts missing events.
+pub(crate) const EVENT_CAPACITY: usize = 256;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SessionEvent {
+    Created {
+        session_id: SessionId,
+    },
+    MessageAdded {
+        session_id: SessionId,
+        message_id: MessageId,
+        role: Role,
+    },
+    StateChanged {
+        session_id: SessionId,
+        from: SessionState,
+        to: SessionState,
+    },
+    Compacted {
+        session_id: SessionId,
+        record: CompactRecord,
+    },
+    TodosUpdated {
+        session_id: SessionId,
+        todos: Vec<TodoItem>,
+    },
+    Deleted {
+        session_id: SessionId,
+    },
+}
+
+impl SessionEvent {
+    pub fn session_id(&self) -> SessionId {
+        match self {
+            Self::Created { session_id }
+            | Self::MessageAdded { session_id, .. }
+            | Self::StateChanged { session_id, .. }
+            | Self::Compacted { session_id, .. }
+            | Self::TodosUpdated { session_id, .. }
+            | Self::Deleted { session_id } => *session_id,
+        }
+    }
+
+    /// Events describing the change from `before` (`None`: new session) to `after`.
+    pub fn diff(before: Option<&Session>, after: &Session) -> Vec<Self> {
+        let session_id = after.id;
+        let Some(before) = before else {
+            let mut events = vec![Self::Created { session_id }];
+            events.extend(after.messages.iter().map(|m| Self::MessageAdded {
+                session_id,
+                message_id: m.id.clone(),
+                role: m.role,
+            }));
+            return events;
+        };
+
+        let mut events = Vec::new();
+        let known: HashSet<&MessageId> = before.messages.iter().map(|m| &m.id).collect();
+        events.extend(
+            after
+                .messages
+                .iter()
+                .filter(|m| !known.contains(&m.id))
+                .map(|m| Self::MessageAdded {
+                    session_id,
+                    message_id: m.id.clone(),
+                    role: m.role,
+                }),
+        );
+        if before.state != after.state {
+            events.push(Self::StateChanged {
+                session_id,
+                from: before.state,
+                to: after.state,
+            });
+        }
+        let known: HashSet<_> = before.compact_history.iter().map(|r| r.id).collect();
+        events.extend(
+            after
+                .compact_history
+                .iter()
+                .filter(|r| !known.contains(&r.id))
+                .map(|record| Self::Compacted {
+                    session_id,
+                    record: record.clone(),
+                }),
+        );
+        let todo_key = |t: &TodoItem| (t.id, t.status, t.content.clone());
+        if !before
+            .todos
+            .iter()
+            .map(todo_key)
+            .eq(after.todos.iter().map(todo_key))
+        {
+            events.push(Self::TodosUpdated {
+                session_id,
+                todos: after.todos.clone(),
+            });
+        }
+        events
+    }
+}
+
+/// Events of a single session. Dropping it unsubscribes.
+pub struct SessionSubscription {
+    session_id: SessionId,
+    receiver: broadcast::Receiver<SessionEvent>,
+}
+
+impl SessionSubscription {
+    pub(crate) fn new(session_id: SessionId, receiver: broadcast::Receiver<SessionEvent>) -> Self {
+        Self {
+            session_id,
+            receiver,
+        }
+    }
+
+    pub fn session_id(&self) -> SessionId {
+        self.session_id
+    }
+
+    /// Next event for this session; `None` once every manager is dropped.
+    ///
+    /// A subscriber that falls more than 256 events behind skips the
+    /// missed events (logged) and continues with the oldest retained one.
+    pub async fn recv(&mut self) -> Option<SessionEvent> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) if event.session_id() == self.session_id => return Some(event),
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    tracing::warn!(session_id = %self.session_id, missed, "Session subscriber lagged");
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    pub fn into_stream(self) -> impl Stream<Item = SessionEvent> + Send {
+        futures::stream::unfold(self, |mut sub| async move {
+            sub.recv().await.map(|event| (event, sub))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::state::{SessionConfig, SessionMessage};
+    use crate::session::types::TodoStatus;
+    use crate::types::ContentBlock;
+
+    #[test]
+    fn test_diff() {
+        let mut before = Session::new(SessionConfig::default());
+        before.add_message(SessionMessage::user(vec![ContentBlock::text("hi")]));
+        before
+            .todos
+            .push(TodoItem::new(before.id, "Write tests", "Writing tests"));
+
+        let mut after = before.clone();
+        after.add_message(SessionMessage::assistant(vec![ContentBlock::text("hello")]));
+        after.set_state(SessionState::Active);
+        after.todos[0].status = TodoStatus::Completed;
+
+        let events = SessionEvent::diff(Some(&before), &after);
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            &events[0],
+            SessionEvent::MessageAdded { role: Role::Assistant, message_id, .. }
+                if Some(message_id) == after.current_leaf_id.as_ref()
+        ));
+        assert!(matches!(
+            events[1],
+            SessionEvent::StateChanged {
+                from: SessionState::Created,
+                to: SessionState::Active,
+                ..
+            }
+        ));
+        assert!(matches!(&events[2], SessionEvent::TodosUpdated { todos, .. } if todos.len() == 1));
+
+        assert!(SessionEvent::diff(Some(&after), &after).is_empty());
+        assert_eq!(SessionEvent::diff(None, &after).len(), 3);
+    }
+}
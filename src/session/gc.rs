@@ -0,0 +1,188 @@
+//! Background collection of expired sessions.
+//!
+//! Sessions past `expires_at` are only removed when something deletes them.
+//! [`SessionManager::start_gc`](super::SessionManager::start_gc) runs
+//! [`collect_expired`](super::SessionManager::collect_expired) on an interval:
+//! each expired session is handed to the archive hook (if any) as a
+//! [`SessionArchive`] and deleted once the hook succeeds. Sessions whose
+//! archival fails are kept and retried on the next run.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use super::SessionResult;
+use super::archive::SessionArchive;
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Receives each expired session before it is deleted.
+pub type ArchiveHook =
+    Arc<dyn Fn(SessionArchive) -> BoxFuture<'static, SessionResult<()>> + Send + Sync>;
+
+#[derive(Clone)]
+pub struct GcConfig {
+    pub interval: Duration,
+    pub(crate) archive: Option<ArchiveHook>,
+}
+
+impl Default for GcConfig {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_INTERVAL,
+            archive: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for GcConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GcConfig")
+            .field("interval", &self.interval)
+            .field("archive", &self.archive.is_some())
+            .finish()
+    }
+}
+
+impl GcConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time between scans (default: 5 minutes).
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Archive expired sessions before deletion. Returning an error keeps the
+    /// session for the next run.
+    pub fn on_archive<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn(SessionArchive) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = SessionResult<()>> + Send + 'static,
+    {
+        self.archive = Some(Arc::new(move |archive| Box::pin(hook(archive))));
+        self
+    }
+}
+
+/// Outcome of one collection run.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GcReport {
+    pub scanned: usize,
+    pub archived: usize,
+    pub deleted: usize,
+    /// Sessions kept because archival or deletion failed.
+    pub failed: usize,
+}
+
+/// Running garbage collector. Stops when dropped.
+pub struct GcHandle {
+    token: CancellationToken,
+    task: Option<JoinHandle<()>>,
+}
+
+impl GcHandle {
+    pub(crate) fn new(token: CancellationToken, task: JoinHandle<()>) -> Self {
+        Self {
+            token,
+            task: Some(task),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.task.as_ref().is_some_and(|t| !t.is_finished())
+    }
+
+    /// Stop after the current run (if any) and wait for the task to exit.
+    pub async fn stop(mut self) {
+        self.token.cancel();
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
+    }
+}
+
+impl Drop for GcHandle {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::state::SessionConfig;
+    use crate::session::{SessionError, SessionManager};
+    use std::sync::Mutex;
+
+    fn expiring() -> SessionConfig {
+        SessionConfig {
+            ttl_secs: Some(0),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_collect_archives_then_deletes() {
+        let manager = SessionManager::in_memory();
+        let expired = manager.create(expiring()).await.unwrap();
+        let live = manager.create(SessionConfig::default()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let archived = Arc::new(Mutex::new(Vec::new()));
+        let sink = archived.clone();
+        let config = GcConfig::new().on_archive(move |archive| {
+            sink.lock().unwrap().push(archive.session.id);
+            async { Ok(()) }
+        });
+
+        let report = manager.collect_expired(&config).await.unwrap();
+        assert_eq!(
+            report,
+            GcReport {
+                scanned: 2,
+                archived: 1,
+                deleted: 1,
+                failed: 0
+            }
+        );
+        assert_eq!(*archived.lock().unwrap(), vec![expired.id]);
+        assert_eq!(manager.list().await.unwrap(), vec![live.id]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_archive_keeps_session() {
+        let manager = SessionManager::in_memory();
+        manager.create(expiring()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let config = GcConfig::new().on_archive(|_| async {
+            Err(SessionError::Storage {
+                message: "bucket unavailable".into(),
+            })
+        });
+        let report = manager.collect_expired(&config).await.unwrap();
+        assert_eq!(report.failed, 1);
+        assert_eq!(report.deleted, 0);
+        assert_eq!(manager.list().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_background_task() {
+        let manager = SessionManager::in_memory();
+        manager.create(expiring()).await.unwrap();
+
+        let handle = manager.start_gc(GcConfig::new().interval(Duration::from_millis(5)));
+        assert!(handle.is_running());
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(manager.list().await.unwrap().is_empty());
+
+        handle.stop().await;
+    }
+}
@@ -2,17 +2,82 @@
 
 use std::sync::Arc;
 
+use tokio::sync::broadcast;
+
+use super::access::{AccessPolicy, OwnerPolicy, Principal, SessionAccess};
+use super::analytics::{SessionStatsReport, StatsQuery};
+use super::archive::SessionArchive;
+use super::events::{EVENT_CAPACITY, SessionEvent, SessionSubscription};
+use super::gc::{GcConfig, GcHandle, GcReport};
 use super::persistence::{MemoryPersistence, Persistence};
-use super::state::{Session, SessionConfig, SessionId, SessionMessage, SessionState};
+use super::search::{self, SearchHit, SearchIndex, SearchQuery};
+use super::state::{
+    BranchInfo, DiffSince, MAX_SNAPSHOTS, MessageId, Session, SessionConfig, SessionDelta,
+    SessionId, SessionMessage, SessionSnapshot, SessionState,
+};
 use super::{SessionError, SessionResult};
 
+#[derive(Clone)]
 pub struct SessionManager {
     persistence: Arc<dyn Persistence>,
+    search_index: Option<Arc<dyn SearchIndex>>,
+    events: broadcast::Sender<SessionEvent>,
+    access_policy: Arc<dyn AccessPolicy>,
+    principal: Option<Principal>,
 }
 
 impl SessionManager {
     pub fn new(persistence: Arc<dyn Persistence>) -> Self {
-        Self { persistence }
+        Self {
+            persistence,
+            search_index: None,
+            events: broadcast::channel(EVENT_CAPACITY).0,
+            access_policy: Arc::new(OwnerPolicy::default()),
+            principal: None,
+        }
+    }
+
+    /// Policy consulted by managers scoped with [`for_principal`](Self::for_principal)
+    /// (default: [`OwnerPolicy`]).
+    pub fn with_access_policy(mut self, policy: Arc<dyn AccessPolicy>) -> Self {
+        self.access_policy = policy;
+        self
+    }
+
+    /// A manager sharing this one's storage and events that acts for
+    /// `principal`: every operation is checked against the access policy and
+    /// new sessions are owned by the principal.
+    pub fn for_principal(&self, principal: Principal) -> Self {
+        Self {
+            principal: Some(principal),
+            ..self.clone()
+        }
+    }
+
+    pub fn principal(&self) -> Option<&Principal> {
+        self.principal.as_ref()
+    }
+
+    /// Fail with [`SessionError::AccessDenied`] unless the principal may
+    /// perform `access` on `session`. Always succeeds for an unscoped manager.
+    pub fn authorize(&self, session: &Session, access: SessionAccess) -> SessionResult<()> {
+        match &self.principal {
+            Some(principal) if !self.access_policy.allows(principal, session, access) => {
+                Err(SessionError::AccessDenied {
+                    id: session.id.to_string(),
+                    principal: principal.id.clone(),
+                    access,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Keep `index` up to date with every session written through this manager
+    /// and use it for [`search`](Self::search).
+    pub fn with_search_index(mut self, index: Arc<dyn SearchIndex>) -> Self {
+        self.search_index = Some(index);
+        self
     }
 
     pub fn in_memory() -> Self {
@@ -20,8 +85,8 @@ impl SessionManager {
     }
 
     pub async fn create(&self, config: SessionConfig) -> SessionResult<Session> {
-        let session = Session::new(config);
-        self.persistence.save(&session).await?;
+        let session = self.new_session(config);
+        self.save(&session).await?;
         Ok(session)
     }
 
@@ -30,13 +95,23 @@ impl SessionManager {
         config: SessionConfig,
         tenant_id: impl Into<String>,
     ) -> SessionResult<Session> {
-        let mut session = Session::new(config);
+        let mut session = self.new_session(config);
         session.tenant_id = Some(tenant_id.into());
-        self.persistence.save(&session).await?;
+        self.save(&session).await?;
         Ok(session)
     }
 
+    fn new_session(&self, config: SessionConfig) -> Session {
+        let mut session = Session::new(config);
+        session.owner = self.principal.as_ref().map(|p| p.id.clone());
+        session
+    }
+
     pub async fn get(&self, id: &SessionId) -> SessionResult<Session> {
+        self.load_for(id, SessionAccess::Read).await
+    }
+
+    async fn load_for(&self, id: &SessionId, access: SessionAccess) -> SessionResult<Session> {
         let session = self
             .persistence
             .load(id)
@@ -44,19 +119,32 @@ impl SessionManager {
             .ok_or_else(|| SessionError::NotFound { id: id.to_string() })?;
 
         if session.is_expired() {
-            self.persistence.delete(id).await?;
+            self.remove(id).await?;
             return Err(SessionError::Expired { id: id.to_string() });
         }
 
+        self.authorize(&session, access)?;
         Ok(session)
     }
 
+    /// Check `access` against the stored copy of `id`, if there is one.
+    async fn authorize_stored(&self, id: &SessionId, access: SessionAccess) -> SessionResult<()> {
+        if self.principal.is_some()
+            && let Some(stored) = self.persistence.load(id).await?
+        {
+            self.authorize(&stored, access)?;
+        }
+        Ok(())
+    }
+
     pub async fn get_by_str(&self, id: &str) -> SessionResult<Session> {
         self.get(&SessionId::from(id)).await
     }
 
     pub async fn update(&self, session: &Session) -> SessionResult<()> {
-        self.persistence.save(session).await
+        self.authorize_stored(&session.id, SessionAccess::Append)
+            .await?;
+        self.save(session).await
     }
 
     pub async fn add_message(
@@ -64,25 +152,71 @@ impl SessionManager {
         session_id: &SessionId,
         message: SessionMessage,
     ) -> SessionResult<()> {
-        self.persistence.add_message(session_id, message).await
+        self.authorize_stored(session_id, SessionAccess::Append)
+            .await?;
+        let added = SessionEvent::MessageAdded {
+            session_id: *session_id,
+            message_id: message.id.clone(),
+            role: message.role,
+        };
+        self.persistence.add_message(session_id, message).await?;
+        self.emit(added);
+        if self.search_index.is_some()
+            && let Some(session) = self.persistence.load(session_id).await?
+        {
+            self.reindex(&session).await;
+        }
+        Ok(())
     }
 
     pub async fn delete(&self, id: &SessionId) -> SessionResult<bool> {
-        self.persistence.delete(id).await
+        self.authorize_stored(id, SessionAccess::Append).await?;
+        self.remove(id).await
     }
 
+    async fn remove(&self, id: &SessionId) -> SessionResult<bool> {
+        let deleted = self.persistence.delete(id).await?;
+        if deleted {
+            self.emit(SessionEvent::Deleted { session_id: *id });
+        }
+        if let Some(index) = &self.search_index
+            && let Err(e) = index.remove(id).await
+        {
+            tracing::warn!(session_id = %id, error = %e, "Failed to remove session from search index");
+        }
+        Ok(deleted)
+    }
+
+    /// Session IDs; a scoped manager only lists sessions its principal may read.
     pub async fn list(&self) -> SessionResult<Vec<SessionId>> {
-        self.persistence.list(None).await
+        let ids = self.persistence.list(None).await?;
+        self.readable(ids).await
     }
 
     pub async fn list_for_tenant(&self, tenant_id: &str) -> SessionResult<Vec<SessionId>> {
-        self.persistence.list(Some(tenant_id)).await
+        let ids = self.persistence.list(Some(tenant_id)).await?;
+        self.readable(ids).await
+    }
+
+    async fn readable(&self, ids: Vec<SessionId>) -> SessionResult<Vec<SessionId>> {
+        if self.principal.is_none() {
+            return Ok(ids);
+        }
+        let mut readable = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(session) = self.persistence.load(&id).await?
+                && self.authorize(&session, SessionAccess::Read).is_ok()
+            {
+                readable.push(id);
+            }
+        }
+        Ok(readable)
     }
 
     pub async fn fork(&self, id: &SessionId) -> SessionResult<Session> {
         let original = self.get(id).await?;
 
-        let mut forked = Session::new(original.config.clone());
+        let mut forked = self.new_session(original.config.clone());
         forked.parent_id = Some(original.id);
         forked.tenant_id = original.tenant_id.clone();
         forked.summary = original.summary.clone();
@@ -99,24 +233,357 @@ impl SessionManager {
             forked.current_leaf_id = Some(last.id.clone());
         }
 
-        self.persistence.save(&forked).await?;
+        self.save(&forked).await?;
         Ok(forked)
     }
 
+    pub async fn branches(&self, id: &SessionId) -> SessionResult<Vec<BranchInfo>> {
+        Ok(self.get(id).await?.branches())
+    }
+
+    /// Continue the session from `message_id` (a branch leaf or earlier message).
+    pub async fn switch_branch(&self, id: &SessionId, message_id: &MessageId) -> SessionResult<()> {
+        let mut session = self.load_for(id, SessionAccess::Append).await?;
+        if !session.switch_branch(message_id) {
+            return Err(SessionError::NotFound {
+                id: message_id.to_string(),
+            });
+        }
+        self.save(&session).await
+    }
+
+    /// Delete an abandoned branch, returning the number of removed messages.
+    pub async fn delete_branch(&self, id: &SessionId, leaf: &MessageId) -> SessionResult<usize> {
+        let mut session = self.load_for(id, SessionAccess::Append).await?;
+        let removed = session.delete_branch(leaf);
+        if removed > 0 {
+            self.save(&session).await?;
+        }
+        Ok(removed)
+    }
+
+    /// Append `summary` of the branch ending at `leaf` to the current branch.
+    pub async fn merge_branch(
+        &self,
+        id: &SessionId,
+        leaf: &MessageId,
+        summary: impl Into<String>,
+    ) -> SessionResult<()> {
+        let mut session = self.load_for(id, SessionAccess::Append).await?;
+        if !session.merge_branch(leaf, summary) {
+            return Err(SessionError::NotFound {
+                id: leaf.to_string(),
+            });
+        }
+        self.save(&session).await
+    }
+
+    /// Snapshot the stored session, returning the snapshot ID.
+    ///
+    /// The snapshot is stored as a separate record; beyond [`MAX_SNAPSHOTS`]
+    /// the oldest is deleted. Fails on backends that cannot store snapshots.
+    pub async fn snapshot(
+        &self,
+        id: &SessionId,
+        label: impl Into<String>,
+    ) -> SessionResult<uuid::Uuid> {
+        let session = self.load_for(id, SessionAccess::Append).await?;
+        let snapshot = session.snapshot_labeled(label);
+        self.persistence.save_snapshot(&snapshot).await?;
+
+        let stored = self.persistence.list_snapshots(id).await?;
+        for old in stored
+            .iter()
+            .take(stored.len().saturating_sub(MAX_SNAPSHOTS))
+        {
+            self.persistence.delete_snapshot(id, old.id).await?;
+        }
+        Ok(snapshot.id)
+    }
+
+    /// Snapshots of the stored session, oldest first.
+    pub async fn snapshots(&self, id: &SessionId) -> SessionResult<Vec<SessionSnapshot>> {
+        self.load_for(id, SessionAccess::Read).await?;
+        self.persistence.list_snapshots(id).await
+    }
+
+    /// Roll the stored session back to a snapshot in a single write. The
+    /// snapshot is kept so it can be restored again.
+    pub async fn restore(&self, id: &SessionId, snapshot_id: uuid::Uuid) -> SessionResult<Session> {
+        let mut session = self.load_for(id, SessionAccess::Append).await?;
+        let snapshot = self
+            .persistence
+            .load_snapshot(id, snapshot_id)
+            .await?
+            .ok_or_else(|| SessionError::NotFound {
+                id: snapshot_id.to_string(),
+            })?;
+        session.restore(&snapshot);
+        self.save(&session).await?;
+        Ok(session)
+    }
+
+    pub async fn drop_snapshot(
+        &self,
+        id: &SessionId,
+        snapshot_id: uuid::Uuid,
+    ) -> SessionResult<bool> {
+        self.load_for(id, SessionAccess::Append).await?;
+        self.persistence.delete_snapshot(id, snapshot_id).await
+    }
+
+    /// Changes to the stored session after `since`; see [`Session::diff`].
+    pub async fn diff(
+        &self,
+        id: &SessionId,
+        since: impl Into<DiffSince>,
+    ) -> SessionResult<Option<SessionDelta>> {
+        Ok(self.get(id).await?.diff(since))
+    }
+
     pub async fn complete(&self, id: &SessionId) -> SessionResult<()> {
-        let mut session = self.get(id).await?;
+        let mut session = self.load_for(id, SessionAccess::Append).await?;
         session.set_state(SessionState::Completed);
-        self.persistence.save(&session).await
+        self.save(&session).await
     }
 
     pub async fn set_error(&self, id: &SessionId) -> SessionResult<()> {
-        let mut session = self.get(id).await?;
+        let mut session = self.load_for(id, SessionAccess::Append).await?;
         session.set_state(SessionState::Failed);
-        self.persistence.save(&session).await
+        self.save(&session).await
     }
 
+    /// Delete expired sessions; a scoped manager only deletes those its
+    /// principal may modify.
     pub async fn cleanup_expired(&self) -> SessionResult<usize> {
-        self.persistence.cleanup_expired().await
+        if self.principal.is_some() {
+            let mut removed = 0;
+            for id in self.persistence.list(None).await? {
+                if let Some(session) = self.persistence.load(&id).await?
+                    && session.is_expired()
+                    && self.authorize(&session, SessionAccess::Append).is_ok()
+                    && self.remove(&id).await?
+                {
+                    removed += 1;
+                }
+            }
+            return Ok(removed);
+        }
+        let Some(index) = &self.search_index else {
+            return self.persistence.cleanup_expired().await;
+        };
+        let before = self.persistence.list(None).await?;
+        let removed = self.persistence.cleanup_expired().await?;
+        if removed > 0 {
+            let after: std::collections::HashSet<_> =
+                self.persistence.list(None).await?.into_iter().collect();
+            for id in before.iter().filter(|id| !after.contains(id)) {
+                if let Err(e) = index.remove(id).await {
+                    tracing::warn!(session_id = %id, error = %e, "Failed to remove session from search index");
+                }
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Archive (via the configured hook) and delete every expired session.
+    /// A scoped manager only considers sessions its principal may modify.
+    pub async fn collect_expired(&self, config: &GcConfig) -> SessionResult<GcReport> {
+        let mut report = GcReport::default();
+        for id in self.persistence.list(None).await? {
+            let Some(session) = self.persistence.load(&id).await? else {
+                continue;
+            };
+            if self.authorize(&session, SessionAccess::Append).is_err() {
+                continue;
+            }
+            report.scanned += 1;
+            if !session.is_expired() {
+                continue;
+            }
+
+            if let Some(archive) = &config.archive {
+                let summaries = self.persistence.get_summaries(&id).await?;
+                if let Err(e) = archive(SessionArchive::new(session, summaries)).await {
+                    tracing::warn!(session_id = %id, error = %e, "Failed to archive expired session");
+                    report.failed += 1;
+                    continue;
+                }
+                report.archived += 1;
+            }
+
+            match self.delete(&id).await {
+                Ok(_) => report.deleted += 1,
+                Err(e) => {
+                    tracing::warn!(session_id = %id, error = %e, "Failed to delete expired session");
+                    report.failed += 1;
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Run [`collect_expired`](Self::collect_expired) every `config.interval`
+    /// on the current Tokio runtime until the handle is stopped or dropped.
+    pub fn start_gc(&self, config: GcConfig) -> GcHandle {
+        let token = tokio_util::sync::CancellationToken::new();
+        let manager = self.clone();
+        let stop = token.clone();
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(config.interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = stop.cancelled() => break,
+                    _ = ticker.tick() => {}
+                }
+                match manager.collect_expired(&config).await {
+                    Ok(report) if report.deleted > 0 || report.failed > 0 => {
+                        tracing::info!(
+                            deleted = report.deleted,
+                            archived = report.archived,
+                            failed = report.failed,
+                            "Collected expired sessions"
+                        );
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!(error = %e, "Session garbage collection failed"),
+                }
+            }
+        });
+        GcHandle::new(token, task)
+    }
+
+    /// Token usage, cost, tool call and failure rollups per tenant and day.
+    /// A scoped manager only counts sessions its principal may read.
+    pub async fn stats(&self, query: &StatsQuery) -> SessionResult<SessionStatsReport> {
+        let mut report = SessionStatsReport::default();
+        for id in self.persistence.list(query.tenant_id.as_deref()).await? {
+            if let Some(session) = self.persistence.load(&id).await?
+                && query.matches(&session)
+                && self.authorize(&session, SessionAccess::Read).is_ok()
+            {
+                report.add(&session);
+            }
+        }
+        Ok(report)
+    }
+
+    /// Full-text search over message text, summaries and todos.
+    ///
+    /// Uses the configured [`SearchIndex`], or scans all stored sessions
+    /// without one.
+    pub async fn search(&self, query: impl Into<SearchQuery>) -> SessionResult<Vec<SearchHit>> {
+        let query = query.into();
+        let hits = match &self.search_index {
+            Some(index) => index.search(&query).await?,
+            None => search::scan(self.persistence.as_ref(), &query).await?,
+        };
+        if self.principal.is_none() {
+            return Ok(hits);
+        }
+        let mut visible = Vec::with_capacity(hits.len());
+        for hit in hits {
+            if let Some(session) = self.persistence.load(&hit.session_id).await?
+                && self.authorize(&session, SessionAccess::Read).is_ok()
+            {
+                visible.push(hit);
+            }
+        }
+        Ok(visible)
+    }
+
+    /// Index every stored session, e.g. after attaching a fresh index.
+    pub async fn rebuild_search_index(&self) -> SessionResult<usize> {
+        let Some(index) = &self.search_index else {
+            return Ok(0);
+        };
+        let mut indexed = 0;
+        for id in self.persistence.list(None).await? {
+            if let Some(session) = self.persistence.load(&id).await? {
+                index.index(&session).await?;
+                indexed += 1;
+            }
+        }
+        Ok(indexed)
+    }
+
+    /// Follow the lifecycle events of `session_id` written through this
+    /// manager or any of its clones. Requires read access to the session.
+    pub async fn subscribe(&self, session_id: SessionId) -> SessionResult<SessionSubscription> {
+        self.load_for(&session_id, SessionAccess::Read).await?;
+        Ok(SessionSubscription::new(
+            session_id,
+            self.events.subscribe(),
+        ))
+    }
+
+    /// Deliver `event` to subscribers, e.g. when relaying events from another
+    /// process. A scoped manager needs append access to the event's session.
+    pub async fn publish(&self, event: SessionEvent) -> SessionResult<()> {
+        if self.principal.is_some() {
+            self.load_for(&event.session_id(), SessionAccess::Append)
+                .await?;
+        }
+        self.emit(event);
+        Ok(())
+    }
+
+    fn emit(&self, event: SessionEvent) {
+        // Send only fails when nobody is subscribed.
+        let _ = self.events.send(event);
+    }
+
+    async fn save(&self, session: &Session) -> SessionResult<()> {
+        let tracking = self.events.receiver_count() > 0;
+        let before = if tracking {
+            self.persistence.load(&session.id).await?
+        } else {
+            None
+        };
+        self.persistence.save(session).await?;
+        self.reindex(session).await;
+        if tracking {
+            for event in SessionEvent::diff(before.as_ref(), session) {
+                self.emit(event);
+            }
+        }
+        Ok(())
+    }
+
+    /// Index failures are logged, not returned: the session itself is saved.
+    async fn reindex(&self, session: &Session) {
+        if let Some(index) = &self.search_index
+            && let Err(e) = index.index(session).await
+        {
+            tracing::warn!(session_id = %session.id, error = %e, "Failed to update search index");
+        }
+    }
+
+    /// Export a session with its summaries as a portable archive.
+    pub async fn export(&self, id: &SessionId) -> SessionResult<SessionArchive> {
+        let session = self.get(id).await?;
+        let summaries = self.persistence.get_summaries(id).await?;
+        Ok(SessionArchive::new(session, summaries))
+    }
+
+    /// Restore an archived session under its original ID.
+    ///
+    /// Fails with [`SessionError::Conflict`] if a session with that ID exists.
+    pub async fn import(&self, archive: SessionArchive) -> SessionResult<Session> {
+        archive.check_version()?;
+        self.authorize(&archive.session, SessionAccess::Append)?;
+        if self.persistence.load(&archive.session.id).await?.is_some() {
+            return Err(SessionError::Conflict {
+                id: archive.session.id.to_string(),
+            });
+        }
+
+        self.save(&archive.session).await?;
+        for summary in archive.summaries {
+            self.persistence.add_summary(summary).await?;
+        }
+        Ok(archive.session)
     }
 
     pub async fn exists(&self, id: &SessionId) -> SessionResult<bool> {
@@ -136,6 +603,7 @@ impl Default for SessionManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::session::types::SummarySnapshot;
     use crate::types::ContentBlock;
 
     #[tokio::test]
@@ -179,6 +647,39 @@ mod tests {
         assert_eq!(restored.messages.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_fork_at_round_trip() {
+        let manager = SessionManager::in_memory();
+        let session = manager.create(SessionConfig::default()).await.unwrap();
+        for text in ["Hello", "Hi!", "Use Go"] {
+            let message = SessionMessage::user(vec![ContentBlock::text(text)]);
+            manager.add_message(&session.id, message).await.unwrap();
+        }
+        let parent = manager.get(&session.id).await.unwrap();
+        let fork_point = parent.messages[1].id.clone();
+
+        let forked = parent.fork_at(&fork_point).unwrap();
+        manager.update(&forked).await.unwrap();
+
+        let parent_ids: Vec<_> = parent.messages.iter().map(|m| m.id.clone()).collect();
+        let reloaded = manager.get(&session.id).await.unwrap();
+        assert_eq!(reloaded.messages.len(), 3);
+        assert!(reloaded.messages.iter().all(|m| !m.is_sidechain));
+        assert_eq!(
+            reloaded
+                .messages
+                .iter()
+                .map(|m| m.id.clone())
+                .collect::<Vec<_>>(),
+            parent_ids
+        );
+
+        let child = manager.get(&forked.id).await.unwrap();
+        assert_eq!(child.parent_id, Some(session.id));
+        assert_eq!(child.current_branch().len(), 2);
+        assert!(child.messages.iter().all(|m| !parent_ids.contains(&m.id)));
+    }
+
     #[tokio::test]
     async fn test_session_manager_fork() {
         let manager = SessionManager::in_memory();
@@ -205,6 +706,105 @@ mod tests {
         assert!(forked.messages.iter().all(|m| m.is_sidechain));
     }
 
+    #[tokio::test]
+    async fn test_session_manager_export_import() {
+        let source = SessionManager::in_memory();
+        let session = source.create(SessionConfig::default()).await.unwrap();
+        let session_id = session.id;
+        source
+            .add_message(
+                &session_id,
+                SessionMessage::user(vec![ContentBlock::text("Hello")]),
+            )
+            .await
+            .unwrap();
+        source
+            .persistence
+            .add_summary(SummarySnapshot::new(session_id, "Greeting"))
+            .await
+            .unwrap();
+
+        let archive = source.export(&session_id).await.unwrap();
+        let json = archive.to_json().unwrap();
+
+        let target = SessionManager::in_memory();
+        let imported = target
+            .import(SessionArchive::from_json(&json).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(imported.id, session_id);
+        assert_eq!(target.get(&session_id).await.unwrap().messages.len(), 1);
+        assert_eq!(
+            target.persistence.get_summaries(&session_id).await.unwrap()[0].summary,
+            "Greeting"
+        );
+
+        assert!(matches!(
+            target.import(archive).await,
+            Err(SessionError::Conflict { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_session_manager_branches() {
+        let manager = SessionManager::in_memory();
+        let mut session = manager.create(SessionConfig::default()).await.unwrap();
+        session.add_message(SessionMessage::user(vec![ContentBlock::text("Hello")]));
+        let fork = session.current_leaf_id.clone().unwrap();
+        session.add_message(SessionMessage::assistant(vec![ContentBlock::text("Hi")]));
+        let abandoned = session.current_leaf_id.clone().unwrap();
+        manager.update(&session).await.unwrap();
+
+        manager.switch_branch(&session.id, &fork).await.unwrap();
+        manager
+            .add_message(
+                &session.id,
+                SessionMessage::assistant(vec![ContentBlock::text("Hey")]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(manager.branches(&session.id).await.unwrap().len(), 2);
+
+        manager
+            .merge_branch(&session.id, &abandoned, "Said hi")
+            .await
+            .unwrap();
+        assert_eq!(
+            manager
+                .delete_branch(&session.id, &abandoned)
+                .await
+                .unwrap(),
+            1
+        );
+        let restored = manager.get(&session.id).await.unwrap();
+        assert_eq!(restored.branches().len(), 1);
+        assert_eq!(restored.current_branch().len(), 3);
+        assert!(matches!(
+            manager.switch_branch(&session.id, &abandoned).await,
+            Err(SessionError::NotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_session_manager_search() {
+        let manager = SessionManager::in_memory();
+        let session = manager.create(SessionConfig::default()).await.unwrap();
+        manager
+            .add_message(
+                &session.id,
+                SessionMessage::user(vec![ContentBlock::text("Rotate the auth config secret")]),
+            )
+            .await
+            .unwrap();
+        manager.create(SessionConfig::default()).await.unwrap();
+
+        let hits = manager.search("auth config").await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, session.id);
+        assert!(hits[0].message_id.is_some());
+        assert!(manager.search("billing").await.unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn test_session_manager_complete() {
         let manager = SessionManager::in_memory();
@@ -261,4 +861,185 @@ mod tests {
         let result = manager.get(&session_id).await;
         assert!(matches!(result, Err(SessionError::Expired { .. })));
     }
+
+    #[tokio::test]
+    async fn test_subscribe_follows_one_session() {
+        let manager = SessionManager::in_memory();
+        let session = manager.create(SessionConfig::default()).await.unwrap();
+        let other = manager.create(SessionConfig::default()).await.unwrap();
+
+        // A clone shares the bus, as a second handle in the same process would.
+        let mut events = manager.clone().subscribe(session.id).await.unwrap();
+        manager
+            .add_message(
+                &other.id,
+                SessionMessage::user(vec![ContentBlock::text("x")]),
+            )
+            .await
+            .unwrap();
+        manager
+            .add_message(
+                &session.id,
+                SessionMessage::user(vec![ContentBlock::text("hi")]),
+            )
+            .await
+            .unwrap();
+        manager.complete(&session.id).await.unwrap();
+        manager.delete(&session.id).await.unwrap();
+
+        assert!(matches!(
+            events.recv().await,
+            Some(SessionEvent::MessageAdded {
+                role: crate::types::Role::User,
+                ..
+            })
+        ));
+        assert!(matches!(
+            events.recv().await,
+            Some(SessionEvent::StateChanged {
+                to: SessionState::Completed,
+                ..
+            })
+        ));
+        assert!(matches!(
+            events.recv().await,
+            Some(SessionEvent::Deleted { session_id }) if session_id == session.id
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_rollback() {
+        let manager = SessionManager::in_memory();
+        let session = manager.create(SessionConfig::default()).await.unwrap();
+        manager
+            .add_message(
+                &session.id,
+                SessionMessage::user(vec![ContentBlock::text("hi")]),
+            )
+            .await
+            .unwrap();
+        let snapshot = manager.snapshot(&session.id, "before run").await.unwrap();
+
+        let mut failed = manager.get(&session.id).await.unwrap();
+        failed.add_message(SessionMessage::assistant(vec![ContentBlock::text("oops")]));
+        manager.update(&failed).await.unwrap();
+
+        let restored = manager.restore(&session.id, snapshot).await.unwrap();
+        assert_eq!(restored.messages.len(), 1);
+        assert_eq!(manager.get(&session.id).await.unwrap().messages.len(), 1);
+        assert!(matches!(
+            manager.restore(&session.id, uuid::Uuid::nil()).await,
+            Err(SessionError::NotFound { .. })
+        ));
+
+        let stored = manager.get(&session.id).await.unwrap();
+        assert!(
+            !serde_json::to_string(&stored)
+                .unwrap()
+                .contains("before run")
+        );
+        assert_eq!(manager.snapshots(&session.id).await.unwrap().len(), 1);
+        assert!(manager.drop_snapshot(&session.id, snapshot).await.unwrap());
+        assert!(manager.snapshots(&session.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_limit() {
+        let manager = SessionManager::in_memory();
+        let session = manager.create(SessionConfig::default()).await.unwrap();
+        let first = manager.snapshot(&session.id, "first").await.unwrap();
+        for i in 0..MAX_SNAPSHOTS {
+            manager
+                .snapshot(&session.id, format!("s{i}"))
+                .await
+                .unwrap();
+        }
+
+        let snapshots = manager.snapshots(&session.id).await.unwrap();
+        assert_eq!(snapshots.len(), MAX_SNAPSHOTS);
+        assert!(snapshots.iter().all(|s| s.id != first));
+        assert!(matches!(
+            manager.restore(&session.id, first).await,
+            Err(SessionError::NotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_principal_scoped_access() {
+        let manager = SessionManager::in_memory();
+        let alice = manager.for_principal(Principal::user("alice"));
+        let bob = manager.for_principal(Principal::user("bob"));
+        let admin = manager.for_principal(Principal::admin("ops"));
+
+        let session = alice.create(SessionConfig::default()).await.unwrap();
+        assert_eq!(session.owner.as_deref(), Some("alice"));
+        let legacy = manager.create(SessionConfig::default()).await.unwrap();
+
+        assert!(alice.get(&session.id).await.is_ok());
+        assert!(admin.get(&session.id).await.is_ok());
+        assert!(matches!(
+            bob.get(&session.id).await,
+            Err(SessionError::AccessDenied {
+                access: SessionAccess::Read,
+                ..
+            })
+        ));
+        let message = SessionMessage::user(vec![ContentBlock::text("hi")]);
+        assert!(bob.add_message(&session.id, message).await.is_err());
+        assert!(bob.complete(&session.id).await.is_err());
+        assert!(bob.delete(&session.id).await.is_err());
+        assert!(bob.update(&session).await.is_err());
+        assert!(bob.authorize(&session, SessionAccess::Execute).is_err());
+        assert!(bob.subscribe(session.id).await.is_err());
+        assert!(alice.subscribe(session.id).await.is_ok());
+        let relayed = SessionEvent::Deleted {
+            session_id: session.id,
+        };
+        assert!(bob.publish(relayed.clone()).await.is_err());
+        assert!(alice.publish(relayed.clone()).await.is_ok());
+        assert!(manager.publish(relayed).await.is_ok());
+
+        assert_eq!(bob.list().await.unwrap(), vec![legacy.id]);
+        assert_eq!(manager.list().await.unwrap().len(), 2);
+
+        let strict = manager
+            .clone()
+            .with_access_policy(Arc::new(OwnerPolicy::strict()))
+            .for_principal(Principal::user("bob"));
+        assert!(strict.get(&legacy.id).await.is_err());
+
+        let stats = |m: SessionManager| async move {
+            m.stats(&StatsQuery::new()).await.unwrap().total().sessions
+        };
+        assert_eq!(stats(alice.clone()).await, 2);
+        assert_eq!(stats(strict.clone()).await, 0);
+        assert_eq!(stats(manager.clone()).await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_scoped_cleanup_keeps_other_owners_sessions() {
+        let manager = SessionManager::in_memory();
+        let alice = manager.for_principal(Principal::user("alice"));
+        let bob = manager.for_principal(Principal::user("bob"));
+        let expiring = || SessionConfig {
+            ttl_secs: Some(0),
+            ..Default::default()
+        };
+        let theirs = alice.create(expiring()).await.unwrap();
+        let mine = bob.create(expiring()).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+        let stored = |id| {
+            let persistence = manager.persistence.clone();
+            async move { persistence.load(&id).await.unwrap().is_some() }
+        };
+
+        assert_eq!(bob.cleanup_expired().await.unwrap(), 1);
+        assert!(!stored(mine.id).await);
+        assert!(stored(theirs.id).await);
+
+        let report = bob.collect_expired(&GcConfig::default()).await.unwrap();
+        assert_eq!(report.deleted, 0);
+        assert!(stored(theirs.id).await);
+    }
 }
@@ -0,0 +1,336 @@
+//! Copying sessions between persistence backends.
+//!
+//! [`SessionManager::migrate`] copies every session (with its summaries) from
+//! one backend to another in batches, reading each copy back from the target
+//! to verify it. A dry run only loads the sources and checks for conflicts, so
+//! operators can see what a migration would do before writing anything.
+//! Pending queue items are transient and are not copied.
+
+use std::sync::Arc;
+
+use futures::future::join_all;
+
+use super::SessionResult;
+use super::manager::SessionManager;
+use super::persistence::Persistence;
+use super::state::{Session, SessionId};
+
+const DEFAULT_BATCH_SIZE: usize = 100;
+
+/// Called after each batch with the report so far.
+pub type ProgressHook = Arc<dyn Fn(&MigrationReport) + Send + Sync>;
+
+#[derive(Clone)]
+pub struct MigrationConfig {
+    /// Sessions copied concurrently per batch (default: 100).
+    pub batch_size: usize,
+    /// Load and check sessions without writing to the target.
+    pub dry_run: bool,
+    /// Replace sessions that already exist in the target instead of skipping them.
+    pub overwrite: bool,
+    /// Only migrate sessions of this tenant.
+    pub tenant_id: Option<String>,
+    pub(crate) progress: Option<ProgressHook>,
+}
+
+impl Default for MigrationConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: DEFAULT_BATCH_SIZE,
+            dry_run: false,
+            overwrite: false,
+            tenant_id: None,
+            progress: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for MigrationConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MigrationConfig")
+            .field("batch_size", &self.batch_size)
+            .field("dry_run", &self.dry_run)
+            .field("overwrite", &self.overwrite)
+            .field("tenant_id", &self.tenant_id)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+impl MigrationConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    pub fn tenant(mut self, tenant_id: impl Into<String>) -> Self {
+        self.tenant_id = Some(tenant_id.into());
+        self
+    }
+
+    pub fn on_progress<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&MigrationReport) + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(hook));
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MigrationFailure {
+    pub session_id: SessionId,
+    pub error: String,
+}
+
+/// Outcome of a migration (or of a dry run, where nothing is written).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Sessions found in the source.
+    pub total: usize,
+    /// Sessions copied and verified (in a dry run: that would be copied).
+    pub migrated: usize,
+    /// Sessions that already exist in the target, or are expired.
+    pub skipped: usize,
+    pub failures: Vec<MigrationFailure>,
+    pub dry_run: bool,
+}
+
+impl MigrationReport {
+    /// Sessions handled so far.
+    pub fn processed(&self) -> usize {
+        self.migrated + self.skipped + self.failures.len()
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+enum Outcome {
+    Migrated,
+    Skipped,
+}
+
+impl SessionManager {
+    /// Copy all sessions and their summaries from `from` to `to`.
+    ///
+    /// Individual failures are collected in the report rather than aborting
+    /// the run; only failing to list the source is an error.
+    pub async fn migrate(
+        from: &dyn Persistence,
+        to: &dyn Persistence,
+        config: &MigrationConfig,
+    ) -> SessionResult<MigrationReport> {
+        let ids = from.list(config.tenant_id.as_deref()).await?;
+        let mut report = MigrationReport {
+            total: ids.len(),
+            dry_run: config.dry_run,
+            ..Default::default()
+        };
+
+        for batch in ids.chunks(config.batch_size.max(1)) {
+            let outcomes = join_all(batch.iter().map(|id| migrate_one(from, to, id, config))).await;
+            for (id, outcome) in batch.iter().zip(outcomes) {
+                match outcome {
+                    Ok(Outcome::Migrated) => report.migrated += 1,
+                    Ok(Outcome::Skipped) => report.skipped += 1,
+                    Err(error) => {
+                        tracing::warn!(session_id = %id, error = %error, "Failed to migrate session");
+                        report.failures.push(MigrationFailure {
+                            session_id: *id,
+                            error,
+                        });
+                    }
+                }
+            }
+            if let Some(progress) = &config.progress {
+                progress(&report);
+            }
+        }
+        Ok(report)
+    }
+}
+
+async fn migrate_one(
+    from: &dyn Persistence,
+    to: &dyn Persistence,
+    id: &SessionId,
+    config: &MigrationConfig,
+) -> Result<Outcome, String> {
+    let session = match from.load(id).await {
+        Ok(Some(session)) => session,
+        Ok(None) => return Ok(Outcome::Skipped),
+        Err(e) => return Err(format!("load from {}: {}", from.name(), e)),
+    };
+    if session.is_expired() {
+        return Ok(Outcome::Skipped);
+    }
+    let summaries = from
+        .get_summaries(id)
+        .await
+        .map_err(|e| format!("load summaries from {}: {}", from.name(), e))?;
+
+    let exists = to
+        .load(id)
+        .await
+        .map_err(|e| format!("check {}: {}", to.name(), e))?
+        .is_some();
+    if exists && !config.overwrite {
+        return Ok(Outcome::Skipped);
+    }
+    if config.dry_run {
+        return Ok(Outcome::Migrated);
+    }
+
+    if exists {
+        // Summaries are append-only; start from a clean slate.
+        to.delete(id)
+            .await
+            .map_err(|e| format!("replace in {}: {}", to.name(), e))?;
+    }
+    to.save(&session)
+        .await
+        .map_err(|e| format!("save to {}: {}", to.name(), e))?;
+    for summary in &summaries {
+        to.add_summary(summary.clone())
+            .await
+            .map_err(|e| format!("save summary to {}: {}", to.name(), e))?;
+    }
+
+    verify(to, &session, summaries.len()).await?;
+    Ok(Outcome::Migrated)
+}
+
+/// Read the copy back and compare it with the source.
+async fn verify(to: &dyn Persistence, source: &Session, summaries: usize) -> Result<(), String> {
+    let copy = to
+        .load(&source.id)
+        .await
+        .map_err(|e| format!("verify: {}", e))?
+        .ok_or("verify: session missing from target")?;
+    let stored_summaries = to
+        .get_summaries(&source.id)
+        .await
+        .map_err(|e| format!("verify summaries: {}", e))?
+        .len();
+
+    let mismatch = [
+        ("messages", source.messages.len(), copy.messages.len()),
+        ("todos", source.todos.len(), copy.todos.len()),
+        (
+            "compacts",
+            source.compact_history.len(),
+            copy.compact_history.len(),
+        ),
+        ("summaries", summaries, stored_summaries),
+    ]
+    .into_iter()
+    .find(|(_, expected, actual)| expected != actual);
+
+    match mismatch {
+        Some((what, expected, actual)) => Err(format!(
+            "verify: expected {} {}, target has {}",
+            expected, what, actual
+        )),
+        None if copy.current_leaf_id != source.current_leaf_id => {
+            Err("verify: current leaf differs".into())
+        }
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::persistence::MemoryPersistence;
+    use crate::session::state::{SessionConfig, SessionMessage};
+    use crate::session::types::SummarySnapshot;
+    use crate::types::ContentBlock;
+    use std::sync::Mutex;
+
+    async fn seeded(count: usize) -> (MemoryPersistence, Vec<SessionId>) {
+        let persistence = MemoryPersistence::new();
+        let mut ids = Vec::new();
+        for i in 0..count {
+            let mut session = Session::new(SessionConfig::default());
+            session.add_message(SessionMessage::user(vec![ContentBlock::text(format!(
+                "message {i}"
+            ))]));
+            persistence.save(&session).await.unwrap();
+            persistence
+                .add_summary(SummarySnapshot::new(session.id, "summary"))
+                .await
+                .unwrap();
+            ids.push(session.id);
+        }
+        (persistence, ids)
+    }
+
+    #[tokio::test]
+    async fn test_migrate_with_progress() {
+        let (source, ids) = seeded(5).await;
+        let target = MemoryPersistence::new();
+
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        let sink = batches.clone();
+        let config = MigrationConfig::new()
+            .batch_size(2)
+            .on_progress(move |r| sink.lock().unwrap().push(r.processed()));
+
+        let report = SessionManager::migrate(&source, &target, &config)
+            .await
+            .unwrap();
+        assert_eq!(report.total, 5);
+        assert_eq!(report.migrated, 5);
+        assert!(report.is_success());
+        assert_eq!(*batches.lock().unwrap(), vec![2, 4, 5]);
+
+        for id in &ids {
+            let copy = target.load(id).await.unwrap().unwrap();
+            assert_eq!(copy.messages.len(), 1);
+            assert_eq!(target.get_summaries(id).await.unwrap().len(), 1);
+        }
+
+        // Existing sessions are skipped on a second run...
+        let report = SessionManager::migrate(&source, &target, &config)
+            .await
+            .unwrap();
+        assert_eq!(report.skipped, 5);
+
+        // ...and replaced without duplicating summaries when overwriting.
+        let report = SessionManager::migrate(&source, &target, &config.clone().overwrite(true))
+            .await
+            .unwrap();
+        assert_eq!(report.migrated, 5);
+        assert_eq!(target.get_summaries(&ids[0]).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_writes_nothing() {
+        let (source, _) = seeded(3).await;
+        let target = MemoryPersistence::new();
+
+        let report =
+            SessionManager::migrate(&source, &target, &MigrationConfig::new().dry_run(true))
+                .await
+                .unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.migrated, 3);
+        assert!(target.list(None).await.unwrap().is_empty());
+    }
+}
@@ -1,38 +1,77 @@
 //! Session management for stateful conversations.
 
+pub mod access;
+pub mod analytics;
+pub mod archive;
+pub mod cli_transcript;
 pub mod compact;
+pub mod compression;
+pub mod events;
+pub mod gc;
 pub mod manager;
+pub mod migrate;
 pub mod persistence;
 #[cfg(feature = "jsonl")]
 pub mod persistence_jsonl;
+#[cfg(feature = "object-store")]
+pub mod persistence_object_store;
 #[cfg(feature = "postgres")]
 pub mod persistence_postgres;
 #[cfg(feature = "redis-backend")]
 pub mod persistence_redis;
+pub mod persistence_scrubbed;
+#[cfg(feature = "sqlite")]
+pub mod persistence_sqlite;
 pub mod queue;
+pub mod search;
+#[cfg(feature = "tantivy-search")]
+pub mod search_tantivy;
 pub mod session_state;
 pub mod state;
 pub mod types;
 
 pub use crate::types::TokenUsage;
-pub use compact::{CompactExecutor, CompactStrategy, DEFAULT_COMPACT_THRESHOLD};
+pub use access::{AccessPolicy, OwnerPolicy, Principal, SessionAccess};
+pub use analytics::{SessionStatsReport, StatsBucket, StatsKey, StatsQuery, ToolCallStats};
+pub use archive::{
+    ARCHIVE_FORMAT_VERSION, AttachmentEntry, AttachmentKind, AttachmentLocation, SessionArchive,
+};
+pub use cli_transcript::CliTranscripts;
+pub use compact::{
+    CompactExecutor, CompactStrategy, CompactVerification, DEFAULT_COMPACT_THRESHOLD,
+    PrunedToolResults, RequiredMention, SlidingWindow, ToolResultPruning,
+};
+pub use compression::CompressionConfig;
+pub use events::{SessionEvent, SessionSubscription};
+pub use gc::{ArchiveHook, GcConfig, GcHandle, GcReport};
 pub use manager::SessionManager;
+pub use migrate::{MigrationConfig, MigrationFailure, MigrationReport, ProgressHook};
 pub use persistence::{MemoryPersistence, Persistence, PersistenceFactory};
 #[cfg(feature = "jsonl")]
 pub use persistence_jsonl::{
     JsonlConfig, JsonlConfigBuilder, JsonlEntry, JsonlPersistence, SyncMode,
 };
+#[cfg(feature = "object-store")]
+pub use persistence_object_store::{ObjectStoreConfig, ObjectStorePersistence, WriteBatch};
 #[cfg(feature = "postgres")]
 pub use persistence_postgres::{
     PgPoolConfig, PostgresConfig, PostgresPersistence, PostgresSchema, SchemaIssue,
 };
 #[cfg(feature = "redis-backend")]
 pub use persistence_redis::{RedisConfig, RedisPersistence};
+pub use persistence_scrubbed::ScrubbedPersistence;
+#[cfg(feature = "sqlite")]
+pub use persistence_sqlite::{SqliteConfig, SqlitePersistence, SqliteSchema};
 pub use queue::{InputQueue, MergedInput, QueueError, QueuedInput, SharedInputQueue};
+pub use search::{HitSource, SearchEntry, SearchHit, SearchIndex, SearchQuery};
+#[cfg(feature = "tantivy-search")]
+pub use search_tantivy::TantivySearchIndex;
 pub use session_state::{ExecutionGuard, ToolState};
 pub use state::{
-    MessageId, MessageMetadata, Session, SessionConfig, SessionId, SessionMessage,
-    SessionPermissions, SessionState, SessionToolLimits, SessionType,
+    BranchInfo, ChangeRecord, DiffSince, MAX_CHANGE_LOG, MAX_SNAPSHOTS, MessageId, MessageMetadata,
+    Session, SessionChange, SessionConfig, SessionDelta, SessionId, SessionMessage,
+    SessionPermissions, SessionSnapshot, SessionState, SessionToolLimits, SessionType,
+    StateTransition, TodoChange,
 };
 pub use types::{
     CompactRecord, CompactTrigger, EnvironmentContext, Plan, PlanStatus, QueueItem, QueueOperation,
@@ -52,6 +91,16 @@ pub enum SessionError {
     #[error("Storage error: {message}")]
     Storage { message: String },
 
+    #[error("Session was modified concurrently: {id}")]
+    Conflict { id: String },
+
+    #[error("Access denied: {principal} may not {access} session {id}")]
+    AccessDenied {
+        id: String,
+        principal: String,
+        access: SessionAccess,
+    },
+
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
@@ -64,13 +113,19 @@ pub enum SessionError {
 
 pub type SessionResult<T> = std::result::Result<T, SessionError>;
 
-#[cfg(any(feature = "postgres", feature = "redis-backend"))]
+#[cfg(any(
+    feature = "postgres",
+    feature = "sqlite",
+    feature = "redis-backend",
+    feature = "object-store",
+    feature = "tantivy-search"
+))]
 pub(crate) trait StorageResultExt<T> {
     fn storage_err(self) -> SessionResult<T>;
     fn storage_err_ctx(self, context: &str) -> SessionResult<T>;
 }
 
-#[cfg(any(feature = "postgres", feature = "redis-backend"))]
+#[cfg(any(feature = "postgres", feature = "sqlite", feature = "redis-backend"))]
 pub(crate) async fn with_retry<F, Fut, T>(
     max_retries: u32,
     initial_backoff: std::time::Duration,
@@ -105,7 +160,13 @@ where
     }
 }
 
-#[cfg(any(feature = "postgres", feature = "redis-backend"))]
+#[cfg(any(
+    feature = "postgres",
+    feature = "sqlite",
+    feature = "redis-backend",
+    feature = "object-store",
+    feature = "tantivy-search"
+))]
 impl<T, E: std::fmt::Display> StorageResultExt<T> for std::result::Result<T, E> {
     fn storage_err(self) -> SessionResult<T> {
         self.map_err(|e| SessionError::Storage {
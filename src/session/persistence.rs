@@ -6,7 +6,7 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
-use super::state::{Session, SessionId, SessionMessage};
+use super::state::{Session, SessionId, SessionMessage, SessionSnapshot};
 use super::types::{QueueItem, SummarySnapshot};
 use super::{SessionError, SessionResult};
 
@@ -38,6 +38,40 @@ pub trait Persistence: Send + Sync {
     // Cleanup
     async fn cleanup_expired(&self) -> SessionResult<usize>;
 
+    // Snapshots
+    //
+    // Snapshots are stored as records of their own, never inside the session
+    // document. Backends without snapshot storage fail these calls.
+
+    async fn save_snapshot(&self, snapshot: &SessionSnapshot) -> SessionResult<()> {
+        let _ = snapshot;
+        Err(snapshots_unsupported(self.name()))
+    }
+
+    async fn load_snapshot(
+        &self,
+        session_id: &SessionId,
+        snapshot_id: Uuid,
+    ) -> SessionResult<Option<SessionSnapshot>> {
+        let _ = (session_id, snapshot_id);
+        Err(snapshots_unsupported(self.name()))
+    }
+
+    /// Snapshots of a session, oldest first.
+    async fn list_snapshots(&self, session_id: &SessionId) -> SessionResult<Vec<SessionSnapshot>> {
+        let _ = session_id;
+        Err(snapshots_unsupported(self.name()))
+    }
+
+    async fn delete_snapshot(
+        &self,
+        session_id: &SessionId,
+        snapshot_id: Uuid,
+    ) -> SessionResult<bool> {
+        let _ = (session_id, snapshot_id);
+        Err(snapshots_unsupported(self.name()))
+    }
+
     /// Append a message to an existing session.
     ///
     /// Concurrency contract: implementations may hold a write lock for the duration
@@ -60,11 +94,18 @@ pub trait Persistence: Send + Sync {
     }
 }
 
+fn snapshots_unsupported(backend: &str) -> SessionError {
+    SessionError::Storage {
+        message: format!("{backend} persistence does not store snapshots"),
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct MemoryPersistence {
     sessions: Arc<RwLock<HashMap<String, Session>>>,
     summaries: Arc<RwLock<HashMap<String, Vec<SummarySnapshot>>>>,
     queue: Arc<RwLock<HashMap<String, Vec<QueueItem>>>>,
+    snapshots: Arc<RwLock<HashMap<String, Vec<SessionSnapshot>>>>,
 }
 
 impl MemoryPersistence {
@@ -122,8 +163,10 @@ impl Persistence for MemoryPersistence {
         let mut sessions = self.sessions.write().await;
         let mut summaries = self.summaries.write().await;
         let mut queue = self.queue.write().await;
+        let mut snapshots = self.snapshots.write().await;
         summaries.remove(&key);
         queue.remove(&key);
+        snapshots.remove(&key);
         Ok(sessions.remove(&key).is_some())
     }
 
@@ -181,7 +224,7 @@ impl Persistence for MemoryPersistence {
     async fn dequeue(&self, session_id: &SessionId) -> SessionResult<Option<QueueItem>> {
         let mut queue = self.queue.write().await;
         if let Some(items) = queue.get_mut(&session_id.to_string()) {
-            items.sort_by(|a, b| b.priority.cmp(&a.priority));
+            items.sort_by_key(|i| std::cmp::Reverse(i.priority));
             if let Some(pos) = items
                 .iter()
                 .position(|i| i.status == super::types::QueueStatus::Pending)
@@ -220,12 +263,13 @@ impl Persistence for MemoryPersistence {
     }
 
     async fn cleanup_expired(&self) -> SessionResult<usize> {
-        // Hold all three write locks simultaneously to prevent races where a
+        // Hold all write locks simultaneously to prevent races where a
         // concurrent operation could observe a session removed from `sessions`
-        // but still present in `summaries` or `queue`.
+        // but still present in `summaries`, `queue` or `snapshots`.
         let mut sessions = self.sessions.write().await;
         let mut summaries = self.summaries.write().await;
         let mut queue = self.queue.write().await;
+        let mut snapshots = self.snapshots.write().await;
 
         let expired_keys: Vec<String> = sessions
             .iter()
@@ -237,10 +281,58 @@ impl Persistence for MemoryPersistence {
             sessions.remove(key);
             summaries.remove(key);
             queue.remove(key);
+            snapshots.remove(key);
         }
 
         Ok(expired_keys.len())
     }
+
+    async fn save_snapshot(&self, snapshot: &SessionSnapshot) -> SessionResult<()> {
+        self.snapshots
+            .write()
+            .await
+            .entry(snapshot.session_id.to_string())
+            .or_default()
+            .push(snapshot.clone());
+        Ok(())
+    }
+
+    async fn load_snapshot(
+        &self,
+        session_id: &SessionId,
+        snapshot_id: Uuid,
+    ) -> SessionResult<Option<SessionSnapshot>> {
+        Ok(self
+            .snapshots
+            .read()
+            .await
+            .get(&session_id.to_string())
+            .and_then(|snapshots| snapshots.iter().find(|s| s.id == snapshot_id).cloned()))
+    }
+
+    async fn list_snapshots(&self, session_id: &SessionId) -> SessionResult<Vec<SessionSnapshot>> {
+        Ok(self
+            .snapshots
+            .read()
+            .await
+            .get(&session_id.to_string())
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn delete_snapshot(
+        &self,
+        session_id: &SessionId,
+        snapshot_id: Uuid,
+    ) -> SessionResult<bool> {
+        let mut snapshots = self.snapshots.write().await;
+        let Some(stored) = snapshots.get_mut(&session_id.to_string()) else {
+            return Ok(false);
+        };
+        let before = stored.len();
+        stored.retain(|s| s.id != snapshot_id);
+        Ok(stored.len() != before)
+    }
 }
 
 pub struct PersistenceFactory;
@@ -267,6 +359,20 @@ impl PersistenceFactory {
             super::persistence_jsonl::JsonlPersistence::default_config().await?,
         ))
     }
+
+    /// Create a SQLite persistence backend (requires `sqlite` feature).
+    #[cfg(feature = "sqlite")]
+    pub async fn sqlite(
+        config: super::persistence_sqlite::SqliteConfig,
+    ) -> SessionResult<Arc<dyn Persistence>> {
+        Ok(Arc::new(
+            super::persistence_sqlite::SqlitePersistence::open_with_config(config)
+                .await
+                .map_err(|e| SessionError::Storage {
+                    message: e.to_string(),
+                })?,
+        ))
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,901 @@
+//! Object-store persistence backend (S3, GCS, Azure Blob, local or in-memory).
+//!
+//! Every session gets its own key space, so no database is needed:
+//!
+//! ```text
+//! {prefix}/sessions/{session_id}/session.json
+//! {prefix}/sessions/{session_id}/summaries/{created_ms}-{seq}-{summary_id}.json
+//! {prefix}/sessions/{session_id}/snapshots/{created_ms}-{seq}-{snapshot_id}.json
+//! {prefix}/sessions/{session_id}/queue/{item_id}.json
+//! {prefix}/tenants/{tenant_id}/{session_id}      (tenant index marker)
+//! {prefix}/queue/{item_id}                        (item -> session index)
+//! ```
+//!
+//! Writes use conditional puts keyed on the ETag last seen by this instance.
+//! Saving a session that another writer changed in the meantime fails with
+//! [`SessionError::Conflict`]; read-modify-write operations (`add_message`,
+//! `dequeue`, ...) retry on conflict instead. The store must support
+//! `PutMode::Update` (S3, GCS, Azure and `InMemory` do; `LocalFileSystem`
+//! does not).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+use futures::{StreamExt, TryStreamExt};
+use object_store::path::Path;
+use object_store::{ObjectStore, PutMode, PutOptions, PutPayload, UpdateVersion};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use super::persistence::Persistence;
+use super::state::{Session, SessionId, SessionMessage, SessionSnapshot};
+use super::types::{QueueItem, QueueStatus, SummarySnapshot};
+use super::{SessionError, SessionResult, StorageResultExt};
+use crate::common::clock;
+
+const SESSION_FILE: &str = "session.json";
+
+/// Orders summaries and snapshots written in the same millisecond.
+static KEY_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// Object name that sorts by creation time, then by write order within this
+/// process.
+fn ordered_key(created_at: chrono::DateTime<chrono::Utc>, id: &Uuid) -> String {
+    format!(
+        "{:016}-{:020}-{}.json",
+        created_at.timestamp_millis(),
+        KEY_SEQUENCE.fetch_add(1, Ordering::Relaxed),
+        id
+    )
+}
+
+/// Object-store persistence configuration.
+#[derive(Clone, Debug)]
+pub struct ObjectStoreConfig {
+    /// Key prefix all objects are written under (default: `claude`).
+    pub prefix: String,
+    /// Sessions without explicit TTL that haven't been updated within this
+    /// period are removed by `cleanup_expired()` (default: 30).
+    pub retention_days: u32,
+    /// Attempts for read-modify-write operations losing an ETag race.
+    pub max_conflict_retries: u32,
+    /// Concurrent requests used by [`WriteBatch::commit`].
+    pub write_concurrency: usize,
+}
+
+impl Default for ObjectStoreConfig {
+    fn default() -> Self {
+        Self {
+            prefix: "claude".to_string(),
+            retention_days: 30,
+            max_conflict_retries: 5,
+            write_concurrency: 8,
+        }
+    }
+}
+
+impl ObjectStoreConfig {
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    pub fn retention_days(mut self, days: u32) -> Self {
+        self.retention_days = days;
+        self
+    }
+
+    pub fn max_conflict_retries(mut self, retries: u32) -> Self {
+        self.max_conflict_retries = retries;
+        self
+    }
+
+    pub fn write_concurrency(mut self, concurrency: usize) -> Self {
+        self.write_concurrency = concurrency.max(1);
+        self
+    }
+}
+
+/// Version and tenant of a session as last read or written by this instance.
+#[derive(Clone, Debug)]
+struct Known {
+    version: UpdateVersion,
+    tenant_id: Option<String>,
+}
+
+/// Session persistence over any [`ObjectStore`].
+pub struct ObjectStorePersistence {
+    store: Arc<dyn ObjectStore>,
+    root: Path,
+    config: ObjectStoreConfig,
+    known: RwLock<HashMap<SessionId, Known>>,
+}
+
+impl ObjectStorePersistence {
+    pub fn new(store: Arc<dyn ObjectStore>) -> Self {
+        Self::with_config(store, ObjectStoreConfig::default())
+    }
+
+    pub fn with_config(store: Arc<dyn ObjectStore>, config: ObjectStoreConfig) -> Self {
+        Self {
+            root: Path::from(config.prefix.as_str()),
+            store,
+            config,
+            known: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Create from a URL such as `s3://bucket/prefix`, `gs://bucket/prefix`
+    /// or `az://container/prefix`. The URL path is used as the key prefix.
+    ///
+    /// Options are passed to the store builder (e.g. `aws_region`); the
+    /// matching `object-store-*` feature must be enabled for cloud schemes.
+    pub fn from_url<I, K, V>(url: &str, options: I) -> SessionResult<Self>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: Into<String>,
+    {
+        let url = url::Url::parse(url).storage_err_ctx("invalid object store URL")?;
+        let (store, path) = object_store::parse_url_opts(&url, options).storage_err()?;
+        let config = ObjectStoreConfig::default().prefix(path.as_ref());
+        Ok(Self::with_config(Arc::from(store), config))
+    }
+
+    pub fn store(&self) -> &Arc<dyn ObjectStore> {
+        &self.store
+    }
+
+    pub fn config(&self) -> &ObjectStoreConfig {
+        &self.config
+    }
+
+    /// Start a batch of session writes, flushed together by [`WriteBatch::commit`].
+    pub fn batch(&self) -> WriteBatch<'_> {
+        WriteBatch {
+            persistence: self,
+            sessions: HashMap::new(),
+        }
+    }
+
+    // ========================================================================
+    // Key layout
+    // ========================================================================
+
+    fn session_dir(&self, id: &SessionId) -> Path {
+        self.root.child("sessions").child(id.to_string())
+    }
+
+    fn session_path(&self, id: &SessionId) -> Path {
+        self.session_dir(id).child(SESSION_FILE)
+    }
+
+    fn summary_path(&self, snapshot: &SummarySnapshot) -> Path {
+        self.session_dir(&snapshot.session_id)
+            .child("summaries")
+            .child(ordered_key(snapshot.created_at, &snapshot.id))
+    }
+
+    fn snapshot_path(&self, snapshot: &SessionSnapshot) -> Path {
+        self.session_dir(&snapshot.session_id)
+            .child("snapshots")
+            .child(ordered_key(snapshot.created_at, &snapshot.id))
+    }
+
+    fn queue_path(&self, session_id: &SessionId, item_id: &Uuid) -> Path {
+        self.session_dir(session_id)
+            .child("queue")
+            .child(format!("{item_id}.json"))
+    }
+
+    fn queue_index_path(&self, item_id: &Uuid) -> Path {
+        self.root.child("queue").child(item_id.to_string())
+    }
+
+    fn tenant_path(&self, tenant_id: &str, id: &SessionId) -> Path {
+        self.root
+            .child("tenants")
+            .child(tenant_id)
+            .child(id.to_string())
+    }
+
+    // ========================================================================
+    // Object helpers
+    // ========================================================================
+
+    async fn get_json<T: DeserializeOwned>(
+        &self,
+        path: &Path,
+    ) -> SessionResult<Option<(T, UpdateVersion)>> {
+        let result = match self.store.get(path).await {
+            Ok(result) => result,
+            Err(object_store::Error::NotFound { .. }) => return Ok(None),
+            Err(e) => return Err(e).storage_err(),
+        };
+        let version = UpdateVersion {
+            e_tag: result.meta.e_tag.clone(),
+            version: result.meta.version.clone(),
+        };
+        let bytes = result.bytes().await.storage_err()?;
+        Ok(Some((serde_json::from_slice(&bytes)?, version)))
+    }
+
+    async fn put_json<T: Serialize>(
+        &self,
+        path: &Path,
+        value: &T,
+        mode: PutMode,
+    ) -> SessionResult<std::result::Result<UpdateVersion, object_store::Error>> {
+        let payload = PutPayload::from(serde_json::to_vec(value)?);
+        Ok(self
+            .store
+            .put_opts(path, payload, PutOptions::from(mode))
+            .await
+            .map(|result| UpdateVersion {
+                e_tag: result.e_tag,
+                version: result.version,
+            }))
+    }
+
+    async fn delete_object(&self, path: &Path) -> SessionResult<()> {
+        match self.store.delete(path).await {
+            Ok(()) | Err(object_store::Error::NotFound { .. }) => Ok(()),
+            Err(e) => Err(e).storage_err(),
+        }
+    }
+
+    /// Conditionally rewrite the object at `path`, retrying on ETag races.
+    ///
+    /// `apply` returns `None` to leave the object untouched; otherwise its
+    /// output is returned with the new version. Returns `None` when the
+    /// object does not exist.
+    async fn update<T, R, F>(
+        &self,
+        path: &Path,
+        mut apply: F,
+    ) -> SessionResult<Option<(T, Option<(R, UpdateVersion)>)>>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnMut(&mut T) -> Option<R>,
+    {
+        let mut attempt = 0;
+        loop {
+            let Some((mut value, version)) = self.get_json::<T>(path).await? else {
+                return Ok(None);
+            };
+            let Some(output) = apply(&mut value) else {
+                return Ok(Some((value, None)));
+            };
+            match self
+                .put_json(path, &value, PutMode::Update(version))
+                .await?
+            {
+                Ok(version) => return Ok(Some((value, Some((output, version))))),
+                Err(object_store::Error::Precondition { .. })
+                    if attempt < self.config.max_conflict_retries =>
+                {
+                    attempt += 1;
+                    tracing::debug!(%path, attempt, "Retrying object update after ETag conflict");
+                }
+                Err(object_store::Error::Precondition { .. }) => {
+                    return Err(SessionError::Conflict {
+                        id: path.to_string(),
+                    });
+                }
+                Err(e) => return Err(e).storage_err(),
+            }
+        }
+    }
+
+    async fn list_paths(&self, prefix: &Path) -> SessionResult<Vec<Path>> {
+        self.store
+            .list(Some(prefix))
+            .map_ok(|meta| meta.location)
+            .try_collect()
+            .await
+            .storage_err()
+    }
+
+    async fn sync_tenant(&self, session: &Session, previous: Option<&Known>) -> SessionResult<()> {
+        let previous_tenant = previous.and_then(|k| k.tenant_id.as_deref());
+        if previous.is_some() && previous_tenant == session.tenant_id.as_deref() {
+            return Ok(());
+        }
+        if let Some(old) = previous_tenant {
+            self.delete_object(&self.tenant_path(old, &session.id))
+                .await?;
+        }
+        if let Some(tenant) = &session.tenant_id {
+            self.store
+                .put(&self.tenant_path(tenant, &session.id), PutPayload::new())
+                .await
+                .storage_err()?;
+        }
+        Ok(())
+    }
+
+    async fn remember(&self, session: &Session, version: UpdateVersion) {
+        self.known.write().await.insert(
+            session.id,
+            Known {
+                version,
+                tenant_id: session.tenant_id.clone(),
+            },
+        );
+    }
+
+    async fn write_session(&self, session: &Session) -> SessionResult<()> {
+        let previous = self.known.read().await.get(&session.id).cloned();
+        let mode = match &previous {
+            Some(known) => PutMode::Update(known.version.clone()),
+            None => PutMode::Create,
+        };
+
+        match self
+            .put_json(&self.session_path(&session.id), session, mode)
+            .await?
+        {
+            Ok(version) => {
+                self.sync_tenant(session, previous.as_ref()).await?;
+                self.remember(session, version).await;
+                Ok(())
+            }
+            Err(object_store::Error::Precondition { .. })
+            | Err(object_store::Error::AlreadyExists { .. }) => Err(SessionError::Conflict {
+                id: session.id.to_string(),
+            }),
+            Err(e) => Err(e).storage_err(),
+        }
+    }
+
+    async fn queue_items(&self, session_id: &SessionId) -> SessionResult<Vec<QueueItem>> {
+        let prefix = self.session_dir(session_id).child("queue");
+        let mut items = Vec::new();
+        for path in self.list_paths(&prefix).await? {
+            if let Some((item, _)) = self.get_json::<QueueItem>(&path).await? {
+                items.push(item);
+            }
+        }
+        items.sort_by(|a, b| {
+            b.priority
+                .cmp(&a.priority)
+                .then(a.created_at.cmp(&b.created_at))
+        });
+        Ok(items)
+    }
+
+    async fn delete_session_objects(
+        &self,
+        id: &SessionId,
+        tenant_id: Option<&str>,
+    ) -> SessionResult<()> {
+        let paths = self.list_paths(&self.session_dir(id)).await?;
+        let queue_dir = self.session_dir(id).child("queue");
+        let mut indexes = Vec::new();
+        for path in &paths {
+            if path.prefix_matches(&queue_dir)
+                && let Some(item_id) = path
+                    .filename()
+                    .and_then(|f| f.strip_suffix(".json"))
+                    .and_then(|f| Uuid::parse_str(f).ok())
+            {
+                indexes.push(self.queue_index_path(&item_id));
+            }
+        }
+        if let Some(tenant) = tenant_id {
+            indexes.push(self.tenant_path(tenant, id));
+        }
+
+        let locations = futures::stream::iter(paths.into_iter().chain(indexes).map(Ok)).boxed();
+        let results: Vec<_> = self.store.delete_stream(locations).collect().await;
+        for result in results {
+            match result {
+                Ok(_) | Err(object_store::Error::NotFound { .. }) => {}
+                Err(e) => return Err(e).storage_err(),
+            }
+        }
+        self.known.write().await.remove(id);
+        Ok(())
+    }
+
+    /// Locate a snapshot object by id; keys also carry the creation time.
+    async fn find_snapshot(
+        &self,
+        session_id: &SessionId,
+        snapshot_id: Uuid,
+    ) -> SessionResult<Option<Path>> {
+        let suffix = format!("-{snapshot_id}.json");
+        let prefix = self.session_dir(session_id).child("snapshots");
+        Ok(self
+            .list_paths(&prefix)
+            .await?
+            .into_iter()
+            .find(|p| p.filename().is_some_and(|f| f.ends_with(&suffix))))
+    }
+
+    async fn session_ids(&self) -> SessionResult<Vec<SessionId>> {
+        let listing = self
+            .store
+            .list_with_delimiter(Some(&self.root.child("sessions")))
+            .await
+            .storage_err()?;
+        Ok(listing
+            .common_prefixes
+            .iter()
+            .filter_map(|p| p.filename().and_then(SessionId::parse))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl Persistence for ObjectStorePersistence {
+    fn name(&self) -> &str {
+        "object-store"
+    }
+
+    async fn save(&self, session: &Session) -> SessionResult<()> {
+        self.write_session(session).await
+    }
+
+    async fn load(&self, id: &SessionId) -> SessionResult<Option<Session>> {
+        let Some((session, version)) = self.get_json::<Session>(&self.session_path(id)).await?
+        else {
+            return Ok(None);
+        };
+        self.remember(&session, version).await;
+        Ok(Some(session))
+    }
+
+    async fn delete(&self, id: &SessionId) -> SessionResult<bool> {
+        let Some((session, _)) = self.get_json::<Session>(&self.session_path(id)).await? else {
+            return Ok(false);
+        };
+        self.delete_session_objects(id, session.tenant_id.as_deref())
+            .await?;
+        Ok(true)
+    }
+
+    async fn list(&self, tenant_id: Option<&str>) -> SessionResult<Vec<SessionId>> {
+        match tenant_id {
+            Some(tenant) => Ok(self
+                .list_paths(&self.root.child("tenants").child(tenant))
+                .await?
+                .iter()
+                .filter_map(|p| p.filename().and_then(SessionId::parse))
+                .collect()),
+            None => self.session_ids().await,
+        }
+    }
+
+    async fn add_message(
+        &self,
+        session_id: &SessionId,
+        message: SessionMessage,
+    ) -> SessionResult<()> {
+        let path = self.session_path(session_id);
+        let updated = self
+            .update::<Session, _, _>(&path, |session| {
+                session.add_message(message.clone());
+                Some(())
+            })
+            .await?;
+        match updated {
+            Some((session, Some(((), version)))) => {
+                self.remember(&session, version).await;
+                Ok(())
+            }
+            Some((_, None)) => Ok(()),
+            None => Err(SessionError::NotFound {
+                id: session_id.to_string(),
+            }),
+        }
+    }
+
+    async fn add_summary(&self, snapshot: SummarySnapshot) -> SessionResult<()> {
+        match self
+            .put_json(&self.summary_path(&snapshot), &snapshot, PutMode::Overwrite)
+            .await?
+        {
+            Ok(_) => Ok(()),
+            Err(e) => Err(e).storage_err(),
+        }
+    }
+
+    async fn get_summaries(&self, session_id: &SessionId) -> SessionResult<Vec<SummarySnapshot>> {
+        let prefix = self.session_dir(session_id).child("summaries");
+        let mut paths = self.list_paths(&prefix).await?;
+        // Keys start with the zero-padded creation time and write sequence.
+        paths.sort();
+        let mut summaries = Vec::with_capacity(paths.len());
+        for path in paths {
+            if let Some((summary, _)) = self.get_json(&path).await? {
+                summaries.push(summary);
+            }
+        }
+        Ok(summaries)
+    }
+
+    async fn save_snapshot(&self, snapshot: &SessionSnapshot) -> SessionResult<()> {
+        // Saving a snapshot again replaces it in place, keeping its position.
+        let path = match self
+            .find_snapshot(&snapshot.session_id, snapshot.id)
+            .await?
+        {
+            Some(path) => path,
+            None => self.snapshot_path(snapshot),
+        };
+        match self.put_json(&path, snapshot, PutMode::Overwrite).await? {
+            Ok(_) => Ok(()),
+            Err(e) => Err(e).storage_err(),
+        }
+    }
+
+    async fn load_snapshot(
+        &self,
+        session_id: &SessionId,
+        snapshot_id: Uuid,
+    ) -> SessionResult<Option<SessionSnapshot>> {
+        let Some(path) = self.find_snapshot(session_id, snapshot_id).await? else {
+            return Ok(None);
+        };
+        Ok(self.get_json(&path).await?.map(|(snapshot, _)| snapshot))
+    }
+
+    async fn list_snapshots(&self, session_id: &SessionId) -> SessionResult<Vec<SessionSnapshot>> {
+        let prefix = self.session_dir(session_id).child("snapshots");
+        let mut paths = self.list_paths(&prefix).await?;
+        paths.sort();
+        let mut snapshots = Vec::with_capacity(paths.len());
+        for path in paths {
+            if let Some((snapshot, _)) = self.get_json(&path).await? {
+                snapshots.push(snapshot);
+            }
+        }
+        Ok(snapshots)
+    }
+
+    async fn delete_snapshot(
+        &self,
+        session_id: &SessionId,
+        snapshot_id: Uuid,
+    ) -> SessionResult<bool> {
+        let Some(path) = self.find_snapshot(session_id, snapshot_id).await? else {
+            return Ok(false);
+        };
+        self.delete_object(&path).await?;
+        Ok(true)
+    }
+
+    async fn enqueue(
+        &self,
+        session_id: &SessionId,
+        content: String,
+        priority: i32,
+    ) -> SessionResult<QueueItem> {
+        let item = QueueItem::enqueue(*session_id, content).priority(priority);
+        self.store
+            .put(
+                &self.queue_index_path(&item.id),
+                PutPayload::from(session_id.to_string()),
+            )
+            .await
+            .storage_err()?;
+        match self
+            .put_json(
+                &self.queue_path(session_id, &item.id),
+                &item,
+                PutMode::Create,
+            )
+            .await?
+        {
+            Ok(_) => Ok(item),
+            Err(e) => Err(e).storage_err(),
+        }
+    }
+
+    async fn dequeue(&self, session_id: &SessionId) -> SessionResult<Option<QueueItem>> {
+        for candidate in self.queue_items(session_id).await? {
+            if candidate.status != QueueStatus::Pending {
+                continue;
+            }
+            let path = self.queue_path(session_id, &candidate.id);
+            // Another consumer may claim the item first; move on if so.
+            let claimed = self
+                .update::<QueueItem, _, _>(&path, |item| {
+                    (item.status == QueueStatus::Pending).then(|| {
+                        item.start_processing();
+                    })
+                })
+                .await?;
+            if let Some((item, Some(_))) = claimed {
+                return Ok(Some(item));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn cancel_queued(&self, item_id: Uuid) -> SessionResult<bool> {
+        let index = self.queue_index_path(&item_id);
+        let session_id = match self.store.get(&index).await {
+            Ok(result) => {
+                let bytes = result.bytes().await.storage_err()?;
+                match std::str::from_utf8(&bytes).ok().and_then(SessionId::parse) {
+                    Some(id) => id,
+                    None => return Ok(false),
+                }
+            }
+            Err(object_store::Error::NotFound { .. }) => return Ok(false),
+            Err(e) => return Err(e).storage_err(),
+        };
+
+        let cancelled = self
+            .update::<QueueItem, _, _>(&self.queue_path(&session_id, &item_id), |item| {
+                (item.status == QueueStatus::Pending).then(|| item.cancel())
+            })
+            .await?;
+        Ok(matches!(cancelled, Some((_, Some(_)))))
+    }
+
+    async fn pending_queue(&self, session_id: &SessionId) -> SessionResult<Vec<QueueItem>> {
+        Ok(self
+            .queue_items(session_id)
+            .await?
+            .into_iter()
+            .filter(|item| item.status == QueueStatus::Pending)
+            .collect())
+    }
+
+    async fn cleanup_expired(&self) -> SessionResult<usize> {
+        let cutoff = clock::now_utc() - chrono::Duration::days(self.config.retention_days as i64);
+        let mut removed = 0;
+        for id in self.session_ids().await? {
+            let Some((session, _)) = self.get_json::<Session>(&self.session_path(&id)).await?
+            else {
+                continue;
+            };
+            if session.is_expired() || session.updated_at < cutoff {
+                self.delete_session_objects(&id, session.tenant_id.as_deref())
+                    .await?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Session writes buffered in memory and flushed concurrently.
+///
+/// Saving the same session several times keeps only the latest state, so a
+/// burst of updates costs one request per session.
+pub struct WriteBatch<'a> {
+    persistence: &'a ObjectStorePersistence,
+    sessions: HashMap<SessionId, Session>,
+}
+
+impl WriteBatch<'_> {
+    pub fn save(&mut self, session: &Session) -> &mut Self {
+        self.sessions.insert(session.id, session.clone());
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// Write all buffered sessions, returning how many were written.
+    ///
+    /// Every session is attempted; the first error is returned afterwards.
+    pub async fn commit(self) -> SessionResult<usize> {
+        let persistence = self.persistence;
+        let results: Vec<_> = futures::stream::iter(self.sessions.into_values())
+            .map(|session| async move { persistence.write_session(&session).await })
+            .buffer_unordered(persistence.config.write_concurrency)
+            .collect()
+            .await;
+        let written = results.iter().filter(|r| r.is_ok()).count();
+        match results.into_iter().find_map(Result::err) {
+            Some(e) => Err(e),
+            None => Ok(written),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::state::SessionConfig;
+    use crate::types::ContentBlock;
+    use object_store::memory::InMemory;
+
+    fn persistence(store: &Arc<InMemory>) -> ObjectStorePersistence {
+        ObjectStorePersistence::new(store.clone())
+    }
+
+    #[tokio::test]
+    async fn test_roundtrip_and_tenant_index() {
+        let store = Arc::new(InMemory::new());
+        let persistence = persistence(&store);
+
+        let mut session = Session::new(SessionConfig::default());
+        session.tenant_id = Some("tenant-a".into());
+        persistence.save(&session).await.unwrap();
+        persistence.save(&session).await.unwrap();
+        persistence
+            .add_message(
+                &session.id,
+                SessionMessage::user(vec![ContentBlock::text("Hi")]),
+            )
+            .await
+            .unwrap();
+        let other = Session::new(SessionConfig::default());
+        persistence.save(&other).await.unwrap();
+
+        let loaded = persistence.load(&session.id).await.unwrap().unwrap();
+        assert_eq!(loaded.messages.len(), 1);
+        assert_eq!(persistence.list(None).await.unwrap().len(), 2);
+        assert_eq!(
+            persistence.list(Some("tenant-a")).await.unwrap(),
+            vec![session.id]
+        );
+
+        assert!(persistence.delete(&session.id).await.unwrap());
+        assert!(persistence.load(&session.id).await.unwrap().is_none());
+        assert!(persistence.list(Some("tenant-a")).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_save_conflicts() {
+        let store = Arc::new(InMemory::new());
+        let writer_a = persistence(&store);
+        let writer_b = persistence(&store);
+
+        let session = Session::new(SessionConfig::default());
+        writer_a.save(&session).await.unwrap();
+        let mut seen_by_b = writer_b.load(&session.id).await.unwrap().unwrap();
+        seen_by_b.summary = Some("from b".into());
+        writer_b.save(&seen_by_b).await.unwrap();
+
+        assert!(matches!(
+            writer_a.save(&session).await,
+            Err(SessionError::Conflict { .. })
+        ));
+        // Creating over an existing session without loading it also conflicts.
+        assert!(matches!(
+            persistence(&store).save(&session).await,
+            Err(SessionError::Conflict { .. })
+        ));
+
+        let mut reloaded = writer_a.load(&session.id).await.unwrap().unwrap();
+        assert_eq!(reloaded.summary.as_deref(), Some("from b"));
+        reloaded.summary = Some("from a".into());
+        writer_a.save(&reloaded).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_queue_and_summaries() {
+        let store = Arc::new(InMemory::new());
+        let persistence = persistence(&store);
+        let id = SessionId::new();
+
+        persistence.enqueue(&id, "Low".into(), 1).await.unwrap();
+        let high = persistence.enqueue(&id, "High".into(), 10).await.unwrap();
+        let dropped = persistence.enqueue(&id, "Drop".into(), 5).await.unwrap();
+        assert!(persistence.cancel_queued(dropped.id).await.unwrap());
+        assert!(!persistence.cancel_queued(dropped.id).await.unwrap());
+
+        let next = persistence.dequeue(&id).await.unwrap().unwrap();
+        assert_eq!(next.id, high.id);
+        assert_eq!(next.status, QueueStatus::Processing);
+        let pending = persistence.pending_queue(&id).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].content, "Low");
+
+        persistence
+            .add_summary(SummarySnapshot::new(id, "First"))
+            .await
+            .unwrap();
+        persistence
+            .add_summary(SummarySnapshot::new(id, "Second"))
+            .await
+            .unwrap();
+        let summaries = persistence.get_summaries(&id).await.unwrap();
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[1].summary, "Second");
+    }
+
+    #[tokio::test]
+    async fn test_snapshots_and_delete() {
+        let store = Arc::new(InMemory::new());
+        let persistence = persistence(&store);
+        let session = Session::new(SessionConfig::default());
+        persistence.save(&session).await.unwrap();
+
+        let first = session.snapshot_labeled("first");
+        persistence.save_snapshot(&first).await.unwrap();
+        let second = session.snapshot();
+        persistence.save_snapshot(&second).await.unwrap();
+        persistence.save_snapshot(&first).await.unwrap();
+        let ids: Vec<_> = persistence
+            .list_snapshots(&session.id)
+            .await
+            .unwrap()
+            .iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(ids, [first.id, second.id]);
+
+        let loaded = persistence
+            .load_snapshot(&session.id, first.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.label.as_deref(), Some("first"));
+        assert!(
+            persistence
+                .delete_snapshot(&session.id, first.id)
+                .await
+                .unwrap()
+        );
+        let listed = persistence.list_snapshots(&session.id).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, second.id);
+
+        assert!(persistence.delete(&session.id).await.unwrap());
+        assert!(
+            persistence
+                .list_snapshots(&session.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_coalesces_writes() {
+        let store = Arc::new(InMemory::new());
+        let persistence = persistence(&store);
+
+        let mut first = Session::new(SessionConfig::default());
+        let second = Session::new(SessionConfig::default());
+        let mut batch = persistence.batch();
+        batch.save(&first);
+        first.summary = Some("latest".into());
+        batch.save(&first).save(&second);
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch.commit().await.unwrap(), 2);
+
+        let loaded = persistence.load(&first.id).await.unwrap().unwrap();
+        assert_eq!(loaded.summary.as_deref(), Some("latest"));
+        assert_eq!(persistence.list(None).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_expired() {
+        let store = Arc::new(InMemory::new());
+        let persistence = persistence(&store);
+        let expiring = Session::new(SessionConfig {
+            ttl_secs: Some(0),
+            ..Default::default()
+        });
+        let kept = Session::new(SessionConfig::default());
+        persistence.save(&expiring).await.unwrap();
+        persistence.save(&kept).await.unwrap();
+        let item = persistence
+            .enqueue(&expiring.id, "pending".into(), 0)
+            .await
+            .unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+        assert_eq!(persistence.cleanup_expired().await.unwrap(), 1);
+        assert_eq!(persistence.list(None).await.unwrap(), vec![kept.id]);
+        assert!(!persistence.cancel_queued(item.id).await.unwrap());
+    }
+}
@@ -39,6 +39,7 @@ use sqlx::postgres::PgPoolOptions;
 use sqlx::{PgPool, Postgres, Row, Transaction};
 use uuid::Uuid;
 
+use super::compression::{self, CompressionConfig};
 use super::persistence::Persistence;
 use super::state::{Session, SessionConfig, SessionId, SessionMessage};
 use super::types::{CompactRecord, Plan, QueueItem, QueueStatus, SummarySnapshot, TodoItem};
@@ -132,6 +133,8 @@ pub struct PostgresConfig {
     /// Sessions without explicit TTL that haven't been updated within
     /// this period are cleaned up by `cleanup_expired()`.
     pub retention_days: u32,
+    /// Compress large message content (default: off). See [`super::compression`].
+    pub compression: Option<CompressionConfig>,
 }
 
 impl Default for PostgresConfig {
@@ -164,6 +167,7 @@ impl PostgresConfig {
             plans_table: format!("{prefix}plans"),
             pool: PgPoolConfig::default(),
             retention_days: 30,
+            compression: None,
         })
     }
 
@@ -177,6 +181,13 @@ impl PostgresConfig {
         self
     }
 
+    /// Store large message content zstd-compressed. Existing uncompressed
+    /// rows keep loading; requires the `compression` feature.
+    pub fn compression(mut self, config: CompressionConfig) -> Self {
+        self.compression = Some(config);
+        self
+    }
+
     /// Get all table names.
     pub fn table_names(&self) -> Vec<&str> {
         vec![
@@ -254,6 +265,7 @@ impl PostgresSchema {
     id VARCHAR(255) PRIMARY KEY,
     parent_id VARCHAR(255),
     tenant_id VARCHAR(255),
+    owner VARCHAR(255),
     session_type VARCHAR(32) NOT NULL DEFAULT 'main',
     state VARCHAR(32) NOT NULL DEFAULT 'created',
     mode VARCHAR(32) NOT NULL DEFAULT 'default',
@@ -273,6 +285,11 @@ impl PostgresSchema {
 );"#,
                 sessions = c.sessions_table
             ),
+            // Added after the initial schema; upgrades existing tables.
+            format!(
+                "ALTER TABLE {} ADD COLUMN IF NOT EXISTS owner VARCHAR(255);",
+                c.sessions_table
+            ),
             format!(
                 r#"CREATE TABLE IF NOT EXISTS {messages} (
     id VARCHAR(255) PRIMARY KEY,
@@ -653,7 +670,7 @@ impl PostgresPersistence {
 
         let row = sqlx::query(&format!(
             r#"
-            SELECT id, parent_id, tenant_id, session_type, state, mode,
+            SELECT id, parent_id, tenant_id, owner, session_type, state, mode,
                    config, permissions, summary,
                    total_input_tokens, total_output_tokens, total_cost_usd,
                    current_leaf_id, static_context_hash, error,
@@ -724,6 +741,7 @@ impl PostgresPersistence {
                 .and_then(|s| s.parse().ok()),
             session_type,
             tenant_id: row.try_get("tenant_id").ok(),
+            owner: row.try_get("owner").ok(),
             state,
             config,
             permissions,
@@ -747,6 +765,7 @@ impl PostgresPersistence {
             todos,
             current_plan: plan,
             compact_history: VecDeque::from(compacts),
+            change_log: VecDeque::new(),
         })
     }
 
@@ -787,14 +806,19 @@ impl PostgresPersistence {
                 }
             };
 
-            let content: Vec<crate::types::ContentBlock> = match row
+            let metadata_value = row.try_get::<serde_json::Value, _>("metadata").ok();
+            let encoding = metadata_value
+                .as_ref()
+                .and_then(compression::content_encoding);
+
+            let content = match row
                 .try_get::<serde_json::Value, _>("content")
-                .ok()
-                .and_then(|v| serde_json::from_value(v).ok())
+                .storage_err_ctx("read content column")
+                .and_then(|v| compression::decode_content(v, encoding))
             {
-                Some(c) => c,
-                None => {
-                    tracing::warn!(id = id_str, "Skipping message row: failed to parse content");
+                Ok(c) => c,
+                Err(e) => {
+                    tracing::warn!(id = id_str, error = %e, "Skipping message row: failed to parse content");
                     continue;
                 }
             };
@@ -813,12 +837,12 @@ impl PostgresPersistence {
                 .ok()
                 .and_then(|v| serde_json::from_value(v).ok());
 
-            let metadata = match row.try_get::<serde_json::Value, _>("metadata") {
-                Ok(v) => serde_json::from_value(v).unwrap_or_else(|e| {
+            let metadata = match metadata_value {
+                Some(v) => serde_json::from_value(v).unwrap_or_else(|e| {
                     tracing::warn!(id = id_str, error = %e, "Failed to deserialize message metadata");
                     Default::default()
                 }),
-                Err(_) => Default::default(),
+                None => Default::default(),
             };
 
             let environment = row
@@ -1193,6 +1217,13 @@ impl PostgresPersistence {
         // Upsert each message (INSERT ... ON CONFLICT (id) DO UPDATE)
         for message in messages {
             let role = enum_to_db(&message.role, "user");
+            let (content, encoding) =
+                compression::encode_content(&message.content, c.compression.as_ref())?;
+            let mut metadata = serde_json::to_value(&message.metadata).unwrap_or_else(|e| {
+                tracing::warn!(message_id = %message.id, error = %e, "Failed to serialize message metadata");
+                serde_json::Value::Object(Default::default())
+            });
+            compression::set_content_encoding(&mut metadata, encoding);
 
             sqlx::query(&format!(
                 r#"
@@ -1219,10 +1250,7 @@ impl PostgresPersistence {
             .bind(session_id.to_string())
             .bind(message.parent_id.as_ref().map(|id| id.to_string()))
             .bind(&role)
-            .bind(serde_json::to_value(&message.content).unwrap_or_else(|e| {
-                tracing::warn!(message_id = %message.id, error = %e, "Failed to serialize message content");
-                serde_json::Value::Array(Vec::new())
-            }))
+            .bind(content)
             .bind(message.is_sidechain)
             .bind(message.is_compact_summary)
             .bind(&message.metadata.model)
@@ -1233,10 +1261,7 @@ impl PostgresPersistence {
                     .as_ref()
                     .and_then(|u| serde_json::to_value(u).ok()),
             )
-            .bind(serde_json::to_value(&message.metadata).unwrap_or_else(|e| {
-                tracing::warn!(message_id = %message.id, error = %e, "Failed to serialize message metadata");
-                serde_json::Value::Object(Default::default())
-            }))
+            .bind(metadata)
             .bind(
                 message
                     .environment
@@ -1283,14 +1308,15 @@ impl PostgresPersistence {
                 config, permissions, summary,
                 total_input_tokens, total_output_tokens, total_cost_usd,
                 current_leaf_id, static_context_hash, error,
-                created_at, updated_at, expires_at
+                created_at, updated_at, expires_at, owner
             ) VALUES (
                 $1, $2, $3, $4, $5, $6, $7, $8, $9,
-                $10, $11, $12, $13, $14, $15, $16, $17, $18
+                $10, $11, $12, $13, $14, $15, $16, $17, $18, $19
             )
             ON CONFLICT (id) DO UPDATE SET
                 parent_id = EXCLUDED.parent_id,
                 tenant_id = EXCLUDED.tenant_id,
+                owner = EXCLUDED.owner,
                 session_type = EXCLUDED.session_type,
                 state = EXCLUDED.state,
                 mode = EXCLUDED.mode,
@@ -1332,6 +1358,7 @@ impl PostgresPersistence {
         .bind(session.created_at)
         .bind(session.updated_at)
         .bind(session.expires_at)
+        .bind(&session.owner)
         .execute(&mut *tx)
         .await
         .storage_err()?;
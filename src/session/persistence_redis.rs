@@ -6,7 +6,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use super::persistence::Persistence;
-use super::state::{Session, SessionId};
+use super::state::{Session, SessionId, SessionSnapshot};
 use super::types::{QueueItem, SummarySnapshot};
 use super::{SessionError, SessionResult, StorageResultExt};
 use uuid::Uuid;
@@ -117,6 +117,10 @@ impl RedisPersistence {
         format!("{}summaries:{}", self.config.key_prefix, session_id)
     }
 
+    fn snapshots_key(&self, session_id: &SessionId) -> String {
+        format!("{}snapshots:{}", self.config.key_prefix, session_id)
+    }
+
     fn queue_key(&self, session_id: &SessionId) -> String {
         format!("{}queue:{}", self.config.key_prefix, session_id)
     }
@@ -285,6 +289,9 @@ impl Persistence for RedisPersistence {
         conn.del::<_, ()>(&self.summaries_key(id))
             .await
             .storage_err()?;
+        conn.del::<_, ()>(&self.snapshots_key(id))
+            .await
+            .storage_err()?;
         conn.del::<_, ()>(&queue_key).await.storage_err()?;
         conn.del::<_, ()>(&self.children_key(id))
             .await
@@ -344,6 +351,62 @@ impl Persistence for RedisPersistence {
             .collect()
     }
 
+    async fn save_snapshot(&self, snapshot: &SessionSnapshot) -> SessionResult<()> {
+        let mut conn = self.get_connection().await?;
+        let key = self.snapshots_key(&snapshot.session_id);
+        let data = serde_json::to_string(snapshot).map_err(SessionError::Serialization)?;
+
+        conn.rpush::<_, _, ()>(&key, &data).await.storage_err()?;
+
+        Ok(())
+    }
+
+    async fn load_snapshot(
+        &self,
+        session_id: &SessionId,
+        snapshot_id: Uuid,
+    ) -> SessionResult<Option<SessionSnapshot>> {
+        Ok(self
+            .list_snapshots(session_id)
+            .await?
+            .into_iter()
+            .find(|s| s.id == snapshot_id))
+    }
+
+    async fn list_snapshots(&self, session_id: &SessionId) -> SessionResult<Vec<SessionSnapshot>> {
+        let mut conn = self.get_connection().await?;
+        let key = self.snapshots_key(session_id);
+
+        let items: Vec<String> = conn.lrange(&key, 0, -1).await.storage_err()?;
+
+        items
+            .into_iter()
+            .map(|json| serde_json::from_str(&json).map_err(SessionError::Serialization))
+            .collect()
+    }
+
+    async fn delete_snapshot(
+        &self,
+        session_id: &SessionId,
+        snapshot_id: Uuid,
+    ) -> SessionResult<bool> {
+        let mut conn = self.get_connection().await?;
+        let key = self.snapshots_key(session_id);
+
+        let items: Vec<String> = conn.lrange(&key, 0, -1).await.storage_err()?;
+        for json in items {
+            let matches = serde_json::from_str::<SessionSnapshot>(&json)
+                .map(|s| s.id == snapshot_id)
+                .unwrap_or(false);
+            if matches {
+                let removed: i32 = conn.lrem(&key, 1, &json).await.storage_err()?;
+                return Ok(removed > 0);
+            }
+        }
+
+        Ok(false)
+    }
+
     async fn enqueue(
         &self,
         session_id: &SessionId,
@@ -439,11 +502,13 @@ impl Persistence for RedisPersistence {
         let mut cleaned = 0;
 
         // Redis auto-expires session keys via TTL, but related data becomes orphaned.
-        // Clean up orphaned summaries, queues, queue_index, children sets, and tenant refs.
+        // Clean up orphaned summaries, snapshots, queues, queue_index, children sets, and tenant refs.
 
-        // 1. Clean orphaned summaries
+        // 1. Clean orphaned summaries and snapshots
         let pattern = format!("{}summaries:*", self.config.key_prefix);
         cleaned += self.cleanup_orphaned_keys(&mut conn, &pattern).await?;
+        let pattern = format!("{}snapshots:*", self.config.key_prefix);
+        cleaned += self.cleanup_orphaned_keys(&mut conn, &pattern).await?;
 
         // 2. Clean orphaned queues and their index entries
         let pattern = format!("{}queue:*", self.config.key_prefix);
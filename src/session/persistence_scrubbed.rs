@@ -0,0 +1,246 @@
+//! Persistence decorator that keeps PII out of stored sessions.
+
+use uuid::Uuid;
+
+use super::SessionResult;
+use super::persistence::Persistence;
+use super::state::{Session, SessionId, SessionMessage, SessionSnapshot};
+use super::types::{QueueItem, SummarySnapshot};
+use crate::security::PiiScrubber;
+
+/// Wraps a backend so message content, summaries, snapshots and queued input are
+/// scrubbed with a [`PiiScrubber`] on the way in and restored on the way out.
+///
+/// Restoring needs the tokens in the scrubber's vault; sessions loaded by a
+/// scrubber without them keep the tokens.
+pub struct ScrubbedPersistence<P> {
+    inner: P,
+    scrubber: PiiScrubber,
+}
+
+impl<P: Persistence> ScrubbedPersistence<P> {
+    pub fn new(inner: P, scrubber: PiiScrubber) -> Self {
+        Self { inner, scrubber }
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    fn scrub_session(&self, session: &Session) -> Session {
+        let mut session = session.clone();
+        for message in &mut session.messages {
+            self.scrubber.scrub_blocks(&mut message.content);
+        }
+        for record in &mut session.compact_history {
+            record.summary = self.scrubber.scrub(&record.summary);
+        }
+        session.summary = session.summary.map(|s| self.scrubber.scrub(&s));
+        session
+    }
+
+    fn restore_session(&self, mut session: Session) -> Session {
+        for message in &mut session.messages {
+            self.scrubber.restore_blocks(&mut message.content);
+        }
+        for record in &mut session.compact_history {
+            record.summary = self.scrubber.restore(&record.summary);
+        }
+        session.summary = session.summary.map(|s| self.scrubber.restore(&s));
+        session
+    }
+
+    fn scrub_snapshot(&self, snapshot: &SessionSnapshot) -> SessionSnapshot {
+        let mut snapshot = snapshot.clone();
+        for message in &mut snapshot.messages {
+            self.scrubber.scrub_blocks(&mut message.content);
+        }
+        for record in &mut snapshot.compact_history {
+            record.summary = self.scrubber.scrub(&record.summary);
+        }
+        snapshot.summary = snapshot.summary.map(|s| self.scrubber.scrub(&s));
+        snapshot
+    }
+
+    fn restore_snapshot(&self, mut snapshot: SessionSnapshot) -> SessionSnapshot {
+        for message in &mut snapshot.messages {
+            self.scrubber.restore_blocks(&mut message.content);
+        }
+        for record in &mut snapshot.compact_history {
+            record.summary = self.scrubber.restore(&record.summary);
+        }
+        snapshot.summary = snapshot.summary.map(|s| self.scrubber.restore(&s));
+        snapshot
+    }
+
+    fn restore_item(&self, mut item: QueueItem) -> QueueItem {
+        item.content = self.scrubber.restore(&item.content);
+        item
+    }
+}
+
+#[async_trait::async_trait]
+impl<P: Persistence> Persistence for ScrubbedPersistence<P> {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn save(&self, session: &Session) -> SessionResult<()> {
+        self.inner.save(&self.scrub_session(session)).await
+    }
+
+    async fn load(&self, id: &SessionId) -> SessionResult<Option<Session>> {
+        Ok(self
+            .inner
+            .load(id)
+            .await?
+            .map(|session| self.restore_session(session)))
+    }
+
+    async fn delete(&self, id: &SessionId) -> SessionResult<bool> {
+        self.inner.delete(id).await
+    }
+
+    async fn list(&self, tenant_id: Option<&str>) -> SessionResult<Vec<SessionId>> {
+        self.inner.list(tenant_id).await
+    }
+
+    async fn add_summary(&self, mut snapshot: SummarySnapshot) -> SessionResult<()> {
+        snapshot.summary = self.scrubber.scrub(&snapshot.summary);
+        self.inner.add_summary(snapshot).await
+    }
+
+    async fn get_summaries(&self, session_id: &SessionId) -> SessionResult<Vec<SummarySnapshot>> {
+        let mut summaries = self.inner.get_summaries(session_id).await?;
+        for snapshot in &mut summaries {
+            snapshot.summary = self.scrubber.restore(&snapshot.summary);
+        }
+        Ok(summaries)
+    }
+
+    async fn enqueue(
+        &self,
+        session_id: &SessionId,
+        content: String,
+        priority: i32,
+    ) -> SessionResult<QueueItem> {
+        let item = self
+            .inner
+            .enqueue(session_id, self.scrubber.scrub(&content), priority)
+            .await?;
+        Ok(self.restore_item(item))
+    }
+
+    async fn dequeue(&self, session_id: &SessionId) -> SessionResult<Option<QueueItem>> {
+        Ok(self
+            .inner
+            .dequeue(session_id)
+            .await?
+            .map(|item| self.restore_item(item)))
+    }
+
+    async fn cancel_queued(&self, item_id: Uuid) -> SessionResult<bool> {
+        self.inner.cancel_queued(item_id).await
+    }
+
+    async fn pending_queue(&self, session_id: &SessionId) -> SessionResult<Vec<QueueItem>> {
+        let items = self.inner.pending_queue(session_id).await?;
+        Ok(items
+            .into_iter()
+            .map(|item| self.restore_item(item))
+            .collect())
+    }
+
+    async fn cleanup_expired(&self) -> SessionResult<usize> {
+        self.inner.cleanup_expired().await
+    }
+
+    async fn save_snapshot(&self, snapshot: &SessionSnapshot) -> SessionResult<()> {
+        self.inner
+            .save_snapshot(&self.scrub_snapshot(snapshot))
+            .await
+    }
+
+    async fn load_snapshot(
+        &self,
+        session_id: &SessionId,
+        snapshot_id: Uuid,
+    ) -> SessionResult<Option<SessionSnapshot>> {
+        Ok(self
+            .inner
+            .load_snapshot(session_id, snapshot_id)
+            .await?
+            .map(|snapshot| self.restore_snapshot(snapshot)))
+    }
+
+    async fn list_snapshots(&self, session_id: &SessionId) -> SessionResult<Vec<SessionSnapshot>> {
+        let snapshots = self.inner.list_snapshots(session_id).await?;
+        Ok(snapshots
+            .into_iter()
+            .map(|snapshot| self.restore_snapshot(snapshot))
+            .collect())
+    }
+
+    async fn delete_snapshot(
+        &self,
+        session_id: &SessionId,
+        snapshot_id: Uuid,
+    ) -> SessionResult<bool> {
+        self.inner.delete_snapshot(session_id, snapshot_id).await
+    }
+
+    async fn add_message(
+        &self,
+        session_id: &SessionId,
+        mut message: SessionMessage,
+    ) -> SessionResult<()> {
+        self.scrubber.scrub_blocks(&mut message.content);
+        self.inner.add_message(session_id, message).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::{MemoryPersistence, SessionConfig};
+    use crate::types::ContentBlock;
+
+    #[tokio::test]
+    async fn test_scrubbed_round_trip() {
+        let scrubber = PiiScrubber::new();
+        let persistence = ScrubbedPersistence::new(MemoryPersistence::new(), scrubber.clone());
+
+        let mut session = Session::new(SessionConfig::default());
+        session.add_message(SessionMessage::user(vec![ContentBlock::text(
+            "I am dave@example.com",
+        )]));
+        persistence.save(&session).await.unwrap();
+        persistence
+            .add_message(
+                &session.id,
+                SessionMessage::user(vec![ContentBlock::text("cc erin@example.com")]),
+            )
+            .await
+            .unwrap();
+
+        let stored = persistence
+            .inner()
+            .load(&session.id)
+            .await
+            .unwrap()
+            .unwrap();
+        let stored = serde_json::to_string(&stored.messages).unwrap();
+        assert!(stored.contains("[EMAIL_1]") && stored.contains("[EMAIL_2]"));
+        assert!(!stored.contains("@example.com"));
+
+        let loaded = persistence.load(&session.id).await.unwrap().unwrap();
+        let loaded = serde_json::to_string(&loaded.messages).unwrap();
+        assert!(loaded.contains("dave@example.com") && loaded.contains("erin@example.com"));
+
+        let fresh = ScrubbedPersistence::new(
+            MemoryPersistence::new(),
+            PiiScrubber::new().with_vault(scrubber.vault()),
+        );
+        assert_eq!(fresh.scrubber.restore("[EMAIL_2]"), "erin@example.com");
+    }
+}
@@ -0,0 +1,937 @@
+//! SQLite session persistence for single-machine deployments.
+//!
+//! Sessions live in one database file opened in WAL mode, so readers never
+//! block the writer. Each session row stores the serialized [`Session`] plus
+//! indexed columns (tenant, state, parent, timestamps) for querying without
+//! deserializing. The schema is versioned through `PRAGMA user_version` and
+//! upgraded by [`SqliteSchema::migrate`].
+//!
+//! ```rust,no_run
+//! use claude_agent::session::{SessionState, SqlitePersistence};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let persistence = SqlitePersistence::open("sessions.db").await?;
+//! let running = persistence
+//!     .find(Some("tenant-a"), Some(SessionState::Active))
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous};
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+
+use super::persistence::Persistence;
+use super::state::{Session, SessionId, SessionMessage, SessionSnapshot, SessionState};
+use super::types::{QueueItem, QueueOperation, QueueStatus, SummarySnapshot};
+use super::{SessionError, SessionResult, StorageResultExt};
+use crate::common::clock;
+
+fn enum_to_db<T: serde::Serialize>(value: &T, default: &str) -> String {
+    serde_json::to_string(value)
+        .map(|s| s.trim_matches('"').to_string())
+        .unwrap_or_else(|_| default.to_string())
+}
+
+fn to_millis(time: DateTime<Utc>) -> i64 {
+    time.timestamp_millis()
+}
+
+fn from_millis(millis: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(millis).unwrap_or_default()
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// SQLite persistence configuration.
+#[derive(Clone, Debug)]
+pub struct SqliteConfig {
+    pub path: PathBuf,
+    pub max_connections: u32,
+    /// How long a connection waits for a lock before failing with `SQLITE_BUSY`.
+    pub busy_timeout: Duration,
+    /// Sessions without explicit TTL that haven't been updated within this
+    /// period are removed by `cleanup_expired()` (default: 30).
+    pub retention_days: u32,
+    /// Maximum retry attempts for lock contention.
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for SqliteConfig {
+    fn default() -> Self {
+        Self::new(
+            crate::common::home_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join(".claude")
+                .join("sessions.db"),
+        )
+    }
+}
+
+impl SqliteConfig {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_connections: 4,
+            busy_timeout: Duration::from_secs(5),
+            retention_days: 30,
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_secs(1),
+        }
+    }
+
+    pub fn max_connections(mut self, max: u32) -> Self {
+        self.max_connections = max;
+        self
+    }
+
+    pub fn busy_timeout(mut self, timeout: Duration) -> Self {
+        self.busy_timeout = timeout;
+        self
+    }
+
+    pub fn retention_days(mut self, days: u32) -> Self {
+        self.retention_days = days;
+        self
+    }
+
+    pub(crate) fn connect_options(&self) -> SqliteConnectOptions {
+        SqliteConnectOptions::new()
+            .filename(&self.path)
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            .synchronous(SqliteSynchronous::Normal)
+            .busy_timeout(self.busy_timeout)
+            .foreign_keys(true)
+    }
+}
+
+// ============================================================================
+// Schema Management
+// ============================================================================
+
+/// Versioned schema for SQLite persistence.
+///
+/// `MIGRATIONS[i]` upgrades the database from version `i` to `i + 1`. The
+/// current version is stored in `PRAGMA user_version`; append new migrations
+/// instead of editing released ones.
+pub struct SqliteSchema;
+
+impl SqliteSchema {
+    pub const MIGRATIONS: &'static [&'static str] = &[
+        r#"
+CREATE TABLE sessions (
+    id TEXT PRIMARY KEY,
+    parent_id TEXT,
+    tenant_id TEXT,
+    session_type TEXT NOT NULL,
+    state TEXT NOT NULL,
+    data TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    expires_at INTEGER
+);
+CREATE INDEX idx_sessions_tenant_state ON sessions(tenant_id, state);
+CREATE INDEX idx_sessions_state ON sessions(state);
+CREATE INDEX idx_sessions_parent ON sessions(parent_id);
+CREATE INDEX idx_sessions_expires ON sessions(expires_at) WHERE expires_at IS NOT NULL;
+CREATE INDEX idx_sessions_updated ON sessions(updated_at);
+
+CREATE TABLE summaries (
+    id TEXT PRIMARY KEY,
+    session_id TEXT NOT NULL,
+    summary TEXT NOT NULL,
+    leaf_message_id TEXT,
+    created_at INTEGER NOT NULL
+);
+CREATE INDEX idx_summaries_session ON summaries(session_id, created_at);
+
+CREATE TABLE queue (
+    id TEXT PRIMARY KEY,
+    session_id TEXT NOT NULL,
+    content TEXT NOT NULL,
+    priority INTEGER NOT NULL DEFAULT 0,
+    status TEXT NOT NULL DEFAULT 'pending',
+    created_at INTEGER NOT NULL,
+    processed_at INTEGER
+);
+CREATE INDEX idx_queue_session_status ON queue(session_id, status, priority DESC, created_at);
+"#,
+        r#"
+CREATE TABLE snapshots (
+    id TEXT PRIMARY KEY,
+    session_id TEXT NOT NULL,
+    data TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+CREATE INDEX idx_snapshots_session ON snapshots(session_id, created_at);
+"#,
+    ];
+
+    /// Schema version this build writes.
+    pub fn latest_version() -> u32 {
+        Self::MIGRATIONS.len() as u32
+    }
+
+    pub async fn version(pool: &SqlitePool) -> Result<u32, sqlx::Error> {
+        let version: i64 = sqlx::query_scalar("PRAGMA user_version")
+            .fetch_one(pool)
+            .await?;
+        Ok(version as u32)
+    }
+
+    /// Apply pending migrations, returning the resulting version.
+    ///
+    /// Fails if the database was created by a newer build.
+    pub async fn migrate(pool: &SqlitePool) -> Result<u32, sqlx::Error> {
+        let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+        let current: i64 = sqlx::query_scalar("PRAGMA user_version")
+            .fetch_one(&mut *tx)
+            .await?;
+        let current = current as u32;
+        let latest = Self::latest_version();
+
+        if current > latest {
+            return Err(sqlx::Error::Protocol(format!(
+                "Database schema version {current} is newer than supported version {latest}"
+            )));
+        }
+
+        for (version, migration) in Self::MIGRATIONS.iter().enumerate().skip(current as usize) {
+            sqlx::raw_sql(migration).execute(&mut *tx).await?;
+            tracing::debug!(version = version + 1, "Applied SQLite schema migration");
+        }
+        sqlx::raw_sql(&format!("PRAGMA user_version = {latest}"))
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(latest)
+    }
+}
+
+// ============================================================================
+// Persistence Implementation
+// ============================================================================
+
+/// SQLite session persistence.
+pub struct SqlitePersistence {
+    pool: SqlitePool,
+    config: SqliteConfig,
+}
+
+impl SqlitePersistence {
+    /// Open (creating if needed) the database at `path` and migrate it.
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self, sqlx::Error> {
+        Self::open_with_config(SqliteConfig::new(path.as_ref())).await
+    }
+
+    /// Open with custom configuration and migrate.
+    pub async fn open_with_config(config: SqliteConfig) -> Result<Self, sqlx::Error> {
+        if let Some(parent) = config.path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let pool = SqlitePoolOptions::new()
+            .max_connections(config.max_connections.max(1))
+            .connect_with(config.connect_options())
+            .await?;
+        SqliteSchema::migrate(&pool).await?;
+        Ok(Self { pool, config })
+    }
+
+    /// Get the underlying connection pool.
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    /// Get the configuration.
+    pub fn config(&self) -> &SqliteConfig {
+        &self.config
+    }
+
+    /// Session IDs filtered by tenant and/or state, most recently updated first.
+    pub async fn find(
+        &self,
+        tenant_id: Option<&str>,
+        state: Option<SessionState>,
+    ) -> SessionResult<Vec<SessionId>> {
+        let state = state.map(|s| enum_to_db(&s, "created"));
+        self.with_retry(|| async {
+            let rows = sqlx::query(
+                "SELECT id FROM sessions \
+                 WHERE (?1 IS NULL OR tenant_id = ?1) AND (?2 IS NULL OR state = ?2) \
+                 ORDER BY updated_at DESC",
+            )
+            .bind(tenant_id)
+            .bind(state.as_deref())
+            .fetch_all(&self.pool)
+            .await
+            .storage_err()?;
+
+            Ok(rows
+                .iter()
+                .filter_map(|row| {
+                    let id: &str = row.try_get("id").ok()?;
+                    SessionId::parse(id).or_else(|| {
+                        tracing::warn!(id, "Skipping session row: failed to parse id");
+                        None
+                    })
+                })
+                .collect())
+        })
+        .await
+    }
+
+    // ========================================================================
+    // Internal helpers
+    // ========================================================================
+
+    async fn with_retry<F, Fut, T>(&self, operation: F) -> SessionResult<T>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = SessionResult<T>>,
+    {
+        super::with_retry(
+            self.config.max_retries,
+            self.config.initial_backoff,
+            self.config.max_backoff,
+            Self::is_retryable,
+            operation,
+        )
+        .await
+    }
+
+    fn is_retryable(error: &SessionError) -> bool {
+        match error {
+            SessionError::Storage { message } => {
+                message.contains("database is locked") || message.contains("busy")
+            }
+            _ => false,
+        }
+    }
+
+    async fn upsert<'e, E>(executor: E, session: &Session) -> SessionResult<()>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+    {
+        sqlx::query(
+            r#"
+            INSERT INTO sessions
+                (id, parent_id, tenant_id, session_type, state, data, created_at, updated_at, expires_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            ON CONFLICT(id) DO UPDATE SET
+                parent_id = excluded.parent_id,
+                tenant_id = excluded.tenant_id,
+                session_type = excluded.session_type,
+                state = excluded.state,
+                data = excluded.data,
+                updated_at = excluded.updated_at,
+                expires_at = excluded.expires_at
+            "#,
+        )
+        .bind(session.id.to_string())
+        .bind(session.parent_id.map(|id| id.to_string()))
+        .bind(session.tenant_id.as_deref())
+        .bind(session_type_name(session))
+        .bind(enum_to_db(&session.state, "created"))
+        .bind(serde_json::to_string(session)?)
+        .bind(to_millis(session.created_at))
+        .bind(to_millis(session.updated_at))
+        .bind(session.expires_at.map(to_millis))
+        .execute(executor)
+        .await
+        .storage_err()?;
+        Ok(())
+    }
+
+    async fn load_data<'e, E>(executor: E, id: &SessionId) -> SessionResult<Option<Session>>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+    {
+        let data: Option<String> = sqlx::query_scalar("SELECT data FROM sessions WHERE id = ?1")
+            .bind(id.to_string())
+            .fetch_optional(executor)
+            .await
+            .storage_err()?;
+        data.map(|d| serde_json::from_str(&d).map_err(SessionError::from))
+            .transpose()
+    }
+
+    async fn delete_where(&self, condition: &str, bind: i64) -> SessionResult<usize> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .storage_err_ctx("begin cleanup transaction")?;
+        let ids: Vec<String> =
+            sqlx::query_scalar(&format!("SELECT id FROM sessions WHERE {condition}"))
+                .bind(bind)
+                .fetch_all(&mut *tx)
+                .await
+                .storage_err()?;
+        for id in &ids {
+            for table in ["summaries", "queue", "snapshots"] {
+                sqlx::query(&format!("DELETE FROM {table} WHERE session_id = ?1"))
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await
+                    .storage_err()?;
+            }
+            sqlx::query("DELETE FROM sessions WHERE id = ?1")
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .storage_err()?;
+        }
+        tx.commit().await.storage_err()?;
+        Ok(ids.len())
+    }
+
+    fn queue_item(row: &sqlx::sqlite::SqliteRow, session_id: SessionId) -> Option<QueueItem> {
+        let id: &str = row.try_get("id").ok()?;
+        let status: &str = row.try_get("status").ok()?;
+        Some(QueueItem {
+            id: Uuid::parse_str(id).ok()?,
+            session_id,
+            operation: QueueOperation::Enqueue,
+            content: row.try_get("content").ok()?,
+            priority: row.try_get("priority").unwrap_or(0),
+            status: serde_json::from_str(&format!("\"{status}\"")).unwrap_or_default(),
+            created_at: from_millis(row.try_get("created_at").unwrap_or(0)),
+            processed_at: row
+                .try_get::<Option<i64>, _>("processed_at")
+                .ok()
+                .flatten()
+                .map(from_millis),
+        })
+    }
+}
+
+fn session_type_name(session: &Session) -> &'static str {
+    match session.session_type {
+        super::state::SessionType::Main => "main",
+        super::state::SessionType::Subagent { .. } => "subagent",
+    }
+}
+
+#[async_trait]
+impl Persistence for SqlitePersistence {
+    fn name(&self) -> &str {
+        "sqlite"
+    }
+
+    async fn save(&self, session: &Session) -> SessionResult<()> {
+        self.with_retry(|| Self::upsert(&self.pool, session)).await
+    }
+
+    async fn load(&self, id: &SessionId) -> SessionResult<Option<Session>> {
+        self.with_retry(|| Self::load_data(&self.pool, id)).await
+    }
+
+    async fn delete(&self, id: &SessionId) -> SessionResult<bool> {
+        self.with_retry(|| async {
+            let mut tx = self.pool.begin().await.storage_err()?;
+            for table in ["summaries", "queue", "snapshots"] {
+                sqlx::query(&format!("DELETE FROM {table} WHERE session_id = ?1"))
+                    .bind(id.to_string())
+                    .execute(&mut *tx)
+                    .await
+                    .storage_err()?;
+            }
+            let result = sqlx::query("DELETE FROM sessions WHERE id = ?1")
+                .bind(id.to_string())
+                .execute(&mut *tx)
+                .await
+                .storage_err()?;
+            tx.commit().await.storage_err()?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    async fn list(&self, tenant_id: Option<&str>) -> SessionResult<Vec<SessionId>> {
+        self.find(tenant_id, None).await
+    }
+
+    async fn add_message(
+        &self,
+        session_id: &SessionId,
+        message: SessionMessage,
+    ) -> SessionResult<()> {
+        self.with_retry(|| async {
+            // IMMEDIATE takes the write lock up front so the read-modify-write
+            // cannot interleave with another writer.
+            let mut tx = self
+                .pool
+                .begin_with("BEGIN IMMEDIATE")
+                .await
+                .storage_err()?;
+            let mut session = Self::load_data(&mut *tx, session_id)
+                .await?
+                .ok_or_else(|| SessionError::NotFound {
+                    id: session_id.to_string(),
+                })?;
+            session.add_message(message.clone());
+            Self::upsert(&mut *tx, &session).await?;
+            tx.commit().await.storage_err()?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn add_summary(&self, snapshot: SummarySnapshot) -> SessionResult<()> {
+        self.with_retry(|| async {
+            let mut tx = self
+                .pool
+                .begin_with("BEGIN IMMEDIATE")
+                .await
+                .storage_err()?;
+            sqlx::query(
+                "INSERT INTO summaries (id, session_id, summary, leaf_message_id, created_at) \
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )
+            .bind(snapshot.id.to_string())
+            .bind(snapshot.session_id.to_string())
+            .bind(&snapshot.summary)
+            .bind(snapshot.leaf_message_id.as_ref().map(|id| id.to_string()))
+            .bind(to_millis(snapshot.created_at))
+            .execute(&mut *tx)
+            .await
+            .storage_err()?;
+
+            if let Some(mut session) = Self::load_data(&mut *tx, &snapshot.session_id).await? {
+                session.summary = Some(snapshot.summary.clone());
+                session.updated_at = clock::now_utc();
+                Self::upsert(&mut *tx, &session).await?;
+            }
+
+            tx.commit().await.storage_err()?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn get_summaries(&self, session_id: &SessionId) -> SessionResult<Vec<SummarySnapshot>> {
+        self.with_retry(|| async {
+            let rows = sqlx::query(
+                "SELECT id, summary, leaf_message_id, created_at FROM summaries \
+                 WHERE session_id = ?1 ORDER BY created_at ASC, rowid ASC",
+            )
+            .bind(session_id.to_string())
+            .fetch_all(&self.pool)
+            .await
+            .storage_err()?;
+
+            Ok(rows
+                .iter()
+                .filter_map(|row| {
+                    let id: &str = row.try_get("id").ok()?;
+                    Some(SummarySnapshot {
+                        id: Uuid::parse_str(id).ok()?,
+                        session_id: *session_id,
+                        summary: row.try_get("summary").ok()?,
+                        leaf_message_id: row
+                            .try_get::<Option<&str>, _>("leaf_message_id")
+                            .ok()
+                            .flatten()
+                            .and_then(|s| s.parse().ok()),
+                        created_at: from_millis(row.try_get("created_at").unwrap_or(0)),
+                    })
+                })
+                .collect())
+        })
+        .await
+    }
+
+    async fn enqueue(
+        &self,
+        session_id: &SessionId,
+        content: String,
+        priority: i32,
+    ) -> SessionResult<QueueItem> {
+        let item = QueueItem::enqueue(*session_id, content).priority(priority);
+        self.with_retry(|| async {
+            sqlx::query(
+                "INSERT INTO queue (id, session_id, content, priority, status, created_at) \
+                 VALUES (?1, ?2, ?3, ?4, 'pending', ?5)",
+            )
+            .bind(item.id.to_string())
+            .bind(session_id.to_string())
+            .bind(&item.content)
+            .bind(priority)
+            .bind(to_millis(item.created_at))
+            .execute(&self.pool)
+            .await
+            .storage_err()?;
+            Ok(item.clone())
+        })
+        .await
+    }
+
+    async fn dequeue(&self, session_id: &SessionId) -> SessionResult<Option<QueueItem>> {
+        self.with_retry(|| async {
+            let row = sqlx::query(
+                r#"
+                UPDATE queue SET status = 'processing'
+                WHERE id = (
+                    SELECT id FROM queue
+                    WHERE session_id = ?1 AND status = 'pending'
+                    ORDER BY priority DESC, created_at ASC, rowid ASC
+                    LIMIT 1
+                )
+                RETURNING id, content, priority, status, created_at, processed_at
+                "#,
+            )
+            .bind(session_id.to_string())
+            .fetch_optional(&self.pool)
+            .await
+            .storage_err()?;
+
+            Ok(row.and_then(|row| Self::queue_item(&row, *session_id)))
+        })
+        .await
+    }
+
+    async fn cancel_queued(&self, item_id: Uuid) -> SessionResult<bool> {
+        self.with_retry(|| async {
+            let result = sqlx::query(
+                "UPDATE queue SET status = 'cancelled', processed_at = ?1 \
+                 WHERE id = ?2 AND status = 'pending'",
+            )
+            .bind(to_millis(clock::now_utc()))
+            .bind(item_id.to_string())
+            .execute(&self.pool)
+            .await
+            .storage_err()?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+
+    async fn pending_queue(&self, session_id: &SessionId) -> SessionResult<Vec<QueueItem>> {
+        self.with_retry(|| async {
+            let rows = sqlx::query(
+                "SELECT id, content, priority, status, created_at, processed_at FROM queue \
+                 WHERE session_id = ?1 AND status = 'pending' \
+                 ORDER BY priority DESC, created_at ASC, rowid ASC",
+            )
+            .bind(session_id.to_string())
+            .fetch_all(&self.pool)
+            .await
+            .storage_err()?;
+
+            Ok(rows
+                .iter()
+                .filter_map(|row| Self::queue_item(row, *session_id))
+                .filter(|item| item.status == QueueStatus::Pending)
+                .collect())
+        })
+        .await
+    }
+
+    async fn cleanup_expired(&self) -> SessionResult<usize> {
+        let now = clock::now_utc();
+        let cutoff = now - chrono::Duration::days(self.config.retention_days as i64);
+        self.with_retry(|| async {
+            let expired = self
+                .delete_where("expires_at IS NOT NULL AND expires_at < ?1", to_millis(now))
+                .await?;
+            let stale = self
+                .delete_where("updated_at < ?1", to_millis(cutoff))
+                .await?;
+            Ok(expired + stale)
+        })
+        .await
+    }
+
+    async fn save_snapshot(&self, snapshot: &SessionSnapshot) -> SessionResult<()> {
+        let data = serde_json::to_string(snapshot)?;
+        self.with_retry(|| async {
+            sqlx::query(
+                "INSERT INTO snapshots (id, session_id, data, created_at) VALUES (?1, ?2, ?3, ?4)",
+            )
+            .bind(snapshot.id.to_string())
+            .bind(snapshot.session_id.to_string())
+            .bind(&data)
+            .bind(to_millis(snapshot.created_at))
+            .execute(&self.pool)
+            .await
+            .storage_err()?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn load_snapshot(
+        &self,
+        session_id: &SessionId,
+        snapshot_id: Uuid,
+    ) -> SessionResult<Option<SessionSnapshot>> {
+        self.with_retry(|| async {
+            let data: Option<String> =
+                sqlx::query_scalar("SELECT data FROM snapshots WHERE id = ?1 AND session_id = ?2")
+                    .bind(snapshot_id.to_string())
+                    .bind(session_id.to_string())
+                    .fetch_optional(&self.pool)
+                    .await
+                    .storage_err()?;
+            data.map(|d| serde_json::from_str(&d).map_err(SessionError::from))
+                .transpose()
+        })
+        .await
+    }
+
+    async fn list_snapshots(&self, session_id: &SessionId) -> SessionResult<Vec<SessionSnapshot>> {
+        self.with_retry(|| async {
+            let rows: Vec<String> = sqlx::query_scalar(
+                "SELECT data FROM snapshots WHERE session_id = ?1 \
+                 ORDER BY created_at ASC, rowid ASC",
+            )
+            .bind(session_id.to_string())
+            .fetch_all(&self.pool)
+            .await
+            .storage_err()?;
+            rows.iter()
+                .map(|d| serde_json::from_str(d).map_err(SessionError::from))
+                .collect()
+        })
+        .await
+    }
+
+    async fn delete_snapshot(
+        &self,
+        session_id: &SessionId,
+        snapshot_id: Uuid,
+    ) -> SessionResult<bool> {
+        self.with_retry(|| async {
+            let result = sqlx::query("DELETE FROM snapshots WHERE id = ?1 AND session_id = ?2")
+                .bind(snapshot_id.to_string())
+                .bind(session_id.to_string())
+                .execute(&self.pool)
+                .await
+                .storage_err()?;
+            Ok(result.rows_affected() > 0)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::state::SessionConfig;
+    use crate::types::ContentBlock;
+
+    async fn open(dir: &tempfile::TempDir) -> SqlitePersistence {
+        SqlitePersistence::open(dir.path().join("sessions.db"))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_migrate_and_wal() {
+        let dir = tempfile::tempdir().unwrap();
+        let persistence = open(&dir).await;
+
+        assert_eq!(
+            SqliteSchema::version(persistence.pool()).await.unwrap(),
+            SqliteSchema::latest_version()
+        );
+        let mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+            .fetch_one(persistence.pool())
+            .await
+            .unwrap();
+        assert_eq!(mode, "wal");
+
+        // Re-opening an up-to-date database is a no-op.
+        drop(persistence);
+        let persistence = open(&dir).await;
+        assert_eq!(
+            SqliteSchema::migrate(persistence.pool()).await.unwrap(),
+            SqliteSchema::latest_version()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_save_load_and_messages() {
+        let dir = tempfile::tempdir().unwrap();
+        let persistence = open(&dir).await;
+        let session = Session::new(SessionConfig::default());
+        let id = session.id;
+
+        persistence.save(&session).await.unwrap();
+        persistence
+            .add_message(&id, SessionMessage::user(vec![ContentBlock::text("Hello")]))
+            .await
+            .unwrap();
+
+        let loaded = persistence.load(&id).await.unwrap().unwrap();
+        assert_eq!(loaded.messages.len(), 1);
+        assert_eq!(loaded.created_at, session.created_at);
+
+        assert!(persistence.delete(&id).await.unwrap());
+        assert!(persistence.load(&id).await.unwrap().is_none());
+        assert!(matches!(
+            persistence
+                .add_message(&id, SessionMessage::user(vec![ContentBlock::text("Hi")]))
+                .await,
+            Err(SessionError::NotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_find_by_tenant_and_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let persistence = open(&dir).await;
+
+        let mut active = Session::new(SessionConfig::default());
+        active.tenant_id = Some("tenant-a".into());
+        active.state = SessionState::Active;
+        let mut done = Session::new(SessionConfig::default());
+        done.tenant_id = Some("tenant-a".into());
+        done.state = SessionState::Completed;
+        let mut other = Session::new(SessionConfig::default());
+        other.tenant_id = Some("tenant-b".into());
+        other.state = SessionState::Active;
+        for session in [&active, &done, &other] {
+            persistence.save(session).await.unwrap();
+        }
+
+        assert_eq!(persistence.list(None).await.unwrap().len(), 3);
+        assert_eq!(persistence.list(Some("tenant-a")).await.unwrap().len(), 2);
+        assert_eq!(
+            persistence
+                .find(Some("tenant-a"), Some(SessionState::Active))
+                .await
+                .unwrap(),
+            vec![active.id]
+        );
+        assert_eq!(
+            persistence
+                .find(None, Some(SessionState::Active))
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_summaries_and_queue() {
+        let dir = tempfile::tempdir().unwrap();
+        let persistence = open(&dir).await;
+        let session = Session::new(SessionConfig::default());
+        let id = session.id;
+        persistence.save(&session).await.unwrap();
+
+        persistence
+            .add_summary(SummarySnapshot::new(id, "First"))
+            .await
+            .unwrap();
+        persistence
+            .add_summary(SummarySnapshot::new(id, "Second"))
+            .await
+            .unwrap();
+        let summaries = persistence.get_summaries(&id).await.unwrap();
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(
+            persistence.load(&id).await.unwrap().unwrap().summary,
+            Some("Second".into())
+        );
+
+        persistence.enqueue(&id, "Low".into(), 1).await.unwrap();
+        let high = persistence.enqueue(&id, "High".into(), 10).await.unwrap();
+        let cancelled = persistence.enqueue(&id, "Drop".into(), 5).await.unwrap();
+        assert!(persistence.cancel_queued(cancelled.id).await.unwrap());
+
+        let next = persistence.dequeue(&id).await.unwrap().unwrap();
+        assert_eq!(next.id, high.id);
+        assert_eq!(next.status, QueueStatus::Processing);
+        let pending = persistence.pending_queue(&id).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].content, "Low");
+    }
+
+    #[tokio::test]
+    async fn test_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let persistence = open(&dir).await;
+        let mut session = Session::new(SessionConfig::default());
+        session.add_message(SessionMessage::user(vec![ContentBlock::text("Hello")]));
+        persistence.save(&session).await.unwrap();
+
+        let snapshot = session.snapshot_labeled("before run");
+        persistence.save_snapshot(&snapshot).await.unwrap();
+        persistence
+            .save_snapshot(&session.snapshot())
+            .await
+            .unwrap();
+
+        let loaded = persistence
+            .load_snapshot(&session.id, snapshot.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.messages.len(), 1);
+        assert_eq!(loaded.label.as_deref(), Some("before run"));
+        let listed = persistence.list_snapshots(&session.id).await.unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].id, snapshot.id);
+
+        assert!(
+            persistence
+                .delete_snapshot(&session.id, snapshot.id)
+                .await
+                .unwrap()
+        );
+        assert!(persistence.delete(&session.id).await.unwrap());
+        assert!(
+            persistence
+                .list_snapshots(&session.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_expired() {
+        let dir = tempfile::tempdir().unwrap();
+        let persistence = open(&dir).await;
+        let expiring = Session::new(SessionConfig {
+            ttl_secs: Some(0),
+            ..Default::default()
+        });
+        let kept = Session::new(SessionConfig::default());
+        persistence.save(&expiring).await.unwrap();
+        persistence.save(&kept).await.unwrap();
+        persistence
+            .enqueue(&expiring.id, "pending".into(), 0)
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        assert_eq!(persistence.cleanup_expired().await.unwrap(), 1);
+        assert_eq!(persistence.list(None).await.unwrap(), vec![kept.id]);
+        assert!(
+            persistence
+                .pending_queue(&expiring.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
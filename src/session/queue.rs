@@ -10,6 +10,7 @@ use uuid::Uuid;
 
 use super::state::SessionId;
 use super::types::EnvironmentContext;
+use crate::common::clock;
 
 const MAX_QUEUE_SIZE: usize = 100;
 const MAX_MERGE_CHARS: usize = 100_000;
@@ -41,11 +42,11 @@ pub struct QueuedInput {
 impl QueuedInput {
     pub fn new(session_id: SessionId, content: impl Into<String>) -> Self {
         Self {
-            id: Uuid::new_v4(),
+            id: clock::new_uuid(),
             session_id,
             content: content.into(),
             environment: None,
-            created_at: Utc::now(),
+            created_at: clock::now_utc(),
         }
     }
 
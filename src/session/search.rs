@@ -0,0 +1,302 @@
+//! Full-text search over sessions.
+//!
+//! A [`SearchIndex`] indexes message text, summaries and todos. Without one,
+//! [`SessionManager::search`](super::SessionManager::search) falls back to a
+//! `LIKE`-style scan of every stored session: all query terms must appear
+//! (case-insensitive substring match) and hits are ranked by term frequency.
+//! Enable `tantivy-search` for a real inverted index.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::SessionResult;
+use super::persistence::Persistence;
+use super::state::{MessageId, Session, SessionId};
+use crate::types::{ContentBlock, ToolResultContent, ToolResultContentBlock};
+
+const DEFAULT_LIMIT: usize = 20;
+const SNIPPET_CHARS: usize = 160;
+
+/// Where a hit was found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HitSource {
+    Message,
+    Summary,
+    Todo,
+}
+
+impl HitSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Message => "message",
+            Self::Summary => "summary",
+            Self::Todo => "todo",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "message" => Some(Self::Message),
+            "summary" => Some(Self::Summary),
+            "todo" => Some(Self::Todo),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub session_id: SessionId,
+    /// Set for message hits.
+    pub message_id: Option<MessageId>,
+    pub source: HitSource,
+    /// Relevance; only comparable between hits of the same search.
+    pub score: f32,
+    pub snippet: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchQuery {
+    pub text: String,
+    pub tenant_id: Option<String>,
+    pub limit: usize,
+}
+
+impl SearchQuery {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            tenant_id: None,
+            limit: DEFAULT_LIMIT,
+        }
+    }
+
+    pub fn tenant(mut self, tenant_id: impl Into<String>) -> Self {
+        self.tenant_id = Some(tenant_id.into());
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    fn terms(&self) -> Vec<String> {
+        self.text
+            .split_whitespace()
+            .map(|t| t.to_lowercase())
+            .collect()
+    }
+}
+
+impl From<&str> for SearchQuery {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<String> for SearchQuery {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+/// Pluggable full-text index kept up to date by the session manager.
+#[async_trait]
+pub trait SearchIndex: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Index (or re-index) all searchable text of a session.
+    async fn index(&self, session: &Session) -> SessionResult<()>;
+
+    async fn remove(&self, session_id: &SessionId) -> SessionResult<()>;
+
+    async fn search(&self, query: &SearchQuery) -> SessionResult<Vec<SearchHit>>;
+}
+
+/// A piece of searchable text extracted from a session.
+#[derive(Clone, Debug)]
+pub struct SearchEntry {
+    pub message_id: Option<MessageId>,
+    pub source: HitSource,
+    pub text: String,
+}
+
+/// Message text (including tool results), summaries and todos of `session`.
+pub fn searchable_entries(session: &Session) -> Vec<SearchEntry> {
+    let mut entries = Vec::new();
+
+    for message in &session.messages {
+        let mut parts = Vec::new();
+        for block in &message.content {
+            match block {
+                ContentBlock::Text { text, .. } => parts.push(text.as_str()),
+                ContentBlock::ToolResult(result) => match &result.content {
+                    Some(ToolResultContent::Text(text)) => parts.push(text.as_str()),
+                    Some(ToolResultContent::Blocks(blocks)) => {
+                        parts.extend(blocks.iter().filter_map(|b| match b {
+                            ToolResultContentBlock::Text { text } => Some(text.as_str()),
+                            _ => None,
+                        }))
+                    }
+                    None => {}
+                },
+                _ => {}
+            }
+        }
+        if !parts.is_empty() {
+            entries.push(SearchEntry {
+                message_id: Some(message.id.clone()),
+                source: HitSource::Message,
+                text: parts.join("\n"),
+            });
+        }
+    }
+
+    let mut summaries: Vec<&str> = session
+        .compact_history
+        .iter()
+        .map(|r| r.summary.as_str())
+        .collect();
+    if let Some(summary) = session.summary.as_deref()
+        && !summaries.contains(&summary)
+    {
+        summaries.push(summary);
+    }
+    entries.extend(summaries.into_iter().map(|text| SearchEntry {
+        message_id: None,
+        source: HitSource::Summary,
+        text: text.to_string(),
+    }));
+
+    entries.extend(session.todos.iter().map(|todo| SearchEntry {
+        message_id: None,
+        source: HitSource::Todo,
+        text: todo.content.clone(),
+    }));
+
+    entries
+}
+
+/// Scan every session in `persistence`, matching like `LIKE '%term%'` per term.
+pub async fn scan(
+    persistence: &dyn Persistence,
+    query: &SearchQuery,
+) -> SessionResult<Vec<SearchHit>> {
+    let terms = query.terms();
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut hits = Vec::new();
+    for id in persistence.list(query.tenant_id.as_deref()).await? {
+        let Some(session) = persistence.load(&id).await? else {
+            continue;
+        };
+        if session.is_expired() {
+            continue;
+        }
+        for entry in searchable_entries(&session) {
+            let haystack = entry.text.to_lowercase();
+            let counts: Vec<usize> = terms.iter().map(|t| haystack.matches(t).count()).collect();
+            if counts.contains(&0) {
+                continue;
+            }
+            let total: usize = counts.iter().sum();
+            // Favor dense matches over long texts that mention a term once.
+            let score = total as f32 / (1.0 + (entry.text.len() as f32).ln());
+            hits.push(SearchHit {
+                session_id: id,
+                message_id: entry.message_id,
+                source: entry.source,
+                score,
+                snippet: snippet(&entry.text, &haystack, &terms[0]),
+            });
+        }
+    }
+
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits.truncate(query.limit);
+    Ok(hits)
+}
+
+/// Up to `SNIPPET_CHARS` characters of `text` around the first `term` match.
+fn snippet(text: &str, lowercase: &str, term: &str) -> String {
+    // Lowercasing can change byte offsets for some scripts; fall back to the start.
+    let start_char = lowercase
+        .find(term)
+        .filter(|_| lowercase.len() == text.len())
+        .and_then(|byte| text.get(..byte))
+        .map(|prefix| prefix.chars().count())
+        .unwrap_or(0)
+        .saturating_sub(SNIPPET_CHARS / 4);
+    let mut out: String = text.chars().skip(start_char).take(SNIPPET_CHARS).collect();
+    if start_char > 0 {
+        out.insert(0, '…');
+    }
+    if text.chars().count() > start_char + SNIPPET_CHARS {
+        out.push('…');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::persistence::MemoryPersistence;
+    use crate::session::state::{SessionConfig, SessionMessage};
+    use crate::session::types::TodoItem;
+
+    #[tokio::test]
+    async fn test_scan_ranks_and_filters() {
+        let persistence = MemoryPersistence::new();
+
+        let mut auth = Session::new(SessionConfig::default());
+        auth.tenant_id = Some("acme".into());
+        auth.add_message(SessionMessage::user(vec![ContentBlock::text(
+            "Change the auth config to use OAuth",
+        )]));
+        auth.update_summary("Switched auth config to OAuth; auth tests updated");
+        auth.todos.push(TodoItem::new(
+            auth.id,
+            "Update auth config docs",
+            "Updating docs",
+        ));
+        persistence.save(&auth).await.unwrap();
+
+        let mut other = Session::new(SessionConfig::default());
+        other.add_message(SessionMessage::user(vec![ContentBlock::text(
+            "Fix the config loader",
+        )]));
+        persistence.save(&other).await.unwrap();
+
+        let hits = scan(&persistence, &"AUTH config".into()).await.unwrap();
+        assert_eq!(hits.len(), 3);
+        assert!(hits.iter().all(|h| h.session_id == auth.id));
+        assert_eq!(hits[0].source, HitSource::Summary);
+        let message_hit = hits
+            .iter()
+            .find(|h| h.source == HitSource::Message)
+            .unwrap();
+        assert_eq!(message_hit.message_id, auth.current_leaf_id);
+
+        let hits = scan(
+            &persistence,
+            &SearchQuery::new("config").tenant("acme").limit(1),
+        )
+        .await
+        .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, auth.id);
+    }
+
+    #[test]
+    fn test_snippet_window() {
+        let text = format!("{} needle {}", "a".repeat(300), "b".repeat(300));
+        let s = snippet(&text, &text.to_lowercase(), "needle");
+        assert!(s.starts_with('…') && s.ends_with('…'));
+        assert!(s.contains("needle"));
+        assert_eq!(snippet("short", "short", "short"), "short");
+    }
+}
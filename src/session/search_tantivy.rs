@@ -0,0 +1,234 @@
+//! Tantivy-backed session search index.
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use tantivy::collector::TopDocs;
+use tantivy::directory::MmapDirectory;
+use tantivy::query::{BooleanQuery, Occur, QueryParser, TermQuery};
+use tantivy::schema::{Field, IndexRecordOption, STORED, STRING, Schema, TEXT, Value};
+use tantivy::snippet::SnippetGenerator;
+use tantivy::{Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument, Term, doc};
+
+use super::search::{HitSource, SearchHit, SearchIndex, SearchQuery, searchable_entries};
+use super::state::{MessageId, Session, SessionId};
+use super::{SessionResult, StorageResultExt};
+
+const WRITER_MEMORY_BYTES: usize = 20_000_000;
+const SNIPPET_CHARS: usize = 160;
+
+#[derive(Clone, Copy)]
+struct Fields {
+    session_id: Field,
+    message_id: Field,
+    source: Field,
+    tenant_id: Field,
+    text: Field,
+}
+
+impl Fields {
+    fn schema() -> (Schema, Self) {
+        let mut builder = Schema::builder();
+        let fields = Self {
+            session_id: builder.add_text_field("session_id", STRING | STORED),
+            message_id: builder.add_text_field("message_id", STRING | STORED),
+            source: builder.add_text_field("source", STRING | STORED),
+            tenant_id: builder.add_text_field("tenant_id", STRING),
+            text: builder.add_text_field("text", TEXT | STORED),
+        };
+        (builder.build(), fields)
+    }
+}
+
+/// Inverted index over session text, ranked by BM25.
+pub struct TantivySearchIndex {
+    index: Index,
+    reader: IndexReader,
+    writer: Mutex<IndexWriter>,
+    fields: Fields,
+}
+
+impl TantivySearchIndex {
+    /// Index kept in memory only.
+    pub fn in_memory() -> SessionResult<Self> {
+        let (schema, fields) = Fields::schema();
+        Self::from_index(Index::create_in_ram(schema), fields)
+    }
+
+    /// Open the index stored in `dir`, creating it if needed.
+    pub fn open(dir: impl AsRef<Path>) -> SessionResult<Self> {
+        std::fs::create_dir_all(dir.as_ref()).storage_err_ctx("create search index directory")?;
+        let directory = MmapDirectory::open(dir.as_ref()).storage_err()?;
+        let (schema, fields) = Fields::schema();
+        let index = Index::open_or_create(directory, schema).storage_err()?;
+        Self::from_index(index, fields)
+    }
+
+    fn from_index(index: Index, fields: Fields) -> SessionResult<Self> {
+        let writer = index
+            .writer_with_num_threads(1, WRITER_MEMORY_BYTES)
+            .storage_err()?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .storage_err()?;
+        Ok(Self {
+            index,
+            reader,
+            writer: Mutex::new(writer),
+            fields,
+        })
+    }
+
+    fn commit(
+        &self,
+        update: impl FnOnce(&mut IndexWriter) -> SessionResult<()>,
+    ) -> SessionResult<()> {
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        update(&mut writer)?;
+        writer.commit().storage_err()?;
+        self.reader.reload().storage_err()
+    }
+}
+
+#[async_trait]
+impl SearchIndex for TantivySearchIndex {
+    fn name(&self) -> &str {
+        "tantivy"
+    }
+
+    async fn index(&self, session: &Session) -> SessionResult<()> {
+        let f = self.fields;
+        let session_id = session.id.to_string();
+        let tenant_id = session.tenant_id.clone().unwrap_or_default();
+        self.commit(|writer| {
+            writer.delete_term(Term::from_field_text(f.session_id, &session_id));
+            for entry in searchable_entries(session) {
+                let message_id = entry
+                    .message_id
+                    .as_ref()
+                    .map(|id| id.to_string())
+                    .unwrap_or_default();
+                writer
+                    .add_document(doc!(
+                        f.session_id => session_id.as_str(),
+                        f.message_id => message_id,
+                        f.source => entry.source.as_str(),
+                        f.tenant_id => tenant_id.as_str(),
+                        f.text => entry.text,
+                    ))
+                    .storage_err()?;
+            }
+            Ok(())
+        })
+    }
+
+    async fn remove(&self, session_id: &SessionId) -> SessionResult<()> {
+        let term = Term::from_field_text(self.fields.session_id, &session_id.to_string());
+        self.commit(|writer| {
+            writer.delete_term(term);
+            Ok(())
+        })
+    }
+
+    async fn search(&self, query: &SearchQuery) -> SessionResult<Vec<SearchHit>> {
+        let f = self.fields;
+        if query.text.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut parser = QueryParser::for_index(&self.index, vec![f.text]);
+        parser.set_conjunction_by_default();
+        // Lenient parsing: user input is free text, not query syntax.
+        let (text_query, _) = parser.parse_query_lenient(&query.text);
+        let search_query: Box<dyn tantivy::query::Query> = match &query.tenant_id {
+            Some(tenant) => Box::new(BooleanQuery::new(vec![
+                (Occur::Must, text_query.box_clone()),
+                (
+                    Occur::Must,
+                    Box::new(TermQuery::new(
+                        Term::from_field_text(f.tenant_id, tenant),
+                        IndexRecordOption::Basic,
+                    )),
+                ),
+            ])),
+            None => text_query.box_clone(),
+        };
+
+        let searcher = self.reader.searcher();
+        let top = searcher
+            .search(&search_query, &TopDocs::with_limit(query.limit.max(1)))
+            .storage_err()?;
+        let mut snippets =
+            SnippetGenerator::create(&searcher, &*text_query, f.text).storage_err()?;
+        snippets.set_max_num_chars(SNIPPET_CHARS);
+
+        let mut hits = Vec::with_capacity(top.len());
+        for (score, address) in top {
+            let doc: TantivyDocument = searcher.doc(address).storage_err()?;
+            let text_of = |field| {
+                doc.get_first(field)
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            let Some(session_id) = SessionId::parse(&text_of(f.session_id)) else {
+                continue;
+            };
+            let message_id = text_of(f.message_id);
+            hits.push(SearchHit {
+                session_id,
+                message_id: (!message_id.is_empty()).then_some(MessageId(message_id)),
+                source: HitSource::parse(&text_of(f.source)).unwrap_or(HitSource::Message),
+                score,
+                snippet: snippets.snippet_from_doc(&doc).fragment().to_string(),
+            });
+        }
+        Ok(hits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::state::{SessionConfig, SessionMessage};
+    use crate::types::ContentBlock;
+
+    fn session(tenant: &str, text: &str) -> Session {
+        let mut session = Session::new(SessionConfig::default());
+        session.tenant_id = Some(tenant.into());
+        session.add_message(SessionMessage::user(vec![ContentBlock::text(text)]));
+        session
+    }
+
+    #[tokio::test]
+    async fn test_index_search_remove() {
+        let index = TantivySearchIndex::in_memory().unwrap();
+        let auth = session("acme", "We changed the auth config to require MFA");
+        let other = session("globex", "The auth service is down");
+        index.index(&auth).await.unwrap();
+        index.index(&other).await.unwrap();
+
+        let hits = index.search(&"auth config".into()).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, auth.id);
+        assert_eq!(hits[0].message_id, auth.current_leaf_id);
+        assert!(hits[0].snippet.contains("config"));
+
+        let hits = index
+            .search(&SearchQuery::new("auth").tenant("globex"))
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, other.id);
+
+        // Re-indexing replaces the previous documents.
+        index.index(&auth).await.unwrap();
+        assert_eq!(index.search(&"MFA".into()).await.unwrap().len(), 1);
+
+        index.remove(&auth.id).await.unwrap();
+        assert!(index.search(&"MFA".into()).await.unwrap().is_empty());
+    }
+}
@@ -61,6 +61,7 @@ struct ToolStateInner {
     session: RwLock<Session>,
     executions: ToolExecutionLog,
     input_queue: SharedInputQueue,
+    interjections: SharedInputQueue,
     execution_lock: Semaphore,
     executing: AtomicBool,
     queue_notify: Notify,
@@ -83,6 +84,7 @@ impl ToolStateInner {
             session: RwLock::new(Session::from_id(session_id, SessionConfig::default())),
             executions: ToolExecutionLog::new(),
             input_queue: SharedInputQueue::new(),
+            interjections: SharedInputQueue::new(),
             execution_lock: Semaphore::new(1),
             executing: AtomicBool::new(false),
             queue_notify: Notify::new(),
@@ -96,6 +98,7 @@ impl ToolStateInner {
             session: RwLock::new(session),
             executions: ToolExecutionLog::new(),
             input_queue: SharedInputQueue::new(),
+            interjections: SharedInputQueue::new(),
             execution_lock: Semaphore::new(1),
             executing: AtomicBool::new(false),
             queue_notify: Notify::new(),
@@ -277,6 +280,20 @@ impl ToolState {
         self.0.input_queue.cancel_all().await.len()
     }
 
+    /// Queue input to be merged into the running execution at its next iteration boundary.
+    pub async fn interject(&self, content: impl Into<String>) -> Result<Uuid, QueueError> {
+        let input = QueuedInput::new(self.session_id(), content);
+        self.0.interjections.enqueue(input).await
+    }
+
+    pub async fn take_interjections(&self) -> Option<MergedInput> {
+        self.0.interjections.merge_all().await
+    }
+
+    pub async fn pending_interjections(&self) -> usize {
+        self.0.interjections.pending_count().await
+    }
+
     pub fn is_executing(&self) -> bool {
         self.0.executing.load(Ordering::Acquire)
     }
@@ -331,9 +348,18 @@ impl ToolState {
         &self,
         client: &crate::Client,
         keep_messages: usize,
+    ) -> crate::Result<crate::types::CompactResult> {
+        self.compact_with(client, keep_messages, None).await
+    }
+
+    pub async fn compact_with(
+        &self,
+        client: &crate::Client,
+        keep_messages: usize,
+        strategy: Option<&crate::session::CompactStrategy>,
     ) -> crate::Result<crate::types::CompactResult> {
         let mut session = self.0.session.write().await;
-        session.compact(client, keep_messages).await
+        session.compact_with(client, keep_messages, strategy).await
     }
 }
 
@@ -0,0 +1,214 @@
+//! Branches of the message tree.
+//!
+//! Every message points at its parent, so rewinding (switching the leaf to an
+//! earlier message) and continuing creates a new branch. A branch is named by
+//! its leaf: a message no other message points at.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+
+use super::{MessageId, Session, SessionMessage};
+use crate::common::clock;
+
+/// A branch of the message tree, identified by its leaf message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BranchInfo {
+    pub leaf_id: MessageId,
+    /// Last message this branch shares with another branch, if any.
+    pub fork_point: Option<MessageId>,
+    /// Messages from the root down to the leaf.
+    pub depth: usize,
+    /// Messages after the fork point, owned by this branch alone.
+    pub exclusive: usize,
+    pub is_current: bool,
+    /// Timestamp of the leaf message.
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Session {
+    /// All branches, oldest leaf first.
+    pub fn branches(&self) -> Vec<BranchInfo> {
+        let children = self.child_counts();
+        let mut branches: Vec<_> = self
+            .messages
+            .iter()
+            .filter(|m| !children.contains_key(&m.id))
+            .map(|leaf| {
+                let exclusive = self.exclusive_part(&leaf.id, &children);
+                let depth = self.branch_to(&leaf.id).len();
+                BranchInfo {
+                    leaf_id: leaf.id.clone(),
+                    fork_point: exclusive.last().and_then(|m| m.parent_id.clone()),
+                    depth,
+                    exclusive: exclusive.len(),
+                    is_current: self.current_leaf_id.as_ref() == Some(&leaf.id),
+                    updated_at: leaf.timestamp,
+                }
+            })
+            .collect();
+        branches.sort_by_key(|b| b.updated_at);
+        branches
+    }
+
+    /// Messages owned only by the branch ending at `leaf`, in order.
+    pub fn branch_messages(&self, leaf: &MessageId) -> Vec<&SessionMessage> {
+        let mut exclusive = self.exclusive_part(leaf, &self.child_counts());
+        exclusive.reverse();
+        exclusive
+    }
+
+    /// Continue the conversation from `message_id`, which may be a leaf or
+    /// any earlier message. Returns `false` if the message is unknown.
+    pub fn switch_branch(&mut self, message_id: &MessageId) -> bool {
+        if !self.messages.iter().any(|m| &m.id == message_id) {
+            return false;
+        }
+        self.current_leaf_id = Some(message_id.clone());
+        self.updated_at = clock::now_utc();
+        true
+    }
+
+    /// Remove the messages owned only by the branch ending at `leaf`.
+    ///
+    /// Returns the number of removed messages; `0` if `leaf` is not a leaf or
+    /// the branch contains the current position.
+    pub fn delete_branch(&mut self, leaf: &MessageId) -> usize {
+        let children = self.child_counts();
+        if children.contains_key(leaf) {
+            return 0;
+        }
+        let doomed: HashSet<MessageId> = self
+            .exclusive_part(leaf, &children)
+            .into_iter()
+            .map(|m| m.id.clone())
+            .collect();
+        if self
+            .current_leaf_id
+            .as_ref()
+            .is_some_and(|current| doomed.contains(current))
+        {
+            return 0;
+        }
+
+        self.messages.retain(|m| !doomed.contains(&m.id));
+        if !doomed.is_empty() {
+            self.updated_at = clock::now_utc();
+        }
+        doomed.len()
+    }
+
+    /// Record a summary of the branch ending at `leaf` on the current branch.
+    ///
+    /// The branch itself is kept; delete it afterwards if it is no longer
+    /// needed. Returns `false` if `leaf` is unknown.
+    pub fn merge_branch(&mut self, leaf: &MessageId, summary: impl Into<String>) -> bool {
+        if !self.messages.iter().any(|m| &m.id == leaf) {
+            return false;
+        }
+        self.add_interjection(format!("[Merged branch summary]\n{}", summary.into()));
+        true
+    }
+
+    fn child_counts(&self) -> HashMap<&MessageId, usize> {
+        let mut counts = HashMap::new();
+        for parent in self.messages.iter().filter_map(|m| m.parent_id.as_ref()) {
+            *counts.entry(parent).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Walk up from `leaf` until the parent has other children (leaf first).
+    fn exclusive_part(
+        &self,
+        leaf: &MessageId,
+        children: &HashMap<&MessageId, usize>,
+    ) -> Vec<&SessionMessage> {
+        let mut path = self.branch_to(leaf);
+        let mut exclusive = Vec::new();
+        while let Some(msg) = path.pop() {
+            exclusive.push(msg);
+            let shared = msg
+                .parent_id
+                .as_ref()
+                .is_some_and(|p| children.get(p).copied().unwrap_or(0) > 1);
+            if shared {
+                break;
+            }
+        }
+        exclusive
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::state::SessionConfig;
+    use crate::types::ContentBlock;
+
+    fn text(session: &mut Session, user: bool, text: &str) -> MessageId {
+        let block = vec![ContentBlock::text(text)];
+        session.add_message(if user {
+            SessionMessage::user(block)
+        } else {
+            SessionMessage::assistant(block)
+        });
+        session.current_leaf_id.clone().unwrap()
+    }
+
+    /// Root -> answer, then two continuations from the answer.
+    fn forked_session() -> (Session, MessageId, MessageId, MessageId) {
+        let mut session = Session::new(SessionConfig::default());
+        text(&mut session, true, "Pick a language");
+        let fork = text(&mut session, false, "Rust or Go?");
+        text(&mut session, true, "Go");
+        let go = text(&mut session, false, "Go it is");
+        assert!(session.switch_branch(&fork));
+        let rust = text(&mut session, true, "Rust");
+        (session, fork, go, rust)
+    }
+
+    #[test]
+    fn test_list_branches() {
+        let (session, fork, go, rust) = forked_session();
+        let branches = session.branches();
+
+        assert_eq!(branches.len(), 2);
+        assert_eq!(branches[0].leaf_id, go);
+        assert_eq!(branches[0].fork_point, Some(fork.clone()));
+        assert_eq!(branches[0].depth, 4);
+        assert_eq!(branches[0].exclusive, 2);
+        assert!(!branches[0].is_current);
+        assert_eq!(branches[1].leaf_id, rust);
+        assert_eq!(branches[1].exclusive, 1);
+        assert!(branches[1].is_current);
+        assert_eq!(session.branch_messages(&go).len(), 2);
+    }
+
+    #[test]
+    fn test_switch_and_delete() {
+        let (mut session, _, go, rust) = forked_session();
+
+        assert_eq!(session.delete_branch(&rust), 0);
+        assert!(!session.switch_branch(&MessageId::new()));
+        assert!(session.switch_branch(&go));
+        assert_eq!(session.current_branch().len(), 4);
+
+        assert_eq!(session.delete_branch(&rust), 1);
+        assert_eq!(session.messages.len(), 4);
+        assert_eq!(session.branches().len(), 1);
+        assert!(session.branches()[0].fork_point.is_none());
+    }
+
+    #[test]
+    fn test_merge_branch() {
+        let (mut session, _, go, _) = forked_session();
+
+        assert!(session.merge_branch(&go, "Tried Go; rejected for lack of generics"));
+        let branch = session.current_branch();
+        assert_eq!(branch.len(), 3);
+        let last = branch.last().unwrap();
+        assert_eq!(last.content.len(), 2);
+        assert!(!session.merge_branch(&MessageId::new(), "missing"));
+    }
+}
@@ -0,0 +1,284 @@
+//! Incremental views of a session for polling clients.
+//!
+//! [`Session::diff`] returns what happened after a message or a point in time:
+//! messages appended to the current branch, the usage they reported, todo
+//! changes and state transitions. Todo changes and transitions come from a
+//! bounded change log written by [`Session::set_todos`] and
+//! [`Session::set_state`]; todos are matched by content since `TodoWrite`
+//! replaces the whole list on every call.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{MessageId, Session, SessionMessage, SessionState};
+use crate::common::clock;
+use crate::session::types::{TodoItem, TodoStatus};
+use crate::types::TokenUsage;
+
+/// Change log entries kept per session; the oldest is dropped beyond this.
+pub const MAX_CHANGE_LOG: usize = 100;
+
+/// Where a [`Session::diff`] starts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiffSince {
+    /// Everything after this message of the current branch.
+    Message(MessageId),
+    /// Everything after this instant.
+    Time(DateTime<Utc>),
+}
+
+impl From<MessageId> for DiffSince {
+    fn from(id: MessageId) -> Self {
+        Self::Message(id)
+    }
+}
+
+impl From<DateTime<Utc>> for DiffSince {
+    fn from(at: DateTime<Utc>) -> Self {
+        Self::Time(at)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TodoChange {
+    Added {
+        content: String,
+        status: TodoStatus,
+    },
+    StatusChanged {
+        content: String,
+        from: TodoStatus,
+        to: TodoStatus,
+    },
+    Removed {
+        content: String,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SessionChange {
+    State {
+        from: SessionState,
+        to: SessionState,
+    },
+    Todo(TodoChange),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangeRecord {
+    pub at: DateTime<Utc>,
+    pub change: SessionChange,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateTransition {
+    pub from: SessionState,
+    pub to: SessionState,
+    pub at: DateTime<Utc>,
+}
+
+/// What changed in a session since a [`DiffSince`] point.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SessionDelta {
+    /// Messages appended to the current branch, oldest first.
+    pub messages: Vec<SessionMessage>,
+    /// Usage reported by those messages.
+    pub usage: TokenUsage,
+    pub todo_changes: Vec<TodoChange>,
+    pub state_transitions: Vec<StateTransition>,
+    pub state: SessionState,
+    /// Latest change covered; pass back as `DiffSince::Time` on the next poll.
+    pub as_of: DateTime<Utc>,
+}
+
+impl SessionDelta {
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+            && self.todo_changes.is_empty()
+            && self.state_transitions.is_empty()
+    }
+}
+
+impl Session {
+    /// Changes after `since`.
+    ///
+    /// Returns `None` when `since` names a message that is not on the current
+    /// branch (e.g. it was compacted away or belongs to another branch); the
+    /// caller should fetch the whole session instead. Todo and state changes
+    /// older than the last [`MAX_CHANGE_LOG`] entries are not reported.
+    pub fn diff(&self, since: impl Into<DiffSince>) -> Option<SessionDelta> {
+        let branch = self.current_branch();
+        let (start, after) = match since.into() {
+            DiffSince::Message(id) => {
+                let index = branch.iter().position(|m| m.id == id)?;
+                (index + 1, branch[index].timestamp)
+            }
+            DiffSince::Time(at) => {
+                let start = branch
+                    .iter()
+                    .position(|m| m.timestamp > at)
+                    .unwrap_or(branch.len());
+                (start, at)
+            }
+        };
+
+        let messages: Vec<SessionMessage> = branch[start..].iter().map(|m| (*m).clone()).collect();
+        let mut usage = TokenUsage::default();
+        for message_usage in messages.iter().filter_map(|m| m.usage.as_ref()) {
+            usage.add(message_usage);
+        }
+
+        let as_of = branch
+            .last()
+            .map(|m| m.timestamp)
+            .into_iter()
+            .chain(self.change_log.back().map(|r| r.at))
+            .fold(self.updated_at, DateTime::max);
+
+        let mut todo_changes = Vec::new();
+        let mut state_transitions = Vec::new();
+        for record in self.change_log.iter().filter(|r| r.at > after) {
+            match &record.change {
+                SessionChange::State { from, to } => state_transitions.push(StateTransition {
+                    from: *from,
+                    to: *to,
+                    at: record.at,
+                }),
+                SessionChange::Todo(change) => todo_changes.push(change.clone()),
+            }
+        }
+
+        Some(SessionDelta {
+            messages,
+            usage,
+            todo_changes,
+            state_transitions,
+            state: self.state,
+            as_of,
+        })
+    }
+
+    pub(crate) fn log_change(&mut self, change: SessionChange) {
+        if self.change_log.len() >= MAX_CHANGE_LOG {
+            self.change_log.pop_front();
+        }
+        self.change_log.push_back(ChangeRecord {
+            at: clock::now_utc(),
+            change,
+        });
+    }
+}
+
+/// Differences between two todo lists, matched by content.
+pub(crate) fn todo_changes(before: &[TodoItem], after: &[TodoItem]) -> Vec<TodoChange> {
+    let previous: HashMap<&str, TodoStatus> = before
+        .iter()
+        .map(|t| (t.content.as_str(), t.status))
+        .collect();
+    let mut changes = Vec::new();
+    for todo in after {
+        match previous.get(todo.content.as_str()) {
+            None => changes.push(TodoChange::Added {
+                content: todo.content.clone(),
+                status: todo.status,
+            }),
+            Some(&from) if from != todo.status => changes.push(TodoChange::StatusChanged {
+                content: todo.content.clone(),
+                from,
+                to: todo.status,
+            }),
+            Some(_) => {}
+        }
+    }
+    changes.extend(
+        before
+            .iter()
+            .filter(|t| !after.iter().any(|a| a.content == t.content))
+            .map(|t| TodoChange::Removed {
+                content: t.content.clone(),
+            }),
+    );
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::state::SessionConfig;
+    use crate::types::ContentBlock;
+
+    #[test]
+    fn test_diff_since_message() {
+        let mut session = Session::new(SessionConfig::default());
+        session.set_todos(vec![TodoItem::new(
+            session.id,
+            "Write docs",
+            "Writing docs",
+        )]);
+        session.add_message(SessionMessage::user(vec![ContentBlock::text("start")]));
+        let cursor = session.current_leaf_id.clone().unwrap();
+
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let mut reply = SessionMessage::assistant(vec![ContentBlock::text("on it")]);
+        reply.usage = Some(TokenUsage {
+            input_tokens: 10,
+            output_tokens: 5,
+            ..Default::default()
+        });
+        session.add_message(reply);
+        session.set_state(SessionState::Active);
+        let mut todos = session.todos.clone();
+        todos[0].status = TodoStatus::Completed;
+        todos.push(TodoItem::new(session.id, "Publish", "Publishing"));
+        session.set_todos(todos);
+
+        let delta = session.diff(cursor).unwrap();
+        assert_eq!(delta.messages.len(), 1);
+        assert_eq!(delta.usage.output_tokens, 5);
+        assert_eq!(
+            delta.todo_changes,
+            vec![
+                TodoChange::StatusChanged {
+                    content: "Write docs".into(),
+                    from: TodoStatus::Pending,
+                    to: TodoStatus::Completed,
+                },
+                TodoChange::Added {
+                    content: "Publish".into(),
+                    status: TodoStatus::Pending,
+                },
+            ]
+        );
+        assert_eq!(delta.state_transitions.len(), 1);
+        assert_eq!(delta.state, SessionState::Active);
+
+        let delta = session.diff(delta.as_of).unwrap();
+        assert!(delta.is_empty());
+        assert!(session.diff(MessageId::new()).is_none());
+    }
+
+    #[test]
+    fn test_diff_since_time() {
+        let mut session = Session::new(SessionConfig::default());
+        session.add_message(SessionMessage::user(vec![ContentBlock::text("one")]));
+        session.set_todos(vec![TodoItem::new(session.id, "Old", "Old")]);
+        let delta = session.diff(session.created_at - chrono::Duration::seconds(1));
+        let delta = delta.unwrap();
+        assert_eq!(delta.messages.len(), 1);
+        assert_eq!(delta.todo_changes.len(), 1);
+
+        session.set_todos(Vec::new());
+        assert_eq!(
+            todo_changes(&[TodoItem::new(session.id, "Old", "Old")], &[]),
+            vec![TodoChange::Removed {
+                content: "Old".into()
+            }]
+        );
+        let future = clock::now_utc() + chrono::Duration::seconds(60);
+        assert!(session.diff(future).unwrap().is_empty());
+    }
+}
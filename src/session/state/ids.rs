@@ -5,13 +5,15 @@ use std::str::FromStr;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::common::clock;
+
 #[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct SessionId(pub Uuid);
 
 impl SessionId {
     pub fn new() -> Self {
-        Self(Uuid::new_v4())
+        Self(clock::new_uuid())
     }
 
     pub fn parse(s: &str) -> Option<Self> {
@@ -63,7 +65,7 @@ pub struct MessageId(pub String);
 
 impl MessageId {
     pub fn new() -> Self {
-        Self(Uuid::new_v4().to_string())
+        Self(clock::new_uuid().to_string())
     }
 
     pub fn from_string(s: impl Into<String>) -> Self {
@@ -4,6 +4,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use super::ids::MessageId;
+use crate::common::clock;
 use crate::session::types::EnvironmentContext;
 use crate::types::{ContentBlock, Message, Role, TokenUsage};
 
@@ -57,7 +58,7 @@ impl SessionMessage {
             is_sidechain: false,
             is_compact_summary: false,
             usage: None,
-            timestamp: Utc::now(),
+            timestamp: clock::now_utc(),
             metadata: MessageMetadata::default(),
             environment: None,
         }
@@ -72,7 +73,7 @@ impl SessionMessage {
             is_sidechain: false,
             is_compact_summary: false,
             usage: None,
-            timestamp: Utc::now(),
+            timestamp: clock::now_utc(),
             metadata: MessageMetadata::default(),
             environment: None,
         }
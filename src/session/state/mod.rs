@@ -1,16 +1,25 @@
 //! Session state management.
 
+mod branch;
 mod config;
+mod diff;
 mod enums;
 mod ids;
 mod message;
 mod policy;
+mod snapshot;
 
+pub use branch::BranchInfo;
 pub use config::SessionConfig;
+pub use diff::{
+    ChangeRecord, DiffSince, MAX_CHANGE_LOG, SessionChange, SessionDelta, StateTransition,
+    TodoChange,
+};
 pub use enums::{SessionState, SessionType};
 pub use ids::{MessageId, SessionId};
 pub use message::{MessageMetadata, SessionMessage, ThinkingMetadata, ToolResultMeta};
 pub use policy::{PermissionMode, SessionPermissions, SessionToolLimits};
+pub use snapshot::{MAX_SNAPSHOTS, SessionSnapshot};
 
 use std::collections::{HashMap, VecDeque};
 
@@ -18,6 +27,7 @@ use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
+use crate::common::clock;
 use crate::session::types::{CompactRecord, Plan, TodoItem, TodoStatus};
 use crate::types::{CacheControl, CacheTtl, ContentBlock, Message, Role, TokenUsage, Usage};
 
@@ -29,6 +39,9 @@ pub struct Session {
     pub parent_id: Option<SessionId>,
     pub session_type: SessionType,
     pub tenant_id: Option<String>,
+    /// Principal that owns the session; see [`AccessPolicy`](crate::session::AccessPolicy).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
     pub state: SessionState,
     pub config: SessionConfig,
     pub permissions: SessionPermissions,
@@ -50,6 +63,9 @@ pub struct Session {
     pub current_plan: Option<Plan>,
     #[serde(default)]
     pub compact_history: VecDeque<CompactRecord>,
+    /// Recent state and todo changes, read by [`diff`](Self::diff).
+    #[serde(default, skip_serializing_if = "VecDeque::is_empty")]
+    pub change_log: VecDeque<ChangeRecord>,
 }
 
 impl Session {
@@ -80,7 +96,7 @@ impl Session {
         session_type: SessionType,
         config: SessionConfig,
     ) -> Self {
-        let now = Utc::now();
+        let now = clock::now_utc();
         let expires_at = config
             .ttl_secs
             .map(|ttl| now + chrono::Duration::seconds(ttl as i64));
@@ -90,6 +106,7 @@ impl Session {
             parent_id,
             session_type,
             tenant_id: None,
+            owner: None,
             state: SessionState::Created,
             permissions: config.permissions.clone(),
             config,
@@ -107,6 +124,7 @@ impl Session {
             todos: Vec::with_capacity(8),
             current_plan: None,
             compact_history: VecDeque::new(),
+            change_log: VecDeque::new(),
         }
     }
 
@@ -129,7 +147,8 @@ impl Session {
     }
 
     pub fn is_expired(&self) -> bool {
-        self.expires_at.is_some_and(|expires| Utc::now() > expires)
+        self.expires_at
+            .is_some_and(|expires| clock::now_utc() > expires)
     }
 
     pub fn add_message(&mut self, mut message: SessionMessage) {
@@ -141,15 +160,23 @@ impl Session {
             self.total_usage.add(usage);
         }
         self.messages.push(message);
-        self.updated_at = Utc::now();
+        self.updated_at = clock::now_utc();
     }
 
     pub fn current_branch(&self) -> Vec<&SessionMessage> {
+        match &self.current_leaf_id {
+            Some(leaf) => self.branch_to(leaf),
+            None => Vec::new(),
+        }
+    }
+
+    /// Messages from the root of the tree down to `leaf`, in order.
+    pub fn branch_to(&self, leaf: &MessageId) -> Vec<&SessionMessage> {
         let index: HashMap<&MessageId, &SessionMessage> =
             self.messages.iter().map(|m| (&m.id, m)).collect();
 
         let mut result = Vec::new();
-        let mut current_id = self.current_leaf_id.as_ref();
+        let mut current_id = Some(leaf);
 
         while let Some(id) = current_id {
             if let Some(&msg) = index.get(id) {
@@ -164,6 +191,46 @@ impl Session {
         result
     }
 
+    /// Branch the conversation at `message_id` into a new child session.
+    ///
+    /// The child holds copies of the messages from the root down to
+    /// `message_id` and continues from there. The copies get fresh ids, so
+    /// backends that key messages by id keep the two sessions apart. Returns
+    /// `None` if the message is not part of this session.
+    pub fn fork_at(&self, message_id: &MessageId) -> Option<Session> {
+        let branch = self.branch_to(message_id);
+        if branch.last().map(|m| &m.id) != Some(message_id) {
+            return None;
+        }
+
+        let mut forked = Self::init(
+            SessionId::new(),
+            Some(self.id),
+            self.session_type.clone(),
+            self.config.clone(),
+        );
+        forked.tenant_id = self.tenant_id.clone();
+        forked.permissions = self.permissions.clone();
+        forked.summary = self.summary.clone();
+        forked.static_context_hash = self.static_context_hash.clone();
+        forked.todos = self.todos.clone();
+        forked.current_plan = self.current_plan.clone();
+        forked.compact_history = self.compact_history.clone();
+        let mut ids: HashMap<MessageId, MessageId> = HashMap::new();
+        forked.messages = branch
+            .into_iter()
+            .map(|msg| {
+                let mut cloned = msg.clone();
+                cloned.id = MessageId::new();
+                cloned.parent_id = msg.parent_id.as_ref().and_then(|p| ids.get(p).cloned());
+                ids.insert(msg.id.clone(), cloned.id.clone());
+                cloned
+            })
+            .collect();
+        forked.current_leaf_id = forked.messages.last().map(|m| m.id.clone());
+        Some(forked)
+    }
+
     /// Convert session messages to API format with default caching (5m TTL).
     pub fn to_api_messages(&self) -> Vec<Message> {
         self.to_api_messages_with_cache(Some(CacheTtl::FiveMinutes))
@@ -207,13 +274,22 @@ impl Session {
     }
 
     pub fn set_state(&mut self, state: SessionState) {
+        if state != self.state {
+            self.log_change(SessionChange::State {
+                from: self.state,
+                to: state,
+            });
+        }
         self.state = state;
-        self.updated_at = Utc::now();
+        self.updated_at = clock::now_utc();
     }
 
     pub fn set_todos(&mut self, todos: Vec<TodoItem>) {
+        for change in diff::todo_changes(&self.todos, &todos) {
+            self.log_change(SessionChange::Todo(change));
+        }
         self.todos = todos;
-        self.updated_at = Utc::now();
+        self.updated_at = clock::now_utc();
     }
 
     pub fn todos_in_progress_count(&self) -> usize {
@@ -228,21 +304,21 @@ impl Session {
         if let Some(n) = name {
             plan = plan.name(n);
         }
-        self.updated_at = Utc::now();
+        self.updated_at = clock::now_utc();
         self.current_plan.insert(plan)
     }
 
     pub fn update_plan_content(&mut self, content: String) {
         if let Some(ref mut plan) = self.current_plan {
             plan.content = content;
-            self.updated_at = Utc::now();
+            self.updated_at = clock::now_utc();
         }
     }
 
     pub fn exit_plan_mode(&mut self) -> Option<Plan> {
         if let Some(ref mut plan) = self.current_plan {
             plan.approve();
-            self.updated_at = Utc::now();
+            self.updated_at = clock::now_utc();
         }
         self.current_plan.take()
     }
@@ -250,7 +326,7 @@ impl Session {
     pub fn cancel_plan(&mut self) -> Option<Plan> {
         if let Some(ref mut plan) = self.current_plan {
             plan.cancel();
-            self.updated_at = Utc::now();
+            self.updated_at = clock::now_utc();
         }
         self.current_plan.take()
     }
@@ -266,12 +342,12 @@ impl Session {
             self.compact_history.pop_front();
         }
         self.compact_history.push_back(record);
-        self.updated_at = Utc::now();
+        self.updated_at = clock::now_utc();
     }
 
     pub fn update_summary(&mut self, summary: impl Into<String>) {
         self.summary = Some(summary.into());
-        self.updated_at = Utc::now();
+        self.updated_at = clock::now_utc();
     }
 
     pub fn add_user_message(&mut self, content: impl Into<String>) {
@@ -299,6 +375,27 @@ impl Session {
         self.add_message(msg);
     }
 
+    /// Add user input that arrived mid-run.
+    ///
+    /// Appended to the trailing user turn (e.g. tool results) when there is one,
+    /// so the conversation keeps alternating roles.
+    pub fn add_interjection(&mut self, content: impl Into<String>) {
+        let block = ContentBlock::text(content.into());
+        let leaf = self.current_leaf_id.clone();
+        match self
+            .messages
+            .iter_mut()
+            .rev()
+            .find(|m| Some(&m.id) == leaf.as_ref())
+        {
+            Some(last) if last.role == Role::User => {
+                last.content.push(block);
+                self.updated_at = clock::now_utc();
+            }
+            _ => self.add_message(SessionMessage::user(vec![block])),
+        }
+    }
+
     pub fn should_compact(&self, max_tokens: u64, threshold: f32, keep_messages: usize) -> bool {
         self.messages.len() > keep_messages
             && self.current_input_tokens as f32 > max_tokens as f32 * threshold
@@ -313,28 +410,110 @@ impl Session {
         &mut self,
         client: &crate::Client,
         keep_messages: usize,
+    ) -> crate::Result<crate::types::CompactResult> {
+        self.compact_with(client, keep_messages, None).await
+    }
+
+    /// Summarize all but the last `keep_messages` messages. Without a
+    /// strategy, a short built-in prompt (text truncated to 800 characters)
+    /// is sent to the small model with 2000 max tokens; a strategy supplies
+    /// the prompt, model and summary length instead. With a sliding window
+    /// configured, the window replaces `keep_messages` and no request is made.
+    pub async fn compact_with(
+        &mut self,
+        client: &crate::Client,
+        keep_messages: usize,
+        strategy: Option<&crate::session::CompactStrategy>,
     ) -> crate::Result<crate::types::CompactResult> {
         use crate::client::ModelType;
         use crate::client::messages::CreateMessageRequest;
+        use crate::session::CompactExecutor;
+        use crate::session::compact::{RequiredMention, bullet_list, extract_summary};
         use crate::types::CompactResult;
 
+        if let Some(window) = strategy.and_then(|s| s.sliding_window.as_ref()) {
+            return Ok(window.apply(self));
+        }
         if self.messages.len() <= keep_messages {
             return Ok(CompactResult::NotNeeded);
         }
 
+        let split_point = self.messages.len() - keep_messages;
+        let Some(strategy) = strategy else {
+            let summary_prompt = Self::format_for_summary(&self.messages[..split_point]);
+            let model = client.adapter().model(ModelType::Small).to_string();
+            let request = CreateMessageRequest::new(&model, vec![Message::user(&summary_prompt)])
+                .max_tokens(2000);
+            let response = client.send(request).await?;
+            return Ok(self.replace_with_summary(split_point, response.text()));
+        };
+
+        let to_summarize: Vec<_> = self.messages[..split_point].iter().collect();
+        let summary_prompt = CompactExecutor::new(strategy.clone()).summary_prompt(&to_summarize);
+        let models = &client.adapter().config().models;
+        let summarize = |model: String, prompt: String| async move {
+            let request = CreateMessageRequest::new(&model, vec![Message::user(prompt)])
+                .max_tokens(strategy.max_summary_tokens);
+            let response = client.send(request).await?;
+            crate::Result::Ok(extract_summary(&response.text()).to_string())
+        };
+
+        let mut summary = summarize(
+            strategy.resolve_model(models).to_string(),
+            summary_prompt.clone(),
+        )
+        .await?;
+
+        if let Some(verify) = &strategy.verify {
+            let required = RequiredMention::collect(self, split_point);
+            let missing_from = |summary: &str| -> Vec<&RequiredMention> {
+                required
+                    .iter()
+                    .filter(|r| !r.is_mentioned(summary))
+                    .collect()
+            };
+            let mut missing = missing_from(&summary);
+            let mut retries = 0;
+            while !missing.is_empty() && retries < verify.max_retries {
+                retries += 1;
+                tracing::warn!(
+                    session_id = %self.id,
+                    missing = missing.len(),
+                    retry = retries,
+                    "Compaction summary dropped required context, retrying"
+                );
+                let prompt = format!(
+                    "{}\n\n# Required Context\n\nA previous summary omitted the following. \
+                     The summary must mention each of them:\n{}",
+                    summary_prompt,
+                    bullet_list(&missing)
+                );
+                summary = summarize(
+                    models.resolve_alias(&verify.retry_model).to_string(),
+                    prompt,
+                )
+                .await?;
+                missing = missing_from(&summary);
+            }
+            if !missing.is_empty() {
+                summary.push_str("\n\nStill relevant (not covered above):\n");
+                summary.push_str(&bullet_list(&missing));
+            }
+        }
+
+        Ok(self.replace_with_summary(split_point, summary))
+    }
+
+    /// Replace `messages[..split_point]` with a compact-summary message.
+    fn replace_with_summary(
+        &mut self,
+        split_point: usize,
+        summary: String,
+    ) -> crate::types::CompactResult {
         let tokens_before = self.current_input_tokens;
         let original_count = self.messages.len();
-        let split_point = original_count - keep_messages;
-        let to_summarize: Vec<_> = self.messages[..split_point].to_vec();
         let to_keep: Vec<_> = self.messages[split_point..].to_vec();
 
-        let summary_prompt = Self::format_for_summary(&to_summarize);
-        let model = client.adapter().model(ModelType::Small).to_string();
-        let request = CreateMessageRequest::new(&model, vec![Message::user(&summary_prompt)])
-            .max_tokens(2000);
-        let response = client.send(request).await?;
-        let summary = response.text();
-
         // Build new message list before modifying self (swap pattern for data safety)
         let mut new_messages = Vec::with_capacity(1 + to_keep.len());
         let summary_msg = SessionMessage::user(vec![ContentBlock::text(format!(
@@ -358,7 +537,7 @@ impl Session {
         // This also prevents immediate re-compaction since should_compact() returns false when 0.
         self.current_input_tokens = 0;
         self.summary = Some(summary.clone());
-        self.updated_at = Utc::now();
+        self.updated_at = clock::now_utc();
 
         let record = CompactRecord::new(self.id)
             .counts(original_count, self.messages.len())
@@ -366,12 +545,12 @@ impl Session {
             .saved_tokens(tokens_before as usize);
         self.record_compact(record);
 
-        Ok(CompactResult::Compacted {
+        crate::types::CompactResult::Compacted {
             original_count,
             new_count: self.messages.len(),
             saved_tokens: tokens_before as usize,
             summary,
-        })
+        }
     }
 
     fn format_for_summary(messages: &[SessionMessage]) -> String {
@@ -414,7 +593,7 @@ impl Session {
     pub fn clear_messages(&mut self) {
         self.messages.clear();
         self.current_leaf_id = None;
-        self.updated_at = Utc::now();
+        self.updated_at = clock::now_utc();
     }
 }
 
@@ -423,6 +602,21 @@ mod tests {
     use super::*;
     use crate::types::{ContentBlock, Role};
 
+    #[test]
+    fn test_add_interjection() {
+        let mut session = Session::new(SessionConfig::default());
+        session.add_interjection("first");
+        assert_eq!(session.messages.len(), 1);
+
+        session.add_tool_results(vec![crate::types::ToolResultBlock::success("t1", "ok")]);
+        session.add_interjection("skip the tests");
+        assert_eq!(session.messages.len(), 2);
+        let last = session.messages.last().unwrap();
+        assert_eq!(last.role, Role::User);
+        assert_eq!(last.content.len(), 2);
+        assert_eq!(last.content[1].as_text(), Some("skip the tests"));
+    }
+
     #[test]
     fn test_session_creation() {
         let config = SessionConfig::default();
@@ -460,6 +654,33 @@ mod tests {
         assert_eq!(branch[1].role, Role::Assistant);
     }
 
+    #[test]
+    fn test_fork_at() {
+        let mut session = Session::new(SessionConfig::default());
+        session.add_message(SessionMessage::user(vec![ContentBlock::text("Hello")]));
+        session.add_message(SessionMessage::assistant(vec![ContentBlock::text("Hi!")]));
+        let fork_point = session.current_leaf_id.clone().unwrap();
+        session.add_message(SessionMessage::user(vec![ContentBlock::text("Use Go")]));
+
+        let forked = session.fork_at(&fork_point).unwrap();
+
+        assert_ne!(forked.id, session.id);
+        assert_eq!(forked.parent_id, Some(session.id));
+        let branch = forked.current_branch();
+        assert_eq!(branch.len(), 2);
+        assert_eq!(branch[1].role, session.messages[1].role);
+        assert!(
+            branch
+                .iter()
+                .all(|m| session.messages.iter().all(|p| p.id != m.id))
+        );
+        assert_eq!(branch[1].parent_id.as_ref(), Some(&branch[0].id));
+        assert!(branch.iter().all(|m| !m.is_sidechain));
+        assert_eq!(session.current_branch().len(), 3);
+
+        assert!(session.fork_at(&MessageId::new()).is_none());
+    }
+
     #[test]
     fn test_session_expiry() {
         let config = SessionConfig {
@@ -7,6 +7,8 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
+use crate::permissions::{PermissionAuditEntry, PermissionDecision};
+
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum PermissionMode {
@@ -30,6 +32,26 @@ pub struct SessionPermissions {
     pub deny: Vec<String>,
     #[serde(default)]
     pub tool_limits: HashMap<String, SessionToolLimits>,
+    /// Permission decisions made in the session, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub audit: Vec<PermissionAuditEntry>,
+}
+
+impl SessionPermissions {
+    /// Audit entries for `tool_name`.
+    pub fn audit_for<'a>(
+        &'a self,
+        tool_name: &'a str,
+    ) -> impl Iterator<Item = &'a PermissionAuditEntry> + 'a {
+        self.audit.iter().filter(move |e| e.tool_name == tool_name)
+    }
+
+    /// Audit entries of denied calls.
+    pub fn denials(&self) -> impl Iterator<Item = &PermissionAuditEntry> {
+        self.audit
+            .iter()
+            .filter(|e| e.decision == PermissionDecision::Deny)
+    }
 }
 
 /// Session-level tool limits for storage.
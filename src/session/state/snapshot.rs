@@ -0,0 +1,143 @@
+//! Point-in-time snapshots of conversation state.
+//!
+//! A snapshot captures messages, todos, plan, summary, compaction history and
+//! token counters so destructive operations (compaction, clearing) or a failed
+//! agent run can be undone in one step. Snapshots are standalone records: the
+//! session document never embeds them, and [`Persistence`] backends store them
+//! next to the session (see [`Persistence::save_snapshot`]).
+//!
+//! [`Persistence`]: crate::session::Persistence
+//! [`Persistence::save_snapshot`]: crate::session::Persistence::save_snapshot
+
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{MessageId, Session, SessionId, SessionMessage};
+use crate::common::clock;
+use crate::session::types::{CompactRecord, Plan, TodoItem};
+use crate::types::TokenUsage;
+
+/// Snapshots kept per session by [`SessionManager`](crate::session::SessionManager);
+/// the oldest is dropped beyond this.
+pub const MAX_SNAPSHOTS: usize = 10;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub id: Uuid,
+    pub session_id: SessionId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub messages: Vec<SessionMessage>,
+    pub current_leaf_id: Option<MessageId>,
+    pub summary: Option<String>,
+    pub todos: Vec<TodoItem>,
+    pub current_plan: Option<Plan>,
+    pub compact_history: VecDeque<CompactRecord>,
+    pub total_usage: TokenUsage,
+    pub current_input_tokens: u64,
+    pub total_cost_usd: Decimal,
+}
+
+impl Session {
+    /// Capture the current conversation state.
+    pub fn snapshot(&self) -> SessionSnapshot {
+        self.capture_snapshot(None)
+    }
+
+    /// Like [`snapshot`](Self::snapshot), with a label such as "before compaction".
+    pub fn snapshot_labeled(&self, label: impl Into<String>) -> SessionSnapshot {
+        self.capture_snapshot(Some(label.into()))
+    }
+
+    /// Roll back to `snapshot`. Returns `false`, leaving the session
+    /// unchanged, if the snapshot was taken of another session.
+    pub fn restore(&mut self, snapshot: &SessionSnapshot) -> bool {
+        if snapshot.session_id != self.id {
+            return false;
+        }
+        let snapshot = snapshot.clone();
+        self.messages = snapshot.messages;
+        self.current_leaf_id = snapshot.current_leaf_id;
+        self.summary = snapshot.summary;
+        self.todos = snapshot.todos;
+        self.current_plan = snapshot.current_plan;
+        self.compact_history = snapshot.compact_history;
+        self.total_usage = snapshot.total_usage;
+        self.current_input_tokens = snapshot.current_input_tokens;
+        self.total_cost_usd = snapshot.total_cost_usd;
+        self.updated_at = clock::now_utc();
+        true
+    }
+
+    fn capture_snapshot(&self, label: Option<String>) -> SessionSnapshot {
+        SessionSnapshot {
+            id: clock::new_uuid(),
+            session_id: self.id,
+            label,
+            created_at: clock::now_utc(),
+            messages: self.messages.clone(),
+            current_leaf_id: self.current_leaf_id.clone(),
+            summary: self.summary.clone(),
+            todos: self.todos.clone(),
+            current_plan: self.current_plan.clone(),
+            compact_history: self.compact_history.clone(),
+            total_usage: self.total_usage.clone(),
+            current_input_tokens: self.current_input_tokens,
+            total_cost_usd: self.total_cost_usd,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::state::SessionConfig;
+    use crate::session::types::TodoStatus;
+    use crate::types::ContentBlock;
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let mut session = Session::new(SessionConfig::default());
+        session.add_message(SessionMessage::user(vec![ContentBlock::text("keep me")]));
+        session
+            .todos
+            .push(TodoItem::new(session.id, "Refactor", "Refactoring"));
+        session.current_input_tokens = 1200;
+        let snapshot = session.snapshot_labeled("before clear");
+        assert_eq!(snapshot.label.as_deref(), Some("before clear"));
+
+        session.clear_messages();
+        session.todos[0].status = TodoStatus::Completed;
+        session.current_input_tokens = 0;
+
+        assert!(session.restore(&snapshot));
+        assert_eq!(session.messages.len(), 1);
+        assert!(session.current_leaf_id.is_some());
+        assert_eq!(session.todos[0].status, TodoStatus::Pending);
+        assert_eq!(session.current_input_tokens, 1200);
+
+        let other = Session::new(SessionConfig::default());
+        assert!(!session.restore(&other.snapshot()));
+        assert_eq!(session.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_session_document_has_no_snapshots() {
+        let mut session = Session::new(SessionConfig::default());
+        session.add_message(SessionMessage::user(vec![ContentBlock::text("hi")]));
+        let snapshot = session.snapshot();
+
+        let json = serde_json::to_value(&session).unwrap();
+        assert!(json.get("snapshots").is_none());
+
+        let stored: SessionSnapshot =
+            serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+        assert_eq!(stored.session_id, session.id);
+        assert_eq!(stored.messages.len(), 1);
+    }
+}
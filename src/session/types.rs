@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use super::state::{MessageId, SessionId};
+use crate::common::clock;
 
 /// Environment context for coding-mode sessions.
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -98,7 +99,7 @@ impl ToolExecution {
         tool_input: serde_json::Value,
     ) -> Self {
         Self {
-            id: Uuid::new_v4(),
+            id: clock::new_uuid(),
             session_id,
             message_id: None,
             tool_name: tool_name.into(),
@@ -110,7 +111,7 @@ impl ToolExecution {
             input_tokens: None,
             output_tokens: None,
             plan_id: None,
-            created_at: Utc::now(),
+            created_at: clock::now_utc(),
         }
     }
 
@@ -199,13 +200,13 @@ pub struct Plan {
 impl Plan {
     pub fn new(session_id: SessionId) -> Self {
         Self {
-            id: Uuid::new_v4(),
+            id: clock::new_uuid(),
             session_id,
             name: None,
             content: String::new(),
             status: PlanStatus::Draft,
             error: None,
-            created_at: Utc::now(),
+            created_at: clock::now_utc(),
             approved_at: None,
             started_at: None,
             completed_at: None,
@@ -224,28 +225,28 @@ impl Plan {
 
     pub fn approve(&mut self) {
         self.status = PlanStatus::Approved;
-        self.approved_at = Some(Utc::now());
+        self.approved_at = Some(clock::now_utc());
     }
 
     pub fn start_execution(&mut self) {
         self.status = PlanStatus::Executing;
-        self.started_at = Some(Utc::now());
+        self.started_at = Some(clock::now_utc());
     }
 
     pub fn complete(&mut self) {
         self.status = PlanStatus::Completed;
-        self.completed_at = Some(Utc::now());
+        self.completed_at = Some(clock::now_utc());
     }
 
     pub fn fail(&mut self, error: impl Into<String>) {
         self.status = PlanStatus::Failed;
-        self.completed_at = Some(Utc::now());
+        self.completed_at = Some(clock::now_utc());
         self.error = Some(error.into());
     }
 
     pub fn cancel(&mut self) {
         self.status = PlanStatus::Cancelled;
-        self.completed_at = Some(Utc::now());
+        self.completed_at = Some(clock::now_utc());
     }
 }
 
@@ -289,13 +290,13 @@ impl TodoItem {
         active_form: impl Into<String>,
     ) -> Self {
         Self {
-            id: Uuid::new_v4(),
+            id: clock::new_uuid(),
             session_id,
             content: content.into(),
             active_form: active_form.into(),
             status: TodoStatus::Pending,
             plan_id: None,
-            created_at: Utc::now(),
+            created_at: clock::now_utc(),
             started_at: None,
             completed_at: None,
         }
@@ -308,12 +309,12 @@ impl TodoItem {
 
     pub fn start(&mut self) {
         self.status = TodoStatus::InProgress;
-        self.started_at = Some(Utc::now());
+        self.started_at = Some(clock::now_utc());
     }
 
     pub fn complete(&mut self) {
         self.status = TodoStatus::Completed;
-        self.completed_at = Some(Utc::now());
+        self.completed_at = Some(clock::now_utc());
     }
 
     pub fn status_icon(&self) -> &'static str {
@@ -363,7 +364,7 @@ pub struct CompactRecord {
 impl CompactRecord {
     pub fn new(session_id: SessionId) -> Self {
         Self {
-            id: Uuid::new_v4(),
+            id: clock::new_uuid(),
             session_id,
             trigger: CompactTrigger::default(),
             pre_tokens: 0,
@@ -373,7 +374,7 @@ impl CompactRecord {
             original_count: 0,
             new_count: 0,
             logical_parent_id: None,
-            created_at: Utc::now(),
+            created_at: clock::now_utc(),
         }
     }
 
@@ -423,11 +424,11 @@ pub struct SummarySnapshot {
 impl SummarySnapshot {
     pub fn new(session_id: SessionId, summary: impl Into<String>) -> Self {
         Self {
-            id: Uuid::new_v4(),
+            id: clock::new_uuid(),
             session_id,
             summary: summary.into(),
             leaf_message_id: None,
-            created_at: Utc::now(),
+            created_at: clock::now_utc(),
         }
     }
 
@@ -468,13 +469,13 @@ pub struct QueueItem {
 impl QueueItem {
     pub fn enqueue(session_id: SessionId, content: impl Into<String>) -> Self {
         Self {
-            id: Uuid::new_v4(),
+            id: clock::new_uuid(),
             session_id,
             operation: QueueOperation::Enqueue,
             content: content.into(),
             priority: 0,
             status: QueueStatus::Pending,
-            created_at: Utc::now(),
+            created_at: clock::now_utc(),
             processed_at: None,
         }
     }
@@ -490,12 +491,12 @@ impl QueueItem {
 
     pub fn complete(&mut self) {
         self.status = QueueStatus::Completed;
-        self.processed_at = Some(Utc::now());
+        self.processed_at = Some(clock::now_utc());
     }
 
     pub fn cancel(&mut self) {
         self.status = QueueStatus::Cancelled;
-        self.processed_at = Some(Utc::now());
+        self.processed_at = Some(clock::now_utc());
     }
 }
 
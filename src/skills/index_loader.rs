@@ -38,6 +38,23 @@ pub struct SkillFrontmatter {
 
 use crate::common::serde_defaults::default_true;
 
+/// Frontmatter schema for slash command files (`commands/*.md`).
+///
+/// Every field is optional; a command without frontmatter is just a prompt body.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommandFrontmatter {
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default, alias = "allowed-tools")]
+    pub allowed_tools: Vec<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default, alias = "argument-hint")]
+    pub argument_hint: Option<String>,
+    #[serde(default, alias = "disable-model-invocation")]
+    pub disable_model_invocation: bool,
+}
+
 /// Loader for creating SkillIndex entries from files.
 ///
 /// This loader extracts only the frontmatter metadata, creating a lightweight
@@ -117,6 +134,67 @@ impl SkillIndexLoader {
         .await
     }
 
+    /// Parse a slash command file into a skill index.
+    ///
+    /// Commands are named by their path (`frontend/component.md` → `frontend:component`)
+    /// rather than by frontmatter. Without a `description`, the first line of the body is used.
+    pub fn parse_command(
+        &self,
+        content: &str,
+        name: impl Into<String>,
+        path: &Path,
+    ) -> crate::Result<SkillIndex> {
+        let (fm, body) = if content.starts_with("---") {
+            let doc = parse_frontmatter::<CommandFrontmatter>(content)?;
+            (doc.frontmatter, doc.body)
+        } else {
+            (CommandFrontmatter::default(), content.to_string())
+        };
+
+        let description = fm.description.unwrap_or_else(|| {
+            body.lines()
+                .map(|l| l.trim_start_matches('#').trim())
+                .find(|l| !l.is_empty())
+                .unwrap_or_default()
+                .to_string()
+        });
+
+        let mut index = SkillIndex::new(name, description).source(ContentSource::file(path));
+        if !fm.allowed_tools.is_empty() {
+            index = index.allowed_tools(fm.allowed_tools);
+        }
+        if let Some(model_val) = fm.model {
+            index = index.model(model_val);
+        }
+        if let Some(hint) = fm.argument_hint {
+            index = index.argument_hint(hint);
+        }
+        index.disable_model_invocation = fm.disable_model_invocation;
+
+        Ok(index)
+    }
+
+    /// Scan a `commands/` directory, namespacing nested commands with `:`.
+    pub async fn scan_commands(&self, dir: &Path) -> crate::Result<Vec<SkillIndex>> {
+        use crate::common::index_loader::{self, DirAction};
+
+        let loader = Self::new();
+        let root = dir.to_path_buf();
+        index_loader::scan_directory(
+            dir,
+            |p| {
+                let name = command_name(&root, p);
+                Box::pin(async move {
+                    index_loader::load_file(p, |c, p| loader.parse_command(c, name, p), "command")
+                        .await
+                })
+            },
+            |p| p.extension().is_some_and(|e| e == "md"),
+            |_| DirAction::Recurse,
+        )
+        .await
+    }
+
     /// Create an inline skill index with content already available.
     pub fn create_inline(
         &self,
@@ -128,6 +206,15 @@ impl SkillIndexLoader {
     }
 }
 
+fn command_name(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path).with_extension("");
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -368,4 +455,42 @@ Content"#;
         assert!(index.context.is_none());
         assert!(index.agent.is_none());
     }
+
+    #[test]
+    fn test_parse_command_without_frontmatter() {
+        let loader = SkillIndexLoader::new();
+        let index = loader
+            .parse_command(
+                "# Review the diff\n\nCheck $ARGUMENTS carefully.",
+                "review",
+                Path::new("/commands/review.md"),
+            )
+            .unwrap();
+
+        assert_eq!(index.name, "review");
+        assert_eq!(index.description, "Review the diff");
+        assert!(index.source.is_file());
+    }
+
+    #[tokio::test]
+    async fn test_scan_commands_namespaces_subdirs() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("frontend")).unwrap();
+        std::fs::write(
+            dir.path().join("frontend").join("component.md"),
+            "---\ndescription: Scaffold a component\nallowed-tools: [Write]\n---\nBody",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let commands = SkillIndexLoader::new()
+            .scan_commands(dir.path())
+            .await
+            .unwrap();
+
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].name, "frontend:component");
+        assert_eq!(commands[0].description, "Scaffold a component");
+        assert_eq!(commands[0].allowed_tools, vec!["Write"]);
+    }
 }
@@ -46,7 +46,7 @@ mod skill_tool;
 
 pub use executor::{ExecutionMode, SkillExecutionCallback, SkillExecutor};
 pub use index::SkillIndex;
-pub use index_loader::{SkillFrontmatter, SkillIndexLoader};
+pub use index_loader::{CommandFrontmatter, SkillFrontmatter, SkillIndexLoader};
 pub use skill_tool::SkillTool;
 
 use std::path::PathBuf;
@@ -165,6 +165,10 @@ mod tests {
         assert!(skill.is_tool_allowed("Bash")); // Base tool name
         assert!(skill.is_tool_allowed("Read"));
         assert!(!skill.is_tool_allowed("Write"));
+
+        let bash = |command: &str| serde_json::json!({"command": command});
+        assert!(skill.is_tool_use_allowed("Bash", &bash("git add . && git commit -m x")));
+        assert!(!skill.is_tool_use_allowed("Bash", &bash("git add . && npm publish")));
     }
 
     #[test]
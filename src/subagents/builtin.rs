@@ -49,7 +49,7 @@ Thoroughness levels:
 Be thorough but efficient. Return a concise summary of your findings."#,
     ))
     .source_type(SourceType::Builtin)
-    .tools(["Read", "Grep", "Glob", "Bash", "TodoWrite", "KillShell"])
+    .tools(["Read", "Grep", "Glob", "LS", "Bash", "TodoWrite", "BashOutput", "KillShell"])
     .model_type(ModelType::Small)
 }
 
@@ -77,7 +77,7 @@ Present your plan clearly with:
 - Recommended approach with rationale"#,
     ))
     .source_type(SourceType::Builtin)
-    .tools(["Read", "Grep", "Glob", "Bash", "TodoWrite", "KillShell"])
+    .tools(["Read", "Grep", "Glob", "LS", "Bash", "TodoWrite", "BashOutput", "KillShell"])
     .model_type(ModelType::Primary)
 }
 
@@ -0,0 +1,153 @@
+//! Deterministic test mode for the agent loop.
+//!
+//! Combines a scripted [`MockAdapter`] with virtual time and seeded IDs so two
+//! runs of the same script produce byte-identical sessions, events and
+//! requests:
+//!
+//! ```rust,no_run
+//! use claude_agent::testing::{Deterministic, MockAdapter};
+//! use claude_agent::{Agent, AgentConfig, Client};
+//!
+//! # async fn example() -> claude_agent::Result<()> {
+//! let _guard = Deterministic::new(42).install();
+//! let adapter = MockAdapter::new()
+//!     .tool_use("Read", serde_json::json!({"file_path": "README.md"}))
+//!     .text("Done.");
+//! let agent = Agent::new(Client::new(adapter.clone())?, AgentConfig::default());
+//! let result = agent.execute("Summarize the README").await?;
+//! assert_eq!(result.text(), "Done.");
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Virtual state is per thread: install the guard inside a current-thread
+//! runtime (the `#[tokio::test]` default) before creating the agent.
+//!
+//! Requires the `testing` feature; enable it for dev-dependencies only.
+
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+pub use crate::client::MockAdapter;
+use crate::common::clock::{self, VirtualState};
+
+/// 2025-01-01T00:00:00Z
+const DEFAULT_START: i64 = 1_735_689_600;
+
+/// Configuration of virtual time and ID generation.
+#[derive(Debug, Clone)]
+pub struct Deterministic {
+    seed: u64,
+    start: DateTime<Utc>,
+    tool_duration: Duration,
+}
+
+impl Deterministic {
+    /// UUIDs (session, message, todo and result IDs) are drawn from `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            start: DateTime::from_timestamp(DEFAULT_START, 0).unwrap_or_default(),
+            tool_duration: Duration::ZERO,
+        }
+    }
+
+    /// Wall-clock time at install. Defaults to 2025-01-01T00:00:00Z.
+    pub fn start_time(mut self, start: DateTime<Utc>) -> Self {
+        self.start = start;
+        self
+    }
+
+    /// Duration recorded for every tool call. Defaults to zero.
+    pub fn tool_duration(mut self, duration: Duration) -> Self {
+        self.tool_duration = duration;
+        self
+    }
+
+    /// Switch the current thread to virtual time and seeded IDs until the
+    /// guard is dropped.
+    #[must_use = "deterministic mode ends when the guard is dropped"]
+    pub fn install(self) -> DeterministicGuard {
+        let previous = clock::swap(Some(VirtualState::new(
+            self.seed,
+            self.start,
+            self.tool_duration,
+        )));
+        DeterministicGuard {
+            previous,
+            _not_send: PhantomData,
+        }
+    }
+}
+
+/// Keeps deterministic mode active; restores the previous mode on drop.
+pub struct DeterministicGuard {
+    previous: Option<VirtualState>,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl DeterministicGuard {
+    /// Move virtual time forward. Time only advances through this method.
+    pub fn advance(&self, by: Duration) {
+        clock::advance(by);
+    }
+
+    /// Current virtual wall-clock time.
+    pub fn now(&self) -> DateTime<Utc> {
+        clock::now_utc()
+    }
+}
+
+impl Drop for DeterministicGuard {
+    fn drop(&mut self) {
+        clock::swap(self.previous.take());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::SessionId;
+
+    #[test]
+    fn test_seeded_ids_and_virtual_time() {
+        let first = {
+            let guard = Deterministic::new(7).install();
+            assert_eq!(guard.now().timestamp(), DEFAULT_START);
+            guard.advance(Duration::from_secs(90));
+            assert_eq!(guard.now().timestamp(), DEFAULT_START + 90);
+            (SessionId::new(), SessionId::new())
+        };
+        let second = {
+            let _guard = Deterministic::new(7).install();
+            (SessionId::new(), SessionId::new())
+        };
+        assert_eq!(first, second);
+        assert_ne!(first.0, first.1);
+        assert_eq!(first.0.0.get_version_num(), 4);
+
+        let other = {
+            let _guard = Deterministic::new(8).install();
+            SessionId::new()
+        };
+        assert_ne!(first.0, other);
+        assert!(clock::now_utc().timestamp() > DEFAULT_START + 90);
+    }
+
+    #[test]
+    fn test_nested_guards_restore() {
+        let outer = Deterministic::new(1).install();
+        {
+            let _inner = Deterministic::new(2)
+                .start_time(DateTime::from_timestamp(0, 0).unwrap())
+                .install();
+            assert_eq!(clock::now_utc().timestamp(), 0);
+        }
+        assert_eq!(outer.now().timestamp(), DEFAULT_START);
+        let start = clock::now();
+        outer.advance(Duration::from_millis(250));
+        assert_eq!(clock::elapsed(start), Duration::from_millis(250));
+    }
+}
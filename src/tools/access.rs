@@ -2,6 +2,8 @@
 
 use std::collections::HashSet;
 
+use crate::permissions::WRITE_TOOLS;
+
 /// Controls which tools are available to the agent.
 #[derive(Debug, Clone, Default)]
 pub enum ToolAccess {
@@ -33,6 +35,24 @@ impl ToolAccess {
         Self::Except(tools.into_iter().map(Into::into).collect())
     }
 
+    /// The same access without the tools that write files ([`WRITE_TOOLS`]).
+    pub fn read_only(self) -> Self {
+        match self {
+            Self::None => Self::None,
+            Self::All => Self::except(WRITE_TOOLS.iter().copied()),
+            Self::Only(tools) => Self::Only(
+                tools
+                    .into_iter()
+                    .filter(|tool| !WRITE_TOOLS.contains(&tool.as_str()))
+                    .collect(),
+            ),
+            Self::Except(mut tools) => {
+                tools.extend(WRITE_TOOLS.iter().map(|tool| tool.to_string()));
+                Self::Except(tools)
+            }
+        }
+    }
+
     #[inline]
     pub fn is_allowed(&self, tool_name: &str) -> bool {
         match self {
@@ -80,4 +100,17 @@ mod tests {
         assert!(!access.is_allowed("Bash"));
         assert!(!access.is_allowed("KillShell"));
     }
+
+    #[test]
+    fn test_read_only_removes_write_tools() {
+        let access = ToolAccess::all().read_only();
+        assert!(access.is_allowed("Read") && access.is_allowed("Bash"));
+        assert!(!access.is_allowed("Write") && !access.is_allowed("NotebookEdit"));
+
+        let access = ToolAccess::only(["Read", "Edit"]).read_only();
+        assert!(access.is_allowed("Read") && !access.is_allowed("Edit"));
+
+        let access = ToolAccess::except(["Bash"]).read_only();
+        assert!(!access.is_allowed("Bash") && !access.is_allowed("MultiEdit"));
+    }
 }
@@ -0,0 +1,570 @@
+//! AstEdit tool - structure-aware edits using tree-sitter.
+
+use std::path::Path;
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use tree_sitter::{Language, Node, Parser, Query, QueryCursor, StreamingIterator, Tree};
+
+use super::SchemaTool;
+use super::context::ExecutionContext;
+use crate::security::fs::SecureFileHandle;
+use crate::types::ToolResult;
+
+/// Node kinds that name a symbol, renamed by `rename`.
+const IDENTIFIER_KINDS: &[&str] = &[
+    "identifier",
+    "type_identifier",
+    "field_identifier",
+    "property_identifier",
+    "private_property_identifier",
+    "shorthand_property_identifier",
+    "shorthand_property_identifier_pattern",
+    "package_identifier",
+];
+
+/// Suffixes of node kinds that define a named function, type or value.
+const DEFINITION_SUFFIXES: &[&str] = &[
+    "_item",
+    "_definition",
+    "_declaration",
+    "_declarator",
+    "_spec",
+];
+
+/// Wrappers that belong to the definition they contain (decorators, `export`).
+const DEFINITION_WRAPPERS: &[&str] = &[
+    "decorated_definition",
+    "export_statement",
+    "type_declaration",
+    "lexical_declaration",
+    "variable_declaration",
+];
+
+/// Siblings kept with the definition that follows them.
+const LEADING_KINDS: &[&str] = &[
+    "attribute_item",
+    "line_comment",
+    "block_comment",
+    "comment",
+    "decorator",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AstOperation {
+    Rename,
+    InsertAfter,
+    InsertBefore,
+    Replace,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct AstEditInput {
+    /// The absolute path to the file to modify
+    pub file_path: String,
+    /// "rename" a symbol, "insert_after"/"insert_before" a definition, or "replace" the nodes a query selects
+    pub operation: AstOperation,
+    /// rename: identifier to rename. insert_after/insert_before: name of the function, type or class to insert next to
+    #[serde(default)]
+    pub symbol: Option<String>,
+    /// rename: the new identifier
+    #[serde(default)]
+    pub new_name: Option<String>,
+    /// replace: tree-sitter query; the node captured as @target is replaced (e.g. `(function_item name: (identifier) @n (#eq? @n "main")) @target`)
+    #[serde(default)]
+    pub query: Option<String>,
+    /// insert_after/insert_before: code to insert. replace: replacement code. Indented to match the target.
+    #[serde(default)]
+    pub content: Option<String>,
+    /// replace: replace every node the query selects (default false: exactly one must match)
+    #[serde(default)]
+    pub replace_all: bool,
+}
+
+/// Structure-aware edits for Rust, Python, JavaScript, TypeScript and Go.
+///
+/// Edits that would turn a file that parses cleanly into one with syntax errors
+/// are rejected.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AstEditTool;
+
+#[async_trait]
+impl SchemaTool for AstEditTool {
+    type Input = AstEditInput;
+
+    const NAME: &'static str = "AstEdit";
+    const DESCRIPTION: &'static str = r#"Performs structure-aware edits using the file's syntax tree (Rust, Python, JavaScript, TypeScript, Go).
+
+Usage:
+- Prefer this over Edit when whitespace or repeated text makes string matching fragile.
+- operation "rename": renames every identifier equal to `symbol` to `new_name`. Strings and comments are left alone.
+- operation "insert_after" / "insert_before": inserts `content` next to the function, type or class named `symbol`, with a blank line between and the definition's indentation. Fails if the name is defined more than once.
+- operation "replace": replaces the node captured as @target by the tree-sitter `query` with `content`. Fails if several nodes match unless `replace_all` is true.
+- The edit is rejected if it would introduce a syntax error."#;
+
+    async fn handle(&self, input: AstEditInput, context: &ExecutionContext) -> ToolResult {
+        let path = match context.try_resolve_for(Self::NAME, &input.file_path) {
+            Ok(p) => p,
+            Err(e) => return e,
+        };
+        let Some(language) = language_for(path.as_path()) else {
+            return ToolResult::error(
+                "Unsupported file type; AstEdit supports .rs, .py, .js, .jsx, .mjs, .cjs, .ts, .tsx and .go",
+            );
+        };
+
+        let display_path = path.as_path().display().to_string();
+        let result = tokio::task::spawn_blocking(move || {
+            let handle = SecureFileHandle::open_read(path.clone()).map_err(|e| e.to_string())?;
+            let original_content = handle.read_to_string().map_err(|e| e.to_string())?;
+
+            let (new_content, summary) = apply(&language, &original_content, &input)?;
+
+            let recheck_handle =
+                SecureFileHandle::open_read(path.clone()).map_err(|e| e.to_string())?;
+            let current_content = recheck_handle.read_to_string().map_err(|e| e.to_string())?;
+            if current_content != original_content {
+                return Err("File was modified externally; operation aborted".to_string());
+            }
+
+            let write_handle = SecureFileHandle::open_write(path).map_err(|e| e.to_string())?;
+            write_handle
+                .atomic_write(new_content.as_bytes())
+                .map_err(|e| e.to_string())?;
+
+            Ok(summary)
+        })
+        .await;
+
+        match result {
+            Ok(Ok(summary)) => ToolResult::success(format!("{} in {}", summary, display_path)),
+            Ok(Err(e)) => ToolResult::error(e),
+            Err(e) => ToolResult::error(format!("Task failed: {}", e)),
+        }
+    }
+}
+
+fn language_for(path: &Path) -> Option<Language> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    let language = match extension.as_str() {
+        "rs" => tree_sitter_rust::LANGUAGE,
+        "py" | "pyi" => tree_sitter_python::LANGUAGE,
+        "js" | "jsx" | "mjs" | "cjs" => tree_sitter_javascript::LANGUAGE,
+        "ts" | "mts" | "cts" => tree_sitter_typescript::LANGUAGE_TYPESCRIPT,
+        "tsx" => tree_sitter_typescript::LANGUAGE_TSX,
+        "go" => tree_sitter_go::LANGUAGE,
+        _ => return None,
+    };
+    Some(language.into())
+}
+
+fn parse(language: &Language, source: &str) -> Result<Tree, String> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(language)
+        .map_err(|e| format!("Failed to load grammar: {}", e))?;
+    parser
+        .parse(source, None)
+        .ok_or_else(|| "Failed to parse file".to_string())
+}
+
+/// Apply `input` to `source`, returning the new source and a summary.
+fn apply(
+    language: &Language,
+    source: &str,
+    input: &AstEditInput,
+) -> Result<(String, String), String> {
+    let tree = parse(language, source)?;
+    let root = tree.root_node();
+
+    let (edits, summary) = match input.operation {
+        AstOperation::Rename => {
+            let symbol = required(&input.symbol, "symbol")?;
+            let new_name = required(&input.new_name, "new_name")?;
+            if !is_identifier(new_name) {
+                return Err(format!("new_name `{}` is not a valid identifier", new_name));
+            }
+            let edits: Vec<_> = identifiers(root, source, symbol)
+                .into_iter()
+                .map(|node| (node.byte_range(), new_name.to_string()))
+                .collect();
+            if edits.is_empty() {
+                return Err(format!("No identifier named `{}` found", symbol));
+            }
+            let summary = format!(
+                "Renamed {} occurrences of `{}` to `{}`",
+                edits.len(),
+                symbol,
+                new_name
+            );
+            (edits, summary)
+        }
+        AstOperation::InsertAfter | AstOperation::InsertBefore => {
+            let symbol = required(&input.symbol, "symbol")?;
+            let content = required(&input.content, "content")?;
+            let node = single_definition(root, source, symbol)?;
+            let indent = line_indent(source, node.start_byte());
+            let code = indent_lines(content.trim_end_matches('\n'), indent);
+            let (at, text, position) = if input.operation == AstOperation::InsertAfter {
+                (node.end_byte(), format!("\n\n{}", code), "after")
+            } else {
+                let start = leading_start(node);
+                let code = code.strip_prefix(indent).unwrap_or(&code);
+                (start, format!("{}\n\n{}", code, indent), "before")
+            };
+            (
+                vec![(at..at, text)],
+                format!("Inserted code {} `{}`", position, symbol),
+            )
+        }
+        AstOperation::Replace => {
+            let query = required(&input.query, "query")?;
+            let content = input.content.as_deref().unwrap_or_default();
+            let nodes = query_targets(language, root, source, query)?;
+            if nodes.is_empty() {
+                return Err("Query matched no nodes".to_string());
+            }
+            if nodes.len() > 1 && !input.replace_all {
+                return Err(format!(
+                    "Query matched {} nodes (lines {}). Use replace_all=true to replace all, \
+                     or narrow the query.",
+                    nodes.len(),
+                    lines_of(&nodes)
+                ));
+            }
+            let edits: Vec<_> = nodes
+                .iter()
+                .map(|node| {
+                    let indent = line_indent(source, node.start_byte());
+                    let code = indent_lines(content, indent);
+                    let code = code.strip_prefix(indent).unwrap_or(&code).to_string();
+                    (node.byte_range(), code)
+                })
+                .collect();
+            let summary = format!("Replaced {} node(s)", edits.len());
+            (edits, summary)
+        }
+    };
+
+    let new_source = splice(source, edits);
+    if !root.has_error() && parse(language, &new_source)?.root_node().has_error() {
+        return Err("Edit would introduce a syntax error; file left unchanged".to_string());
+    }
+    Ok((new_source, summary))
+}
+
+fn required<'a>(value: &'a Option<String>, name: &str) -> Result<&'a str, String> {
+    value
+        .as_deref()
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| format!("`{}` is required for this operation", name))
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Depth-first list of the nodes under `root`, in source order.
+fn descendants(root: Node<'_>) -> Vec<Node<'_>> {
+    let mut nodes = Vec::new();
+    let mut cursor = root.walk();
+    loop {
+        nodes.push(cursor.node());
+        if cursor.goto_first_child() {
+            continue;
+        }
+        loop {
+            if cursor.goto_next_sibling() {
+                break;
+            }
+            if !cursor.goto_parent() {
+                return nodes;
+            }
+        }
+    }
+}
+
+fn text<'a>(node: Node<'_>, source: &'a str) -> &'a str {
+    &source[node.byte_range()]
+}
+
+fn identifiers<'t>(root: Node<'t>, source: &str, symbol: &str) -> Vec<Node<'t>> {
+    descendants(root)
+        .into_iter()
+        .filter(|n| IDENTIFIER_KINDS.contains(&n.kind()) && text(*n, source) == symbol)
+        .collect()
+}
+
+/// The one definition named `symbol`, including its decorators or `export`.
+fn single_definition<'t>(root: Node<'t>, source: &str, symbol: &str) -> Result<Node<'t>, String> {
+    let mut found: Vec<Node<'t>> = descendants(root)
+        .into_iter()
+        .filter(|n| is_definition(*n))
+        .filter(|n| {
+            let name = match n.kind() {
+                "impl_item" => n.child_by_field_name("type"),
+                _ => n.child_by_field_name("name"),
+            };
+            name.is_some_and(|name| text(name, source) == symbol)
+        })
+        .map(|mut node| {
+            while let Some(parent) = node.parent()
+                && DEFINITION_WRAPPERS.contains(&parent.kind())
+            {
+                node = parent;
+            }
+            node
+        })
+        .collect();
+    found.dedup_by_key(|n| n.id());
+    match found.len() {
+        0 => Err(format!("No definition named `{}` found", symbol)),
+        1 => Ok(found[0]),
+        n => Err(format!(
+            "`{}` is defined {} times (lines {}); use operation \"replace\" with a query to pick one",
+            symbol,
+            n,
+            lines_of(&found)
+        )),
+    }
+}
+
+fn is_definition(node: Node<'_>) -> bool {
+    let kind = node.kind();
+    DEFINITION_SUFFIXES.iter().any(|s| kind.ends_with(s))
+        && !kind.contains("field")
+        && !kind.contains("parameter")
+}
+
+/// Start of `node` extended over attributes, decorators and comments directly above it.
+fn leading_start(node: Node<'_>) -> usize {
+    let mut start = node;
+    while let Some(prev) = start.prev_sibling()
+        && LEADING_KINDS.contains(&prev.kind())
+        && prev.end_position().row + 1 >= start.start_position().row
+    {
+        start = prev;
+    }
+    start.start_byte()
+}
+
+fn query_targets<'t>(
+    language: &Language,
+    root: Node<'t>,
+    source: &str,
+    query: &str,
+) -> Result<Vec<Node<'t>>, String> {
+    let query = Query::new(language, query).map_err(|e| format!("Invalid query: {}", e))?;
+    let names = query.capture_names();
+    let target = match names.iter().position(|n| *n == "target") {
+        Some(index) => index,
+        None if names.len() == 1 => 0,
+        None => return Err("Query must capture the node to replace as @target".to_string()),
+    } as u32;
+
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(&query, root, source.as_bytes());
+    let mut nodes: Vec<Node<'t>> = Vec::new();
+    while let Some(m) = matches.next() {
+        for capture in m.captures.iter().filter(|c| c.index == target) {
+            if !nodes.iter().any(|n| n.id() == capture.node.id()) {
+                nodes.push(capture.node);
+            }
+        }
+    }
+    nodes.sort_by_key(|n| n.start_byte());
+    if nodes
+        .windows(2)
+        .any(|pair| pair[0].end_byte() > pair[1].start_byte())
+    {
+        return Err(
+            "Query selects overlapping nodes; narrow it so targets do not nest".to_string(),
+        );
+    }
+    Ok(nodes)
+}
+
+fn lines_of(nodes: &[Node<'_>]) -> String {
+    nodes
+        .iter()
+        .map(|n| (n.start_position().row + 1).to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn line_indent(source: &str, byte: usize) -> &str {
+    let line_start = source[..byte].rfind('\n').map_or(0, |i| i + 1);
+    let line = &source[line_start..];
+    let width = line.len() - line.trim_start_matches([' ', '\t']).len();
+    &line[..width]
+}
+
+fn indent_lines(code: &str, indent: &str) -> String {
+    code.lines()
+        .map(|line| {
+            if line.trim().is_empty() {
+                String::new()
+            } else {
+                format!("{}{}", indent, line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Apply non-overlapping `(range, replacement)` edits to `source`.
+fn splice(source: &str, mut edits: Vec<(std::ops::Range<usize>, String)>) -> String {
+    edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+    let mut result = source.to_string();
+    for (range, replacement) in edits {
+        result.replace_range(range, &replacement);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::Tool;
+    use tempfile::tempdir;
+
+    const RUST: &str = r#"/// Adds.
+fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+fn main() {
+    let total = add(1, 2);
+    println!("add {}", total);
+}
+"#;
+
+    fn run(source: &str, input: serde_json::Value) -> Result<String, String> {
+        let mut input = input;
+        input["file_path"] = serde_json::json!("x.rs");
+        let input: AstEditInput = serde_json::from_value(input).unwrap();
+        apply(&tree_sitter_rust::LANGUAGE.into(), source, &input).map(|(s, _)| s)
+    }
+
+    #[test]
+    fn test_rename_skips_strings() {
+        let out = run(
+            RUST,
+            serde_json::json!({"operation": "rename", "symbol": "add", "new_name": "sum"}),
+        )
+        .unwrap();
+        assert!(out.contains("fn sum(a: i32"));
+        assert!(out.contains("let total = sum(1, 2);"));
+        assert!(out.contains("\"add {}\""));
+
+        let err = run(
+            RUST,
+            serde_json::json!({"operation": "rename", "symbol": "add", "new_name": "1x"}),
+        )
+        .unwrap_err();
+        assert!(err.contains("not a valid identifier"));
+    }
+
+    #[test]
+    fn test_insert_around_definition() {
+        let out = run(
+            RUST,
+            serde_json::json!({
+                "operation": "insert_after",
+                "symbol": "add",
+                "content": "fn sub(a: i32, b: i32) -> i32 {\n    a - b\n}\n"
+            }),
+        )
+        .unwrap();
+        assert!(
+            out.contains(
+                "    a + b\n}\n\nfn sub(a: i32, b: i32) -> i32 {\n    a - b\n}\n\nfn main"
+            )
+        );
+
+        let out = run(
+            RUST,
+            serde_json::json!({"operation": "insert_before", "symbol": "add", "content": "use std::fmt;"}),
+        )
+        .unwrap();
+        assert!(out.starts_with("use std::fmt;\n\n/// Adds.\nfn add"));
+    }
+
+    #[test]
+    fn test_replace_by_query() {
+        let out = run(
+            RUST,
+            serde_json::json!({
+                "operation": "replace",
+                "query": "(function_item name: (identifier) @name (#eq? @name \"add\") body: (block) @target)",
+                "content": "{\n    a.wrapping_add(b)\n}"
+            }),
+        )
+        .unwrap();
+        assert!(out.contains("-> i32 {\n    a.wrapping_add(b)\n}"));
+
+        let err = run(
+            RUST,
+            serde_json::json!({"operation": "replace", "query": "(function_item) @target", "content": ""}),
+        )
+        .unwrap_err();
+        assert!(err.contains("matched 2 nodes"));
+    }
+
+    #[test]
+    fn test_rejects_syntax_errors() {
+        let err = run(
+            RUST,
+            serde_json::json!({"operation": "insert_after", "symbol": "main", "content": "fn broken( {"}),
+        )
+        .unwrap_err();
+        assert!(err.contains("syntax error"));
+    }
+
+    #[tokio::test]
+    async fn test_ast_edit_tool_writes_file() {
+        let dir = tempdir().unwrap();
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        let file = root.join("app.py");
+        std::fs::write(&file, "def greet(name):\n    return name\n\n\ngreet('x')\n").unwrap();
+        let context = ExecutionContext::from_path(&root).unwrap();
+
+        let result = AstEditTool
+            .execute(
+                serde_json::json!({
+                    "file_path": file.to_str().unwrap(),
+                    "operation": "rename",
+                    "symbol": "greet",
+                    "new_name": "welcome"
+                }),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error(), "{}", result.text());
+        assert_eq!(
+            std::fs::read_to_string(&file).unwrap(),
+            "def welcome(name):\n    return name\n\n\nwelcome('x')\n"
+        );
+
+        std::fs::write(root.join("notes.txt"), "x").unwrap();
+        let result = AstEditTool
+            .execute(
+                serde_json::json!({
+                    "file_path": root.join("notes.txt").to_str().unwrap(),
+                    "operation": "rename",
+                    "symbol": "x",
+                    "new_name": "y"
+                }),
+                &context,
+            )
+            .await;
+        assert!(result.is_error());
+    }
+}
@@ -0,0 +1,203 @@
+//! Per-session backups of files changed by write tools
+//! ([`WRITE_TOOLS`](crate::permissions::WRITE_TOOLS)), for rollback.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::context::ExecutionContext;
+use crate::security::SecurityError;
+use crate::security::fs::SecureFileHandle;
+
+struct Backup {
+    path: PathBuf,
+    /// Content before the session's first write; `None` if the file did not exist.
+    original: Option<Vec<u8>>,
+}
+
+/// Original content of every file a session's write tools touched.
+///
+/// Only the first write to a path is backed up, so a rollback restores the
+/// workspace to its state before the session started changing it. Backups are
+/// held in memory until the session is rolled back or discarded.
+#[derive(Default)]
+pub struct WriteBackups {
+    sessions: Mutex<HashMap<String, Vec<Backup>>>,
+}
+
+impl WriteBackups {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember `path`'s current content unless `session_id` already has.
+    ///
+    /// The file is read through the sandbox of `context`, within its file
+    /// limits and without following symlinks.
+    pub async fn snapshot(
+        &self,
+        session_id: &str,
+        path: &Path,
+        context: &ExecutionContext,
+    ) -> Result<(), SecurityError> {
+        if self.contains(session_id, path) {
+            return Ok(());
+        }
+        let safe_path = context.resolve(&path.to_string_lossy())?;
+        let limits = context.file_limits().clone();
+        let original = tokio::task::spawn_blocking(move || {
+            match SecureFileHandle::open_read(safe_path).and_then(|h| h.limits(limits).read_bytes())
+            {
+                Ok(bytes) => Ok(Some(bytes)),
+                Err(SecurityError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e),
+            }
+        })
+        .await
+        .map_err(|e| SecurityError::Io(std::io::Error::other(e.to_string())))??;
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let backups = sessions.entry(session_id.to_string()).or_default();
+        if !backups.iter().any(|b| b.path == path) {
+            backups.push(Backup {
+                path: path.to_path_buf(),
+                original,
+            });
+        }
+        Ok(())
+    }
+
+    fn contains(&self, session_id: &str, path: &Path) -> bool {
+        let sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions
+            .get(session_id)
+            .is_some_and(|backups| backups.iter().any(|b| b.path == path))
+    }
+
+    /// Files `session_id` has changed, in the order they were first written.
+    pub fn paths(&self, session_id: &str) -> Vec<PathBuf> {
+        let sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions
+            .get(session_id)
+            .map(|backups| backups.iter().map(|b| b.path.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Forget `session_id`'s backups, keeping its changes.
+    pub fn discard(&self, session_id: &str) {
+        self.sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(session_id);
+    }
+
+    /// Restore every file `session_id` changed and return their paths.
+    ///
+    /// Files the session created are deleted. If a restore fails, the
+    /// remaining backups are kept so the rollback can be retried.
+    pub async fn restore(
+        &self,
+        session_id: &str,
+        context: &ExecutionContext,
+    ) -> crate::Result<Vec<PathBuf>> {
+        let backups = self
+            .sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(session_id)
+            .unwrap_or_default();
+
+        let mut restored = Vec::with_capacity(backups.len());
+        let mut pending = backups.into_iter();
+        while let Some(backup) = pending.next() {
+            if let Err(e) = restore_file(&backup, context).await {
+                let mut remaining = vec![backup];
+                remaining.extend(pending);
+                self.sessions
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(session_id.to_string(), remaining);
+                return Err(e);
+            }
+            restored.push(backup.path);
+        }
+        Ok(restored)
+    }
+}
+
+async fn restore_file(backup: &Backup, context: &ExecutionContext) -> crate::Result<()> {
+    let path = context
+        .resolve(&backup.path.to_string_lossy())
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    let original = backup.original.clone();
+    tokio::task::spawn_blocking(move || match original {
+        Some(original) => SecureFileHandle::for_atomic_write(path)?.atomic_write(&original),
+        // The session created the file.
+        None => match path.remove() {
+            Err(SecurityError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        },
+    })
+    .await
+    .map_err(|e| std::io::Error::other(e.to_string()))?
+    .map_err(|e| std::io::Error::other(e.to_string()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_snapshot_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        let context = ExecutionContext::from_path(&root).unwrap();
+        let existing = root.join("a.txt");
+        let created = root.join("new/b.txt");
+        std::fs::write(&existing, "original").unwrap();
+
+        let backups = WriteBackups::new();
+        backups.snapshot("s1", &existing, &context).await.unwrap();
+        std::fs::write(&existing, "first edit").unwrap();
+        backups.snapshot("s1", &existing, &context).await.unwrap();
+        std::fs::write(&existing, "second edit").unwrap();
+        backups.snapshot("s1", &created, &context).await.unwrap();
+        std::fs::create_dir_all(created.parent().unwrap()).unwrap();
+        std::fs::write(&created, "created").unwrap();
+        assert_eq!(backups.paths("s1"), [existing.clone(), created.clone()]);
+        assert!(backups.paths("s2").is_empty());
+
+        let restored = backups.restore("s1", &context).await.unwrap();
+        assert_eq!(restored.len(), 2);
+        assert_eq!(std::fs::read_to_string(&existing).unwrap(), "original");
+        assert!(!created.exists());
+        assert!(backups.paths("s1").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_and_restore_stay_in_sandbox() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside = std::fs::canonicalize(dir.path()).unwrap();
+        let root = outside.join("project");
+        std::fs::create_dir(&root).unwrap();
+        let context = ExecutionContext::from_path(&root).unwrap();
+        let secret = outside.join("secret.txt");
+        std::fs::write(&secret, "token").unwrap();
+
+        let backups = WriteBackups::new();
+        assert!(backups.snapshot("s1", &secret, &context).await.is_err());
+        let link = root.join("link.txt");
+        std::os::unix::fs::symlink(&secret, &link).unwrap();
+        assert!(backups.snapshot("s1", &link, &context).await.is_err());
+        assert!(backups.paths("s1").is_empty());
+
+        // A file the session created that became a link out of the sandbox
+        // is not touched, and its backup is kept.
+        let created = root.join("created.txt");
+        backups.snapshot("s1", &created, &context).await.unwrap();
+        std::os::unix::fs::symlink(&secret, &created).unwrap();
+        assert!(backups.restore("s1", &context).await.is_err());
+        assert_eq!(backups.paths("s1"), [created]);
+        assert_eq!(std::fs::read_to_string(&secret).unwrap(), "token");
+    }
+}
@@ -14,6 +14,9 @@ use tokio::time::timeout;
 use super::SchemaTool;
 use super::context::ExecutionContext;
 use super::process::ProcessManager;
+use crate::security::ResourceLimits;
+use crate::security::limits::CgroupScope;
+use crate::security::sandbox::{SandboxViolation, classify_failure};
 use crate::types::ToolResult;
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -27,7 +30,7 @@ pub struct BashInput {
     /// Optional timeout in milliseconds (max 600000)
     #[serde(default)]
     pub timeout: Option<u64>,
-    /// Set to true to run this command in the background. Use TaskOutput to read the output later.
+    /// Set to true to run this command in the background. Use BashOutput to read the output later.
     #[serde(default)]
     pub run_in_background: Option<bool>,
     /// Set this to true to dangerously override sandbox mode and run commands without sandboxing.
@@ -71,7 +74,8 @@ impl BashTool {
         bypass_sandbox: bool,
     ) -> ToolResult {
         let timeout_duration = Duration::from_millis(timeout_ms);
-        let env = context.sanitized_env_with_sandbox();
+        let env = context.sanitized_env_for_command(command);
+        let started = chrono::Utc::now();
         let limits = context.resource_limits().clone();
 
         let wrapped_command = if bypass_sandbox {
@@ -91,6 +95,10 @@ impl BashTool {
         cmd.stdout(Stdio::piped());
         cmd.stderr(Stdio::piped());
 
+        let cgroup = match enter_cgroup(&mut cmd, &limits) {
+            Ok(cgroup) => cgroup,
+            Err(e) => return ToolResult::error(e),
+        };
         #[cfg(unix)]
         unsafe {
             cmd.pre_exec(move || {
@@ -100,6 +108,9 @@ impl BashTool {
                 Ok(())
             });
         }
+        if !bypass_sandbox {
+            install_seccomp(&mut cmd, context);
+        }
 
         // Ensure process is killed when dropped (safety net)
         cmd.kill_on_drop(true);
@@ -116,6 +127,11 @@ impl BashTool {
 
         match timeout(timeout_duration, child.wait()).await {
             Ok(Ok(status)) => {
+                // Leftover background children would keep the pipes open.
+                if let Some(cgroup) = &cgroup {
+                    cgroup.kill();
+                }
+
                 // Read output from taken handles
                 let mut stdout_buf = Vec::new();
                 let mut stderr_buf = Vec::new();
@@ -157,8 +173,16 @@ impl BashTool {
                     let code = status.code().unwrap_or(-1);
                     combined = format!("Exit code: {}\n{}", code, combined);
                 }
+                if cgroup.as_ref().is_some_and(CgroupScope::oom_killed) {
+                    combined = format!("Killed: cgroup memory limit exceeded\n{}", combined);
+                }
 
-                ToolResult::success(combined)
+                let violations = if bypass_sandbox || !context.is_sandboxed() {
+                    Vec::new()
+                } else {
+                    sandbox_violations(command, status.code(), &stderr, context, started)
+                };
+                ToolResult::success(combined).violations(violations)
             }
             Ok(Err(e)) => ToolResult::error(format!("Failed to execute command: {}", e)),
             Err(_) => {
@@ -179,7 +203,7 @@ impl BashTool {
         context: &ExecutionContext,
         bypass_sandbox: bool,
     ) -> ToolResult {
-        let env = context.sanitized_env_with_sandbox();
+        let env = context.sanitized_env_for_command(command);
 
         let wrapped_command = if bypass_sandbox {
             command.to_string()
@@ -190,13 +214,22 @@ impl BashTool {
             }
         };
 
+        let mut cmd = ProcessManager::command(&wrapped_command, context.root(), env);
+        let cgroup = match enter_cgroup(&mut cmd, context.resource_limits()) {
+            Ok(cgroup) => cgroup,
+            Err(e) => return ToolResult::error(e),
+        };
+        if !bypass_sandbox {
+            install_seccomp(&mut cmd, context);
+        }
+
         match self
             .process_manager
-            .spawn_with_env(&wrapped_command, context.root(), env)
+            .spawn_command(cmd, &wrapped_command, cgroup)
             .await
         {
             Ok(id) => ToolResult::success(format!(
-                "Background process started with ID: {}\nUse TaskOutput tool to monitor output.",
+                "Background process started with ID: {}\nUse BashOutput to monitor output and KillShell to stop it.",
                 id
             )),
             Err(e) => ToolResult::error(e),
@@ -204,6 +237,88 @@ impl BashTool {
     }
 }
 
+/// Denials the sandbox reported while `command` ran: failures its error
+/// output attributes to the sandbox, and connections the egress proxy blocked
+/// since `started`. Commands running at the same time share the proxy, so
+/// their blocked connections may be attributed to each other.
+fn sandbox_violations(
+    command: &str,
+    exit_code: Option<i32>,
+    stderr: &str,
+    context: &ExecutionContext,
+    started: chrono::DateTime<chrono::Utc>,
+) -> Vec<SandboxViolation> {
+    let mut violations = if exit_code == Some(0) {
+        Vec::new()
+    } else {
+        classify_failure(command, exit_code, stderr)
+    };
+    let egress: Vec<_> = context
+        .egress_log()
+        .into_iter()
+        .filter(|attempt| attempt.timestamp >= started)
+        .collect();
+    violations.extend(SandboxViolation::from_egress(command, &egress));
+    for violation in &violations {
+        tracing::warn!(
+            kind = %violation.kind,
+            command,
+            detail = %violation.detail,
+            "Sandbox violation"
+        );
+    }
+    violations
+}
+
+/// Make `cmd` join a new cgroup when the resource limits configure one.
+/// Dropping the returned scope kills what is left of the process tree. A
+/// cgroup that cannot be created is logged and skipped, like the rlimits.
+#[cfg(unix)]
+fn enter_cgroup(cmd: &mut Command, limits: &ResourceLimits) -> Result<Option<CgroupScope>, String> {
+    let Some(cgroup) = &limits.cgroup else {
+        return Ok(None);
+    };
+    let scope = match CgroupScope::create(cgroup) {
+        Ok(scope) => scope,
+        Err(e) => {
+            tracing::warn!(error = %e, "Command will run without cgroup limits");
+            return Ok(None);
+        }
+    };
+    let entry = scope
+        .entry()
+        .map_err(|e| format!("Failed to open cgroup: {}", e))?;
+    // SAFETY: `CgroupEntry::enter` only makes a `write` syscall.
+    unsafe {
+        cmd.pre_exec(move || entry.enter());
+    }
+    Ok(Some(scope))
+}
+
+#[cfg(not(unix))]
+fn enter_cgroup(
+    _cmd: &mut Command,
+    _limits: &ResourceLimits,
+) -> Result<Option<CgroupScope>, String> {
+    Ok(None)
+}
+
+/// Make `cmd` install the sandbox's seccomp filter before `exec`. A filter
+/// that cannot be installed fails the spawn rather than running unfiltered.
+#[cfg(target_os = "linux")]
+fn install_seccomp(cmd: &mut Command, context: &ExecutionContext) {
+    if let Some(filter) = context.seccomp_filter() {
+        // SAFETY: `SeccompFilter::apply` only makes the `prctl` and `seccomp`
+        // syscalls, which are async-signal-safe.
+        unsafe {
+            cmd.pre_exec(move || filter.apply());
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn install_seccomp(_cmd: &mut Command, _context: &ExecutionContext) {}
+
 impl Default for BashTool {
     fn default() -> Self {
         Self::new()
@@ -240,7 +355,7 @@ Usage notes:
   - You can specify an optional timeout in milliseconds (up to 600000ms / 10 minutes). If not specified, commands will timeout after 120000ms (2 minutes).
   - It is very helpful if you write a clear, concise description of what this command does in 5-10 words.
   - If the output exceeds 30000 characters, output will be truncated before being returned to you.
-  - You can use the `run_in_background` parameter to run the command in the background, which allows you to continue working while the command runs. You can monitor the output using the Bash tool as it becomes available. You do not need to use '&' at the end of the command when using this parameter.
+  - You can use the `run_in_background` parameter to run the command in the background, which allows you to continue working while the command runs. You can monitor the output using the BashOutput tool as it becomes available. You do not need to use '&' at the end of the command when using this parameter.
 
   - Avoid using Bash with the `find`, `grep`, `cat`, `head`, `tail`, `sed`, `awk`, or `echo` commands, unless explicitly instructed or when these commands are truly necessary for the task. Instead, always prefer using the dedicated tools for these commands:
     - File search: Use Glob (NOT find or ls)
@@ -301,6 +416,36 @@ mod tests {
         );
     }
 
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_seccomp_denies_syscall() {
+        use crate::security::{SandboxConfig, SecurityContext, sandbox::SeccompProfile};
+
+        let dir = tempfile::tempdir().unwrap();
+        let security = SecurityContext::builder()
+            .root(dir.path())
+            .sandbox(
+                SandboxConfig::new(dir.path().to_path_buf())
+                    .seccomp(SeccompProfile::empty().deny("unshare")),
+            )
+            .build()
+            .unwrap();
+        let context = ExecutionContext::new(security);
+        if context.seccomp_filter().is_none() {
+            return; // Landlock or seccomp unavailable
+        }
+
+        let script = "import ctypes; print('rc', ctypes.CDLL(None).unshare(0))";
+        let result = BashTool::new()
+            .execute(
+                serde_json::json!({"command": format!("python3 -c \"{script}\" || echo missing")}),
+                &context,
+            )
+            .await;
+        let text = result.text();
+        assert!(text.contains("rc -1") || text.contains("missing"), "{text}");
+    }
+
     #[tokio::test]
     async fn test_background_command() {
         let tool = BashTool::new();
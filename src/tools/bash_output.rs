@@ -0,0 +1,203 @@
+//! BashOutput tool - reads new output from background shell processes.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::SchemaTool;
+use super::context::ExecutionContext;
+use super::process::{ProcessManager, ProcessOutput, ProcessStatus};
+use crate::types::ToolResult;
+
+const MAX_OUTPUT: usize = 30_000;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct BashOutputInput {
+    /// The ID of the background shell to retrieve output from
+    pub bash_id: String,
+    /// Optional regular expression to filter the output lines. Only lines matching this regex will be included in the result. Any lines that do not match will no longer be available to read.
+    #[serde(default)]
+    pub filter: Option<String>,
+}
+
+pub struct BashOutputTool {
+    process_manager: Arc<ProcessManager>,
+}
+
+impl BashOutputTool {
+    pub fn new() -> Self {
+        Self {
+            process_manager: Arc::new(ProcessManager::new()),
+        }
+    }
+
+    pub fn process_manager(manager: Arc<ProcessManager>) -> Self {
+        Self {
+            process_manager: manager,
+        }
+    }
+
+    pub fn get_process_manager(&self) -> &Arc<ProcessManager> {
+        &self.process_manager
+    }
+}
+
+impl Default for BashOutputTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn keep_matching(text: &str, filter: Option<&Regex>) -> String {
+    match filter {
+        Some(re) => text
+            .lines()
+            .filter(|line| re.is_match(line))
+            .map(|line| format!("{line}\n"))
+            .collect(),
+        None => text.to_string(),
+    }
+}
+
+/// Keep the most recent `MAX_OUTPUT` bytes of `text`.
+fn tail(text: &mut String) {
+    if text.len() > MAX_OUTPUT {
+        let mut start = text.len() - MAX_OUTPUT;
+        while !text.is_char_boundary(start) {
+            start += 1;
+        }
+        text.replace_range(..start, "... (earlier output truncated)\n");
+    }
+}
+
+fn format_output(output: ProcessOutput, filter: Option<&Regex>) -> String {
+    let status = match output.status {
+        ProcessStatus::Running => "Status: running".to_string(),
+        ProcessStatus::Exited(Some(code)) => format!("Status: exited with code {}", code),
+        ProcessStatus::Exited(None) => "Status: terminated by signal".to_string(),
+    };
+
+    let mut combined = keep_matching(&output.stdout, filter);
+    let stderr = keep_matching(&output.stderr, filter);
+    if !stderr.is_empty() {
+        if !combined.is_empty() {
+            combined.push_str("\n--- stderr ---\n");
+        }
+        combined.push_str(&stderr);
+    }
+    tail(&mut combined);
+
+    if combined.is_empty() {
+        combined = "(no new output)".to_string();
+    }
+    if output.truncated {
+        combined = format!("(some output was dropped before it was read)\n{}", combined);
+    }
+    format!("{}\n{}", status, combined)
+}
+
+#[async_trait]
+impl SchemaTool for BashOutputTool {
+    type Input = BashOutputInput;
+
+    const NAME: &'static str = "BashOutput";
+    const DESCRIPTION: &'static str = r#"
+- Retrieves output from a running or completed background bash shell
+- Takes a bash_id parameter identifying the shell
+- Always returns only new output since the last check, with stdout and stderr separated
+- Returns the shell status: running, or exited with its exit code
+- Supports optional regex filtering to show only lines matching a pattern
+- Use this tool when you need to monitor or check the output of a long-running shell
+- Shell IDs can be obtained from Bash tool responses when using run_in_background"#;
+
+    async fn handle(&self, input: BashOutputInput, _context: &ExecutionContext) -> ToolResult {
+        let filter = match input.filter.as_deref().map(Regex::new).transpose() {
+            Ok(filter) => filter,
+            Err(e) => return ToolResult::error(format!("Invalid filter regex: {}", e)),
+        };
+
+        match self.process_manager.read_new(&input.bash_id).await {
+            Ok(output) => ToolResult::success(format_output(output, filter.as_ref())),
+            Err(e) => ToolResult::error(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::Tool;
+    use std::path::PathBuf;
+
+    #[tokio::test]
+    async fn test_bash_output_incremental_and_filtered() {
+        let manager = Arc::new(ProcessManager::new());
+        let id = manager
+            .spawn(
+                "echo 'compiled ok'; echo 'warning: unused' >&2; exit 1",
+                &PathBuf::from("/tmp"),
+            )
+            .await
+            .unwrap();
+        for _ in 0..250 {
+            if manager.status(&id).await != Some(ProcessStatus::Running) {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+
+        let tool = BashOutputTool::process_manager(manager.clone());
+        let context = ExecutionContext::default();
+        let result = tool
+            .execute(
+                serde_json::json!({"bash_id": id, "filter": "warning"}),
+                &context,
+            )
+            .await;
+        let text = result.text();
+        assert!(text.starts_with("Status: exited with code 1"));
+        assert!(text.contains("warning: unused"));
+        assert!(!text.contains("compiled ok"));
+
+        let result = tool
+            .execute(serde_json::json!({"bash_id": id}), &context)
+            .await;
+        assert!(result.text().contains("(no new output)"));
+    }
+
+    #[tokio::test]
+    async fn test_bash_output_errors() {
+        let tool = BashOutputTool::new();
+        let context = ExecutionContext::default();
+        let result = tool
+            .execute(serde_json::json!({"bash_id": "missing"}), &context)
+            .await;
+        assert!(result.is_error());
+
+        let result = tool
+            .execute(
+                serde_json::json!({"bash_id": "missing", "filter": "("}),
+                &context,
+            )
+            .await;
+        assert!(result.text().contains("Invalid filter regex"));
+    }
+
+    #[test]
+    fn test_format_output_sections() {
+        let output = ProcessOutput {
+            stdout: "listening on :3000\n".into(),
+            stderr: "deprecated flag\n".into(),
+            truncated: false,
+            status: ProcessStatus::Running,
+        };
+        assert_eq!(
+            format_output(output, None),
+            "Status: running\nlistening on :3000\n\n--- stderr ---\ndeprecated flag\n"
+        );
+    }
+}
@@ -0,0 +1,331 @@
+//! Headless-browser WebFetch backend for JavaScript-rendered pages.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use base64::Engine;
+use chromiumoxide::browser::{Browser, BrowserConfig};
+use chromiumoxide::page::{Page, ScreenshotParams};
+use futures::StreamExt;
+use reqwest::Url;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+use super::SchemaTool;
+use super::context::ExecutionContext;
+use super::http::check_url;
+use crate::types::{ToolOutput, ToolOutputBlock, ToolResult};
+
+const DEFAULT_MAX_TEXT_BYTES: usize = 100 * 1024;
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const SELECTOR_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct BrowserFetchInput {
+    /// The http(s) URL of the page to render
+    pub url: String,
+    /// Also return a PNG screenshot of the page (default false)
+    #[serde(default)]
+    pub screenshot: Option<bool>,
+    /// Capture the whole scrollable page instead of the viewport (default false)
+    #[serde(default)]
+    pub full_page: Option<bool>,
+    /// CSS selector to wait for before reading the page, for content loaded after the initial render
+    #[serde(default)]
+    pub wait_for: Option<String>,
+    /// Optional timeout in milliseconds, capped by the tool's configured timeout
+    #[serde(default)]
+    pub timeout: Option<u64>,
+}
+
+/// Client-side `WebFetch` that renders pages in a headless Chromium.
+///
+/// It replaces the server-side `web_fetch` tool, which only sees the HTML the
+/// server returns and so gets an empty shell for single-page apps. The browser
+/// is launched on first use and reused across calls.
+///
+/// The requested URL and the URL the page ends up on must pass the execution
+/// context's [`NetworkSandbox`](crate::security::NetworkSandbox) allowlist;
+/// subresources the page loads itself are not checked.
+pub struct BrowserFetchTool {
+    browser: Mutex<Option<Arc<Browser>>>,
+    chrome_executable: Option<PathBuf>,
+    no_sandbox: bool,
+    window_size: (u32, u32),
+    max_text_bytes: usize,
+    timeout: Duration,
+}
+
+impl BrowserFetchTool {
+    pub fn new() -> Self {
+        Self {
+            browser: Mutex::new(None),
+            chrome_executable: None,
+            no_sandbox: false,
+            window_size: (1280, 800),
+            max_text_bytes: DEFAULT_MAX_TEXT_BYTES,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Chrome or Chromium binary to launch instead of the one found on `PATH`.
+    pub fn chrome_executable(mut self, path: impl Into<PathBuf>) -> Self {
+        self.chrome_executable = Some(path.into());
+        self
+    }
+
+    /// Launch Chromium with `--no-sandbox`, needed when running as root in containers.
+    pub fn no_sandbox(mut self) -> Self {
+        self.no_sandbox = true;
+        self
+    }
+
+    /// Viewport size used for rendering and screenshots (default 1280x800).
+    pub fn window_size(mut self, width: u32, height: u32) -> Self {
+        self.window_size = (width, height);
+        self
+    }
+
+    /// Page text beyond this is truncated (default 100KB).
+    pub fn max_text_bytes(mut self, bytes: usize) -> Self {
+        self.max_text_bytes = bytes;
+        self
+    }
+
+    /// Upper bound for loading and reading a page (default 30s).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn browser(&self) -> Result<Arc<Browser>, String> {
+        let mut guard = self.browser.lock().await;
+        if let Some(browser) = guard.as_ref() {
+            return Ok(Arc::clone(browser));
+        }
+
+        let (width, height) = self.window_size;
+        let mut config = BrowserConfig::builder().window_size(width, height);
+        if let Some(path) = &self.chrome_executable {
+            config = config.chrome_executable(path);
+        }
+        if self.no_sandbox {
+            config = config.no_sandbox();
+        }
+        let config = config.build()?;
+
+        let (browser, mut handler) = Browser::launch(config)
+            .await
+            .map_err(|e| format!("Failed to launch browser: {}", e))?;
+        tokio::spawn(async move { while handler.next().await.is_some() {} });
+
+        let browser = Arc::new(browser);
+        *guard = Some(Arc::clone(&browser));
+        Ok(browser)
+    }
+
+    async fn fetch(
+        &self,
+        input: BrowserFetchInput,
+        context: &ExecutionContext,
+    ) -> Result<Vec<ToolOutputBlock>, String> {
+        let url = Url::parse(&input.url).map_err(|e| format!("Invalid URL: {}", e))?;
+        check_url(&url, context)?;
+
+        let timeout = input
+            .timeout
+            .map(Duration::from_millis)
+            .map_or(self.timeout, |t| t.min(self.timeout));
+
+        let browser = self.browser().await?;
+        let page = match browser.new_page("about:blank").await {
+            Ok(page) => page,
+            Err(e) => {
+                // The browser process is gone; launch a fresh one next time.
+                self.browser.lock().await.take();
+                return Err(format!("Failed to open page: {}", e));
+            }
+        };
+
+        let result = tokio::time::timeout(timeout, self.render(&page, &url, &input, context))
+            .await
+            .unwrap_or_else(|_| {
+                Err(format!(
+                    "Page load timed out after {}ms",
+                    timeout.as_millis()
+                ))
+            });
+        let _ = page.close().await;
+        result
+    }
+
+    async fn render(
+        &self,
+        page: &Page,
+        url: &Url,
+        input: &BrowserFetchInput,
+        context: &ExecutionContext,
+    ) -> Result<Vec<ToolOutputBlock>, String> {
+        page.goto(url.as_str())
+            .await
+            .map_err(|e| format!("Failed to load {}: {}", url, e))?;
+        page.wait_for_navigation()
+            .await
+            .map_err(|e| format!("Failed to load {}: {}", url, e))?;
+
+        let final_url = page
+            .url()
+            .await
+            .ok()
+            .flatten()
+            .and_then(|u| Url::parse(&u).ok())
+            .unwrap_or_else(|| url.clone());
+        if final_url != *url {
+            check_url(&final_url, context)?;
+        }
+
+        if let Some(selector) = &input.wait_for {
+            while page.find_element(selector.as_str()).await.is_err() {
+                tokio::time::sleep(SELECTOR_POLL_INTERVAL).await;
+            }
+        }
+
+        let title = page.get_title().await.ok().flatten().unwrap_or_default();
+        let text: String = page
+            .evaluate("document.body ? document.body.innerText : ''")
+            .await
+            .map_err(|e| format!("Failed to read page text: {}", e))?
+            .into_value()
+            .map_err(|e| format!("Failed to read page text: {}", e))?;
+
+        let mut blocks = vec![ToolOutputBlock::Text {
+            text: self.format_text(&final_url, &title, &text),
+        }];
+
+        if input.screenshot.unwrap_or(false) {
+            let png = page
+                .screenshot(
+                    ScreenshotParams::builder()
+                        .full_page(input.full_page.unwrap_or(false))
+                        .build(),
+                )
+                .await
+                .map_err(|e| format!("Failed to capture screenshot: {}", e))?;
+            blocks.push(ToolOutputBlock::Image {
+                data: base64::engine::general_purpose::STANDARD.encode(png),
+                media_type: "image/png".to_string(),
+            });
+        }
+
+        Ok(blocks)
+    }
+
+    fn format_text(&self, url: &Url, title: &str, text: &str) -> String {
+        let mut text = text.trim();
+        let truncated = text.len() > self.max_text_bytes;
+        if truncated {
+            let mut end = self.max_text_bytes;
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            text = &text[..end];
+        }
+
+        let mut output = format!("URL: {}\n", url);
+        if !title.is_empty() {
+            output.push_str(&format!("Title: {}\n", title));
+        }
+        output.push('\n');
+        output.push_str(text);
+        if truncated {
+            output.push_str(&format!(
+                "\n... (page text truncated at {} bytes)",
+                self.max_text_bytes
+            ));
+        }
+        output
+    }
+}
+
+impl Default for BrowserFetchTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl SchemaTool for BrowserFetchTool {
+    type Input = BrowserFetchInput;
+
+    const NAME: &'static str = "WebFetch";
+    const DESCRIPTION: &'static str = r#"Loads a web page in a headless browser and returns its rendered text, optionally with a screenshot.
+
+- JavaScript runs before the text is read, so single-page apps and client-rendered documentation sites return their real content
+- Set `wait_for` to a CSS selector when content appears after the initial load (e.g. "main article")
+- Set `screenshot` to also get a PNG of the viewport, or of the whole page with `full_page`; use it when layout, charts or images matter
+- Only domains allowed by the network sandbox can be fetched
+- Long page text is truncated
+- For calling APIs, use HttpRequest instead"#;
+
+    async fn handle(&self, input: BrowserFetchInput, context: &ExecutionContext) -> ToolResult {
+        match self.fetch(input, context).await {
+            Ok(blocks) => ToolOutput::SuccessBlocks(blocks).into(),
+            Err(e) => ToolResult::error(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::Tool;
+
+    #[tokio::test]
+    async fn test_rejects_before_launching_browser() {
+        // A missing binary would fail the launch, so these must fail earlier.
+        let tool = BrowserFetchTool::new().chrome_executable("/nonexistent/chrome");
+        let context = ExecutionContext::from_path(std::env::temp_dir()).unwrap();
+
+        let result = tool
+            .execute(serde_json::json!({"url": "https://example.com/"}), &context)
+            .await;
+        assert!(result.text().contains("not allowed by the network sandbox"));
+
+        let result = tool
+            .execute(serde_json::json!({"url": "file:///etc/passwd"}), &context)
+            .await;
+        assert!(result.text().contains("Unsupported URL scheme"));
+
+        let result = tool
+            .execute(serde_json::json!({"url": "not a url"}), &context)
+            .await;
+        assert!(result.text().contains("Invalid URL"));
+
+        let result = tool
+            .execute(
+                serde_json::json!({"url": "http://localhost/", "javascript": "1"}),
+                &context,
+            )
+            .await;
+        assert!(result.is_error());
+    }
+
+    #[test]
+    fn test_format_text_truncates_on_char_boundary() {
+        let tool = BrowserFetchTool::new().max_text_bytes(5);
+        let url = Url::parse("http://localhost/docs").unwrap();
+
+        let text = tool.format_text(&url, "Docs", "abcdé fgh");
+        assert_eq!(
+            text,
+            "URL: http://localhost/docs\nTitle: Docs\n\nabcd\n... (page text truncated at 5 bytes)"
+        );
+
+        let text = tool.format_text(&url, "", "  short  ");
+        assert_eq!(text, "URL: http://localhost/docs\n\nshort");
+    }
+}
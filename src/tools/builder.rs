@@ -5,8 +5,11 @@ use std::sync::Arc;
 
 use super::ProcessManager;
 use super::access::ToolAccess;
+use super::cache::ToolCacheConfig;
 use super::context::ExecutionContext;
 use super::env::ToolExecutionEnv;
+use super::middleware::ToolMiddleware;
+use super::quota::ToolQuotas;
 use super::registry::ToolRegistry;
 use super::traits::Tool;
 use crate::agent::{TaskOutputTool, TaskRegistry, TaskTool};
@@ -24,8 +27,20 @@ pub struct ToolRegistryBuilder {
     subagent_registry: Option<IndexRegistry<SubagentIndex>>,
     policy: Option<PermissionPolicy>,
     sandbox_config: Option<crate::security::SandboxConfig>,
+    allowed_paths: Vec<PathBuf>,
     tool_state: Option<ToolState>,
     session_id: Option<SessionId>,
+    middleware: Vec<Arc<dyn ToolMiddleware>>,
+    cache: Option<ToolCacheConfig>,
+    quotas: Option<ToolQuotas>,
+    files_client: Option<Arc<crate::Client>>,
+    backup_writes: bool,
+    search_excludes: Option<Vec<String>>,
+    audit_log: Option<crate::security::AuditLog>,
+    read_only: bool,
+    file_limits: Option<crate::security::FileLimits>,
+    env_policy: Option<crate::security::bash::EnvPolicy>,
+    bash_policy: Option<crate::security::bash::BashPolicy>,
 }
 
 impl ToolRegistryBuilder {
@@ -38,8 +53,20 @@ impl ToolRegistryBuilder {
             subagent_registry: None,
             policy: None,
             sandbox_config: None,
+            allowed_paths: Vec::new(),
             tool_state: None,
             session_id: None,
+            middleware: Vec::new(),
+            cache: None,
+            quotas: None,
+            files_client: None,
+            backup_writes: false,
+            search_excludes: None,
+            audit_log: None,
+            read_only: false,
+            file_limits: None,
+            env_policy: None,
+            bash_policy: None,
         }
     }
 
@@ -78,6 +105,12 @@ impl ToolRegistryBuilder {
         self
     }
 
+    /// Directories outside the working directory that tools may access.
+    pub fn allowed_paths(mut self, paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        self.allowed_paths.extend(paths);
+        self
+    }
+
     pub fn tool_state(mut self, state: ToolState) -> Self {
         self.tool_state = Some(state);
         self
@@ -88,28 +121,133 @@ impl ToolRegistryBuilder {
         self
     }
 
+    /// Wrap every tool execution; see [`ToolMiddleware`] for ordering.
+    pub fn middleware(mut self, middleware: impl ToolMiddleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Cache successful results of read-only tools.
+    pub fn cache(mut self, config: ToolCacheConfig) -> Self {
+        self.cache = Some(config);
+        self
+    }
+
+    /// Upload files too large to send inline (e.g. big images read by `Read`)
+    /// through the Files API with `client`.
+    pub fn files_client(mut self, client: Arc<crate::Client>) -> Self {
+        self.files_client = Some(client);
+        self
+    }
+
+    /// Directory names Glob and Grep skip, replacing the default
+    /// `node_modules` and `target`.
+    pub fn search_excludes(
+        mut self,
+        excludes: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.search_excludes = Some(excludes.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Record tool calls blocked by the security guard.
+    pub fn audit_log(mut self, log: crate::security::AuditLog) -> Self {
+        self.audit_log = Some(log);
+        self
+    }
+
+    /// Size, line-length and binary limits for reading and writing files.
+    pub fn file_limits(mut self, limits: crate::security::FileLimits) -> Self {
+        self.file_limits = Some(limits);
+        self
+    }
+
+    /// Environment inherited by Bash commands and hooks.
+    pub fn env_policy(mut self, policy: crate::security::bash::EnvPolicy) -> Self {
+        self.env_policy = Some(policy);
+        self
+    }
+
+    /// Commands, arguments and shell features Bash may use.
+    pub fn bash_policy(mut self, policy: crate::security::bash::BashPolicy) -> Self {
+        self.bash_policy = Some(policy);
+        self
+    }
+
+    /// Enforce [`SecurityPolicy::read_only`](crate::security::SecurityPolicy::read_only):
+    /// file writes fail, Bash runs only read-only commands and the write
+    /// tools are not registered.
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Back up files before write tools change them, enabling
+    /// [`ToolRegistry::rollback_writes`].
+    pub fn backup_writes(mut self) -> Self {
+        self.backup_writes = true;
+        self
+    }
+
+    /// Limit how much each session may use its tools.
+    pub fn quotas(mut self, quotas: ToolQuotas) -> Self {
+        self.quotas = Some(quotas);
+        self
+    }
+
     pub fn build(self) -> ToolRegistry {
-        let access = &self.access;
+        let access = if self.read_only {
+            self.access.read_only()
+        } else {
+            self.access
+        };
         let wd = self
             .working_dir
             .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
         let permission_policy = self.policy.unwrap_or_default();
 
-        let sandbox_config = self
+        let mut sandbox_config = self
             .sandbox_config
             .unwrap_or_else(|| crate::security::SandboxConfig::disabled().working_dir(wd.clone()));
+        sandbox_config
+            .allowed_paths
+            .extend(self.allowed_paths.iter().cloned());
 
-        let security = crate::security::SecurityContext::builder()
+        let mut policy = crate::security::SecurityPolicy::new(permission_policy);
+        policy.read_only = self.read_only;
+        let mut security = crate::security::SecurityContext::builder()
             .root(&wd)
+            .allowed_paths(self.allowed_paths)
             .sandbox(sandbox_config)
-            .build()
-            .map(|mut security| {
-                security.policy = crate::security::SecurityPolicy::new(permission_policy);
+            .policy(policy);
+        if let Some(limits) = self.file_limits {
+            security = security.file_limits(limits);
+        }
+        if let Some(policy) = self.env_policy {
+            security = security.env_policy(policy);
+        }
+        if let Some(policy) = self.bash_policy {
+            security = security.bash_policy(policy);
+        }
+        let security = security.build().unwrap_or_else(|_| {
+            let security = crate::security::SecurityContext::permissive();
+            if self.read_only {
+                security.into_read_only()
+            } else {
                 security
-            })
-            .unwrap_or_else(|_| crate::security::SecurityContext::permissive());
+            }
+        });
 
-        let context = ExecutionContext::new(security);
+        let mut context = ExecutionContext::new(security);
+        if let Some(client) = self.files_client {
+            context = context.files_client(client);
+        }
+        if let Some(excludes) = self.search_excludes {
+            context = context.search_excludes(excludes);
+        }
+        if let Some(log) = self.audit_log {
+            context = context.audit_log(log);
+        }
         let task_registry = self
             .task_registry
             .unwrap_or_else(|| TaskRegistry::new(Arc::new(MemoryPersistence::new())));
@@ -134,9 +272,14 @@ impl ToolRegistryBuilder {
             Arc::new(super::ReadTool),
             Arc::new(super::WriteTool),
             Arc::new(super::EditTool),
+            Arc::new(super::MultiEditTool),
             Arc::new(super::GlobTool),
             Arc::new(super::GrepTool),
+            Arc::new(super::LsTool),
             Arc::new(super::BashTool::process_manager(process_manager.clone())),
+            Arc::new(super::BashOutputTool::process_manager(
+                process_manager.clone(),
+            )),
             Arc::new(super::KillShellTool::process_manager(
                 process_manager.clone(),
             )),
@@ -160,6 +303,18 @@ impl ToolRegistryBuilder {
                 registry.register(tool);
             }
         }
+        for middleware in self.middleware {
+            registry.add_middleware(middleware);
+        }
+        if let Some(config) = self.cache {
+            registry.enable_cache(config);
+        }
+        if let Some(quotas) = self.quotas {
+            registry.set_quotas(quotas);
+        }
+        if self.backup_writes {
+            registry.enable_backups();
+        }
 
         registry
     }
@@ -0,0 +1,283 @@
+//! Per-session cache of read-only tool results.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::permissions::{READ_ONLY_TOOLS, WRITE_TOOLS};
+use crate::types::ToolResult;
+
+const DEFAULT_TTL: Duration = Duration::from_secs(300);
+const DEFAULT_CACHED_TOOLS: &[&str] = &["Read", "Glob", "Grep", "LS"];
+
+/// Which tool results to cache and for how long.
+#[derive(Debug, Clone)]
+pub struct ToolCacheConfig {
+    pub ttl: Duration,
+    pub tools: HashSet<String>,
+}
+
+impl Default for ToolCacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl: DEFAULT_TTL,
+            tools: DEFAULT_CACHED_TOOLS.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+}
+
+impl ToolCacheConfig {
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Also cache `tool`. Only add tools whose result depends solely on their input.
+    pub fn tool(mut self, tool: impl Into<String>) -> Self {
+        self.tools.insert(tool.into());
+        self
+    }
+
+    pub fn without_tool(mut self, tool: &str) -> Self {
+        self.tools.remove(tool);
+        self
+    }
+}
+
+struct CacheEntry {
+    stored_at: Instant,
+    /// Path the call read or searched under; `None` means the working directory.
+    path: Option<PathBuf>,
+    result: ToolResult,
+}
+
+/// Successful results of cacheable tools, keyed by tool and canonicalized input.
+///
+/// Owned by one session's [`ToolRegistry`](super::ToolRegistry). A successful
+/// write tool call drops entries that read the written file or searched a
+/// directory containing it. Any other successful call to a tool not known to be
+/// read-only (Bash, subagents, MCP tools, ...) drops everything.
+pub struct ToolResultCache {
+    config: ToolCacheConfig,
+    entries: Mutex<HashMap<(String, String), CacheEntry>>,
+}
+
+impl ToolResultCache {
+    pub fn new(config: ToolCacheConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &ToolCacheConfig {
+        &self.config
+    }
+
+    pub fn is_cacheable(&self, tool: &str) -> bool {
+        self.config.tools.contains(tool)
+    }
+
+    pub fn get(&self, tool: &str, input: &serde_json::Value) -> Option<ToolResult> {
+        let key = (tool.to_string(), canonicalize(input));
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(&key) {
+            Some(entry) if entry.stored_at.elapsed() < self.config.ttl => {
+                Some(entry.result.clone())
+            }
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Store `result` if the tool is cacheable and the call succeeded.
+    pub fn insert(
+        &self,
+        tool: &str,
+        input: &serde_json::Value,
+        path: Option<PathBuf>,
+        result: &ToolResult,
+    ) {
+        if !self.is_cacheable(tool) || result.is_error() {
+            return;
+        }
+        let entry = CacheEntry {
+            stored_at: Instant::now(),
+            path,
+            result: result.clone(),
+        };
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert((tool.to_string(), canonicalize(input)), entry);
+    }
+
+    /// Whether a successful call of `tool` may change files: true for every
+    /// tool that is neither cached nor known to be read-only.
+    pub fn is_invalidated_by(&self, tool: &str) -> bool {
+        !self.is_cacheable(tool) && !READ_ONLY_TOOLS.contains(&tool)
+    }
+
+    /// Drop entries a successful call of `tool` may have made stale.
+    /// `written` is the file a write tool wrote, when known.
+    pub fn invalidate(&self, tool: &str, written: Option<&Path>) {
+        if !self.is_invalidated_by(tool) {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match written {
+            Some(written) if WRITE_TOOLS.contains(&tool) => entries.retain(|_, entry| {
+                entry
+                    .path
+                    .as_deref()
+                    .is_some_and(|path| !written.starts_with(path))
+            }),
+            _ => entries.clear(),
+        }
+    }
+
+    pub fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The file or directory a call reads, searches or writes, as given in its input.
+pub(crate) fn input_path(input: &serde_json::Value) -> Option<&str> {
+    ["file_path", "notebook_path", "path"]
+        .iter()
+        .find_map(|key| input.get(*key).and_then(|v| v.as_str()))
+}
+
+/// JSON with object keys sorted, so equal inputs produce equal keys.
+fn canonicalize(value: &serde_json::Value) -> String {
+    fn sorted(value: &serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::Object(map) => {
+                let mut keys: Vec<_> = map.keys().collect();
+                keys.sort();
+                let mut out = serde_json::Map::new();
+                for key in keys {
+                    out.insert(key.clone(), sorted(&map[key]));
+                }
+                serde_json::Value::Object(out)
+            }
+            serde_json::Value::Array(items) => {
+                serde_json::Value::Array(items.iter().map(sorted).collect())
+            }
+            other => other.clone(),
+        }
+    }
+    sorted(value).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_canonical_keys() {
+        assert_eq!(
+            canonicalize(&json!({"b": 1, "a": {"d": 2, "c": 3}})),
+            canonicalize(&json!({"a": {"c": 3, "d": 2}, "b": 1}))
+        );
+    }
+
+    #[test]
+    fn test_hit_miss_and_ttl() {
+        let cache = ToolResultCache::new(ToolCacheConfig::default());
+        let input = json!({"file_path": "/p/a.rs"});
+        cache.insert(
+            "Read",
+            &input,
+            Some("/p/a.rs".into()),
+            &ToolResult::success("x"),
+        );
+        cache.insert(
+            "Bash",
+            &json!({"command": "ls"}),
+            None,
+            &ToolResult::success("x"),
+        );
+        cache.insert(
+            "Read",
+            &json!({"file_path": "/p/b.rs"}),
+            Some("/p/b.rs".into()),
+            &ToolResult::error("missing"),
+        );
+        assert_eq!(cache.get("Read", &input).unwrap().text(), "x");
+        assert_eq!(cache.len(), 1);
+
+        let cache = ToolResultCache::new(ToolCacheConfig::default().ttl(Duration::ZERO));
+        cache.insert(
+            "Read",
+            &input,
+            Some("/p/a.rs".into()),
+            &ToolResult::success("x"),
+        );
+        assert!(cache.get("Read", &input).is_none());
+    }
+
+    #[test]
+    fn test_invalidation() {
+        let cache = ToolResultCache::new(ToolCacheConfig::default());
+        let read_a = json!({"file_path": "/p/src/a.rs"});
+        let read_b = json!({"file_path": "/p/src/b.rs"});
+        let grep_src = json!({"pattern": "x", "path": "/p/src"});
+        let grep_docs = json!({"pattern": "x", "path": "/p/docs"});
+        let glob_cwd = json!({"pattern": "*.rs"});
+        let ok = ToolResult::success("x");
+        cache.insert("Read", &read_a, Some("/p/src/a.rs".into()), &ok);
+        cache.insert("Read", &read_b, Some("/p/src/b.rs".into()), &ok);
+        cache.insert("Grep", &grep_src, Some("/p/src".into()), &ok);
+        cache.insert("Grep", &grep_docs, Some("/p/docs".into()), &ok);
+        cache.insert("Glob", &glob_cwd, None, &ok);
+
+        cache.invalidate("Read", Some(Path::new("/p/src/a.rs")));
+        assert_eq!(cache.len(), 5);
+
+        cache.invalidate("Edit", Some(Path::new("/p/src/a.rs")));
+        assert!(cache.get("Read", &read_a).is_none());
+        assert!(cache.get("Grep", &grep_src).is_none());
+        assert!(cache.get("Glob", &glob_cwd).is_none());
+        assert!(cache.get("Read", &read_b).is_some());
+        assert!(cache.get("Grep", &grep_docs).is_some());
+
+        cache.invalidate("WebFetch", None);
+        cache.invalidate("str_replace_editor", Some(Path::new("/p/src/b.rs")));
+        assert!(cache.get("Read", &read_b).is_none());
+        assert!(cache.get("Grep", &grep_docs).is_some());
+
+        cache.invalidate("Bash", None);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_tools_flush() {
+        let cache = ToolResultCache::new(ToolCacheConfig::default());
+        let read = json!({"file_path": "/p/a.rs"});
+        let ok = ToolResult::success("x");
+        for tool in ["Task", "mcp__db__migrate", "Custom"] {
+            cache.insert("Read", &read, Some("/p/a.rs".into()), &ok);
+            cache.invalidate(tool, Some(Path::new("/elsewhere")));
+            assert!(cache.is_empty(), "{tool} kept the cache");
+        }
+        assert!(!cache.is_invalidated_by("Grep"));
+        assert!(!cache.is_invalidated_by("WebSearch"));
+    }
+}
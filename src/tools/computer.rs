@@ -0,0 +1,384 @@
+//! Computer tool - screenshot, mouse and keyboard actions through a pluggable backend.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::Tool;
+use super::context::ExecutionContext;
+use crate::client::messages::ApiTool;
+use crate::types::{ComputerUseTool, ToolOutput, ToolOutputBlock, ToolResult};
+
+const MAX_WAIT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ScrollDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// An action requested by the model, as defined by `computer_20250124`.
+///
+/// Coordinates are `[x, y]` pixels within the advertised display size.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ComputerAction {
+    Screenshot,
+    CursorPosition,
+    Key {
+        text: String,
+    },
+    HoldKey {
+        text: String,
+        duration: f64,
+    },
+    Type {
+        text: String,
+    },
+    MouseMove {
+        coordinate: [u32; 2],
+    },
+    LeftClick {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        coordinate: Option<[u32; 2]>,
+        /// Modifier keys held during the click (e.g. "shift").
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        text: Option<String>,
+    },
+    RightClick {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        coordinate: Option<[u32; 2]>,
+    },
+    MiddleClick {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        coordinate: Option<[u32; 2]>,
+    },
+    DoubleClick {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        coordinate: Option<[u32; 2]>,
+    },
+    TripleClick {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        coordinate: Option<[u32; 2]>,
+    },
+    LeftMouseDown,
+    LeftMouseUp,
+    LeftClickDrag {
+        start_coordinate: [u32; 2],
+        coordinate: [u32; 2],
+    },
+    Scroll {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        coordinate: Option<[u32; 2]>,
+        scroll_direction: ScrollDirection,
+        scroll_amount: u32,
+        /// Modifier keys held while scrolling.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        text: Option<String>,
+    },
+    Wait {
+        duration: f64,
+    },
+}
+
+impl ComputerAction {
+    /// Coordinates the action targets, for bounds checking.
+    fn coordinates(&self) -> Vec<[u32; 2]> {
+        match self {
+            Self::MouseMove { coordinate } => vec![*coordinate],
+            Self::LeftClickDrag {
+                start_coordinate,
+                coordinate,
+            } => vec![*start_coordinate, *coordinate],
+            Self::LeftClick { coordinate, .. }
+            | Self::RightClick { coordinate }
+            | Self::MiddleClick { coordinate }
+            | Self::DoubleClick { coordinate }
+            | Self::TripleClick { coordinate }
+            | Self::Scroll { coordinate, .. } => coordinate.iter().copied().collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// What a backend produced for an action.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComputerOutput {
+    /// Base64-encoded image of the display.
+    Screenshot {
+        data: String,
+        media_type: String,
+    },
+    Text(String),
+    Done,
+}
+
+impl ComputerOutput {
+    pub fn png(data: impl Into<String>) -> Self {
+        Self::Screenshot {
+            data: data.into(),
+            media_type: "image/png".to_string(),
+        }
+    }
+}
+
+/// Desktop driver behind [`ComputerTool`] (xdotool, a VNC session, a browser, ...).
+#[async_trait]
+pub trait ComputerBackend: Send + Sync {
+    /// Display size advertised to the model, in pixels.
+    fn display_size(&self) -> (u32, u32);
+
+    /// X11 display number, when the environment has several.
+    fn display_number(&self) -> Option<u32> {
+        None
+    }
+
+    /// Perform an action. `Screenshot` must return [`ComputerOutput::Screenshot`].
+    async fn perform(&self, action: &ComputerAction) -> crate::Result<ComputerOutput>;
+}
+
+/// Client-executed `computer_20250124` tool.
+///
+/// The request is sent with the computer-use beta flag, and every action runs
+/// through the [`ComputerBackend`]. By default a screenshot is attached after
+/// each action so the model can see its effect.
+pub struct ComputerTool {
+    backend: Arc<dyn ComputerBackend>,
+    screenshot_after_action: bool,
+}
+
+impl ComputerTool {
+    pub fn new(backend: impl ComputerBackend + 'static) -> Self {
+        Self::from_arc(Arc::new(backend))
+    }
+
+    pub fn from_arc(backend: Arc<dyn ComputerBackend>) -> Self {
+        Self {
+            backend,
+            screenshot_after_action: true,
+        }
+    }
+
+    /// Attach a screenshot to the result of every non-screenshot action (default true).
+    pub fn screenshot_after_action(mut self, enabled: bool) -> Self {
+        self.screenshot_after_action = enabled;
+        self
+    }
+
+    fn check_bounds(&self, action: &ComputerAction) -> Result<(), String> {
+        let (width, height) = self.backend.display_size();
+        for [x, y] in action.coordinates() {
+            if x >= width || y >= height {
+                return Err(format!(
+                    "Coordinate ({}, {}) is outside the {}x{} display",
+                    x, y, width, height
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    async fn run(&self, action: ComputerAction) -> crate::Result<Vec<ToolOutputBlock>> {
+        if let ComputerAction::Wait { duration } = action {
+            let secs = duration.max(0.0).min(MAX_WAIT.as_secs_f64());
+            tokio::time::sleep(Duration::from_secs_f64(secs)).await;
+        }
+
+        let mut blocks = Vec::new();
+        match self.backend.perform(&action).await? {
+            ComputerOutput::Screenshot { data, media_type } => {
+                return Ok(vec![ToolOutputBlock::Image { data, media_type }]);
+            }
+            ComputerOutput::Text(text) => blocks.push(ToolOutputBlock::Text { text }),
+            ComputerOutput::Done => {}
+        }
+
+        if self.screenshot_after_action
+            && action != ComputerAction::CursorPosition
+            && let ComputerOutput::Screenshot { data, media_type } =
+                self.backend.perform(&ComputerAction::Screenshot).await?
+        {
+            blocks.push(ToolOutputBlock::Image { data, media_type });
+        }
+        Ok(blocks)
+    }
+}
+
+#[async_trait]
+impl Tool for ComputerTool {
+    fn name(&self) -> &str {
+        ComputerUseTool::NAME
+    }
+
+    fn description(&self) -> &str {
+        "Control the computer's screen, mouse and keyboard."
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        serde_json::to_value(schemars::schema_for!(ComputerAction))
+            .unwrap_or_else(|_| serde_json::json!({"type": "object"}))
+    }
+
+    fn api_tool(&self) -> ApiTool {
+        let (width, height) = self.backend.display_size();
+        let mut tool = ComputerUseTool::new(width, height);
+        if let Some(number) = self.backend.display_number() {
+            tool = tool.display_number(number);
+        }
+        ApiTool::Computer(tool)
+    }
+
+    async fn execute(&self, input: serde_json::Value, _context: &ExecutionContext) -> ToolResult {
+        let action: ComputerAction = match serde_json::from_value(input) {
+            Ok(action) => action,
+            Err(e) => return ToolResult::error(format!("Invalid input: {}", e)),
+        };
+        if let Err(e) = self.check_bounds(&action) {
+            return ToolOutput::invalid_input(e).into();
+        }
+
+        match self.run(action).await {
+            Ok(blocks) if blocks.is_empty() => ToolResult::success("Done"),
+            Ok(blocks) => ToolOutput::SuccessBlocks(blocks).into(),
+            Err(e) => ToolResult::error(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingBackend {
+        actions: Mutex<Vec<ComputerAction>>,
+    }
+
+    #[async_trait]
+    impl ComputerBackend for RecordingBackend {
+        fn display_size(&self) -> (u32, u32) {
+            (1024, 768)
+        }
+
+        async fn perform(&self, action: &ComputerAction) -> crate::Result<ComputerOutput> {
+            self.actions.lock().unwrap().push(action.clone());
+            Ok(match action {
+                ComputerAction::Screenshot => ComputerOutput::png("aW1n"),
+                ComputerAction::CursorPosition => ComputerOutput::Text("X=1,Y=2".into()),
+                _ => ComputerOutput::Done,
+            })
+        }
+    }
+
+    #[test]
+    fn test_action_deserialization() {
+        let action: ComputerAction = serde_json::from_value(serde_json::json!({
+            "action": "left_click",
+            "coordinate": [10, 20]
+        }))
+        .unwrap();
+        assert_eq!(
+            action,
+            ComputerAction::LeftClick {
+                coordinate: Some([10, 20]),
+                text: None
+            }
+        );
+
+        let action: ComputerAction = serde_json::from_value(serde_json::json!({
+            "action": "scroll",
+            "coordinate": [5, 5],
+            "scroll_direction": "down",
+            "scroll_amount": 3
+        }))
+        .unwrap();
+        assert!(matches!(
+            action,
+            ComputerAction::Scroll {
+                scroll_direction: ScrollDirection::Down,
+                scroll_amount: 3,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_api_tool() {
+        let tool = ComputerTool::new(RecordingBackend::default());
+        let ApiTool::Computer(api) = tool.api_tool() else {
+            panic!("expected computer tool");
+        };
+        assert_eq!(api, ComputerUseTool::new(1024, 768));
+    }
+
+    #[tokio::test]
+    async fn test_click_attaches_screenshot() {
+        let backend = Arc::new(RecordingBackend::default());
+        let tool = ComputerTool::from_arc(backend.clone());
+
+        let result = tool
+            .execute(
+                serde_json::json!({"action": "left_click", "coordinate": [100, 200]}),
+                &ExecutionContext::permissive(),
+            )
+            .await;
+        let ToolOutput::SuccessBlocks(blocks) = result.output else {
+            panic!("expected blocks, got {:?}", result.output);
+        };
+        assert!(
+            matches!(&blocks[..], [ToolOutputBlock::Image { media_type, .. }] if media_type == "image/png")
+        );
+        assert_eq!(backend.actions.lock().unwrap().len(), 2);
+
+        let result = tool
+            .execute(
+                serde_json::json!({"action": "cursor_position"}),
+                &ExecutionContext::permissive(),
+            )
+            .await;
+        assert_eq!(result.text(), "X=1,Y=2");
+    }
+
+    #[tokio::test]
+    async fn test_without_screenshot_after_action() {
+        let tool = ComputerTool::new(RecordingBackend::default()).screenshot_after_action(false);
+        let result = tool
+            .execute(
+                serde_json::json!({"action": "type", "text": "hello"}),
+                &ExecutionContext::permissive(),
+            )
+            .await;
+        assert_eq!(result.text(), "Done");
+    }
+
+    #[tokio::test]
+    async fn test_rejects_out_of_bounds_and_invalid_input() {
+        let backend = Arc::new(RecordingBackend::default());
+        let tool = ComputerTool::from_arc(backend.clone());
+
+        let result = tool
+            .execute(
+                serde_json::json!({"action": "mouse_move", "coordinate": [1024, 0]}),
+                &ExecutionContext::permissive(),
+            )
+            .await;
+        assert!(result.is_error());
+        assert!(result.text().contains("outside the 1024x768 display"));
+
+        let result = tool
+            .execute(
+                serde_json::json!({"action": "fly"}),
+                &ExecutionContext::permissive(),
+            )
+            .await;
+        assert!(result.is_error());
+        assert!(backend.actions.lock().unwrap().is_empty());
+    }
+}
@@ -5,19 +5,23 @@ use std::path::Path;
 use std::sync::Arc;
 
 use crate::hooks::{HookContext, HookEvent, HookInput, HookManager};
-use crate::permissions::{PermissionResult, ToolLimits};
+use crate::permissions::{ArgumentMatcher, PermissionGrants, PermissionResult, ToolLimits};
+use crate::security::audit::{AuditEvent, AuditLog};
 use crate::security::bash::{BashAnalysis, SanitizedEnv};
 use crate::security::fs::SecureFileHandle;
 use crate::security::guard::SecurityGuard;
 use crate::security::path::SafePath;
-use crate::security::sandbox::{DomainCheck, SandboxResult};
-use crate::security::{ResourceLimits, SecurityContext, SecurityError};
+use crate::security::sandbox::{DomainCheck, EgressAttempt, SandboxResult};
+use crate::security::{FileLimits, ResourceLimits, SecurityContext, SecurityError};
 
 #[derive(Clone)]
 pub struct ExecutionContext {
     security: Arc<SecurityContext>,
     hooks: Option<HookManager>,
     session_id: Option<String>,
+    files: Option<Arc<crate::Client>>,
+    search_excludes: Arc<[String]>,
+    audit: Option<AuditLog>,
 }
 
 impl ExecutionContext {
@@ -26,6 +30,9 @@ impl ExecutionContext {
             security: Arc::new(security),
             hooks: None,
             session_id: None,
+            files: None,
+            search_excludes: default_search_excludes(),
+            audit: None,
         }
     }
 
@@ -43,6 +50,9 @@ impl ExecutionContext {
             security: Arc::new(SecurityContext::permissive()),
             hooks: None,
             session_id: None,
+            files: None,
+            search_excludes: default_search_excludes(),
+            audit: None,
         }
     }
 
@@ -56,9 +66,51 @@ impl ExecutionContext {
         self.session_id.as_deref()
     }
 
+    /// Let tools upload files too large to send inline through the Files API.
+    pub fn files_client(mut self, client: Arc<crate::Client>) -> Self {
+        self.files = Some(client);
+        self
+    }
+
+    pub fn files(&self) -> Option<&crate::Client> {
+        self.files.as_deref()
+    }
+
+    /// Directory names Glob and Grep skip (default: `node_modules`, `target`).
+    pub fn search_excludes(
+        mut self,
+        excludes: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.search_excludes = excludes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn excluded_from_search(&self) -> &[String] {
+        &self.search_excludes
+    }
+
+    /// Record blocked tool calls to `log`.
+    pub fn audit_log(mut self, log: AuditLog) -> Self {
+        self.audit = Some(log);
+        self
+    }
+
+    pub fn audit(&self) -> Option<&AuditLog> {
+        self.audit.as_ref()
+    }
+
+    /// Write `event` to the audit log, if one is configured.
+    pub async fn record_audit(&self, event: AuditEvent) {
+        if let Some(log) = &self.audit {
+            log.record(event).await;
+        }
+    }
+
     pub async fn fire_hook(&self, event: HookEvent, input: HookInput) {
         if let Some(ref hooks) = self.hooks {
-            let context = HookContext::new(input.session_id.clone()).cwd(self.root().to_path_buf());
+            let context = HookContext::new(input.session_id.clone())
+                .cwd(self.root().to_path_buf())
+                .env_policy(self.security.env.clone());
             if let Err(e) = hooks.execute(event, input, &context).await {
                 tracing::warn!(error = %e, "Hook execution failed");
             }
@@ -139,6 +191,11 @@ impl ExecutionContext {
         self.security.fs.is_within(path)
     }
 
+    /// Whether the context only permits reads.
+    pub fn is_read_only(&self) -> bool {
+        self.security.fs.is_read_only()
+    }
+
     pub fn analyze_bash(&self, command: &str) -> BashAnalysis {
         self.security.bash.analyze(command)
     }
@@ -148,13 +205,20 @@ impl ExecutionContext {
     }
 
     fn sanitized_env(&self) -> SanitizedEnv {
-        SanitizedEnv::from_current().working_dir(self.root())
+        self.security
+            .env
+            .sanitize_current()
+            .working_dir(self.root())
     }
 
     pub fn resource_limits(&self) -> &ResourceLimits {
         &self.security.limits
     }
 
+    pub fn file_limits(&self) -> &FileLimits {
+        self.security.fs.file_limits()
+    }
+
     pub fn check_domain(&self, domain: &str) -> DomainCheck {
         self.security.network.check(domain)
     }
@@ -175,17 +239,79 @@ impl ExecutionContext {
         self.security.sandbox.wrap_command(command)
     }
 
+    /// Seccomp filter to install in spawned commands, if the sandbox has one.
+    #[cfg(target_os = "linux")]
+    pub fn seccomp_filter(&self) -> Option<Arc<crate::security::sandbox::SeccompFilter>> {
+        self.security.sandbox.seccomp_filter()
+    }
+
+    /// Connections the sandbox's egress proxy has seen, oldest first.
+    pub fn egress_log(&self) -> Vec<EgressAttempt> {
+        self.security.sandbox.egress_log()
+    }
+
     pub fn sandbox_env(&self) -> HashMap<String, String> {
         self.security.sandbox.environment_vars()
     }
 
+    /// Sanitized environment for running `command` in the sandbox, with the
+    /// proxy credentials of its network rule.
+    pub fn sanitized_env_for_command(&self, command: &str) -> SanitizedEnv {
+        let sandbox_env = self.security.sandbox.environment_vars_for(command);
+        self.sanitized_env().vars(sandbox_env)
+    }
+
     pub fn sanitized_env_with_sandbox(&self) -> SanitizedEnv {
         let sandbox_env = self.sandbox_env();
         self.sanitized_env().vars(sandbox_env)
     }
 
     pub fn check_permission(&self, tool_name: &str, input: &serde_json::Value) -> PermissionResult {
-        self.security.policy.permission.check(tool_name, input)
+        let result = self
+            .security
+            .policy
+            .permission
+            .check_in(tool_name, input, self.root());
+        self.with_allowed_directories(tool_name, result)
+    }
+
+    /// Like [`check_permission`](Self::check_permission), consulting a
+    /// session's temporary grants.
+    pub fn check_permission_with_grants(
+        &self,
+        tool_name: &str,
+        input: &serde_json::Value,
+        grants: &PermissionGrants,
+    ) -> PermissionResult {
+        let result = self.security.policy.permission.check_with_grants(
+            tool_name,
+            input,
+            self.root(),
+            grants,
+        );
+        self.with_allowed_directories(tool_name, result)
+    }
+
+    /// Point a denied file tool at the directories it may use.
+    fn with_allowed_directories(
+        &self,
+        tool_name: &str,
+        result: PermissionResult,
+    ) -> PermissionResult {
+        if result.is_allowed()
+            || self.security.fs.is_permissive()
+            || !matches!(
+                ArgumentMatcher::for_tool(tool_name, ""),
+                Some(ArgumentMatcher::Path(..))
+            )
+        {
+            return result;
+        }
+        let mut remediation = result.remediation.clone().unwrap_or_default();
+        remediation.allowed_directories = std::iter::once(self.root().to_path_buf())
+            .chain(self.security.fs.allowed_paths().iter().cloned())
+            .collect();
+        result.remediation(remediation)
     }
 
     pub fn validate_security(
@@ -193,8 +319,24 @@ impl ExecutionContext {
         tool_name: &str,
         input: &serde_json::Value,
     ) -> Result<(), String> {
-        SecurityGuard::validate(&self.security, tool_name, input).map_err(|e| e.to_string())
+        self.check_security(tool_name, input)
+            .map_err(|e| e.to_string())
     }
+
+    pub fn check_security(
+        &self,
+        tool_name: &str,
+        input: &serde_json::Value,
+    ) -> Result<(), SecurityError> {
+        SecurityGuard::validate(&self.security, tool_name, input)
+    }
+}
+
+fn default_search_excludes() -> Arc<[String]> {
+    super::walk::DEFAULT_SEARCH_EXCLUDES
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
 }
 
 impl Default for ExecutionContext {
@@ -57,27 +57,11 @@ Usage:
         let display_path = path.as_path().display().to_string();
 
         let result = tokio::task::spawn_blocking(move || {
-            let handle =
-                SecureFileHandle::open_read(path.clone()).map_err(|e| e.to_string())?;
+            let handle = SecureFileHandle::open_read(path.clone()).map_err(|e| e.to_string())?;
             let original_content = handle.read_to_string().map_err(|e| e.to_string())?;
 
-            let count = original_content.matches(&old_string).count();
-            if count == 0 {
-                return Err("old_string not found in file. Make sure it matches exactly including whitespace.".to_string());
-            }
-            if count > 1 && !replace_all {
-                return Err(format!(
-                    "old_string found {} times. Use replace_all=true to replace all, \
-                     or provide more context to make it unique.",
-                    count
-                ));
-            }
-
-            let new_content = if replace_all {
-                original_content.replace(&old_string, &new_string)
-            } else {
-                original_content.replacen(&old_string, &new_string, 1)
-            };
+            let (new_content, count) =
+                replace(&original_content, &old_string, &new_string, replace_all)?;
 
             let recheck_handle =
                 SecureFileHandle::open_read(path.clone()).map_err(|e| e.to_string())?;
@@ -110,6 +94,37 @@ Usage:
     }
 }
 
+/// Replace `old_string` in `content`, returning the new content and the number
+/// of replacements. Fails if it is missing, or ambiguous without `replace_all`.
+pub(crate) fn replace(
+    content: &str,
+    old_string: &str,
+    new_string: &str,
+    replace_all: bool,
+) -> Result<(String, usize), String> {
+    let count = content.matches(old_string).count();
+    if count == 0 {
+        return Err(
+            "old_string not found in file. Make sure it matches exactly including whitespace."
+                .to_string(),
+        );
+    }
+    if count > 1 && !replace_all {
+        return Err(format!(
+            "old_string found {} times. Use replace_all=true to replace all, \
+             or provide more context to make it unique.",
+            count
+        ));
+    }
+
+    let new_content = if replace_all {
+        content.replace(old_string, new_string)
+    } else {
+        content.replacen(old_string, new_string, 1)
+    };
+    Ok((new_content, count))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -18,6 +18,9 @@ pub struct GlobInput {
     /// simply omit it for the default behavior. Must be a valid directory path if provided.
     #[serde(default)]
     pub path: Option<String>,
+    /// Include files excluded by .gitignore/.ignore and the default excludes (node_modules, target). Default: false.
+    #[serde(default)]
+    pub no_ignore: Option<bool>,
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -31,6 +34,7 @@ impl SchemaTool for GlobTool {
     const DESCRIPTION: &'static str = r#"- Fast file pattern matching tool that works with any codebase size
 - Supports glob patterns like "**/*.js" or "src/**/*.ts"
 - Returns matching file paths sorted by modification time
+- Skips files ignored by .gitignore/.ignore and vendored directories (node_modules, target); set no_ignore to include them
 - Use this tool when you need to find files by name patterns
 - When you are doing an open ended search that may require multiple rounds of globbing and grepping, use the Task tool instead
 - You can call multiple tools in a single response. It is always better to speculatively perform multiple searches in parallel if they are potentially useful."#;
@@ -42,12 +46,13 @@ impl SchemaTool for GlobTool {
         };
 
         let full_pattern = base_path.join(&input.pattern);
-        let pattern_str = full_pattern.to_string_lossy().to_string();
+        let excludes = context.excluded_from_search().to_vec();
+        let no_ignore = input.no_ignore.unwrap_or(false);
 
         let glob_result = tokio::task::spawn_blocking(move || {
-            glob::glob(&pattern_str).map(|paths| {
+            super::walk::glob_walk(&full_pattern, &excludes, no_ignore).map(|paths| {
                 paths
-                    .filter_map(|r| r.ok())
+                    .into_iter()
                     .filter_map(|p| {
                         std::fs::canonicalize(&p).ok().and_then(|canonical| {
                             canonical
@@ -77,7 +82,7 @@ impl SchemaTool for GlobTool {
             return ToolResult::success("No files matched the pattern");
         }
 
-        entries.sort_by(|a, b| b.1.cmp(&a.1));
+        entries.sort_by_key(|e| std::cmp::Reverse(e.1));
 
         let output: Vec<String> = entries
             .into_iter()
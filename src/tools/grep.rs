@@ -1,10 +1,15 @@
 //! Grep tool - content search with regex using ripgrep.
+//!
+//! Spawns `rg` by default; the `grep-engine` feature searches in-process with
+//! ripgrep's own crates instead.
 
+#[cfg(not(feature = "grep-engine"))]
 use std::process::Stdio;
 
 use async_trait::async_trait;
 use schemars::JsonSchema;
 use serde::Deserialize;
+#[cfg(not(feature = "grep-engine"))]
 use tokio::process::Command;
 
 use super::SchemaTool;
@@ -52,6 +57,9 @@ pub struct GrepInput {
     /// Skip first N lines/entries before applying head_limit. Works across all output modes. Defaults to 0.
     #[serde(default)]
     pub offset: Option<usize>,
+    /// Search files excluded by .gitignore/.ignore and the default excludes (node_modules, target). Default: false.
+    #[serde(default)]
+    pub no_ignore: Option<bool>,
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -71,6 +79,7 @@ impl SchemaTool for GrepTool {
   - Output modes: "content" shows matching lines, "files_with_matches" shows only file paths (default), "count" shows match counts
   - Use Task tool for open-ended searches requiring multiple rounds
   - Pattern syntax: Uses ripgrep (not grep) - literal braces need escaping (use `interface\{\}` to find `interface{}` in Go code)
+  - Skips files ignored by .gitignore/.ignore and vendored directories (node_modules, target); set no_ignore to search them
   - Multiline matching: By default patterns match within single lines only. For cross-line patterns like `struct \{[\s\S]*?field`, use `multiline: true`"#;
 
     async fn handle(&self, input: GrepInput, context: &ExecutionContext) -> ToolResult {
@@ -79,81 +88,107 @@ impl SchemaTool for GrepTool {
             Err(e) => return e,
         };
 
-        let mut cmd = Command::new("rg");
-
-        match input.output_mode.as_deref() {
-            Some("content") => {
-                if input.line_numbers.unwrap_or(true) {
-                    cmd.arg("-n");
-                }
-            }
-            Some("files_with_matches") | None => {
-                cmd.arg("-l");
-            }
-            Some("count") => {
-                cmd.arg("-c");
-            }
-            Some(mode) => {
-                return ToolResult::error(format!("Unknown output_mode: {}", mode));
-            }
+        if let Some(mode) = input.output_mode.as_deref()
+            && !matches!(mode, "content" | "files_with_matches" | "count")
+        {
+            return ToolResult::error(format!("Unknown output_mode: {}", mode));
         }
 
-        if input.case_insensitive.unwrap_or(false) {
-            cmd.arg("-i");
+        #[cfg(feature = "grep-engine")]
+        let output =
+            super::grep_engine::search(&input, &search_path, context.excluded_from_search()).await;
+        #[cfg(not(feature = "grep-engine"))]
+        let output = run_ripgrep(&input, &search_path, context.excluded_from_search()).await;
+
+        let stdout = match output {
+            Ok(stdout) => stdout,
+            Err(e) => return ToolResult::error(e),
+        };
+
+        if stdout.is_empty() {
+            return ToolResult::success("No matches found");
         }
 
-        if let Some(c) = input.context {
-            cmd.arg("-C").arg(c.to_string());
-        } else {
-            if let Some(a) = input.after_context {
-                cmd.arg("-A").arg(a.to_string());
-            }
-            if let Some(b) = input.before_context {
-                cmd.arg("-B").arg(b.to_string());
+        let result = apply_pagination(&stdout, input.offset, input.head_limit);
+        ToolResult::success(result)
+    }
+}
+
+/// Search by spawning the `rg` binary.
+#[cfg(not(feature = "grep-engine"))]
+async fn run_ripgrep(
+    input: &GrepInput,
+    search_path: &std::path::Path,
+    excludes: &[String],
+) -> Result<String, String> {
+    let mut cmd = Command::new("rg");
+
+    match input.output_mode.as_deref() {
+        Some("content") => {
+            if input.line_numbers.unwrap_or(true) {
+                cmd.arg("-n");
             }
         }
-
-        if let Some(t) = &input.file_type {
-            cmd.arg("-t").arg(t);
+        Some("count") => {
+            cmd.arg("-c");
         }
-
-        if let Some(g) = &input.glob {
-            cmd.arg("-g").arg(g);
+        _ => {
+            cmd.arg("-l");
         }
+    }
+
+    if input.case_insensitive.unwrap_or(false) {
+        cmd.arg("-i");
+    }
 
-        if input.multiline.unwrap_or(false) {
-            cmd.arg("-U").arg("--multiline-dotall");
+    if let Some(c) = input.context {
+        cmd.arg("-C").arg(c.to_string());
+    } else {
+        if let Some(a) = input.after_context {
+            cmd.arg("-A").arg(a.to_string());
         }
+        if let Some(b) = input.before_context {
+            cmd.arg("-B").arg(b.to_string());
+        }
+    }
 
-        cmd.arg(&input.pattern);
-        cmd.arg(&search_path);
-        cmd.stdout(Stdio::piped());
-        cmd.stderr(Stdio::piped());
-
-        let output = match cmd.output().await {
-            Ok(o) => o,
-            Err(e) => {
-                return ToolResult::error(format!(
-                    "Failed to execute ripgrep (is rg installed?): {}",
-                    e
-                ));
-            }
-        };
+    if let Some(t) = &input.file_type {
+        cmd.arg("-t").arg(t);
+    }
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
+    if let Some(g) = &input.glob {
+        cmd.arg("-g").arg(g);
+    }
 
-        if !output.status.success() && !stderr.is_empty() {
-            return ToolResult::error(format!("Ripgrep error: {}", stderr));
-        }
+    if input.multiline.unwrap_or(false) {
+        cmd.arg("-U").arg("--multiline-dotall");
+    }
 
-        if stdout.is_empty() {
-            return ToolResult::success("No matches found");
+    if input.no_ignore.unwrap_or(false) {
+        cmd.arg("--no-ignore");
+    } else {
+        cmd.arg("--no-require-git");
+        for exclude in excludes {
+            cmd.arg("-g").arg(format!("!{}", exclude));
         }
+    }
 
-        let result = apply_pagination(&stdout, input.offset, input.head_limit);
-        ToolResult::success(result)
+    cmd.arg(&input.pattern);
+    cmd.arg(search_path);
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+
+    let output = cmd
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute ripgrep (is rg installed?): {}", e))?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() && !stderr.is_empty() {
+        return Err(format!("Ripgrep error: {}", stderr));
     }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
 }
 
 fn apply_pagination(content: &str, offset: Option<usize>, limit: Option<usize>) -> String {
@@ -0,0 +1,315 @@
+//! In-process Grep backend built on ripgrep's crates.
+//!
+//! Produces the same output as `rg` with the flags [`GrepTool`](super::GrepTool)
+//! passes it, without spawning a process per search.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use grep_regex::RegexMatcherBuilder;
+use grep_searcher::{
+    BinaryDetection, Searcher, SearcherBuilder, Sink, SinkContext, SinkMatch, sinks,
+};
+use ignore::WalkBuilder;
+use ignore::overrides::OverrideBuilder;
+use ignore::types::TypesBuilder;
+
+use super::grep::GrepInput;
+
+#[derive(Clone, Copy)]
+enum OutputMode {
+    FilesWithMatches,
+    Content,
+    Count,
+}
+
+/// Run `input` against `search_path`, returning rg-formatted output.
+pub(crate) async fn search(
+    input: &GrepInput,
+    search_path: &Path,
+    excludes: &[String],
+) -> Result<String, String> {
+    let mode = match input.output_mode.as_deref() {
+        Some("content") => OutputMode::Content,
+        Some("count") => OutputMode::Count,
+        _ => OutputMode::FilesWithMatches,
+    };
+    let multiline = input.multiline.unwrap_or(false);
+
+    let matcher = RegexMatcherBuilder::new()
+        .case_insensitive(input.case_insensitive.unwrap_or(false))
+        .multi_line(true)
+        .dot_matches_new_line(multiline)
+        .line_terminator((!multiline).then_some(b'\n'))
+        .build(&input.pattern)
+        .map_err(|e| format!("Invalid pattern: {}", e))?;
+
+    let (before, after) = match input.context {
+        Some(c) => (c, c),
+        None => (
+            input.before_context.unwrap_or(0),
+            input.after_context.unwrap_or(0),
+        ),
+    };
+    let mut searcher = SearcherBuilder::new();
+    searcher
+        .line_number(true)
+        .multi_line(multiline)
+        .binary_detection(BinaryDetection::quit(b'\x00'));
+    if matches!(mode, OutputMode::Content) {
+        searcher
+            .before_context(before as usize)
+            .after_context(after as usize);
+    }
+    let searcher = searcher.build();
+
+    let files = walk(input, search_path, excludes)?;
+    let with_filename = search_path.is_dir();
+    let line_numbers = input.line_numbers.unwrap_or(true);
+    let separate_files = matches!(mode, OutputMode::Content) && before + after > 0;
+
+    tokio::task::spawn_blocking(move || {
+        let mut searcher = searcher;
+        let mut out = Vec::new();
+        for file in files {
+            let shown = file.to_string_lossy().into_owned();
+            let prefix = with_filename.then_some(shown.as_str());
+            // Unreadable files are skipped, as rg does after reporting them.
+            let _ = match mode {
+                OutputMode::FilesWithMatches => {
+                    let mut found = false;
+                    let result = searcher.search_path(
+                        &matcher,
+                        &file,
+                        sinks::Lossy(|_, _| {
+                            found = true;
+                            Ok(false)
+                        }),
+                    );
+                    if found {
+                        out.push(shown.clone());
+                    }
+                    result
+                }
+                OutputMode::Count => {
+                    let mut count = 0u64;
+                    let result = searcher.search_path(
+                        &matcher,
+                        &file,
+                        sinks::Lossy(|_, _| {
+                            count += 1;
+                            Ok(true)
+                        }),
+                    );
+                    if count > 0 {
+                        out.push(match prefix {
+                            Some(path) => format!("{}:{}", path, count),
+                            None => count.to_string(),
+                        });
+                    }
+                    result
+                }
+                OutputMode::Content => {
+                    let mut sink = ContentSink {
+                        lines: Vec::new(),
+                        path: prefix,
+                        line_numbers,
+                    };
+                    let result = searcher.search_path(&matcher, &file, &mut sink);
+                    if !sink.lines.is_empty() {
+                        if separate_files && !out.is_empty() {
+                            out.push("--".to_string());
+                        }
+                        out.append(&mut sink.lines);
+                    }
+                    result
+                }
+            };
+        }
+        out.join("\n")
+    })
+    .await
+    .map_err(|e| format!("Search failed: {}", e))
+}
+
+/// Files under `search_path` that rg would search with the same options.
+fn walk(
+    input: &GrepInput,
+    search_path: &Path,
+    excludes: &[String],
+) -> Result<Vec<PathBuf>, String> {
+    let no_ignore = input.no_ignore.unwrap_or(false);
+    let mut walker = WalkBuilder::new(search_path);
+    walker
+        .ignore(!no_ignore)
+        .git_ignore(!no_ignore)
+        .git_global(!no_ignore)
+        .git_exclude(!no_ignore)
+        .parents(!no_ignore)
+        .require_git(false)
+        .sort_by_file_name(|a, b| a.cmp(b));
+
+    if let Some(file_type) = &input.file_type {
+        let mut types = TypesBuilder::new();
+        types.add_defaults();
+        types.select(file_type);
+        walker.types(types.build().map_err(|e| e.to_string())?);
+    }
+
+    let mut overrides = OverrideBuilder::new(search_path);
+    if let Some(glob) = &input.glob {
+        overrides.add(glob).map_err(|e| e.to_string())?;
+    }
+    if !no_ignore {
+        for exclude in excludes {
+            overrides
+                .add(&format!("!{}", exclude))
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    walker.overrides(overrides.build().map_err(|e| e.to_string())?);
+
+    Ok(walker
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .map(ignore::DirEntry::into_path)
+        .collect())
+}
+
+/// Collects matching and context lines as `path:line:text` / `path-line-text`.
+struct ContentSink<'a> {
+    lines: Vec<String>,
+    path: Option<&'a str>,
+    line_numbers: bool,
+}
+
+impl ContentSink<'_> {
+    fn push(&mut self, line_number: Option<u64>, separator: char, bytes: &[u8]) {
+        let text = String::from_utf8_lossy(bytes);
+        for (i, line) in text.lines().enumerate() {
+            let mut entry = String::new();
+            if let Some(path) = self.path {
+                entry.push_str(path);
+                entry.push(separator);
+            }
+            if self.line_numbers
+                && let Some(n) = line_number
+            {
+                entry.push_str(&(n + i as u64).to_string());
+                entry.push(separator);
+            }
+            entry.push_str(line);
+            self.lines.push(entry);
+        }
+    }
+}
+
+impl Sink for ContentSink<'_> {
+    type Error = io::Error;
+
+    fn matched(&mut self, _: &Searcher, mat: &SinkMatch<'_>) -> Result<bool, io::Error> {
+        self.push(mat.line_number(), ':', mat.bytes());
+        Ok(true)
+    }
+
+    fn context(&mut self, _: &Searcher, context: &SinkContext<'_>) -> Result<bool, io::Error> {
+        self.push(context.line_number(), '-', context.bytes());
+        Ok(true)
+    }
+
+    fn context_break(&mut self, _: &Searcher) -> Result<bool, io::Error> {
+        self.lines.push("--".to_string());
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(value: serde_json::Value) -> GrepInput {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn fixture() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        std::fs::write(root.join("a.rs"), "fn main() {\n    let x = 1;\n    x\n}\n").unwrap();
+        std::fs::write(root.join("b.py"), "def main():\n    pass\n").unwrap();
+        std::fs::write(root.join("bin.dat"), b"main\x00\x01\x02").unwrap();
+        std::fs::create_dir(root.join("target")).unwrap();
+        std::fs::write(root.join("target/gen.rs"), "fn main() {}\n").unwrap();
+        (dir, root)
+    }
+
+    fn excludes() -> Vec<String> {
+        vec!["target".to_string()]
+    }
+
+    #[tokio::test]
+    async fn test_files_with_matches_skips_binary_and_excludes() {
+        let (_dir, root) = fixture();
+        let out = search(
+            &input(serde_json::json!({"pattern": "main"})),
+            &root,
+            &excludes(),
+        )
+        .await
+        .unwrap();
+        let expected = [root.join("a.rs"), root.join("b.py")]
+            .map(|p| p.to_string_lossy().into_owned())
+            .join("\n");
+        assert_eq!(out, expected);
+
+        let out = search(
+            &input(serde_json::json!({"pattern": "main", "type": "rust", "no_ignore": true})),
+            &root,
+            &excludes(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(out.lines().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_content_with_context_and_count() {
+        let (_dir, root) = fixture();
+        let file = root.join("a.rs");
+        let out = search(
+            &input(serde_json::json!({"pattern": "let", "output_mode": "content", "-C": 1})),
+            &file,
+            &[],
+        )
+        .await
+        .unwrap();
+        assert_eq!(out, "1-fn main() {\n2:    let x = 1;\n3-    x");
+
+        let out = search(
+            &input(serde_json::json!({"pattern": "x", "output_mode": "count"})),
+            &file,
+            &[],
+        )
+        .await
+        .unwrap();
+        assert_eq!(out, "2");
+    }
+
+    #[tokio::test]
+    async fn test_multiline() {
+        let (_dir, root) = fixture();
+        let file = root.join("a.rs");
+        let pattern = serde_json::json!({"pattern": r"main[\s\S]*?let", "output_mode": "content"});
+        assert!(
+            search(&input(pattern.clone()), &file, &[])
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let mut multiline = pattern;
+        multiline["multiline"] = serde_json::json!(true);
+        let out = search(&input(multiline), &file, &[]).await.unwrap();
+        assert_eq!(out, "1:fn main() {\n2:    let x = 1;");
+    }
+}
@@ -0,0 +1,394 @@
+//! HttpRequest tool - HTTP calls gated by the network sandbox.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::header::{
+    AUTHORIZATION, COOKIE, HeaderMap, HeaderName, HeaderValue, LOCATION, PROXY_AUTHORIZATION,
+};
+use reqwest::{Method, Url};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::SchemaTool;
+use super::context::ExecutionContext;
+use crate::security::sandbox::DomainCheck;
+use crate::types::ToolResult;
+
+const DEFAULT_MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_REDIRECTS: usize = 5;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct HttpRequestInput {
+    /// The http(s) URL to request
+    pub url: String,
+    /// HTTP method: GET, POST, PUT, PATCH, DELETE or HEAD (default GET)
+    #[serde(default)]
+    pub method: Option<String>,
+    /// Request headers
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Request body, sent as-is
+    #[serde(default)]
+    pub body: Option<String>,
+    /// Optional timeout in milliseconds, capped by the tool's configured timeout
+    #[serde(default)]
+    pub timeout: Option<u64>,
+}
+
+/// Calls HTTP APIs without shelling out to curl.
+///
+/// Every request and every redirect hop must pass the execution context's
+/// [`NetworkSandbox`](crate::security::NetworkSandbox) allowlist. Headers set
+/// with [`header`](Self::header) (e.g. credentials) are added to every request
+/// and never shown to the model.
+#[derive(Debug, Clone)]
+pub struct HttpRequestTool {
+    client: reqwest::Client,
+    headers: HeaderMap,
+    max_response_bytes: usize,
+    timeout: Duration,
+}
+
+impl HttpRequestTool {
+    pub fn new() -> Self {
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_default();
+        Self {
+            client,
+            headers: HeaderMap::new(),
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Response bodies beyond this are truncated (default 1MB).
+    pub fn max_response_bytes(mut self, bytes: usize) -> Self {
+        self.max_response_bytes = bytes;
+        self
+    }
+
+    /// Upper bound for a whole request, including redirects (default 30s).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Header added to every request. Invalid names or values are ignored.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            self.headers.insert(name, value);
+        }
+        self
+    }
+
+    async fn send(
+        &self,
+        input: HttpRequestInput,
+        context: &ExecutionContext,
+    ) -> Result<String, String> {
+        let method = input.method.as_deref().unwrap_or("GET").to_uppercase();
+        let method = match method.as_str() {
+            "GET" | "POST" | "PUT" | "PATCH" | "DELETE" | "HEAD" => {
+                Method::from_bytes(method.as_bytes()).map_err(|e| e.to_string())?
+            }
+            other => return Err(format!("Unsupported HTTP method: {}", other)),
+        };
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in &input.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| format!("Invalid header name: {}", name))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|_| format!("Invalid value for header {}", name))?;
+            headers.insert(name, value);
+        }
+        headers.extend(self.headers.clone());
+
+        let timeout = input
+            .timeout
+            .map(Duration::from_millis)
+            .map_or(self.timeout, |t| t.min(self.timeout));
+
+        let mut url = Url::parse(&input.url).map_err(|e| format!("Invalid URL: {}", e))?;
+        let follows_redirects = matches!(method, Method::GET | Method::HEAD);
+
+        let request = async {
+            let mut hops = 0;
+            loop {
+                check_url(&url, context)?;
+                let mut request = self
+                    .client
+                    .request(method.clone(), url.clone())
+                    .headers(headers.clone());
+                if let Some(body) = &input.body {
+                    request = request.body(body.clone());
+                }
+                let response = request.send().await.map_err(|e| e.to_string())?;
+
+                let location = response
+                    .headers()
+                    .get(LOCATION)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| url.join(v).ok());
+                match location {
+                    Some(next)
+                        if follows_redirects
+                            && response.status().is_redirection()
+                            && hops < MAX_REDIRECTS =>
+                    {
+                        hops += 1;
+                        // Credentials stay with the origin they were meant for.
+                        if next.origin() != url.origin() {
+                            for name in self.headers.keys().chain([
+                                &AUTHORIZATION,
+                                &COOKIE,
+                                &PROXY_AUTHORIZATION,
+                            ]) {
+                                headers.remove(name);
+                            }
+                        }
+                        url = next;
+                    }
+                    _ => return self.format_response(response).await,
+                }
+            }
+        };
+
+        tokio::time::timeout(timeout, request)
+            .await
+            .map_err(|_| format!("Request timed out after {}ms", timeout.as_millis()))?
+    }
+
+    async fn format_response(&self, mut response: reqwest::Response) -> Result<String, String> {
+        let mut output = format!("HTTP {}\n", response.status());
+        for (name, value) in response.headers() {
+            let _ = writeln!(
+                output,
+                "{}: {}",
+                name,
+                value.to_str().unwrap_or("(non-ASCII value)")
+            );
+        }
+
+        let mut body = Vec::new();
+        let mut truncated = false;
+        while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+            let remaining = self.max_response_bytes - body.len();
+            if chunk.len() > remaining {
+                body.extend_from_slice(&chunk[..remaining]);
+                truncated = true;
+                break;
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        output.push('\n');
+        match String::from_utf8(body) {
+            Ok(text) => output.push_str(&text),
+            Err(e) => {
+                let _ = write!(output, "(binary body, {} bytes)", e.as_bytes().len());
+            }
+        }
+        if truncated {
+            let _ = write!(
+                output,
+                "\n... (response truncated at {} bytes)",
+                self.max_response_bytes
+            );
+        }
+        Ok(output)
+    }
+}
+
+/// Reject non-http(s) URLs and hosts the network sandbox does not allow.
+pub(super) fn check_url(url: &Url, context: &ExecutionContext) -> Result<(), String> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("Unsupported URL scheme: {}", url.scheme()));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| format!("URL has no host: {}", url))?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    match context.check_domain(host) {
+        DomainCheck::Allowed => Ok(()),
+        DomainCheck::Blocked => Err(format!(
+            "Domain '{}' is not allowed by the network sandbox",
+            host
+        )),
+    }
+}
+
+impl Default for HttpRequestTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl SchemaTool for HttpRequestTool {
+    type Input = HttpRequestInput;
+
+    const NAME: &'static str = "HttpRequest";
+    const DESCRIPTION: &'static str = r#"Sends an HTTP request and returns the status line, response headers and body.
+
+- Use this tool to call REST APIs instead of running curl or wget with the Bash tool
+- Supports GET, POST, PUT, PATCH, DELETE and HEAD; set `headers` and a raw `body` (e.g. JSON with a Content-Type header) as needed
+- Only domains allowed by the network sandbox can be reached; redirects are followed for GET and HEAD and checked the same way
+- Large response bodies are truncated
+- For reading web pages as text, prefer WebFetch"#;
+
+    async fn handle(&self, input: HttpRequestInput, context: &ExecutionContext) -> ToolResult {
+        match self.send(input, context).await {
+            Ok(output) => ToolResult::success(output),
+            Err(e) => ToolResult::error(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::Tool;
+    use wiremock::matchers::{body_string, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn redirect(location: &str) -> ResponseTemplate {
+        ResponseTemplate::new(302).insert_header("location", location)
+    }
+
+    #[tokio::test]
+    async fn test_post_with_headers() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/items"))
+            .and(header("authorization", "Bearer secret"))
+            .and(body_string(r#"{"name":"x"}"#))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(r#"{"id":7}"#, "application/json"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        let tool = HttpRequestTool::new().header("Authorization", "Bearer secret");
+
+        let result = tool
+            .execute(
+                serde_json::json!({
+                    "url": format!("{}/items", server.uri()),
+                    "method": "post",
+                    "headers": {"Content-Type": "application/json"},
+                    "body": r#"{"name":"x"}"#
+                }),
+                &ExecutionContext::permissive(),
+            )
+            .await;
+
+        let text = result.text();
+        assert!(!result.is_error(), "{}", text);
+        assert!(text.starts_with("HTTP 200 OK"));
+        assert!(text.ends_with(r#"{"id":7}"#));
+    }
+
+    #[tokio::test]
+    async fn test_redirect_and_truncation() {
+        let server = MockServer::start().await;
+        Mock::given(path("/start"))
+            .respond_with(redirect("/final"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/final"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("0123456789"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let tool = HttpRequestTool::new().max_response_bytes(4);
+
+        let result = tool
+            .execute(
+                serde_json::json!({"url": format!("{}/start", server.uri())}),
+                &ExecutionContext::permissive(),
+            )
+            .await;
+
+        let text = result.text();
+        assert!(text.contains("\n\n0123\n... (response truncated at 4 bytes)"));
+    }
+
+    #[tokio::test]
+    async fn test_cross_origin_redirect_drops_credentials() {
+        let target = MockServer::start().await;
+        Mock::given(path("/landing"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("done"))
+            .mount(&target)
+            .await;
+        let server = MockServer::start().await;
+        Mock::given(path("/start"))
+            .and(header("x-api-key", "secret"))
+            .and(header("authorization", "Bearer token"))
+            .respond_with(redirect(&format!("{}/landing", target.uri())))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let tool = HttpRequestTool::new().header("X-Api-Key", "secret");
+
+        let result = tool
+            .execute(
+                serde_json::json!({
+                    "url": format!("{}/start", server.uri()),
+                    "headers": {
+                        "Authorization": "Bearer token",
+                        "Cookie": "session=1",
+                        "Accept": "text/plain"
+                    }
+                }),
+                &ExecutionContext::permissive(),
+            )
+            .await;
+
+        assert!(!result.is_error(), "{}", result.text());
+        let redirected = &target.received_requests().await.unwrap()[0];
+        assert_eq!(redirected.method.as_str(), "GET");
+        assert_eq!(redirected.headers["accept"], "text/plain");
+        for name in ["x-api-key", "authorization", "cookie"] {
+            assert!(!redirected.headers.contains_key(name), "{name} leaked");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sandbox_and_input_validation() {
+        let tool = HttpRequestTool::new();
+        let context = ExecutionContext::from_path(std::env::temp_dir()).unwrap();
+
+        let result = tool
+            .execute(
+                serde_json::json!({"url": "https://example.com/api"}),
+                &context,
+            )
+            .await;
+        assert!(result.text().contains("not allowed by the network sandbox"));
+
+        let result = tool
+            .execute(serde_json::json!({"url": "file:///etc/passwd"}), &context)
+            .await;
+        assert!(result.text().contains("Unsupported URL scheme"));
+
+        let result = tool
+            .execute(
+                serde_json::json!({"url": "http://localhost/", "method": "TRACE"}),
+                &context,
+            )
+            .await;
+        assert!(result.text().contains("Unsupported HTTP method"));
+    }
+}
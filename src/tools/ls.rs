@@ -0,0 +1,337 @@
+//! LS tool - directory listing with sandbox validation.
+
+use std::fmt::Write;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::SchemaTool;
+use super::context::ExecutionContext;
+use crate::types::ToolResult;
+
+const DEFAULT_DEPTH: usize = 1;
+const MAX_DEPTH: usize = 5;
+const MAX_ENTRIES: usize = 1000;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct LsInput {
+    /// The absolute path to the directory to list. Defaults to the working directory.
+    #[serde(default)]
+    pub path: Option<String>,
+    /// How many levels to descend (default 1, max 5). 1 lists only the directory itself.
+    #[serde(default)]
+    pub depth: Option<usize>,
+    /// List of glob patterns for entry names to ignore (e.g. "target", "*.log")
+    #[serde(default)]
+    pub ignore: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EntryKind {
+    Dir,
+    File,
+    Symlink,
+    Other,
+}
+
+impl EntryKind {
+    fn of(metadata: &Metadata) -> Self {
+        let file_type = metadata.file_type();
+        if file_type.is_symlink() {
+            Self::Symlink
+        } else if file_type.is_dir() {
+            Self::Dir
+        } else if file_type.is_file() {
+            Self::File
+        } else {
+            Self::Other
+        }
+    }
+
+    fn as_char(self) -> char {
+        match self {
+            Self::Dir => 'd',
+            Self::File => '-',
+            Self::Symlink => 'l',
+            Self::Other => '?',
+        }
+    }
+}
+
+#[derive(Debug)]
+struct LsEntry {
+    /// Path relative to the listed directory.
+    path: PathBuf,
+    kind: EntryKind,
+    size: u64,
+    modified: Option<DateTime<Utc>>,
+    mode: Option<u32>,
+}
+
+impl LsEntry {
+    fn permissions(&self) -> String {
+        let Some(mode) = self.mode else {
+            return "---------".to_string();
+        };
+        let mut out = String::with_capacity(9);
+        for shift in [6, 3, 0] {
+            let bits = (mode >> shift) & 0o7;
+            out.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+            out.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+            out.push(if bits & 0o1 != 0 { 'x' } else { '-' });
+        }
+        out
+    }
+
+    fn line(&self) -> String {
+        let modified = self
+            .modified
+            .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "-".repeat(16));
+        let size = match self.kind {
+            EntryKind::Dir => "-".to_string(),
+            _ => self.size.to_string(),
+        };
+        let suffix = if self.kind == EntryKind::Dir { "/" } else { "" };
+        format!(
+            "{}{} {:>10} {} {}{}",
+            self.kind.as_char(),
+            self.permissions(),
+            size,
+            modified,
+            self.path.display(),
+            suffix
+        )
+    }
+}
+
+#[cfg(unix)]
+fn mode(metadata: &Metadata) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(metadata.permissions().mode())
+}
+
+#[cfg(not(unix))]
+fn mode(_metadata: &Metadata) -> Option<u32> {
+    None
+}
+
+struct Walk<'a> {
+    root: &'a Path,
+    max_depth: usize,
+    ignore: &'a [glob::Pattern],
+    context: &'a ExecutionContext,
+    entries: Vec<LsEntry>,
+    truncated: bool,
+}
+
+impl Walk<'_> {
+    fn visit(&mut self, dir: &Path, depth: usize) -> std::io::Result<()> {
+        let mut children: Vec<_> = std::fs::read_dir(dir)?.filter_map(Result::ok).collect();
+        children.sort_by_key(|e| e.file_name());
+
+        for child in children {
+            if self.entries.len() >= MAX_ENTRIES {
+                self.truncated = true;
+                return Ok(());
+            }
+            let name = child.file_name();
+            let name = name.to_string_lossy();
+            if self.ignore.iter().any(|p| p.matches(&name)) {
+                continue;
+            }
+            let path = child.path();
+            // Symlinks are listed but never followed.
+            let Ok(metadata) = std::fs::symlink_metadata(&path) else {
+                continue;
+            };
+            let kind = EntryKind::of(&metadata);
+            self.entries.push(LsEntry {
+                path: path.strip_prefix(self.root).unwrap_or(&path).to_path_buf(),
+                kind,
+                size: metadata.len(),
+                modified: metadata.modified().ok().map(DateTime::<Utc>::from),
+                mode: mode(&metadata),
+            });
+            if kind == EntryKind::Dir && depth < self.max_depth && self.context.is_within(&path) {
+                // Unreadable subdirectories are listed without their contents.
+                let _ = self.visit(&path, depth + 1);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LsTool;
+
+#[async_trait]
+impl SchemaTool for LsTool {
+    type Input = LsInput;
+
+    const NAME: &'static str = "LS";
+    const DESCRIPTION: &'static str = r#"Lists files and directories in a given path, like `ls -la`.
+
+- Each entry shows type and permissions, size in bytes, modification time (UTC) and the path relative to the listed directory; directories end with `/`
+- The path parameter must be an absolute path, not a relative path; omit it to list the working directory
+- Use `depth` to descend into subdirectories (default 1, max 5) and `ignore` to skip entries by glob pattern
+- Symlinks are listed but not followed
+- Prefer this tool over running `ls` with the Bash tool. You should generally prefer the Glob and Grep tools if you know which files to search for."#;
+
+    async fn handle(&self, input: LsInput, context: &ExecutionContext) -> ToolResult {
+        let dir = match context.try_resolve_or_root_for(Self::NAME, input.path.as_deref()) {
+            Ok(path) => path,
+            Err(e) => return e,
+        };
+        if !dir.is_dir() {
+            return ToolResult::error(format!("Not a directory: {}", dir.display()));
+        }
+
+        let ignore = match input
+            .ignore
+            .iter()
+            .map(|p| glob::Pattern::new(p))
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(patterns) => patterns,
+            Err(e) => return ToolResult::error(format!("Invalid ignore pattern: {}", e)),
+        };
+        let max_depth = input.depth.unwrap_or(DEFAULT_DEPTH).clamp(1, MAX_DEPTH);
+
+        let context = context.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let mut walk = Walk {
+                root: &dir,
+                max_depth,
+                ignore: &ignore,
+                context: &context,
+                entries: Vec::new(),
+                truncated: false,
+            };
+            walk.visit(&dir, 1)
+                .map(|()| (dir.clone(), walk.entries, walk.truncated))
+        })
+        .await;
+
+        let (dir, entries, truncated) = match result {
+            Ok(Ok(listing)) => listing,
+            Ok(Err(e)) => return ToolResult::error(format!("Failed to list directory: {}", e)),
+            Err(e) => return ToolResult::error(format!("LS task failed: {}", e)),
+        };
+
+        let mut output = format!("{}/\n", dir.display());
+        if entries.is_empty() {
+            output.push_str("(empty directory)");
+        }
+        for entry in &entries {
+            let _ = writeln!(output, "{}", entry.line());
+        }
+        if truncated {
+            let _ = write!(
+                output,
+                "... (listing truncated at {} entries; narrow the path or lower depth)",
+                MAX_ENTRIES
+            );
+        }
+        ToolResult::success(output.trim_end().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::Tool;
+    use tempfile::tempdir;
+
+    fn setup() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempdir().unwrap();
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        std::fs::create_dir_all(root.join("src/nested")).unwrap();
+        std::fs::create_dir_all(root.join("target")).unwrap();
+        std::fs::write(root.join("Cargo.toml"), "[package]").unwrap();
+        std::fs::write(root.join("src/lib.rs"), "pub fn f() {}").unwrap();
+        std::fs::write(root.join("src/nested/deep.rs"), "").unwrap();
+        (dir, root)
+    }
+
+    #[tokio::test]
+    async fn test_ls_entries() {
+        let (_dir, root) = setup();
+        let context = ExecutionContext::from_path(&root).unwrap();
+
+        let result = LsTool.execute(serde_json::json!({}), &context).await;
+        let text = result.text();
+        assert!(!result.is_error(), "{}", text);
+        let cargo = text.lines().find(|l| l.ends_with("Cargo.toml")).unwrap();
+        assert!(cargo.starts_with('-'));
+        assert!(cargo.contains(" 9 "));
+        assert!(
+            text.lines()
+                .any(|l| l.starts_with('d') && l.ends_with("src/"))
+        );
+        assert!(!text.contains("lib.rs"));
+    }
+
+    #[tokio::test]
+    async fn test_ls_depth_and_ignore() {
+        let (_dir, root) = setup();
+        let context = ExecutionContext::from_path(&root).unwrap();
+
+        let result = LsTool
+            .execute(
+                serde_json::json!({"depth": 2, "ignore": ["target"]}),
+                &context,
+            )
+            .await;
+        let text = result.text();
+        assert!(text.contains("src/lib.rs"));
+        assert!(text.contains("src/nested/"));
+        assert!(!text.contains("deep.rs"));
+        assert!(!text.contains("target"));
+
+        let result = LsTool
+            .execute(
+                serde_json::json!({"path": root.join("src").to_str().unwrap(), "depth": 10}),
+                &context,
+            )
+            .await;
+        assert!(result.text().contains("nested/deep.rs"));
+    }
+
+    #[tokio::test]
+    async fn test_ls_outside_root_blocked() {
+        let (_dir, root) = setup();
+        let context = ExecutionContext::from_path(&root).unwrap();
+
+        let result = LsTool
+            .execute(serde_json::json!({"path": "/etc"}), &context)
+            .await;
+        assert!(result.is_error());
+
+        let result = LsTool
+            .execute(
+                serde_json::json!({"path": root.join("Cargo.toml").to_str().unwrap()}),
+                &context,
+            )
+            .await;
+        assert!(result.text().contains("Not a directory"));
+    }
+
+    #[test]
+    fn test_permissions_string() {
+        let entry = LsEntry {
+            path: PathBuf::from("run.sh"),
+            kind: EntryKind::File,
+            size: 12,
+            modified: None,
+            mode: Some(0o100754),
+        };
+        assert_eq!(entry.permissions(), "rwxr-xr--");
+        assert!(entry.line().starts_with("-rwxr-xr--         12"));
+    }
+}
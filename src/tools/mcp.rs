@@ -1,12 +1,16 @@
 //! MCP tool wrapper for seamless integration with ToolRegistry.
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use serde_json::Value;
 
 use super::{ExecutionContext, Tool};
-use crate::mcp::{McpManager, McpToolDefinition, make_mcp_name, parse_mcp_name};
+use crate::mcp::{
+    MCP_MAX_CALL_DURATION, McpManager, McpToolDefinition, McpToolsetRegistry, make_mcp_name,
+    parse_mcp_name,
+};
 use crate::types::{ToolDefinition, ToolResult};
 
 /// Wrapper that adapts an MCP tool to the Tool trait.
@@ -15,6 +19,8 @@ use crate::types::{ToolDefinition, ToolResult};
 /// through the standard tool execution pipeline.
 pub struct McpToolWrapper {
     manager: Arc<McpManager>,
+    /// Name the tool is registered and permission-checked under.
+    name: String,
     qualified_name: String,
     server_name: String,
     tool_name: String,
@@ -31,6 +37,7 @@ impl McpToolWrapper {
         let qualified_name = make_mcp_name(&server_name, &tool.name);
         Self {
             manager,
+            name: qualified_name.clone(),
             qualified_name,
             server_name,
             tool_name: tool.name.clone(),
@@ -38,6 +45,12 @@ impl McpToolWrapper {
         }
     }
 
+    /// Register the tool under `name` instead of its qualified name.
+    pub fn exposed_as(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
     pub fn qualified_name(&self) -> &str {
         &self.qualified_name
     }
@@ -54,7 +67,7 @@ impl McpToolWrapper {
 #[async_trait]
 impl Tool for McpToolWrapper {
     fn name(&self) -> &str {
-        &self.qualified_name
+        &self.name
     }
 
     fn description(&self) -> &str {
@@ -67,12 +80,18 @@ impl Tool for McpToolWrapper {
 
     fn definition(&self) -> ToolDefinition {
         ToolDefinition::new(
-            &self.qualified_name,
+            &self.name,
             &self.definition.description,
             self.definition.input_schema.clone(),
         )
     }
 
+    /// The client enforces the MCP call timeout itself, extending it while the
+    /// server reports progress, so the registry only caps the total duration.
+    fn default_timeout(&self) -> Option<Duration> {
+        Some(MCP_MAX_CALL_DURATION)
+    }
+
     async fn execute(&self, input: Value, _context: &ExecutionContext) -> ToolResult {
         match self.manager.call_tool(&self.qualified_name, input).await {
             Ok(result) => {
@@ -89,12 +108,22 @@ impl Tool for McpToolWrapper {
 
 /// Creates MCP tool wrappers for all tools from an McpManager.
 pub async fn create_mcp_tools(manager: Arc<McpManager>) -> Vec<Arc<dyn Tool>> {
-    let tools_list = manager.list_tools().await;
+    create_filtered_mcp_tools(manager, &McpToolsetRegistry::new()).await
+}
+
+/// Creates MCP tool wrappers for the tools each server's toolset filter keeps,
+/// under their exposed names.
+pub async fn create_filtered_mcp_tools(
+    manager: Arc<McpManager>,
+    toolsets: &McpToolsetRegistry,
+) -> Vec<Arc<dyn Tool>> {
+    let tools_list = toolsets.filter_tools(manager.list_tools().await);
     let mut wrappers: Vec<Arc<dyn Tool>> = Vec::with_capacity(tools_list.len());
 
-    for (qualified_name, definition) in tools_list {
-        if let Some((server, _tool)) = parse_mcp_name(&qualified_name) {
-            let wrapper = McpToolWrapper::new(Arc::clone(&manager), server, definition);
+    for (exposed_name, definition) in tools_list {
+        if let Some((server, _tool)) = parse_mcp_name(&exposed_name) {
+            let wrapper = McpToolWrapper::new(Arc::clone(&manager), server, definition)
+                .exposed_as(exposed_name.as_str());
             wrappers.push(Arc::new(wrapper));
         }
     }
@@ -0,0 +1,461 @@
+//! Memory tool - Anthropic's `memory` commands executed against a [`MemoryProvider`].
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::Tool;
+use super::context::ExecutionContext;
+use crate::client::messages::ApiTool;
+use crate::context::{ContextResult, MemoryProvider};
+use crate::types::{MemoryToolDefinition, ToolOutput, ToolResult};
+
+/// Directory the model addresses its memory files under.
+pub const MEMORY_ROOT: &str = "/memories";
+/// Directory listings from `view` descend this many levels.
+const VIEW_DEPTH: usize = 2;
+
+/// A command issued by the model, as defined by `memory_20250818`.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum MemoryCommand {
+    View {
+        path: String,
+        /// `[start, end]` line numbers, 1-based and inclusive; `end` of -1 reads to EOF.
+        #[serde(default)]
+        view_range: Option<[i64; 2]>,
+    },
+    Create {
+        path: String,
+        file_text: String,
+    },
+    StrReplace {
+        path: String,
+        old_str: String,
+        #[serde(default)]
+        new_str: String,
+    },
+    Insert {
+        path: String,
+        /// Line after which to insert; 0 inserts at the beginning.
+        insert_line: usize,
+        insert_text: String,
+    },
+    Delete {
+        path: String,
+    },
+    Rename {
+        old_path: String,
+        new_path: String,
+    },
+}
+
+/// Client-executed Anthropic memory tool.
+///
+/// The model reads and writes files under `/memories`, which map onto the
+/// [`MemoryProvider`]; paths outside it are rejected, and in read-only mode
+/// only `view` runs. Requests carrying the tool are sent with the
+/// context-management beta flag, and tool-use clearing leaves its results in
+/// place.
+pub struct MemoryTool {
+    provider: Arc<dyn MemoryProvider>,
+}
+
+impl MemoryTool {
+    pub fn new(provider: impl MemoryProvider + 'static) -> Self {
+        Self::from_arc(Arc::new(provider))
+    }
+
+    pub fn from_arc(provider: Arc<dyn MemoryProvider>) -> Self {
+        Self { provider }
+    }
+
+    pub fn provider(&self) -> &Arc<dyn MemoryProvider> {
+        &self.provider
+    }
+
+    async fn view(&self, path: &str, view_range: Option<[i64; 2]>) -> ContextResult<ToolResult> {
+        let relative = match store_path(path) {
+            Ok(relative) => relative,
+            Err(e) => return Ok(e),
+        };
+
+        if self.provider.is_dir(&relative).await? {
+            if view_range.is_some() {
+                return Ok(ToolResult::error(
+                    "view_range is not allowed when path is a directory",
+                ));
+            }
+            let base_depth = depth(&relative);
+            let mut lines = vec![format!(
+                "Here're the files and directories up to {} levels deep in {}, excluding hidden items:",
+                VIEW_DEPTH,
+                display_path(&relative)
+            )];
+            for entry in self.provider.list(&relative).await? {
+                let hidden = entry
+                    .path
+                    .split('/')
+                    .skip(base_depth)
+                    .any(|part| part.starts_with('.'));
+                if hidden || depth(&entry.path) - base_depth > VIEW_DEPTH {
+                    continue;
+                }
+                lines.push(format!("{}\t{}", entry.size, display_path(&entry.path)));
+            }
+            return Ok(ToolResult::success(lines.join("\n")));
+        }
+
+        let Some(content) = self.provider.read(&relative).await? else {
+            return Ok(missing(path));
+        };
+        let total = content.lines().count();
+        let (start, end) = match view_range {
+            None => (1, total),
+            Some([start, end]) if start >= 1 && (end == -1 || end >= start) => {
+                let end = if end == -1 { total } else { end as usize };
+                (start as usize, end.min(total))
+            }
+            Some([start, end]) => {
+                return Ok(ToolResult::error(format!(
+                    "Invalid view_range [{}, {}]: start must be >= 1 and end >= start or -1",
+                    start, end
+                )));
+            }
+        };
+        let numbered: Vec<String> = content
+            .lines()
+            .enumerate()
+            .skip(start - 1)
+            .take(end.saturating_sub(start - 1))
+            .map(|(i, line)| format!("{:>6}\t{}", i + 1, line))
+            .collect();
+        Ok(ToolResult::success(numbered.join("\n")))
+    }
+
+    async fn run(&self, command: MemoryCommand) -> ContextResult<ToolResult> {
+        match command {
+            MemoryCommand::View { path, view_range } => self.view(&path, view_range).await,
+            MemoryCommand::Create { path, file_text } => {
+                let relative = match file_path(&path) {
+                    Ok(relative) => relative,
+                    Err(e) => return Ok(e),
+                };
+                if self.provider.is_dir(&relative).await? {
+                    return Ok(ToolResult::error(format!("{} is a directory", path)));
+                }
+                self.provider.write(&relative, &file_text).await?;
+                Ok(ToolResult::success(format!(
+                    "File created successfully at {}",
+                    path
+                )))
+            }
+            MemoryCommand::StrReplace {
+                path,
+                old_str,
+                new_str,
+            } => {
+                let relative = match file_path(&path) {
+                    Ok(relative) => relative,
+                    Err(e) => return Ok(e),
+                };
+                let Some(content) = self.provider.read(&relative).await? else {
+                    return Ok(missing(&path));
+                };
+                match super::edit::replace(&content, &old_str, &new_str, false) {
+                    Ok((updated, _)) => {
+                        self.provider.write(&relative, &updated).await?;
+                        Ok(ToolResult::success(format!("Edited {}", path)))
+                    }
+                    Err(e) => Ok(ToolResult::error(e)),
+                }
+            }
+            MemoryCommand::Insert {
+                path,
+                insert_line,
+                insert_text,
+            } => {
+                let relative = match file_path(&path) {
+                    Ok(relative) => relative,
+                    Err(e) => return Ok(e),
+                };
+                let Some(content) = self.provider.read(&relative).await? else {
+                    return Ok(missing(&path));
+                };
+                let mut lines: Vec<&str> = content.lines().collect();
+                if insert_line > lines.len() {
+                    return Ok(ToolResult::error(format!(
+                        "insert_line {} is beyond the end of the file ({} lines)",
+                        insert_line,
+                        lines.len()
+                    )));
+                }
+                lines.splice(insert_line..insert_line, insert_text.lines());
+                let mut updated = lines.join("\n");
+                if content.ends_with('\n') || content.is_empty() {
+                    updated.push('\n');
+                }
+                self.provider.write(&relative, &updated).await?;
+                Ok(ToolResult::success(format!(
+                    "Inserted text after line {} in {}",
+                    insert_line, path
+                )))
+            }
+            MemoryCommand::Delete { path } => {
+                let relative = match file_path(&path) {
+                    Ok(relative) => relative,
+                    Err(e) => return Ok(e),
+                };
+                if self.provider.delete(&relative).await? {
+                    Ok(ToolResult::success(format!("Deleted {}", path)))
+                } else {
+                    Ok(missing(&path))
+                }
+            }
+            MemoryCommand::Rename { old_path, new_path } => {
+                let (from, to) = match (file_path(&old_path), file_path(&new_path)) {
+                    (Ok(from), Ok(to)) => (from, to),
+                    (Err(e), _) | (_, Err(e)) => return Ok(e),
+                };
+                self.provider.rename(&from, &to).await?;
+                Ok(ToolResult::success(format!(
+                    "Renamed {} to {}",
+                    old_path, new_path
+                )))
+            }
+        }
+    }
+}
+
+/// Map a `/memories/...` path onto a store path, rejecting anything that
+/// would leave the memory directory.
+fn store_path(path: &str) -> Result<String, ToolResult> {
+    let invalid = || {
+        ToolOutput::invalid_input(format!("Path must be inside {}: {}", MEMORY_ROOT, path)).into()
+    };
+    let Some(rest) = path.strip_prefix(MEMORY_ROOT) else {
+        return Err(invalid());
+    };
+    if !rest.is_empty() && !rest.starts_with('/') {
+        return Err(invalid());
+    }
+    let parts: Vec<&str> = rest.split('/').filter(|p| !p.is_empty()).collect();
+    if parts
+        .iter()
+        .any(|p| *p == "." || *p == ".." || p.contains('\\'))
+    {
+        return Err(invalid());
+    }
+    Ok(parts.join("/"))
+}
+
+/// Like [`store_path`], but the memory directory itself is not a valid target.
+fn file_path(path: &str) -> Result<String, ToolResult> {
+    let relative = store_path(path)?;
+    if relative.is_empty() {
+        return Err(ToolOutput::invalid_input(format!(
+            "{} itself cannot be modified",
+            MEMORY_ROOT
+        ))
+        .into());
+    }
+    Ok(relative)
+}
+
+fn display_path(relative: &str) -> String {
+    if relative.is_empty() {
+        MEMORY_ROOT.to_string()
+    } else {
+        format!("{}/{}", MEMORY_ROOT, relative)
+    }
+}
+
+fn depth(relative: &str) -> usize {
+    if relative.is_empty() {
+        0
+    } else {
+        relative.split('/').count()
+    }
+}
+
+fn missing(path: &str) -> ToolResult {
+    ToolResult::error(format!("The path {} does not exist", path))
+}
+
+#[async_trait]
+impl Tool for MemoryTool {
+    fn name(&self) -> &str {
+        MemoryToolDefinition::NAME
+    }
+
+    fn description(&self) -> &str {
+        "Store and retrieve information across conversations in the /memories directory."
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        serde_json::to_value(schemars::schema_for!(MemoryCommand))
+            .unwrap_or_else(|_| serde_json::json!({"type": "object"}))
+    }
+
+    fn api_tool(&self) -> ApiTool {
+        ApiTool::Memory(MemoryToolDefinition::default())
+    }
+
+    async fn execute(&self, input: serde_json::Value, context: &ExecutionContext) -> ToolResult {
+        let command: MemoryCommand = match serde_json::from_value(input) {
+            Ok(command) => command,
+            Err(e) => return ToolResult::error(format!("Invalid input: {}", e)),
+        };
+        if context.is_read_only() && !matches!(command, MemoryCommand::View { .. }) {
+            return ToolResult::error("Memory cannot be modified in read-only mode");
+        }
+        match self.run(command).await {
+            Ok(result) => result,
+            Err(e) => ToolResult::error(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::MemoryContextProvider;
+
+    async fn run(tool: &MemoryTool, input: serde_json::Value) -> ToolResult {
+        tool.execute(input, &ExecutionContext::permissive()).await
+    }
+
+    #[test]
+    fn test_store_path() {
+        assert_eq!(store_path("/memories").unwrap(), "");
+        assert_eq!(store_path("/memories/").unwrap(), "");
+        assert_eq!(store_path("/memories/a/b.md").unwrap(), "a/b.md");
+        assert!(store_path("/memoriesx/a.md").is_err());
+        assert!(store_path("/etc/passwd").is_err());
+        assert!(store_path("/memories/../etc/passwd").is_err());
+        assert!(file_path("/memories").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_memory_commands() {
+        let tool = MemoryTool::new(MemoryContextProvider::new());
+
+        let result = run(
+            &tool,
+            serde_json::json!({"command": "create", "path": "/memories/notes/todo.md", "file_text": "- a\n- c\n"}),
+        )
+        .await;
+        assert!(!result.is_error(), "{}", result.text());
+
+        let result = run(
+            &tool,
+            serde_json::json!({"command": "insert", "path": "/memories/notes/todo.md", "insert_line": 1, "insert_text": "- b"}),
+        )
+        .await;
+        assert!(!result.is_error(), "{}", result.text());
+
+        let result = run(
+            &tool,
+            serde_json::json!({"command": "str_replace", "path": "/memories/notes/todo.md", "old_str": "- c", "new_str": "- d"}),
+        )
+        .await;
+        assert!(!result.is_error(), "{}", result.text());
+
+        let result = run(
+            &tool,
+            serde_json::json!({"command": "view", "path": "/memories/notes/todo.md", "view_range": [2, -1]}),
+        )
+        .await;
+        assert_eq!(result.text(), "     2\t- b\n     3\t- d");
+
+        let result = run(
+            &tool,
+            serde_json::json!({"command": "view", "path": "/memories"}),
+        )
+        .await;
+        assert!(result.text().contains("\t/memories/notes\n"));
+        assert!(result.text().contains("\t/memories/notes/todo.md"));
+
+        let result = run(
+            &tool,
+            serde_json::json!({"command": "rename", "old_path": "/memories/notes", "new_path": "/memories/archive"}),
+        )
+        .await;
+        assert!(!result.is_error(), "{}", result.text());
+
+        let result = run(
+            &tool,
+            serde_json::json!({"command": "delete", "path": "/memories/archive/todo.md"}),
+        )
+        .await;
+        assert!(!result.is_error(), "{}", result.text());
+
+        let result = run(
+            &tool,
+            serde_json::json!({"command": "view", "path": "/memories/archive/todo.md"}),
+        )
+        .await;
+        assert!(result.is_error());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_paths_outside_memories() {
+        let provider = Arc::new(MemoryContextProvider::new());
+        let tool = MemoryTool::from_arc(provider.clone());
+
+        let result = run(
+            &tool,
+            serde_json::json!({"command": "create", "path": "/memories/../x.md", "file_text": "x"}),
+        )
+        .await;
+        assert!(result.is_error());
+        assert!(provider.list("").await.unwrap().is_empty());
+
+        let result = run(
+            &tool,
+            serde_json::json!({"command": "delete", "path": "/memories"}),
+        )
+        .await;
+        assert!(result.is_error());
+    }
+
+    #[tokio::test]
+    async fn test_read_only() {
+        let provider = Arc::new(MemoryContextProvider::new());
+        provider.write("notes.md", "- a").await.unwrap();
+        let tool = MemoryTool::from_arc(provider.clone());
+        let security = crate::security::SecurityContext::new(std::env::temp_dir())
+            .unwrap()
+            .into_read_only();
+        let context = ExecutionContext::new(security);
+
+        let result = tool
+            .execute(
+                serde_json::json!({"command": "view", "path": "/memories/notes.md"}),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error(), "{}", result.text());
+
+        let result = tool
+            .execute(
+                serde_json::json!({"command": "delete", "path": "/memories/notes.md"}),
+                &context,
+            )
+            .await;
+        assert!(result.is_error());
+        assert!(provider.read("notes.md").await.unwrap().is_some());
+    }
+
+    #[test]
+    fn test_api_tool() {
+        let tool = MemoryTool::new(MemoryContextProvider::new());
+        assert!(matches!(tool.api_tool(), ApiTool::Memory(_)));
+        assert_eq!(
+            tool.api_tool().beta_feature(),
+            Some(crate::client::BetaFeature::ContextManagement)
+        );
+    }
+}
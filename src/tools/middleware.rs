@@ -0,0 +1,33 @@
+//! Middleware wrapping every tool execution in a [`ToolRegistry`](super::ToolRegistry).
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::types::ToolResult;
+
+/// Cross-cutting behavior around tool calls: argument validation, output
+/// rewriting, metrics.
+///
+/// Middleware runs for every tool in the registry. `before` hooks run in
+/// registration order ahead of the permission and security checks, so any
+/// rewritten input is what gets checked; `after` hooks run in reverse order on
+/// the final result, including denials and timeouts.
+#[async_trait]
+pub trait ToolMiddleware: Send + Sync {
+    /// Called before the tool runs. Mutate `input` to rewrite the arguments, or
+    /// return a result to skip the tool (and the remaining `before` hooks).
+    async fn before(&self, _name: &str, _input: &mut serde_json::Value) -> Option<ToolResult> {
+        None
+    }
+
+    /// Called with the result and the time spent since the first `before` hook.
+    async fn after(
+        &self,
+        _name: &str,
+        _input: &serde_json::Value,
+        _result: &mut ToolResult,
+        _duration: Duration,
+    ) {
+    }
+}
@@ -1,45 +1,105 @@
 //! Built-in tools for the agent.
+//!
+//! [`ToolRegistry::default_tools`] registers the core file, search, shell and
+//! task tools. The others are opt-in: construct them and register them with
+//! [`AgentBuilder::tool`](crate::agent::AgentBuilder::tool). These are
+//! [`HttpRequestTool`], [`ComputerTool`], [`MemoryTool`] and
+//! [`TextEditorTool`], plus `SqlTool` (`sqlite` or `postgres` feature),
+//! `AstEditTool` (`ast-edit`), `BrowserFetchTool` (`browser`) and
+//! `HttpFetchTool` (`web-fetch`).
 
 mod access;
+#[cfg(feature = "ast-edit")]
+mod ast_edit;
+mod backup;
 mod bash;
+mod bash_output;
+#[cfg(feature = "browser")]
+mod browser;
 mod builder;
+mod cache;
+mod computer;
 mod context;
-mod edit;
+pub(crate) mod edit;
 mod env;
 mod glob;
 mod grep;
+#[cfg(feature = "grep-engine")]
+mod grep_engine;
+mod http;
 mod kill;
+mod ls;
 pub mod mcp;
+mod memory;
+mod middleware;
+pub(crate) mod multi_edit;
 mod plan;
 mod process;
+mod quota;
 mod read;
 mod registry;
 pub mod search;
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+mod sql;
 #[cfg(test)]
 mod testing;
+mod text_editor;
 mod todo;
 mod traits;
+mod walk;
+#[cfg(feature = "web-fetch")]
+mod web_fetch;
 mod write;
 
-pub use crate::common::{is_tool_allowed, matches_tool_pattern};
+pub use crate::common::{
+    is_tool_allowed, is_tool_use_allowed, matches_tool_pattern, matches_tool_use,
+};
 pub use access::ToolAccess;
+#[cfg(feature = "ast-edit")]
+pub use ast_edit::{AstEditTool, AstOperation};
+pub use backup::WriteBackups;
 pub use bash::BashTool;
+pub use bash_output::BashOutputTool;
+#[cfg(feature = "browser")]
+pub use browser::BrowserFetchTool;
 pub use builder::ToolRegistryBuilder;
+pub use cache::{ToolCacheConfig, ToolResultCache};
+pub use computer::{
+    ComputerAction, ComputerBackend, ComputerOutput, ComputerTool, ScrollDirection,
+};
 pub use context::ExecutionContext;
 pub use edit::EditTool;
 pub use env::ToolExecutionEnv;
 pub use glob::GlobTool;
 pub use grep::GrepTool;
+pub use http::HttpRequestTool;
 pub use kill::KillShellTool;
-pub use mcp::{McpToolWrapper, create_mcp_tools};
+pub use ls::LsTool;
+pub use mcp::{McpToolWrapper, create_filtered_mcp_tools, create_mcp_tools};
+pub use memory::{MEMORY_ROOT, MemoryCommand, MemoryTool};
+pub use middleware::ToolMiddleware;
+pub use multi_edit::MultiEditTool;
 pub use plan::PlanTool;
-pub use process::{ProcessId, ProcessInfo, ProcessManager};
+pub use process::{ProcessId, ProcessInfo, ProcessManager, ProcessOutput, ProcessStatus};
+pub use quota::{ToolQuotas, ToolUsageTracker};
 pub use read::ReadTool;
 pub use registry::ToolRegistry;
-pub use search::{PreparedTools, SearchMode, ToolSearchConfig, ToolSearchManager};
+pub use search::{
+    Embedder, HashEmbedder, HttpEmbedder, PreparedTools, SearchMode, ToolSearchConfig,
+    ToolSearchManager,
+};
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+pub use sql::{SqlFormat, SqlTool};
+pub use text_editor::{TextEditorCommand, TextEditorTool};
 pub use todo::TodoWriteTool;
 pub use traits::{SchemaTool, Tool};
+pub use walk::DEFAULT_SEARCH_EXCLUDES;
+#[cfg(feature = "web-fetch")]
+pub use web_fetch::{HttpFetchTool, MarkdownOptions};
 pub use write::WriteTool;
 
 pub use crate::security::sandbox::{DomainCheck, NetworkSandbox};
-pub use crate::types::{ToolOutput, ToolResult, ToolSearchTool, WebFetchTool, WebSearchTool};
+pub use crate::types::{
+    ComputerUseTool, MemoryToolDefinition, TextEditorDefinition, TextEditorVersion, ToolOutput,
+    ToolResult, ToolSearchTool, WebFetchTool, WebSearchTool,
+};
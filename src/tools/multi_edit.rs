@@ -0,0 +1,224 @@
+//! MultiEdit tool - applies several string replacements to one file atomically.
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::SchemaTool;
+use super::context::ExecutionContext;
+use super::edit::replace;
+use crate::security::fs::SecureFileHandle;
+use crate::types::ToolResult;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct MultiEditInput {
+    /// The absolute path to the file to modify
+    pub file_path: String,
+    /// Array of edit operations to perform sequentially on the file
+    pub edits: Vec<EditOperation>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct EditOperation {
+    /// The text to replace
+    pub old_string: String,
+    /// The text to replace it with
+    pub new_string: String,
+    /// Replace all occurences of old_string (default false)
+    #[serde(default)]
+    pub replace_all: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MultiEditTool;
+
+/// Apply `edits` in order, each to the result of the previous one.
+pub(crate) fn apply_edits(content: &str, edits: &[EditOperation]) -> Result<String, String> {
+    let mut content = content.to_string();
+    for (index, edit) in edits.iter().enumerate() {
+        if edit.old_string == edit.new_string {
+            return Err(format!(
+                "Edit {}: old_string and new_string must be different",
+                index + 1
+            ));
+        }
+        content = replace(
+            &content,
+            &edit.old_string,
+            &edit.new_string,
+            edit.replace_all,
+        )
+        .map_err(|e| format!("Edit {}: {}", index + 1, e))?
+        .0;
+    }
+    Ok(content)
+}
+
+#[async_trait]
+impl SchemaTool for MultiEditTool {
+    type Input = MultiEditInput;
+
+    const NAME: &'static str = "MultiEdit";
+    const DESCRIPTION: &'static str = r#"Makes multiple edits to a single file in one operation. Prefer it over the Edit tool when you need several changes to the same file.
+
+Usage:
+- You must use your `Read` tool at least once in the conversation before editing.
+- Each edit has the same parameters as the Edit tool: `old_string`, `new_string` and optional `replace_all`.
+- Edits are applied in sequence, in the order provided; each one operates on the result of the previous edit, so plan them so earlier edits do not change text that later edits need to find.
+- All edits are validated before anything is written. If any edit fails (not found, not unique, or no change), none are applied and the file is left untouched.
+- Preserve the exact indentation (tabs/spaces) as it appears AFTER the line number prefix of Read tool output.
+- Use `replace_all` for replacing and renaming strings across the file."#;
+
+    async fn handle(&self, input: MultiEditInput, context: &ExecutionContext) -> ToolResult {
+        if input.edits.is_empty() {
+            return ToolResult::error("edits must contain at least one edit");
+        }
+
+        let path = match context.try_resolve_for(Self::NAME, &input.file_path) {
+            Ok(p) => p,
+            Err(e) => return e,
+        };
+
+        let edits = input.edits;
+        let edit_count = edits.len();
+        let display_path = path.as_path().display().to_string();
+
+        let result = tokio::task::spawn_blocking(move || {
+            let handle = SecureFileHandle::open_read(path.clone()).map_err(|e| e.to_string())?;
+            let original_content = handle.read_to_string().map_err(|e| e.to_string())?;
+
+            let new_content = apply_edits(&original_content, &edits)?;
+
+            let recheck_handle =
+                SecureFileHandle::open_read(path.clone()).map_err(|e| e.to_string())?;
+            let current_content = recheck_handle.read_to_string().map_err(|e| e.to_string())?;
+            if current_content != original_content {
+                return Err("File was modified externally; operation aborted".to_string());
+            }
+
+            let write_handle = SecureFileHandle::open_write(path).map_err(|e| e.to_string())?;
+            write_handle
+                .atomic_write(new_content.as_bytes())
+                .map_err(|e| e.to_string())
+        })
+        .await;
+
+        match result {
+            Ok(Ok(())) => ToolResult::success(format!(
+                "Applied {} edit{} to {}",
+                edit_count,
+                if edit_count == 1 { "" } else { "s" },
+                display_path
+            )),
+            Ok(Err(e)) => ToolResult::error(e),
+            Err(e) => ToolResult::error(format!("Task failed: {}", e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::Tool;
+    use tempfile::tempdir;
+    use tokio::fs;
+
+    #[tokio::test]
+    async fn test_multi_edit_sequential() {
+        let dir = tempdir().unwrap();
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        let file_path = root.join("lib.rs");
+        fs::write(&file_path, "fn old() {}\nfn main() { old(); old(); }\n")
+            .await
+            .unwrap();
+
+        let test_context = ExecutionContext::from_path(&root).unwrap();
+        let result = MultiEditTool
+            .execute(
+                serde_json::json!({
+                    "file_path": file_path.to_str().unwrap(),
+                    "edits": [
+                        {"old_string": "old", "new_string": "renamed", "replace_all": true},
+                        {"old_string": "fn renamed() {}", "new_string": "fn renamed() { todo!() }"}
+                    ]
+                }),
+                &test_context,
+            )
+            .await;
+
+        assert!(!result.is_error(), "{:?}", result);
+        let content = fs::read_to_string(&file_path).await.unwrap();
+        assert_eq!(
+            content,
+            "fn renamed() { todo!() }\nfn main() { renamed(); renamed(); }\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_multi_edit_all_or_nothing() {
+        let dir = tempdir().unwrap();
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        let file_path = root.join("test.txt");
+        fs::write(&file_path, "alpha beta beta").await.unwrap();
+
+        let test_context = ExecutionContext::from_path(&root).unwrap();
+        let result = MultiEditTool
+            .execute(
+                serde_json::json!({
+                    "file_path": file_path.to_str().unwrap(),
+                    "edits": [
+                        {"old_string": "alpha", "new_string": "gamma"},
+                        {"old_string": "beta", "new_string": "delta"}
+                    ]
+                }),
+                &test_context,
+            )
+            .await;
+
+        assert!(result.is_error());
+        assert!(result.text().contains("Edit 2"));
+        let content = fs::read_to_string(&file_path).await.unwrap();
+        assert_eq!(content, "alpha beta beta");
+    }
+
+    #[tokio::test]
+    async fn test_multi_edit_empty_and_escape_blocked() {
+        let dir = tempdir().unwrap();
+        let test_context = ExecutionContext::from_path(dir.path()).unwrap();
+
+        let result = MultiEditTool
+            .execute(
+                serde_json::json!({"file_path": "/etc/passwd", "edits": []}),
+                &test_context,
+            )
+            .await;
+        assert!(result.is_error());
+
+        let result = MultiEditTool
+            .execute(
+                serde_json::json!({
+                    "file_path": "/etc/passwd",
+                    "edits": [{"old_string": "root", "new_string": "evil"}]
+                }),
+                &test_context,
+            )
+            .await;
+        assert!(result.is_error());
+    }
+
+    #[test]
+    fn test_apply_edits_rejects_noop() {
+        let edits = vec![EditOperation {
+            old_string: "same".into(),
+            new_string: "same".into(),
+            replace_all: false,
+        }];
+        assert!(
+            apply_edits("same", &edits)
+                .unwrap_err()
+                .starts_with("Edit 1")
+        );
+    }
+}
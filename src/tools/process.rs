@@ -2,14 +2,16 @@
 
 use std::collections::HashMap;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex as StdMutex};
 use std::time::Instant;
 
-use tokio::io::AsyncReadExt;
+use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio::process::{Child, Command};
 use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
 
 use crate::security::bash::SanitizedEnv;
+use crate::security::limits::CgroupScope;
 
 /// Unique identifier for a managed process.
 pub type ProcessId = String;
@@ -27,15 +29,174 @@ pub struct ProcessInfo {
     pub pid: Option<u32>,
 }
 
+/// Run state of a background process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessStatus {
+    Running,
+    /// Exit code, or `None` if the process was terminated by a signal.
+    Exited(Option<i32>),
+}
+
+/// Output a process produced since the previous [`ProcessManager::read_new`].
+#[derive(Debug, Clone)]
+pub struct ProcessOutput {
+    pub stdout: String,
+    pub stderr: String,
+    /// Some unread output was dropped because the buffer limit was exceeded.
+    pub truncated: bool,
+    pub status: ProcessStatus,
+}
+
 const MAX_OUTPUT_BUFFER_SIZE: usize = 1024 * 1024; // 1MB limit
+const READ_CHUNK_SIZE: usize = 8192;
+
+/// Bounded text buffer that remembers how much of it has been read.
+#[derive(Default)]
+struct StreamBuffer {
+    data: String,
+    /// Bytes dropped from the front to stay under the limit.
+    dropped: usize,
+    /// Absolute offset consumed by `take_new`.
+    read: usize,
+}
+
+impl StreamBuffer {
+    fn push(&mut self, text: &str) {
+        self.data.push_str(text);
+        // Truncate buffer if it exceeds the limit (keep the most recent data)
+        // Uses drain() for in-place removal without new allocation
+        if self.data.len() > MAX_OUTPUT_BUFFER_SIZE {
+            let remove_bytes = self.data.len() - MAX_OUTPUT_BUFFER_SIZE;
+            // Find safe UTF-8 character boundary
+            let boundary = self
+                .data
+                .char_indices()
+                .find(|(i, _)| *i >= remove_bytes)
+                .map_or(remove_bytes, |(i, _)| i);
+            self.data.drain(..boundary);
+            self.dropped += boundary;
+        }
+    }
+
+    fn take_new(&mut self) -> (String, bool) {
+        let truncated = self.read < self.dropped;
+        let start = self.read.saturating_sub(self.dropped);
+        let new = self.data[start..].to_string();
+        self.read = self.dropped + self.data.len();
+        (new, truncated)
+    }
+}
+
+#[derive(Default)]
+struct OutputBuffers {
+    /// stdout and stderr interleaved in arrival order.
+    combined: StreamBuffer,
+    stdout: StreamBuffer,
+    stderr: StreamBuffer,
+}
+
+#[derive(Clone, Copy)]
+enum Stream {
+    Stdout,
+    Stderr,
+}
 
 struct ManagedProcess {
     child: Child,
     info: ProcessInfo,
-    output_buffer: String,
+    output: Arc<StdMutex<OutputBuffers>>,
+    readers: Vec<JoinHandle<()>>,
+    /// Cgroup the process tree runs in, removed with the process.
+    cgroup: Option<CgroupScope>,
+}
+
+impl ManagedProcess {
+    /// Current status; once exited, waits briefly for the readers to drain the pipes.
+    async fn settle(&mut self) -> ProcessStatus {
+        let status = match self.child.try_wait() {
+            Ok(Some(status)) => ProcessStatus::Exited(status.code()),
+            _ => return ProcessStatus::Running,
+        };
+        for _ in 0..20 {
+            if self.readers.iter().all(JoinHandle::is_finished) {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        status
+    }
+
+    /// Kill the descendants too when the process runs in its own cgroup.
+    fn kill_tree(&self) {
+        if let Some(cgroup) = &self.cgroup {
+            cgroup.kill();
+        }
+    }
+
+    fn combined_output(&self) -> String {
+        lock(&self.output).combined.data.clone()
+    }
+}
+
+impl Drop for ManagedProcess {
+    fn drop(&mut self) {
+        // Grandchildren may keep the pipes open after the shell is gone.
+        for reader in &self.readers {
+            reader.abort();
+        }
+    }
+}
+
+fn lock(output: &StdMutex<OutputBuffers>) -> std::sync::MutexGuard<'_, OutputBuffers> {
+    output.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Copy a pipe into the shared buffers until EOF, decoding UTF-8 across chunk boundaries.
+fn pump(
+    mut pipe: impl AsyncRead + Unpin + Send + 'static,
+    output: Arc<StdMutex<OutputBuffers>>,
+    stream: Stream,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut chunk = vec![0u8; READ_CHUNK_SIZE];
+        let mut pending: Vec<u8> = Vec::new();
+        loop {
+            let n = match pipe.read(&mut chunk).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            pending.extend_from_slice(&chunk[..n]);
+            let valid = match std::str::from_utf8(&pending) {
+                Ok(_) => pending.len(),
+                Err(e) if e.error_len().is_none() => e.valid_up_to(),
+                Err(_) => pending.len(),
+            };
+            let text = String::from_utf8_lossy(&pending[..valid]).into_owned();
+            pending.drain(..valid);
+            append(&output, stream, &text);
+        }
+        if !pending.is_empty() {
+            append(&output, stream, &String::from_utf8_lossy(&pending));
+        }
+    })
+}
+
+fn append(output: &StdMutex<OutputBuffers>, stream: Stream, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    let mut buffers = lock(output);
+    buffers.combined.push(text);
+    match stream {
+        Stream::Stdout => buffers.stdout.push(text),
+        Stream::Stderr => buffers.stderr.push(text),
+    }
 }
 
 /// Manager for background shell processes.
+///
+/// Output is collected continuously into bounded buffers (the most recent
+/// 1MB per stream is kept), so slow pollers never block the process.
 #[derive(Clone)]
 pub struct ProcessManager {
     processes: Arc<Mutex<HashMap<ProcessId, ManagedProcess>>>,
@@ -63,6 +224,14 @@ impl ProcessManager {
         working_dir: &Path,
         env: SanitizedEnv,
     ) -> Result<ProcessId, String> {
+        self.spawn_command(Self::command(command, working_dir, env), command, None)
+            .await
+    }
+
+    /// `bash -c command` as [`spawn_with_env`](Self::spawn_with_env) runs it,
+    /// for callers that configure it further before
+    /// [`spawn_command`](Self::spawn_command).
+    pub(crate) fn command(command: &str, working_dir: &Path, env: SanitizedEnv) -> Command {
         let mut cmd = Command::new("bash");
         cmd.arg("-c").arg(command);
         cmd.current_dir(working_dir);
@@ -72,8 +241,16 @@ impl ProcessManager {
         cmd.stderr(std::process::Stdio::piped());
         // Ensure process is killed when Child is dropped (safety net)
         cmd.kill_on_drop(true);
+        cmd
+    }
 
-        let child = cmd.spawn().map_err(|e| format!("Failed to spawn: {}", e))?;
+    pub(crate) async fn spawn_command(
+        &self,
+        mut cmd: Command,
+        command: &str,
+        cgroup: Option<CgroupScope>,
+    ) -> Result<ProcessId, String> {
+        let mut child = cmd.spawn().map_err(|e| format!("Failed to spawn: {}", e))?;
 
         let id = uuid::Uuid::new_v4().to_string();
         let pid = child.id();
@@ -85,10 +262,21 @@ impl ProcessManager {
             pid,
         };
 
+        let output = Arc::new(StdMutex::new(OutputBuffers::default()));
+        let mut readers = Vec::with_capacity(2);
+        if let Some(stdout) = child.stdout.take() {
+            readers.push(pump(stdout, output.clone(), Stream::Stdout));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            readers.push(pump(stderr, output.clone(), Stream::Stderr));
+        }
+
         let managed = ManagedProcess {
             child,
             info,
-            output_buffer: String::new(),
+            output,
+            readers,
+            cgroup,
         };
 
         self.processes.lock().await.insert(id.clone(), managed);
@@ -100,6 +288,7 @@ impl ProcessManager {
         let mut processes = self.processes.lock().await;
 
         if let Some(mut proc) = processes.remove(id) {
+            proc.kill_tree();
             proc.child
                 .kill()
                 .await
@@ -112,62 +301,62 @@ impl ProcessManager {
         }
     }
 
-    /// Get output from a background process (non-blocking read of available output).
+    /// Kill every tracked background process and reap them.
+    ///
+    /// Returns the number of processes that were terminated.
+    pub async fn kill_all(&self) -> usize {
+        let drained: Vec<_> = self.processes.lock().await.drain().collect();
+        let count = drained.len();
+
+        for (id, mut proc) in drained {
+            proc.kill_tree();
+            if let Err(e) = proc.child.kill().await {
+                tracing::debug!(process_id = %id, error = %e, "Failed to kill background process");
+            }
+            let _ = proc.child.wait().await;
+        }
+
+        count
+    }
+
+    /// All output collected so far, stdout and stderr interleaved.
     pub async fn get_output(&self, id: &ProcessId) -> Result<String, String> {
         let mut processes = self.processes.lock().await;
 
         let proc = processes
             .get_mut(id)
             .ok_or_else(|| format!("Process '{}' not found", id))?;
+        proc.settle().await;
+        Ok(proc.combined_output())
+    }
 
-        // Try to read available stdout
-        if let Some(ref mut stdout) = proc.child.stdout {
-            let mut buffer = vec![0u8; 8192];
-            match tokio::time::timeout(
-                std::time::Duration::from_millis(100),
-                stdout.read(&mut buffer),
-            )
-            .await
-            {
-                Ok(Ok(n)) if n > 0 => {
-                    let s = String::from_utf8_lossy(&buffer[..n]);
-                    proc.output_buffer.push_str(&s);
-                }
-                _ => {}
-            }
-        }
-
-        // Try to read available stderr
-        if let Some(ref mut stderr) = proc.child.stderr {
-            let mut buffer = vec![0u8; 8192];
-            match tokio::time::timeout(
-                std::time::Duration::from_millis(100),
-                stderr.read(&mut buffer),
-            )
-            .await
-            {
-                Ok(Ok(n)) if n > 0 => {
-                    let s = String::from_utf8_lossy(&buffer[..n]);
-                    proc.output_buffer.push_str(&s);
-                }
-                _ => {}
-            }
-        }
+    /// Output produced since the previous call, per stream, with the current status.
+    pub async fn read_new(&self, id: &ProcessId) -> Result<ProcessOutput, String> {
+        let mut processes = self.processes.lock().await;
 
-        // Truncate buffer if it exceeds the limit (keep the most recent data)
-        // Uses drain() for in-place removal without new allocation
-        if proc.output_buffer.len() > MAX_OUTPUT_BUFFER_SIZE {
-            let remove_bytes = proc.output_buffer.len() - MAX_OUTPUT_BUFFER_SIZE;
-            // Find safe UTF-8 character boundary
-            let boundary = proc
-                .output_buffer
-                .char_indices()
-                .find(|(i, _)| *i >= remove_bytes)
-                .map_or(remove_bytes, |(i, _)| i);
-            proc.output_buffer.drain(..boundary);
-        }
+        let proc = processes
+            .get_mut(id)
+            .ok_or_else(|| format!("Process '{}' not found", id))?;
+        let status = proc.settle().await;
+        let mut buffers = lock(&proc.output);
+        let (stdout, stdout_truncated) = buffers.stdout.take_new();
+        let (stderr, stderr_truncated) = buffers.stderr.take_new();
+        Ok(ProcessOutput {
+            stdout,
+            stderr,
+            truncated: stdout_truncated || stderr_truncated,
+            status,
+        })
+    }
 
-        Ok(proc.output_buffer.clone())
+    /// Status of a process, or `None` if it is not tracked.
+    pub async fn status(&self, id: &ProcessId) -> Option<ProcessStatus> {
+        let mut processes = self.processes.lock().await;
+        let proc = processes.get_mut(id)?;
+        Some(match proc.child.try_wait() {
+            Ok(Some(status)) => ProcessStatus::Exited(status.code()),
+            _ => ProcessStatus::Running,
+        })
     }
 
     /// Check if a process is still running.
@@ -199,10 +388,11 @@ impl ProcessManager {
         let ids: Vec<_> = processes.keys().cloned().collect();
         for id in ids {
             if let Some(proc) = processes.get_mut(&id)
-                && let Ok(Some(_status)) = proc.child.try_wait()
+                && let ProcessStatus::Exited(_) = proc.settle().await
                 && let Some(proc) = processes.remove(&id)
             {
-                finished.push((proc.info, proc.output_buffer));
+                let output = proc.combined_output();
+                finished.push((proc.info.clone(), output));
             }
         }
 
@@ -247,6 +437,18 @@ mod tests {
         assert!(!mgr.is_running(&id).await);
     }
 
+    #[tokio::test]
+    async fn test_kill_all() {
+        let mgr = ProcessManager::new();
+        let a = mgr.spawn("sleep 10", &PathBuf::from("/tmp")).await.unwrap();
+        let b = mgr.spawn("sleep 10", &PathBuf::from("/tmp")).await.unwrap();
+
+        assert_eq!(mgr.kill_all().await, 2);
+        assert!(!mgr.is_running(&a).await);
+        assert!(!mgr.is_running(&b).await);
+        assert!(mgr.list().await.is_empty());
+    }
+
     #[tokio::test]
     async fn test_get_output() {
         let mgr = ProcessManager::new();
@@ -278,6 +480,75 @@ mod tests {
         assert!(finished[0].1.contains("done"));
     }
 
+    #[tokio::test]
+    async fn test_read_new_is_incremental() {
+        let mgr = ProcessManager::new();
+        let id = mgr
+            .spawn(
+                "echo first; echo oops >&2; sleep 10; echo second",
+                &PathBuf::from("/tmp"),
+            )
+            .await
+            .unwrap();
+
+        let mut output = mgr.read_new(&id).await.unwrap();
+        for _ in 0..250 {
+            if output.stdout.contains("first") && output.stderr.contains("oops") {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            let next = mgr.read_new(&id).await.unwrap();
+            output.stdout.push_str(&next.stdout);
+            output.stderr.push_str(&next.stderr);
+        }
+        assert_eq!(output.stdout, "first\n");
+        assert_eq!(output.stderr, "oops\n");
+        assert_eq!(mgr.status(&id).await, Some(ProcessStatus::Running));
+
+        let again = mgr.read_new(&id).await.unwrap();
+        assert!(again.stdout.is_empty() && again.stderr.is_empty());
+        assert!(mgr.get_output(&id).await.unwrap().contains("first"));
+
+        mgr.kill(&id).await.unwrap();
+        assert_eq!(mgr.status(&id).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_read_new_reports_exit() {
+        let mgr = ProcessManager::new();
+        let id = mgr
+            .spawn("echo bye; exit 3", &PathBuf::from("/tmp"))
+            .await
+            .unwrap();
+
+        let mut output = mgr.read_new(&id).await.unwrap();
+        for _ in 0..250 {
+            if output.status != ProcessStatus::Running {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            let next = mgr.read_new(&id).await.unwrap();
+            output.stdout.push_str(&next.stdout);
+            output.status = next.status;
+        }
+        assert_eq!(output.status, ProcessStatus::Exited(Some(3)));
+        assert_eq!(output.stdout, "bye\n");
+        assert!(!output.truncated);
+    }
+
+    #[test]
+    fn test_stream_buffer_tracks_dropped_output() {
+        let mut buffer = StreamBuffer::default();
+        buffer.push("abc");
+        assert_eq!(buffer.take_new(), ("abc".to_string(), false));
+
+        buffer.push(&"x".repeat(MAX_OUTPUT_BUFFER_SIZE + 10));
+        let (new, truncated) = buffer.take_new();
+        assert!(truncated);
+        assert_eq!(new.len(), MAX_OUTPUT_BUFFER_SIZE);
+        assert_eq!(buffer.take_new(), (String::new(), false));
+    }
+
     #[tokio::test]
     async fn test_process_not_found() {
         let mgr = ProcessManager::new();
@@ -0,0 +1,164 @@
+//! Per-session tool usage quotas.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Caps on how much one session may use its tools.
+///
+/// Guards against runaway loops: once a cap is reached, further calls fail
+/// with [`ToolError::QuotaExceeded`](crate::types::ToolError::QuotaExceeded)
+/// without running the tool.
+#[derive(Debug, Clone, Default)]
+pub struct ToolQuotas {
+    /// Maximum number of tool calls across all tools.
+    pub max_total_calls: Option<u64>,
+    /// Maximum number of calls per tool.
+    pub max_calls: HashMap<String, u64>,
+    /// Maximum output size per tool, in bytes of result text.
+    pub max_output_bytes: HashMap<String, u64>,
+}
+
+impl ToolQuotas {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn total_calls(mut self, max: u64) -> Self {
+        self.max_total_calls = Some(max);
+        self
+    }
+
+    pub fn calls(mut self, tool: impl Into<String>, max: u64) -> Self {
+        self.max_calls.insert(tool.into(), max);
+        self
+    }
+
+    /// Cap the output `tool` may return. The call that crosses the cap
+    /// completes; later calls are rejected.
+    pub fn output_bytes(mut self, tool: impl Into<String>, max: u64) -> Self {
+        self.max_output_bytes.insert(tool.into(), max);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.max_total_calls.is_none()
+            && self.max_calls.is_empty()
+            && self.max_output_bytes.is_empty()
+    }
+}
+
+#[derive(Default)]
+struct Usage {
+    total_calls: u64,
+    calls: HashMap<String, u64>,
+    output_bytes: HashMap<String, u64>,
+}
+
+/// Tool usage of one session, checked against its [`ToolQuotas`].
+///
+/// Owned by one session's [`ToolRegistry`](super::ToolRegistry).
+pub struct ToolUsageTracker {
+    quotas: ToolQuotas,
+    usage: Mutex<Usage>,
+}
+
+impl ToolUsageTracker {
+    pub fn new(quotas: ToolQuotas) -> Self {
+        Self {
+            quotas,
+            usage: Mutex::new(Usage::default()),
+        }
+    }
+
+    pub fn quotas(&self) -> &ToolQuotas {
+        &self.quotas
+    }
+
+    /// Count a call to `tool`, or explain which quota it would exceed.
+    pub fn acquire(&self, tool: &str) -> Result<(), String> {
+        let mut usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(max) = self.quotas.max_total_calls
+            && usage.total_calls >= max
+        {
+            return Err(format!("session limit of {} tool calls reached", max));
+        }
+        let calls = usage.calls.get(tool).copied().unwrap_or(0);
+        if let Some(&max) = self.quotas.max_calls.get(tool)
+            && calls >= max
+        {
+            return Err(format!("session limit of {} {} calls reached", max, tool));
+        }
+        if let Some(&max) = self.quotas.max_output_bytes.get(tool)
+            && usage.output_bytes.get(tool).copied().unwrap_or(0) >= max
+        {
+            return Err(format!(
+                "session limit of {} bytes of {} output reached",
+                max, tool
+            ));
+        }
+        usage.total_calls += 1;
+        usage.calls.insert(tool.to_string(), calls + 1);
+        Ok(())
+    }
+
+    pub fn record_output(&self, tool: &str, bytes: u64) {
+        let mut usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        *usage.output_bytes.entry(tool.to_string()).or_default() += bytes;
+    }
+
+    pub fn total_calls(&self) -> u64 {
+        self.usage
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .total_calls
+    }
+
+    pub fn calls(&self, tool: &str) -> u64 {
+        let usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        usage.calls.get(tool).copied().unwrap_or(0)
+    }
+
+    pub fn output_bytes(&self, tool: &str) -> u64 {
+        let usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        usage.output_bytes.get(tool).copied().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_quotas() {
+        let tracker = ToolUsageTracker::new(ToolQuotas::new().total_calls(3).calls("Bash", 1));
+        assert!(tracker.acquire("Bash").is_ok());
+        assert!(
+            tracker
+                .acquire("Bash")
+                .unwrap_err()
+                .contains("1 Bash calls")
+        );
+        assert!(tracker.acquire("Read").is_ok());
+        assert!(tracker.acquire("Read").is_ok());
+        assert!(
+            tracker
+                .acquire("Read")
+                .unwrap_err()
+                .contains("3 tool calls")
+        );
+        assert_eq!(tracker.total_calls(), 3);
+        assert_eq!(tracker.calls("Bash"), 1);
+    }
+
+    #[test]
+    fn test_output_quota() {
+        let tracker = ToolUsageTracker::new(ToolQuotas::new().output_bytes("WebFetch", 10));
+        assert!(tracker.acquire("WebFetch").is_ok());
+        tracker.record_output("WebFetch", 8);
+        assert!(tracker.acquire("WebFetch").is_ok());
+        tracker.record_output("WebFetch", 8);
+        assert!(tracker.acquire("WebFetch").is_err());
+        assert!(tracker.acquire("Read").is_ok());
+        assert_eq!(tracker.output_bytes("WebFetch"), 16);
+    }
+}
@@ -9,9 +9,15 @@ use serde::Deserialize;
 
 use super::SchemaTool;
 use super::context::ExecutionContext;
-use crate::types::ToolResult;
+use crate::client::UploadFileRequest;
+use crate::security::{FileLimits, SafePath, SecureFileHandle};
+use crate::types::{ToolOutput, ToolOutputBlock, ToolResult};
 
 const LARGE_FILE_THRESHOLD: u64 = 10 * 1024 * 1024; // 10MB
+/// Largest image sent inline; the API rejects bigger ones.
+const MAX_INLINE_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
+/// Largest PDF sent inline, keeping the base64 payload under the 32MB request limit.
+const MAX_INLINE_PDF_BYTES: u64 = 20 * 1024 * 1024;
 
 #[derive(Debug, Deserialize, JsonSchema)]
 #[schemars(deny_unknown_fields)]
@@ -31,91 +37,161 @@ pub struct ReadTool;
 
 enum FileType {
     Text,
-    #[cfg(feature = "multimedia")]
     Pdf,
-    #[cfg(feature = "multimedia")]
-    Image,
+    Image(&'static str),
+    UnsupportedImage,
     Jupyter,
 }
 
 fn detect_file_type(path: &Path) -> FileType {
-    match path.extension().and_then(|e| e.to_str()) {
-        #[cfg(feature = "multimedia")]
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
         Some("pdf") => FileType::Pdf,
-        #[cfg(feature = "multimedia")]
-        Some("png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp" | "ico" | "tiff") => FileType::Image,
+        Some("png") => FileType::Image("image/png"),
+        Some("jpg" | "jpeg") => FileType::Image("image/jpeg"),
+        Some("gif") => FileType::Image("image/gif"),
+        Some("webp") => FileType::Image("image/webp"),
+        Some("bmp" | "ico" | "tif" | "tiff") => FileType::UnsupportedImage,
         Some("ipynb") => FileType::Jupyter,
         _ => FileType::Text,
     }
 }
 
-async fn read_text(path: &Path, offset: usize, limit: usize) -> ToolResult {
-    let content = match tokio::fs::read_to_string(path).await {
-        Ok(c) => c,
-        Err(e) => return ToolResult::error(format!("Failed to read file: {}", e)),
+/// Lines `offset..offset + limit` of a text file, read no further than
+/// needed. A window that stops short of the end says how to continue.
+async fn read_text(path: SafePath, offset: usize, limit: usize, limits: FileLimits) -> ToolResult {
+    let detect_binary = limits.detect_binary;
+    let max_line_length = limits.max_line_length;
+    let result = tokio::task::spawn_blocking(move || {
+        let handle = SecureFileHandle::open_read(path)?.limits(limits);
+        if detect_binary && handle.is_binary()? {
+            return Ok(Err(handle.size()?));
+        }
+        handle.read_lines(offset, limit).map(Ok)
+    })
+    .await;
+
+    let window = match result {
+        Ok(Ok(Ok(window))) => window,
+        Ok(Ok(Err(size))) => {
+            return ToolResult::error(format!(
+                "Binary file ({} bytes); its content cannot be shown as text",
+                size
+            ));
+        }
+        Ok(Err(e)) => return ToolResult::error(format!("Failed to read file: {}", e)),
+        Err(e) => return ToolResult::error(format!("Task failed: {}", e)),
     };
 
-    let lines: Vec<&str> = content.lines().collect();
-    let total_lines = lines.len();
-    let selected_lines: Vec<&str> = lines.into_iter().skip(offset).take(limit).collect();
-
-    if selected_lines.is_empty() {
+    if window.lines.is_empty() {
         return ToolResult::success(format!(
             "File is empty or offset {} exceeds file length {}",
-            offset, total_lines
+            offset,
+            window.total_lines.unwrap_or(offset)
         ));
     }
 
-    let estimated_capacity: usize = selected_lines
-        .iter()
-        .map(|line| 8 + line.len().min(2003))
-        .sum();
-    let mut output = String::with_capacity(estimated_capacity);
-
-    for (i, line) in selected_lines.iter().enumerate() {
+    let mut output = String::new();
+    for (i, line) in window.lines.iter().enumerate() {
         if i > 0 {
             output.push('\n');
         }
-        let line_num = offset + i + 1;
-        if line.len() > 2000 {
-            let _ = write!(output, "{:>6}\t{}...", line_num, &line[..2000]);
-        } else {
-            let _ = write!(output, "{:>6}\t{}", line_num, line);
+        let _ = write!(output, "{:>6}\t{}", offset + i + 1, line);
+        if window.cut_lines.contains(&i) {
+            output.push_str("...");
         }
     }
 
+    if window.is_partial() {
+        let end = offset + window.lines.len();
+        let _ = write!(
+            output,
+            "\n\n[Showing lines {}-{} of a {}-byte file. Use offset={} to read more.]",
+            offset + 1,
+            end,
+            window.file_size,
+            end
+        );
+    }
+    if !window.cut_lines.is_empty() {
+        let _ = write!(
+            output,
+            "\n[{} line(s) cut at {} bytes.]",
+            window.cut_lines.len(),
+            max_line_length
+        );
+    }
+
     ToolResult::success(output)
 }
 
-#[cfg(feature = "multimedia")]
-async fn read_pdf(path: &Path) -> ToolResult {
+/// Return an image or PDF as a content block: inline when small enough,
+/// otherwise uploaded through the Files API if the context allows it.
+async fn read_attachment(
+    path: &Path,
+    media_type: &str,
+    max_inline: u64,
+    context: &ExecutionContext,
+) -> ToolResult {
+    use base64::Engine;
+
     let bytes = match tokio::fs::read(path).await {
         Ok(b) => b,
-        Err(e) => return ToolResult::error(format!("Failed to read PDF: {}", e)),
+        Err(e) => return ToolResult::error(format!("Failed to read file: {}", e)),
     };
+    let is_image = media_type.starts_with("image/");
 
-    match pdf_extract::extract_text_from_mem(&bytes) {
-        Ok(text) => ToolResult::success(text),
-        Err(e) => ToolResult::error(format!("Failed to extract PDF text: {}", e)),
+    if bytes.len() as u64 <= max_inline {
+        let data = base64::engine::general_purpose::STANDARD.encode(&bytes);
+        let media_type = media_type.to_string();
+        let block = if is_image {
+            ToolOutputBlock::Image { data, media_type }
+        } else {
+            ToolOutputBlock::Document { data, media_type }
+        };
+        return ToolOutput::SuccessBlocks(vec![block]).into();
     }
-}
 
-#[cfg(feature = "multimedia")]
-async fn read_image(path: &Path) -> ToolResult {
-    use base64::Engine;
-
-    let bytes = match tokio::fs::read(path).await {
-        Ok(b) => b,
-        Err(e) => return ToolResult::error(format!("Failed to read image: {}", e)),
+    let Some(client) = context.files() else {
+        #[cfg(feature = "multimedia")]
+        if !is_image {
+            return extract_pdf_text(&bytes);
+        }
+        return ToolResult::error(format!(
+            "File is {} bytes, over the {} byte limit for inline attachments",
+            bytes.len(),
+            max_inline
+        ));
     };
 
-    let mime = mime_guess::from_path(path)
-        .first()
-        .map(|m| m.to_string())
-        .unwrap_or_else(|| "application/octet-stream".to_string());
+    let size = bytes.len();
+    let mut request = UploadFileRequest::from_bytes(bytes, media_type);
+    if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+        request = request.filename(name);
+    }
+    match client.files().upload(request).await {
+        Ok(file) => {
+            tracing::debug!(path = %path.display(), size, file_id = %file.id, "Uploaded large attachment");
+            ToolOutput::SuccessBlocks(vec![ToolOutputBlock::File {
+                file_id: file.id,
+                media_type: media_type.to_string(),
+            }])
+            .into()
+        }
+        Err(e) => ToolResult::error(format!("Failed to upload file: {}", e)),
+    }
+}
 
-    let encoded = base64::engine::general_purpose::STANDARD.encode(&bytes);
-    ToolResult::success(format!("data:{};base64,{}", mime, encoded))
+/// Text of a PDF too large to attach, for when uploads are unavailable.
+#[cfg(feature = "multimedia")]
+fn extract_pdf_text(bytes: &[u8]) -> ToolResult {
+    match pdf_extract::extract_text_from_mem(bytes) {
+        Ok(text) => ToolResult::success(text),
+        Err(e) => ToolResult::error(format!("Failed to extract PDF text: {}", e)),
+    }
 }
 
 async fn read_jupyter(path: &Path) -> ToolResult {
@@ -202,9 +278,10 @@ Usage:
 - By default, it reads up to 2000 lines starting from the beginning of the file
 - You can optionally specify a line offset and limit (especially handy for long files), but it's recommended to read the whole file by not providing these parameters
 - Any lines longer than 2000 characters will be truncated
+- Binary files are refused, and very large files are returned in windows that say which offset to read next
 - Results are returned using cat -n format, with line numbers starting at 1
-- This tool can read images (eg PNG, JPG, etc). When reading an image file the contents are returned as base64-encoded data URI for multimodal processing.
-- This tool can read PDF files (.pdf). PDFs are processed page by page, extracting both text and visual content for analysis.
+- This tool can read images (PNG, JPEG, GIF, WebP). When reading an image file the contents are presented visually.
+- This tool can read PDF files (.pdf). PDFs are attached as documents, so both text and visual content can be analyzed.
 - This tool can read Jupyter notebooks (.ipynb files) and returns all cells with their outputs, combining code, text, and visualizations.
 - This tool can only read files, not directories. To read a directory, use an ls command via the Bash tool.
 - You can call multiple tools in a single response. It is always better to speculatively read multiple potentially useful files in parallel.
@@ -219,6 +296,16 @@ Usage:
         let file_type = detect_file_type(path.as_path());
 
         if !matches!(file_type, FileType::Text) {
+            let max = context.file_limits().max_file_size;
+            if let Ok(meta) = tokio::fs::metadata(path.as_path()).await
+                && meta.len() > max
+            {
+                return ToolResult::error(format!(
+                    "File is {} bytes, over the {} byte limit for reading",
+                    meta.len(),
+                    max
+                ));
+            }
             warn_if_large_file(path.as_path()).await;
         }
 
@@ -226,12 +313,23 @@ Usage:
             FileType::Text => {
                 let offset = input.offset.unwrap_or(0);
                 let limit = input.limit.unwrap_or(2000);
-                read_text(path.as_path(), offset, limit).await
+                read_text(path, offset, limit, context.file_limits().clone()).await
             }
-            #[cfg(feature = "multimedia")]
-            FileType::Pdf => read_pdf(path.as_path()).await,
-            #[cfg(feature = "multimedia")]
-            FileType::Image => read_image(path.as_path()).await,
+            FileType::Pdf => {
+                read_attachment(
+                    path.as_path(),
+                    "application/pdf",
+                    MAX_INLINE_PDF_BYTES,
+                    context,
+                )
+                .await
+            }
+            FileType::Image(media_type) => {
+                read_attachment(path.as_path(), media_type, MAX_INLINE_IMAGE_BYTES, context).await
+            }
+            FileType::UnsupportedImage => ToolResult::error(
+                "Unsupported image format; supported formats are PNG, JPEG, GIF and WebP",
+            ),
             FileType::Jupyter => read_jupyter(path.as_path()).await,
         }
     }
@@ -274,6 +372,53 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_read_partial_and_binary() {
+        let dir = tempdir().unwrap();
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        let log: String = (0..100)
+            .map(|i| format!("{i:04} {}\n", "x".repeat(i)))
+            .collect();
+        fs::write(root.join("app.log"), &log).await.unwrap();
+        fs::write(root.join("data.txt"), b"head\x00tail")
+            .await
+            .unwrap();
+
+        let security = crate::security::SecurityContext::builder()
+            .root(&root)
+            .file_limits(FileLimits::new().max_read_bytes(200).max_line_length(50))
+            .build()
+            .unwrap();
+        let context = ExecutionContext::new(security);
+        let read = |name: &str, offset: usize| {
+            let input = serde_json::json!({
+                "file_path": root.join(name).to_str().unwrap(),
+                "offset": offset,
+            });
+            let context = &context;
+            async move { ReadTool.execute(input, context).await }
+        };
+
+        let result = read("app.log", 0).await;
+        let ToolOutput::Success(content) = &result.output else {
+            panic!("Expected success: {:?}", result.output);
+        };
+        assert!(content.contains("0000"));
+        assert!(content.contains("of a 5550-byte file. Use offset="));
+
+        let result = read("app.log", 90).await;
+        let ToolOutput::Success(content) = &result.output else {
+            panic!("Expected success: {:?}", result.output);
+        };
+        assert!(content.starts_with("    91\t0090 xxx"));
+        assert!(content.contains("...\n"));
+        assert!(content.contains("cut at 50 bytes"));
+
+        let result = read("data.txt", 0).await;
+        assert!(result.is_error());
+        assert!(result.text().contains("Binary file (9 bytes)"));
+    }
+
     #[tokio::test]
     async fn test_read_jupyter_notebook() {
         let dir = tempdir().unwrap();
@@ -318,6 +463,55 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_read_image_and_pdf_as_blocks() {
+        let dir = tempdir().unwrap();
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        fs::write(root.join("shot.PNG"), b"\x89PNG\r\n")
+            .await
+            .unwrap();
+        fs::write(root.join("doc.pdf"), b"%PDF-1.4").await.unwrap();
+        let test_context = ExecutionContext::from_path(&root).unwrap();
+
+        let image = ReadTool
+            .execute(serde_json::json!({"file_path": "shot.PNG"}), &test_context)
+            .await;
+        assert!(matches!(
+            &image.output,
+            ToolOutput::SuccessBlocks(blocks)
+                if matches!(&blocks[..], [ToolOutputBlock::Image { media_type, .. }] if media_type == "image/png")
+        ));
+
+        let pdf = ReadTool
+            .execute(serde_json::json!({"file_path": "doc.pdf"}), &test_context)
+            .await;
+        assert!(matches!(
+            &pdf.output,
+            ToolOutput::SuccessBlocks(blocks)
+                if matches!(&blocks[..], [ToolOutputBlock::Document { media_type, .. }] if media_type == "application/pdf")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_read_image_limits() {
+        let dir = tempdir().unwrap();
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        let large = vec![0u8; MAX_INLINE_IMAGE_BYTES as usize + 1];
+        fs::write(root.join("large.jpg"), large).await.unwrap();
+        fs::write(root.join("icon.bmp"), b"BM").await.unwrap();
+        let test_context = ExecutionContext::from_path(&root).unwrap();
+
+        let result = ReadTool
+            .execute(serde_json::json!({"file_path": "large.jpg"}), &test_context)
+            .await;
+        assert!(result.text().contains("byte limit"));
+
+        let result = ReadTool
+            .execute(serde_json::json!({"file_path": "icon.bmp"}), &test_context)
+            .await;
+        assert!(result.text().contains("Unsupported image format"));
+    }
+
     #[tokio::test]
     async fn test_read_path_traversal_blocked() {
         let dir = tempdir().unwrap();
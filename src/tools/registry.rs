@@ -1,43 +1,122 @@
 //! Tool registry for managing and executing tools.
 
 use std::collections::HashMap;
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use super::ProcessManager;
 use super::access::ToolAccess;
+use super::backup::WriteBackups;
 use super::builder::ToolRegistryBuilder;
+use super::cache::{self, ToolCacheConfig, ToolResultCache};
 use super::context::ExecutionContext;
 use super::env::ToolExecutionEnv;
+use super::middleware::ToolMiddleware;
+use super::quota::{ToolQuotas, ToolUsageTracker};
 use super::traits::Tool;
 use crate::agent::TaskRegistry;
-use crate::permissions::PermissionPolicy;
+use crate::client::messages::ApiTool;
+use crate::config::SettingsLoader;
+use crate::permissions::{
+    DecisionSource, PermissionAuditEntry, PermissionDecision, PermissionGrants, PermissionHandler,
+    PermissionPolicy, PermissionRequest, PermissionResult, PermissionRule, PermissionScope,
+    PermissionStatus, ToolLimits,
+};
+use crate::security::AuditEvent;
+use crate::security::audit::AuditEventKind;
 use crate::session::MemoryPersistence;
+use crate::session::session_state::ToolState;
 use crate::types::{ToolDefinition, ToolOutput, ToolResult};
 use std::path::PathBuf;
+use tokio::sync::Semaphore;
+
+/// Timeout for tools without a configured one.
+const DEFAULT_TOOL_TIMEOUT_MS: u64 = 120_000;
 
 #[derive(Clone)]
 pub struct ToolRegistry {
     tools: HashMap<String, Arc<dyn Tool>>,
+    /// Every version added with `register_version`, keyed by name then version.
+    versions: HashMap<String, HashMap<String, Arc<dyn Tool>>>,
+    /// Former names, resolved to a tool name or `name@version`.
+    aliases: HashMap<String, String>,
     task_registry: TaskRegistry,
     env: ToolExecutionEnv,
+    middleware: Vec<Arc<dyn ToolMiddleware>>,
+    limits: HashMap<String, ToolLimits>,
+    /// Permits enforcing `max_concurrent`, keyed by tool and cap; shared by
+    /// clones so the cap holds across sessions of the same agent.
+    permits: Arc<Mutex<HashMap<(String, usize), Arc<Semaphore>>>>,
+    cache: Option<Arc<ToolResultCache>>,
+    usage: Option<Arc<ToolUsageTracker>>,
+    /// Shared by clones; backups are keyed by session.
+    backups: Option<Arc<WriteBackups>>,
+    permission_handler: Option<Arc<dyn PermissionHandler>>,
+    /// Temporary grants; each session starts without any.
+    grants: PermissionGrants,
 }
 
 impl ToolRegistry {
     pub fn new() -> Self {
         Self {
             tools: HashMap::new(),
+            versions: HashMap::new(),
+            aliases: HashMap::new(),
             task_registry: TaskRegistry::new(Arc::new(MemoryPersistence::new())),
             env: ToolExecutionEnv::default(),
+            middleware: Vec::new(),
+            limits: HashMap::new(),
+            permits: Arc::default(),
+            cache: None,
+            usage: None,
+            backups: None,
+            permission_handler: None,
+            grants: PermissionGrants::default(),
         }
     }
 
     pub(crate) fn from_env(task_registry: TaskRegistry, env: ToolExecutionEnv) -> Self {
         Self {
             tools: HashMap::new(),
+            versions: HashMap::new(),
+            aliases: HashMap::new(),
             task_registry,
             env,
+            middleware: Vec::new(),
+            limits: HashMap::new(),
+            permits: Arc::default(),
+            cache: None,
+            usage: None,
+            backups: None,
+            permission_handler: None,
+            grants: PermissionGrants::default(),
+        }
+    }
+
+    /// Copy of this registry bound to another session's tool state.
+    ///
+    /// Session-bound tools (`TodoWrite`, `Plan`) are recreated for `state`;
+    /// all other tools are shared.
+    pub(crate) fn with_tool_state(&self, state: ToolState) -> Self {
+        let mut registry = self.clone();
+        if registry.tools.contains_key("TodoWrite") {
+            registry.register(Arc::new(super::TodoWriteTool::new(
+                state.clone(),
+                state.session_id(),
+            )));
+        }
+        if registry.tools.contains_key("Plan") {
+            registry.register(Arc::new(super::PlanTool::new(state.clone())));
+        }
+        registry.env.tool_state = Some(state);
+        registry.grants = PermissionGrants::default();
+        if let Some(cache) = &registry.cache {
+            registry.cache = Some(Arc::new(ToolResultCache::new(cache.config().clone())));
+        }
+        if let Some(usage) = &registry.usage {
+            registry.usage = Some(Arc::new(ToolUsageTracker::new(usage.quotas().clone())));
         }
+        registry
     }
 
     pub fn builder() -> ToolRegistryBuilder {
@@ -47,8 +126,18 @@ impl ToolRegistry {
     pub fn from_context(context: ExecutionContext) -> Self {
         Self {
             tools: HashMap::new(),
+            versions: HashMap::new(),
+            aliases: HashMap::new(),
             task_registry: TaskRegistry::new(Arc::new(MemoryPersistence::new())),
             env: ToolExecutionEnv::new(context),
+            middleware: Vec::new(),
+            limits: HashMap::new(),
+            permits: Arc::default(),
+            cache: None,
+            usage: None,
+            backups: None,
+            permission_handler: None,
+            grants: PermissionGrants::default(),
         }
     }
 
@@ -101,23 +190,394 @@ impl ToolRegistry {
         self.tools.get(name)
     }
 
-    pub async fn execute(&self, name: &str, input: serde_json::Value) -> ToolResult {
-        let tool = match self.tools.get(name) {
+    /// Register one version of a tool, keeping the others.
+    ///
+    /// The tool's [`ToolDefinition::version`] must be set. The newest version
+    /// registered so far is the one sent to the API under the tool's name;
+    /// any version stays callable as `name@version`, e.g. through an
+    /// [`alias`](Self::alias) for calls recorded in older sessions.
+    pub fn register_version(&mut self, tool: Arc<dyn Tool>) -> crate::Result<()> {
+        let name = tool.name().to_string();
+        let version = tool.definition().version.ok_or_else(|| {
+            crate::Error::Config(format!("Tool {} has no version to register", name))
+        })?;
+
+        let newest = self
+            .versions
+            .get(&name)
+            .and_then(|versions| versions.keys().max_by(|a, b| compare_versions(a, b)))
+            .is_none_or(|current| compare_versions(&version, current).is_ge());
+        if newest {
+            self.tools.insert(name.clone(), Arc::clone(&tool));
+        }
+        self.versions.entry(name).or_default().insert(version, tool);
+        Ok(())
+    }
+
+    /// Resolve calls to `alias` as calls to `target`: a tool name, or
+    /// `name@version` for a specific registered version.
+    ///
+    /// Meant for a tool's former names, so calls through an alias are reported
+    /// as deprecated.
+    pub fn alias(&mut self, alias: impl Into<String>, target: impl Into<String>) {
+        self.aliases.insert(alias.into(), target.into());
+    }
+
+    /// The tool a call to `name` runs: a registered tool, an alias, or
+    /// `name@version`.
+    pub fn resolve(&self, name: &str) -> Option<&Arc<dyn Tool>> {
+        if let Some(tool) = self.tools.get(name) {
+            return Some(tool);
+        }
+        let target = self.aliases.get(name).map_or(name, String::as_str);
+        if let Some(tool) = self.tools.get(target) {
+            return Some(tool);
+        }
+        let (tool, version) = target.split_once('@')?;
+        self.versions.get(tool)?.get(version)
+    }
+
+    /// Name of the tool a call to `name` runs, or `name` itself if unknown.
+    pub(crate) fn canonical_name<'a>(&'a self, name: &'a str) -> &'a str {
+        self.resolve(name).map_or(name, |tool| tool.name())
+    }
+
+    /// Deprecation notice for a call to `name`, if it goes through an alias
+    /// or reaches a tool marked deprecated.
+    pub fn deprecation(&self, name: &str) -> Option<String> {
+        let tool = self.resolve(name)?;
+        let notice = tool.definition().deprecated;
+        match self.aliases.get(name) {
+            Some(target) => {
+                let mut message = format!("{} is a former name of {}", name, target);
+                if let Some(notice) = notice {
+                    message.push_str(": ");
+                    message.push_str(&notice);
+                }
+                Some(message)
+            }
+            None => notice,
+        }
+    }
+
+    /// Set timeout and concurrency limits for `tool`.
+    ///
+    /// Fields set here take precedence over the permission policy's limits.
+    pub fn set_limits(&mut self, tool: impl Into<String>, limits: ToolLimits) {
+        self.limits.insert(tool.into(), limits);
+    }
+
+    /// Effective limits for `tool`: registry limits over the permission policy's.
+    pub fn limits_for(&self, tool: &str) -> ToolLimits {
+        let policy = self.env.context.limits_for(tool);
+        match self.limits.get(tool) {
+            Some(limits) => limits.clone().or(&policy),
+            None => policy,
+        }
+    }
+
+    fn semaphore(&self, tool: &str, max: usize) -> Arc<Semaphore> {
+        let mut permits = self.permits.lock().unwrap_or_else(|e| e.into_inner());
+        permits
+            .entry((tool.to_string(), max))
+            .or_insert_with(|| Arc::new(Semaphore::new(max.max(1))))
+            .clone()
+    }
+
+    /// Cache successful results of read-only tools for this session.
+    pub fn enable_cache(&mut self, config: ToolCacheConfig) {
+        self.cache = Some(Arc::new(ToolResultCache::new(config)));
+    }
+
+    #[inline]
+    pub fn cache(&self) -> Option<&Arc<ToolResultCache>> {
+        self.cache.as_ref()
+    }
+
+    /// Enforce per-session usage quotas on tool calls.
+    pub fn set_quotas(&mut self, quotas: ToolQuotas) {
+        self.usage = Some(Arc::new(ToolUsageTracker::new(quotas)));
+    }
+
+    #[inline]
+    pub fn usage(&self) -> Option<&Arc<ToolUsageTracker>> {
+        self.usage.as_ref()
+    }
+
+    /// Back up files before write tools change them, so a session's writes
+    /// can be undone with [`rollback_writes`](Self::rollback_writes).
+    pub fn enable_backups(&mut self) {
+        self.backups = Some(Arc::new(WriteBackups::new()));
+    }
+
+    #[inline]
+    pub fn backups(&self) -> Option<&Arc<WriteBackups>> {
+        self.backups.as_ref()
+    }
+
+    /// Restore every file the session's write tools changed, deleting files it
+    /// created. Returns the restored paths.
+    pub async fn rollback_writes(&self, session_id: &str) -> crate::Result<Vec<PathBuf>> {
+        let backups = self
+            .backups
+            .as_ref()
+            .ok_or_else(|| crate::Error::Config("write backups are not enabled".into()))?;
+        backups.restore(session_id, &self.env.context).await
+    }
+
+    fn session_key(&self) -> String {
+        match &self.env.tool_state {
+            Some(state) => state.session_id().to_string(),
+            None => self
+                .env
+                .context
+                .session_id()
+                .unwrap_or_default()
+                .to_string(),
+        }
+    }
+
+    /// Absolute form of the path a call refers to.
+    fn resolved_path(&self, input: &serde_json::Value) -> Option<PathBuf> {
+        let path = cache::input_path(input)?;
+        Some(match self.env.context.resolve(path) {
+            Ok(resolved) => resolved.as_path().to_path_buf(),
+            Err(_) => PathBuf::from(path),
+        })
+    }
+
+    /// Decide calls the permission policy marks as `Ask`. Without a handler
+    /// they are denied.
+    pub fn set_permission_handler(&mut self, handler: Arc<dyn PermissionHandler>) {
+        self.permission_handler = Some(handler);
+    }
+
+    /// Temporary grants of this registry's session.
+    pub fn grants(&self) -> &PermissionGrants {
+        &self.grants
+    }
+
+    async fn resolve_permission(
+        &self,
+        name: &str,
+        input: &serde_json::Value,
+        asked: PermissionResult,
+    ) -> PermissionResult {
+        let Some(handler) = &self.permission_handler else {
+            let denied = PermissionResult::denied(format!(
+                "{} (no permission handler to ask)",
+                asked.reason
+            ))
+            .decided_by(asked.source, asked.rule);
+            return match asked.remediation {
+                Some(remediation) => denied.remediation(remediation),
+                None => denied,
+            };
+        };
+        if let Some(remembered) = self.remembered_permission(name, input).await {
+            return remembered;
+        }
+        let request = PermissionRequest {
+            session_id: self.session_key(),
+            tool_name: name.to_string(),
+            input: input.clone(),
+            reason: asked.reason,
+        };
+        let decision = handler
+            .decide(&request)
+            .await
+            .decided_by(DecisionSource::Handler, None);
+        self.remember_permission(&decision).await;
+        decision
+    }
+
+    /// Add `result` to the session's permission audit trail.
+    async fn audit_permission(
+        &self,
+        name: &str,
+        input: &serde_json::Value,
+        result: &PermissionResult,
+    ) {
+        if let Some(state) = &self.env.tool_state {
+            let entry = PermissionAuditEntry::new(name, input, result);
+            state
+                .with_session_mut(|session| session.permissions.audit.push(entry))
+                .await;
+        }
+    }
+
+    /// Decision from rules a handler asked to remember for this session.
+    async fn remembered_permission(
+        &self,
+        name: &str,
+        input: &serde_json::Value,
+    ) -> Option<PermissionResult> {
+        let state = self.env.tool_state.as_ref()?;
+        let (allow, deny) = state
+            .with_session(|session| {
+                (
+                    session.permissions.allow.clone(),
+                    session.permissions.deny.clone(),
+                )
+            })
+            .await;
+        let matching = |patterns: &[String], rule: fn(String) -> PermissionRule| {
+            patterns
+                .iter()
+                .find(|pattern| rule(pattern.to_string()).matches_with_input(name, input))
+                .cloned()
+        };
+        if let Some(pattern) = matching(&deny, PermissionRule::deny_pattern) {
+            return Some(
+                PermissionResult::denied(format!("Denied earlier in this session: {pattern}"))
+                    .decided_by(DecisionSource::Remembered, Some(pattern)),
+            );
+        }
+        matching(&allow, PermissionRule::allow_pattern).map(|pattern| {
+            PermissionResult::allowed(format!("Allowed earlier in this session: {pattern}"))
+                .decided_by(DecisionSource::Remembered, Some(pattern))
+        })
+    }
+
+    async fn remember_permission(&self, decision: &PermissionResult) {
+        if let Some(grant) = decision.grant.clone().filter(|_| decision.is_allowed()) {
+            self.grants.add(grant);
+        }
+        let Some(update) = &decision.remember else {
+            return;
+        };
+        let rule_decision = match decision.status {
+            PermissionStatus::Allowed => PermissionDecision::Allow,
+            PermissionStatus::Denied => PermissionDecision::Deny,
+            PermissionStatus::Ask => return,
+        };
+        if let Some(state) = &self.env.tool_state {
+            state
+                .with_session_mut(|session| {
+                    let patterns = match rule_decision {
+                        PermissionDecision::Allow => &mut session.permissions.allow,
+                        _ => &mut session.permissions.deny,
+                    };
+                    if !patterns.contains(&update.pattern) {
+                        patterns.push(update.pattern.clone());
+                    }
+                })
+                .await;
+        }
+        if update.scope == PermissionScope::Project
+            && let Err(e) = SettingsLoader::add_local_permission(
+                self.env.context.root(),
+                rule_decision,
+                &update.pattern,
+            )
+            .await
+        {
+            tracing::warn!(pattern = %update.pattern, error = %e, "Failed to save permission rule");
+        }
+    }
+
+    /// Wrap every execution in `middleware`, after any already added.
+    pub fn add_middleware(&mut self, middleware: Arc<dyn ToolMiddleware>) {
+        self.middleware.push(middleware);
+    }
+
+    pub async fn execute(&self, name: &str, mut input: serde_json::Value) -> ToolResult {
+        let tool = match self.resolve(name) {
             Some(t) => t,
             None => return ToolResult::unknown_tool(name),
         };
+        // Policies, limits and middleware see the tool's own name, not an alias.
+        let name = tool.name();
+
+        if self.middleware.is_empty() {
+            return self.run(tool, name, input).await;
+        }
 
-        let decision = self.env.context.check_permission(name, &input);
+        let start = Instant::now();
+        let mut short_circuit = None;
+        for middleware in &self.middleware {
+            short_circuit = middleware.before(name, &mut input).await;
+            if short_circuit.is_some() {
+                break;
+            }
+        }
+        let mut result = match short_circuit {
+            Some(result) => result,
+            None => self.run(tool, name, input.clone()).await,
+        };
+        let duration = start.elapsed();
+        for middleware in self.middleware.iter().rev() {
+            middleware.after(name, &input, &mut result, duration).await;
+        }
+        result
+    }
+
+    async fn run(
+        &self,
+        tool: &Arc<dyn Tool>,
+        name: &str,
+        mut input: serde_json::Value,
+    ) -> ToolResult {
+        let mut decision =
+            self.env
+                .context
+                .check_permission_with_grants(name, &input, &self.grants);
+        self.audit_permission(name, &input, &decision).await;
+        if decision.needs_approval() {
+            decision = self.resolve_permission(name, &input, decision).await;
+            self.audit_permission(name, &input, &decision).await;
+            if let Some(approved) = decision.input.take().filter(|_| decision.is_allowed()) {
+                input = approved;
+            }
+        }
         if !decision.is_allowed() {
-            return ToolResult::permission_denied(name, decision.reason);
+            return ToolResult::permission_denied(name, decision.message());
+        }
+
+        if let Err(e) = self.env.context.check_security(name, &input) {
+            if let Some(event) = AuditEvent::for_tool_call(self.session_key(), name, &input, &e) {
+                self.env.context.record_audit(event).await;
+            }
+            return ToolResult::security_error(e.to_string());
         }
 
-        if let Err(e) = self.env.context.validate_security(name, &input) {
-            return ToolResult::security_error(e);
+        if let Some(usage) = &self.usage
+            && let Err(reason) = usage.acquire(name)
+        {
+            return ToolResult::quota_exceeded(reason);
         }
 
-        let limits = self.env.context.limits_for(name);
-        let timeout_ms = limits.timeout_ms.unwrap_or(120_000);
+        if let Some(backups) = &self.backups
+            && crate::permissions::is_write_tool(name)
+            && let Some(path) = self.resolved_path(&input)
+            && let Err(e) = backups
+                .snapshot(&self.session_key(), &path, &self.env.context)
+                .await
+        {
+            return ToolResult::error(format!("Failed to back up {}: {}", path.display(), e));
+        }
+
+        let cache = self.cache.as_ref().filter(|c| c.is_cacheable(name));
+        if let Some(hit) = cache.and_then(|c| c.get(name, &input)) {
+            return hit;
+        }
+        let cache_input = cache.map(|_| input.clone());
+        let written = match &self.cache {
+            Some(cache) if cache.is_invalidated_by(name) => Some(self.resolved_path(&input)),
+            _ => None,
+        };
+
+        let limits = self.limits_for(name);
+        let timeout_ms = limits
+            .timeout_ms
+            .or_else(|| tool.default_timeout().map(|t| t.as_millis() as u64))
+            .unwrap_or(DEFAULT_TOOL_TIMEOUT_MS);
+
+        // Held until the call finishes; waiting for it does not count toward the timeout.
+        let _permit = match limits.max_concurrent {
+            Some(max) => self.semaphore(name, max).acquire_owned().await.ok(),
+            None => None,
+        };
 
         let result = tokio::time::timeout(
             Duration::from_millis(timeout_ms),
@@ -125,10 +585,37 @@ impl ToolRegistry {
         )
         .await;
 
-        match result {
+        let result = match result {
             Ok(tool_result) => self.apply_output_limits(tool_result, &limits),
             Err(_) => ToolResult::timeout(timeout_ms),
+        };
+        for violation in result.sandbox_violations() {
+            let event = AuditEvent::new(
+                AuditEventKind::SandboxViolation,
+                format!("{} denied: {}", violation.kind, violation.detail),
+            )
+            .session(self.session_key())
+            .tool(name)
+            .command(violation.command.clone());
+            self.env.context.record_audit(event).await;
+        }
+
+        if let Some(cache) = &self.cache {
+            if let Some(input) = &cache_input
+                && !result.is_error()
+            {
+                cache.insert(name, input, self.resolved_path(input), &result);
+            }
+            if let Some(written) = written
+                && !result.is_error()
+            {
+                cache.invalidate(name, written.as_deref());
+            }
+        }
+        if let Some(usage) = &self.usage {
+            usage.record_output(name, result.text().len() as u64);
         }
+        result
     }
 
     fn apply_output_limits(
@@ -154,6 +641,11 @@ impl ToolRegistry {
         self.tools.values().map(|t| t.definition()).collect()
     }
 
+    /// Tools as sent to the API, including Anthropic-defined tool types.
+    pub fn api_tools(&self) -> Vec<ApiTool> {
+        self.tools.values().map(|t| t.api_tool()).collect()
+    }
+
     pub fn names(&self) -> Vec<&str> {
         self.tools.keys().map(|s| s.as_str()).collect()
     }
@@ -180,6 +672,7 @@ impl ToolRegistry {
     }
 
     pub fn unregister(&mut self, name: &str) -> Option<Arc<dyn Tool>> {
+        self.versions.remove(name);
         self.tools.remove(name)
     }
 }
@@ -190,9 +683,21 @@ impl Default for ToolRegistry {
     }
 }
 
+/// Order dotted versions numerically where possible (`1.10` after `1.9`).
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let parts = |v: &str| -> Vec<u64> {
+        v.trim_start_matches('v')
+            .split('.')
+            .map(|p| p.parse().unwrap_or(0))
+            .collect()
+    };
+    parts(a).cmp(&parts(b)).then_with(|| a.cmp(b))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::permissions::PermissionGrant;
     use crate::tools::access::ToolAccess;
 
     #[test]
@@ -208,9 +713,12 @@ mod tests {
         assert!(registry.contains("Read"));
         assert!(registry.contains("Write"));
         assert!(registry.contains("Edit"));
+        assert!(registry.contains("MultiEdit"));
         assert!(registry.contains("Glob"));
         assert!(registry.contains("Grep"));
+        assert!(registry.contains("LS"));
         assert!(registry.contains("Bash"));
+        assert!(registry.contains("BashOutput"));
         assert!(registry.contains("KillShell"));
         assert!(registry.contains("Task"));
         assert!(registry.contains("TaskOutput"));
@@ -267,4 +775,660 @@ mod tests {
         let removed = registry.unregister("NonExistent");
         assert!(removed.is_none());
     }
+
+    struct EchoTool;
+
+    #[async_trait::async_trait]
+    impl Tool for EchoTool {
+        fn name(&self) -> &str {
+            "Echo"
+        }
+
+        fn description(&self) -> &str {
+            "Echo the input"
+        }
+
+        fn input_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object"})
+        }
+
+        async fn execute(&self, input: serde_json::Value, _: &ExecutionContext) -> ToolResult {
+            ToolResult::success(input["text"].as_str().unwrap_or_default())
+        }
+    }
+
+    struct VersionedSearch {
+        version: &'static str,
+        deprecated: Option<&'static str>,
+    }
+
+    #[async_trait::async_trait]
+    impl Tool for VersionedSearch {
+        fn name(&self) -> &str {
+            "Search"
+        }
+
+        fn description(&self) -> &str {
+            "Search"
+        }
+
+        fn input_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object"})
+        }
+
+        fn definition(&self) -> ToolDefinition {
+            let definition =
+                ToolDefinition::new(self.name(), self.description(), self.input_schema())
+                    .version(self.version);
+            match self.deprecated {
+                Some(message) => definition.deprecated(message),
+                None => definition,
+            }
+        }
+
+        async fn execute(&self, _: serde_json::Value, _: &ExecutionContext) -> ToolResult {
+            ToolResult::success(format!("v{}", self.version))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_versions_and_aliases() {
+        let mut registry = ToolRegistry::from_context(ExecutionContext::permissive());
+        for (version, deprecated) in [("2", None), ("1.10", None), ("1.9", Some("use v2"))] {
+            registry
+                .register_version(Arc::new(VersionedSearch {
+                    version,
+                    deprecated,
+                }))
+                .unwrap();
+        }
+        assert!(registry.register_version(Arc::new(EchoTool)).is_err());
+        registry.alias("FindText", "Search@1.9");
+        registry.alias("Lookup", "Search");
+
+        assert_eq!(registry.definitions().len(), 1);
+        let empty = serde_json::json!({});
+        assert_eq!(registry.execute("Search", empty.clone()).await.text(), "v2");
+        assert_eq!(
+            registry.execute("Search@1.10", empty.clone()).await.text(),
+            "v1.10"
+        );
+        assert_eq!(
+            registry.execute("FindText", empty.clone()).await.text(),
+            "v1.9"
+        );
+        assert_eq!(registry.execute("Lookup", empty.clone()).await.text(), "v2");
+        assert!(registry.execute("Search@3", empty).await.is_error());
+
+        assert_eq!(registry.deprecation("Search"), None);
+        assert_eq!(
+            registry.deprecation("Search@1.9").as_deref(),
+            Some("use v2")
+        );
+        assert_eq!(
+            registry.deprecation("FindText").as_deref(),
+            Some("FindText is a former name of Search@1.9: use v2")
+        );
+        assert_eq!(
+            registry.deprecation("Lookup").as_deref(),
+            Some("Lookup is a former name of Search")
+        );
+    }
+
+    struct Recorder {
+        label: &'static str,
+        log: Arc<std::sync::Mutex<Vec<String>>>,
+        block: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl ToolMiddleware for Recorder {
+        async fn before(&self, name: &str, input: &mut serde_json::Value) -> Option<ToolResult> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("{} before {}", self.label, name));
+            if self.block {
+                return Some(ToolResult::error("blocked"));
+            }
+            input["text"] = serde_json::json!(format!(
+                "{}{}",
+                input["text"].as_str().unwrap_or_default(),
+                self.label
+            ));
+            None
+        }
+
+        async fn after(
+            &self,
+            name: &str,
+            _input: &serde_json::Value,
+            result: &mut ToolResult,
+            _duration: Duration,
+        ) {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("{} after {}", self.label, name));
+            if let ToolOutput::Success(text) = &mut result.output {
+                text.push('!');
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_middleware_wraps_execution() {
+        let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut registry = ToolRegistry::from_context(ExecutionContext::permissive());
+        registry.register(Arc::new(EchoTool));
+        for label in ["a", "b"] {
+            registry.add_middleware(Arc::new(Recorder {
+                label,
+                log: log.clone(),
+                block: false,
+            }));
+        }
+
+        let result = registry
+            .execute("Echo", serde_json::json!({"text": "x"}))
+            .await;
+        assert_eq!(result.text(), "xab!!");
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "a before Echo",
+                "b before Echo",
+                "b after Echo",
+                "a after Echo"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_middleware_short_circuit() {
+        let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut registry = ToolRegistry::from_context(ExecutionContext::permissive());
+        registry.register(Arc::new(EchoTool));
+        registry.add_middleware(Arc::new(Recorder {
+            label: "gate",
+            log: log.clone(),
+            block: true,
+        }));
+        registry.add_middleware(Arc::new(Recorder {
+            label: "inner",
+            log: log.clone(),
+            block: false,
+        }));
+
+        let result = registry
+            .execute("Echo", serde_json::json!({"text": "x"}))
+            .await;
+        assert!(result.is_error());
+        assert!(result.text().contains("blocked"));
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["gate before Echo", "inner after Echo", "gate after Echo"]
+        );
+    }
+
+    struct SlowTool {
+        running: std::sync::atomic::AtomicUsize,
+        peak: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl Tool for SlowTool {
+        fn name(&self) -> &str {
+            "Slow"
+        }
+
+        fn description(&self) -> &str {
+            "Sleep for a while"
+        }
+
+        fn input_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object"})
+        }
+
+        async fn execute(&self, _: serde_json::Value, _: &ExecutionContext) -> ToolResult {
+            use std::sync::atomic::Ordering;
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(running, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            ToolResult::success("done")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_limits_enforced() {
+        let tool = Arc::new(SlowTool {
+            running: Default::default(),
+            peak: Default::default(),
+        });
+        let mut registry = ToolRegistry::from_context(ExecutionContext::permissive());
+        registry.register(tool.clone());
+        registry.set_limits("Slow", ToolLimits::concurrency(2));
+
+        let calls = (0..5).map(|_| registry.execute("Slow", serde_json::json!({})));
+        let results = futures::future::join_all(calls).await;
+        assert!(results.iter().all(|r| !r.is_error()));
+        assert_eq!(tool.peak.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+        registry.set_limits("Slow", ToolLimits::serial().with_timeout(1));
+        let result = registry.execute("Slow", serde_json::json!({})).await;
+        assert!(result.is_error());
+        assert_eq!(registry.limits_for("Slow").max_concurrent, Some(1));
+    }
+
+    /// Stands in for an MCP call whose server keeps reporting progress.
+    struct LongRunningTool(Option<Duration>);
+
+    #[async_trait::async_trait]
+    impl Tool for LongRunningTool {
+        fn name(&self) -> &str {
+            "LongRunning"
+        }
+
+        fn description(&self) -> &str {
+            "Run for several minutes"
+        }
+
+        fn input_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object"})
+        }
+
+        fn default_timeout(&self) -> Option<Duration> {
+            self.0
+        }
+
+        async fn execute(&self, _: serde_json::Value, _: &ExecutionContext) -> ToolResult {
+            tokio::time::sleep(Duration::from_secs(300)).await;
+            ToolResult::success("done")
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_tool_default_timeout() {
+        let mut registry = ToolRegistry::from_context(ExecutionContext::permissive());
+        registry.register(Arc::new(LongRunningTool(None)));
+        let result = registry.execute("LongRunning", serde_json::json!({})).await;
+        assert!(result.is_error());
+
+        let mut registry = ToolRegistry::from_context(ExecutionContext::permissive());
+        registry.register(Arc::new(LongRunningTool(Some(
+            crate::mcp::MCP_MAX_CALL_DURATION,
+        ))));
+        let result = registry.execute("LongRunning", serde_json::json!({})).await;
+        assert_eq!(result.text(), "done");
+
+        registry.set_limits("LongRunning", ToolLimits::default().with_timeout(1_000));
+        let result = registry.execute("LongRunning", serde_json::json!({})).await;
+        assert!(result.is_error());
+    }
+
+    #[tokio::test]
+    async fn test_cache_hits_and_invalidation() {
+        let test = crate::tools::testing::helpers::TestContext::new();
+        let path = test.write_file("notes.txt", "first");
+        let registry = ToolRegistry::builder()
+            .access(ToolAccess::only(["Read", "Write"]))
+            .working_dir(path.parent().unwrap())
+            .policy(PermissionPolicy::permissive())
+            .cache(ToolCacheConfig::default())
+            .build();
+        let read = serde_json::json!({"file_path": path.to_str().unwrap()});
+
+        let result = registry.execute("Read", read.clone()).await;
+        assert!(result.text().contains("first"));
+
+        // Changed behind the registry's back: the cached result is served.
+        std::fs::write(&path, "second").unwrap();
+        let result = registry.execute("Read", read.clone()).await;
+        assert!(result.text().contains("first"));
+
+        let write = serde_json::json!({"file_path": path.to_str().unwrap(), "content": "third"});
+        assert!(!registry.execute("Write", write).await.is_error());
+        let result = registry.execute("Read", read).await;
+        assert!(result.text().contains("third"));
+        assert_eq!(registry.cache().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_quotas_enforced_per_session() {
+        let mut registry = ToolRegistry::from_context(ExecutionContext::permissive());
+        registry.register(Arc::new(EchoTool));
+        registry.set_quotas(ToolQuotas::new().calls("Echo", 2));
+        let input = serde_json::json!({"text": "x"});
+
+        assert!(!registry.execute("Echo", input.clone()).await.is_error());
+        assert!(!registry.execute("Echo", input.clone()).await.is_error());
+        let result = registry.execute("Echo", input.clone()).await;
+        assert!(result.is_non_retryable());
+        assert!(result.text().contains("quota exceeded"));
+
+        let session = registry.with_tool_state(ToolState::new(crate::session::SessionId::new()));
+        assert!(!session.execute("Echo", input).await.is_error());
+        assert_eq!(registry.usage().unwrap().calls("Echo"), 2);
+    }
+
+    struct ApproveHello;
+
+    #[async_trait::async_trait]
+    impl PermissionHandler for ApproveHello {
+        async fn decide(&self, request: &PermissionRequest) -> PermissionResult {
+            match request.input["text"].as_str() {
+                Some("hello") => PermissionResult::allowed("approved"),
+                Some("hi") => PermissionResult::allowed("approved")
+                    .with_input(serde_json::json!({"text": "hello"})),
+                _ => PermissionResult::denied("rejected by user"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ask_goes_to_permission_handler() {
+        let mut registry = ToolRegistry::builder()
+            .access(ToolAccess::none())
+            .policy(PermissionPolicy::builder().ask("Echo").build())
+            .build();
+        registry.register(Arc::new(EchoTool));
+        let echo = |text: &str| serde_json::json!({"text": text});
+
+        let result = registry.execute("Echo", echo("hello")).await;
+        assert!(result.text().contains("no permission handler"));
+
+        registry.set_permission_handler(Arc::new(ApproveHello));
+        assert_eq!(
+            registry.execute("Echo", echo("hello")).await.text(),
+            "hello"
+        );
+        assert_eq!(registry.execute("Echo", echo("hi")).await.text(), "hello");
+        let result = registry.execute("Echo", echo("bye")).await;
+        assert!(result.is_error());
+        assert!(result.text().contains("rejected by user"));
+    }
+
+    #[tokio::test]
+    async fn test_allowed_paths_outside_working_dir() {
+        let project = tempfile::tempdir().unwrap();
+        let shared = tempfile::tempdir().unwrap();
+        let file = shared.path().join("notes.txt");
+        std::fs::write(&file, "shared notes").unwrap();
+        let read = serde_json::json!({"file_path": file.to_str().unwrap()});
+        let builder = || {
+            ToolRegistry::builder()
+                .access(ToolAccess::only(["Read"]))
+                .working_dir(project.path())
+                .policy(PermissionPolicy::permissive())
+        };
+
+        assert!(
+            builder()
+                .build()
+                .execute("Read", read.clone())
+                .await
+                .is_error()
+        );
+        let registry = builder()
+            .allowed_paths([shared.path().to_path_buf()])
+            .build();
+        let result = registry.execute("Read", read).await;
+        assert!(result.text().contains("shared notes"));
+    }
+
+    #[tokio::test]
+    async fn test_security_violations_audited() {
+        use crate::security::audit::{AuditLog, AuditQuery, MemoryAuditSink};
+
+        let project = tempfile::tempdir().unwrap();
+        let session_id = crate::session::SessionId::new();
+        let log = AuditLog::new().sink(MemoryAuditSink::new());
+        let registry = ToolRegistry::builder()
+            .access(ToolAccess::only(["Read", "Bash"]))
+            .working_dir(project.path())
+            .policy(PermissionPolicy::permissive())
+            .session_id(session_id)
+            .audit_log(log.clone())
+            .build();
+
+        let read = registry
+            .execute("Read", serde_json::json!({"file_path": "../../etc/passwd"}))
+            .await;
+        assert!(read.is_error());
+        let bash = registry
+            .execute("Bash", serde_json::json!({"command": "rm -rf /"}))
+            .await;
+        assert!(bash.is_error());
+
+        let events = log
+            .query(&AuditQuery::new().session(session_id.to_string()))
+            .await
+            .unwrap();
+        let kinds: Vec<_> = events.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                AuditEventKind::SandboxViolation,
+                AuditEventKind::BlockedCommand
+            ]
+        );
+        assert_eq!(events[1].command.as_deref(), Some("rm -rf /"));
+        assert_eq!(events[0].tool_name.as_deref(), Some("Read"));
+    }
+
+    #[tokio::test]
+    async fn test_read_only_registry() {
+        let project = tempfile::tempdir().unwrap();
+        std::fs::write(project.path().join("notes.txt"), "original").unwrap();
+        let registry = ToolRegistry::builder()
+            .working_dir(project.path())
+            .policy(PermissionPolicy::permissive())
+            .read_only()
+            .build();
+
+        assert!(registry.get("Write").is_none());
+        assert!(registry.get("Edit").is_none());
+        assert!(registry.get("Read").is_some());
+
+        let cat = registry
+            .execute("Bash", serde_json::json!({"command": "cat notes.txt"}))
+            .await;
+        assert!(!cat.is_error(), "{:?}", cat);
+        let write = registry
+            .execute(
+                "Bash",
+                serde_json::json!({"command": "echo changed > notes.txt"}),
+            )
+            .await;
+        assert!(write.is_error());
+        assert_eq!(
+            std::fs::read_to_string(project.path().join("notes.txt")).unwrap(),
+            "original"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_denial_remediation() {
+        let project = tempfile::tempdir().unwrap();
+        let registry = ToolRegistry::builder()
+            .access(ToolAccess::only(["Read"]))
+            .working_dir(project.path())
+            .policy(
+                PermissionPolicy::builder()
+                    .allow("Read(src/**)")
+                    .deny("Read(*.env)")
+                    .build(),
+            )
+            .build();
+
+        let result = registry
+            .execute("Read", serde_json::json!({"file_path": "prod.env"}))
+            .await;
+        let text = result.text();
+        let guidance = text
+            .split_once("<remediation>")
+            .and_then(|(_, rest)| rest.strip_suffix("</remediation>"))
+            .unwrap();
+        let remediation: crate::permissions::Remediation = serde_json::from_str(guidance).unwrap();
+        assert_eq!(remediation.allowed_rules, ["Read(src/**)"]);
+        assert_eq!(
+            remediation.allowed_directories,
+            [registry.get_context().root().to_path_buf()]
+        );
+    }
+
+    #[derive(Default)]
+    struct AlwaysAllow(std::sync::atomic::AtomicUsize);
+
+    #[async_trait::async_trait]
+    impl PermissionHandler for AlwaysAllow {
+        async fn decide(&self, request: &PermissionRequest) -> PermissionResult {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let scope = match request.input["text"].as_str() {
+                Some("project") => PermissionScope::Project,
+                _ => PermissionScope::Session,
+            };
+            PermissionResult::allowed("approved").remember(scope, "Echo")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remembered_permissions() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = ToolState::new(crate::session::SessionId::new());
+        let mut registry = ToolRegistry::builder()
+            .access(ToolAccess::none())
+            .working_dir(dir.path())
+            .tool_state(state.clone())
+            .policy(PermissionPolicy::builder().ask("Echo").build())
+            .build();
+        registry.register(Arc::new(EchoTool));
+        let handler = Arc::new(AlwaysAllow::default());
+        registry.set_permission_handler(handler.clone());
+        let echo = |text: &str| serde_json::json!({"text": text});
+
+        assert_eq!(registry.execute("Echo", echo("a")).await.text(), "a");
+        assert_eq!(registry.execute("Echo", echo("b")).await.text(), "b");
+        assert_eq!(handler.0.load(std::sync::atomic::Ordering::SeqCst), 1);
+        let allow = state
+            .with_session(|session| session.permissions.allow.clone())
+            .await;
+        assert_eq!(allow, ["Echo"]);
+
+        let other = registry.with_tool_state(ToolState::new(crate::session::SessionId::new()));
+        assert_eq!(
+            other.execute("Echo", echo("project")).await.text(),
+            "project"
+        );
+        assert_eq!(handler.0.load(std::sync::atomic::Ordering::SeqCst), 2);
+        let local =
+            std::fs::read_to_string(dir.path().join(".claude/settings.local.json")).unwrap();
+        assert!(local.contains("\"Echo\""));
+    }
+
+    #[tokio::test]
+    async fn test_permission_audit() {
+        let state = ToolState::new(crate::session::SessionId::new());
+        let mut registry = ToolRegistry::builder()
+            .access(ToolAccess::none())
+            .tool_state(state.clone())
+            .policy(PermissionPolicy::builder().ask("Echo").build())
+            .build();
+        registry.register(Arc::new(EchoTool));
+        registry.set_permission_handler(Arc::new(AlwaysAllow::default()));
+
+        let a = serde_json::json!({"text": "a"});
+        registry.execute("Echo", a.clone()).await;
+        registry
+            .execute("Echo", serde_json::json!({"text": "b"}))
+            .await;
+
+        let permissions = state.with_session(|s| s.permissions.clone()).await;
+        let trail: Vec<_> = permissions
+            .audit_for("Echo")
+            .map(|e| (e.decision, e.source, e.rule.as_deref()))
+            .collect();
+        assert_eq!(
+            trail,
+            [
+                (PermissionDecision::Ask, DecisionSource::Rule, Some("Echo")),
+                (PermissionDecision::Allow, DecisionSource::Handler, None),
+                (PermissionDecision::Ask, DecisionSource::Rule, Some("Echo")),
+                (
+                    PermissionDecision::Allow,
+                    DecisionSource::Remembered,
+                    Some("Echo")
+                ),
+            ]
+        );
+        assert!(permissions.audit[1].is_for("Echo", &a));
+        assert!(!permissions.audit[2].is_for("Echo", &a));
+        assert_eq!(permissions.denials().count(), 0);
+    }
+
+    #[derive(Default)]
+    struct GrantTwice(std::sync::atomic::AtomicUsize);
+
+    #[async_trait::async_trait]
+    impl PermissionHandler for GrantTwice {
+        async fn decide(&self, _request: &PermissionRequest) -> PermissionResult {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            PermissionResult::allowed("approved").grant(PermissionGrant::new("Echo").uses(2))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_temporary_grants() {
+        let mut registry = ToolRegistry::builder()
+            .access(ToolAccess::none())
+            .policy(PermissionPolicy::builder().ask("Echo").build())
+            .build();
+        registry.register(Arc::new(EchoTool));
+        let handler = Arc::new(GrantTwice::default());
+        registry.set_permission_handler(handler.clone());
+        let echo = serde_json::json!({"text": "hi"});
+        let asked = || handler.0.load(std::sync::atomic::Ordering::SeqCst);
+
+        for expected in [1, 1, 1, 2] {
+            assert_eq!(registry.execute("Echo", echo.clone()).await.text(), "hi");
+            assert_eq!(asked(), expected);
+        }
+        assert_eq!(registry.grants().active()[0].remaining_uses(), Some(2));
+
+        let session = registry.with_tool_state(ToolState::new(crate::session::SessionId::new()));
+        assert!(session.grants().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rollback_writes() {
+        let test = crate::tools::testing::helpers::TestContext::new();
+        let path = test.write_file("notes.txt", "original");
+        let session_id = crate::session::SessionId::new();
+        let registry = ToolRegistry::builder()
+            .access(ToolAccess::only(["Write", "Edit"]))
+            .working_dir(path.parent().unwrap())
+            .policy(PermissionPolicy::permissive())
+            .session_id(session_id)
+            .backup_writes()
+            .build();
+        let file = path.to_str().unwrap();
+        let created = path.with_file_name("created.txt");
+
+        let edit = serde_json::json!({"file_path": file, "old_string": "original", "new_string": "edited"});
+        assert!(!registry.execute("Edit", edit).await.is_error());
+        let write = serde_json::json!({"file_path": file, "content": "rewritten"});
+        assert!(!registry.execute("Write", write).await.is_error());
+        let create = serde_json::json!({"file_path": created.to_str().unwrap(), "content": "new"});
+        assert!(!registry.execute("Write", create).await.is_error());
+
+        let restored = registry
+            .rollback_writes(&session_id.to_string())
+            .await
+            .unwrap();
+        assert_eq!(restored.len(), 2);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "original");
+        assert!(!created.exists());
+        assert!(ToolRegistry::new().rollback_writes("none").await.is_err());
+    }
 }
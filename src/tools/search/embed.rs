@@ -0,0 +1,209 @@
+//! Text embedders for semantic tool search.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+/// Turns texts into vectors whose cosine similarity reflects their meaning.
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    /// One vector per text, in order.
+    async fn embed(&self, texts: &[String]) -> crate::Result<Vec<Vec<f32>>>;
+}
+
+impl fmt::Debug for dyn Embedder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Embedder")
+    }
+}
+
+const DEFAULT_HASH_DIMENSIONS: usize = 512;
+
+/// Local embedder hashing words and character trigrams into a fixed-size vector.
+///
+/// Needs no model or network access. It matches related word forms
+/// ("query", "queries") that keyword search misses, but not synonyms; use an
+/// [`HttpEmbedder`] for that.
+#[derive(Debug, Clone)]
+pub struct HashEmbedder {
+    dimensions: usize,
+}
+
+impl HashEmbedder {
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            dimensions: dimensions.max(1),
+        }
+    }
+
+    fn embed_one(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0f32; self.dimensions];
+        let lower = text.to_lowercase();
+        for word in lower
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+        {
+            vector[self.bucket(word)] += 1.0;
+            let padded: Vec<char> = format!(" {} ", word).chars().collect();
+            for trigram in padded.windows(3) {
+                vector[self.bucket(&trigram.iter().collect::<String>())] += 0.5;
+            }
+        }
+        normalize(&mut vector);
+        vector
+    }
+
+    fn bucket(&self, feature: &str) -> usize {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        feature.hash(&mut hasher);
+        (hasher.finish() % self.dimensions as u64) as usize
+    }
+}
+
+impl Default for HashEmbedder {
+    fn default() -> Self {
+        Self::new(DEFAULT_HASH_DIMENSIONS)
+    }
+}
+
+#[async_trait]
+impl Embedder for HashEmbedder {
+    async fn embed(&self, texts: &[String]) -> crate::Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|t| self.embed_one(t)).collect())
+    }
+}
+
+/// Embedder calling an OpenAI-compatible `/embeddings` endpoint
+/// (OpenAI, Voyage AI, Ollama, and most self-hosted servers).
+#[derive(Clone)]
+pub struct HttpEmbedder {
+    client: reqwest::Client,
+    url: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+impl HttpEmbedder {
+    /// `url` is the full endpoint, e.g. `https://api.voyageai.com/v1/embeddings`.
+    pub fn new(url: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.into(),
+            model: model.into(),
+            api_key: None,
+        }
+    }
+
+    /// Sent as a bearer token.
+    pub fn api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+}
+
+impl fmt::Debug for HttpEmbedder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpEmbedder")
+            .field("url", &self.url)
+            .field("model", &self.model)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    embedding: Vec<f32>,
+    #[serde(default)]
+    index: Option<usize>,
+}
+
+#[async_trait]
+impl Embedder for HttpEmbedder {
+    async fn embed(&self, texts: &[String]) -> crate::Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut request = self.client.post(&self.url).json(&serde_json::json!({
+            "model": self.model,
+            "input": texts,
+        }));
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(crate::Error::Api {
+                message: response.text().await.unwrap_or_default(),
+                status: Some(status.as_u16()),
+                error_type: None,
+            });
+        }
+
+        let mut data = response.json::<EmbeddingResponse>().await?.data;
+        data.sort_by_key(|d| d.index);
+        if data.len() != texts.len() {
+            return Err(crate::Error::Parse(format!(
+                "expected {} embeddings, got {}",
+                texts.len(),
+                data.len()
+            )));
+        }
+        Ok(data.into_iter().map(|d| d.embedding).collect())
+    }
+}
+
+fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+}
+
+/// Cosine similarity of two vectors; 0 if either is empty or zero.
+pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|v| v * v).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        (dot / (norm_a * norm_b)) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_hash_embedder_similarity() {
+        let embedder = HashEmbedder::default();
+        let vectors = embedder
+            .embed(&[
+                "run database queries".to_string(),
+                "execute a database query".to_string(),
+                "fetch the weather forecast".to_string(),
+            ])
+            .await
+            .unwrap();
+
+        let related = cosine_similarity(&vectors[0], &vectors[1]);
+        let unrelated = cosine_similarity(&vectors[0], &vectors[2]);
+        assert!(related > unrelated);
+        assert!((cosine_similarity(&vectors[0], &vectors[0]) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_cosine_similarity_zero_vector() {
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+        assert_eq!(cosine_similarity(&[], &[]), 0.0);
+    }
+}
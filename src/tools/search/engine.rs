@@ -1,5 +1,8 @@
 //! Search engine implementations for tool discovery.
 
+use std::sync::Arc;
+
+use super::embed::{Embedder, HashEmbedder, cosine_similarity};
 use super::index::{ToolIndex, ToolIndexEntry};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -7,6 +10,8 @@ pub enum SearchMode {
     #[default]
     Regex,
     Bm25,
+    /// Rank by embedding similarity between the query and each tool.
+    Semantic,
 }
 
 #[derive(Debug, Clone)]
@@ -15,13 +20,30 @@ pub struct SearchHit {
     pub score: f64,
 }
 
+#[derive(Debug)]
 pub struct SearchEngine {
     mode: SearchMode,
+    embedder: Option<Arc<dyn Embedder>>,
 }
 
 impl SearchEngine {
+    /// Semantic mode uses a [`HashEmbedder`]; see [`semantic`](Self::semantic)
+    /// to choose the embedder.
     pub fn new(mode: SearchMode) -> Self {
-        Self { mode }
+        match mode {
+            SearchMode::Semantic => Self::semantic(Arc::new(HashEmbedder::default())),
+            _ => Self {
+                mode,
+                embedder: None,
+            },
+        }
+    }
+
+    pub fn semantic(embedder: Arc<dyn Embedder>) -> Self {
+        Self {
+            mode: SearchMode::Semantic,
+            embedder: Some(embedder),
+        }
     }
 
     pub fn regex() -> Self {
@@ -36,6 +58,12 @@ impl SearchEngine {
         self.mode
     }
 
+    pub fn embedder(&self) -> Option<&Arc<dyn Embedder>> {
+        self.embedder.as_ref()
+    }
+
+    /// Keyword search. Semantic mode needs to embed the query, so here it
+    /// falls back to BM25; use [`search_semantic`](Self::search_semantic).
     pub fn search(&self, index: &ToolIndex, query: &str, limit: usize) -> Vec<SearchHit> {
         if query.is_empty() || index.is_empty() {
             return Vec::new();
@@ -43,10 +71,53 @@ impl SearchEngine {
 
         match self.mode {
             SearchMode::Regex => self.search_regex(index, query, limit),
-            SearchMode::Bm25 => self.search_bm25(index, query, limit),
+            SearchMode::Bm25 | SearchMode::Semantic => self.search_bm25(index, query, limit),
         }
     }
 
+    /// Rank tools by similarity to `query` using the index's embeddings.
+    ///
+    /// Falls back to [`search`](Self::search) when there is no embedder, the
+    /// index has not been embedded, or embedding the query fails.
+    pub async fn search_semantic(
+        &self,
+        index: &ToolIndex,
+        query: &str,
+        limit: usize,
+    ) -> Vec<SearchHit> {
+        let (Some(embedder), Some(entries)) = (&self.embedder, index.embedded_entries()) else {
+            return self.search(index, query, limit);
+        };
+        if query.trim().is_empty() {
+            return Vec::new();
+        }
+
+        let query_vector = match embedder.embed(&[query.to_string()]).await {
+            Ok(mut vectors) if vectors.len() == 1 => vectors.remove(0),
+            Ok(_) => return self.search(index, query, limit),
+            Err(e) => {
+                tracing::warn!(error = %e, "Query embedding failed, using keyword search");
+                return self.search(index, query, limit);
+            }
+        };
+
+        let mut hits: Vec<SearchHit> = entries
+            .map(|(entry, vector)| SearchHit {
+                entry: entry.clone(),
+                score: cosine_similarity(&query_vector, vector),
+            })
+            .filter(|hit| hit.score > 0.0)
+            .collect();
+
+        hits.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        hits.truncate(limit);
+        hits
+    }
+
     fn search_regex(&self, index: &ToolIndex, pattern: &str, limit: usize) -> Vec<SearchHit> {
         let regex = match regex::Regex::new(pattern) {
             Ok(r) => r,
@@ -204,6 +275,34 @@ mod tests {
         assert!(hits[0].entry.tool_name.contains("weather"));
     }
 
+    #[tokio::test]
+    async fn test_semantic_search() {
+        let engine = SearchEngine::new(SearchMode::Semantic);
+        let mut index = make_index();
+        let texts: Vec<String> = index
+            .entries()
+            .iter()
+            .map(|e| e.searchable_text())
+            .collect();
+        let vectors = engine.embedder().unwrap().embed(&texts).await.unwrap();
+        index.set_embeddings(vectors);
+
+        let hits = engine
+            .search_semantic(&index, "queries against the database", 2)
+            .await;
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].entry.server_name, "database");
+    }
+
+    #[tokio::test]
+    async fn test_semantic_search_without_embeddings_uses_bm25() {
+        let engine = SearchEngine::new(SearchMode::Semantic);
+        let hits = engine
+            .search_semantic(&make_index(), "weather location", 5)
+            .await;
+        assert!(hits[0].entry.tool_name.contains("weather"));
+    }
+
     #[test]
     fn test_empty_query() {
         let engine = SearchEngine::regex();
@@ -62,6 +62,8 @@ impl ToolIndexEntry {
 pub struct ToolIndex {
     entries: Vec<ToolIndexEntry>,
     total_tokens: usize,
+    /// One vector per entry once the index has been embedded.
+    embeddings: Vec<Vec<f32>>,
 }
 
 impl ToolIndex {
@@ -72,6 +74,26 @@ impl ToolIndex {
     pub fn add(&mut self, entry: ToolIndexEntry) {
         self.total_tokens += entry.estimated_tokens;
         self.entries.push(entry);
+        self.embeddings.clear();
+    }
+
+    /// Attach one embedding per entry, in entry order. Ignored on a length mismatch.
+    pub fn set_embeddings(&mut self, embeddings: Vec<Vec<f32>>) {
+        if embeddings.len() == self.entries.len() {
+            self.embeddings = embeddings;
+        }
+    }
+
+    /// Entries paired with their embeddings, or `None` if the index is not embedded.
+    pub fn embedded_entries(&self) -> Option<impl Iterator<Item = (&ToolIndexEntry, &[f32])>> {
+        if self.embeddings.is_empty() || self.embeddings.len() != self.entries.len() {
+            return None;
+        }
+        Some(
+            self.entries
+                .iter()
+                .zip(self.embeddings.iter().map(Vec::as_slice)),
+        )
     }
 
     pub fn total_tokens(&self) -> usize {
@@ -98,6 +120,7 @@ impl ToolIndex {
 
     pub fn clear(&mut self) {
         self.entries.clear();
+        self.embeddings.clear();
         self.total_tokens = 0;
     }
 }
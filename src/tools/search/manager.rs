@@ -5,6 +5,7 @@ use std::sync::Arc;
 
 use tokio::sync::RwLock;
 
+use super::embed::Embedder;
 use super::engine::{SearchEngine, SearchMode};
 use super::index::{ToolIndex, ToolIndexEntry};
 use crate::mcp::{McpManager, McpToolDefinition, McpToolsetRegistry};
@@ -17,6 +18,9 @@ pub struct ToolSearchConfig {
     pub search_mode: SearchMode,
     pub max_results: usize,
     pub always_load: Vec<String>,
+    /// Embedder for [`SearchMode::Semantic`]; a local
+    /// [`HashEmbedder`](super::HashEmbedder) when unset.
+    pub embedder: Option<Arc<dyn Embedder>>,
 }
 
 impl Default for ToolSearchConfig {
@@ -27,6 +31,7 @@ impl Default for ToolSearchConfig {
             search_mode: SearchMode::Regex,
             max_results: 5,
             always_load: Vec::new(),
+            embedder: None,
         }
     }
 }
@@ -55,6 +60,13 @@ impl ToolSearchConfig {
         self.always_load = tools;
         self
     }
+
+    /// Search semantically with `embedder`.
+    pub fn embedder(mut self, embedder: Arc<dyn Embedder>) -> Self {
+        self.embedder = Some(embedder);
+        self.search_mode = SearchMode::Semantic;
+        self
+    }
 }
 
 pub struct ToolSearchManager {
@@ -67,7 +79,10 @@ pub struct ToolSearchManager {
 
 impl ToolSearchManager {
     pub fn new(config: ToolSearchConfig) -> Self {
-        let engine = SearchEngine::new(config.search_mode);
+        let engine = match (&config.embedder, config.search_mode) {
+            (Some(embedder), SearchMode::Semantic) => SearchEngine::semantic(Arc::clone(embedder)),
+            _ => SearchEngine::new(config.search_mode),
+        };
         Self {
             config,
             index: Arc::new(RwLock::new(ToolIndex::new())),
@@ -88,7 +103,11 @@ impl ToolSearchManager {
     }
 
     pub async fn build_index(&self, mcp_manager: &McpManager) {
-        let tools = mcp_manager.list_tools().await;
+        let tools = self
+            .toolset_registry
+            .read()
+            .await
+            .filter_tools(mcp_manager.list_tools().await);
 
         let mut index = self.index.write().await;
         let mut definitions = self.definitions.write().await;
@@ -97,12 +116,30 @@ impl ToolSearchManager {
         definitions.clear();
 
         for (qualified_name, tool) in tools {
-            if let Some((server, _)) = crate::mcp::parse_mcp_name(&qualified_name) {
-                let entry = ToolIndexEntry::from_mcp_tool(server, &tool);
+            if let Some((server, exposed)) = crate::mcp::parse_mcp_name(&qualified_name) {
+                let renamed = McpToolDefinition {
+                    name: exposed.to_string(),
+                    ..tool.clone()
+                };
+                let entry = ToolIndexEntry::from_mcp_tool(server, &renamed);
                 index.add(entry);
                 definitions.insert(qualified_name, tool);
             }
         }
+
+        if let Some(embedder) = self.engine.embedder() {
+            let texts: Vec<String> = index
+                .entries()
+                .iter()
+                .map(|e| e.searchable_text())
+                .collect();
+            match embedder.embed(&texts).await {
+                Ok(embeddings) => index.set_embeddings(embeddings),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Tool embedding failed, using keyword search")
+                }
+            }
+        }
     }
 
     pub async fn should_use_search(&self) -> bool {
@@ -143,6 +180,8 @@ impl ToolSearchManager {
                     input_schema: def.input_schema.clone(),
                     strict: None,
                     defer_loading: None,
+                    version: None,
+                    deprecated: None,
                 };
                 immediate.push(tool_def);
                 continue;
@@ -161,6 +200,8 @@ impl ToolSearchManager {
                 input_schema: def.input_schema.clone(),
                 strict: None,
                 defer_loading: if should_defer { Some(true) } else { None },
+                version: None,
+                deprecated: None,
             };
 
             if should_defer {
@@ -182,10 +223,30 @@ impl ToolSearchManager {
 
     pub async fn search(&self, query: &str) -> Vec<String> {
         let index = self.index.read().await;
-        let hits = self.engine.search(&index, query, self.config.max_results);
+        let hits = match self.engine.mode() {
+            SearchMode::Semantic => {
+                self.engine
+                    .search_semantic(&index, query, self.config.max_results)
+                    .await
+            }
+            _ => self.engine.search(&index, query, self.config.max_results),
+        };
         hits.into_iter().map(|h| h.entry.qualified_name).collect()
     }
 
+    /// Like [`prepare_tools`](Self::prepare_tools), but in semantic mode the
+    /// deferred tools most relevant to `prompt` are loaded immediately.
+    pub async fn prepare_tools_for(&self, prompt: &str) -> PreparedTools {
+        let mut prepared = self.prepare_tools().await;
+        if !prepared.use_search || self.engine.mode() != SearchMode::Semantic {
+            return prepared;
+        }
+
+        let selected = self.search(prompt).await;
+        prepared.load(&selected);
+        prepared
+    }
+
     pub async fn get_definition(&self, qualified_name: &str) -> Option<ToolDefinition> {
         let definitions = self.definitions.read().await;
         definitions.get(qualified_name).map(|def| ToolDefinition {
@@ -194,6 +255,8 @@ impl ToolSearchManager {
             input_schema: def.input_schema.clone(),
             strict: None,
             defer_loading: None,
+            version: None,
+            deprecated: None,
         })
     }
 
@@ -208,6 +271,8 @@ impl ToolSearchManager {
                     input_schema: def.input_schema.clone(),
                     strict: None,
                     defer_loading: None,
+                    version: None,
+                    deprecated: None,
                 })
             })
             .collect()
@@ -235,6 +300,25 @@ impl PreparedTools {
         self.immediate.iter().chain(self.deferred.iter())
     }
 
+    /// Move the named deferred tools to `immediate`, returning how many moved.
+    ///
+    /// Used once a tool search has found them, so later requests carry their
+    /// full definitions even if the turn with the search result is compacted
+    /// away or was never stored in the history.
+    pub fn load<S: AsRef<str>>(&mut self, names: &[S]) -> usize {
+        let (loaded, deferred): (Vec<_>, Vec<_>) = std::mem::take(&mut self.deferred)
+            .into_iter()
+            .partition(|tool| names.iter().any(|n| n.as_ref() == tool.name));
+        self.deferred = deferred;
+        let count = loaded.len();
+        self.immediate
+            .extend(loaded.into_iter().map(|tool| ToolDefinition {
+                defer_loading: None,
+                ..tool
+            }));
+        count
+    }
+
     pub fn token_savings(&self) -> usize {
         if self.use_search {
             self.deferred
@@ -270,6 +354,35 @@ mod tests {
         assert_eq!(config.threshold_tokens(), 5_000);
     }
 
+    #[test]
+    fn test_config_embedder_enables_semantic_mode() {
+        let config = ToolSearchConfig::default()
+            .embedder(Arc::new(crate::tools::search::HashEmbedder::default()));
+        assert_eq!(config.search_mode, SearchMode::Semantic);
+
+        let manager = ToolSearchManager::new(config);
+        assert!(manager.engine.embedder().is_some());
+    }
+
+    #[test]
+    fn test_prepared_tools_load() {
+        let tool = |name: &str| ToolDefinition::new(name, "", serde_json::json!({})).deferred();
+        let mut prepared = PreparedTools {
+            use_search: true,
+            search_mode: SearchMode::Bm25,
+            immediate: Vec::new(),
+            deferred: vec![tool("mcp__a__x"), tool("mcp__a__y")],
+            total_tokens: 0,
+            threshold_tokens: 0,
+        };
+
+        assert_eq!(prepared.load(&["mcp__a__y", "mcp__a__z"]), 1);
+        assert_eq!(prepared.immediate[0].name, "mcp__a__y");
+        assert!(!prepared.immediate[0].is_deferred());
+        assert_eq!(prepared.deferred.len(), 1);
+        assert_eq!(prepared.load(&["mcp__a__y"]), 0);
+    }
+
     #[tokio::test]
     async fn test_manager_creation() {
         let manager = ToolSearchManager::default();
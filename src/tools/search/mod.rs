@@ -1,9 +1,11 @@
 //! Tool search functionality for progressive disclosure.
 
+mod embed;
 mod engine;
 mod index;
 mod manager;
 
+pub use embed::{Embedder, HashEmbedder, HttpEmbedder};
 pub use engine::{SearchEngine, SearchHit, SearchMode};
 pub use index::{ToolIndex, ToolIndexEntry};
 pub use manager::{PreparedTools, ToolSearchConfig, ToolSearchManager};
@@ -0,0 +1,734 @@
+//! Sql tool - parameterized queries against SQLite or PostgreSQL.
+
+use std::fmt::Write;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+use sqlx::{Column, Row};
+
+use super::SchemaTool;
+use super::context::ExecutionContext;
+use crate::types::ToolResult;
+
+const DEFAULT_MAX_ROWS: usize = 500;
+const DEFAULT_MAX_BYTES: usize = 64 * 1024;
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Statements accepted when the tool is read-only.
+const READ_KEYWORDS: &[&str] = &["SELECT", "WITH", "VALUES", "EXPLAIN", "TABLE", "SHOW"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SqlFormat {
+    #[default]
+    Markdown,
+    Json,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct SqlInput {
+    /// A single SQL statement. Pass values through `params` using placeholders ($1, $2 for PostgreSQL; ?1, ?2 for SQLite) instead of writing them inline.
+    pub query: String,
+    /// Values bound to the statement's placeholders, in order
+    #[serde(default)]
+    pub params: Vec<Value>,
+    /// Result format: "markdown" (default) or "json"
+    #[serde(default)]
+    pub format: SqlFormat,
+}
+
+#[derive(Debug, Clone)]
+enum SqlBackend {
+    #[cfg(feature = "sqlite")]
+    Sqlite(sqlx::SqlitePool),
+    #[cfg(feature = "postgres")]
+    Postgres(sqlx::PgPool),
+}
+
+/// Runs SQL against a connection pool supplied by the application.
+///
+/// Statements are always prepared with values bound from `params`, and only one
+/// statement is accepted per call. The tool is read-only unless
+/// [`allow_writes`](Self::allow_writes) is set: statements must start with a
+/// query keyword and run in a transaction that is always rolled back, and the
+/// database itself refuses writes (PostgreSQL marks the transaction
+/// `READ ONLY`, SQLite runs it with `PRAGMA query_only`).
+#[derive(Debug, Clone)]
+pub struct SqlTool {
+    backend: SqlBackend,
+    read_only: bool,
+    max_rows: usize,
+    max_bytes: usize,
+    timeout: Duration,
+}
+
+impl SqlTool {
+    fn with_backend(backend: SqlBackend) -> Self {
+        Self {
+            backend,
+            read_only: true,
+            max_rows: DEFAULT_MAX_ROWS,
+            max_bytes: DEFAULT_MAX_BYTES,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    #[cfg(feature = "sqlite")]
+    pub fn sqlite(pool: sqlx::SqlitePool) -> Self {
+        Self::with_backend(SqlBackend::Sqlite(pool))
+    }
+
+    #[cfg(feature = "postgres")]
+    pub fn postgres(pool: sqlx::PgPool) -> Self {
+        Self::with_backend(SqlBackend::Postgres(pool))
+    }
+
+    /// Accept data-modifying statements and commit them.
+    pub fn allow_writes(mut self) -> Self {
+        self.read_only = false;
+        self
+    }
+
+    /// Rows beyond this are dropped from the result (default 500).
+    pub fn max_rows(mut self, rows: usize) -> Self {
+        self.max_rows = rows;
+        self
+    }
+
+    /// Approximate cap on the size of returned cell data (default 64KB).
+    pub fn max_bytes(mut self, bytes: usize) -> Self {
+        self.max_bytes = bytes;
+        self
+    }
+
+    /// Upper bound for a single statement (default 30s).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn run(&self, input: &SqlInput) -> Result<String, String> {
+        let statement = Statement::parse(&input.query)?;
+        if self.read_only && !statement.is_read() {
+            return Err(format!(
+                "Only read-only statements ({}) are allowed",
+                READ_KEYWORDS.join(", ")
+            ));
+        }
+
+        let mut collector = Collector::new(self.max_rows, self.max_bytes);
+        let query = async {
+            match &self.backend {
+                #[cfg(feature = "sqlite")]
+                SqlBackend::Sqlite(pool) => {
+                    sqlite::run(pool, input, &statement, self.read_only, &mut collector).await
+                }
+                #[cfg(feature = "postgres")]
+                SqlBackend::Postgres(pool) => {
+                    postgres::run(pool, input, &statement, self.read_only, &mut collector).await
+                }
+            }
+        };
+        tokio::time::timeout(self.timeout, query)
+            .await
+            .map_err(|_| format!("Query timed out after {}ms", self.timeout.as_millis()))?
+            .map_err(|e| format!("Query failed: {}", e))?;
+
+        Ok(match input.format {
+            SqlFormat::Markdown => collector.to_markdown(),
+            SqlFormat::Json => collector.to_json(),
+        })
+    }
+}
+
+#[async_trait]
+impl SchemaTool for SqlTool {
+    type Input = SqlInput;
+
+    const NAME: &'static str = "Sql";
+    const DESCRIPTION: &'static str = r#"Runs a single SQL statement against the configured database and returns the result rows.
+
+- Never write values inline; use placeholders and pass them in `params` ($1, $2 for PostgreSQL; ?1, ?2 for SQLite)
+- Only one statement per call
+- Unless the tool allows writes, only read-only queries (SELECT, WITH, VALUES, EXPLAIN) are accepted
+- Results are returned as a markdown table by default, or as JSON with `format: "json"`
+- Large results are truncated; add LIMIT, filters or aggregates instead of fetching whole tables"#;
+
+    async fn handle(&self, input: SqlInput, _context: &ExecutionContext) -> ToolResult {
+        match self.run(&input).await {
+            Ok(output) => ToolResult::success(output),
+            Err(e) => ToolResult::error(e),
+        }
+    }
+}
+
+/// Lexical facts about a SQL statement, ignoring literals and comments.
+#[derive(Debug)]
+struct Statement {
+    keyword: String,
+    returning: bool,
+}
+
+impl Statement {
+    /// Check that `query` holds exactly one statement and find its leading keyword.
+    fn parse(query: &str) -> Result<Self, String> {
+        let chars: Vec<char> = query.chars().collect();
+        let mut keyword = None;
+        let mut returning = false;
+        let mut terminated = false;
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+            if c.is_whitespace() {
+                i += 1;
+                continue;
+            }
+            if c == '-' && next == Some('-') {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            if c == '/' && next == Some('*') {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+                continue;
+            }
+            if terminated {
+                return Err("Only a single SQL statement is allowed per call".to_string());
+            }
+            if c == ';' {
+                terminated = true;
+                i += 1;
+                continue;
+            }
+
+            keyword.get_or_insert_with(String::new);
+            if matches!(c, '\'' | '"' | '`' | '[') {
+                let close = if c == '[' { ']' } else { c };
+                i += 1;
+                while i < chars.len() && chars[i] != close {
+                    i += 1;
+                }
+                i += 1;
+            } else if c == '$' && dollar_tag(&chars[i..]).is_some() {
+                let tag = dollar_tag(&chars[i..]).unwrap_or_default();
+                i += tag.len();
+                while i < chars.len() && !chars[i..].starts_with(&tag) {
+                    i += 1;
+                }
+                i += tag.len();
+            } else if c.is_alphanumeric() || c == '_' {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect::<String>().to_uppercase();
+                returning |= word == "RETURNING";
+                if keyword.as_deref() == Some("") {
+                    keyword = Some(word);
+                }
+            } else {
+                i += 1;
+            }
+        }
+
+        match keyword {
+            Some(keyword) => Ok(Self { keyword, returning }),
+            None => Err("Query is empty".to_string()),
+        }
+    }
+
+    fn is_read(&self) -> bool {
+        READ_KEYWORDS.contains(&self.keyword.as_str())
+    }
+
+    fn returns_rows(&self) -> bool {
+        self.is_read() || self.returning
+    }
+}
+
+/// PostgreSQL dollar-quote opener (`$$` or `$tag$`) at the start of `chars`.
+fn dollar_tag(chars: &[char]) -> Option<Vec<char>> {
+    let end = chars[1..].iter().position(|&c| c == '$')? + 1;
+    let tag = &chars[1..end];
+    let valid = tag.first().is_none_or(|c| !c.is_ascii_digit())
+        && tag.iter().all(|c| c.is_alphanumeric() || *c == '_');
+    valid.then(|| chars[..=end].to_vec())
+}
+
+/// Accumulates result rows within the configured limits.
+#[derive(Debug)]
+struct Collector {
+    max_rows: usize,
+    max_bytes: usize,
+    bytes: usize,
+    columns: Vec<String>,
+    rows: Vec<Vec<Value>>,
+    truncated: Option<&'static str>,
+    rows_affected: Option<u64>,
+}
+
+impl Collector {
+    fn new(max_rows: usize, max_bytes: usize) -> Self {
+        Self {
+            max_rows,
+            max_bytes,
+            bytes: 0,
+            columns: Vec::new(),
+            rows: Vec::new(),
+            truncated: None,
+            rows_affected: None,
+        }
+    }
+
+    /// Add a row, returning `false` once no more rows fit.
+    fn push<R: Row>(&mut self, row: &R, decode: impl Fn(&R, usize) -> Value) -> bool {
+        if self.columns.is_empty() {
+            self.columns = row.columns().iter().map(|c| c.name().to_string()).collect();
+        }
+        if self.rows.len() >= self.max_rows {
+            self.truncated = Some("row limit");
+            return false;
+        }
+        let values: Vec<Value> = (0..row.len()).map(|i| decode(row, i)).collect();
+        let size: usize = values.iter().map(|v| cell(v).len()).sum();
+        if self.bytes + size > self.max_bytes {
+            self.truncated = Some("size limit");
+            return false;
+        }
+        self.bytes += size;
+        self.rows.push(values);
+        true
+    }
+
+    fn summary(&self) -> String {
+        if let Some(affected) = self.rows_affected {
+            return format!("{} row(s) affected", affected);
+        }
+        match self.truncated {
+            Some(limit) => format!(
+                "(showing first {} rows; {} reached, refine the query)",
+                self.rows.len(),
+                limit
+            ),
+            None => format!("({} rows)", self.rows.len()),
+        }
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut output = String::new();
+        if !self.columns.is_empty() {
+            let escape = |s: &str| s.replace('|', "\\|").replace('\n', " ");
+            let header: Vec<String> = self.columns.iter().map(|c| escape(c)).collect();
+            let _ = writeln!(output, "| {} |", header.join(" | "));
+            let _ = writeln!(output, "|{}", "---|".repeat(self.columns.len()));
+            for row in &self.rows {
+                let cells: Vec<String> = row.iter().map(|v| escape(&cell(v))).collect();
+                let _ = writeln!(output, "| {} |", cells.join(" | "));
+            }
+            output.push('\n');
+        }
+        output.push_str(&self.summary());
+        output
+    }
+
+    fn to_json(&self) -> String {
+        let mut value = serde_json::json!({
+            "columns": self.columns,
+            "rows": self.rows,
+            "truncated": self.truncated.is_some(),
+        });
+        if let Some(affected) = self.rows_affected {
+            value["rows_affected"] = affected.into();
+        }
+        value.to_string()
+    }
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use sqlx::pool::PoolConnection;
+    use sqlx::sqlite::{Sqlite, SqliteArguments, SqliteConnection, SqliteRow};
+    use sqlx::{Connection, SqlitePool, TypeInfo, ValueRef};
+
+    use super::*;
+
+    type Query<'q> = sqlx::query::Query<'q, Sqlite, SqliteArguments<'q>>;
+
+    fn bind<'q>(query: Query<'q>, value: &'q Value) -> Query<'q> {
+        match value {
+            Value::Null => query.bind(None::<String>),
+            Value::Bool(b) => query.bind(*b),
+            Value::Number(n) => match n.as_i64() {
+                Some(i) => query.bind(i),
+                None => query.bind(n.as_f64()),
+            },
+            Value::String(s) => query.bind(s.as_str()),
+            other => query.bind(other.to_string()),
+        }
+    }
+
+    fn decode(row: &SqliteRow, index: usize) -> Value {
+        let Ok(raw) = row.try_get_raw(index) else {
+            return Value::Null;
+        };
+        if raw.is_null() {
+            return Value::Null;
+        }
+        let value = match raw.type_info().name() {
+            "INTEGER" => row.try_get_unchecked::<i64, _>(index).map(Value::from),
+            "REAL" => row.try_get_unchecked::<f64, _>(index).map(Value::from),
+            "BLOB" => row
+                .try_get_unchecked::<Vec<u8>, _>(index)
+                .map(|b| Value::from(format!("<{} bytes>", b.len()))),
+            _ => row.try_get_unchecked::<String, _>(index).map(Value::from),
+        };
+        value.unwrap_or(Value::Null)
+    }
+
+    pub(super) async fn run(
+        pool: &SqlitePool,
+        input: &SqlInput,
+        statement: &Statement,
+        read_only: bool,
+        collector: &mut Collector,
+    ) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        if !read_only {
+            return execute(&mut conn, input, statement, read_only, collector).await;
+        }
+
+        let mut guard = QueryOnly(Some(conn));
+        let conn = guard.0.as_mut().expect("connection is held until reset");
+        sqlx::query("PRAGMA query_only = ON")
+            .execute(&mut **conn)
+            .await?;
+        let result = execute(conn, input, statement, read_only, collector).await;
+        guard.reset().await?;
+        result
+    }
+
+    async fn execute(
+        conn: &mut SqliteConnection,
+        input: &SqlInput,
+        statement: &Statement,
+        read_only: bool,
+        collector: &mut Collector,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = conn.begin().await?;
+        let query = input
+            .params
+            .iter()
+            .fold(sqlx::query(&input.query), |query, value| bind(query, value));
+
+        if statement.returns_rows() {
+            let mut rows = query.fetch(&mut *tx);
+            while let Some(row) = rows.try_next().await? {
+                if !collector.push(&row, decode) {
+                    break;
+                }
+            }
+        } else {
+            collector.rows_affected = Some(query.execute(&mut *tx).await?.rows_affected());
+        }
+
+        if read_only {
+            tx.rollback().await
+        } else {
+            tx.commit().await
+        }
+    }
+
+    /// A pooled connection that may have `query_only` switched on.
+    ///
+    /// Unless [`reset`](Self::reset) succeeds, for instance when the call
+    /// times out, the connection is closed rather than handed back to the
+    /// pool still refusing writes.
+    struct QueryOnly(Option<PoolConnection<Sqlite>>);
+
+    impl QueryOnly {
+        async fn reset(&mut self) -> Result<(), sqlx::Error> {
+            if let Some(conn) = self.0.as_mut() {
+                sqlx::query("PRAGMA query_only = OFF")
+                    .execute(&mut **conn)
+                    .await?;
+                self.0 = None;
+            }
+            Ok(())
+        }
+    }
+
+    impl Drop for QueryOnly {
+        fn drop(&mut self) {
+            if let Some(conn) = self.0.as_mut() {
+                conn.close_on_drop();
+            }
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+mod postgres {
+    use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+    use sqlx::postgres::{PgArguments, PgRow, Postgres};
+    use sqlx::types::{Decimal, Uuid};
+    use sqlx::{PgPool, TypeInfo, ValueRef};
+
+    use super::*;
+
+    type Query<'q> = sqlx::query::Query<'q, Postgres, PgArguments>;
+
+    fn bind<'q>(query: Query<'q>, value: &'q Value) -> Query<'q> {
+        match value {
+            Value::Null => query.bind(None::<String>),
+            Value::Bool(b) => query.bind(*b),
+            Value::Number(n) => match n.as_i64() {
+                Some(i) => query.bind(i),
+                None => query.bind(n.as_f64()),
+            },
+            Value::String(s) => query.bind(s.as_str()),
+            other => query.bind(other),
+        }
+    }
+
+    fn decode(row: &PgRow, index: usize) -> Value {
+        let Ok(raw) = row.try_get_raw(index) else {
+            return Value::Null;
+        };
+        if raw.is_null() {
+            return Value::Null;
+        }
+        let type_name = raw.type_info().name().to_string();
+        let value = match type_name.as_str() {
+            "BOOL" => row.try_get::<bool, _>(index).map(Value::from),
+            "INT2" => row.try_get::<i16, _>(index).map(Value::from),
+            "INT4" => row.try_get::<i32, _>(index).map(Value::from),
+            "INT8" => row.try_get::<i64, _>(index).map(Value::from),
+            "FLOAT4" => row.try_get::<f32, _>(index).map(Value::from),
+            "FLOAT8" => row.try_get::<f64, _>(index).map(Value::from),
+            "NUMERIC" => row
+                .try_get::<Decimal, _>(index)
+                .map(|d| Value::from(d.to_string())),
+            "JSON" | "JSONB" => row.try_get::<Value, _>(index),
+            "UUID" => row
+                .try_get::<Uuid, _>(index)
+                .map(|u| Value::from(u.to_string())),
+            "TIMESTAMPTZ" => row
+                .try_get::<DateTime<Utc>, _>(index)
+                .map(|t| Value::from(t.to_rfc3339())),
+            "TIMESTAMP" => row
+                .try_get::<NaiveDateTime, _>(index)
+                .map(|t| Value::from(t.to_string())),
+            "DATE" => row
+                .try_get::<NaiveDate, _>(index)
+                .map(|t| Value::from(t.to_string())),
+            "TIME" => row
+                .try_get::<NaiveTime, _>(index)
+                .map(|t| Value::from(t.to_string())),
+            "BYTEA" => row
+                .try_get::<Vec<u8>, _>(index)
+                .map(|b| Value::from(format!("<{} bytes>", b.len()))),
+            _ => row.try_get::<String, _>(index).map(Value::from),
+        };
+        value.unwrap_or_else(|_| Value::from(format!("<{}>", type_name)))
+    }
+
+    pub(super) async fn run(
+        pool: &PgPool,
+        input: &SqlInput,
+        statement: &Statement,
+        read_only: bool,
+        collector: &mut Collector,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        if read_only {
+            sqlx::query("SET TRANSACTION READ ONLY")
+                .execute(&mut *tx)
+                .await?;
+        }
+        let query = input
+            .params
+            .iter()
+            .fold(sqlx::query(&input.query), |query, value| bind(query, value));
+
+        if statement.returns_rows() {
+            let mut rows = query.fetch(&mut *tx);
+            while let Some(row) = rows.try_next().await? {
+                if !collector.push(&row, decode) {
+                    break;
+                }
+            }
+        } else {
+            collector.rows_affected = Some(query.execute(&mut *tx).await?.rows_affected());
+        }
+
+        if read_only {
+            tx.rollback().await
+        } else {
+            tx.commit().await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_statement_parse() {
+        let statement = Statement::parse("-- top\n/* note */ select 'a;b' from t;  ").unwrap();
+        assert_eq!(statement.keyword, "SELECT");
+        assert!(statement.is_read());
+
+        let statement = Statement::parse("insert into t values ($1) returning id").unwrap();
+        assert!(!statement.is_read());
+        assert!(statement.returns_rows());
+
+        let statement = Statement::parse("select $x$ ; drop $x$").unwrap();
+        assert!(statement.is_read());
+
+        let statement = Statement::parse("select [a;b] from t").unwrap();
+        assert!(statement.is_read());
+        assert!(Statement::parse("select 1 as [a'];commit;delete from t;--']").is_err());
+
+        assert!(Statement::parse("select 1; drop table t").is_err());
+        assert!(Statement::parse("select 1;; ").is_err());
+        assert!(Statement::parse(" -- nothing\n").is_err());
+    }
+
+    #[cfg(feature = "sqlite")]
+    mod sqlite_tests {
+        use super::*;
+        use crate::tools::Tool;
+        use sqlx::sqlite::SqlitePoolOptions;
+
+        async fn pool() -> sqlx::SqlitePool {
+            let pool = SqlitePoolOptions::new()
+                .max_connections(1)
+                .connect("sqlite::memory:")
+                .await
+                .unwrap();
+            sqlx::query(
+                "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, score REAL, avatar BLOB)",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query(
+                "INSERT INTO users (name, score, avatar) VALUES ('ann', 1.5, x'0102'), ('b|o', NULL, NULL)",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            pool
+        }
+
+        #[tokio::test]
+        async fn test_read_only_query() {
+            let pool = pool().await;
+            let tool = SqlTool::sqlite(pool.clone());
+            let context = ExecutionContext::default();
+
+            let result = tool
+                .execute(
+                    serde_json::json!({
+                        "query": "SELECT id, name, score, avatar FROM users WHERE id >= ?1 ORDER BY id",
+                        "params": [1]
+                    }),
+                    &context,
+                )
+                .await;
+            assert_eq!(
+                result.text(),
+                "| id | name | score | avatar |\n|---|---|---|---|\n\
+                 | 1 | ann | 1.5 | <2 bytes> |\n| 2 | b\\|o | NULL | NULL |\n\n(2 rows)"
+            );
+
+            let result = tool
+                .execute(serde_json::json!({"query": "DELETE FROM users"}), &context)
+                .await;
+            assert!(result.text().contains("Only read-only statements"));
+
+            let result = tool
+                .execute(
+                    serde_json::json!({"query": "WITH x AS (SELECT 1) DELETE FROM users"}),
+                    &context,
+                )
+                .await;
+            assert!(result.text().contains("readonly"), "{}", result.text());
+
+            let result = tool
+                .execute(
+                    serde_json::json!({"query": "SELECT 1 AS [a'];COMMIT;DELETE FROM users;--']"}),
+                    &context,
+                )
+                .await;
+            assert!(result.is_error());
+            let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            assert_eq!(count, 2);
+
+            let result = SqlTool::sqlite(pool.clone())
+                .allow_writes()
+                .execute(
+                    serde_json::json!({"query": "DELETE FROM users WHERE id = 2"}),
+                    &context,
+                )
+                .await;
+            assert_eq!(result.text(), "1 row(s) affected");
+        }
+
+        #[tokio::test]
+        async fn test_writes_and_limits() {
+            let pool = pool().await;
+            let tool = SqlTool::sqlite(pool.clone()).allow_writes().max_rows(1);
+            let context = ExecutionContext::default();
+
+            let result = tool
+                .execute(
+                    serde_json::json!({
+                        "query": "INSERT INTO users (name) VALUES (?1)",
+                        "params": ["cy"]
+                    }),
+                    &context,
+                )
+                .await;
+            assert_eq!(result.text(), "1 row(s) affected");
+
+            let result = tool
+                .execute(
+                    serde_json::json!({
+                        "query": "SELECT name FROM users ORDER BY id",
+                        "format": "json"
+                    }),
+                    &context,
+                )
+                .await;
+            let json: Value = serde_json::from_str(&result.text()).unwrap();
+            assert_eq!(json["columns"], serde_json::json!(["name"]));
+            assert_eq!(json["rows"], serde_json::json!([["ann"]]));
+            assert_eq!(json["truncated"], true);
+        }
+    }
+}
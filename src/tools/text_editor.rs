@@ -0,0 +1,503 @@
+//! Text editor tool - Anthropic's `text_editor` commands routed onto the file tools.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::context::ExecutionContext;
+use super::edit::{EditInput, EditTool};
+use super::ls::{LsInput, LsTool};
+use super::read::{ReadInput, ReadTool};
+use super::write::{WriteInput, WriteTool};
+use super::{SchemaTool, Tool};
+use crate::client::messages::ApiTool;
+use crate::security::fs::SecureFileHandle;
+use crate::types::{TextEditorDefinition, TextEditorVersion, ToolResult};
+
+/// Directory listings from `view` descend this many levels.
+const VIEW_DEPTH: usize = 2;
+/// Earlier versions kept per file for `undo_edit`.
+const MAX_UNDO: usize = 10;
+
+/// A command issued by the model, as defined by the `text_editor_*` tool types.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum TextEditorCommand {
+    View {
+        path: String,
+        /// `[start, end]` line numbers, 1-based and inclusive; `end` of -1 reads to EOF.
+        #[serde(default)]
+        view_range: Option<[i64; 2]>,
+    },
+    StrReplace {
+        path: String,
+        old_str: String,
+        #[serde(default)]
+        new_str: Option<String>,
+    },
+    Create {
+        path: String,
+        file_text: String,
+    },
+    Insert {
+        path: String,
+        /// Line after which to insert; 0 inserts at the beginning.
+        insert_line: usize,
+        #[serde(alias = "insert_text")]
+        new_str: String,
+    },
+    UndoEdit {
+        path: String,
+    },
+}
+
+/// Client-executed Anthropic text editor tool.
+///
+/// It is advertised with its fixed `text_editor_*` type, and its commands run
+/// through the same sandboxed implementations as `Read`, `Edit`, `Write` and
+/// `LS`. The version decides the name the model calls, so pick it with
+/// [`for_model`](Self::for_model) when targeting Claude 3.x models.
+pub struct TextEditorTool {
+    definition: TextEditorDefinition,
+    history: Mutex<HashMap<PathBuf, Vec<String>>>,
+}
+
+impl Default for TextEditorTool {
+    fn default() -> Self {
+        Self::new(TextEditorVersion::default())
+    }
+}
+
+impl TextEditorTool {
+    pub fn new(version: TextEditorVersion) -> Self {
+        Self {
+            definition: TextEditorDefinition::new(version),
+            history: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The tool version `model` expects.
+    pub fn for_model(model: &str) -> Self {
+        Self::new(TextEditorVersion::for_model(model))
+    }
+
+    /// Truncate `view` output to this many characters (`text_editor_20250728` only).
+    pub fn max_characters(mut self, max: usize) -> Self {
+        self.definition = self.definition.max_characters(max);
+        self
+    }
+
+    pub fn version(&self) -> TextEditorVersion {
+        self.definition.version
+    }
+
+    async fn view(
+        &self,
+        path: String,
+        view_range: Option<[i64; 2]>,
+        context: &ExecutionContext,
+    ) -> ToolResult {
+        let resolved = match context.try_resolve_or_root_for(self.name(), Some(&path)) {
+            Ok(resolved) => resolved,
+            Err(e) => return e,
+        };
+
+        let result = if resolved.is_dir() {
+            if view_range.is_some() {
+                return ToolResult::error("view_range is not allowed when path is a directory");
+            }
+            let input = LsInput {
+                path: Some(path),
+                depth: Some(VIEW_DEPTH),
+                ignore: Vec::new(),
+            };
+            LsTool.handle(input, context).await
+        } else {
+            let (offset, limit) = match view_range {
+                None => (None, Some(usize::MAX)),
+                Some([start, end]) if start >= 1 && (end == -1 || end >= start) => {
+                    let limit = if end == -1 {
+                        usize::MAX
+                    } else {
+                        (end - start + 1) as usize
+                    };
+                    (Some(start as usize - 1), Some(limit))
+                }
+                Some([start, end]) => {
+                    return ToolResult::error(format!(
+                        "Invalid view_range [{}, {}]: start must be >= 1 and end >= start or -1",
+                        start, end
+                    ));
+                }
+            };
+            let input = ReadInput {
+                file_path: path,
+                offset,
+                limit,
+            };
+            ReadTool.handle(input, context).await
+        };
+
+        match self.definition.max_characters {
+            Some(max) if !result.is_error() => {
+                let text = result.text();
+                match text.char_indices().nth(max) {
+                    Some((cut, _)) => ToolResult::success(format!(
+                        "{}\n... (output truncated at {} characters; use view_range to see more)",
+                        &text[..cut],
+                        max
+                    )),
+                    None => result,
+                }
+            }
+            _ => result,
+        }
+    }
+
+    async fn insert(
+        &self,
+        path: String,
+        insert_line: usize,
+        new_str: String,
+        context: &ExecutionContext,
+    ) -> ToolResult {
+        let path = match context.try_resolve_for(self.name(), &path) {
+            Ok(p) => p,
+            Err(e) => return e,
+        };
+        let display_path = path.as_path().display().to_string();
+
+        let result = tokio::task::spawn_blocking(move || {
+            let handle = SecureFileHandle::open_read(path.clone()).map_err(|e| e.to_string())?;
+            let content = handle.read_to_string().map_err(|e| e.to_string())?;
+
+            let mut lines: Vec<&str> = content.lines().collect();
+            if insert_line > lines.len() {
+                return Err(format!(
+                    "insert_line {} is beyond the end of the file ({} lines)",
+                    insert_line,
+                    lines.len()
+                ));
+            }
+            lines.splice(insert_line..insert_line, new_str.lines());
+            let mut new_content = lines.join("\n");
+            if content.ends_with('\n') || content.is_empty() {
+                new_content.push('\n');
+            }
+
+            let write_handle = SecureFileHandle::open_write(path).map_err(|e| e.to_string())?;
+            write_handle
+                .atomic_write(new_content.as_bytes())
+                .map_err(|e| e.to_string())
+        })
+        .await;
+
+        match result {
+            Ok(Ok(())) => ToolResult::success(format!(
+                "Inserted text after line {} in {}",
+                insert_line, display_path
+            )),
+            Ok(Err(e)) => ToolResult::error(e),
+            Err(e) => ToolResult::error(format!("Task failed: {}", e)),
+        }
+    }
+
+    /// Remember the current content of `path` so `undo_edit` can restore it.
+    /// Returns whether anything was saved.
+    fn save_for_undo(&self, path: &str, context: &ExecutionContext) -> bool {
+        if !self.version().supports_undo() {
+            return false;
+        }
+        let Ok(handle) = context.open_read(path) else {
+            return false;
+        };
+        let Ok(content) = handle.read_to_string() else {
+            return false;
+        };
+        let mut history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+        let versions = history
+            .entry(handle.path().as_path().to_path_buf())
+            .or_default();
+        if versions.len() == MAX_UNDO {
+            versions.remove(0);
+        }
+        versions.push(content);
+        true
+    }
+
+    fn forget_last(&self, path: &str, context: &ExecutionContext) {
+        if let Ok(resolved) = context.resolve(path) {
+            let mut history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(versions) = history.get_mut(resolved.as_path()) {
+                versions.pop();
+            }
+        }
+    }
+
+    async fn undo(&self, path: String, context: &ExecutionContext) -> ToolResult {
+        if !self.version().supports_undo() {
+            return ToolResult::error(format!(
+                "undo_edit is not supported by {}",
+                self.version().tool_type()
+            ));
+        }
+        let resolved = match context.try_resolve_for(self.name(), &path) {
+            Ok(p) => p,
+            Err(e) => return e,
+        };
+        let previous = {
+            let mut history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+            history
+                .get_mut(resolved.as_path())
+                .and_then(|versions| versions.pop())
+        };
+        let Some(previous) = previous else {
+            return ToolResult::error(format!("No edit history for {}", path));
+        };
+
+        let input = WriteInput {
+            file_path: path.clone(),
+            content: previous,
+        };
+        let result = WriteTool.handle(input, context).await;
+        if result.is_error() {
+            return result;
+        }
+        ToolResult::success(format!("Last edit to {} undone", path))
+    }
+}
+
+#[async_trait]
+impl Tool for TextEditorTool {
+    fn name(&self) -> &str {
+        &self.definition.name
+    }
+
+    fn description(&self) -> &str {
+        "View, create and edit files with view, str_replace, create and insert commands."
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        serde_json::to_value(schemars::schema_for!(TextEditorCommand))
+            .unwrap_or_else(|_| serde_json::json!({"type": "object"}))
+    }
+
+    fn api_tool(&self) -> ApiTool {
+        ApiTool::TextEditor(self.definition.clone())
+    }
+
+    async fn execute(&self, input: serde_json::Value, context: &ExecutionContext) -> ToolResult {
+        let command: TextEditorCommand = match serde_json::from_value(input) {
+            Ok(command) => command,
+            Err(e) => return ToolResult::error(format!("Invalid input: {}", e)),
+        };
+
+        let saved;
+        let (path, result) = match command {
+            TextEditorCommand::View { path, view_range } => {
+                return self.view(path, view_range, context).await;
+            }
+            TextEditorCommand::UndoEdit { path } => return self.undo(path, context).await,
+            TextEditorCommand::StrReplace {
+                path,
+                old_str,
+                new_str,
+            } => {
+                saved = self.save_for_undo(&path, context);
+                let input = EditInput {
+                    file_path: path.clone(),
+                    old_string: old_str,
+                    new_string: new_str.unwrap_or_default(),
+                    replace_all: false,
+                };
+                (path, EditTool.handle(input, context).await)
+            }
+            TextEditorCommand::Create { path, file_text } => {
+                saved = self.save_for_undo(&path, context);
+                let input = WriteInput {
+                    file_path: path.clone(),
+                    content: file_text,
+                };
+                (path, WriteTool.handle(input, context).await)
+            }
+            TextEditorCommand::Insert {
+                path,
+                insert_line,
+                new_str,
+            } => {
+                saved = self.save_for_undo(&path, context);
+                let result = self
+                    .insert(path.clone(), insert_line, new_str, context)
+                    .await;
+                (path, result)
+            }
+        };
+
+        if saved && result.is_error() {
+            self.forget_last(&path, context);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn setup() -> (tempfile::TempDir, PathBuf, ExecutionContext) {
+        let dir = tempdir().unwrap();
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        let context = ExecutionContext::from_path(&root).unwrap();
+        (dir, root, context)
+    }
+
+    #[test]
+    fn test_api_tool_per_version() {
+        let tool = TextEditorTool::for_model("claude-3-7-sonnet-20250219");
+        assert_eq!(tool.name(), "str_replace_editor");
+
+        let tool = TextEditorTool::default().max_characters(5000);
+        assert_eq!(tool.name(), "str_replace_based_edit_tool");
+        let ApiTool::TextEditor(definition) = tool.api_tool() else {
+            panic!("expected text editor tool");
+        };
+        assert_eq!(definition.version, TextEditorVersion::V20250728);
+        assert_eq!(definition.max_characters, Some(5000));
+    }
+
+    #[tokio::test]
+    async fn test_create_view_replace_insert() {
+        let (_dir, root, context) = setup();
+        let tool = TextEditorTool::default();
+        let path = root.join("notes.txt");
+        let path_str = path.to_str().unwrap();
+
+        let result = tool
+            .execute(
+                serde_json::json!({"command": "create", "path": path_str, "file_text": "one\ntwo\nthree\n"}),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error(), "{}", result.text());
+
+        let result = tool
+            .execute(
+                serde_json::json!({"command": "view", "path": path_str, "view_range": [2, -1]}),
+                &context,
+            )
+            .await;
+        assert_eq!(result.text(), "     2\ttwo\n     3\tthree");
+
+        let result = tool
+            .execute(
+                serde_json::json!({"command": "str_replace", "path": path_str, "old_str": "two", "new_str": "2"}),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error(), "{}", result.text());
+
+        let result = tool
+            .execute(
+                serde_json::json!({"command": "insert", "path": path_str, "insert_line": 1, "insert_text": "1.5"}),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error(), "{}", result.text());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "one\n1.5\n2\nthree\n"
+        );
+
+        let result = tool
+            .execute(
+                serde_json::json!({"command": "insert", "path": path_str, "insert_line": 9, "new_str": "x"}),
+                &context,
+            )
+            .await;
+        assert!(result.is_error());
+
+        let result = tool
+            .execute(
+                serde_json::json!({"command": "undo_edit", "path": path_str}),
+                &context,
+            )
+            .await;
+        assert!(result.is_error());
+    }
+
+    #[tokio::test]
+    async fn test_view_directory_and_truncation() {
+        let (_dir, root, context) = setup();
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "x".repeat(100)).unwrap();
+
+        let tool = TextEditorTool::default();
+        let result = tool
+            .execute(
+                serde_json::json!({"command": "view", "path": root.to_str().unwrap()}),
+                &context,
+            )
+            .await;
+        assert!(result.text().contains("src/lib.rs"));
+
+        let tool = TextEditorTool::default().max_characters(20);
+        let result = tool
+            .execute(
+                serde_json::json!({"command": "view", "path": root.join("src/lib.rs").to_str().unwrap()}),
+                &context,
+            )
+            .await;
+        assert!(result.text().contains("output truncated at 20 characters"));
+    }
+
+    #[tokio::test]
+    async fn test_undo_edit_on_legacy_version() {
+        let (_dir, root, context) = setup();
+        let path = root.join("a.txt");
+        std::fs::write(&path, "before\n").unwrap();
+        let path_str = path.to_str().unwrap();
+
+        let tool = TextEditorTool::new(TextEditorVersion::V20250124);
+        tool.execute(
+            serde_json::json!({"command": "str_replace", "path": path_str, "old_str": "before", "new_str": "after"}),
+            &context,
+        )
+        .await;
+        // A failed edit leaves the history untouched.
+        let result = tool
+            .execute(
+                serde_json::json!({"command": "str_replace", "path": path_str, "old_str": "missing", "new_str": "x"}),
+                &context,
+            )
+            .await;
+        assert!(result.is_error());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "after\n");
+
+        let result = tool
+            .execute(
+                serde_json::json!({"command": "undo_edit", "path": path_str}),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error(), "{}", result.text());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "before\n");
+    }
+
+    #[tokio::test]
+    async fn test_path_outside_sandbox() {
+        let (_dir, _root, context) = setup();
+        let tool = TextEditorTool::default();
+        let result = tool
+            .execute(
+                serde_json::json!({"command": "create", "path": "/etc/claude-agent-test", "file_text": "x"}),
+                &context,
+            )
+            .await;
+        assert!(result.is_error());
+    }
+}
@@ -1,10 +1,13 @@
 //! Tool trait definitions.
 
+use std::time::Duration;
+
 use async_trait::async_trait;
 use schemars::JsonSchema;
 use serde::de::DeserializeOwned;
 
 use super::context::ExecutionContext;
+use crate::client::messages::ApiTool;
 use crate::types::{ToolDefinition, ToolResult};
 
 /// Core tool trait for all tool implementations.
@@ -18,6 +21,20 @@ pub trait Tool: Send + Sync {
     fn definition(&self) -> ToolDefinition {
         ToolDefinition::new(self.name(), self.description(), self.input_schema())
     }
+
+    /// How the tool is advertised to the API.
+    ///
+    /// Anthropic-defined tools (e.g. computer use) override this to send their
+    /// fixed type instead of a custom definition.
+    fn api_tool(&self) -> ApiTool {
+        ApiTool::Custom(self.definition())
+    }
+
+    /// Timeout for calls whose [`ToolLimits`](crate::permissions::ToolLimits)
+    /// set none; `None` uses the registry default of 120 seconds.
+    fn default_timeout(&self) -> Option<Duration> {
+        None
+    }
 }
 
 /// Schema-based tool trait with automatic JSON schema generation.
@@ -30,6 +47,10 @@ pub trait SchemaTool: Send + Sync {
     const NAME: &'static str;
     const DESCRIPTION: &'static str;
     const STRICT: bool = false;
+    /// Schema version, set when several versions are registered side by side.
+    const VERSION: Option<&'static str> = None;
+    /// Deprecation notice reported when the tool is called.
+    const DEPRECATED: Option<&'static str> = None;
 
     async fn handle(&self, input: Self::Input, context: &ExecutionContext) -> ToolResult;
 
@@ -87,6 +108,12 @@ impl<T: SchemaTool + 'static> Tool for T {
         if T::STRICT {
             definition = definition.strict(true);
         }
+        if let Some(version) = T::VERSION {
+            definition = definition.version(version);
+        }
+        if let Some(message) = T::DEPRECATED {
+            definition = definition.deprecated(message);
+        }
         definition
     }
 
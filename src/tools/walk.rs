@@ -0,0 +1,132 @@
+//! Ignore-aware file walking shared by the Glob and Grep tools.
+
+use std::path::{Component, Path, PathBuf};
+
+/// Directories skipped by searches unless the call opts out with `no_ignore`.
+pub const DEFAULT_SEARCH_EXCLUDES: &[&str] = &["node_modules", "target"];
+
+/// Paths under the literal prefix of `pattern` that match it.
+///
+/// Honors `.gitignore`, `.ignore` and git exclude files (even outside a git
+/// repository) and skips `.git` and any directory named in `excludes`. With
+/// `no_ignore`, only `.git` is skipped.
+pub(crate) fn glob_walk(
+    pattern: &Path,
+    excludes: &[String],
+    no_ignore: bool,
+) -> Result<Vec<PathBuf>, glob::PatternError> {
+    let matcher = glob::Pattern::new(&pattern.to_string_lossy())?;
+    let options = glob::MatchOptions {
+        require_literal_separator: true,
+        ..Default::default()
+    };
+
+    let (root, rest) = split_literal_prefix(pattern);
+    let max_depth = if rest.iter().any(|c| c.contains("**")) {
+        None
+    } else {
+        Some(rest.len())
+    };
+
+    let excludes = excludes.to_vec();
+    let walker = ignore::WalkBuilder::new(&root)
+        .hidden(false)
+        .ignore(!no_ignore)
+        .git_ignore(!no_ignore)
+        .git_global(!no_ignore)
+        .git_exclude(!no_ignore)
+        .parents(!no_ignore)
+        .require_git(false)
+        .max_depth(max_depth)
+        .filter_entry(move |entry| {
+            let name = entry.file_name();
+            name != ".git" && (no_ignore || !excludes.iter().any(|e| name == e.as_str()))
+        })
+        .build();
+
+    Ok(walker
+        .filter_map(Result::ok)
+        .filter(|entry| entry.depth() > 0)
+        .map(ignore::DirEntry::into_path)
+        .filter(|path| matcher.matches_path_with(path, options))
+        .collect())
+}
+
+/// Split `pattern` into the directory made of its leading components without
+/// glob metacharacters, and the remaining components.
+fn split_literal_prefix(pattern: &Path) -> (PathBuf, Vec<String>) {
+    let mut root = PathBuf::new();
+    let mut rest = Vec::new();
+    for component in pattern.components() {
+        let text = component.as_os_str().to_string_lossy();
+        let literal = !text.contains(['*', '?', '[', ']', '{', '}']);
+        if rest.is_empty() && (literal || !matches!(component, Component::Normal(_))) {
+            root.push(component);
+        } else {
+            rest.push(text.into_owned());
+        }
+    }
+    // The last literal component may name a file; walk its parent.
+    if rest.is_empty()
+        && let Some(parent) = root.parent()
+    {
+        rest.push(
+            root.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+        );
+        root = parent.to_path_buf();
+    }
+    (root, rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn excludes() -> Vec<String> {
+        DEFAULT_SEARCH_EXCLUDES
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_split_literal_prefix() {
+        let (root, rest) = split_literal_prefix(Path::new("/repo/src/**/*.rs"));
+        assert_eq!(root, Path::new("/repo/src"));
+        assert_eq!(rest, ["**", "*.rs"]);
+
+        let (root, rest) = split_literal_prefix(Path::new("/repo/Cargo.toml"));
+        assert_eq!(root, Path::new("/repo"));
+        assert_eq!(rest, ["Cargo.toml"]);
+    }
+
+    #[test]
+    fn test_glob_walk_respects_ignores() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        for file in [
+            "src/lib.rs",
+            "generated/out.rs",
+            "node_modules/pkg/index.rs",
+            "target/debug/build.rs",
+        ] {
+            let path = root.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        std::fs::write(root.join(".gitignore"), "generated/\n").unwrap();
+
+        let pattern = root.join("**/*.rs");
+        let found = glob_walk(&pattern, &excludes(), false).unwrap();
+        assert_eq!(found, [root.join("src/lib.rs")]);
+
+        let found = glob_walk(&pattern, &excludes(), true).unwrap();
+        assert_eq!(found.len(), 4);
+
+        let found = glob_walk(&root.join("*.rs"), &excludes(), true).unwrap();
+        assert!(found.is_empty());
+    }
+}
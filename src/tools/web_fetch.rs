@@ -0,0 +1,814 @@
+//! Local WebFetch over plain HTTP with caching, robots.txt and size policy.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::header::{
+    ACCEPT, CONTENT_LENGTH, CONTENT_TYPE, ETAG, HeaderValue, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+    LAST_MODIFIED, LOCATION, USER_AGENT,
+};
+use reqwest::{StatusCode, Url};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+
+use super::SchemaTool;
+use super::context::ExecutionContext;
+use super::http::check_url;
+use crate::types::ToolResult;
+
+const DEFAULT_MAX_BYTES: usize = 5 * 1024 * 1024;
+const DEFAULT_MAX_TEXT_BYTES: usize = 100 * 1024;
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_REDIRECTS: usize = 5;
+const DEFAULT_MIME_TYPES: &[&str] = &[
+    "text/*",
+    "application/xhtml+xml",
+    "application/json",
+    "application/xml",
+];
+/// Page chrome hidden when [`MarkdownOptions::main_content`] is set.
+const CHROME_CSS: &str =
+    "nav, header, footer, aside, form, button, script, style, noscript { display: none; }";
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct WebFetchInput {
+    /// The http(s) URL to fetch
+    pub url: String,
+    /// Optional timeout in milliseconds, capped by the tool's configured timeout
+    #[serde(default)]
+    pub timeout: Option<u64>,
+}
+
+/// How HTML pages are converted to markdown.
+#[derive(Debug, Clone)]
+pub struct MarkdownOptions {
+    /// Column at which text is wrapped.
+    pub width: usize,
+    /// List link targets as numbered references after the text.
+    pub links: bool,
+    /// Keep images without alt text, shown by file name.
+    pub images: bool,
+    /// Drop navigation, headers, footers, sidebars and forms.
+    pub main_content: bool,
+    /// Render tables with borders instead of one cell per line.
+    pub tables: bool,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            width: 120,
+            links: true,
+            images: false,
+            main_content: true,
+            tables: true,
+        }
+    }
+}
+
+impl MarkdownOptions {
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn links(mut self, enabled: bool) -> Self {
+        self.links = enabled;
+        self
+    }
+
+    pub fn images(mut self, enabled: bool) -> Self {
+        self.images = enabled;
+        self
+    }
+
+    pub fn main_content(mut self, enabled: bool) -> Self {
+        self.main_content = enabled;
+        self
+    }
+
+    pub fn tables(mut self, enabled: bool) -> Self {
+        self.tables = enabled;
+        self
+    }
+
+    /// Convert `html`, resolving relative link references against `base`.
+    fn convert(&self, html: &[u8], base: &Url) -> Result<String, String> {
+        let mut config = html2text::config::plain()
+            .link_footnotes(self.links)
+            .raw_mode(!self.tables);
+        if self.images {
+            config = config.empty_img_mode(html2text::config::ImageRenderMode::Filename);
+        }
+        if self.main_content {
+            config = config.add_css(CHROME_CSS).map_err(|e| e.to_string())?;
+        }
+        let text = config
+            .string_from_read(html, self.width)
+            .map_err(|e| format!("Failed to convert HTML: {}", e))?;
+
+        let lines: Vec<String> = text
+            .lines()
+            .map(|line| {
+                let reference = line
+                    .strip_prefix('[')
+                    .and_then(|rest| rest.split_once("]: "))
+                    .filter(|(n, _)| n.chars().all(|c| c.is_ascii_digit()));
+                match reference.and_then(|(n, target)| Some((n, base.join(target).ok()?))) {
+                    Some((n, target)) => format!("[{}]: {}", n, target),
+                    None => line.to_string(),
+                }
+            })
+            .collect();
+        Ok(lines.join("\n"))
+    }
+}
+
+/// A cached response body with the validators used to revalidate it.
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    content_type: String,
+    body: String,
+}
+
+/// Body of a fetched page, before conversion.
+struct Fetched {
+    url: Url,
+    content_type: String,
+    body: String,
+    cached: bool,
+}
+
+/// Client-side `WebFetch` over plain HTTP, converting HTML to markdown.
+///
+/// It replaces the server-side `web_fetch` tool. Every request and redirect hop
+/// must pass the execution context's
+/// [`NetworkSandbox`](crate::security::NetworkSandbox) allowlist and, unless
+/// disabled, the site's robots.txt.
+///
+/// With a [`cache_dir`](Self::cache_dir), responses carrying an `ETag` or
+/// `Last-Modified` header are stored on disk and revalidated with a
+/// conditional request, so unchanged pages are not downloaded again.
+pub struct HttpFetchTool {
+    client: reqwest::Client,
+    user_agent: String,
+    cache_dir: Option<PathBuf>,
+    respect_robots: bool,
+    robots: Mutex<HashMap<String, Arc<RobotsTxt>>>,
+    max_bytes: usize,
+    mime_types: Vec<String>,
+    markdown: MarkdownOptions,
+    max_text_bytes: usize,
+    timeout: Duration,
+}
+
+impl HttpFetchTool {
+    pub fn new() -> Self {
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_default();
+        Self {
+            client,
+            user_agent: format!("claude-agent/{}", env!("CARGO_PKG_VERSION")),
+            cache_dir: None,
+            respect_robots: true,
+            robots: Mutex::new(HashMap::new()),
+            max_bytes: DEFAULT_MAX_BYTES,
+            mime_types: DEFAULT_MIME_TYPES.iter().map(|t| t.to_string()).collect(),
+            markdown: MarkdownOptions::default(),
+            max_text_bytes: DEFAULT_MAX_TEXT_BYTES,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Directory for cached responses; created on first write.
+    pub fn cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// `User-Agent` header; its product token selects the robots.txt group
+    /// (default `claude-agent/<version>`).
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Whether to honor robots.txt (default true).
+    pub fn respect_robots(mut self, enabled: bool) -> Self {
+        self.respect_robots = enabled;
+        self
+    }
+
+    /// Responses larger than this are rejected (default 5MB).
+    pub fn max_bytes(mut self, bytes: usize) -> Self {
+        self.max_bytes = bytes;
+        self
+    }
+
+    /// Accepted content types, replacing the defaults (`text/*`, XHTML, JSON
+    /// and XML). A `type/*` entry accepts every subtype.
+    pub fn mime_types<I, S>(mut self, types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.mime_types = types.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn markdown(mut self, options: MarkdownOptions) -> Self {
+        self.markdown = options;
+        self
+    }
+
+    /// Converted text beyond this is truncated (default 100KB).
+    pub fn max_text_bytes(mut self, bytes: usize) -> Self {
+        self.max_text_bytes = bytes;
+        self
+    }
+
+    /// Upper bound for a whole fetch, including robots.txt and redirects (default 30s).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn run(
+        &self,
+        input: WebFetchInput,
+        context: &ExecutionContext,
+    ) -> Result<String, String> {
+        let url = Url::parse(&input.url).map_err(|e| format!("Invalid URL: {}", e))?;
+        let timeout = input
+            .timeout
+            .map(Duration::from_millis)
+            .map_or(self.timeout, |t| t.min(self.timeout));
+
+        let fetched = tokio::time::timeout(timeout, self.fetch(url, context))
+            .await
+            .map_err(|_| format!("Request timed out after {}ms", timeout.as_millis()))??;
+        self.format(fetched)
+    }
+
+    async fn fetch(&self, mut url: Url, context: &ExecutionContext) -> Result<Fetched, String> {
+        let cache_path = self.cache_path(&url);
+        let cached = match &cache_path {
+            Some(path) => read_cache(path).await,
+            None => None,
+        };
+
+        let mut hops = 0;
+        let response = loop {
+            check_url(&url, context)?;
+            if self.respect_robots && !self.robots_allows(&url).await {
+                return Err(format!("{} is disallowed by the site's robots.txt", url));
+            }
+
+            let mut request = self
+                .client
+                .get(url.clone())
+                .header(USER_AGENT, &self.user_agent)
+                .header(
+                    ACCEPT,
+                    "text/html, application/xhtml+xml, text/markdown, text/plain;q=0.9, */*;q=0.8",
+                );
+            if let Some(entry) = &cached {
+                if let Some(etag) = &entry.etag {
+                    request = request.header(IF_NONE_MATCH, etag);
+                }
+                if let Some(modified) = &entry.last_modified {
+                    request = request.header(IF_MODIFIED_SINCE, modified);
+                }
+            }
+            let response = request.send().await.map_err(|e| e.to_string())?;
+
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| url.join(v).ok());
+            match location {
+                Some(next) if response.status().is_redirection() && hops < MAX_REDIRECTS => {
+                    hops += 1;
+                    url = next;
+                }
+                _ => break response,
+            }
+        };
+
+        if response.status() == StatusCode::NOT_MODIFIED
+            && let Some(entry) = cached
+        {
+            return Ok(Fetched {
+                url,
+                content_type: entry.content_type,
+                body: entry.body,
+                cached: true,
+            });
+        }
+        if !response.status().is_success() {
+            return Err(format!("HTTP {} fetching {}", response.status(), url));
+        }
+
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("application/octet-stream")
+            .to_string();
+        if !self.accepts_mime(&content_type) {
+            return Err(format!("Unsupported content type: {}", content_type));
+        }
+
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v: &HeaderValue| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED);
+        let body = self.read_body(response).await?;
+
+        if let Some(path) = cache_path
+            && (etag.is_some() || last_modified.is_some())
+        {
+            let entry = CacheEntry {
+                url: url.to_string(),
+                etag,
+                last_modified,
+                content_type: content_type.clone(),
+                body: body.clone(),
+            };
+            if let Err(e) = write_cache(&path, &entry).await {
+                tracing::debug!("Failed to cache {}: {}", url, e);
+            }
+        }
+
+        Ok(Fetched {
+            url,
+            content_type,
+            body,
+            cached: false,
+        })
+    }
+
+    async fn read_body(&self, mut response: reqwest::Response) -> Result<String, String> {
+        let too_large = || format!("Response exceeds the {} byte limit", self.max_bytes);
+        let declared = response
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<usize>().ok());
+        if declared.is_some_and(|len| len > self.max_bytes) {
+            return Err(too_large());
+        }
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+            if body.len() + chunk.len() > self.max_bytes {
+                return Err(too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
+    fn accepts_mime(&self, content_type: &str) -> bool {
+        let essence = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        self.mime_types.iter().any(|allowed| {
+            let allowed = allowed.to_ascii_lowercase();
+            match allowed.strip_suffix("/*") {
+                Some(main) => essence.split_once('/').is_some_and(|(ty, _)| ty == main),
+                None => essence == allowed,
+            }
+        })
+    }
+
+    fn cache_path(&self, url: &Url) -> Option<PathBuf> {
+        let dir = self.cache_dir.as_ref()?;
+        let digest = Sha256::digest(url.as_str().as_bytes());
+        let name: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+        Some(dir.join(format!("{}.json", name)))
+    }
+
+    async fn robots_allows(&self, url: &Url) -> bool {
+        let origin = url.origin().ascii_serialization();
+        let cached = self.robots.lock().await.get(&origin).cloned();
+        let robots = match cached {
+            Some(robots) => robots,
+            None => {
+                let robots = Arc::new(self.fetch_robots(&origin).await);
+                self.robots.lock().await.insert(origin, Arc::clone(&robots));
+                robots
+            }
+        };
+
+        let mut path = url.path().to_string();
+        if let Some(query) = url.query() {
+            path.push('?');
+            path.push_str(query);
+        }
+        robots.allows(&path)
+    }
+
+    /// Per RFC 9309: a missing robots.txt (4xx) allows everything, while an
+    /// unreachable one (5xx or network error) disallows everything.
+    async fn fetch_robots(&self, origin: &str) -> RobotsTxt {
+        let agent = self.user_agent.split('/').next().unwrap_or_default();
+        let response = self
+            .client
+            .get(format!("{}/robots.txt", origin))
+            .header(USER_AGENT, &self.user_agent)
+            .send()
+            .await;
+        match response {
+            Ok(response) if response.status().is_success() => match response.text().await {
+                Ok(body) => RobotsTxt::parse(&body, agent),
+                Err(_) => RobotsTxt::disallow_all(),
+            },
+            Ok(response) if response.status().is_client_error() => RobotsTxt::default(),
+            _ => RobotsTxt::disallow_all(),
+        }
+    }
+
+    fn format(&self, fetched: Fetched) -> Result<String, String> {
+        let is_html = fetched.content_type.contains("html");
+        let text = if is_html {
+            self.markdown
+                .convert(fetched.body.as_bytes(), &fetched.url)?
+        } else {
+            fetched.body
+        };
+
+        let mut text = text.trim();
+        let truncated = text.len() > self.max_text_bytes;
+        if truncated {
+            let mut end = self.max_text_bytes;
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            text = &text[..end];
+        }
+
+        let mut output = format!("URL: {}\n", fetched.url);
+        output.push_str(&format!("Content-Type: {}\n", fetched.content_type));
+        if fetched.cached {
+            output.push_str("(not modified since last fetch, served from cache)\n");
+        }
+        output.push('\n');
+        output.push_str(text);
+        if truncated {
+            output.push_str(&format!(
+                "\n... (content truncated at {} bytes)",
+                self.max_text_bytes
+            ));
+        }
+        Ok(output)
+    }
+}
+
+impl Default for HttpFetchTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn read_cache(path: &Path) -> Option<CacheEntry> {
+    let data = tokio::fs::read(path).await.ok()?;
+    serde_json::from_slice(&data).ok()
+}
+
+async fn write_cache(path: &Path, entry: &CacheEntry) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    tokio::fs::write(&tmp, serde_json::to_vec(entry)?).await?;
+    tokio::fs::rename(&tmp, path).await
+}
+
+/// The robots.txt rules that apply to one user agent.
+#[derive(Debug, Default)]
+struct RobotsTxt {
+    /// `(allow, pattern)` pairs; the longest matching pattern decides.
+    rules: Vec<(bool, String)>,
+}
+
+impl RobotsTxt {
+    fn disallow_all() -> Self {
+        Self {
+            rules: vec![(false, "/".to_string())],
+        }
+    }
+
+    /// Rules of the groups naming `agent`, or of the `*` groups if none do.
+    fn parse(body: &str, agent: &str) -> Self {
+        let agent = agent.to_ascii_lowercase();
+        let mut specific = Vec::new();
+        let mut wildcard = Vec::new();
+        let mut group_agents: Vec<String> = Vec::new();
+        let mut in_rules = false;
+        let mut named = false;
+
+        for line in body.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    if in_rules {
+                        group_agents.clear();
+                        in_rules = false;
+                    }
+                    let value = value.to_ascii_lowercase();
+                    named |= value == agent;
+                    group_agents.push(value);
+                }
+                key @ ("allow" | "disallow") => {
+                    in_rules = true;
+                    if value.is_empty() {
+                        continue;
+                    }
+                    let rule = (key == "allow", value.to_string());
+                    if group_agents.iter().any(|a| a == &agent) {
+                        specific.push(rule.clone());
+                    }
+                    if group_agents.iter().any(|a| a == "*") {
+                        wildcard.push(rule);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            rules: if named { specific } else { wildcard },
+        }
+    }
+
+    fn allows(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|(_, pattern)| robots_match(pattern, path))
+            // Longest pattern wins; on a tie, allow wins.
+            .max_by_key(|(allow, pattern)| (pattern.len(), *allow))
+            .is_none_or(|(allow, _)| *allow)
+    }
+}
+
+/// Match a robots.txt path pattern, where `*` matches any run of characters
+/// and a trailing `$` anchors the end.
+fn robots_match(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(p) => (p, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        let is_last = i + 1 == parts.len();
+        if is_last && anchored {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
+
+#[async_trait]
+impl SchemaTool for HttpFetchTool {
+    type Input = WebFetchInput;
+
+    const NAME: &'static str = "WebFetch";
+    const DESCRIPTION: &'static str = r#"Fetches a URL and returns its content, with HTML pages converted to markdown.
+
+- Use this tool to read documentation, articles and other web pages
+- Links are listed as numbered references after the text; navigation, headers and footers are dropped
+- Only domains allowed by the network sandbox can be fetched, and pages disallowed by the site's robots.txt are refused
+- Only text content (HTML, plain text, markdown, JSON, XML) is accepted, and large responses are rejected
+- Pages that render their content with JavaScript may come back nearly empty
+- For calling APIs, use HttpRequest instead"#;
+
+    async fn handle(&self, input: WebFetchInput, context: &ExecutionContext) -> ToolResult {
+        match self.run(input, context).await {
+            Ok(output) => ToolResult::success(output),
+            Err(e) => ToolResult::error(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::Tool;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn body(content_type: &str, body: &str) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_raw(body, content_type)
+    }
+
+    async fn serving(route: &str, response: ResponseTemplate) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(route))
+            .respond_with(response)
+            .mount(&server)
+            .await;
+        server
+    }
+
+    const PAGE: &str = "<html><body><nav><a href=\"/\">Home</a></nav>\
+        <h1>Guide</h1><p>Read <a href=\"/api\">the API</a>.</p>\
+        <footer>Copyright</footer></body></html>";
+
+    #[tokio::test]
+    async fn test_html_to_markdown() {
+        let server = serving("/guide", body("text/html; charset=utf-8", PAGE)).await;
+        let base = server.uri();
+        let url = serde_json::json!({"url": format!("{}/guide", base)});
+
+        let tool = HttpFetchTool::new().respect_robots(false);
+        let text = tool
+            .execute(url.clone(), &ExecutionContext::permissive())
+            .await
+            .text();
+        assert!(text.contains("# Guide"), "{}", text);
+        assert!(text.contains("[the API][1]"));
+        assert!(text.contains(&format!("[1]: {}/api", base)));
+        assert!(!text.contains("Home") && !text.contains("Copyright"));
+
+        let tool = HttpFetchTool::new()
+            .respect_robots(false)
+            .markdown(MarkdownOptions::default().links(false).main_content(false));
+        let text = tool
+            .execute(url, &ExecutionContext::permissive())
+            .await
+            .text();
+        assert!(text.contains("Home") && text.contains("Copyright"));
+        assert!(!text.contains("[1]:"));
+    }
+
+    #[tokio::test]
+    async fn test_etag_revalidation() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304).insert_header("etag", "\"v1\""))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(body("text/plain", "hello").insert_header("etag", "\"v1\""))
+            .mount(&server)
+            .await;
+        let tool = HttpFetchTool::new()
+            .respect_robots(false)
+            .cache_dir(dir.path());
+        let url = serde_json::json!({"url": format!("{}/notes.txt", server.uri())});
+
+        let first = tool
+            .execute(url.clone(), &ExecutionContext::permissive())
+            .await
+            .text();
+        assert!(first.ends_with("\n\nhello"), "{}", first);
+        assert!(!first.contains("from cache"));
+
+        let second = tool
+            .execute(url, &ExecutionContext::permissive())
+            .await
+            .text();
+        assert!(second.contains("served from cache"), "{}", second);
+        assert!(second.ends_with("\n\nhello"));
+
+        let requests = server.received_requests().await.unwrap();
+        assert!(!requests[0].headers.contains_key("if-none-match"));
+        assert_eq!(requests[1].headers["if-none-match"], "\"v1\"");
+    }
+
+    #[tokio::test]
+    async fn test_size_and_mime_policy() {
+        let server = MockServer::start().await;
+        for (route, content_type, content) in [
+            ("/image", "image/png", "png"),
+            ("/text", "text/plain", "0123456789"),
+            ("/pdf", "application/pdf", "%PDF"),
+        ] {
+            Mock::given(method("GET"))
+                .and(path(route))
+                .respond_with(body(content_type, content))
+                .mount(&server)
+                .await;
+        }
+        let url = |route: &str| serde_json::json!({"url": format!("{}{}", server.uri(), route)});
+
+        let tool = HttpFetchTool::new().respect_robots(false).max_bytes(4);
+        let result = tool
+            .execute(url("/image"), &ExecutionContext::permissive())
+            .await;
+        assert!(
+            result
+                .text()
+                .contains("Unsupported content type: image/png")
+        );
+        let result = tool
+            .execute(url("/text"), &ExecutionContext::permissive())
+            .await;
+        assert!(result.text().contains("exceeds the 4 byte limit"));
+
+        let tool = HttpFetchTool::new()
+            .respect_robots(false)
+            .mime_types(["application/pdf"]);
+        let result = tool
+            .execute(url("/pdf"), &ExecutionContext::permissive())
+            .await;
+        assert!(!result.is_error(), "{}", result.text());
+    }
+
+    #[tokio::test]
+    async fn test_robots_txt() {
+        let robots = "User-agent: *\nDisallow: /\n\nUser-agent: claude-agent\nDisallow: /private\n";
+        let server = MockServer::start().await;
+        // robots.txt is fetched once per origin.
+        Mock::given(method("GET"))
+            .and(path("/robots.txt"))
+            .respond_with(body("text/plain", robots))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/public"))
+            .respond_with(body("text/plain", "public"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let tool = HttpFetchTool::new();
+
+        let result = tool
+            .execute(
+                serde_json::json!({"url": format!("{}/private/page", server.uri())}),
+                &ExecutionContext::permissive(),
+            )
+            .await;
+        assert!(
+            result
+                .text()
+                .contains("disallowed by the site's robots.txt")
+        );
+
+        let result = tool
+            .execute(
+                serde_json::json!({"url": format!("{}/public", server.uri())}),
+                &ExecutionContext::permissive(),
+            )
+            .await;
+        assert!(result.text().ends_with("public"), "{}", result.text());
+    }
+
+    #[test]
+    fn test_robots_rules() {
+        let robots = RobotsTxt::parse(
+            "User-agent: other\nDisallow: /\n\n\
+             User-agent: *\nDisallow: /docs/\nAllow: /docs/public\nDisallow: /*.pdf$\n",
+            "claude-agent",
+        );
+        assert!(robots.allows("/"));
+        assert!(!robots.allows("/docs/internal"));
+        assert!(robots.allows("/docs/public/intro"));
+        assert!(!robots.allows("/files/report.pdf"));
+        assert!(robots.allows("/files/report.pdf?download=1"));
+
+        assert!(robots_match("/a*b*c", "/axxbyyc/d"));
+        assert!(!robots_match("/a*b$", "/ab/c"));
+        assert!(RobotsTxt::default().allows("/anything"));
+        assert!(!RobotsTxt::disallow_all().allows("/"));
+    }
+}
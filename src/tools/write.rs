@@ -44,9 +44,16 @@ Usage:
         let content = input.content;
         let content_len = content.len();
         let display_path = path.as_path().display().to_string();
+        let limits = context.file_limits().clone();
+        if content_len as u64 > limits.max_file_size {
+            return ToolResult::error(format!(
+                "Content is {} bytes, over the {} byte limit for written files",
+                content_len, limits.max_file_size
+            ));
+        }
 
         let result = tokio::task::spawn_blocking(move || {
-            let handle = SecureFileHandle::for_atomic_write(path)?;
+            let handle = SecureFileHandle::for_atomic_write(path)?.limits(limits);
             handle.atomic_write(content.as_bytes())?;
             Ok::<_, crate::security::SecurityError>(())
         })
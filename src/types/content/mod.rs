@@ -10,9 +10,12 @@ use serde::{Deserialize, Serialize};
 
 pub use image::ImageSource;
 pub use server_tools::{
-    ServerToolError, ServerToolUseBlock, WebFetchResultItem, WebFetchToolResultBlock,
-    WebFetchToolResultContent, WebSearchResultItem, WebSearchToolResultBlock,
-    WebSearchToolResultContent,
+    CodeExecutionOutput, CodeExecutionResult, CodeExecutionToolResultBlock,
+    CodeExecutionToolResultContent, ServerToolError, ServerToolUseBlock,
+    TextEditorCodeExecutionToolResultBlock, ToolReference, ToolSearchResult,
+    ToolSearchToolResultBlock, ToolSearchToolResultContent, WebFetchResultItem,
+    WebFetchToolResultBlock, WebFetchToolResultContent, WebSearchResultItem,
+    WebSearchToolResultBlock, WebSearchToolResultContent,
 };
 pub use tool_blocks::{ToolResultBlock, ToolResultContent, ToolResultContentBlock, ToolUseBlock};
 
@@ -45,6 +48,10 @@ pub enum ContentBlock {
     ServerToolUse(ServerToolUseBlock),
     WebSearchToolResult(WebSearchToolResultBlock),
     WebFetchToolResult(WebFetchToolResultBlock),
+    CodeExecutionToolResult(CodeExecutionToolResultBlock),
+    BashCodeExecutionToolResult(CodeExecutionToolResultBlock),
+    TextEditorCodeExecutionToolResult(TextEditorCodeExecutionToolResultBlock),
+    ToolSearchToolResult(ToolSearchToolResultBlock),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -301,6 +308,22 @@ impl ContentBlock {
             _ => None,
         }
     }
+
+    /// Result of a server-side code execution run (bash or legacy Python).
+    pub fn as_code_execution_result(&self) -> Option<&CodeExecutionToolResultBlock> {
+        match self {
+            ContentBlock::CodeExecutionToolResult(block)
+            | ContentBlock::BashCodeExecutionToolResult(block) => Some(block),
+            _ => None,
+        }
+    }
+
+    pub fn as_tool_search_result(&self) -> Option<&ToolSearchToolResultBlock> {
+        match self {
+            ContentBlock::ToolSearchToolResult(block) => Some(block),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
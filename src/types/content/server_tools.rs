@@ -1,4 +1,4 @@
-//! Server-side tool types (web_search, web_fetch).
+//! Server-side tool types (web_search, web_fetch, code_execution, tool_search).
 
 use serde::{Deserialize, Serialize};
 
@@ -64,6 +64,104 @@ pub struct WebFetchResultItem {
     pub retrieved_at: Option<String>,
 }
 
+/// Result of a `code_execution` bash command (or a legacy Python run).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeExecutionToolResultBlock {
+    pub tool_use_id: String,
+    pub content: CodeExecutionToolResultContent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CodeExecutionToolResultContent {
+    Result(CodeExecutionResult),
+    Error(ServerToolError),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeExecutionResult {
+    #[serde(rename = "type")]
+    pub result_type: String,
+    #[serde(default)]
+    pub stdout: String,
+    #[serde(default)]
+    pub stderr: String,
+    pub return_code: i32,
+    /// Files written by the run, downloadable through the Files API.
+    #[serde(default)]
+    pub content: Vec<CodeExecutionOutput>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeExecutionOutput {
+    #[serde(rename = "type")]
+    pub output_type: String,
+    pub file_id: String,
+}
+
+/// Result of a `code_execution` file view/create/edit inside the container.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextEditorCodeExecutionToolResultBlock {
+    pub tool_use_id: String,
+    pub content: serde_json::Value,
+}
+
+/// Result of a server-side tool search: references to the deferred tools found.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolSearchToolResultBlock {
+    pub tool_use_id: String,
+    pub content: ToolSearchToolResultContent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ToolSearchToolResultContent {
+    Result(ToolSearchResult),
+    Error(ServerToolError),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolSearchResult {
+    #[serde(rename = "type")]
+    pub result_type: String,
+    #[serde(default)]
+    pub tool_references: Vec<ToolReference>,
+}
+
+/// A deferred tool whose full definition the API loads on reference.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolReference {
+    #[serde(rename = "type")]
+    pub reference_type: String,
+    pub tool_name: String,
+}
+
+impl ToolSearchToolResultBlock {
+    /// Names of the tools the search found.
+    pub fn tool_names(&self) -> Vec<&str> {
+        match &self.content {
+            ToolSearchToolResultContent::Result(result) => result
+                .tool_references
+                .iter()
+                .map(|r| r.tool_name.as_str())
+                .collect(),
+            ToolSearchToolResultContent::Error(_) => Vec::new(),
+        }
+    }
+}
+
+impl CodeExecutionToolResultBlock {
+    /// IDs of the files the run produced.
+    pub fn file_ids(&self) -> Vec<&str> {
+        match &self.content {
+            CodeExecutionToolResultContent::Result(result) => {
+                result.content.iter().map(|o| o.file_id.as_str()).collect()
+            }
+            CodeExecutionToolResultContent::Error(_) => Vec::new(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::types::ContentBlock;
@@ -121,4 +219,76 @@ mod tests {
         let wfr = block.as_web_fetch_result().unwrap();
         assert_eq!(wfr.tool_use_id, "srvtoolu_01234567890abcdef");
     }
+
+    #[test]
+    fn test_code_execution_tool_result_parsing() {
+        let json = r#"{
+            "type": "bash_code_execution_tool_result",
+            "tool_use_id": "srvtoolu_01B3C4D5E6F7G8H9I0J1K2L3",
+            "content": {
+                "type": "bash_code_execution_result",
+                "stdout": "plot saved\n",
+                "stderr": "",
+                "return_code": 0,
+                "content": [{"type": "bash_code_execution_output", "file_id": "file_011CNha8iCJcU1wXNR6q4V8w"}]
+            }
+        }"#;
+        let block: ContentBlock = serde_json::from_str(json).unwrap();
+        let result = block.as_code_execution_result().unwrap();
+        assert_eq!(result.file_ids(), vec!["file_011CNha8iCJcU1wXNR6q4V8w"]);
+
+        let json = r#"{
+            "type": "code_execution_tool_result",
+            "tool_use_id": "srvtoolu_01",
+            "content": {"type": "code_execution_tool_result_error", "error_code": "unavailable"}
+        }"#;
+        let block: ContentBlock = serde_json::from_str(json).unwrap();
+        let result = block.as_code_execution_result().unwrap();
+        assert!(matches!(
+            &result.content,
+            crate::types::CodeExecutionToolResultContent::Error(e) if e.error_code == "unavailable"
+        ));
+
+        let json = r#"{
+            "type": "text_editor_code_execution_tool_result",
+            "tool_use_id": "srvtoolu_02",
+            "content": {"type": "text_editor_code_execution_create_result", "is_file_update": false}
+        }"#;
+        let block: ContentBlock = serde_json::from_str(json).unwrap();
+        assert!(matches!(
+            block,
+            ContentBlock::TextEditorCodeExecutionToolResult(_)
+        ));
+    }
+
+    #[test]
+    fn test_tool_search_tool_result_parsing() {
+        let json = r#"{
+            "type": "tool_search_tool_result",
+            "tool_use_id": "srvtoolu_01ABC",
+            "content": {
+                "type": "tool_search_tool_search_result",
+                "tool_references": [
+                    {"type": "tool_reference", "tool_name": "mcp__weather__get_forecast"}
+                ]
+            }
+        }"#;
+        let block: ContentBlock = serde_json::from_str(json).unwrap();
+        let result = block.as_tool_search_result().unwrap();
+        assert_eq!(result.tool_names(), vec!["mcp__weather__get_forecast"]);
+
+        let json = r#"{
+            "type": "tool_search_tool_result",
+            "tool_use_id": "srvtoolu_02",
+            "content": {"type": "tool_search_tool_result_error", "error_code": "invalid_pattern"}
+        }"#;
+        let block: ContentBlock = serde_json::from_str(json).unwrap();
+        assert!(
+            block
+                .as_tool_search_result()
+                .unwrap()
+                .tool_names()
+                .is_empty()
+        );
+    }
 }
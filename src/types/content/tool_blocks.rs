@@ -3,6 +3,7 @@
 use serde::{Deserialize, Serialize};
 
 use super::image::ImageSource;
+use crate::types::DocumentSource;
 use crate::types::search::SearchResultBlock;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +38,9 @@ pub enum ToolResultContentBlock {
     Image {
         source: ImageSource,
     },
+    Document {
+        source: DocumentSource,
+    },
     #[serde(rename = "search_result")]
     SearchResult(SearchResultBlock),
 }
@@ -79,6 +83,20 @@ impl ToolResultBlock {
                 ToolOutputBlock::Image { data, media_type } => ToolResultContentBlock::Image {
                     source: ImageSource::Base64 { media_type, data },
                 },
+                ToolOutputBlock::Document { data, media_type } => {
+                    ToolResultContentBlock::Document {
+                        source: DocumentSource::Base64 { media_type, data },
+                    }
+                }
+                ToolOutputBlock::File {
+                    file_id,
+                    media_type,
+                } if media_type.starts_with("image/") => ToolResultContentBlock::Image {
+                    source: ImageSource::File { file_id },
+                },
+                ToolOutputBlock::File { file_id, .. } => ToolResultContentBlock::Document {
+                    source: DocumentSource::File { file_id },
+                },
                 ToolOutputBlock::SearchResult(sr) => ToolResultContentBlock::SearchResult(sr),
             })
             .collect();
@@ -132,6 +150,37 @@ mod tests {
         assert_eq!(result.is_error, Some(true));
     }
 
+    #[test]
+    fn test_tool_result_attachment_blocks() {
+        use crate::types::ToolOutputBlock;
+
+        let result = ToolResultBlock::success_blocks(
+            "tool_789",
+            vec![
+                ToolOutputBlock::Document {
+                    data: "JVBERg==".into(),
+                    media_type: "application/pdf".into(),
+                },
+                ToolOutputBlock::File {
+                    file_id: "file_1".into(),
+                    media_type: "image/png".into(),
+                },
+                ToolOutputBlock::File {
+                    file_id: "file_2".into(),
+                    media_type: "application/pdf".into(),
+                },
+            ],
+        );
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["content"][0]["type"], "document");
+        assert_eq!(json["content"][0]["source"]["type"], "base64");
+        assert_eq!(json["content"][1]["type"], "image");
+        assert_eq!(json["content"][1]["source"]["file_id"], "file_1");
+        assert_eq!(json["content"][2]["type"], "document");
+        assert_eq!(json["content"][2]["source"]["file_id"], "file_2");
+    }
+
     #[test]
     fn test_tool_result_search_results() {
         let results = vec![SearchResultBlock::new(
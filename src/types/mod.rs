@@ -14,10 +14,13 @@ pub use citations::{
     PageLocationCitation, SearchResultLocationCitation,
 };
 pub use content::{
-    ContentBlock, ImageSource, ServerToolError, ServerToolUseBlock, ThinkingBlock, ToolResultBlock,
-    ToolResultContent, ToolResultContentBlock, ToolUseBlock, WebFetchResultItem,
-    WebFetchToolResultBlock, WebFetchToolResultContent, WebSearchResultItem,
-    WebSearchToolResultBlock, WebSearchToolResultContent,
+    CodeExecutionOutput, CodeExecutionResult, CodeExecutionToolResultBlock,
+    CodeExecutionToolResultContent, ContentBlock, ImageSource, ServerToolError, ServerToolUseBlock,
+    TextEditorCodeExecutionToolResultBlock, ThinkingBlock, ToolReference, ToolResultBlock,
+    ToolResultContent, ToolResultContentBlock, ToolSearchResult, ToolSearchToolResultBlock,
+    ToolSearchToolResultContent, ToolUseBlock, WebFetchResultItem, WebFetchToolResultBlock,
+    WebFetchToolResultContent, WebSearchResultItem, WebSearchToolResultBlock,
+    WebSearchToolResultContent,
 };
 pub use document::{DocumentBlock, DocumentContentBlock, DocumentSource};
 pub use message::{CacheControl, CacheTtl, CacheType, Message, Role, SystemBlock, SystemPrompt};
@@ -28,6 +31,8 @@ pub use response::{
 };
 pub use search::{SearchResultBlock, SearchResultContentBlock};
 pub use tool::{
-    ServerTool, ToolDefinition, ToolError, ToolInput, ToolOutput, ToolOutputBlock, ToolResult,
-    ToolSearchTool, UserLocation, WebFetchTool, WebSearchTool, estimate_tool_tokens,
+    CodeExecutionTool, ComputerUseTool, MemoryToolDefinition, SandboxViolation, ServerTool,
+    TextEditorDefinition, TextEditorVersion, ToolDefinition, ToolError, ToolInput, ToolMetadata,
+    ToolOutput, ToolOutputBlock, ToolResult, ToolSearchTool, UserLocation, ViolationKind,
+    WebFetchTool, WebSearchTool, estimate_tool_tokens,
 };
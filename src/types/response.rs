@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 
 use super::ContentBlock;
 use super::citations::{Citation, SearchResultLocationCitation};
+use crate::common::clock;
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct TokenUsage {
@@ -103,6 +104,8 @@ pub enum StopReason {
     /// Model refused to generate output due to safety reasons.
     /// When using structured outputs, the response may not match the schema.
     Refusal,
+    /// A long-running server tool turn was paused; resend to let it continue.
+    PauseTurn,
 }
 
 /// Server-side tool usage from API response.
@@ -536,7 +539,7 @@ impl PermissionDenial {
             tool_use_id: tool_use_id.into(),
             tool_input,
             reason: None,
-            timestamp: Some(chrono::Utc::now()),
+            timestamp: Some(clock::now_utc()),
         }
     }
 
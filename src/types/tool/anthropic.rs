@@ -0,0 +1,185 @@
+//! Anthropic-defined tools with a fixed schema that are executed by the client.
+
+use serde::{Deserialize, Serialize};
+
+/// API definition of the computer-use tool (`computer_20250124`).
+///
+/// The model sees the display size and issues screenshot, mouse and keyboard
+/// actions against it; the actions themselves run locally through
+/// [`ComputerTool`](crate::tools::ComputerTool).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComputerUseTool {
+    #[serde(rename = "type")]
+    pub tool_type: String,
+    pub name: String,
+    pub display_width_px: u32,
+    pub display_height_px: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_number: Option<u32>,
+}
+
+impl ComputerUseTool {
+    pub const TYPE: &'static str = "computer_20250124";
+    pub const NAME: &'static str = "computer";
+
+    pub fn new(display_width_px: u32, display_height_px: u32) -> Self {
+        Self {
+            tool_type: Self::TYPE.to_string(),
+            name: Self::NAME.to_string(),
+            display_width_px,
+            display_height_px,
+            display_number: None,
+        }
+    }
+
+    /// X11 display number, for environments with several displays.
+    pub fn display_number(mut self, number: u32) -> Self {
+        self.display_number = Some(number);
+        self
+    }
+}
+
+/// Revisions of the Anthropic text editor tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TextEditorVersion {
+    /// Claude 3.x; named `str_replace_editor`, supports `undo_edit`.
+    #[serde(rename = "text_editor_20250124")]
+    V20250124,
+    /// Claude 4; named `str_replace_based_edit_tool`, no `undo_edit`.
+    #[serde(rename = "text_editor_20250429")]
+    V20250429,
+    /// Claude 4 with `max_characters` for `view`.
+    #[default]
+    #[serde(rename = "text_editor_20250728")]
+    V20250728,
+}
+
+impl TextEditorVersion {
+    /// The version a model expects: Claude 3.x models use the January 2025
+    /// revision, everything newer the latest one.
+    pub fn for_model(model: &str) -> Self {
+        if model.contains("claude-3") {
+            Self::V20250124
+        } else {
+            Self::V20250728
+        }
+    }
+
+    pub fn tool_type(&self) -> &'static str {
+        match self {
+            Self::V20250124 => "text_editor_20250124",
+            Self::V20250429 => "text_editor_20250429",
+            Self::V20250728 => "text_editor_20250728",
+        }
+    }
+
+    /// Tool name the model calls for this version.
+    pub fn tool_name(&self) -> &'static str {
+        match self {
+            Self::V20250124 => "str_replace_editor",
+            Self::V20250429 | Self::V20250728 => "str_replace_based_edit_tool",
+        }
+    }
+
+    pub fn supports_undo(&self) -> bool {
+        matches!(self, Self::V20250124)
+    }
+}
+
+/// API definition of the text editor tool (`text_editor_*`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextEditorDefinition {
+    #[serde(rename = "type")]
+    pub version: TextEditorVersion,
+    pub name: String,
+    /// Truncation limit for `view` output (`text_editor_20250728` only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_characters: Option<usize>,
+}
+
+impl TextEditorDefinition {
+    pub fn new(version: TextEditorVersion) -> Self {
+        Self {
+            version,
+            name: version.tool_name().to_string(),
+            max_characters: None,
+        }
+    }
+
+    /// Ignored by versions before `text_editor_20250728`.
+    pub fn max_characters(mut self, max: usize) -> Self {
+        if self.version == TextEditorVersion::V20250728 {
+            self.max_characters = Some(max);
+        }
+        self
+    }
+}
+
+/// API definition of the memory tool (`memory_20250818`).
+///
+/// The model keeps files under `/memories` that persist across conversations;
+/// the commands run locally through [`MemoryTool`](crate::tools::MemoryTool).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryToolDefinition {
+    #[serde(rename = "type")]
+    pub tool_type: String,
+    pub name: String,
+}
+
+impl MemoryToolDefinition {
+    pub const TYPE: &'static str = "memory_20250818";
+    pub const NAME: &'static str = "memory";
+}
+
+impl Default for MemoryToolDefinition {
+    fn default() -> Self {
+        Self {
+            tool_type: Self::TYPE.to_string(),
+            name: Self::NAME.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_computer_use_tool_serialization() {
+        let tool = ComputerUseTool::new(1280, 800).display_number(1);
+        assert_eq!(
+            serde_json::to_value(&tool).unwrap(),
+            serde_json::json!({
+                "type": "computer_20250124",
+                "name": "computer",
+                "display_width_px": 1280,
+                "display_height_px": 800,
+                "display_number": 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_text_editor_version_for_model() {
+        let legacy = TextEditorVersion::for_model("claude-3-7-sonnet-20250219");
+        assert_eq!(legacy, TextEditorVersion::V20250124);
+        assert_eq!(legacy.tool_name(), "str_replace_editor");
+
+        let latest = TextEditorVersion::for_model("claude-sonnet-4-5-20250929");
+        assert_eq!(latest, TextEditorVersion::V20250728);
+        assert_eq!(
+            serde_json::to_value(TextEditorDefinition::new(latest).max_characters(10_000)).unwrap(),
+            serde_json::json!({
+                "type": "text_editor_20250728",
+                "name": "str_replace_based_edit_tool",
+                "max_characters": 10000
+            })
+        );
+        assert_eq!(
+            TextEditorDefinition::new(legacy)
+                .max_characters(10_000)
+                .max_characters,
+            None
+        );
+    }
+}
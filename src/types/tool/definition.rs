@@ -11,6 +11,12 @@ pub struct ToolDefinition {
     pub strict: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub defer_loading: Option<bool>,
+    /// Version of the tool's input schema. Not sent to the API.
+    #[serde(skip)]
+    pub version: Option<String>,
+    /// Why the tool is deprecated and what to use instead. Not sent to the API.
+    #[serde(skip)]
+    pub deprecated: Option<String>,
 }
 
 impl ToolDefinition {
@@ -25,6 +31,8 @@ impl ToolDefinition {
             input_schema,
             strict: None,
             defer_loading: None,
+            version: None,
+            deprecated: None,
         }
     }
 
@@ -43,6 +51,20 @@ impl ToolDefinition {
         self
     }
 
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    pub fn deprecated(mut self, message: impl Into<String>) -> Self {
+        self.deprecated = Some(message.into());
+        self
+    }
+
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecated.is_some()
+    }
+
     pub fn is_deferred(&self) -> bool {
         self.defer_loading.unwrap_or(false)
     }
@@ -30,6 +30,9 @@ pub enum ToolError {
 
     #[error("unknown tool: {name}")]
     UnknownTool { name: String },
+
+    #[error("quota exceeded: {message}")]
+    QuotaExceeded { message: String },
 }
 
 impl ToolError {
@@ -82,6 +85,12 @@ impl ToolError {
         Self::UnknownTool { name: name.into() }
     }
 
+    pub fn quota_exceeded(message: impl Into<String>) -> Self {
+        Self::QuotaExceeded {
+            message: message.into(),
+        }
+    }
+
     pub fn contains(&self, pattern: &str) -> bool {
         self.to_string().contains(pattern)
     }
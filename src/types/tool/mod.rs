@@ -1,11 +1,19 @@
 //! Tool-related types.
 
+mod anthropic;
 mod definition;
 mod error;
 mod output;
 mod server;
+mod violation;
 
+pub use anthropic::{
+    ComputerUseTool, MemoryToolDefinition, TextEditorDefinition, TextEditorVersion,
+};
 pub use definition::{ToolDefinition, estimate_tool_tokens};
 pub use error::ToolError;
-pub use output::{ToolInput, ToolOutput, ToolOutputBlock, ToolResult};
-pub use server::{ServerTool, ToolSearchTool, UserLocation, WebFetchTool, WebSearchTool};
+pub use output::{ToolInput, ToolMetadata, ToolOutput, ToolOutputBlock, ToolResult};
+pub use server::{
+    CodeExecutionTool, ServerTool, ToolSearchTool, UserLocation, WebFetchTool, WebSearchTool,
+};
+pub use violation::{SandboxViolation, ViolationKind};
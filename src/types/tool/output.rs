@@ -3,6 +3,7 @@
 use serde::{Deserialize, Serialize};
 
 use super::error::ToolError;
+use super::violation::SandboxViolation;
 use crate::types::response::Usage;
 
 #[derive(Debug, Clone)]
@@ -30,6 +31,17 @@ pub enum ToolOutputBlock {
         data: String,
         media_type: String,
     },
+    /// Base64-encoded document, such as a PDF.
+    Document {
+        data: String,
+        media_type: String,
+    },
+    /// File uploaded through the Files API; sent as an image or document
+    /// block depending on `media_type`.
+    File {
+        file_id: String,
+        media_type: String,
+    },
     #[serde(rename = "search_result")]
     SearchResult(crate::types::search::SearchResultBlock),
 }
@@ -151,6 +163,16 @@ pub struct ToolResult {
     pub output: ToolOutput,
     pub inner_usage: Option<Usage>,
     pub inner_model: Option<String>,
+    /// Details of the run that are not sent to the model; boxed since most
+    /// results have none.
+    pub metadata: Option<Box<ToolMetadata>>,
+}
+
+/// What a tool reports about its run besides its output.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ToolMetadata {
+    /// Operations the sandbox denied while the tool ran.
+    pub violations: Vec<SandboxViolation>,
 }
 
 impl ToolResult {
@@ -159,6 +181,7 @@ impl ToolResult {
             output: ToolOutput::success(content),
             inner_usage: None,
             inner_model: None,
+            metadata: None,
         }
     }
 
@@ -167,6 +190,7 @@ impl ToolResult {
             output: ToolOutput::error(message),
             inner_usage: None,
             inner_model: None,
+            metadata: None,
         }
     }
 
@@ -175,6 +199,7 @@ impl ToolResult {
             output: ToolOutput::Empty,
             inner_usage: None,
             inner_model: None,
+            metadata: None,
         }
     }
 
@@ -188,6 +213,18 @@ impl ToolResult {
         self
     }
 
+    pub fn violations(mut self, violations: Vec<SandboxViolation>) -> Self {
+        if !violations.is_empty() {
+            self.metadata.get_or_insert_default().violations = violations;
+        }
+        self
+    }
+
+    /// Operations the sandbox denied while the tool ran.
+    pub fn sandbox_violations(&self) -> &[SandboxViolation] {
+        self.metadata.as_ref().map_or(&[], |m| &m.violations)
+    }
+
     pub fn inner_call(mut self, usage: Usage, model: impl Into<String>) -> Self {
         self.inner_usage = Some(usage);
         self.inner_model = Some(model.into());
@@ -205,6 +242,7 @@ impl ToolResult {
                 ToolError::PermissionDenied { .. }
                     | ToolError::SecurityViolation { .. }
                     | ToolError::UnknownTool { .. }
+                    | ToolError::QuotaExceeded { .. }
             )
         )
     }
@@ -226,6 +264,7 @@ impl ToolResult {
             output: ToolOutput::permission_denied(tool, reason),
             inner_usage: None,
             inner_model: None,
+            metadata: None,
         }
     }
 
@@ -234,6 +273,7 @@ impl ToolResult {
             output: ToolOutput::tool_error(ToolError::unknown_tool(name)),
             inner_usage: None,
             inner_model: None,
+            metadata: None,
         }
     }
 
@@ -242,6 +282,7 @@ impl ToolResult {
             output: ToolOutput::timeout(timeout_ms),
             inner_usage: None,
             inner_model: None,
+            metadata: None,
         }
     }
 
@@ -250,6 +291,16 @@ impl ToolResult {
             output: ToolOutput::security_error(message),
             inner_usage: None,
             inner_model: None,
+            metadata: None,
+        }
+    }
+
+    pub fn quota_exceeded(message: impl Into<String>) -> Self {
+        Self {
+            output: ToolOutput::tool_error(ToolError::quota_exceeded(message)),
+            inner_usage: None,
+            inner_model: None,
+            metadata: None,
         }
     }
 }
@@ -260,6 +311,7 @@ impl From<ToolOutput> for ToolResult {
             output,
             inner_usage: None,
             inner_model: None,
+            metadata: None,
         }
     }
 }
@@ -208,10 +208,34 @@ impl Default for ToolSearchTool {
     }
 }
 
+/// Sandboxed code execution on Anthropic's servers (bash and file editing).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeExecutionTool {
+    #[serde(rename = "type")]
+    pub tool_type: String,
+    pub name: String,
+}
+
+impl Default for CodeExecutionTool {
+    fn default() -> Self {
+        Self {
+            tool_type: "code_execution_20250825".to_string(),
+            name: "code_execution".to_string(),
+        }
+    }
+}
+
+impl CodeExecutionTool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(untagged)]
 pub enum ServerTool {
     WebSearch(WebSearchTool),
     WebFetch(WebFetchTool),
     ToolSearch(ToolSearchTool),
+    CodeExecution(CodeExecutionTool),
 }
@@ -0,0 +1,62 @@
+//! Sandbox denials reported with a tool result.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::common::clock;
+
+const MAX_DETAIL_CHARS: usize = 200;
+
+/// What a sandboxed command was denied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViolationKind {
+    /// File access outside the allowed paths.
+    Filesystem,
+    /// Connection to a host that is not allowed.
+    Network,
+    /// System call rejected by the seccomp filter.
+    Syscall,
+    /// Denied operation from a command that tries to leave the sandbox, such
+    /// as `nsenter`, `chroot` or the container runtime socket.
+    Escape,
+}
+
+impl ViolationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Filesystem => "filesystem",
+            Self::Network => "network",
+            Self::Syscall => "syscall",
+            Self::Escape => "escape",
+        }
+    }
+}
+
+impl std::fmt::Display for ViolationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One operation the sandbox denied a command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SandboxViolation {
+    pub timestamp: DateTime<Utc>,
+    pub kind: ViolationKind,
+    pub command: String,
+    /// The error line or blocked host that shows the denial.
+    pub detail: String,
+}
+
+impl SandboxViolation {
+    pub fn new(kind: ViolationKind, command: impl Into<String>, detail: impl Into<String>) -> Self {
+        let detail: String = detail.into();
+        Self {
+            timestamp: clock::now_utc(),
+            kind,
+            command: command.into(),
+            detail: detail.chars().take(MAX_DETAIL_CHARS).collect(),
+        }
+    }
+}
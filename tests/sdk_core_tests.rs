@@ -581,9 +581,12 @@ mod tool_registry_tests {
             "Read",
             "Write",
             "Edit",
+            "MultiEdit",
             "Glob",
             "Grep",
+            "LS",
             "Bash",
+            "BashOutput",
             "KillShell",
             "Task",
             "TaskOutput",
@@ -600,7 +603,7 @@ mod tool_registry_tests {
     fn test_registry_tool_definitions_count() {
         let registry = ToolRegistry::default_tools(ToolAccess::All, None, None);
         let definitions = registry.definitions();
-        assert_eq!(definitions.len(), 12);
+        assert_eq!(definitions.len(), 15);
         for def in &definitions {
             assert!(!def.name.is_empty());
             assert!(!def.description.is_empty());
@@ -809,6 +812,88 @@ mod tool_registry_tests {
     }
 }
 
+// =============================================================================
+// #[tool] Macro
+// =============================================================================
+
+mod tool_macro_tests {
+    use super::*;
+    use claude_agent::{SchemaTool, ToolResult};
+    use schemars::JsonSchema;
+    use serde::Deserialize;
+
+    #[derive(Deserialize, JsonSchema)]
+    struct GreetInput {
+        /// Who to greet
+        name: String,
+        #[serde(default)]
+        shout: bool,
+    }
+
+    /// Greet someone by name.
+    ///
+    /// Set `shout` for emphasis.
+    #[claude_agent::tool]
+    async fn greet(input: GreetInput) -> ToolResult {
+        let greeting = format!("Hello, {}!", input.name);
+        if input.shout {
+            ToolResult::success(greeting.to_uppercase())
+        } else {
+            ToolResult::success(greeting)
+        }
+    }
+
+    #[derive(Deserialize, JsonSchema)]
+    struct WhereInput {}
+
+    #[claude_agent::tool(
+        name = "WorkingDir",
+        description = "Show the working directory",
+        strict
+    )]
+    async fn working_dir(_input: WhereInput, context: &ExecutionContext) -> String {
+        context.root().display().to_string()
+    }
+
+    #[test]
+    fn test_generated_definition() {
+        assert_eq!(GreetTool::NAME, "Greet");
+        assert_eq!(
+            GreetTool::DESCRIPTION,
+            "Greet someone by name.\n\nSet `shout` for emphasis."
+        );
+
+        let definition = GreetTool.definition();
+        let properties = &definition.input_schema["properties"];
+        assert_eq!(properties["name"]["type"], "string");
+        assert_eq!(properties["name"]["description"], "Who to greet");
+        assert_eq!(definition.input_schema["required"], json!(["name"]));
+
+        let definition = WorkingDirTool.definition();
+        assert_eq!(definition.name, "WorkingDir");
+        assert_eq!(definition.description, "Show the working directory");
+        assert_eq!(definition.strict, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_generated_execution() {
+        let temp_dir = TempDir::new().unwrap();
+        let context = create_test_context(&temp_dir);
+
+        let result = GreetTool
+            .execute(json!({"name": "Ada", "shout": true}), &context)
+            .await;
+        assert_eq!(result.text(), "HELLO, ADA!");
+
+        let result = GreetTool.execute(json!({"shout": true}), &context).await;
+        assert!(result.is_error());
+        assert!(result.text().contains("Invalid input"));
+
+        let result = WorkingDirTool.execute(json!({}), &context).await;
+        assert_eq!(result.text(), context.root().display().to_string());
+    }
+}
+
 // =============================================================================
 // Individual Tool Execution
 // =============================================================================